@@ -16,9 +16,10 @@
 
 use async_trait::async_trait;
 use better_auth_core::error::{AuthError, AuthResult};
-use better_auth_core::schema::ModelDefinition;
-use better_auth_core::traits::StorageAdapter;
-use better_auth_core::types::{Account, Session, User};
+use better_auth_core::schema::{MigrationOp, ModelDefinition, SchemaDefinition};
+use better_auth_core::traits::{next_updated_at, StorageAdapter};
+use better_auth_core::types::{Account, Session, User, UserFilter};
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -26,22 +27,40 @@ use tokio::sync::RwLock;
 /// In-memory storage for a single entity type.
 type Store<T> = Arc<RwLock<HashMap<String, T>>>;
 
+/// In-memory storage for a relationship between two entity IDs.
+type Relation<V> = Arc<RwLock<HashMap<(String, String), V>>>;
+
 /// In-memory storage adapter for Better Auth.
 ///
 /// This adapter stores all data in memory and is suitable for
 /// testing and development. Data is lost when the process exits.
+///
+/// It has no transactions: [`run_in_transaction`] runs its closure
+/// directly, so writes made before a failure are kept.
+///
+/// [`run_in_transaction`]: better_auth_core::run_in_transaction
 #[derive(Debug, Clone)]
 pub struct MemoryAdapter {
     users: Store<User>,
     sessions: Store<Session>,
     accounts: Store<Account>,
-    tables: Arc<RwLock<Vec<String>>>,
+    /// Models passed to `migrate`, reported by `introspect_schema`.
+    schema: Arc<RwLock<SchemaDefinition>>,
     // Access control stores
     roles: Store<better_auth_plugin_access::DbRole>,
     permissions: Store<better_auth_plugin_access::DbPermission>,
-    role_permissions: Arc<RwLock<HashMap<(String, String), ()>>>,
-    user_permissions: Arc<RwLock<HashMap<(String, String), chrono::DateTime<chrono::Utc>>>>,
-    role_hierarchy: Arc<RwLock<HashMap<(String, String), ()>>>,
+    role_permissions: Relation<()>,
+    /// (user_id, permission_id) -> optional expiry.
+    user_permissions: Relation<Option<DateTime<Utc>>>,
+    role_hierarchy: Relation<()>,
+    email_otps: Store<better_auth_plugin_email_otp::EmailOtp>,
+    magic_link_tokens: Store<better_auth_plugin_magic_link::MagicLinkToken>,
+    password_history: Store<better_auth_plugin_password::PasswordHistoryEntry>,
+    /// Two-factor data keyed by user ID.
+    two_factor: Store<better_auth_plugin_two_factor::TwoFactorData>,
+    trusted_devices: Store<better_auth_plugin_two_factor::TrustedDevice>,
+    passkeys: Store<better_auth_plugin_passkey::Passkey>,
+    api_keys: Store<better_auth_plugin_api_key::ApiKey>,
 }
 
 impl MemoryAdapter {
@@ -51,12 +70,19 @@ impl MemoryAdapter {
             users: Arc::new(RwLock::new(HashMap::new())),
             sessions: Arc::new(RwLock::new(HashMap::new())),
             accounts: Arc::new(RwLock::new(HashMap::new())),
-            tables: Arc::new(RwLock::new(Vec::new())),
+            schema: Arc::new(RwLock::new(SchemaDefinition::new())),
             roles: Arc::new(RwLock::new(HashMap::new())),
             permissions: Arc::new(RwLock::new(HashMap::new())),
             role_permissions: Arc::new(RwLock::new(HashMap::new())),
             user_permissions: Arc::new(RwLock::new(HashMap::new())),
             role_hierarchy: Arc::new(RwLock::new(HashMap::new())),
+            email_otps: Arc::new(RwLock::new(HashMap::new())),
+            magic_link_tokens: Arc::new(RwLock::new(HashMap::new())),
+            password_history: Arc::new(RwLock::new(HashMap::new())),
+            two_factor: Arc::new(RwLock::new(HashMap::new())),
+            trusted_devices: Arc::new(RwLock::new(HashMap::new())),
+            passkeys: Arc::new(RwLock::new(HashMap::new())),
+            api_keys: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -70,6 +96,13 @@ impl MemoryAdapter {
         self.role_permissions.write().await.clear();
         self.user_permissions.write().await.clear();
         self.role_hierarchy.write().await.clear();
+        self.email_otps.write().await.clear();
+        self.magic_link_tokens.write().await.clear();
+        self.password_history.write().await.clear();
+        self.two_factor.write().await.clear();
+        self.trusted_devices.write().await.clear();
+        self.passkeys.write().await.clear();
+        self.api_keys.write().await.clear();
     }
 
     /// Returns the number of users stored.
@@ -81,6 +114,53 @@ impl MemoryAdapter {
     pub async fn session_count(&self) -> usize {
         self.sessions.read().await.len()
     }
+
+    /// User extension fields that must be unique: the username plugin's
+    /// `username`, and any field a migrated `user` model declares unique.
+    async fn unique_user_extensions(&self) -> Vec<String> {
+        let mut keys = vec!["username".to_string()];
+        if let Some(model) = self.schema.read().await.get_model("user") {
+            let fields = model
+                .fields
+                .iter()
+                .filter(|f| f.unique)
+                .map(|f| f.name.clone());
+            let indexes = model
+                .indexes
+                .iter()
+                .filter(|i| i.unique && i.columns.len() == 1)
+                .map(|i| i.columns[0].clone());
+            keys.extend(fields.chain(indexes));
+        }
+        keys
+    }
+}
+
+/// Fails if another user already has the value `user` holds for one of
+/// the unique extension `keys`.
+fn check_unique_extensions(
+    users: &HashMap<String, User>,
+    user: &User,
+    keys: &[String],
+) -> AuthResult<()> {
+    for key in keys {
+        let Some(value) = user.extensions.get(key) else {
+            continue;
+        };
+        if users
+            .values()
+            .any(|u| u.id != user.id && u.extensions.get(key) == Some(value))
+        {
+            return Err(AuthError::duplicate(
+                "user",
+                key,
+                value
+                    .as_str()
+                    .map_or_else(|| value.to_string(), String::from),
+            ));
+        }
+    }
+    Ok(())
 }
 
 impl Default for MemoryAdapter {
@@ -94,6 +174,7 @@ impl StorageAdapter for MemoryAdapter {
     // ==================== User Operations ====================
 
     async fn create_user(&self, user: &User) -> AuthResult<User> {
+        let unique = self.unique_user_extensions().await;
         let mut users = self.users.write().await;
 
         // Check for duplicate email
@@ -101,51 +182,160 @@ impl StorageAdapter for MemoryAdapter {
             return Err(AuthError::duplicate("user", "email", &user.email));
         }
 
+        let mut user = user.clone();
+        let now = Utc::now();
+        user.created_at = now;
+        user.updated_at = now;
+        user.deleted_at = None;
+        user.extensions.retain(|_, value| !value.is_null());
+        check_unique_extensions(&users, &user, &unique)?;
+
         users.insert(user.id.clone(), user.clone());
-        Ok(user.clone())
+        Ok(user)
     }
 
     async fn get_user_by_id(&self, id: &str) -> AuthResult<Option<User>> {
         let users = self.users.read().await;
-        Ok(users.get(id).cloned())
+        Ok(users.get(id).filter(|u| !u.is_deleted()).cloned())
     }
 
     async fn get_user_by_email(&self, email: &str) -> AuthResult<Option<User>> {
         let users = self.users.read().await;
-        Ok(users.values().find(|u| u.email == email).cloned())
+        Ok(users
+            .values()
+            .find(|u| u.email == email && !u.is_deleted())
+            .cloned())
+    }
+
+    async fn get_user_by_extension(
+        &self,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> AuthResult<Option<User>> {
+        let users = self.users.read().await;
+        Ok(users
+            .values()
+            .find(|u| u.extensions.get(key) == Some(value) && !u.is_deleted())
+            .cloned())
+    }
+
+    async fn list_users(
+        &self,
+        offset: usize,
+        limit: usize,
+        filter: Option<UserFilter>,
+    ) -> AuthResult<(Vec<User>, usize)> {
+        let users = self.users.read().await;
+        let filter = filter.unwrap_or_default();
+        let mut matching: Vec<&User> = users.values().filter(|u| filter.matches(u)).collect();
+        matching.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+        let total = matching.len();
+        let page = matching.into_iter().skip(offset).take(limit).cloned().collect();
+        Ok((page, total))
+    }
+
+    async fn count_users(&self) -> AuthResult<usize> {
+        let users = self.users.read().await;
+        Ok(users.values().filter(|u| !u.is_deleted()).count())
     }
 
     async fn update_user(&self, user: &User) -> AuthResult<User> {
+        let unique = self.unique_user_extensions().await;
         let mut users = self.users.write().await;
 
-        if !users.contains_key(&user.id) {
-            return Err(AuthError::not_found("user", "id", &user.id));
-        }
+        let existing = users
+            .get(&user.id)
+            .ok_or_else(|| AuthError::not_found("user", "id", &user.id))?;
+
+        // Extensions the caller didn't load are kept; nulls clear a key.
+        let mut extensions = existing.extensions.clone();
+        extensions.extend(user.extensions.clone());
+        extensions.retain(|_, value| !value.is_null());
+
+        let mut user = user.clone();
+        user.created_at = existing.created_at;
+        user.updated_at = next_updated_at(existing.updated_at);
+        user.deleted_at = existing.deleted_at;
+        user.extensions = extensions;
+        check_unique_extensions(&users, &user, &unique)?;
 
         users.insert(user.id.clone(), user.clone());
-        Ok(user.clone())
+        Ok(user)
     }
 
     async fn delete_user(&self, id: &str) -> AuthResult<()> {
         let mut users = self.users.write().await;
         users.remove(id);
 
-        // Also delete associated sessions and accounts
+        // Also delete associated sessions, accounts and plugin data
         let mut sessions = self.sessions.write().await;
         sessions.retain(|_, s| s.user_id != id);
 
         let mut accounts = self.accounts.write().await;
         accounts.retain(|_, a| a.user_id != id);
 
+        let mut history = self.password_history.write().await;
+        history.retain(|_, h| h.user_id != id);
+
+        self.two_factor.write().await.remove(id);
+        self.trusted_devices.write().await.retain(|_, d| d.user_id != id);
+        self.passkeys.write().await.retain(|_, p| p.user_id != id);
+        self.api_keys.write().await.retain(|_, k| k.user_id != id);
+
         Ok(())
     }
 
+    async fn soft_delete_user(&self, id: &str) -> AuthResult<()> {
+        let mut users = self.users.write().await;
+        if let Some(user) = users.get_mut(id).filter(|u| !u.is_deleted()) {
+            user.deleted_at = Some(Utc::now());
+            user.updated_at = next_updated_at(user.updated_at);
+        }
+
+        let mut sessions = self.sessions.write().await;
+        sessions.retain(|_, s| s.user_id != id);
+
+        Ok(())
+    }
+
+    async fn restore_user(&self, id: &str) -> AuthResult<Option<User>> {
+        let mut users = self.users.write().await;
+        let Some(user) = users.get_mut(id).filter(|u| u.is_deleted()) else {
+            return Ok(None);
+        };
+        user.deleted_at = None;
+        user.updated_at = next_updated_at(user.updated_at);
+        Ok(Some(user.clone()))
+    }
+
+    async fn purge_deleted_users(&self, deleted_before: DateTime<Utc>) -> AuthResult<usize> {
+        let purged: Vec<String> = self
+            .users
+            .read()
+            .await
+            .values()
+            .filter(|u| u.deleted_at.is_some_and(|at| at < deleted_before))
+            .map(|u| u.id.clone())
+            .collect();
+        for id in &purged {
+            self.delete_user(id).await?;
+        }
+        Ok(purged.len())
+    }
+
     // ==================== Session Operations ====================
 
     async fn create_session(&self, session: &Session) -> AuthResult<Session> {
         let mut sessions = self.sessions.write().await;
+
+        let mut session = session.clone();
+        let now = Utc::now();
+        session.created_at = now;
+        session.updated_at = now;
+
         sessions.insert(session.id.clone(), session.clone());
-        Ok(session.clone())
+        Ok(session)
     }
 
     async fn get_session_by_id(&self, id: &str) -> AuthResult<Option<Session>> {
@@ -170,12 +360,16 @@ impl StorageAdapter for MemoryAdapter {
     async fn update_session(&self, session: &Session) -> AuthResult<Session> {
         let mut sessions = self.sessions.write().await;
 
-        if !sessions.contains_key(&session.id) {
-            return Err(AuthError::not_found("session", "id", &session.id));
-        }
+        let existing = sessions
+            .get(&session.id)
+            .ok_or_else(|| AuthError::not_found("session", "id", &session.id))?;
+
+        let mut session = session.clone();
+        session.created_at = existing.created_at;
+        session.updated_at = next_updated_at(existing.updated_at);
 
         sessions.insert(session.id.clone(), session.clone());
-        Ok(session.clone())
+        Ok(session)
     }
 
     async fn delete_session(&self, id: &str) -> AuthResult<()> {
@@ -206,8 +400,13 @@ impl StorageAdapter for MemoryAdapter {
             ));
         }
 
+        let mut account = account.clone();
+        let now = Utc::now();
+        account.created_at = now;
+        account.updated_at = now;
+
         accounts.insert(account.id.clone(), account.clone());
-        Ok(account.clone())
+        Ok(account)
     }
 
     async fn get_account(
@@ -222,6 +421,11 @@ impl StorageAdapter for MemoryAdapter {
             .cloned())
     }
 
+    async fn get_account_by_id(&self, id: &str) -> AuthResult<Option<Account>> {
+        let accounts = self.accounts.read().await;
+        Ok(accounts.get(id).cloned())
+    }
+
     async fn get_accounts_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Account>> {
         let accounts = self.accounts.read().await;
         Ok(accounts
@@ -231,6 +435,21 @@ impl StorageAdapter for MemoryAdapter {
             .collect())
     }
 
+    async fn update_account(&self, account: &Account) -> AuthResult<Account> {
+        let mut accounts = self.accounts.write().await;
+
+        let existing = accounts
+            .get(&account.id)
+            .ok_or_else(|| AuthError::not_found("account", "id", &account.id))?;
+
+        let mut account = account.clone();
+        account.created_at = existing.created_at;
+        account.updated_at = next_updated_at(existing.updated_at);
+
+        accounts.insert(account.id.clone(), account.clone());
+        Ok(account)
+    }
+
     async fn delete_account(&self, id: &str) -> AuthResult<()> {
         let mut accounts = self.accounts.write().await;
         accounts.remove(id);
@@ -239,19 +458,28 @@ impl StorageAdapter for MemoryAdapter {
 
     // ==================== Schema Operations ====================
 
-    async fn migrate(&self, models: &[ModelDefinition]) -> AuthResult<()> {
-        let mut tables = self.tables.write().await;
-        for model in models {
-            if !tables.contains(&model.name) {
-                tables.push(model.name.clone());
+    /// Records `models` so that `table_exists` and `introspect_schema`
+    /// report them. There is no DDL to run, so no operations are returned.
+    async fn migrate(
+        &self,
+        models: &[ModelDefinition],
+        dry_run: bool,
+    ) -> AuthResult<Vec<MigrationOp>> {
+        if !dry_run {
+            let mut schema = self.schema.write().await;
+            for model in models {
+                schema.add_model(model.clone());
             }
         }
-        Ok(())
+        Ok(Vec::new())
     }
 
     async fn table_exists(&self, table_name: &str) -> AuthResult<bool> {
-        let tables = self.tables.read().await;
-        Ok(tables.contains(&table_name.to_string()))
+        Ok(self.schema.read().await.get_model(table_name).is_some())
+    }
+
+    async fn introspect_schema(&self) -> AuthResult<SchemaDefinition> {
+        Ok(self.schema.read().await.clone())
     }
 }
 
@@ -265,8 +493,12 @@ impl better_auth_plugin_access::AccessStorageExt for MemoryAdapter {
         if roles.contains_key(&role.id) {
             return Err(AuthError::duplicate("role", "id", &role.id));
         }
+        let mut role = role.clone();
+        let now = Utc::now();
+        role.created_at = now;
+        role.updated_at = now;
         roles.insert(role.id.clone(), role.clone());
-        Ok(role.clone())
+        Ok(role)
     }
 
     async fn get_role(&self, id: &str) -> AuthResult<Option<better_auth_plugin_access::DbRole>> {
@@ -281,18 +513,21 @@ impl better_auth_plugin_access::AccessStorageExt for MemoryAdapter {
 
     async fn update_role(&self, role: &better_auth_plugin_access::DbRole) -> AuthResult<better_auth_plugin_access::DbRole> {
         let mut roles = self.roles.write().await;
-        if !roles.contains_key(&role.id) {
-            return Err(AuthError::not_found("role", "id", &role.id));
-        }
+        let existing = roles
+            .get(&role.id)
+            .ok_or_else(|| AuthError::not_found("role", "id", &role.id))?;
+        let mut role = role.clone();
+        role.created_at = existing.created_at;
+        role.updated_at = next_updated_at(existing.updated_at);
         roles.insert(role.id.clone(), role.clone());
-        Ok(role.clone())
+        Ok(role)
     }
 
     async fn delete_role(&self, id: &str) -> AuthResult<()> {
         let mut roles = self.roles.write().await;
         if let Some(role) = roles.get(id) {
             if role.is_system {
-                return Err(AuthError::Forbidden("Cannot delete system roles".into()));
+                return Err(AuthError::forbidden("Cannot delete system roles"));
             }
         }
         roles.remove(id);
@@ -313,8 +548,10 @@ impl better_auth_plugin_access::AccessStorageExt for MemoryAdapter {
         if perms.contains_key(&perm.id) {
             return Err(AuthError::duplicate("permission", "id", &perm.id));
         }
+        let mut perm = perm.clone();
+        perm.created_at = Utc::now();
         perms.insert(perm.id.clone(), perm.clone());
-        Ok(perm.clone())
+        Ok(perm)
     }
 
     async fn get_permission(&self, id: &str) -> AuthResult<Option<better_auth_plugin_access::DbPermission>> {
@@ -336,7 +573,7 @@ impl better_auth_plugin_access::AccessStorageExt for MemoryAdapter {
         let mut perms = self.permissions.write().await;
         if let Some(perm) = perms.get(id) {
             if perm.is_system {
-                return Err(AuthError::Forbidden("Cannot delete system permissions".into()));
+                return Err(AuthError::forbidden("Cannot delete system permissions"));
             }
         }
         perms.remove(id);
@@ -383,10 +620,18 @@ impl better_auth_plugin_access::AccessStorageExt for MemoryAdapter {
     // User-Permission relationships
     async fn grant_permission_to_user(&self, user_id: &str, permission_id: &str) -> AuthResult<()> {
         let mut user_perms = self.user_permissions.write().await;
-        user_perms.insert(
-            (user_id.to_string(), permission_id.to_string()),
-            chrono::Utc::now(),
-        );
+        user_perms.insert((user_id.to_string(), permission_id.to_string()), None);
+        Ok(())
+    }
+
+    async fn grant_permission_to_user_until(
+        &self,
+        user_id: &str,
+        permission_id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()> {
+        let mut user_perms = self.user_permissions.write().await;
+        user_perms.insert((user_id.to_string(), permission_id.to_string()), Some(expires_at));
         Ok(())
     }
 
@@ -396,14 +641,21 @@ impl better_auth_plugin_access::AccessStorageExt for MemoryAdapter {
         Ok(())
     }
 
+    async fn revoke_all_user_permissions(&self, user_id: &str) -> AuthResult<()> {
+        let mut user_perms = self.user_permissions.write().await;
+        user_perms.retain(|(u, _), _| u != user_id);
+        Ok(())
+    }
+
     async fn get_user_permissions(&self, user_id: &str) -> AuthResult<Vec<better_auth_plugin_access::DbPermission>> {
         let user_perms = self.user_permissions.read().await;
         let perms = self.permissions.read().await;
         
+        let now = Utc::now();
         let perm_ids: Vec<String> = user_perms
-            .keys()
-            .filter(|(u, _)| u == user_id)
-            .map(|(_, p)| p.clone())
+            .iter()
+            .filter(|((u, _), expires_at)| u == user_id && expires_at.is_none_or(|e| e > now))
+            .map(|((_, p), _)| p.clone())
             .collect();
         
         Ok(perm_ids
@@ -415,6 +667,16 @@ impl better_auth_plugin_access::AccessStorageExt for MemoryAdapter {
     // Role hierarchy
     async fn set_role_parent(&self, child_id: &str, parent_id: &str) -> AuthResult<()> {
         let mut hierarchy = self.role_hierarchy.write().await;
+        let mut parents: HashMap<String, Vec<String>> = HashMap::new();
+        for (child, parent) in hierarchy.keys() {
+            parents.entry(child.clone()).or_default().push(parent.clone());
+        }
+        if better_auth_plugin_access::creates_cycle(&parents, child_id, parent_id) {
+            return Err(AuthError::conflict(format!(
+                "Role '{}' cannot inherit from '{}': it would become its own ancestor",
+                child_id, parent_id
+            )));
+        }
         hierarchy.insert((child_id.to_string(), parent_id.to_string()), ());
         Ok(())
     }
@@ -448,6 +710,244 @@ impl better_auth_plugin_access::AccessStorageExt for MemoryAdapter {
     }
 }
 
+// ==================== Email OTP Extension ====================
+
+#[async_trait]
+impl better_auth_plugin_email_otp::EmailOtpStore for MemoryAdapter {
+    async fn create_email_otp(&self, otp: &better_auth_plugin_email_otp::EmailOtp) -> AuthResult<better_auth_plugin_email_otp::EmailOtp> {
+        let mut otps = self.email_otps.write().await;
+        otps.retain(|_, o| o.email != otp.email || o.otp_type != otp.otp_type);
+        otps.insert(otp.id.clone(), otp.clone());
+        Ok(otp.clone())
+    }
+
+    async fn get_email_otp(&self, email: &str, otp_type: &str) -> AuthResult<Option<better_auth_plugin_email_otp::EmailOtp>> {
+        let otps = self.email_otps.read().await;
+        Ok(otps.values().find(|o| o.email == email && o.otp_type == otp_type).cloned())
+    }
+
+    async fn update_email_otp(&self, otp: &better_auth_plugin_email_otp::EmailOtp) -> AuthResult<better_auth_plugin_email_otp::EmailOtp> {
+        let mut otps = self.email_otps.write().await;
+        let existing = otps
+            .get_mut(&otp.id)
+            .ok_or_else(|| AuthError::not_found("email_otp", "id", &otp.id))?;
+        *existing = otp.clone();
+        Ok(otp.clone())
+    }
+
+    async fn delete_email_otp(&self, id: &str) -> AuthResult<()> {
+        self.email_otps.write().await.remove(id);
+        Ok(())
+    }
+}
+
+// ==================== Magic Link Extension ====================
+
+#[async_trait]
+impl better_auth_plugin_magic_link::MagicLinkTokenStore for MemoryAdapter {
+    async fn create_magic_link_token(&self, token: &better_auth_plugin_magic_link::MagicLinkToken) -> AuthResult<better_auth_plugin_magic_link::MagicLinkToken> {
+        let mut tokens = self.magic_link_tokens.write().await;
+        if tokens.values().any(|t| t.token == token.token) {
+            return Err(AuthError::duplicate("magic_link_token", "token", "<redacted>"));
+        }
+        tokens.insert(token.id.clone(), token.clone());
+        Ok(token.clone())
+    }
+
+    async fn get_magic_link_token(&self, token: &str) -> AuthResult<Option<better_auth_plugin_magic_link::MagicLinkToken>> {
+        let tokens = self.magic_link_tokens.read().await;
+        Ok(tokens.values().find(|t| t.token == token).cloned())
+    }
+
+    async fn use_magic_link_token(&self, id: &str) -> AuthResult<bool> {
+        // Checked and marked under one write lock, so a token is used once.
+        let mut tokens = self.magic_link_tokens.write().await;
+        match tokens.get_mut(id).filter(|t| !t.used) {
+            Some(token) => {
+                token.mark_used();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+// ==================== Password History Extension ====================
+
+/// A user's password history, newest first.
+fn user_password_history(
+    history: &HashMap<String, better_auth_plugin_password::PasswordHistoryEntry>,
+    user_id: &str,
+) -> Vec<better_auth_plugin_password::PasswordHistoryEntry> {
+    let mut entries: Vec<_> = history.values().filter(|h| h.user_id == user_id).cloned().collect();
+    entries.sort_by_key(|h| std::cmp::Reverse(h.created_at));
+    entries
+}
+
+#[async_trait]
+impl better_auth_plugin_password::PasswordHistoryStore for MemoryAdapter {
+    async fn add_password_history(&self, entry: &better_auth_plugin_password::PasswordHistoryEntry) -> AuthResult<()> {
+        self.password_history.write().await.insert(entry.id.clone(), entry.clone());
+        Ok(())
+    }
+
+    async fn get_password_history(&self, user_id: &str, limit: usize) -> AuthResult<Vec<better_auth_plugin_password::PasswordHistoryEntry>> {
+        let mut entries = user_password_history(&*self.password_history.read().await, user_id);
+        entries.truncate(limit);
+        Ok(entries)
+    }
+
+    async fn trim_password_history(&self, user_id: &str, keep: usize) -> AuthResult<()> {
+        let mut history = self.password_history.write().await;
+        for entry in user_password_history(&history, user_id).into_iter().skip(keep) {
+            history.remove(&entry.id);
+        }
+        Ok(())
+    }
+}
+
+// ==================== Two-Factor Extension ====================
+
+#[async_trait]
+impl better_auth_plugin_two_factor::TwoFactorStore for MemoryAdapter {
+    async fn create_two_factor(&self, data: &better_auth_plugin_two_factor::TwoFactorData) -> AuthResult<better_auth_plugin_two_factor::TwoFactorData> {
+        self.two_factor.write().await.insert(data.user_id.clone(), data.clone());
+        Ok(data.clone())
+    }
+
+    async fn get_two_factor(&self, user_id: &str) -> AuthResult<Option<better_auth_plugin_two_factor::TwoFactorData>> {
+        Ok(self.two_factor.read().await.get(user_id).cloned())
+    }
+
+    async fn advance_totp_step(&self, user_id: &str, step: u64) -> AuthResult<bool> {
+        // Checked and set under one write lock, so a step is used once.
+        let mut two_factor = self.two_factor.write().await;
+        match two_factor.get_mut(user_id).filter(|d| d.last_used_step.is_none_or(|last| last < step)) {
+            Some(data) => {
+                data.last_used_step = Some(step);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn swap_backup_codes(&self, user_id: &str, current: &str, codes: &str) -> AuthResult<bool> {
+        let mut two_factor = self.two_factor.write().await;
+        match two_factor.get_mut(user_id).filter(|d| d.backup_codes == current) {
+            Some(data) => {
+                data.backup_codes = codes.to_string();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete_two_factor(&self, user_id: &str) -> AuthResult<()> {
+        self.two_factor.write().await.remove(user_id);
+        Ok(())
+    }
+}
+
+// ==================== Trusted Device Extension ====================
+
+#[async_trait]
+impl better_auth_plugin_two_factor::TrustedDeviceStore for MemoryAdapter {
+    async fn create_trusted_device(&self, device: &better_auth_plugin_two_factor::TrustedDevice) -> AuthResult<better_auth_plugin_two_factor::TrustedDevice> {
+        let mut devices = self.trusted_devices.write().await;
+        if devices.values().any(|d| d.user_id == device.user_id && d.device_hash == device.device_hash) {
+            return Err(AuthError::duplicate("trusted_device", "device_hash", "<redacted>"));
+        }
+        devices.insert(device.id.clone(), device.clone());
+        Ok(device.clone())
+    }
+
+    async fn get_trusted_device(&self, user_id: &str, device_hash: &str) -> AuthResult<Option<better_auth_plugin_two_factor::TrustedDevice>> {
+        let devices = self.trusted_devices.read().await;
+        Ok(devices.values().find(|d| d.user_id == user_id && d.device_hash == device_hash).cloned())
+    }
+
+    async fn delete_user_trusted_devices(&self, user_id: &str) -> AuthResult<Vec<better_auth_plugin_two_factor::TrustedDevice>> {
+        let mut devices = self.trusted_devices.write().await;
+        let ids: Vec<String> = devices.values().filter(|d| d.user_id == user_id).map(|d| d.id.clone()).collect();
+        Ok(ids.iter().filter_map(|id| devices.remove(id)).collect())
+    }
+}
+
+// ==================== Passkey Extension ====================
+
+#[async_trait]
+impl better_auth_plugin_passkey::PasskeyStore for MemoryAdapter {
+    async fn create_passkey(&self, passkey: &better_auth_plugin_passkey::Passkey) -> AuthResult<better_auth_plugin_passkey::Passkey> {
+        let mut passkeys = self.passkeys.write().await;
+        if passkeys.values().any(|p| p.credential_id == passkey.credential_id) {
+            return Err(AuthError::duplicate("passkey", "credential_id", &passkey.credential_id));
+        }
+        passkeys.insert(passkey.id.clone(), passkey.clone());
+        Ok(passkey.clone())
+    }
+
+    async fn get_user_passkeys(&self, user_id: &str) -> AuthResult<Vec<better_auth_plugin_passkey::Passkey>> {
+        let passkeys = self.passkeys.read().await;
+        let mut result: Vec<_> = passkeys.values().filter(|p| p.user_id == user_id).cloned().collect();
+        result.sort_by_key(|p| p.created_at);
+        Ok(result)
+    }
+
+    async fn get_passkey_by_credential_id(&self, credential_id: &str) -> AuthResult<Option<better_auth_plugin_passkey::Passkey>> {
+        let passkeys = self.passkeys.read().await;
+        Ok(passkeys.values().find(|p| p.credential_id == credential_id).cloned())
+    }
+
+    async fn update_passkey(&self, passkey: &better_auth_plugin_passkey::Passkey) -> AuthResult<better_auth_plugin_passkey::Passkey> {
+        let mut passkeys = self.passkeys.write().await;
+        let existing = passkeys
+            .get_mut(&passkey.id)
+            .ok_or_else(|| AuthError::not_found("passkey", "id", &passkey.id))?;
+        *existing = passkey.clone();
+        Ok(passkey.clone())
+    }
+
+    async fn delete_user_passkeys(&self, user_id: &str) -> AuthResult<()> {
+        self.passkeys.write().await.retain(|_, p| p.user_id != user_id);
+        Ok(())
+    }
+}
+
+// ==================== API Key Extension ====================
+
+#[async_trait]
+impl better_auth_plugin_api_key::ApiKeyStore for MemoryAdapter {
+    async fn create_api_key(&self, key: &better_auth_plugin_api_key::ApiKey) -> AuthResult<better_auth_plugin_api_key::ApiKey> {
+        self.api_keys.write().await.insert(key.id.clone(), key.clone());
+        Ok(key.clone())
+    }
+
+    async fn get_api_keys_by_prefix(&self, key_prefix: &str) -> AuthResult<Vec<better_auth_plugin_api_key::ApiKey>> {
+        let keys = self.api_keys.read().await;
+        Ok(keys.values().filter(|k| k.key_prefix == key_prefix).cloned().collect())
+    }
+
+    async fn get_api_keys_by_user_id(&self, user_id: &str) -> AuthResult<Vec<better_auth_plugin_api_key::ApiKey>> {
+        let keys = self.api_keys.read().await;
+        let mut result: Vec<_> = keys.values().filter(|k| k.user_id == user_id).cloned().collect();
+        result.sort_by_key(|k| k.created_at);
+        Ok(result)
+    }
+
+    async fn delete_api_keys_by_user_id(&self, user_id: &str) -> AuthResult<()> {
+        self.api_keys.write().await.retain(|_, k| k.user_id != user_id);
+        Ok(())
+    }
+
+    async fn record_api_key_use(&self, id: &str) -> AuthResult<Result<better_auth_plugin_api_key::ApiKey, better_auth_plugin_api_key::UsageDenied>> {
+        // Read and updated under one write lock, so concurrent uses share
+        // the key's budget.
+        let mut keys = self.api_keys.write().await;
+        let key = keys.get_mut(id).ok_or_else(|| AuthError::not_found("api_key", "id", id))?;
+        Ok(key.record_use(Utc::now()).map(|()| key.clone()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -491,4 +991,359 @@ mod tests {
         assert!(fetched.is_some());
         assert_eq!(fetched.unwrap().user_id, "user_123");
     }
+
+    #[tokio::test]
+    async fn test_get_user_by_extension() {
+        let adapter = MemoryAdapter::new();
+        let mut user = User::new("test_id".to_string(), "test@example.com".to_string());
+        user.set_extension("username", "jane_doe");
+        adapter.create_user(&user).await.unwrap();
+
+        let found = adapter
+            .get_user_by_extension("username", &serde_json::json!("jane_doe"))
+            .await
+            .unwrap();
+        assert_eq!(found.unwrap().id, "test_id");
+
+        let missing = adapter
+            .get_user_by_extension("username", &serde_json::json!("john_doe"))
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unique_user_extensions() {
+        use better_auth_core::schema::{Field, FieldType};
+
+        let adapter = MemoryAdapter::new();
+        let mut jane = User::new("jane".to_string(), "jane@example.com".to_string());
+        jane.set_extension("username", "jane_doe");
+        adapter.create_user(&jane).await.unwrap();
+
+        let mut john = User::new("john".to_string(), "john@example.com".to_string());
+        john.set_extension("username", "jane_doe");
+        assert!(matches!(
+            adapter.create_user(&john).await,
+            Err(AuthError::DuplicateEntry { .. })
+        ));
+        john.set_extension("username", "john_doe");
+        let mut john = adapter.create_user(&john).await.unwrap();
+
+        // Updating to a taken username fails; keeping your own does not.
+        john.set_extension("username", "jane_doe");
+        assert!(matches!(
+            adapter.update_user(&john).await,
+            Err(AuthError::DuplicateEntry { .. })
+        ));
+        assert!(adapter.update_user(&jane).await.is_ok());
+
+        // Fields a migrated `user` model declares unique are enforced too.
+        let user_model = ModelDefinition::new("user")
+            .field(Field::optional("handle", FieldType::String(50)).unique());
+        adapter.migrate(&[user_model], false).await.unwrap();
+        jane.set_extension("handle", "jd");
+        adapter.update_user(&jane).await.unwrap();
+        let mut john = adapter.get_user_by_id("john").await.unwrap().unwrap();
+        john.set_extension("handle", "jd");
+        assert!(matches!(
+            adapter.update_user(&john).await,
+            Err(AuthError::DuplicateEntry { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_bumps_updated_at() {
+        let adapter = MemoryAdapter::new();
+        let user = User::new("test_id".to_string(), "test@example.com".to_string());
+
+        let created = adapter.create_user(&user).await.unwrap();
+        assert_eq!(created.created_at, created.updated_at);
+
+        let mut stale = created.clone();
+        stale.created_at = DateTime::<Utc>::MIN_UTC;
+        stale.updated_at = DateTime::<Utc>::MIN_UTC;
+        stale.name = Some("Test".to_string());
+
+        let first = adapter.update_user(&stale).await.unwrap();
+        assert_eq!(first.created_at, created.created_at);
+        assert!(first.updated_at > created.updated_at);
+
+        let second = adapter.update_user(&first).await.unwrap();
+        assert!(second.updated_at > first.updated_at);
+
+        let fetched = adapter.get_user_by_id("test_id").await.unwrap().unwrap();
+        assert_eq!(fetched.updated_at, second.updated_at);
+    }
+
+    #[tokio::test]
+    async fn test_update_user_keeps_extensions_it_was_not_given() {
+        let adapter = MemoryAdapter::new();
+        let mut user = User::new("test_id".to_string(), "test@example.com".to_string());
+        user.set_extension("two_factor_secret", "JBSWY3DPEHPK3PXP");
+        adapter.create_user(&user).await.unwrap();
+
+        // A copy loaded without the 2FA plugin's fields.
+        let mut renamed = User::new("test_id".to_string(), "test@example.com".to_string());
+        renamed.name = Some("Jane".to_string());
+        let updated = adapter.update_user(&renamed).await.unwrap();
+        assert_eq!(updated.name.as_deref(), Some("Jane"));
+        let secret: Option<String> = updated.get_extension("two_factor_secret");
+        assert_eq!(secret.as_deref(), Some("JBSWY3DPEHPK3PXP"));
+
+        let mut fetched = adapter.get_user_by_id("test_id").await.unwrap().unwrap();
+        assert_eq!(fetched.name.as_deref(), Some("Jane"));
+        assert!(fetched.extensions.contains_key("two_factor_secret"));
+
+        fetched.remove_extension("two_factor_secret");
+        let updated = adapter.update_user(&fetched).await.unwrap();
+        assert!(updated.extensions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_account_update() {
+        let adapter = MemoryAdapter::new();
+        let account = Account::new(
+            "user_123".to_string(),
+            "google".to_string(),
+            "google-1".to_string(),
+        );
+        let created = adapter.create_account(&account).await.unwrap();
+
+        let mut refreshed = created.clone();
+        refreshed.access_token = Some("new-access".to_string());
+        let updated = adapter.update_account(&refreshed).await.unwrap();
+        assert!(updated.updated_at > created.updated_at);
+
+        let fetched = adapter.get_account_by_id(&created.id).await.unwrap().unwrap();
+        assert_eq!(fetched.access_token.as_deref(), Some("new-access"));
+        assert!(adapter.get_account_by_id("missing").await.unwrap().is_none());
+
+        let mut unknown = created;
+        unknown.id = "missing".to_string();
+        assert!(adapter.update_account(&unknown).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_users_paging_and_filter() {
+        let adapter = MemoryAdapter::new();
+        for i in 0..5 {
+            let domain = if i % 2 == 0 { "example.com" } else { "other.org" };
+            let user = User::new(format!("id{}", i), format!("user{}@{}", i, domain));
+            adapter.create_user(&user).await.unwrap();
+        }
+
+        let (page, total) = adapter.list_users(0, 2, None).await.unwrap();
+        assert_eq!(total, 5);
+        assert_eq!(page.len(), 2);
+        let (rest, _) = adapter.list_users(2, 10, None).await.unwrap();
+        assert_eq!(rest.len(), 3);
+        assert!(page.iter().all(|u| rest.iter().all(|r| r.id != u.id)));
+
+        let filter = UserFilter::new().email_contains("EXAMPLE");
+        let (page, total) = adapter.list_users(0, 10, Some(filter)).await.unwrap();
+        assert_eq!(total, 3);
+        assert!(page.iter().all(|u| u.email.ends_with("@example.com")));
+
+        let cutoff = page[0].created_at;
+        let filter = UserFilter::new().created_after(cutoff);
+        let (page, total) = adapter.list_users(0, 10, Some(filter)).await.unwrap();
+        assert!(total < 5);
+        assert!(page.iter().all(|u| u.created_at > cutoff));
+
+        let filter = UserFilter::new().created_before(cutoff);
+        let (page, _) = adapter.list_users(0, 10, Some(filter)).await.unwrap();
+        assert!(page.iter().all(|u| u.created_at < cutoff));
+
+        let mut flagged = adapter.get_user_by_id("id3").await.unwrap().unwrap();
+        flagged.set_extension("is_anonymous", true);
+        adapter.update_user(&flagged).await.unwrap();
+        let filter = UserFilter::new().extension("is_anonymous", true);
+        let (page, total) = adapter.list_users(0, 10, Some(filter)).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(page[0].id, "id3");
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_restore_and_purge() {
+        let adapter = MemoryAdapter::new();
+        let user = User::new("test_id".to_string(), "test@example.com".to_string());
+        adapter.create_user(&user).await.unwrap();
+        let session = adapter
+            .create_session(&Session::new("test_id".to_string()))
+            .await
+            .unwrap();
+
+        adapter.soft_delete_user("test_id").await.unwrap();
+        assert!(adapter.get_user_by_id("test_id").await.unwrap().is_none());
+        let by_email = adapter.get_user_by_email("test@example.com").await.unwrap();
+        assert!(by_email.is_none());
+        let session = adapter.get_session_by_token(&session.token).await.unwrap();
+        assert!(session.is_none());
+        assert_eq!(adapter.count_users().await.unwrap(), 0);
+        let filter = UserFilter::new().include_deleted();
+        let (page, _) = adapter.list_users(0, 10, Some(filter)).await.unwrap();
+        assert!(page[0].is_deleted());
+
+        // The email is still taken while the user can be restored.
+        assert!(adapter.create_user(&user).await.is_err());
+
+        let restored = adapter.restore_user("test_id").await.unwrap().unwrap();
+        assert!(!restored.is_deleted());
+        assert!(adapter.get_user_by_id("test_id").await.unwrap().is_some());
+        assert!(adapter.restore_user("test_id").await.unwrap().is_none());
+
+        adapter.soft_delete_user("test_id").await.unwrap();
+        let purged = adapter
+            .purge_deleted_users(Utc::now() - chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 0);
+        assert_eq!(adapter.purge_deleted_users(Utc::now()).await.unwrap(), 1);
+        assert_eq!(adapter.user_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_email_otp_store() {
+        use better_auth_plugin_email_otp::{EmailOtp, EmailOtpStore};
+
+        let adapter = MemoryAdapter::new();
+        let expires_at = Utc::now() + chrono::Duration::minutes(5);
+        let first = EmailOtp::new("jane@example.com", "111111", "sign-in", expires_at);
+        adapter.create_email_otp(&first).await.unwrap();
+        let other = EmailOtp::new("jane@example.com", "222222", "forget-password", expires_at);
+        adapter.create_email_otp(&other).await.unwrap();
+
+        // A new code replaces the earlier one of the same type only.
+        let mut second = EmailOtp::new("jane@example.com", "333333", "sign-in", expires_at);
+        adapter.create_email_otp(&second).await.unwrap();
+        let found = adapter.get_email_otp("jane@example.com", "sign-in").await.unwrap().unwrap();
+        assert_eq!(found.otp, "333333");
+        assert!(adapter.get_email_otp("jane@example.com", "forget-password").await.unwrap().is_some());
+
+        second.increment_attempts();
+        adapter.update_email_otp(&second).await.unwrap();
+        let found = adapter.get_email_otp("jane@example.com", "sign-in").await.unwrap().unwrap();
+        assert_eq!(found.attempts, 1);
+
+        adapter.delete_email_otp(&second.id).await.unwrap();
+        assert!(adapter.get_email_otp("jane@example.com", "sign-in").await.unwrap().is_none());
+        assert!(adapter.update_email_otp(&second).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_magic_link_token_is_used_once() {
+        use better_auth_plugin_magic_link::{MagicLinkToken, MagicLinkTokenStore};
+
+        let adapter = MemoryAdapter::new();
+        let token = MagicLinkToken::new("jane@example.com", "abc123", Utc::now() + chrono::Duration::minutes(5));
+        adapter.create_magic_link_token(&token).await.unwrap();
+
+        let found = adapter.get_magic_link_token("abc123").await.unwrap().unwrap();
+        assert_eq!(found.id, token.id);
+        assert!(adapter.use_magic_link_token(&token.id).await.unwrap());
+        assert!(!adapter.use_magic_link_token(&token.id).await.unwrap());
+        assert!(adapter.get_magic_link_token("abc123").await.unwrap().unwrap().used);
+    }
+
+    #[tokio::test]
+    async fn test_password_history_store() {
+        use better_auth_plugin_password::{PasswordHistoryEntry, PasswordHistoryStore};
+
+        let adapter = MemoryAdapter::new();
+        for (i, hash) in ["hash-1", "hash-2", "hash-3"].iter().enumerate() {
+            let mut entry = PasswordHistoryEntry::new("user_1", *hash);
+            entry.created_at += chrono::Duration::seconds(i as i64);
+            adapter.add_password_history(&entry).await.unwrap();
+        }
+        adapter.add_password_history(&PasswordHistoryEntry::new("user_2", "other")).await.unwrap();
+
+        let recent = adapter.get_password_history("user_1", 2).await.unwrap();
+        let hashes: Vec<_> = recent.iter().map(|e| e.password_hash.as_str()).collect();
+        assert_eq!(hashes, ["hash-3", "hash-2"]);
+
+        adapter.trim_password_history("user_1", 1).await.unwrap();
+        let kept = adapter.get_password_history("user_1", 10).await.unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].password_hash, "hash-3");
+        assert_eq!(adapter.get_password_history("user_2", 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_two_factor_store() {
+        use better_auth_plugin_two_factor::{TwoFactorData, TwoFactorStore};
+
+        let adapter = MemoryAdapter::new();
+        adapter.create_user(&User::new("user_1".to_string(), "jane@example.com".to_string())).await.unwrap();
+        adapter.create_two_factor(&TwoFactorData::new("user_1", "secret", &[])).await.unwrap();
+
+        assert!(adapter.advance_totp_step("user_1", 5).await.unwrap());
+        assert!(!adapter.advance_totp_step("user_1", 5).await.unwrap());
+        assert!(!adapter.advance_totp_step("user_1", 4).await.unwrap());
+        assert_eq!(adapter.get_two_factor("user_1").await.unwrap().unwrap().last_used_step, Some(5));
+
+        let current = adapter.get_two_factor("user_1").await.unwrap().unwrap().backup_codes;
+        assert!(adapter.swap_backup_codes("user_1", &current, "[\"a\"]").await.unwrap());
+        assert!(!adapter.swap_backup_codes("user_1", &current, "[\"b\"]").await.unwrap());
+
+        adapter.delete_user("user_1").await.unwrap();
+        assert!(adapter.get_two_factor("user_1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_trusted_device_store() {
+        use better_auth_plugin_two_factor::{TrustedDevice, TrustedDeviceStore};
+
+        let adapter = MemoryAdapter::new();
+        let expires_at = Utc::now() + chrono::Duration::days(30);
+        adapter.create_trusted_device(&TrustedDevice::new("user_1", "hash-1", expires_at)).await.unwrap();
+        adapter.create_trusted_device(&TrustedDevice::new("user_2", "hash-1", expires_at)).await.unwrap();
+        assert!(adapter.create_trusted_device(&TrustedDevice::new("user_1", "hash-1", expires_at)).await.is_err());
+
+        assert!(adapter.get_trusted_device("user_1", "hash-1").await.unwrap().is_some());
+        assert!(adapter.get_trusted_device("user_1", "hash-2").await.unwrap().is_none());
+
+        let removed = adapter.delete_user_trusted_devices("user_1").await.unwrap();
+        assert_eq!(removed.len(), 1);
+        assert!(adapter.get_trusted_device("user_1", "hash-1").await.unwrap().is_none());
+        assert!(adapter.get_trusted_device("user_2", "hash-1").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_passkey_store() {
+        use better_auth_plugin_passkey::{Passkey, PasskeyStore};
+
+        let adapter = MemoryAdapter::new();
+        let passkey = adapter.create_passkey(&Passkey::new("user_1", "cred_1", "key")).await.unwrap();
+        assert!(adapter.create_passkey(&Passkey::new("user_2", "cred_1", "key")).await.is_err());
+
+        let mut found = adapter.get_passkey_by_credential_id("cred_1").await.unwrap().unwrap();
+        assert_eq!(found.id, passkey.id);
+        found.counter = 7;
+        adapter.update_passkey(&found).await.unwrap();
+        assert_eq!(adapter.get_user_passkeys("user_1").await.unwrap()[0].counter, 7);
+
+        adapter.delete_user_passkeys("user_1").await.unwrap();
+        assert!(adapter.get_passkey_by_credential_id("cred_1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_api_key_store() {
+        use better_auth_plugin_api_key::{ApiKey, ApiKeyStore, UsageDenied};
+
+        let adapter = MemoryAdapter::new();
+        let key = adapter.create_api_key(&ApiKey::new("user_1", "prefix_secret").with_remaining(1)).await.unwrap();
+        assert_eq!(adapter.get_api_keys_by_prefix("prefix").await.unwrap().len(), 1);
+        assert_eq!(adapter.get_api_keys_by_user_id("user_1").await.unwrap().len(), 1);
+
+        let used = adapter.record_api_key_use(&key.id).await.unwrap().unwrap();
+        assert_eq!(used.remaining, Some(0));
+        assert_eq!(used.request_count, 1);
+        assert!(matches!(adapter.record_api_key_use(&key.id).await.unwrap(), Err(UsageDenied::QuotaExhausted)));
+        assert!(adapter.record_api_key_use("missing").await.is_err());
+
+        adapter.delete_api_keys_by_user_id("user_1").await.unwrap();
+        assert!(adapter.get_api_keys_by_user_id("user_1").await.unwrap().is_empty());
+    }
+
 }
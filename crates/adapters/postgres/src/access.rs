@@ -0,0 +1,365 @@
+//! [`AccessStorageExt`] implementation over the access plugin's tables.
+
+use crate::adapter::{BUMP_UPDATED_AT, PostgresAdapter, decode, decode_all, select};
+use crate::error::db_error;
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_plugin_access::{AccessStorageExt, DbPermission, DbRole, creates_cycle};
+use chrono::{DateTime, Utc};
+use sqlx::Connection;
+use std::collections::HashMap;
+
+/// Permissions joined through a relation table, e.g. `role_permissions`.
+fn select_permissions_via(relation: &str) -> String {
+    format!("SELECT to_jsonb(t) FROM permissions t JOIN {relation} r ON r.permission_id = t.id")
+}
+
+#[async_trait]
+impl AccessStorageExt for PostgresAdapter {
+    // ==================== Role Operations ====================
+
+    async fn create_role(&self, role: &DbRole) -> AuthResult<DbRole> {
+        let mut conn = self.conn("role").await?;
+        sqlx::query_scalar(
+            "INSERT INTO roles AS t (id, name, description, is_system, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, now(), now()) RETURNING to_jsonb(t)",
+        )
+        .bind(&role.id)
+        .bind(&role.name)
+        .bind(&role.description)
+        .bind(role.is_system)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("role"))
+        .and_then(decode)
+    }
+
+    async fn get_role(&self, id: &str) -> AuthResult<Option<DbRole>> {
+        let mut conn = self.conn("role").await?;
+        sqlx::query_scalar(&format!("{} WHERE id = $1", select("roles")))
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("role"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn list_roles(&self) -> AuthResult<Vec<DbRole>> {
+        let mut conn = self.conn("role").await?;
+        let rows = sqlx::query_scalar(&format!("{} ORDER BY id", select("roles")))
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(db_error("role"))?;
+        decode_all(rows)
+    }
+
+    async fn update_role(&self, role: &DbRole) -> AuthResult<DbRole> {
+        let mut conn = self.conn("role").await?;
+        sqlx::query_scalar(&format!(
+            "UPDATE roles AS t SET name = $2, description = $3, is_system = $4, {} \
+             WHERE id = $1 RETURNING to_jsonb(t)",
+            BUMP_UPDATED_AT
+        ))
+        .bind(&role.id)
+        .bind(&role.name)
+        .bind(&role.description)
+        .bind(role.is_system)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("role"))?
+        .map(decode)
+        .transpose()?
+        .ok_or_else(|| AuthError::not_found("role", "id", &role.id))
+    }
+
+    async fn delete_role(&self, id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("role").await?;
+        // Role permissions and hierarchy edges go with it via ON DELETE CASCADE.
+        let deleted: Option<bool> = sqlx::query_scalar(
+            "WITH target AS (SELECT is_system FROM roles WHERE id = $1), \
+             deleted AS (DELETE FROM roles WHERE id = $1 AND NOT is_system RETURNING id) \
+             SELECT is_system FROM target",
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("role"))?;
+        if deleted == Some(true) {
+            return Err(AuthError::forbidden("Cannot delete system roles"));
+        }
+        Ok(())
+    }
+
+    // ==================== Permission Operations ====================
+
+    async fn create_permission(&self, perm: &DbPermission) -> AuthResult<DbPermission> {
+        let mut conn = self.conn("permission").await?;
+        sqlx::query_scalar(
+            "INSERT INTO permissions AS t (id, name, resource, action, description, is_system, \
+             created_at) VALUES ($1, $2, $3, $4, $5, $6, now()) RETURNING to_jsonb(t)",
+        )
+        .bind(&perm.id)
+        .bind(&perm.name)
+        .bind(&perm.resource)
+        .bind(&perm.action)
+        .bind(&perm.description)
+        .bind(perm.is_system)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("permission"))
+        .and_then(decode)
+    }
+
+    async fn get_permission(&self, id: &str) -> AuthResult<Option<DbPermission>> {
+        let mut conn = self.conn("permission").await?;
+        sqlx::query_scalar(&format!("{} WHERE id = $1", select("permissions")))
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("permission"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn get_permission_by_name(&self, name: &str) -> AuthResult<Option<DbPermission>> {
+        let mut conn = self.conn("permission").await?;
+        sqlx::query_scalar(&format!("{} WHERE name = $1", select("permissions")))
+            .bind(name)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("permission"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn list_permissions(&self) -> AuthResult<Vec<DbPermission>> {
+        let mut conn = self.conn("permission").await?;
+        let rows = sqlx::query_scalar(&format!("{} ORDER BY name", select("permissions")))
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(db_error("permission"))?;
+        decode_all(rows)
+    }
+
+    async fn delete_permission(&self, id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("permission").await?;
+        let deleted: Option<bool> = sqlx::query_scalar(
+            "WITH target AS (SELECT is_system FROM permissions WHERE id = $1), \
+             deleted AS (DELETE FROM permissions WHERE id = $1 AND NOT is_system RETURNING id) \
+             SELECT is_system FROM target",
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("permission"))?;
+        if deleted == Some(true) {
+            return Err(AuthError::forbidden("Cannot delete system permissions"));
+        }
+        Ok(())
+    }
+
+    // ==================== Role-Permission Relationships ====================
+
+    async fn assign_permission_to_role(
+        &self,
+        role_id: &str,
+        permission_id: &str,
+    ) -> AuthResult<()> {
+        let mut conn = self.conn("role_permission").await?;
+        sqlx::query(
+            "INSERT INTO role_permissions (role_id, permission_id) VALUES ($1, $2) \
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(role_id)
+        .bind(permission_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(db_error("role_permission"))?;
+        Ok(())
+    }
+
+    async fn remove_permission_from_role(
+        &self,
+        role_id: &str,
+        permission_id: &str,
+    ) -> AuthResult<()> {
+        let mut conn = self.conn("role_permission").await?;
+        sqlx::query("DELETE FROM role_permissions WHERE role_id = $1 AND permission_id = $2")
+            .bind(role_id)
+            .bind(permission_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("role_permission"))?;
+        Ok(())
+    }
+
+    async fn get_role_permissions(&self, role_id: &str) -> AuthResult<Vec<DbPermission>> {
+        let mut conn = self.conn("role_permission").await?;
+        let rows = sqlx::query_scalar(&format!(
+            "{} WHERE r.role_id = $1 ORDER BY t.name",
+            select_permissions_via("role_permissions")
+        ))
+        .bind(role_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("role_permission"))?;
+        decode_all(rows)
+    }
+
+    // ==================== User-Permission Relationships ====================
+
+    async fn grant_permission_to_user(&self, user_id: &str, permission_id: &str) -> AuthResult<()> {
+        self.grant(user_id, permission_id, None).await
+    }
+
+    async fn grant_permission_to_user_until(
+        &self,
+        user_id: &str,
+        permission_id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()> {
+        self.grant(user_id, permission_id, Some(expires_at)).await
+    }
+
+    async fn revoke_permission_from_user(
+        &self,
+        user_id: &str,
+        permission_id: &str,
+    ) -> AuthResult<()> {
+        let mut conn = self.conn("user_permission").await?;
+        sqlx::query("DELETE FROM user_permissions WHERE user_id = $1 AND permission_id = $2")
+            .bind(user_id)
+            .bind(permission_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("user_permission"))?;
+        Ok(())
+    }
+
+    async fn revoke_all_user_permissions(&self, user_id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("user_permission").await?;
+        sqlx::query("DELETE FROM user_permissions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("user_permission"))?;
+        Ok(())
+    }
+
+    async fn get_user_permissions(&self, user_id: &str) -> AuthResult<Vec<DbPermission>> {
+        let mut conn = self.conn("user_permission").await?;
+        let rows = sqlx::query_scalar(&format!(
+            "{} WHERE r.user_id = $1 AND (r.expires_at IS NULL OR r.expires_at > now()) \
+             ORDER BY t.name",
+            select_permissions_via("user_permissions")
+        ))
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("user_permission"))?;
+        decode_all(rows)
+    }
+
+    // ==================== Role Hierarchy ====================
+
+    async fn set_role_parent(&self, child_id: &str, parent_id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("role_hierarchy").await?;
+        let mut tx = conn.begin().await.map_err(db_error("role_hierarchy"))?;
+        // Block concurrent edits so two edges cannot form a cycle together.
+        sqlx::query("LOCK TABLE role_hierarchy IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error("role_hierarchy"))?;
+        let edges: Vec<(String, String)> =
+            sqlx::query_as("SELECT child_role_id, parent_role_id FROM role_hierarchy")
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(db_error("role_hierarchy"))?;
+        if creates_cycle(&into_hierarchy(edges), child_id, parent_id) {
+            return Err(AuthError::conflict(format!(
+                "Role '{}' cannot inherit from '{}': it would become its own ancestor",
+                child_id, parent_id
+            )));
+        }
+
+        sqlx::query(
+            "INSERT INTO role_hierarchy (child_role_id, parent_role_id) VALUES ($1, $2) \
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(child_id)
+        .bind(parent_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error("role_hierarchy"))?;
+        tx.commit().await.map_err(db_error("role_hierarchy"))
+    }
+
+    async fn remove_role_parent(&self, child_id: &str, parent_id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("role_hierarchy").await?;
+        sqlx::query("DELETE FROM role_hierarchy WHERE child_role_id = $1 AND parent_role_id = $2")
+            .bind(child_id)
+            .bind(parent_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("role_hierarchy"))?;
+        Ok(())
+    }
+
+    async fn get_role_parents(&self, role_id: &str) -> AuthResult<Vec<String>> {
+        let mut conn = self.conn("role_hierarchy").await?;
+        sqlx::query_scalar(
+            "SELECT parent_role_id FROM role_hierarchy WHERE child_role_id = $1 \
+             ORDER BY parent_role_id",
+        )
+        .bind(role_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("role_hierarchy"))
+    }
+
+    async fn get_role_hierarchy(&self) -> AuthResult<HashMap<String, Vec<String>>> {
+        let mut conn = self.conn("role_hierarchy").await?;
+        let edges = sqlx::query_as(
+            "SELECT child_role_id, parent_role_id FROM role_hierarchy ORDER BY parent_role_id",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("role_hierarchy"))?;
+        Ok(into_hierarchy(edges))
+    }
+}
+
+impl PostgresAdapter {
+    /// Grants a permission, replacing the expiry of an existing grant.
+    async fn grant(
+        &self,
+        user_id: &str,
+        permission_id: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> AuthResult<()> {
+        let mut conn = self.conn("user_permission").await?;
+        sqlx::query(
+            "INSERT INTO user_permissions (user_id, permission_id, granted_at, expires_at) \
+             VALUES ($1, $2, now(), $3) \
+             ON CONFLICT (user_id, permission_id) \
+             DO UPDATE SET granted_at = now(), expires_at = EXCLUDED.expires_at",
+        )
+        .bind(user_id)
+        .bind(permission_id)
+        .bind(expires_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(db_error("user_permission"))?;
+        Ok(())
+    }
+}
+
+/// Groups (child, parent) edges into a child -> parents map.
+fn into_hierarchy(edges: Vec<(String, String)>) -> HashMap<String, Vec<String>> {
+    let mut hierarchy: HashMap<String, Vec<String>> = HashMap::new();
+    for (child, parent) in edges {
+        hierarchy.entry(child).or_default().push(parent);
+    }
+    hierarchy
+}
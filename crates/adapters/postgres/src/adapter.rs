@@ -0,0 +1,876 @@
+//! [`StorageAdapter`] implementation.
+
+use crate::config::PostgresConfig;
+use crate::error::db_error;
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::schema::{
+    Field, FieldType, IndexDefinition, MigrationOp, MigrationRunner, ModelDefinition,
+    SchemaDefinition, SchemaDiff, SqlDialect,
+};
+use better_auth_core::traits::{StorageAdapter, StorageTransaction};
+use better_auth_core::types::{Account, Session, User, UserFilter};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::PgPool;
+use sqlx::{Connection, Executor, PgConnection, Postgres, QueryBuilder, Transaction};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use tokio::sync::{Mutex, MutexGuard};
+
+/// `updated_at` for an update: the transaction time, or one microsecond
+/// after the stored value if the clock has not moved past it.
+pub(crate) const BUMP_UPDATED_AT: &str =
+    "updated_at = GREATEST(now(), updated_at + interval '1 microsecond')";
+
+/// Storage adapter backed by PostgreSQL.
+///
+/// Plugin fields that extend `user` or `session` are stored in columns of
+/// the same name, created by [`migrate`](StorageAdapter::migrate). Writes
+/// set the columns for every key in `extensions`; keys that are absent are
+/// left unchanged, so clear a field by setting it to `null`.
+///
+/// [`begin`](StorageAdapter::begin) returns an adapter bound to a database
+/// transaction; every operation on it runs in that transaction until it is
+/// committed or rolled back. Dropping it without committing rolls back.
+#[derive(Debug, Clone)]
+pub struct PostgresAdapter {
+    pool: PgPool,
+    /// The open transaction, for adapters returned by `begin`. Taken out
+    /// when committed or rolled back.
+    tx: Option<Arc<Mutex<Option<Transaction<'static, Postgres>>>>>,
+}
+
+/// The connection one operation runs on.
+pub(crate) enum Conn<'a> {
+    Pool(PoolConnection<Postgres>),
+    Tx(MutexGuard<'a, Option<Transaction<'static, Postgres>>>),
+}
+
+impl Deref for Conn<'_> {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &PgConnection {
+        match self {
+            Conn::Pool(conn) => conn,
+            Conn::Tx(tx) => tx.as_ref().expect("checked in PostgresAdapter::conn"),
+        }
+    }
+}
+
+impl DerefMut for Conn<'_> {
+    fn deref_mut(&mut self) -> &mut PgConnection {
+        match self {
+            Conn::Pool(conn) => conn,
+            Conn::Tx(tx) => tx.as_mut().expect("checked in PostgresAdapter::conn"),
+        }
+    }
+}
+
+impl PostgresAdapter {
+    /// Connects to the database at `url` with the default pool settings.
+    pub async fn connect(url: &str) -> AuthResult<Self> {
+        Self::connect_with(url, PostgresConfig::default()).await
+    }
+
+    /// Connects to the database at `url` with the given pool settings.
+    pub async fn connect_with(url: &str, config: PostgresConfig) -> AuthResult<Self> {
+        let pool =
+            config.pool_options().connect(url).await.map_err(|e| {
+                AuthError::database(format!("Failed to connect to Postgres: {}", e))
+            })?;
+        Ok(Self::from_pool(pool))
+    }
+
+    /// Creates an adapter from an existing pool.
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self { pool, tx: None }
+    }
+
+    /// Returns the underlying connection pool.
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Returns the connection for an operation on `entity`: the adapter's
+    /// transaction if it has one, otherwise a connection from the pool.
+    pub(crate) async fn conn(&self, entity: &str) -> AuthResult<Conn<'_>> {
+        match &self.tx {
+            None => self
+                .pool
+                .acquire()
+                .await
+                .map(Conn::Pool)
+                .map_err(db_error(entity)),
+            Some(tx) => {
+                let guard = tx.lock().await;
+                if guard.is_none() {
+                    return Err(AuthError::database("Transaction already finished"));
+                }
+                Ok(Conn::Tx(guard))
+            }
+        }
+    }
+
+    /// Takes the open transaction out of the adapter.
+    async fn take_tx(&self) -> AuthResult<Transaction<'static, Postgres>> {
+        let tx = self
+            .tx
+            .as_ref()
+            .ok_or_else(|| AuthError::database("Adapter is not in a transaction"))?;
+        tx.lock()
+            .await
+            .take()
+            .ok_or_else(|| AuthError::database("Transaction already finished"))
+    }
+}
+
+/// A row of `information_schema.columns`: table, column, data type,
+/// nullable, default, and length, precision, and scale.
+type ColumnRow = (
+    String,
+    String,
+    String,
+    bool,
+    Option<String>,
+    Option<i32>,
+    Option<i32>,
+    Option<i32>,
+);
+
+/// Maps an `information_schema` data type back to a [`FieldType`].
+///
+/// Types with no counterpart are reported as `Text`.
+fn column_type(
+    data_type: &str,
+    length: Option<i32>,
+    precision: Option<i32>,
+    scale: Option<i32>,
+) -> FieldType {
+    match data_type {
+        "character varying" | "character" => FieldType::String(length.unwrap_or(0) as u32),
+        "integer" => FieldType::Integer,
+        "bigint" => FieldType::BigInt,
+        "boolean" => FieldType::Boolean,
+        "timestamp with time zone" | "timestamp without time zone" => FieldType::Timestamp,
+        "date" => FieldType::Date,
+        "json" | "jsonb" => FieldType::Json,
+        "bytea" => FieldType::Binary,
+        "uuid" => FieldType::Uuid,
+        "numeric" => FieldType::Decimal(precision.unwrap_or(0) as u8, scale.unwrap_or(0) as u8),
+        _ => FieldType::Text,
+    }
+}
+
+/// Quotes a table or column name.
+pub(crate) fn quote(ident: &str) -> String {
+    SqlDialect::Postgres.quote_ident(ident)
+}
+
+/// `SELECT` returning each row of `table` as a JSON object, so core fields
+/// and extension columns decode together.
+pub(crate) fn select(table: &str) -> String {
+    format!("SELECT to_jsonb(t) FROM {} t", quote(table))
+}
+
+/// Decodes a row selected with [`select`].
+///
+/// `NULL` columns are dropped first so unset extension columns do not show
+/// up in `extensions`.
+pub(crate) fn decode<T: DeserializeOwned>(row: Value) -> AuthResult<T> {
+    let mut row = match row {
+        Value::Object(map) => map,
+        other => return Err(AuthError::database(format!("Unexpected row: {}", other))),
+    };
+    row.retain(|_, value| !value.is_null());
+    serde_json::from_value(Value::Object(row))
+        .map_err(|e| AuthError::database(format!("Failed to decode row: {}", e)))
+}
+
+/// Decodes every row selected with [`select`].
+pub(crate) fn decode_all<T: DeserializeOwned>(rows: Vec<Value>) -> AuthResult<Vec<T>> {
+    rows.into_iter().map(decode).collect()
+}
+
+/// Fetches the row of `table` with the given `id`.
+async fn fetch_by_id<T: DeserializeOwned>(
+    conn: &mut PgConnection,
+    table: &str,
+    id: &str,
+) -> AuthResult<Option<T>> {
+    sqlx::query_scalar(&format!("{} WHERE id = $1", select(table)))
+        .bind(id)
+        .fetch_optional(conn)
+        .await
+        .map_err(db_error(table))?
+        .map(decode)
+        .transpose()
+}
+
+/// Writes extension fields to the columns of the same name.
+///
+/// Postgres converts each JSON value to its column's type, and fails the
+/// statement if a column does not exist.
+async fn write_extensions(
+    conn: &mut PgConnection,
+    table: &str,
+    id: &str,
+    extensions: &HashMap<String, Value>,
+) -> AuthResult<()> {
+    if extensions.is_empty() {
+        return Ok(());
+    }
+    let columns = extensions
+        .keys()
+        .map(|key| quote(key))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "UPDATE {table} SET ({columns}) = \
+         (SELECT {columns} FROM jsonb_populate_record(NULL::{table}, $2)) WHERE id = $1",
+        table = quote(table),
+    );
+    sqlx::query(&sql)
+        .bind(id)
+        .bind(serde_json::to_value(extensions).unwrap_or_default())
+        .execute(conn)
+        .await
+        .map_err(db_error(table))?;
+    Ok(())
+}
+
+/// Appends the `WHERE` clause for a user filter.
+fn push_user_filter(query: &mut QueryBuilder<'_, Postgres>, filter: &UserFilter) {
+    query.push(" WHERE TRUE");
+    if !filter.include_deleted {
+        query.push(" AND deleted_at IS NULL");
+    }
+    if let Some(needle) = &filter.email_contains {
+        query
+            .push(" AND strpos(lower(email), lower(")
+            .push_bind(needle.clone())
+            .push(")) > 0");
+    }
+    if let Some(after) = filter.created_after {
+        query.push(" AND created_at > ").push_bind(after);
+    }
+    if let Some(before) = filter.created_before {
+        query.push(" AND created_at < ").push_bind(before);
+    }
+    for (key, value) in &filter.extensions {
+        // Compared in the column's own type, as in `get_user_by_extension`.
+        let column = quote(key);
+        query
+            .push(format!(
+                r#" AND {column} = (SELECT {column} FROM jsonb_populate_record(NULL::"user", "#
+            ))
+            .push_bind(serde_json::json!({ key: value }))
+            .push("))");
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for PostgresAdapter {
+    // ==================== User Operations ====================
+
+    async fn create_user(&self, user: &User) -> AuthResult<User> {
+        let mut conn = self.conn("user").await?;
+        let mut tx = conn.begin().await.map_err(db_error("user"))?;
+        sqlx::query(
+            r#"INSERT INTO "user" (id, email, email_verified, name, image, created_at, updated_at)
+               VALUES ($1, $2, $3, $4, $5, now(), now())"#,
+        )
+        .bind(&user.id)
+        .bind(&user.email)
+        .bind(user.email_verified)
+        .bind(&user.name)
+        .bind(&user.image)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error("user"))?;
+        write_extensions(&mut tx, "user", &user.id, &user.extensions).await?;
+
+        let created = fetch_by_id(&mut tx, "user", &user.id)
+            .await?
+            .ok_or_else(|| AuthError::not_found("user", "id", &user.id))?;
+        tx.commit().await.map_err(db_error("user"))?;
+        Ok(created)
+    }
+
+    async fn get_user_by_id(&self, id: &str) -> AuthResult<Option<User>> {
+        let mut conn = self.conn("user").await?;
+        let sql = format!("{} WHERE id = $1 AND deleted_at IS NULL", select("user"));
+        sqlx::query_scalar(&sql)
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("user"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> AuthResult<Option<User>> {
+        let mut conn = self.conn("user").await?;
+        let sql = format!("{} WHERE email = $1 AND deleted_at IS NULL", select("user"));
+        sqlx::query_scalar(&sql)
+            .bind(email)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("user"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn get_user_by_extension(&self, key: &str, value: &Value) -> AuthResult<Option<User>> {
+        let mut conn = self.conn("user").await?;
+        // Compare in the column's own type so an index on it can be used.
+        let column = quote(key);
+        let sql = format!(
+            r#"{} WHERE t.{column} = (SELECT {column} FROM jsonb_populate_record(NULL::"user", $1))
+               AND t.deleted_at IS NULL LIMIT 1"#,
+            select("user"),
+        );
+        sqlx::query_scalar(&sql)
+            .bind(serde_json::json!({ key: value }))
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("user"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn list_users(
+        &self,
+        offset: usize,
+        limit: usize,
+        filter: Option<UserFilter>,
+    ) -> AuthResult<(Vec<User>, usize)> {
+        let mut conn = self.conn("user").await?;
+        let filter = filter.unwrap_or_default();
+
+        let mut count = QueryBuilder::new(r#"SELECT COUNT(*) FROM "user""#);
+        push_user_filter(&mut count, &filter);
+        let total: i64 = count
+            .build_query_scalar()
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(db_error("user"))?;
+
+        let mut page = QueryBuilder::new(select("user"));
+        push_user_filter(&mut page, &filter);
+        page.push(" ORDER BY created_at, id OFFSET ")
+            .push_bind(offset as i64)
+            .push(" LIMIT ")
+            .push_bind(limit as i64);
+        let rows = page
+            .build_query_scalar()
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(db_error("user"))?;
+
+        Ok((decode_all(rows)?, total as usize))
+    }
+
+    async fn count_users(&self) -> AuthResult<usize> {
+        let mut conn = self.conn("user").await?;
+        let sql = r#"SELECT COUNT(*) FROM "user" WHERE deleted_at IS NULL"#;
+        let count: i64 = sqlx::query_scalar(sql)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(db_error("user"))?;
+        Ok(count as usize)
+    }
+
+    async fn update_user(&self, user: &User) -> AuthResult<User> {
+        let mut conn = self.conn("user").await?;
+        let mut tx = conn.begin().await.map_err(db_error("user"))?;
+        let updated = sqlx::query(&format!(
+            r#"UPDATE "user" SET email = $2, email_verified = $3, name = $4, image = $5, {}
+               WHERE id = $1"#,
+            BUMP_UPDATED_AT
+        ))
+        .bind(&user.id)
+        .bind(&user.email)
+        .bind(user.email_verified)
+        .bind(&user.name)
+        .bind(&user.image)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error("user"))?;
+        if updated.rows_affected() == 0 {
+            return Err(AuthError::not_found("user", "id", &user.id));
+        }
+        write_extensions(&mut tx, "user", &user.id, &user.extensions).await?;
+
+        let user = fetch_by_id(&mut tx, "user", &user.id)
+            .await?
+            .ok_or_else(|| AuthError::not_found("user", "id", &user.id))?;
+        tx.commit().await.map_err(db_error("user"))?;
+        Ok(user)
+    }
+
+    async fn delete_user(&self, id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("user").await?;
+        // Sessions and accounts are removed by ON DELETE CASCADE.
+        sqlx::query(r#"DELETE FROM "user" WHERE id = $1"#)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("user"))?;
+        Ok(())
+    }
+
+    async fn soft_delete_user(&self, id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("user").await?;
+        let mut tx = conn.begin().await.map_err(db_error("user"))?;
+        sqlx::query(&format!(
+            r#"UPDATE "user" SET deleted_at = now(), {} WHERE id = $1 AND deleted_at IS NULL"#,
+            BUMP_UPDATED_AT
+        ))
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error("user"))?;
+        sqlx::query("DELETE FROM session WHERE user_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error("session"))?;
+        tx.commit().await.map_err(db_error("user"))?;
+        Ok(())
+    }
+
+    async fn restore_user(&self, id: &str) -> AuthResult<Option<User>> {
+        let mut conn = self.conn("user").await?;
+        sqlx::query_scalar(&format!(
+            r#"UPDATE "user" AS t SET deleted_at = NULL, {}
+               WHERE id = $1 AND deleted_at IS NOT NULL RETURNING to_jsonb(t)"#,
+            BUMP_UPDATED_AT
+        ))
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("user"))?
+        .map(decode)
+        .transpose()
+    }
+
+    async fn purge_deleted_users(&self, deleted_before: DateTime<Utc>) -> AuthResult<usize> {
+        let mut conn = self.conn("user").await?;
+        // Accounts are removed by ON DELETE CASCADE, as in `delete_user`.
+        let purged = sqlx::query(r#"DELETE FROM "user" WHERE deleted_at < $1"#)
+            .bind(deleted_before)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("user"))?;
+        Ok(purged.rows_affected() as usize)
+    }
+
+    // ==================== Session Operations ====================
+
+    async fn create_session(&self, session: &Session) -> AuthResult<Session> {
+        let mut conn = self.conn("session").await?;
+        let mut tx = conn.begin().await.map_err(db_error("session"))?;
+        sqlx::query(
+            "INSERT INTO session (id, user_id, token, expires_at, created_at, updated_at, \
+             authenticated_at, last_used_at, ip_address, user_agent) \
+             VALUES ($1, $2, $3, $4, now(), now(), $5, $6, $7, $8)",
+        )
+        .bind(&session.id)
+        .bind(&session.user_id)
+        .bind(&session.token)
+        .bind(session.expires_at)
+        .bind(session.authenticated_at)
+        .bind(session.last_used_at)
+        .bind(&session.ip_address)
+        .bind(&session.user_agent)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error("session"))?;
+        write_extensions(&mut tx, "session", &session.id, &session.extensions).await?;
+
+        let created = fetch_by_id(&mut tx, "session", &session.id)
+            .await?
+            .ok_or_else(|| AuthError::not_found("session", "id", &session.id))?;
+        tx.commit().await.map_err(db_error("session"))?;
+        Ok(created)
+    }
+
+    async fn get_session_by_id(&self, id: &str) -> AuthResult<Option<Session>> {
+        let mut conn = self.conn("session").await?;
+        fetch_by_id(&mut conn, "session", id).await
+    }
+
+    async fn get_session_by_token(&self, token: &str) -> AuthResult<Option<Session>> {
+        let mut conn = self.conn("session").await?;
+        sqlx::query_scalar(&format!("{} WHERE token = $1", select("session")))
+            .bind(token)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("session"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn get_sessions_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Session>> {
+        let mut conn = self.conn("session").await?;
+        let rows = sqlx::query_scalar(&format!(
+            "{} WHERE user_id = $1 ORDER BY created_at, id",
+            select("session")
+        ))
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("session"))?;
+        decode_all(rows)
+    }
+
+    async fn update_session(&self, session: &Session) -> AuthResult<Session> {
+        let mut conn = self.conn("session").await?;
+        let mut tx = conn.begin().await.map_err(db_error("session"))?;
+        let updated = sqlx::query(&format!(
+            "UPDATE session SET user_id = $2, token = $3, expires_at = $4, \
+             authenticated_at = $5, last_used_at = $6, ip_address = $7, user_agent = $8, \
+             {} WHERE id = $1",
+            BUMP_UPDATED_AT
+        ))
+        .bind(&session.id)
+        .bind(&session.user_id)
+        .bind(&session.token)
+        .bind(session.expires_at)
+        .bind(session.authenticated_at)
+        .bind(session.last_used_at)
+        .bind(&session.ip_address)
+        .bind(&session.user_agent)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error("session"))?;
+        if updated.rows_affected() == 0 {
+            return Err(AuthError::not_found("session", "id", &session.id));
+        }
+        write_extensions(&mut tx, "session", &session.id, &session.extensions).await?;
+
+        let session = fetch_by_id(&mut tx, "session", &session.id)
+            .await?
+            .ok_or_else(|| AuthError::not_found("session", "id", &session.id))?;
+        tx.commit().await.map_err(db_error("session"))?;
+        Ok(session)
+    }
+
+    async fn delete_session(&self, id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("session").await?;
+        sqlx::query("DELETE FROM session WHERE id = $1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("session"))?;
+        Ok(())
+    }
+
+    async fn delete_sessions_by_user_id(&self, user_id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("session").await?;
+        sqlx::query("DELETE FROM session WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("session"))?;
+        Ok(())
+    }
+
+    async fn delete_expired_sessions(&self) -> AuthResult<usize> {
+        let mut conn = self.conn("session").await?;
+        let deleted = sqlx::query("DELETE FROM session WHERE expires_at <= now()")
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("session"))?;
+        Ok(deleted.rows_affected() as usize)
+    }
+
+    // ==================== Account Operations ====================
+
+    async fn create_account(&self, account: &Account) -> AuthResult<Account> {
+        let mut conn = self.conn("account").await?;
+        sqlx::query_scalar(
+            "INSERT INTO account AS t (id, user_id, provider, provider_account_id, \
+             access_token, refresh_token, expires_at, profile, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now(), now()) RETURNING to_jsonb(t)",
+        )
+        .bind(&account.id)
+        .bind(&account.user_id)
+        .bind(&account.provider)
+        .bind(&account.provider_account_id)
+        .bind(&account.access_token)
+        .bind(&account.refresh_token)
+        .bind(account.expires_at)
+        .bind(&account.profile)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("account"))
+        .and_then(decode)
+    }
+
+    async fn get_account(
+        &self,
+        provider: &str,
+        provider_account_id: &str,
+    ) -> AuthResult<Option<Account>> {
+        let mut conn = self.conn("account").await?;
+        sqlx::query_scalar(&format!(
+            "{} WHERE provider = $1 AND provider_account_id = $2",
+            select("account")
+        ))
+        .bind(provider)
+        .bind(provider_account_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("account"))?
+        .map(decode)
+        .transpose()
+    }
+
+    async fn get_account_by_id(&self, id: &str) -> AuthResult<Option<Account>> {
+        let mut conn = self.conn("account").await?;
+        fetch_by_id(&mut conn, "account", id).await
+    }
+
+    async fn get_accounts_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Account>> {
+        let mut conn = self.conn("account").await?;
+        let rows = sqlx::query_scalar(&format!(
+            "{} WHERE user_id = $1 ORDER BY created_at, id",
+            select("account")
+        ))
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("account"))?;
+        decode_all(rows)
+    }
+
+    async fn update_account(&self, account: &Account) -> AuthResult<Account> {
+        let mut conn = self.conn("account").await?;
+        sqlx::query_scalar(&format!(
+            "UPDATE account AS t SET user_id = $2, provider = $3, provider_account_id = $4, \
+             access_token = $5, refresh_token = $6, expires_at = $7, profile = $8, {} \
+             WHERE id = $1 RETURNING to_jsonb(t)",
+            BUMP_UPDATED_AT
+        ))
+        .bind(&account.id)
+        .bind(&account.user_id)
+        .bind(&account.provider)
+        .bind(&account.provider_account_id)
+        .bind(&account.access_token)
+        .bind(&account.refresh_token)
+        .bind(account.expires_at)
+        .bind(&account.profile)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("account"))?
+        .map(decode)
+        .transpose()?
+        .ok_or_else(|| AuthError::not_found("account", "id", &account.id))
+    }
+
+    async fn delete_account(&self, id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("account").await?;
+        sqlx::query("DELETE FROM account WHERE id = $1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("account"))?;
+        Ok(())
+    }
+
+    // ==================== Schema Operations ====================
+
+    /// Creates missing tables, columns, and indexes.
+    ///
+    /// Tables are created after the tables they reference. Existing columns
+    /// are never altered or dropped. All statements run in one transaction.
+    async fn migrate(
+        &self,
+        models: &[ModelDefinition],
+        dry_run: bool,
+    ) -> AuthResult<Vec<MigrationOp>> {
+        let diff = SchemaDiff::additions(&self.introspect_schema().await?, models);
+        let migration =
+            MigrationRunner::new(SqlDialect::Postgres).generate_migration("better_auth", &diff);
+        if dry_run || migration.operations.is_empty() {
+            return Ok(migration.operations);
+        }
+
+        let mut conn = self.conn("schema").await?;
+        let mut tx = conn.begin().await.map_err(db_error("schema"))?;
+        for sql in migration.to_sql() {
+            sqlx::query(&sql)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error("schema"))?;
+        }
+        tx.commit().await.map_err(db_error("schema"))?;
+        Ok(migration.operations)
+    }
+
+    async fn table_exists(&self, table_name: &str) -> AuthResult<bool> {
+        let mut conn = self.conn("schema").await?;
+        sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM information_schema.tables \
+             WHERE table_schema = current_schema() AND table_name = $1)",
+        )
+        .bind(table_name)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("schema"))
+    }
+
+    /// Reads the tables of the current schema with their columns and
+    /// indexes.
+    ///
+    /// Column types, nullability, and defaults are read back; primary keys
+    /// and foreign keys are not.
+    async fn introspect_schema(&self) -> AuthResult<SchemaDefinition> {
+        let mut conn = self.conn("schema").await?;
+        let columns: Vec<ColumnRow> = sqlx::query_as(
+            "SELECT table_name::text, column_name::text, data_type::text, \
+             is_nullable = 'YES', column_default::text, character_maximum_length::int, \
+             numeric_precision::int, numeric_scale::int \
+             FROM information_schema.columns c \
+             JOIN information_schema.tables t USING (table_schema, table_name) \
+             WHERE table_schema = current_schema() AND t.table_type = 'BASE TABLE' \
+             ORDER BY table_name, ordinal_position",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("schema"))?;
+        let indexes: Vec<(String, String, bool, Vec<String>)> = sqlx::query_as(
+            "SELECT t.relname::text, i.relname::text, ix.indisunique, \
+             array_agg(a.attname::text ORDER BY k.ord) \
+             FROM pg_index ix \
+             JOIN pg_class i ON i.oid = ix.indexrelid \
+             JOIN pg_class t ON t.oid = ix.indrelid \
+             CROSS JOIN LATERAL unnest(ix.indkey) WITH ORDINALITY AS k(attnum, ord) \
+             JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = k.attnum \
+             WHERE t.relnamespace = current_schema()::regnamespace AND NOT ix.indisprimary \
+             GROUP BY 1, 2, 3 ORDER BY 1, 2",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("schema"))?;
+
+        let mut models: Vec<ModelDefinition> = Vec::new();
+        for (table, name, data_type, nullable, default, length, precision, scale) in columns {
+            if models.last().is_none_or(|model| model.name != table) {
+                models.push(ModelDefinition::new(table));
+            }
+            let field_type = column_type(&data_type, length, precision, scale);
+            let mut field = if nullable {
+                Field::optional(name, field_type)
+            } else {
+                Field::new(name, field_type)
+            };
+            field.default = default;
+            models.last_mut().unwrap().fields.push(field);
+        }
+        for (table, name, unique, columns) in indexes {
+            if let Some(model) = models.iter_mut().find(|model| model.name == table) {
+                model.indexes.push(IndexDefinition {
+                    name,
+                    columns,
+                    unique,
+                });
+            }
+        }
+        Ok(SchemaDefinition { models })
+    }
+
+    // ==================== Generic Operations ====================
+
+    async fn execute_raw(&self, query: &str) -> AuthResult<()> {
+        let mut conn = self.conn("query").await?;
+        // A plain `&str` runs unprepared, so it may hold several statements.
+        conn.execute(query).await.map_err(db_error("query"))?;
+        Ok(())
+    }
+
+    // ==================== Transactions ====================
+
+    /// Starts a transaction on a connection from the pool.
+    ///
+    /// An adapter that is already in a transaction returns `None`, so nested
+    /// [`run_in_transaction`](better_auth_core::run_in_transaction) calls
+    /// join the enclosing transaction.
+    async fn begin(&self) -> AuthResult<Option<Arc<dyn StorageTransaction>>> {
+        if self.tx.is_some() {
+            return Ok(None);
+        }
+        let tx = self.pool.begin().await.map_err(db_error("transaction"))?;
+        Ok(Some(Arc::new(Self {
+            pool: self.pool.clone(),
+            tx: Some(Arc::new(Mutex::new(Some(tx)))),
+        })))
+    }
+}
+
+#[async_trait]
+impl StorageTransaction for PostgresAdapter {
+    async fn commit(&self) -> AuthResult<()> {
+        self.take_tx()
+            .await?
+            .commit()
+            .await
+            .map_err(db_error("transaction"))
+    }
+
+    async fn rollback(&self) -> AuthResult<()> {
+        self.take_tx()
+            .await?
+            .rollback()
+            .await
+            .map_err(db_error("transaction"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_moves_extension_columns_into_extensions() {
+        let user: User = decode(json!({
+            "id": "u1",
+            "email": "a@example.com",
+            "email_verified": false,
+            "name": null,
+            "image": null,
+            "created_at": "2024-01-01T00:00:00.123456+00:00",
+            "updated_at": "2024-01-01T00:00:00.123456+00:00",
+            "username": "alice",
+            "phone_number": null,
+        }))
+        .unwrap();
+
+        assert_eq!(user.name, None);
+        assert_eq!(user.extensions.len(), 1);
+        assert_eq!(user.extensions["username"], json!("alice"));
+    }
+
+    #[test]
+    fn test_column_types_map_back_to_field_types() {
+        assert_eq!(
+            column_type("character varying", Some(255), None, None),
+            FieldType::String(255)
+        );
+        assert_eq!(
+            column_type("timestamp with time zone", None, None, None),
+            FieldType::Timestamp
+        );
+        assert_eq!(
+            column_type("numeric", None, Some(10), Some(2)),
+            FieldType::Decimal(10, 2)
+        );
+        assert_eq!(column_type("tsvector", None, None, None), FieldType::Text);
+    }
+}
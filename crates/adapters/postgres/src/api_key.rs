@@ -0,0 +1,96 @@
+//! [`ApiKeyStore`] implementation over the API key plugin's table.
+
+use crate::adapter::{PostgresAdapter, decode, decode_all, select};
+use crate::error::db_error;
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_plugin_api_key::{ApiKey, ApiKeyStore, UsageDenied};
+use chrono::Utc;
+use sqlx::Connection;
+
+#[async_trait]
+impl ApiKeyStore for PostgresAdapter {
+    async fn create_api_key(&self, key: &ApiKey) -> AuthResult<ApiKey> {
+        let mut conn = self.conn("api_key").await?;
+        // Every field maps to the column of the same name.
+        let row = serde_json::to_value(key)
+            .map_err(|e| AuthError::database(format!("Failed to encode api_key: {}", e)))?;
+        sqlx::query_scalar(
+            "INSERT INTO api_key AS t SELECT * FROM jsonb_populate_record(NULL::api_key, $1) \
+             RETURNING to_jsonb(t)",
+        )
+        .bind(row)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("api_key"))
+        .and_then(decode)
+    }
+
+    async fn get_api_keys_by_prefix(&self, key_prefix: &str) -> AuthResult<Vec<ApiKey>> {
+        let mut conn = self.conn("api_key").await?;
+        let rows = sqlx::query_scalar(&format!("{} WHERE key_prefix = $1", select("api_key")))
+            .bind(key_prefix)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(db_error("api_key"))?;
+        decode_all(rows)
+    }
+
+    async fn get_api_keys_by_user_id(&self, user_id: &str) -> AuthResult<Vec<ApiKey>> {
+        let mut conn = self.conn("api_key").await?;
+        let rows = sqlx::query_scalar(&format!(
+            "{} WHERE user_id = $1 ORDER BY created_at",
+            select("api_key")
+        ))
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("api_key"))?;
+        decode_all(rows)
+    }
+
+    async fn delete_api_keys_by_user_id(&self, user_id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("api_key").await?;
+        sqlx::query("DELETE FROM api_key WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("api_key"))?;
+        Ok(())
+    }
+
+    async fn record_api_key_use(&self, id: &str) -> AuthResult<Result<ApiKey, UsageDenied>> {
+        let mut conn = self.conn("api_key").await?;
+        let mut tx = conn.begin().await.map_err(db_error("api_key"))?;
+        // The row stays locked until commit, so concurrent uses of the key
+        // wait for this one and see its result.
+        let mut key: ApiKey =
+            sqlx::query_scalar(&format!("{} WHERE id = $1 FOR UPDATE", select("api_key")))
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(db_error("api_key"))?
+                .map(decode)
+                .transpose()?
+                .ok_or_else(|| AuthError::not_found("api_key", "id", id))?;
+        if let Err(denied) = key.record_use(Utc::now()) {
+            return Ok(Err(denied));
+        }
+
+        sqlx::query(
+            "UPDATE api_key SET request_count = $2, remaining = $3, last_request = $4, \
+             last_refill_at = $5, updated_at = $6 WHERE id = $1",
+        )
+        .bind(&key.id)
+        .bind(key.request_count)
+        .bind(key.remaining)
+        .bind(key.last_request)
+        .bind(key.last_refill_at)
+        .bind(key.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error("api_key"))?;
+        tx.commit().await.map_err(db_error("api_key"))?;
+        Ok(Ok(key))
+    }
+}
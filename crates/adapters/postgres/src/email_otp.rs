@@ -0,0 +1,74 @@
+//! [`EmailOtpStore`] implementation over the email OTP plugin's table.
+
+use crate::adapter::{PostgresAdapter, decode, select};
+use crate::error::db_error;
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_plugin_email_otp::{EmailOtp, EmailOtpStore};
+
+#[async_trait]
+impl EmailOtpStore for PostgresAdapter {
+    async fn create_email_otp(&self, otp: &EmailOtp) -> AuthResult<EmailOtp> {
+        let mut conn = self.conn("email_otp").await?;
+        // Replaces any earlier code for the same address and purpose.
+        sqlx::query_scalar(
+            "WITH replaced AS (DELETE FROM email_otp WHERE email = $2 AND otp_type = $4) \
+             INSERT INTO email_otp AS t (id, email, otp, otp_type, expires_at, attempts, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING to_jsonb(t)",
+        )
+        .bind(&otp.id)
+        .bind(&otp.email)
+        .bind(&otp.otp)
+        .bind(&otp.otp_type)
+        .bind(otp.expires_at)
+        .bind(otp.attempts)
+        .bind(otp.created_at)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("email_otp"))
+        .and_then(decode)
+    }
+
+    async fn get_email_otp(&self, email: &str, otp_type: &str) -> AuthResult<Option<EmailOtp>> {
+        let mut conn = self.conn("email_otp").await?;
+        sqlx::query_scalar(&format!(
+            "{} WHERE email = $1 AND otp_type = $2 ORDER BY created_at DESC LIMIT 1",
+            select("email_otp")
+        ))
+        .bind(email)
+        .bind(otp_type)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("email_otp"))?
+        .map(decode)
+        .transpose()
+    }
+
+    async fn update_email_otp(&self, otp: &EmailOtp) -> AuthResult<EmailOtp> {
+        let mut conn = self.conn("email_otp").await?;
+        sqlx::query_scalar(
+            "UPDATE email_otp AS t SET otp = $2, expires_at = $3, attempts = $4 \
+             WHERE id = $1 RETURNING to_jsonb(t)",
+        )
+        .bind(&otp.id)
+        .bind(&otp.otp)
+        .bind(otp.expires_at)
+        .bind(otp.attempts)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("email_otp"))?
+        .map(decode)
+        .transpose()?
+        .ok_or_else(|| AuthError::not_found("email_otp", "id", &otp.id))
+    }
+
+    async fn delete_email_otp(&self, id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("email_otp").await?;
+        sqlx::query("DELETE FROM email_otp WHERE id = $1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("email_otp"))?;
+        Ok(())
+    }
+}
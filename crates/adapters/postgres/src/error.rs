@@ -0,0 +1,65 @@
+//! Translation of Postgres errors into [`AuthError`].
+
+use better_auth_core::error::AuthError;
+use sqlx::postgres::PgDatabaseError;
+
+/// SQLSTATE for `unique_violation`.
+const UNIQUE_VIOLATION: &str = "23505";
+
+/// Returns a mapper from `sqlx` errors on `entity` to [`AuthError`].
+///
+/// Unique violations become [`AuthError::DuplicateEntry`] naming the
+/// offending column and value; everything else is a database error.
+pub(crate) fn db_error(entity: &str) -> impl Fn(sqlx::Error) -> AuthError + '_ {
+    move |err| {
+        if let Some(db_err) = err.as_database_error()
+            && db_err.code().as_deref() == Some(UNIQUE_VIOLATION)
+        {
+            let (field, value) = db_err
+                .try_downcast_ref::<PgDatabaseError>()
+                .and_then(|e| e.detail())
+                .and_then(parse_key_detail)
+                .unwrap_or_else(|| {
+                    let constraint = db_err.constraint().unwrap_or("unknown");
+                    (constraint.to_string(), String::new())
+                });
+            return AuthError::duplicate(entity, field, value);
+        }
+        AuthError::database(err.to_string())
+    }
+}
+
+/// Parses the detail of a unique violation,
+/// `Key (email)=(a@example.com) already exists.`, into column and value.
+fn parse_key_detail(detail: &str) -> Option<(String, String)> {
+    let (field, value) = detail.strip_prefix("Key (")?.split_once(")=(")?;
+    let value = value.strip_suffix(") already exists.")?;
+    Some((field.to_string(), value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_detail() {
+        assert_eq!(
+            parse_key_detail("Key (email)=(a@example.com) already exists."),
+            Some(("email".to_string(), "a@example.com".to_string()))
+        );
+        assert_eq!(
+            parse_key_detail("Key (provider, provider_account_id)=(github, 42) already exists."),
+            Some((
+                "provider, provider_account_id".to_string(),
+                "github, 42".to_string()
+            ))
+        );
+        assert_eq!(parse_key_detail("Failing row contains (1)."), None);
+    }
+
+    #[test]
+    fn test_non_database_errors() {
+        let err = db_error("user")(sqlx::Error::RowNotFound);
+        assert!(matches!(err, AuthError::DatabaseError { .. }));
+    }
+}
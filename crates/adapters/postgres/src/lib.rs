@@ -0,0 +1,54 @@
+//! # Better Auth Postgres Adapter
+//!
+//! PostgreSQL storage for Better Auth, built on a `sqlx` connection pool.
+//! [`PostgresAdapter`] implements [`StorageAdapter`] and the plugin stores
+//! [`AccessStorageExt`], [`ApiKeyStore`], [`EmailOtpStore`],
+//! [`MagicLinkTokenStore`], [`PasskeyStore`], [`PasswordHistoryStore`],
+//! [`TrustedDeviceStore`], and [`TwoFactorStore`].
+//!
+//! Tables come from the same [`ModelDefinition`]s every adapter receives:
+//! [`migrate`](StorageAdapter::migrate) reads the existing tables back and
+//! creates only the missing tables, columns, and indexes, using the Postgres
+//! column types from `FieldType::sql_type`. A dry run returns the planned
+//! statements without running them.
+//! Unique violations are reported as [`AuthError::DuplicateEntry`].
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use better_auth_adapter_postgres::{PostgresAdapter, PostgresConfig};
+//!
+//! let config = PostgresConfig::new().max_connections(20);
+//! let adapter = PostgresAdapter::connect_with("postgres://localhost/app", config).await?;
+//! for op in adapter.migrate(&schema.models, true).await? {
+//!     println!("{:?}", op);
+//! }
+//! adapter.migrate(&schema.models, false).await?;
+//! ```
+//!
+//! [`StorageAdapter`]: better_auth_core::traits::StorageAdapter
+//! [`AccessStorageExt`]: better_auth_plugin_access::AccessStorageExt
+//! [`ApiKeyStore`]: better_auth_plugin_api_key::ApiKeyStore
+//! [`EmailOtpStore`]: better_auth_plugin_email_otp::EmailOtpStore
+//! [`MagicLinkTokenStore`]: better_auth_plugin_magic_link::MagicLinkTokenStore
+//! [`PasskeyStore`]: better_auth_plugin_passkey::PasskeyStore
+//! [`PasswordHistoryStore`]: better_auth_plugin_password::PasswordHistoryStore
+//! [`TrustedDeviceStore`]: better_auth_plugin_two_factor::TrustedDeviceStore
+//! [`TwoFactorStore`]: better_auth_plugin_two_factor::TwoFactorStore
+//! [`ModelDefinition`]: better_auth_core::schema::ModelDefinition
+//! [`AuthError::DuplicateEntry`]: better_auth_core::error::AuthError::DuplicateEntry
+
+mod access;
+mod adapter;
+mod api_key;
+mod config;
+mod email_otp;
+mod error;
+mod magic_link;
+mod passkey;
+mod password_history;
+mod trusted_device;
+mod two_factor;
+
+pub use adapter::PostgresAdapter;
+pub use config::PostgresConfig;
@@ -0,0 +1,51 @@
+//! [`MagicLinkTokenStore`] implementation over the magic link plugin's table.
+
+use crate::adapter::{PostgresAdapter, decode, select};
+use crate::error::db_error;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use better_auth_plugin_magic_link::{MagicLinkToken, MagicLinkTokenStore};
+
+#[async_trait]
+impl MagicLinkTokenStore for PostgresAdapter {
+    async fn create_magic_link_token(&self, token: &MagicLinkToken) -> AuthResult<MagicLinkToken> {
+        let mut conn = self.conn("magic_link_token").await?;
+        sqlx::query_scalar(
+            "INSERT INTO magic_link_token AS t (id, email, token, expires_at, used, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING to_jsonb(t)",
+        )
+        .bind(&token.id)
+        .bind(&token.email)
+        .bind(&token.token)
+        .bind(token.expires_at)
+        .bind(token.used)
+        .bind(token.created_at)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("magic_link_token"))
+        .and_then(decode)
+    }
+
+    async fn get_magic_link_token(&self, token: &str) -> AuthResult<Option<MagicLinkToken>> {
+        let mut conn = self.conn("magic_link_token").await?;
+        sqlx::query_scalar(&format!("{} WHERE token = $1", select("magic_link_token")))
+            .bind(token)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("magic_link_token"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn use_magic_link_token(&self, id: &str) -> AuthResult<bool> {
+        let mut conn = self.conn("magic_link_token").await?;
+        // Only one of two concurrent redemptions matches `NOT used`.
+        let result =
+            sqlx::query("UPDATE magic_link_token SET used = true WHERE id = $1 AND NOT used")
+                .bind(id)
+                .execute(&mut *conn)
+                .await
+                .map_err(db_error("magic_link_token"))?;
+        Ok(result.rows_affected() == 1)
+    }
+}
@@ -0,0 +1,91 @@
+//! [`PasskeyStore`] implementation over the passkey plugin's table.
+
+use crate::adapter::{PostgresAdapter, decode, decode_all, select};
+use crate::error::db_error;
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_plugin_passkey::{Passkey, PasskeyStore};
+
+#[async_trait]
+impl PasskeyStore for PostgresAdapter {
+    async fn create_passkey(&self, passkey: &Passkey) -> AuthResult<Passkey> {
+        let mut conn = self.conn("passkey").await?;
+        sqlx::query_scalar(
+            "INSERT INTO passkey AS t (id, name, public_key, user_id, credential_id, counter, \
+             device_type, backed_up, transports, created_at, aaguid) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING to_jsonb(t)",
+        )
+        .bind(&passkey.id)
+        .bind(&passkey.name)
+        .bind(&passkey.public_key)
+        .bind(&passkey.user_id)
+        .bind(&passkey.credential_id)
+        .bind(passkey.counter)
+        .bind(&passkey.device_type)
+        .bind(passkey.backed_up)
+        .bind(&passkey.transports)
+        .bind(passkey.created_at)
+        .bind(&passkey.aaguid)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("passkey"))
+        .and_then(decode)
+    }
+
+    async fn get_user_passkeys(&self, user_id: &str) -> AuthResult<Vec<Passkey>> {
+        let mut conn = self.conn("passkey").await?;
+        let rows = sqlx::query_scalar(&format!(
+            "{} WHERE user_id = $1 ORDER BY created_at",
+            select("passkey")
+        ))
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("passkey"))?;
+        decode_all(rows)
+    }
+
+    async fn get_passkey_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> AuthResult<Option<Passkey>> {
+        let mut conn = self.conn("passkey").await?;
+        sqlx::query_scalar(&format!("{} WHERE credential_id = $1", select("passkey")))
+            .bind(credential_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("passkey"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn update_passkey(&self, passkey: &Passkey) -> AuthResult<Passkey> {
+        let mut conn = self.conn("passkey").await?;
+        sqlx::query_scalar(
+            "UPDATE passkey AS t SET name = $2, counter = $3, device_type = $4, \
+             backed_up = $5, transports = $6 WHERE id = $1 RETURNING to_jsonb(t)",
+        )
+        .bind(&passkey.id)
+        .bind(&passkey.name)
+        .bind(passkey.counter)
+        .bind(&passkey.device_type)
+        .bind(passkey.backed_up)
+        .bind(&passkey.transports)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("passkey"))?
+        .map(decode)
+        .transpose()?
+        .ok_or_else(|| AuthError::not_found("passkey", "id", &passkey.id))
+    }
+
+    async fn delete_user_passkeys(&self, user_id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("passkey").await?;
+        sqlx::query("DELETE FROM passkey WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("passkey"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! [`PasswordHistoryStore`] implementation over the password plugin's table.
+
+use crate::adapter::{PostgresAdapter, decode_all, select};
+use crate::error::db_error;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use better_auth_plugin_password::{PasswordHistoryEntry, PasswordHistoryStore};
+
+#[async_trait]
+impl PasswordHistoryStore for PostgresAdapter {
+    async fn add_password_history(&self, entry: &PasswordHistoryEntry) -> AuthResult<()> {
+        let mut conn = self.conn("password_history").await?;
+        sqlx::query(
+            "INSERT INTO password_history (id, user_id, password_hash, created_at) \
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(&entry.id)
+        .bind(&entry.user_id)
+        .bind(&entry.password_hash)
+        .bind(entry.created_at)
+        .execute(&mut *conn)
+        .await
+        .map_err(db_error("password_history"))?;
+        Ok(())
+    }
+
+    async fn get_password_history(
+        &self,
+        user_id: &str,
+        limit: usize,
+    ) -> AuthResult<Vec<PasswordHistoryEntry>> {
+        let mut conn = self.conn("password_history").await?;
+        sqlx::query_scalar(&format!(
+            "{} WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2",
+            select("password_history")
+        ))
+        .bind(user_id)
+        .bind(limit as i64)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("password_history"))
+        .and_then(decode_all)
+    }
+
+    async fn trim_password_history(&self, user_id: &str, keep: usize) -> AuthResult<()> {
+        let mut conn = self.conn("password_history").await?;
+        sqlx::query(
+            "DELETE FROM password_history WHERE user_id = $1 AND id NOT IN \
+             (SELECT id FROM password_history WHERE user_id = $1 \
+              ORDER BY created_at DESC LIMIT $2)",
+        )
+        .bind(user_id)
+        .bind(keep as i64)
+        .execute(&mut *conn)
+        .await
+        .map_err(db_error("password_history"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+//! [`TrustedDeviceStore`] implementation over the two-factor plugin's
+//! `trusted_device` table.
+
+use crate::adapter::{PostgresAdapter, decode, decode_all, select};
+use crate::error::db_error;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use better_auth_plugin_two_factor::{TrustedDevice, TrustedDeviceStore};
+
+#[async_trait]
+impl TrustedDeviceStore for PostgresAdapter {
+    async fn create_trusted_device(&self, device: &TrustedDevice) -> AuthResult<TrustedDevice> {
+        let mut conn = self.conn("trusted_device").await?;
+        sqlx::query_scalar(
+            "INSERT INTO trusted_device AS t \
+             (id, user_id, device_hash, user_agent, ip_address, expires_at, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING to_jsonb(t)",
+        )
+        .bind(&device.id)
+        .bind(&device.user_id)
+        .bind(&device.device_hash)
+        .bind(&device.user_agent)
+        .bind(&device.ip_address)
+        .bind(device.expires_at)
+        .bind(device.created_at)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("trusted_device"))
+        .and_then(decode)
+    }
+
+    async fn get_trusted_device(
+        &self,
+        user_id: &str,
+        device_hash: &str,
+    ) -> AuthResult<Option<TrustedDevice>> {
+        let mut conn = self.conn("trusted_device").await?;
+        sqlx::query_scalar(&format!(
+            "{} WHERE user_id = $1 AND device_hash = $2",
+            select("trusted_device")
+        ))
+        .bind(user_id)
+        .bind(device_hash)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(db_error("trusted_device"))?
+        .map(decode)
+        .transpose()
+    }
+
+    async fn delete_user_trusted_devices(&self, user_id: &str) -> AuthResult<Vec<TrustedDevice>> {
+        let mut conn = self.conn("trusted_device").await?;
+        let rows = sqlx::query_scalar(
+            "DELETE FROM trusted_device AS t WHERE user_id = $1 RETURNING to_jsonb(t)",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(db_error("trusted_device"))?;
+        decode_all(rows)
+    }
+}
@@ -0,0 +1,89 @@
+//! [`TwoFactorStore`] implementation over the two-factor plugin's table.
+
+use crate::adapter::{PostgresAdapter, decode, select};
+use crate::error::db_error;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use better_auth_plugin_two_factor::{TwoFactorData, TwoFactorStore};
+
+#[async_trait]
+impl TwoFactorStore for PostgresAdapter {
+    async fn create_two_factor(&self, data: &TwoFactorData) -> AuthResult<TwoFactorData> {
+        let mut conn = self.conn("two_factor").await?;
+        sqlx::query_scalar(
+            "INSERT INTO two_factor AS t \
+             (id, user_id, secret, backup_codes, last_used_step, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (user_id) DO UPDATE SET id = EXCLUDED.id, secret = EXCLUDED.secret, \
+             backup_codes = EXCLUDED.backup_codes, last_used_step = EXCLUDED.last_used_step, \
+             created_at = EXCLUDED.created_at \
+             RETURNING to_jsonb(t)",
+        )
+        .bind(&data.id)
+        .bind(&data.user_id)
+        .bind(&data.secret)
+        .bind(&data.backup_codes)
+        .bind(data.last_used_step.map(|step| step as i64))
+        .bind(data.created_at)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(db_error("two_factor"))
+        .and_then(decode)
+    }
+
+    async fn get_two_factor(&self, user_id: &str) -> AuthResult<Option<TwoFactorData>> {
+        let mut conn = self.conn("two_factor").await?;
+        sqlx::query_scalar(&format!("{} WHERE user_id = $1", select("two_factor")))
+            .bind(user_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_error("two_factor"))?
+            .map(decode)
+            .transpose()
+    }
+
+    async fn advance_totp_step(&self, user_id: &str, step: u64) -> AuthResult<bool> {
+        let mut conn = self.conn("two_factor").await?;
+        // Only one of two concurrent verifications of a step matches.
+        let result = sqlx::query(
+            "UPDATE two_factor SET last_used_step = $2 \
+             WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)",
+        )
+        .bind(user_id)
+        .bind(step as i64)
+        .execute(&mut *conn)
+        .await
+        .map_err(db_error("two_factor"))?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn swap_backup_codes(
+        &self,
+        user_id: &str,
+        current: &str,
+        codes: &str,
+    ) -> AuthResult<bool> {
+        let mut conn = self.conn("two_factor").await?;
+        // Fails if another request changed the codes since `current` was read.
+        let result = sqlx::query(
+            "UPDATE two_factor SET backup_codes = $3 WHERE user_id = $1 AND backup_codes = $2",
+        )
+        .bind(user_id)
+        .bind(current)
+        .bind(codes)
+        .execute(&mut *conn)
+        .await
+        .map_err(db_error("two_factor"))?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn delete_two_factor(&self, user_id: &str) -> AuthResult<()> {
+        let mut conn = self.conn("two_factor").await?;
+        sqlx::query("DELETE FROM two_factor WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_error("two_factor"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,858 @@
+//! Tests against a live database.
+//!
+//! Set `DATABASE_URL` (e.g. `postgres://postgres@localhost/postgres`) to run
+//! them; without it every test returns early. Each test works in its own
+//! schema.
+
+use better_auth_adapter_postgres::{PostgresAdapter, PostgresConfig};
+use better_auth_core::error::AuthError;
+use better_auth_core::schema::{Field, FieldType, IndexDefinition, MigrationOp, core_schema};
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::{Account, Session, User, UserFilter};
+use better_auth_core::{AuthPlugin, SchemaBuilder, SchemaProvider, run_in_transaction};
+use better_auth_plugin_access::{
+    AccessConfig, AccessPlugin, AccessStorageExt, DbPermission, DbRole,
+};
+use better_auth_plugin_api_key::{ApiKey, ApiKeySchema, ApiKeyStore, UsageDenied};
+use better_auth_plugin_email_otp::{EmailOtp, EmailOtpSchema, EmailOtpStore};
+use better_auth_plugin_magic_link::{MagicLinkSchema, MagicLinkToken, MagicLinkTokenStore};
+use better_auth_plugin_passkey::{Passkey, PasskeySchema, PasskeyStore};
+use better_auth_plugin_password::{PasswordHistoryEntry, PasswordHistoryStore};
+use better_auth_plugin_two_factor::{
+    TrustedDevice, TrustedDeviceStore, TwoFactorData, TwoFactorSchema, TwoFactorStore,
+};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Connects to a fresh schema and migrates the core models, plus a
+/// `username` extension on `user`.
+async fn adapter() -> Option<PostgresAdapter> {
+    let url = std::env::var("DATABASE_URL").ok()?;
+    let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+    PostgresAdapter::connect(&url)
+        .await
+        .unwrap()
+        .execute_raw(&format!("CREATE SCHEMA {}", schema))
+        .await
+        .unwrap();
+
+    let options = PgConnectOptions::from_str(&url)
+        .unwrap()
+        .options([("search_path", schema.as_str())]);
+    let pool = PgPoolOptions::new()
+        .max_connections(4)
+        .connect_with(options)
+        .await
+        .unwrap();
+    let adapter = PostgresAdapter::from_pool(pool);
+
+    let mut models = core_schema();
+    let user = models.iter_mut().find(|m| m.name == "user").unwrap();
+    user.fields
+        .push(Field::optional("username", FieldType::String(50)).unique());
+    user.indexes.push(IndexDefinition::unique(
+        "idx_user_username",
+        vec!["username".to_string()],
+    ));
+    adapter.migrate(&models, false).await.unwrap();
+    Some(adapter)
+}
+
+fn user(email: &str) -> User {
+    User::new(uuid::Uuid::new_v4().to_string(), email.to_string())
+}
+
+#[tokio::test]
+async fn test_connect_with_config() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        return;
+    };
+    let config = PostgresConfig::new()
+        .max_connections(2)
+        .acquire_timeout(std::time::Duration::from_secs(5));
+    let adapter = PostgresAdapter::connect_with(&url, config).await.unwrap();
+    assert_eq!(adapter.pool().options().get_max_connections(), 2);
+}
+
+#[tokio::test]
+async fn test_user_roundtrip_with_extensions() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+
+    let mut user = user("alice@example.com");
+    user.name = Some("Alice".to_string());
+    user.set_extension("username", "alice");
+    let created = adapter.create_user(&user).await.unwrap();
+    assert_eq!(created.extensions["username"], json!("alice"));
+    assert!(created.created_at <= Utc::now());
+
+    let found = adapter
+        .get_user_by_extension("username", &json!("alice"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, user.id);
+    assert_eq!(found.name.as_deref(), Some("Alice"));
+
+    let mut renamed = found.clone();
+    renamed.email_verified = true;
+    renamed
+        .extensions
+        .insert("username".to_string(), json!("alice2"));
+    let updated = adapter.update_user(&renamed).await.unwrap();
+    assert!(updated.email_verified);
+    assert_eq!(updated.created_at, found.created_at);
+    assert!(updated.updated_at > found.updated_at);
+    assert_eq!(updated.extensions["username"], json!("alice2"));
+
+    let by_email = adapter
+        .get_user_by_email("alice@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(by_email.extensions["username"], json!("alice2"));
+}
+
+#[tokio::test]
+async fn test_update_user_keeps_extensions_it_was_not_given() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+
+    let mut user = user("bob@example.com");
+    user.set_extension("username", "bob");
+    adapter.create_user(&user).await.unwrap();
+
+    // A copy without the extension fields only changes the core fields.
+    let mut renamed = User::new(user.id.clone(), user.email.clone());
+    renamed.name = Some("Bob".to_string());
+    let updated = adapter.update_user(&renamed).await.unwrap();
+    assert_eq!(updated.name.as_deref(), Some("Bob"));
+    assert_eq!(updated.extensions["username"], json!("bob"));
+
+    let mut cleared = updated.clone();
+    cleared.remove_extension("username");
+    let updated = adapter.update_user(&cleared).await.unwrap();
+    assert!(!updated.extensions.contains_key("username"));
+}
+
+#[tokio::test]
+async fn test_migrate_only_applies_changes() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+
+    let mut models = core_schema();
+    let introspected = adapter.introspect_schema().await.unwrap();
+    let user = introspected.get_model("user").unwrap();
+    assert_eq!(
+        user.get_field("username").unwrap().field_type,
+        FieldType::String(50)
+    );
+    assert!(!user.get_field("name").unwrap().required);
+    let email_index = user.indexes.iter().find(|i| i.name == "idx_user_email");
+    assert!(email_index.unwrap().unique);
+
+    // Already migrated: nothing to do.
+    assert!(adapter.migrate(&models, true).await.unwrap().is_empty());
+    assert!(adapter.migrate(&models, false).await.unwrap().is_empty());
+
+    models[0]
+        .fields
+        .push(Field::optional("nickname", FieldType::String(50)));
+    models.push(
+        better_auth_core::schema::ModelDefinition::new("audit").field(Field::primary_key("id")),
+    );
+    let planned = adapter.migrate(&models, true).await.unwrap();
+    assert!(matches!(
+        &planned[..],
+        [MigrationOp::AddColumn { sql }, MigrationOp::CreateTable { .. }]
+            if sql.contains(r#""nickname""#)
+    ));
+    // A dry run changes nothing.
+    assert!(!adapter.table_exists("audit").await.unwrap());
+
+    assert_eq!(adapter.migrate(&models, false).await.unwrap().len(), 2);
+    assert!(adapter.table_exists("audit").await.unwrap());
+    assert!(adapter.migrate(&models, true).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_duplicates_are_reported() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+
+    adapter.create_user(&user("dup@example.com")).await.unwrap();
+    let err = adapter
+        .create_user(&user("dup@example.com"))
+        .await
+        .unwrap_err();
+    match err {
+        AuthError::DuplicateEntry {
+            entity,
+            field,
+            value,
+        } => {
+            assert_eq!(entity, "user");
+            assert_eq!(field, "email");
+            assert_eq!(value, "dup@example.com");
+        }
+        other => panic!("expected DuplicateEntry, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_failed_extension_write_rolls_back_user() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+
+    let mut user = user("bob@example.com");
+    user.set_extension("no_such_column", true);
+    assert!(adapter.create_user(&user).await.is_err());
+    assert!(adapter.get_user_by_id(&user.id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_transactions() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    let storage: Arc<dyn StorageAdapter> = Arc::new(adapter.clone());
+
+    let kept = user("kept@example.com");
+    run_in_transaction(&storage, |tx| async move {
+        tx.create_user(&kept).await?;
+        tx.create_session(&Session::new(kept.id.clone())).await
+    })
+    .await
+    .unwrap();
+    assert!(
+        adapter
+            .get_user_by_email("kept@example.com")
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    let discarded = user("discarded@example.com");
+    let err = run_in_transaction(&storage, |tx| async move {
+        tx.create_user(&discarded).await?;
+        tx.create_user(&user("kept@example.com")).await
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(err, AuthError::DuplicateEntry { .. }));
+    assert!(
+        adapter
+            .get_user_by_email("discarded@example.com")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // A finished transaction cannot be reused.
+    let tx = storage.begin().await.unwrap().unwrap();
+    tx.commit().await.unwrap();
+    assert!(tx.get_user_by_email("kept@example.com").await.is_err());
+}
+
+#[tokio::test]
+async fn test_list_users() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+
+    for email in ["a@example.com", "b@test.org", "c@example.com"] {
+        adapter.create_user(&user(email)).await.unwrap();
+    }
+
+    let (page, total) = adapter
+        .list_users(0, 1, Some(UserFilter::new().email_contains("EXAMPLE")))
+        .await
+        .unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(page.len(), 1);
+    assert_eq!(adapter.count_users().await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_sessions_and_accounts() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    let user = adapter
+        .create_user(&user("carol@example.com"))
+        .await
+        .unwrap();
+
+    let session = adapter
+        .create_session(&Session::new(user.id.clone()))
+        .await
+        .unwrap();
+    let expired = adapter
+        .create_session(&Session::with_expiration(
+            user.id.clone(),
+            Duration::seconds(-1),
+        ))
+        .await
+        .unwrap();
+    let found = adapter
+        .get_session_by_token(&session.token)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, session.id);
+    assert_eq!(adapter.delete_expired_sessions().await.unwrap(), 1);
+    assert!(
+        adapter
+            .get_session_by_id(&expired.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let mut account = Account::new(user.id.clone(), "github".to_string(), "42".to_string());
+    account.profile = Some(json!({ "login": "jane", "company": "Acme" }));
+    adapter.create_account(&account).await.unwrap();
+    let stored = adapter.get_account("github", "42").await.unwrap().unwrap();
+    assert_eq!(stored.profile, account.profile);
+    assert!(matches!(
+        adapter
+            .create_account(&Account::new(
+                user.id.clone(),
+                "github".to_string(),
+                "42".to_string()
+            ))
+            .await,
+        Err(AuthError::DuplicateEntry { .. })
+    ));
+
+    adapter.delete_user(&user.id).await.unwrap();
+    assert!(
+        adapter
+            .get_session_by_id(&session.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(adapter.get_account("github", "42").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_soft_delete_restore_and_purge() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    let mut dave = user("dave@example.com");
+    dave.set_extension("username", "dave");
+    let dave = adapter.create_user(&dave).await.unwrap();
+    let session = adapter
+        .create_session(&Session::new(dave.id.clone()))
+        .await
+        .unwrap();
+    let account = Account::new(dave.id.clone(), "github".to_string(), "7".to_string());
+    adapter.create_account(&account).await.unwrap();
+
+    adapter.soft_delete_user(&dave.id).await.unwrap();
+    assert!(adapter.get_user_by_id(&dave.id).await.unwrap().is_none());
+    let by_email = adapter.get_user_by_email(&dave.email).await.unwrap();
+    assert!(by_email.is_none());
+    let by_username = adapter
+        .get_user_by_extension("username", &json!("dave"))
+        .await
+        .unwrap();
+    assert!(by_username.is_none());
+    let session = adapter.get_session_by_id(&session.id).await.unwrap();
+    assert!(session.is_none());
+    assert_eq!(adapter.count_users().await.unwrap(), 0);
+    let filter = UserFilter::new().include_deleted();
+    let (page, _) = adapter.list_users(0, 10, Some(filter)).await.unwrap();
+    assert!(page[0].is_deleted());
+
+    let restored = adapter.restore_user(&dave.id).await.unwrap().unwrap();
+    assert!(!restored.is_deleted());
+    assert!(restored.updated_at > dave.updated_at);
+    assert!(adapter.restore_user(&dave.id).await.unwrap().is_none());
+
+    adapter.soft_delete_user(&dave.id).await.unwrap();
+    let grace = Utc::now() - Duration::hours(1);
+    assert_eq!(adapter.purge_deleted_users(grace).await.unwrap(), 0);
+    let purged = adapter
+        .purge_deleted_users(Utc::now() + Duration::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+    assert!(adapter.get_account("github", "7").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_access_storage() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    let adapter = Arc::new(adapter);
+    let plugin = AccessPlugin::new(
+        AccessConfig::builder()
+            .with_storage(adapter.clone())
+            .build(),
+    );
+    let mut builder = SchemaBuilder::new();
+    plugin.define_schema(&mut builder);
+    adapter
+        .migrate(&builder.build().models, false)
+        .await
+        .unwrap();
+
+    let user = adapter
+        .create_user(&user("dave@example.com"))
+        .await
+        .unwrap();
+    adapter
+        .create_role(&DbRole::new("editor", "Editor"))
+        .await
+        .unwrap();
+    adapter
+        .create_role(&DbRole::new("admin", "Admin").system())
+        .await
+        .unwrap();
+    adapter
+        .create_permission(&DbPermission::new("post:edit", "post:edit"))
+        .await
+        .unwrap();
+    adapter
+        .assign_permission_to_role("editor", "post:edit")
+        .await
+        .unwrap();
+    assert_eq!(
+        adapter.get_role_permissions("editor").await.unwrap().len(),
+        1
+    );
+
+    adapter
+        .grant_permission_to_user_until(&user.id, "post:edit", Utc::now() - Duration::seconds(1))
+        .await
+        .unwrap();
+    assert!(
+        adapter
+            .get_user_permissions(&user.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    adapter
+        .grant_permission_to_user(&user.id, "post:edit")
+        .await
+        .unwrap();
+    assert_eq!(
+        adapter.get_user_permissions(&user.id).await.unwrap().len(),
+        1
+    );
+    adapter.revoke_all_user_permissions(&user.id).await.unwrap();
+    assert!(
+        adapter
+            .get_user_permissions(&user.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    adapter.set_role_parent("admin", "editor").await.unwrap();
+    assert!(matches!(
+        adapter.set_role_parent("editor", "admin").await,
+        Err(AuthError::Conflict { .. })
+    ));
+    assert_eq!(
+        adapter.get_role_parents("admin").await.unwrap(),
+        vec!["editor"]
+    );
+
+    assert!(matches!(
+        adapter.delete_role("admin").await,
+        Err(AuthError::Forbidden { .. })
+    ));
+    adapter.delete_role("editor").await.unwrap();
+    assert!(adapter.get_role_hierarchy().await.unwrap().is_empty());
+    assert!(adapter.get_role("admin").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_email_otp_storage() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    adapter
+        .migrate(&EmailOtpSchema::schema(), false)
+        .await
+        .unwrap();
+
+    let expires_at = Utc::now() + Duration::minutes(5);
+    adapter
+        .create_email_otp(&EmailOtp::new(
+            "erin@example.com",
+            "111111",
+            "sign-in",
+            expires_at,
+        ))
+        .await
+        .unwrap();
+    adapter
+        .create_email_otp(&EmailOtp::new(
+            "erin@example.com",
+            "222222",
+            "forget-password",
+            expires_at,
+        ))
+        .await
+        .unwrap();
+
+    // A new code replaces the earlier one of the same type only.
+    let mut otp = EmailOtp::new("erin@example.com", "333333", "sign-in", expires_at);
+    adapter.create_email_otp(&otp).await.unwrap();
+    let found = adapter
+        .get_email_otp("erin@example.com", "sign-in")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.otp, "333333");
+    assert!(
+        adapter
+            .get_email_otp("erin@example.com", "forget-password")
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    otp.increment_attempts();
+    assert_eq!(adapter.update_email_otp(&otp).await.unwrap().attempts, 1);
+
+    adapter.delete_email_otp(&otp.id).await.unwrap();
+    assert!(
+        adapter
+            .get_email_otp("erin@example.com", "sign-in")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(matches!(
+        adapter.update_email_otp(&otp).await,
+        Err(AuthError::NotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_magic_link_token_storage() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    adapter
+        .migrate(&MagicLinkSchema::schema(), false)
+        .await
+        .unwrap();
+
+    let token = MagicLinkToken::new(
+        "erin@example.com",
+        "abc123",
+        Utc::now() + Duration::minutes(5),
+    );
+    adapter.create_magic_link_token(&token).await.unwrap();
+    assert!(matches!(
+        adapter
+            .create_magic_link_token(&MagicLinkToken::new(
+                "erin@example.com",
+                "abc123",
+                token.expires_at
+            ))
+            .await,
+        Err(AuthError::DuplicateEntry { .. })
+    ));
+
+    let found = adapter
+        .get_magic_link_token("abc123")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, token.id);
+    assert!(!found.used);
+
+    // Only one of two concurrent redemptions wins.
+    let (a, b) = tokio::join!(
+        adapter.use_magic_link_token(&token.id),
+        adapter.use_magic_link_token(&token.id)
+    );
+    assert!(a.unwrap() ^ b.unwrap());
+    assert!(
+        adapter
+            .get_magic_link_token("abc123")
+            .await
+            .unwrap()
+            .unwrap()
+            .used
+    );
+}
+
+#[tokio::test]
+async fn test_password_history_storage() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    adapter
+        .migrate(&PasswordHistoryEntry::schema(), false)
+        .await
+        .unwrap();
+    adapter
+        .create_user(&User::new(
+            "frank".to_string(),
+            "frank@example.com".to_string(),
+        ))
+        .await
+        .unwrap();
+
+    for (i, hash) in ["hash-1", "hash-2", "hash-3"].into_iter().enumerate() {
+        let mut entry = PasswordHistoryEntry::new("frank", hash);
+        entry.created_at += Duration::seconds(i as i64);
+        adapter.add_password_history(&entry).await.unwrap();
+    }
+
+    let recent = adapter.get_password_history("frank", 2).await.unwrap();
+    let hashes: Vec<_> = recent.iter().map(|e| e.password_hash.as_str()).collect();
+    assert_eq!(hashes, ["hash-3", "hash-2"]);
+
+    adapter.trim_password_history("frank", 1).await.unwrap();
+    let kept = adapter.get_password_history("frank", 10).await.unwrap();
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].password_hash, "hash-3");
+
+    // History goes with the user.
+    adapter.delete_user("frank").await.unwrap();
+    assert!(
+        adapter
+            .get_password_history("frank", 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_two_factor_storage() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    adapter
+        .migrate(&TwoFactorSchema::schema(), false)
+        .await
+        .unwrap();
+    adapter
+        .create_user(&User::new(
+            "grace".to_string(),
+            "grace@example.com".to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let first = TwoFactorData::new("grace", "first-secret", &[]);
+    adapter.create_two_factor(&first).await.unwrap();
+    assert!(adapter.advance_totp_step("grace", 5).await.unwrap());
+
+    // Enabling again replaces the secret and forgets the used step.
+    let second = TwoFactorData::new("grace", "second-secret", &[]);
+    adapter.create_two_factor(&second).await.unwrap();
+    let found = adapter.get_two_factor("grace").await.unwrap().unwrap();
+    assert_eq!(found.secret, "second-secret");
+    assert_eq!(found.last_used_step, None);
+
+    // Only one of two concurrent verifications of a step wins.
+    let (a, b) = tokio::join!(
+        adapter.advance_totp_step("grace", 7),
+        adapter.advance_totp_step("grace", 7)
+    );
+    assert!(a.unwrap() ^ b.unwrap());
+    assert!(!adapter.advance_totp_step("grace", 6).await.unwrap());
+    let found = adapter.get_two_factor("grace").await.unwrap().unwrap();
+    assert_eq!(found.last_used_step, Some(7));
+
+    // Backup codes only change from the value a request read.
+    let (a, b) = tokio::join!(
+        adapter.swap_backup_codes("grace", &found.backup_codes, "[\"a\"]"),
+        adapter.swap_backup_codes("grace", &found.backup_codes, "[\"b\"]")
+    );
+    assert!(a.unwrap() ^ b.unwrap());
+
+    adapter.delete_two_factor("grace").await.unwrap();
+    assert!(adapter.get_two_factor("grace").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_trusted_device_storage() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    adapter
+        .migrate(&TwoFactorSchema::schema(), false)
+        .await
+        .unwrap();
+    adapter
+        .create_user(&User::new(
+            "heidi".to_string(),
+            "heidi@example.com".to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let mut device = TrustedDevice::new("heidi", "hash-1", Utc::now() + Duration::days(30));
+    device.user_agent = Some("Firefox".to_string());
+    adapter.create_trusted_device(&device).await.unwrap();
+    let duplicate = TrustedDevice::new("heidi", "hash-1", Utc::now() + Duration::days(30));
+    assert!(matches!(
+        adapter.create_trusted_device(&duplicate).await,
+        Err(AuthError::DuplicateEntry { .. })
+    ));
+
+    let found = adapter
+        .get_trusted_device("heidi", "hash-1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, device.id);
+    assert_eq!(found.user_agent.as_deref(), Some("Firefox"));
+    assert!(
+        adapter
+            .get_trusted_device("heidi", "hash-2")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let removed = adapter.delete_user_trusted_devices("heidi").await.unwrap();
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].id, device.id);
+    assert!(
+        adapter
+            .get_trusted_device("heidi", "hash-1")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_passkey_storage() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    adapter
+        .migrate(&PasskeySchema::schema(), false)
+        .await
+        .unwrap();
+    adapter
+        .create_user(&User::new(
+            "ivan".to_string(),
+            "ivan@example.com".to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let mut passkey = Passkey::new("ivan", "cred_1", "public-key");
+    passkey.transports = Some("[\"internal\"]".to_string());
+    adapter.create_passkey(&passkey).await.unwrap();
+    assert!(matches!(
+        adapter
+            .create_passkey(&Passkey::new("ivan", "cred_1", "other-key"))
+            .await,
+        Err(AuthError::DuplicateEntry { .. })
+    ));
+
+    let mut found = adapter
+        .get_passkey_by_credential_id("cred_1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, passkey.id);
+    assert_eq!(found.transports, passkey.transports);
+    found.counter = 7;
+    adapter.update_passkey(&found).await.unwrap();
+    let passkeys = adapter.get_user_passkeys("ivan").await.unwrap();
+    assert_eq!(passkeys.len(), 1);
+    assert_eq!(passkeys[0].counter, 7);
+
+    let missing = Passkey::new("ivan", "cred_2", "public-key");
+    assert!(matches!(
+        adapter.update_passkey(&missing).await,
+        Err(AuthError::NotFound { .. })
+    ));
+
+    adapter.delete_user_passkeys("ivan").await.unwrap();
+    assert!(adapter.get_user_passkeys("ivan").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_api_key_storage() {
+    let Some(adapter) = adapter().await else {
+        return;
+    };
+    adapter
+        .migrate(&ApiKeySchema::schema(), false)
+        .await
+        .unwrap();
+    adapter
+        .create_user(&User::new(
+            "judy".to_string(),
+            "judy@example.com".to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let key = ApiKey::new("judy", "prefix_secret")
+        .with_name("CI")
+        .with_scopes(vec!["post:create".to_string()])
+        .with_remaining(5);
+    adapter.create_api_key(&key).await.unwrap();
+
+    let found = adapter.get_api_keys_by_prefix("prefix").await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].hashed_key, key.hashed_key);
+    assert_eq!(found[0].scopes, key.scopes);
+    assert!(found[0].has_scope("post:create"));
+    assert_eq!(
+        adapter.get_api_keys_by_user_id("judy").await.unwrap().len(),
+        1
+    );
+
+    // Concurrent uses share the budget: exactly five succeed.
+    let adapter = Arc::new(adapter);
+    let uses = (0..10).map(|_| {
+        let adapter = adapter.clone();
+        let id = key.id.clone();
+        tokio::spawn(async move { adapter.record_api_key_use(&id).await.unwrap() })
+    });
+    let mut allowed = 0;
+    for result in uses.collect::<Vec<_>>() {
+        match result.await.unwrap() {
+            Ok(_) => allowed += 1,
+            Err(denied) => assert!(matches!(denied, UsageDenied::QuotaExhausted)),
+        }
+    }
+    assert_eq!(allowed, 5);
+    let stored = adapter.get_api_keys_by_prefix("prefix").await.unwrap();
+    assert_eq!(stored[0].remaining, Some(0));
+    assert_eq!(stored[0].request_count, 5);
+    assert!(matches!(
+        adapter.record_api_key_use("missing").await,
+        Err(AuthError::NotFound { .. })
+    ));
+
+    adapter.delete_api_keys_by_user_id("judy").await.unwrap();
+    assert!(
+        adapter
+            .get_api_keys_by_user_id("judy")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,226 @@
+//! Adapter that splits session storage from the primary database.
+
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::schema::{MigrationOp, ModelDefinition, SchemaDefinition};
+use better_auth_core::traits::{SessionStore, StorageAdapter, StorageTransaction};
+use better_auth_core::types::{Account, Session, User, UserFilter};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Storage adapter that routes session operations to a [`SessionStore`]
+/// and all other operations to a primary [`StorageAdapter`].
+///
+/// Transactions are those of the primary adapter: sessions written inside
+/// one go to the session store straight away and are not rolled back.
+#[derive(Clone)]
+pub struct CompositeAdapter {
+    primary: Arc<dyn StorageAdapter>,
+    sessions: Arc<dyn SessionStore>,
+    /// The primary's transaction, for adapters returned by `begin`.
+    tx: Option<Arc<dyn StorageTransaction>>,
+}
+
+impl CompositeAdapter {
+    /// Creates a composite adapter from a primary adapter and a session store.
+    pub fn new(
+        primary: impl StorageAdapter + 'static,
+        sessions: impl SessionStore + 'static,
+    ) -> Self {
+        Self {
+            primary: Arc::new(primary),
+            sessions: Arc::new(sessions),
+            tx: None,
+        }
+    }
+
+    /// Returns the adapter handling users, accounts, and schema.
+    pub fn primary(&self) -> &dyn StorageAdapter {
+        self.primary.as_ref()
+    }
+
+    /// Returns the store handling sessions.
+    pub fn sessions(&self) -> &dyn SessionStore {
+        self.sessions.as_ref()
+    }
+
+    /// The primary's transaction, if this adapter was returned by `begin`.
+    fn transaction(&self) -> AuthResult<&Arc<dyn StorageTransaction>> {
+        self.tx
+            .as_ref()
+            .ok_or_else(|| AuthError::database("Adapter is not in a transaction"))
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for CompositeAdapter {
+    // ==================== User Operations ====================
+
+    async fn create_user(&self, user: &User) -> AuthResult<User> {
+        self.primary.create_user(user).await
+    }
+
+    async fn get_user_by_id(&self, id: &str) -> AuthResult<Option<User>> {
+        self.primary.get_user_by_id(id).await
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> AuthResult<Option<User>> {
+        self.primary.get_user_by_email(email).await
+    }
+
+    async fn get_user_by_extension(
+        &self,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> AuthResult<Option<User>> {
+        self.primary.get_user_by_extension(key, value).await
+    }
+
+    async fn update_user(&self, user: &User) -> AuthResult<User> {
+        self.primary.update_user(user).await
+    }
+
+    async fn delete_user(&self, id: &str) -> AuthResult<()> {
+        // The primary adapter can't cascade into the session store.
+        self.sessions.delete_sessions_by_user_id(id).await?;
+        self.primary.delete_user(id).await
+    }
+
+    async fn soft_delete_user(&self, id: &str) -> AuthResult<()> {
+        self.sessions.delete_sessions_by_user_id(id).await?;
+        self.primary.soft_delete_user(id).await
+    }
+
+    async fn restore_user(&self, id: &str) -> AuthResult<Option<User>> {
+        self.primary.restore_user(id).await
+    }
+
+    async fn purge_deleted_users(&self, deleted_before: DateTime<Utc>) -> AuthResult<usize> {
+        self.primary.purge_deleted_users(deleted_before).await
+    }
+
+    async fn list_users(
+        &self,
+        offset: usize,
+        limit: usize,
+        filter: Option<UserFilter>,
+    ) -> AuthResult<(Vec<User>, usize)> {
+        self.primary.list_users(offset, limit, filter).await
+    }
+
+    async fn count_users(&self) -> AuthResult<usize> {
+        self.primary.count_users().await
+    }
+
+    // ==================== Session Operations ====================
+
+    async fn create_session(&self, session: &Session) -> AuthResult<Session> {
+        self.sessions.create_session(session).await
+    }
+
+    async fn get_session_by_id(&self, id: &str) -> AuthResult<Option<Session>> {
+        self.sessions.get_session_by_id(id).await
+    }
+
+    async fn get_session_by_token(&self, token: &str) -> AuthResult<Option<Session>> {
+        self.sessions.get_session_by_token(token).await
+    }
+
+    async fn get_sessions_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Session>> {
+        self.sessions.get_sessions_by_user_id(user_id).await
+    }
+
+    async fn update_session(&self, session: &Session) -> AuthResult<Session> {
+        self.sessions.update_session(session).await
+    }
+
+    async fn delete_session(&self, id: &str) -> AuthResult<()> {
+        self.sessions.delete_session(id).await
+    }
+
+    async fn delete_sessions_by_user_id(&self, user_id: &str) -> AuthResult<()> {
+        self.sessions.delete_sessions_by_user_id(user_id).await
+    }
+
+    async fn delete_expired_sessions(&self) -> AuthResult<usize> {
+        self.sessions.delete_expired_sessions().await
+    }
+
+    // ==================== Account Operations ====================
+
+    async fn create_account(&self, account: &Account) -> AuthResult<Account> {
+        self.primary.create_account(account).await
+    }
+
+    async fn get_account(
+        &self,
+        provider: &str,
+        provider_account_id: &str,
+    ) -> AuthResult<Option<Account>> {
+        self.primary.get_account(provider, provider_account_id).await
+    }
+
+    async fn get_account_by_id(&self, id: &str) -> AuthResult<Option<Account>> {
+        self.primary.get_account_by_id(id).await
+    }
+
+    async fn get_accounts_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Account>> {
+        self.primary.get_accounts_by_user_id(user_id).await
+    }
+
+    async fn update_account(&self, account: &Account) -> AuthResult<Account> {
+        self.primary.update_account(account).await
+    }
+
+    async fn delete_account(&self, id: &str) -> AuthResult<()> {
+        self.primary.delete_account(id).await
+    }
+
+    // ==================== Schema Operations ====================
+
+    async fn migrate(
+        &self,
+        models: &[ModelDefinition],
+        dry_run: bool,
+    ) -> AuthResult<Vec<MigrationOp>> {
+        self.primary.migrate(models, dry_run).await
+    }
+
+    async fn table_exists(&self, table_name: &str) -> AuthResult<bool> {
+        self.primary.table_exists(table_name).await
+    }
+
+    async fn introspect_schema(&self) -> AuthResult<SchemaDefinition> {
+        self.primary.introspect_schema().await
+    }
+
+    // ==================== Generic Operations ====================
+
+    async fn execute_raw(&self, query: &str) -> AuthResult<()> {
+        self.primary.execute_raw(query).await
+    }
+
+    // ==================== Transactions ====================
+
+    async fn begin(&self) -> AuthResult<Option<Arc<dyn StorageTransaction>>> {
+        let Some(tx) = self.primary.begin().await? else {
+            return Ok(None);
+        };
+        Ok(Some(Arc::new(Self {
+            primary: tx.clone(),
+            sessions: self.sessions.clone(),
+            tx: Some(tx),
+        })))
+    }
+}
+
+#[async_trait]
+impl StorageTransaction for CompositeAdapter {
+    async fn commit(&self) -> AuthResult<()> {
+        self.transaction()?.commit().await
+    }
+
+    async fn rollback(&self) -> AuthResult<()> {
+        self.transaction()?.rollback().await
+    }
+}
@@ -0,0 +1,36 @@
+//! # Better Auth Redis Adapter
+//!
+//! Redis-backed session storage for Better Auth, for fast session lookups
+//! shared across instances.
+//!
+//! Sessions are written with a TTL matching `expires_at`, so Redis expires
+//! them on its own and no cleanup job is needed. [`CompositeAdapter`] routes
+//! session operations to a [`SessionStore`] and everything else to a primary
+//! [`StorageAdapter`].
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use better_auth_adapter_redis::{CompositeAdapter, RedisSessionStore};
+//!
+//! let sessions = RedisSessionStore::from_url("redis://127.0.0.1/").await?;
+//! let auth = AppAuth::builder()
+//!     .adapter(CompositeAdapter::new(postgres_adapter, sessions))
+//!     .build()?;
+//! ```
+//!
+//! ## Features
+//!
+//! - `redis` (default): enables [`RedisSessionStore`]. Without it only
+//!   [`CompositeAdapter`] is available.
+//!
+//! [`SessionStore`]: better_auth_core::traits::SessionStore
+//! [`StorageAdapter`]: better_auth_core::traits::StorageAdapter
+
+mod composite;
+#[cfg(feature = "redis")]
+mod session_store;
+
+pub use composite::CompositeAdapter;
+#[cfg(feature = "redis")]
+pub use session_store::RedisSessionStore;
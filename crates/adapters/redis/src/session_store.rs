@@ -0,0 +1,282 @@
+//! Redis implementation of [`SessionStore`].
+
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::traits::{next_updated_at, SessionStore};
+use better_auth_core::types::Session;
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+
+/// Key prefix used when none is configured.
+const DEFAULT_PREFIX: &str = "better_auth:";
+
+/// Session store backed by Redis.
+///
+/// Keys used (relative to the configured prefix):
+///
+/// - `session:{id}` — the session as JSON, expiring at `expires_at`
+/// - `session_token:{token}` — the session ID, expiring at `expires_at`
+/// - `user_sessions:{user_id}` — set of session IDs, pruned lazily
+#[derive(Clone)]
+pub struct RedisSessionStore {
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+impl RedisSessionStore {
+    /// Connects to Redis using the given client.
+    pub async fn new(client: redis::Client) -> AuthResult<Self> {
+        let conn = ConnectionManager::new(client).await.map_err(redis_error)?;
+        Ok(Self::from_connection(conn))
+    }
+
+    /// Connects to Redis at the given URL (e.g. `redis://127.0.0.1/`).
+    pub async fn from_url(url: &str) -> AuthResult<Self> {
+        let client = redis::Client::open(url).map_err(redis_error)?;
+        Self::new(client).await
+    }
+
+    /// Creates a store from an existing connection manager.
+    pub fn from_connection(conn: ConnectionManager) -> Self {
+        Self {
+            conn,
+            prefix: DEFAULT_PREFIX.to_string(),
+        }
+    }
+
+    /// Sets the prefix prepended to every key (default: `better_auth:`).
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn session_key(&self, id: &str) -> String {
+        format!("{}session:{}", self.prefix, id)
+    }
+
+    fn token_key(&self, token: &str) -> String {
+        format!("{}session_token:{}", self.prefix, token)
+    }
+
+    fn user_key(&self, user_id: &str) -> String {
+        format!("{}user_sessions:{}", self.prefix, user_id)
+    }
+
+    /// Writes a session and its token index, replacing `stale_token` if set.
+    ///
+    /// Sessions that have already expired are removed rather than written.
+    async fn write(&self, session: &Session, stale_token: Option<&str>) -> AuthResult<()> {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+
+        if let Some(token) = stale_token {
+            pipe.del(self.token_key(token)).ignore();
+        }
+
+        match ttl_millis(session.expires_at) {
+            Some(ttl) => {
+                let json = serde_json::to_string(session)
+                    .map_err(|e| AuthError::internal(format!("Failed to encode session: {}", e)))?;
+                pipe.pset_ex(self.session_key(&session.id), json, ttl)
+                    .ignore()
+                    .pset_ex(self.token_key(&session.token), &session.id, ttl)
+                    .ignore()
+                    .sadd(self.user_key(&session.user_id), &session.id)
+                    .ignore();
+            }
+            None => {
+                pipe.del(self.session_key(&session.id))
+                    .ignore()
+                    .del(self.token_key(&session.token))
+                    .ignore()
+                    .srem(self.user_key(&session.user_id), &session.id)
+                    .ignore();
+            }
+        }
+
+        let mut conn = self.conn.clone();
+        pipe.query_async::<()>(&mut conn).await.map_err(redis_error)
+    }
+
+    /// Loads the sessions with the given IDs, skipping any that expired.
+    async fn load(&self, ids: &[String]) -> AuthResult<Vec<Option<Session>>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<String> = ids.iter().map(|id| self.session_key(id)).collect();
+        let mut conn = self.conn.clone();
+        let values: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+
+        values
+            .into_iter()
+            .map(|value| value.map(|json| decode(&json)).transpose())
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for RedisSessionStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisSessionStore")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl SessionStore for RedisSessionStore {
+    async fn create_session(&self, session: &Session) -> AuthResult<Session> {
+        let mut session = session.clone();
+        let now = Utc::now();
+        session.created_at = now;
+        session.updated_at = now;
+
+        self.write(&session, None).await?;
+        Ok(session)
+    }
+
+    async fn get_session_by_id(&self, id: &str) -> AuthResult<Option<Session>> {
+        let mut conn = self.conn.clone();
+        let value: Option<String> = conn.get(self.session_key(id)).await.map_err(redis_error)?;
+        value.map(|json| decode(&json)).transpose()
+    }
+
+    async fn get_session_by_token(&self, token: &str) -> AuthResult<Option<Session>> {
+        let mut conn = self.conn.clone();
+        let id: Option<String> = conn.get(self.token_key(token)).await.map_err(redis_error)?;
+        match id {
+            Some(id) => self.get_session_by_id(&id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn get_sessions_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Session>> {
+        let user_key = self.user_key(user_id);
+        let mut conn = self.conn.clone();
+        let ids: Vec<String> = conn.smembers(&user_key).await.map_err(redis_error)?;
+
+        let loaded = self.load(&ids).await?;
+
+        // Drop IDs whose sessions have expired out from under the index.
+        let expired: Vec<&String> = ids
+            .iter()
+            .zip(&loaded)
+            .filter(|(_, session)| session.is_none())
+            .map(|(id, _)| id)
+            .collect();
+        if !expired.is_empty() {
+            let _: () = conn.srem(&user_key, expired).await.map_err(redis_error)?;
+        }
+
+        Ok(loaded.into_iter().flatten().collect())
+    }
+
+    async fn update_session(&self, session: &Session) -> AuthResult<Session> {
+        let existing = self
+            .get_session_by_id(&session.id)
+            .await?
+            .ok_or_else(|| AuthError::not_found("session", "id", &session.id))?;
+
+        let mut session = session.clone();
+        session.created_at = existing.created_at;
+        session.updated_at = next_updated_at(existing.updated_at);
+
+        let stale_token = (existing.token != session.token).then_some(existing.token.as_str());
+        self.write(&session, stale_token).await?;
+        Ok(session)
+    }
+
+    async fn delete_session(&self, id: &str) -> AuthResult<()> {
+        let Some(session) = self.get_session_by_id(id).await? else {
+            return Ok(());
+        };
+
+        let mut conn = self.conn.clone();
+        redis::pipe()
+            .atomic()
+            .del(self.session_key(&session.id))
+            .ignore()
+            .del(self.token_key(&session.token))
+            .ignore()
+            .srem(self.user_key(&session.user_id), &session.id)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(redis_error)
+    }
+
+    async fn delete_sessions_by_user_id(&self, user_id: &str) -> AuthResult<()> {
+        let user_key = self.user_key(user_id);
+        let mut conn = self.conn.clone();
+        let ids: Vec<String> = conn.smembers(&user_key).await.map_err(redis_error)?;
+        let sessions = self.load(&ids).await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for id in &ids {
+            pipe.del(self.session_key(id)).ignore();
+        }
+        for session in sessions.iter().flatten() {
+            pipe.del(self.token_key(&session.token)).ignore();
+        }
+        pipe.del(&user_key).ignore();
+
+        pipe.query_async::<()>(&mut conn).await.map_err(redis_error)
+    }
+}
+
+/// Returns the time until `expires_at` in milliseconds, or `None` if it has passed.
+fn ttl_millis(expires_at: DateTime<Utc>) -> Option<u64> {
+    let remaining = (expires_at - Utc::now()).num_milliseconds();
+    u64::try_from(remaining).ok().filter(|ms| *ms > 0)
+}
+
+fn decode(json: &str) -> AuthResult<Session> {
+    serde_json::from_str(json)
+        .map_err(|e| AuthError::internal(format!("Failed to decode session: {}", e)))
+}
+
+fn redis_error(err: redis::RedisError) -> AuthError {
+    AuthError::database(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_ttl_tracks_expires_at() {
+        let ttl = ttl_millis(Utc::now() + Duration::hours(1)).unwrap();
+        assert!(ttl > 59 * 60 * 1000 && ttl <= 60 * 60 * 1000);
+
+        assert_eq!(ttl_millis(Utc::now() - Duration::seconds(1)), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Redis server at REDIS_URL"]
+    async fn test_session_roundtrip() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".into());
+        let store = RedisSessionStore::from_url(&url)
+            .await
+            .unwrap()
+            .with_prefix("better_auth_test:");
+
+        let session = store.create_session(&Session::new("user_123".to_string())).await.unwrap();
+        let fetched = store.get_session_by_token(&session.token).await.unwrap().unwrap();
+        assert_eq!(fetched.id, session.id);
+
+        let updated = store.update_session(&fetched).await.unwrap();
+        assert!(updated.updated_at > session.updated_at);
+
+        assert_eq!(store.get_sessions_by_user_id("user_123").await.unwrap().len(), 1);
+
+        store.delete_sessions_by_user_id("user_123").await.unwrap();
+        assert!(store.get_session_by_token(&session.token).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,54 @@
+//! Small cryptographic helpers shared by plugins.
+
+use sha2::{Digest, Sha256};
+
+/// Compares two secrets without leaking, through timing, where they first
+/// differ.
+///
+/// Use this instead of `==` for tokens, codes and other values an attacker
+/// could guess byte by byte. The length is not hidden.
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut result = 0u8;
+    for (x, y) in a.bytes().zip(b.bytes()) {
+        result |= x ^ y;
+    }
+    result == 0
+}
+
+/// Hashes a one-time secret, such as a sign-in code or link token, for
+/// storage: the hex-encoded SHA-256 digest.
+///
+/// A leaked table then holds no usable links. The hash is fast and unsalted,
+/// so short codes can still be brute-forced offline and rely on their short
+/// expiry; hash passwords with the password plugin instead.
+pub fn hash_secret(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq("token", "token"));
+        assert!(!constant_time_eq("token", "tokex"));
+        assert!(!constant_time_eq("token", "token2"));
+        assert!(constant_time_eq("", ""));
+    }
+    #[test]
+    fn test_hash_secret() {
+        assert_eq!(
+            hash_secret("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_ne!(hash_secret("abc"), hash_secret("abd"));
+    }
+}
@@ -0,0 +1,326 @@
+//! CSRF protection for cookie-authenticated requests.
+//!
+//! Browsers attach the session cookie to requests that other sites trigger,
+//! so a mutating endpoint cannot trust the cookie alone. This module uses the
+//! double-submit cookie pattern: a random token is set in a cookie that the
+//! site's own JavaScript can read, and mutating requests must echo it in the
+//! `X-CSRF-Token` header. Another origin can make the browser send the cookie
+//! but can neither read it nor set the header.
+//!
+//! The CSRF cookie uses the same [`CookieOptions`] as the session cookie,
+//! including `SameSite`, except that it is not `HttpOnly`. `SameSite=Lax`
+//! already stops most cross-site posts; the token also covers older browsers
+//! and same-site subdomains.
+//!
+//! Only requests that carry the session cookie are checked. Requests with an
+//! `Authorization: Bearer` header are exempt: the credential is not sent by
+//! the browser on its own, and a cross-site page cannot add the header
+//! without passing a CORS preflight.
+//!
+//! [`CsrfProtect`] wraps a route handler to enforce this and to issue the
+//! token alongside any session cookie the handler sets.
+
+use async_trait::async_trait;
+
+use crate::crypto::constant_time_eq;
+use crate::error::{AuthError, AuthResult};
+use crate::router::{CookieOptions, Method, Request, RequestHandler, Response};
+use crate::session::{AuthScheme, SESSION_COOKIE};
+
+/// Name of the cookie that carries the CSRF token.
+pub const CSRF_COOKIE: &str = "better_auth_csrf";
+
+/// Header that mutating requests echo the CSRF token in.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// CSRF protection settings.
+#[derive(Debug, Clone)]
+pub struct CsrfConfig {
+    /// Name of the cookie that carries the token.
+    pub cookie_name: String,
+    /// Header the token must be echoed in. Stored lowercase.
+    pub header_name: String,
+    /// Name of the session cookie whose presence triggers the check.
+    pub session_cookie: String,
+    /// Methods that require a token.
+    pub methods: Vec<Method>,
+    /// Options for the token cookie.
+    pub cookie: CookieOptions,
+}
+
+impl Default for CsrfConfig {
+    fn default() -> Self {
+        Self {
+            cookie_name: CSRF_COOKIE.to_string(),
+            header_name: CSRF_HEADER.to_string(),
+            session_cookie: SESSION_COOKIE.to_string(),
+            methods: vec![Method::POST, Method::PUT, Method::PATCH, Method::DELETE],
+            cookie: Self::cookie_options(CookieOptions::secure()),
+        }
+    }
+}
+
+impl CsrfConfig {
+    /// Creates a config with the default cookie, header and methods.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the methods that require a token.
+    pub fn methods(mut self, methods: Vec<Method>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Sets the token cookie name.
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        self.cookie_name = name.into();
+        self
+    }
+
+    /// Sets the header the token is echoed in.
+    pub fn header_name(mut self, name: impl Into<String>) -> Self {
+        self.header_name = name.into().to_lowercase();
+        self
+    }
+
+    /// Sets the session cookie name, if it is not the default.
+    pub fn session_cookie(mut self, name: impl Into<String>) -> Self {
+        self.session_cookie = name.into();
+        self
+    }
+
+    /// Uses the session cookie's options for the token cookie.
+    ///
+    /// `SameSite`, `Secure`, path and domain are kept; `HttpOnly` is turned
+    /// off so the page can read the token.
+    pub fn session_cookie_options(mut self, options: CookieOptions) -> Self {
+        self.cookie = Self::cookie_options(options);
+        self
+    }
+
+    fn cookie_options(options: CookieOptions) -> CookieOptions {
+        CookieOptions {
+            http_only: false,
+            path: options.path.or_else(|| Some("/".to_string())),
+            ..options
+        }
+    }
+
+    /// Sets a new token cookie on `response`.
+    pub fn issue(&self, response: Response) -> Response {
+        response.cookie(&self.cookie_name, &generate_token(), self.cookie.clone())
+    }
+
+    /// Checks that a request carries a valid CSRF token, if it needs one.
+    ///
+    /// A token is required for the configured methods when the request has
+    /// the session cookie and no bearer token. The header and cookie values
+    /// must then both be present and equal, otherwise this fails with
+    /// [`AuthError::InvalidCsrfToken`].
+    pub fn verify(&self, req: &Request) -> AuthResult<()> {
+        let header = |name: &str| req.header(name).cloned();
+        if !self.methods.contains(&req.method)
+            || AuthScheme::Bearer.extract(header).is_some()
+            || self.cookie_token(req, &self.session_cookie).is_none()
+        {
+            return Ok(());
+        }
+
+        match (
+            self.cookie_token(req, &self.cookie_name),
+            header(&self.header_name),
+        ) {
+            (Some(expected), Some(presented)) if constant_time_eq(&expected, &presented) => Ok(()),
+            _ => Err(AuthError::InvalidCsrfToken),
+        }
+    }
+
+    fn cookie_token(&self, req: &Request, name: &str) -> Option<String> {
+        AuthScheme::Cookie {
+            name: name.to_string(),
+        }
+        .extract(|header| req.header(header).cloned())
+    }
+}
+
+/// Generates a random CSRF token.
+pub fn generate_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// A handler wrapper that enforces CSRF protection.
+///
+/// Requests that fail [`CsrfConfig::verify`] get
+/// [`AuthError::InvalidCsrfToken`] (403) and never reach the inner handler.
+/// When the inner handler sets the session cookie, for example on sign-in, a
+/// fresh CSRF token cookie is set alongside it. Requests that have a session
+/// cookie but no token cookie yet are also given one.
+pub struct CsrfProtect<H> {
+    config: CsrfConfig,
+    inner: H,
+}
+
+impl<H: RequestHandler> CsrfProtect<H> {
+    /// Wraps `inner` with the given CSRF settings.
+    pub fn new(config: CsrfConfig, inner: H) -> Self {
+        Self { config, inner }
+    }
+}
+
+#[async_trait]
+impl<H: RequestHandler> RequestHandler for CsrfProtect<H> {
+    async fn handle(&self, req: Request) -> Response {
+        if let Err(err) = self.config.verify(&req) {
+            return Response::new(err.status_code()).json(serde_json::json!({
+                "error": { "code": err.error_code(), "message": err.to_string() }
+            }));
+        }
+
+        let missing_token = self
+            .config
+            .cookie_token(&req, &self.config.session_cookie)
+            .is_some()
+            && self
+                .config
+                .cookie_token(&req, &self.config.cookie_name)
+                .is_none();
+        let response = self.inner.handle(req).await;
+        let session_prefix = format!("{}=", self.config.session_cookie);
+        let sets_session = response
+            .set_cookies()
+            .any(|cookie| cookie.starts_with(&session_prefix));
+        if sets_session || missing_token {
+            self.config.issue(response)
+        } else {
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::RequestHandler;
+
+    /// Signs in by setting the session cookie.
+    struct SignIn;
+
+    #[async_trait]
+    impl RequestHandler for SignIn {
+        async fn handle(&self, _req: Request) -> Response {
+            Response::ok().cookie(SESSION_COOKIE, "session_token", CookieOptions::secure())
+        }
+    }
+
+    fn request(method: Method, headers: &[(&str, &str)]) -> Request {
+        let mut req = Request::new(method, "/user/update");
+        for (name, value) in headers {
+            req.headers.insert(name.to_string(), value.to_string());
+        }
+        req
+    }
+
+    #[test]
+    fn test_verify_requires_matching_token_for_cookie_sessions() {
+        let config = CsrfConfig::new();
+        let cookie = "better_auth_session=abc; better_auth_csrf=tok123";
+
+        let req = request(
+            Method::POST,
+            &[("cookie", cookie), ("x-csrf-token", "tok123")],
+        );
+        assert!(config.verify(&req).is_ok());
+
+        for headers in [
+            &[("cookie", cookie)][..],
+            &[("cookie", cookie), ("x-csrf-token", "other")],
+            &[
+                ("cookie", "better_auth_session=abc"),
+                ("x-csrf-token", "tok123"),
+            ],
+        ] {
+            let err = config
+                .verify(&request(Method::DELETE, headers))
+                .unwrap_err();
+            assert!(matches!(err, AuthError::InvalidCsrfToken));
+            assert_eq!(err.status_code(), 403);
+        }
+
+        // Safe methods are not checked.
+        assert!(
+            config
+                .verify(&request(Method::GET, &[("cookie", cookie)]))
+                .is_ok()
+        );
+        let config = config.methods(vec![Method::GET]);
+        assert!(
+            config
+                .verify(&request(Method::GET, &[("cookie", cookie)]))
+                .is_err()
+        );
+        assert!(
+            config
+                .verify(&request(Method::POST, &[("cookie", cookie)]))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_bearer_and_cookieless_requests_are_exempt() {
+        let config = CsrfConfig::new();
+        let req = request(
+            Method::POST,
+            &[
+                ("authorization", "Bearer abc"),
+                ("cookie", "better_auth_session=abc"),
+            ],
+        );
+        assert!(config.verify(&req).is_ok());
+        assert!(config.verify(&request(Method::POST, &[])).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_issues_token_with_session_cookie() {
+        let handler = CsrfProtect::new(CsrfConfig::new(), SignIn);
+        let response = handler.handle(request(Method::POST, &[])).await;
+        assert_eq!(response.status, 200);
+
+        let cookies: Vec<&str> = response.set_cookies().collect();
+        assert_eq!(cookies.len(), 2);
+        assert!(cookies[0].starts_with("better_auth_session=session_token"));
+        let csrf = cookies[1];
+        assert!(csrf.starts_with("better_auth_csrf="));
+        assert!(!csrf.contains("HttpOnly"));
+        assert!(csrf.contains("SameSite=Lax"));
+        assert!(csrf.contains("Path=/"));
+
+        // The issued token is accepted on the next mutating request.
+        let token = csrf["better_auth_csrf=".len()..].split(';').next().unwrap();
+        assert_eq!(token.len(), 64);
+        let cookie = format!(
+            "better_auth_session=session_token; better_auth_csrf={}",
+            token
+        );
+        let req = request(
+            Method::POST,
+            &[("cookie", &cookie), ("x-csrf-token", token)],
+        );
+        assert_eq!(handler.handle(req).await.status, 200);
+
+        let req = request(
+            Method::POST,
+            &[("cookie", "better_auth_session=session_token")],
+        );
+        let response = handler.handle(req).await;
+        assert_eq!(response.status, 403);
+        assert_eq!(
+            response.body.unwrap()["error"]["code"],
+            "INVALID_CSRF_TOKEN"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Deletion of a user together with everything plugins keep about them.
+//!
+//! [`StorageAdapter::delete_user`] removes the user with their sessions and
+//! accounts, but knows nothing of plugin tables such as API keys, passkeys
+//! or permission grants. [`delete_user_fully`] first gives every plugin its
+//! [`AuthPlugin::on_before_user_delete`] hook to remove those rows, so no
+//! secrets are left behind for a user that no longer exists.
+
+use better_auth_events::{Event, EventBus, auth_events};
+use std::sync::Arc;
+
+use crate::context::AuthContext;
+use crate::error::AuthResult;
+use crate::traits::{AuthPlugin, StorageAdapter, run_in_transaction};
+
+/// Deletes the user with ID `user_id` and their plugin data.
+///
+/// Runs each plugin's [`AuthPlugin::on_before_user_delete`] hook in order,
+/// then [`StorageAdapter::delete_user`], all in one transaction when the
+/// adapter supports it (see [`run_in_transaction`]). Plugins that keep
+/// their rows in a store of their own clean those up outside it.
+///
+/// If a hook fails the user is not deleted and the error is returned.
+/// Otherwise `user.deleted` is emitted once on `events`, after the
+/// deletion is committed.
+pub async fn delete_user_fully(
+    ctx: &AuthContext,
+    user_id: &str,
+    plugins: &[&dyn AuthPlugin],
+    events: Option<&EventBus>,
+) -> AuthResult<()> {
+    run_in_transaction(&ctx.db, |db: Arc<dyn StorageAdapter>| async move {
+        let mut tx_ctx = AuthContext::new(db.clone());
+        tx_ctx.user = ctx.user.clone();
+        tx_ctx.session = ctx.session.clone();
+        tx_ctx.request = ctx.request.clone();
+        tx_ctx.data = ctx.data.clone();
+        for plugin in plugins {
+            plugin.on_before_user_delete(&tx_ctx, user_id).await?;
+        }
+        db.delete_user(user_id).await
+    })
+    .await?;
+
+    if let Some(bus) = events {
+        let payload = serde_json::json!({ "user_id": user_id });
+        bus.emit(Event::simple(auth_events::USER_DELETED, payload).with_source("core"))
+            .await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AuthError;
+    use crate::testing::TestStorage;
+    use crate::types::{Session, User};
+    use async_trait::async_trait;
+
+    /// Transactional storage holding `user_1` and a session of theirs.
+    fn storage() -> TestStorage {
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        TestStorage::new()
+            .transactional()
+            .with_user(user)
+            .with_session(Session::new("user_1".to_string()))
+    }
+
+    /// A plugin that deletes the user's sessions as its cleanup, or fails.
+    struct Cleanup {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl AuthPlugin for Cleanup {
+        fn id(&self) -> &'static str {
+            "cleanup"
+        }
+
+        fn name(&self) -> &'static str {
+            "Cleanup"
+        }
+
+        async fn on_before_user_delete(&self, ctx: &AuthContext, user_id: &str) -> AuthResult<()> {
+            if self.fail {
+                return Err(AuthError::database("cleanup failed"));
+            }
+            ctx.db.delete_sessions_by_user_id(user_id).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hooks_and_deletion_share_a_transaction() {
+        let storage = storage();
+        let ctx = AuthContext::new(Arc::new(storage.clone()));
+        let bus = EventBus::new();
+        let plugins: [&dyn AuthPlugin; 1] = [&Cleanup { fail: false }];
+
+        delete_user_fully(&ctx, "user_1", &plugins, Some(&bus)).await.unwrap();
+        assert_eq!(
+            storage.log(),
+            ["tx:delete_sessions_by_user_id", "tx:delete_user", "commit"]
+        );
+        assert!(storage.rows().users.is_empty());
+        assert!(storage.rows().sessions.is_empty());
+        let events = bus.events_of_type(auth_events::USER_DELETED).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload["user_id"], "user_1");
+    }
+
+    #[tokio::test]
+    async fn test_failed_hook_keeps_the_user() {
+        let storage = storage();
+        let ctx = AuthContext::new(Arc::new(storage.clone()));
+        let bus = EventBus::new();
+        let plugins: [&dyn AuthPlugin; 2] = [&Cleanup { fail: false }, &Cleanup { fail: true }];
+
+        let err = delete_user_fully(&ctx, "user_1", &plugins, Some(&bus))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthError::DatabaseError { .. }));
+        assert_eq!(storage.log(), ["tx:delete_sessions_by_user_id", "rollback"]);
+        assert_eq!(storage.rows().users.len(), 1);
+        assert_eq!(storage.rows().sessions.len(), 1);
+        assert!(bus.events_of_type(auth_events::USER_DELETED).await.is_empty());
+    }
+}
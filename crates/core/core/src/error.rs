@@ -5,6 +5,8 @@
 
 use thiserror::Error;
 
+use crate::types::AuthFactor;
+
 /// The main error type for Better Auth operations.
 ///
 /// This enum covers all error cases that can occur during authentication,
@@ -29,13 +31,42 @@ pub enum AuthError {
     SessionExpired,
 
     /// The user's email has not been verified.
+    ///
+    /// Carries the user's ID and email so a client can offer to resend the
+    /// verification message.
     #[error("Email not verified")]
-    EmailNotVerified,
+    EmailNotVerified { user_id: String, email: String },
 
     /// The account is locked or disabled.
     #[error("Account locked")]
     AccountLocked,
 
+    /// The action requires the user to authenticate again.
+    #[error("Recent authentication required")]
+    ReauthenticationRequired,
+
+    /// The action requires the user to verify one of `factors` on the
+    /// current session first.
+    #[error("Step-up authentication required: {}", join_factors(.factors))]
+    StepUpRequired { factors: Vec<AuthFactor> },
+
+    /// Verification is locked after too many failed attempts.
+    #[error("Too many failed attempts. Try again in {retry_after_seconds} seconds")]
+    TooManyAttempts { retry_after_seconds: u64 },
+
+    /// Signups are not allowed from this email domain.
+    #[error("Signups from '{domain}' are not allowed")]
+    EmailDomainNotAllowed { domain: String },
+
+    /// The caller is authenticated but not allowed to perform the operation.
+    #[error("Forbidden: {reason}")]
+    Forbidden { reason: String },
+
+    /// A cookie-authenticated request is missing its CSRF token, or the
+    /// token does not match.
+    #[error("Missing or invalid CSRF token")]
+    InvalidCsrfToken,
+
     // ==================== Validation Errors ====================
     /// A required field is missing.
     #[error("Missing required field: {field}")]
@@ -74,6 +105,10 @@ pub enum AuthError {
         value: String,
     },
 
+    /// The operation conflicts with the current state of the data.
+    #[error("Conflict: {reason}")]
+    Conflict { reason: String },
+
     /// A schema migration failed.
     #[error("Migration error: {message}")]
     MigrationError { message: String },
@@ -118,6 +153,10 @@ pub enum AuthError {
     #[error("Missing configuration: {key}")]
     MissingConfiguration { key: String },
 
+    /// One or more plugins failed configuration validation.
+    #[error("Invalid plugin configuration: {}", join_issues(.issues))]
+    InvalidPluginConfig { issues: Vec<ConfigIssue> },
+
     // ==================== Internal Errors ====================
     /// An internal error occurred.
     #[error("Internal error: {message}")]
@@ -173,6 +212,20 @@ impl AuthError {
         }
     }
 
+    /// Creates a new forbidden error.
+    pub fn forbidden(reason: impl Into<String>) -> Self {
+        Self::Forbidden {
+            reason: reason.into(),
+        }
+    }
+
+    /// Creates a new conflict error.
+    pub fn conflict(reason: impl Into<String>) -> Self {
+        Self::Conflict {
+            reason: reason.into(),
+        }
+    }
+
     /// Creates a new configuration error.
     pub fn config(message: impl Into<String>) -> Self {
         Self::ConfigurationError {
@@ -180,6 +233,20 @@ impl AuthError {
         }
     }
 
+    /// Creates a plugin configuration error from one or more problems.
+    pub fn invalid_config<I, S>(plugin: &str, messages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::InvalidPluginConfig {
+            issues: messages
+                .into_iter()
+                .map(|message| ConfigIssue::new(plugin, message))
+                .collect(),
+        }
+    }
+
     /// Returns true if this is a user-facing error (vs internal).
     pub fn is_user_error(&self) -> bool {
         matches!(
@@ -188,35 +255,131 @@ impl AuthError {
                 | Self::UserNotFound
                 | Self::SessionNotFound
                 | Self::SessionExpired
-                | Self::EmailNotVerified
+                | Self::EmailNotVerified { .. }
                 | Self::AccountLocked
+                | Self::ReauthenticationRequired
+                | Self::StepUpRequired { .. }
+                | Self::TooManyAttempts { .. }
+                | Self::EmailDomainNotAllowed { .. }
+                | Self::Forbidden { .. }
+                | Self::InvalidCsrfToken
                 | Self::MissingField { .. }
                 | Self::InvalidField { .. }
                 | Self::InvalidEmail
                 | Self::WeakPassword { .. }
+                | Self::Conflict { .. }
                 | Self::InvalidToken
                 | Self::TokenExpired
                 | Self::RateLimitExceeded { .. }
         )
     }
 
+    /// Returns a stable, machine-readable code for this error.
+    ///
+    /// Unlike the `Display` text, codes never change wording, so clients can
+    /// branch on them. They use the same `UPPER_SNAKE_CASE` style as plugin
+    /// error bodies, e.g. `{"error": {"code": "WEAK_PASSWORD", "message": ...}}`.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::InvalidCredentials => "INVALID_CREDENTIALS",
+            Self::UserNotFound => "USER_NOT_FOUND",
+            Self::SessionNotFound => "SESSION_NOT_FOUND",
+            Self::SessionExpired => "SESSION_EXPIRED",
+            Self::EmailNotVerified { .. } => "EMAIL_NOT_VERIFIED",
+            Self::AccountLocked => "ACCOUNT_LOCKED",
+            Self::ReauthenticationRequired => "REAUTHENTICATION_REQUIRED",
+            Self::StepUpRequired { .. } => "STEP_UP_REQUIRED",
+            Self::TooManyAttempts { .. } => "TOO_MANY_ATTEMPTS",
+            Self::EmailDomainNotAllowed { .. } => "EMAIL_DOMAIN_NOT_ALLOWED",
+            Self::Forbidden { .. } => "FORBIDDEN",
+            Self::InvalidCsrfToken => "INVALID_CSRF_TOKEN",
+            Self::MissingField { .. } => "MISSING_FIELD",
+            Self::InvalidField { .. } => "INVALID_FIELD",
+            Self::InvalidEmail => "INVALID_EMAIL",
+            Self::WeakPassword { .. } => "WEAK_PASSWORD",
+            Self::DatabaseError { .. } => "DATABASE_ERROR",
+            Self::NotFound { .. } => "NOT_FOUND",
+            Self::DuplicateEntry { .. } => "DUPLICATE_ENTRY",
+            Self::Conflict { .. } => "CONFLICT",
+            Self::MigrationError { .. } => "MIGRATION_ERROR",
+            Self::PluginError { .. } => "PLUGIN_ERROR",
+            Self::PluginNotEnabled { .. } => "PLUGIN_NOT_ENABLED",
+            Self::HookRejected { .. } => "HOOK_REJECTED",
+            Self::InvalidToken => "INVALID_TOKEN",
+            Self::TokenExpired => "TOKEN_EXPIRED",
+            Self::TokenGenerationFailed { .. } => "TOKEN_GENERATION_FAILED",
+            Self::RateLimitExceeded { .. } => "RATE_LIMIT_EXCEEDED",
+            Self::ConfigurationError { .. } => "CONFIGURATION_ERROR",
+            Self::MissingConfiguration { .. } => "MISSING_CONFIGURATION",
+            Self::InvalidPluginConfig { .. } => "INVALID_PLUGIN_CONFIG",
+            Self::InternalError { .. } => "INTERNAL_ERROR",
+            Self::SerializationError { .. } => "SERIALIZATION_ERROR",
+            Self::Unknown { .. } => "UNKNOWN_ERROR",
+        }
+    }
+
     /// Returns an HTTP status code appropriate for this error.
     pub fn status_code(&self) -> u16 {
         match self {
-            Self::InvalidCredentials | Self::InvalidToken => 401,
-            Self::AccountLocked | Self::EmailNotVerified => 403,
+            Self::InvalidCredentials | Self::InvalidToken | Self::TokenExpired => 401,
+            Self::AccountLocked
+            | Self::EmailNotVerified { .. }
+            | Self::ReauthenticationRequired
+            | Self::StepUpRequired { .. }
+            | Self::EmailDomainNotAllowed { .. }
+            | Self::Forbidden { .. }
+            | Self::InvalidCsrfToken => 403,
             Self::UserNotFound | Self::SessionNotFound | Self::NotFound { .. } => 404,
-            Self::DuplicateEntry { .. } => 409,
+            Self::DuplicateEntry { .. } | Self::Conflict { .. } => 409,
             Self::MissingField { .. }
             | Self::InvalidField { .. }
             | Self::InvalidEmail
             | Self::WeakPassword { .. } => 422,
-            Self::RateLimitExceeded { .. } => 429,
+            Self::TooManyAttempts { .. } | Self::RateLimitExceeded { .. } => 429,
             _ => 500,
         }
     }
 }
 
+/// Lists factors for [`AuthError::StepUpRequired`] messages.
+fn join_factors(factors: &[AuthFactor]) -> String {
+    let names: Vec<&str> = factors.iter().map(AuthFactor::as_str).collect();
+    names.join(", ")
+}
+
+/// A single problem found while validating a plugin's configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// ID of the plugin that reported the problem.
+    pub plugin: String,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl ConfigIssue {
+    /// Creates a new configuration issue.
+    pub fn new(plugin: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            plugin: plugin.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.plugin, self.message)
+    }
+}
+
+fn join_issues(issues: &[ConfigIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 /// A Result type alias using AuthError.
 pub type AuthResult<T> = Result<T, AuthError>;
 
@@ -243,6 +406,25 @@ mod tests {
         assert_eq!(AuthError::InvalidCredentials.status_code(), 401);
         assert_eq!(AuthError::UserNotFound.status_code(), 404);
         assert_eq!(AuthError::InvalidEmail.status_code(), 422);
+        assert_eq!(AuthError::TooManyAttempts { retry_after_seconds: 60 }.status_code(), 429);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(
+            AuthError::InvalidCredentials.error_code(),
+            "INVALID_CREDENTIALS"
+        );
+        let weak = AuthError::WeakPassword {
+            reason: "too short".into(),
+        };
+        assert_eq!(weak.error_code(), "WEAK_PASSWORD");
+        assert_eq!(weak.status_code(), 422);
+        let unverified = AuthError::EmailNotVerified {
+            user_id: "user_1".into(),
+            email: "a@example.com".into(),
+        };
+        assert_eq!(unverified.error_code(), "EMAIL_NOT_VERIFIED");
     }
 
     #[test]
@@ -0,0 +1,340 @@
+//! Export of the data stored about a user.
+//!
+//! [`export_user_data`] builds one JSON document with the user's record,
+//! sessions and linked accounts, plus what each plugin returns from
+//! [`AuthPlugin::export_user_data`] under `plugins.<plugin id>`.
+//! [`register_export_route`] serves it to the signed-in user at
+//! `GET /user/export`.
+//!
+//! Nothing secret is exported:
+//!
+//! - Fields marked [`private`](crate::schema::Field::private) in the schema
+//!   are removed from the records of their model. A plugin's output is
+//!   matched to models by key, so in `{"api_key": [...]}` each record loses
+//!   the `api_key` model's private fields.
+//! - Fields that the default [`RedactionPolicy`] treats as sensitive, such
+//!   as `token`, `password_hash` or `two_factor_secret`, are removed
+//!   wherever they appear, whatever they hold. This covers extension values
+//!   that no schema declares. The only exception is a model name grouping
+//!   a plugin's records, whose records are redacted instead.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+use crate::context::AuthContext;
+use crate::error::{AuthError, AuthResult};
+use crate::redact::RedactionPolicy;
+use crate::router::{Method, Request, RequestHandler, Response, Route, Router};
+use crate::schema::{ModelDefinition, SchemaDefinition};
+use crate::session::{SessionResolver, current_session, error_response};
+use crate::traits::AuthPlugin;
+
+/// Builds the data export for the user with ID `user_id`.
+///
+/// `schema` should include the models of `plugins`, so that their private
+/// fields are known. Fails with [`AuthError::UserNotFound`] if there is no
+/// such user, and with the first error a plugin returns.
+pub async fn export_user_data(
+    ctx: &AuthContext,
+    user_id: &str,
+    plugins: &[Arc<dyn AuthPlugin>],
+    schema: &SchemaDefinition,
+) -> AuthResult<Value> {
+    let user = ctx
+        .db
+        .get_user_by_id(user_id)
+        .await?
+        .ok_or(AuthError::UserNotFound)?;
+    let sessions = ctx.db.get_sessions_by_user_id(user_id).await?;
+    let accounts = ctx.db.get_accounts_by_user_id(user_id).await?;
+
+    let mut export = serde_json::json!({
+        "exported_at": Utc::now(),
+        "user": user,
+        "sessions": sessions,
+        "accounts": accounts,
+    });
+    for (key, model) in [
+        ("user", "user"),
+        ("sessions", "session"),
+        ("accounts", "account"),
+    ] {
+        if let Some(model) = schema.get_model(model) {
+            remove_private_fields(model, &mut export[key]);
+        }
+    }
+
+    let policy = RedactionPolicy::default();
+    remove_sensitive_fields(&policy, &mut export);
+
+    let mut plugin_data = Map::new();
+    for plugin in plugins {
+        let mut data = plugin.export_user_data(ctx, user_id).await?;
+        if data.is_null() {
+            continue;
+        }
+        if let Value::Object(records) = &mut data {
+            // Model names group records, so `{"api_key": [...]}` is kept.
+            records
+                .retain(|name, _| schema.get_model(name).is_some() || !policy.is_sensitive(name));
+            for (name, value) in records.iter_mut() {
+                if let Some(model) = schema.get_model(name) {
+                    remove_private_fields(model, value);
+                }
+                remove_sensitive_fields(&policy, value);
+            }
+        } else {
+            remove_sensitive_fields(&policy, &mut data);
+        }
+        plugin_data.insert(plugin.id().to_string(), data);
+    }
+    export["plugins"] = Value::Object(plugin_data);
+
+    Ok(export)
+}
+
+/// Removes the private fields of `model` from a record or list of records.
+fn remove_private_fields(model: &ModelDefinition, value: &mut Value) {
+    match value {
+        Value::Array(records) => {
+            for record in records {
+                remove_private_fields(model, record);
+            }
+        }
+        Value::Object(record) => {
+            for field in model.fields.iter().filter(|field| field.private) {
+                record.remove(&field.name);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Removes every field `policy` considers sensitive, at any depth.
+fn remove_sensitive_fields(policy: &RedactionPolicy, value: &mut Value) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                remove_sensitive_fields(policy, item);
+            }
+        }
+        Value::Object(map) => {
+            map.retain(|key, _| !policy.is_sensitive(key));
+            for item in map.values_mut() {
+                remove_sensitive_fields(policy, item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Adds `GET /user/export` to `router`.
+///
+/// The route returns the signed-in user's [`export_user_data`] document as
+/// a JSON attachment.
+pub fn register_export_route(
+    router: &mut Router,
+    resolver: SessionResolver,
+    plugins: Vec<Arc<dyn AuthPlugin>>,
+    schema: SchemaDefinition,
+) {
+    router.route(
+        Route::new(
+            Method::GET,
+            "/user/export",
+            ExportUserData::new(resolver, plugins, schema),
+        )
+        .summary("Export my data")
+        .description("Returns everything stored about the signed-in user, without secrets.")
+        .tag("user")
+        .requires_auth(),
+    );
+}
+
+/// Handler for `GET /user/export`.
+pub struct ExportUserData {
+    resolver: SessionResolver,
+    plugins: Vec<Arc<dyn AuthPlugin>>,
+    schema: SchemaDefinition,
+}
+
+impl ExportUserData {
+    /// Creates the handler.
+    pub fn new(
+        resolver: SessionResolver,
+        plugins: Vec<Arc<dyn AuthPlugin>>,
+        schema: SchemaDefinition,
+    ) -> Self {
+        Self {
+            resolver,
+            plugins,
+            schema,
+        }
+    }
+
+    async fn export(&self, req: &Request) -> AuthResult<Response> {
+        let session = current_session(&self.resolver, req).await?;
+        let ctx = AuthContext::new(self.resolver.storage.clone()).with_session(session.clone());
+        let export = export_user_data(&ctx, &session.user_id, &self.plugins, &self.schema).await?;
+        Ok(Response::ok()
+            .header(
+                "content-disposition",
+                r#"attachment; filename="user-data.json""#,
+            )
+            .json(export))
+    }
+}
+
+#[async_trait]
+impl RequestHandler for ExportUserData {
+    async fn handle(&self, req: Request) -> Response {
+        self.export(&req).await.unwrap_or_else(error_response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{Field, FieldType, SchemaBuilder};
+    use crate::testing::TestStorage;
+    use crate::types::{Account, Session, User};
+
+    /// Storage holding one user, `user_1`, with a session for the token
+    /// `"valid"` and a GitHub account.
+    fn storage() -> TestStorage {
+        let mut user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        user.set_extension("password_hash", "$argon2id$hash");
+        user.set_extension("two_factor_secret", "JBSWY3DPEHPK3PXP");
+        user.set_extension("two_factor_backup_codes", "[]");
+        user.set_extension("username", "jane");
+
+        let mut session = Session::new("user_1".to_string());
+        session.token = "valid".to_string();
+
+        let mut account =
+            Account::new("user_1".to_string(), "github".to_string(), "42".to_string());
+        account.access_token = Some("gho_access".to_string());
+
+        TestStorage::new()
+            .with_user(user)
+            .with_session(session)
+            .with_account(account)
+    }
+
+    /// A plugin keeping widgets whose `serial` is private.
+    struct Widgets;
+
+    #[async_trait]
+    impl AuthPlugin for Widgets {
+        fn id(&self) -> &'static str {
+            "widgets"
+        }
+
+        fn name(&self) -> &'static str {
+            "Widgets"
+        }
+
+        fn define_schema(&self, builder: &mut SchemaBuilder) {
+            builder.add_model_mut(
+                ModelDefinition::new("widget")
+                    .field(Field::primary_key("id"))
+                    .field(Field::new("serial", FieldType::Text).private()),
+            );
+            builder.add_model_mut(
+                ModelDefinition::new("widget_secret")
+                    .field(Field::primary_key("id"))
+                    .field(Field::new("value", FieldType::Text).private()),
+            );
+            builder.add_field_mut(
+                "user",
+                Field::optional("two_factor_backup_codes", FieldType::Text).private(),
+            );
+        }
+
+        async fn export_user_data(&self, _ctx: &AuthContext, user_id: &str) -> AuthResult<Value> {
+            Ok(serde_json::json!({
+                "widget": [{ "id": "w1", "owner": user_id, "serial": "SN-1" }],
+                "widget_secret": [{ "id": "s1", "label": "backup", "value": "hunter2" }],
+                "settings": {
+                    "theme": "dark",
+                    "api_key": "sk_live_abc",
+                    "token": ["tok_1", "tok_2"],
+                    "secret": { "value": "hunter2" },
+                },
+                "recovery_token": { "value": "rt_1" },
+            }))
+        }
+    }
+
+    /// A plugin with nothing to export.
+    struct Silent;
+
+    #[async_trait]
+    impl AuthPlugin for Silent {
+        fn id(&self) -> &'static str {
+            "silent"
+        }
+
+        fn name(&self) -> &'static str {
+            "Silent"
+        }
+    }
+
+    fn handler() -> ExportUserData {
+        let plugins: Vec<Arc<dyn AuthPlugin>> = vec![Arc::new(Widgets), Arc::new(Silent)];
+        let mut builder = SchemaBuilder::with_core();
+        for plugin in &plugins {
+            plugin.define_schema(&mut builder);
+        }
+        let resolver = SessionResolver::new(Arc::new(storage()));
+        ExportUserData::new(resolver, plugins, builder.build())
+    }
+
+    #[tokio::test]
+    async fn test_export_leaves_out_private_and_secret_fields() {
+        let mut req = Request::new(Method::GET, "/user/export");
+        req.headers
+            .insert("authorization".to_string(), "Bearer valid".to_string());
+        let response = handler().handle(req).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.headers["content-disposition"],
+            r#"attachment; filename="user-data.json""#
+        );
+
+        let export = response.body.unwrap();
+        let user = &export["user"];
+        assert_eq!(user["email"], "jane@example.com");
+        assert_eq!(user["username"], "jane");
+        for secret in ["password_hash", "two_factor_secret", "two_factor_backup_codes"] {
+            assert!(user.get(secret).is_none(), "{} was exported", secret);
+        }
+
+        assert_eq!(export["sessions"][0]["user_id"], "user_1");
+        assert!(export["sessions"][0].get("token").is_none());
+        assert_eq!(export["accounts"][0]["provider"], "github");
+        assert!(export["accounts"][0].get("access_token").is_none());
+
+        let widgets = &export["plugins"]["widgets"];
+        assert_eq!(widgets["widget"][0]["owner"], "user_1");
+        assert!(widgets["widget"][0].get("serial").is_none());
+        assert_eq!(
+            widgets["widget_secret"],
+            serde_json::json!([{ "id": "s1", "label": "backup" }])
+        );
+        assert_eq!(widgets["settings"], serde_json::json!({ "theme": "dark" }));
+        assert!(widgets.get("recovery_token").is_none());
+        assert!(export["plugins"].get("silent").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_requires_a_session() {
+        let response = handler()
+            .handle(Request::new(Method::GET, "/user/export"))
+            .await;
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body.unwrap()["error"]["code"], "SESSION_NOT_FOUND");
+    }
+}
@@ -5,23 +5,45 @@
 //! trait interfaces that plugins and adapters must implement.
 
 pub mod context;
+pub mod crypto;
+pub mod csrf;
+pub mod deletion;
 pub mod error;
+pub mod export;
+pub mod redact;
+pub mod redirect;
 pub mod router;
 pub mod schema;
+pub mod session;
+pub mod session_token;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod traits;
 pub mod types;
 
 // Re-export commonly used items at the crate root
-pub use error::{AuthError, AuthResult};
+pub use crypto::{constant_time_eq, hash_secret};
+pub use csrf::{CsrfConfig, CsrfProtect};
+pub use deletion::delete_user_fully;
+pub use error::{AuthError, AuthResult, ConfigIssue};
+pub use export::{export_user_data, register_export_route, ExportUserData};
 pub use schema::{
     core_schema, Field, FieldType, IndexDefinition, Migration, MigrationOp, MigrationRunner,
     ModelDefinition, ReferentialAction, SchemaBuilder, SchemaDefinition, SchemaDiff, SchemaDiffOp,
     SqlDialect,
 };
 pub use traits::{
-    AuthExtension, AuthPlugin, ExtensionProvider, HookContext, SchemaProvider, StorageAdapter,
+    AuthExtension, AuthPlugin, ExtensionProvider, HookContext, SchemaProvider, SessionStore,
+    StorageAdapter, StorageTransaction, run_in_transaction, validate_plugins,
 };
-pub use types::{Account, Session, User};
+pub use redact::{redact, Redact, RedactionPolicy};
+pub use session::{
+    end_replaced_session, register_session_routes, ApiKeyLookup, AuthScheme, ListSessions,
+    RejectUserFn, RequireFactor, RequireRecentAuth, ResolvedSession, RevokeSession,
+    SessionResolver,
+};
+pub use session_token::{SessionTokenClaims, SessionTokenStrategy};
+pub use types::{Account, AssuranceLevel, AuthFactor, Session, User, UserDeletion, UserFilter};
 
 // Re-export context types
 pub use context::{AuthContext, RequestParts, SignInCredentials, SignUpData};
@@ -34,4 +56,7 @@ pub use better_auth_events::{
 };
 
 // Re-export router types
-pub use router::{CookieOptions, Method, Request, RequestHandler, Response, Route, Router};
+pub use router::{
+    CookieOptions, HOST_PREFIX, Method, Request, RequestHandler, Response, Route, Router,
+    SECURE_PREFIX,
+};
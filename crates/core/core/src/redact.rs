@@ -0,0 +1,319 @@
+//! Redaction of secrets in logs and error messages.
+//!
+//! [`Redact`] wraps a value so that its `Debug` and `Display` output is
+//! masked, while [`RedactionPolicy`] scrubs sensitive values out of free-form
+//! text such as provider error bodies before they are logged or returned.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Placeholder written in place of redacted values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Field names scrubbed by the default policy.
+const DEFAULT_SENSITIVE_FIELDS: &[&str] = &[
+    "access_token",
+    "api_key",
+    "client_secret",
+    "code",
+    "id_token",
+    "password",
+    "password_hash",
+    "refresh_token",
+    "secret",
+    "token",
+];
+
+/// Maximum length of scrubbed text under the default policy.
+const DEFAULT_MAX_LEN: usize = 512;
+
+/// A value whose `Debug` and `Display` output is always redacted.
+///
+/// Serialization is transparent, so wrapping a field does not change its
+/// stored or wire representation.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Redact<T>(pub T);
+
+impl<T> Redact<T> {
+    /// Returns a reference to the wrapped value.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwraps the value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Redact<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Redact<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Redact<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Rules for scrubbing sensitive values out of text.
+///
+/// Values are masked when they follow a sensitive field name in
+/// `key=value`, `key: value`, or JSON `"key": "value"` form, or when they
+/// follow `Bearer`. Field names match case-insensitively and only as whole
+/// words, so `code` does not match `postcode`; see
+/// [`is_sensitive`](Self::is_sensitive).
+#[derive(Debug, Clone)]
+pub struct RedactionPolicy {
+    fields: HashSet<String>,
+    max_len: usize,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self {
+            fields: DEFAULT_SENSITIVE_FIELDS
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+            max_len: DEFAULT_MAX_LEN,
+        }
+    }
+}
+
+impl RedactionPolicy {
+    /// Creates a policy with the default sensitive fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sensitive field name.
+    pub fn field(mut self, name: impl Into<String>) -> Self {
+        self.fields.insert(name.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Sets the maximum length of scrubbed text; longer text is truncated.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Returns true if `name` is a sensitive field.
+    ///
+    /// Names that end in `_<field>` also match, so `two_factor_secret` is
+    /// covered by `secret`.
+    pub fn is_sensitive(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        self.fields.contains(&name)
+            || self.fields.iter().any(|field| {
+                name.strip_suffix(field.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('_'))
+            })
+    }
+
+    /// Masks sensitive values in `text` and truncates it to the maximum length.
+    pub fn scrub(&self, text: &str) -> String {
+        let lower = text.to_ascii_lowercase();
+        let mut spans = Vec::new();
+
+        let mut word_start = None;
+        for (i, c) in lower.char_indices().chain(std::iter::once((lower.len(), ' '))) {
+            let is_word = c.is_ascii_alphanumeric() || c == '_';
+            match (word_start, is_word) {
+                (None, true) => word_start = Some(i),
+                (Some(start), false) => {
+                    let word = &lower[start..i];
+                    let value = if word == "bearer" {
+                        bearer_value(&lower, i)
+                    } else if self.is_sensitive(word) {
+                        assigned_value(&lower, i)
+                    } else {
+                        None
+                    };
+                    spans.extend(value);
+                    word_start = None;
+                }
+                _ => {}
+            }
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for (start, end) in spans {
+            if start < last {
+                continue;
+            }
+            out.push_str(&text[last..start]);
+            out.push_str(REDACTED);
+            last = end;
+        }
+        out.push_str(&text[last..]);
+
+        truncate(out, self.max_len)
+    }
+
+    /// Masks sensitive fields in a JSON value, recursively.
+    pub fn scrub_json(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.is_sensitive(key) && !value.is_null() {
+                        *value = serde_json::Value::String(REDACTED.to_string());
+                    } else {
+                        self.scrub_json(value);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.scrub_json(item);
+                }
+            }
+            serde_json::Value::String(s) => *s = self.scrub(s),
+            _ => {}
+        }
+    }
+}
+
+/// Scrubs `text` with the default [`RedactionPolicy`].
+pub fn redact(text: &str) -> String {
+    RedactionPolicy::default().scrub(text)
+}
+
+/// Finds the value assigned to a field name ending at `pos`.
+fn assigned_value(text: &str, pos: usize) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut i = pos;
+    // Closing quote of a JSON key.
+    if matches!(bytes.get(i), Some(b'"' | b'\'')) {
+        i += 1;
+    }
+    i = skip_spaces(bytes, i);
+    if !matches!(bytes.get(i), Some(b'=' | b':')) {
+        return None;
+    }
+    i = skip_spaces(bytes, i + 1);
+    value_span(bytes, i)
+}
+
+/// Finds the credential following `Bearer`.
+fn bearer_value(text: &str, pos: usize) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let i = skip_spaces(bytes, pos);
+    if i == pos {
+        return None;
+    }
+    value_span(bytes, i)
+}
+
+fn value_span(bytes: &[u8], start: usize) -> Option<(usize, usize)> {
+    let (start, closing) = match bytes.get(start) {
+        Some(&q @ (b'"' | b'\'')) => (start + 1, Some(q)),
+        _ => (start, None),
+    };
+    let end = bytes[start..]
+        .iter()
+        .position(|&b| match closing {
+            Some(q) => b == q,
+            None => matches!(b, b'&' | b',' | b';' | b'}' | b']' | b'"' | b'\'') || b.is_ascii_whitespace(),
+        })
+        .map_or(bytes.len(), |offset| start + offset);
+    (end > start).then_some((start, end))
+}
+
+fn skip_spaces(bytes: &[u8], mut i: usize) -> usize {
+    while bytes.get(i).is_some_and(|b| *b == b' ' || *b == b'\t') {
+        i += 1;
+    }
+    i
+}
+
+fn truncate(mut text: String, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text;
+    }
+    let mut cut = max_len;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    text.truncate(cut);
+    text.push_str("...(truncated)");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_wrapper() {
+        let secret = Redact("hunter2".to_string());
+        assert_eq!(format!("{:?}", secret), REDACTED);
+        assert_eq!(secret.to_string(), REDACTED);
+        assert_eq!(secret.expose(), "hunter2");
+        assert_eq!(serde_json::to_string(&secret).unwrap(), "\"hunter2\"");
+    }
+
+    #[test]
+    fn test_scrub_masks_token_in_error_string() {
+        let body = r#"{"error":"invalid_grant","access_token": "ya29.a0AfH6SM", "refresh_token":"1//0g"}"#;
+        let scrubbed = redact(&format!("Google token exchange failed: {}", body));
+
+        assert!(!scrubbed.contains("ya29.a0AfH6SM"));
+        assert!(!scrubbed.contains("1//0g"));
+        assert!(scrubbed.contains("invalid_grant"));
+        assert!(scrubbed.contains(r#""access_token": "[REDACTED]""#));
+    }
+
+    #[test]
+    fn test_scrub_form_and_bearer() {
+        let text = "POST /token?code=4/abc123&client_secret=s3cr3t&state=xyz Authorization: Bearer eyJhbGci.x.y";
+        let scrubbed = redact(text);
+
+        assert_eq!(
+            scrubbed,
+            "POST /token?code=[REDACTED]&client_secret=[REDACTED]&state=xyz Authorization: Bearer [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_scrub_whole_words_only() {
+        assert_eq!(redact("postcode=12345"), "postcode=12345");
+        assert_eq!(redact("Password: hunter2"), "Password: [REDACTED]");
+        assert_eq!(redact("two_factor_secret=JBSW"), "two_factor_secret=[REDACTED]");
+        assert_eq!(redact("token_type=bearer"), "token_type=bearer");
+    }
+
+    #[test]
+    fn test_scrub_truncates() {
+        let scrubbed = RedactionPolicy::new().max_len(10).scrub(&"x".repeat(50));
+        assert_eq!(scrubbed, "xxxxxxxxxx...(truncated)");
+    }
+
+    #[test]
+    fn test_scrub_json() {
+        let mut value = json!({
+            "user": { "email": "a@b.com", "password": "hunter2" },
+            "tokens": [{ "access_token": "abc" }],
+            "note": "code=xyz",
+        });
+        RedactionPolicy::default().scrub_json(&mut value);
+
+        assert_eq!(value["user"]["password"], REDACTED);
+        assert_eq!(value["user"]["email"], "a@b.com");
+        assert_eq!(value["tokens"][0]["access_token"], REDACTED);
+        assert_eq!(value["note"], "code=[REDACTED]");
+    }
+}
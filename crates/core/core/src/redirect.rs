@@ -0,0 +1,114 @@
+//! Validation of user-supplied redirect URLs.
+//!
+//! Redirecting to whatever URL a request names is an open redirect: a link
+//! on the real domain that lands on a phishing page. A redirect is allowed
+//! if it is a relative path on this site, or an absolute `http(s)` URL whose
+//! origin is explicitly allowed.
+
+use crate::error::{AuthError, AuthResult};
+
+/// Returns true if `url` is a relative path, or an absolute URL on one of
+/// `allowed_origins`, such as `https://app.example.com`.
+pub fn is_allowed_redirect(url: &str, allowed_origins: &[String]) -> bool {
+    // `//host` and `/\host` are protocol-relative to browsers.
+    if url.starts_with('/') && !url.starts_with("//") && !url.starts_with("/\\") {
+        return true;
+    }
+    origin(url).is_some_and(|origin| {
+        allowed_origins
+            .iter()
+            .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+    })
+}
+
+/// Checks a redirect URL given in the request field `field`.
+///
+/// Fails with [`AuthError::InvalidField`] if the redirect is not allowed.
+pub fn validate_redirect(field: &str, url: &str, allowed_origins: &[String]) -> AuthResult<()> {
+    if is_allowed_redirect(url, allowed_origins) {
+        Ok(())
+    } else {
+        Err(AuthError::InvalidField {
+            field: field.to_string(),
+            reason: "redirect must be a relative path or use an allowed origin".to_string(),
+        })
+    }
+}
+
+/// Returns `url` if it is allowed, otherwise `fallback`.
+pub fn safe_redirect<'a>(
+    url: Option<&'a str>,
+    allowed_origins: &[String],
+    fallback: &'a str,
+) -> &'a str {
+    url.filter(|url| is_allowed_redirect(url, allowed_origins))
+        .unwrap_or(fallback)
+}
+
+/// Returns the `scheme://authority` of an absolute http(s) URL.
+fn origin(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return None;
+    }
+    let authority_len = rest.find(['/', '?', '#', '\\']).unwrap_or(rest.len());
+    Some(&url[..scheme.len() + 3 + authority_len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed() -> Vec<String> {
+        vec!["https://app.example.com".to_string()]
+    }
+
+    #[test]
+    fn test_relative_paths_are_allowed() {
+        assert!(is_allowed_redirect("/", &[]));
+        assert!(is_allowed_redirect("/dashboard?tab=1", &[]));
+    }
+
+    #[test]
+    fn test_allowed_origin() {
+        assert!(is_allowed_redirect("https://app.example.com", &allowed()));
+        assert!(is_allowed_redirect(
+            "https://APP.example.com/home?x=1",
+            &allowed()
+        ));
+        assert!(!is_allowed_redirect("https://app.example.com/home", &[]));
+        assert!(!is_allowed_redirect(
+            "http://app.example.com/home",
+            &allowed()
+        ));
+    }
+
+    #[test]
+    fn test_rejects_open_redirects() {
+        for url in [
+            "//evil.com",
+            "/\\evil.com",
+            "https://evil.com",
+            "https://app.example.com.evil.com",
+            "https://app.example.com@evil.com",
+            "javascript:alert(1)",
+            "evil.com",
+        ] {
+            assert!(!is_allowed_redirect(url, &allowed()), "{url}");
+        }
+        assert!(matches!(
+            validate_redirect("callbackURL", "//evil.com", &allowed()),
+            Err(AuthError::InvalidField { field, .. }) if field == "callbackURL"
+        ));
+    }
+
+    #[test]
+    fn test_safe_redirect_falls_back() {
+        assert_eq!(
+            safe_redirect(Some("https://evil.com"), &allowed(), "/"),
+            "/"
+        );
+        assert_eq!(safe_redirect(None, &allowed(), "/"), "/");
+        assert_eq!(safe_redirect(Some("/home"), &allowed(), "/"), "/home");
+    }
+}
@@ -93,9 +93,15 @@ pub struct Response {
     /// HTTP status code.
     pub status: u16,
     /// Response headers.
+    ///
+    /// Several cookies share the `set-cookie` entry, one per line; see
+    /// [`Response::set_cookies`].
     pub headers: HashMap<String, String>,
     /// Response body (JSON).
     pub body: Option<Value>,
+    /// Response body sent as-is instead of `body`, for non-JSON content
+    /// such as an HTML page.
+    pub raw_body: Option<String>,
 }
 
 impl Response {
@@ -105,6 +111,7 @@ impl Response {
             status,
             headers: HashMap::new(),
             body: None,
+            raw_body: None,
         }
     }
 
@@ -151,11 +158,29 @@ impl Response {
     /// Sets the response body as JSON.
     pub fn json<T: Serialize>(mut self, body: T) -> Self {
         self.body = serde_json::to_value(body).ok();
+        self.raw_body = None;
         self.headers
             .insert("content-type".to_string(), "application/json".to_string());
         self
     }
 
+    /// Sets a non-JSON body and its content type.
+    ///
+    /// The body is kept in `raw_body` and sent as-is, replacing any JSON
+    /// body.
+    pub fn text(mut self, body: impl Into<String>, content_type: impl Into<String>) -> Self {
+        self.body = None;
+        self.raw_body = Some(body.into());
+        self.headers
+            .insert("content-type".to_string(), content_type.into());
+        self
+    }
+
+    /// Sets the response body as an HTML page.
+    pub fn html(self, body: impl Into<String>) -> Self {
+        self.text(body, "text/html; charset=utf-8")
+    }
+
     /// Sets a header.
     pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
         self.headers.insert(name.into().to_lowercase(), value.into());
@@ -163,11 +188,24 @@ impl Response {
     }
 
     /// Sets a cookie.
-    pub fn cookie(self, name: &str, value: &str, options: CookieOptions) -> Self {
+    ///
+    /// Each call adds another cookie rather than replacing earlier ones.
+    /// Names with a `__Host-` or `__Secure-` prefix get the attributes that
+    /// prefix requires; see [`CookieOptions::for_name`].
+    pub fn cookie(mut self, name: &str, value: &str, options: CookieOptions) -> Self {
+        let options = options.for_name(name);
         let cookie_str = format!(
-            "{}={}{}{}{}{}",
+            "{}={}{}{}{}{}{}{}",
             name,
             value,
+            options
+                .path
+                .map(|p| format!("; Path={}", p))
+                .unwrap_or_default(),
+            options
+                .domain
+                .map(|d| format!("; Domain={}", d))
+                .unwrap_or_default(),
             if options.http_only { "; HttpOnly" } else { "" },
             if options.secure { "; Secure" } else { "" },
             options
@@ -179,10 +217,34 @@ impl Response {
                 .map(|a| format!("; Max-Age={}", a))
                 .unwrap_or_default(),
         );
-        self.header("set-cookie", cookie_str)
+        match self.headers.get_mut("set-cookie") {
+            Some(cookies) => {
+                cookies.push('\n');
+                cookies.push_str(&cookie_str);
+            }
+            None => {
+                self.headers.insert("set-cookie".to_string(), cookie_str);
+            }
+        }
+        self
+    }
+
+    /// Returns each `Set-Cookie` value, in the order the cookies were set.
+    pub fn set_cookies(&self) -> impl Iterator<Item = &str> {
+        self.headers
+            .get("set-cookie")
+            .into_iter()
+            .flat_map(|cookies| cookies.lines())
     }
 }
 
+/// Cookie name prefix that browsers only accept on `Secure` cookies with
+/// `Path=/` and no `Domain`, pinning the cookie to one host.
+pub const HOST_PREFIX: &str = "__Host-";
+
+/// Cookie name prefix that browsers only accept on `Secure` cookies.
+pub const SECURE_PREFIX: &str = "__Secure-";
+
 /// Cookie options.
 #[derive(Debug, Clone, Default)]
 pub struct CookieOptions {
@@ -207,6 +269,31 @@ impl CookieOptions {
             ..Default::default()
         }
     }
+
+    /// Secure defaults that also meet the `__Host-` prefix rules: `Secure`,
+    /// `Path=/` and no `Domain`.
+    pub fn host() -> Self {
+        Self {
+            path: Some("/".to_string()),
+            ..Self::secure()
+        }
+    }
+
+    /// Adjusts these options to the rules of `name`'s prefix, if any.
+    ///
+    /// Browsers silently drop a `__Host-` cookie unless it is `Secure`, has
+    /// `Path=/` and has no `Domain`, and a `__Secure-` cookie unless it is
+    /// `Secure`. Other names are left as they are.
+    pub fn for_name(mut self, name: &str) -> Self {
+        if name.starts_with(HOST_PREFIX) {
+            self.secure = true;
+            self.path = Some("/".to_string());
+            self.domain = None;
+        } else if name.starts_with(SECURE_PREFIX) {
+            self.secure = true;
+        }
+        self
+    }
 }
 
 /// Trait for request handlers.
@@ -220,7 +307,7 @@ pub trait RequestHandler: Send + Sync {
 pub struct Route {
     /// The HTTP method.
     pub method: Method,
-    /// The path pattern (e.g., "/users/:id").
+    /// The path pattern (e.g., "/users/:id" or "/files/*path").
     pub path: String,
     /// The handler function.
     pub handler: Box<dyn RequestHandler>,
@@ -239,6 +326,10 @@ pub struct RouteMetadata {
     pub tags: Vec<String>,
     /// Whether authentication is required.
     pub requires_auth: bool,
+    /// Name of the schema describing the JSON request body.
+    pub request_schema: Option<String>,
+    /// Name of the schema describing the JSON success response.
+    pub response_schema: Option<String>,
 }
 
 impl Route {
@@ -275,6 +366,63 @@ impl Route {
         self.metadata.requires_auth = true;
         self
     }
+
+    /// Sets the name of the request body schema.
+    pub fn request_schema(mut self, schema: impl Into<String>) -> Self {
+        self.metadata.request_schema = Some(schema.into());
+        self
+    }
+
+    /// Sets the name of the success response schema.
+    pub fn response_schema(mut self, schema: impl Into<String>) -> Self {
+        self.metadata.response_schema = Some(schema.into());
+        self
+    }
+
+    /// Matches `path` against this route's pattern, returning the values of
+    /// its `:name` and `*name` segments.
+    ///
+    /// A `:name` segment captures one non-empty segment. A `*name` segment
+    /// must come last and captures the rest of the path, at least one
+    /// segment, without its leading slash. Leading and trailing slashes are
+    /// ignored on both the pattern and the path.
+    pub fn match_path(&self, path: &str) -> Option<HashMap<String, String>> {
+        let pattern: Vec<&str> = self.path.trim_matches('/').split('/').collect();
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let mut params = HashMap::new();
+        for (i, expected) in pattern.iter().enumerate() {
+            if let Some(name) = expected.strip_prefix('*') {
+                let rest = segments.get(i..)?;
+                if rest.is_empty() || rest.iter().any(|segment| segment.is_empty()) {
+                    return None;
+                }
+                params.insert(name.to_string(), rest.join("/"));
+                return Some(params);
+            }
+            let segment = segments.get(i)?;
+            match expected.strip_prefix(':') {
+                Some(name) if !segment.is_empty() => {
+                    params.insert(name.to_string(), segment.to_string());
+                }
+                None if expected == segment => {}
+                _ => return None,
+            }
+        }
+        (pattern.len() == segments.len()).then_some(params)
+    }
+
+    /// Ranks each pattern segment: literal, then `:param`, then `*wildcard`.
+    fn specificity(&self) -> Vec<u8> {
+        self.path
+            .trim_matches('/')
+            .split('/')
+            .map(|segment| match segment.chars().next() {
+                Some(':') => 1,
+                Some('*') => 2,
+                _ => 0,
+            })
+            .collect()
+    }
 }
 
 /// A router that collects routes from plugins.
@@ -324,6 +472,25 @@ impl Router {
         self.routes.iter()
     }
 
+    /// Finds the route for `method` and `path`, which is relative to the
+    /// base path, along with the path parameters it captures.
+    ///
+    /// When several routes match, literal segments win over `:param`
+    /// segments, which win over `*wildcard` segments, comparing from the
+    /// left; remaining ties go to the route registered first. So
+    /// `/roles/me` is preferred to `/roles/:id` for `/roles/me`.
+    pub fn match_route(
+        &self,
+        method: Method,
+        path: &str,
+    ) -> Option<(&Route, HashMap<String, String>)> {
+        self.routes
+            .iter()
+            .filter(|route| route.method == method)
+            .filter_map(|route| Some((route, route.match_path(path)?)))
+            .min_by_key(|(route, _)| route.specificity())
+    }
+
     /// Returns the number of routes.
     pub fn len(&self) -> usize {
         self.routes.len()
@@ -347,3 +514,173 @@ impl Default for Router {
         Self::new("/api/auth")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Noop;
+
+    #[async_trait]
+    impl RequestHandler for Noop {
+        async fn handle(&self, _req: Request) -> Response {
+            Response::ok()
+        }
+    }
+
+    #[test]
+    fn test_cookies_accumulate() {
+        let options = CookieOptions {
+            path: Some("/".to_string()),
+            ..CookieOptions::secure()
+        };
+        let response = Response::ok().cookie("a", "1", options);
+        let response = response.cookie("b", "2", CookieOptions::new());
+        let cookies: Vec<&str> = response.set_cookies().collect();
+        assert_eq!(
+            cookies,
+            ["a=1; Path=/; HttpOnly; Secure; SameSite=Lax", "b=2"]
+        );
+    }
+
+    #[test]
+    fn test_cookie_path_and_domain() {
+        let options = CookieOptions {
+            path: Some("/api/auth".to_string()),
+            domain: Some("example.com".to_string()),
+            max_age: Some(60),
+            ..CookieOptions::secure()
+        };
+        let response = Response::ok().cookie("session", "abc", options);
+        assert_eq!(
+            response.headers["set-cookie"],
+            "session=abc; Path=/api/auth; Domain=example.com; HttpOnly; Secure; SameSite=Lax; \
+             Max-Age=60"
+        );
+
+        let response = Response::ok().cookie("session", "abc", CookieOptions::new());
+        assert_eq!(response.headers["set-cookie"], "session=abc");
+    }
+
+    #[test]
+    fn test_cookie_prefix_rules() {
+        let options = CookieOptions {
+            path: Some("/api".to_string()),
+            domain: Some("example.com".to_string()),
+            ..CookieOptions::new()
+        };
+
+        let response = Response::ok().cookie("__Host-session", "abc", options.clone());
+        assert_eq!(
+            response.headers["set-cookie"],
+            "__Host-session=abc; Path=/; Secure"
+        );
+
+        let response = Response::ok().cookie("__Secure-session", "abc", options);
+        assert_eq!(
+            response.headers["set-cookie"],
+            "__Secure-session=abc; Path=/api; Domain=example.com; Secure"
+        );
+
+        let host = CookieOptions::host();
+        assert_eq!(
+            (host.secure, host.path.as_deref(), host.domain),
+            (true, Some("/"), None)
+        );
+    }
+
+    #[test]
+    fn test_match_route() {
+        let mut router = Router::default();
+        router.get("/sessions", Noop);
+        router.delete("/sessions/:id", Noop);
+        router.get("/orgs/:org_id/members/:id", Noop);
+
+        let (route, params) = router.match_route(Method::GET, "/sessions").unwrap();
+        assert_eq!(route.path, "/sessions");
+        assert!(params.is_empty());
+
+        let (route, params) = router.match_route(Method::DELETE, "/sessions/abc").unwrap();
+        assert_eq!(route.path, "/sessions/:id");
+        assert_eq!(params["id"], "abc");
+
+        let (_, params) = router
+            .match_route(Method::GET, "/orgs/o1/members/m2")
+            .unwrap();
+        assert_eq!(
+            (params["org_id"].as_str(), params["id"].as_str()),
+            ("o1", "m2")
+        );
+
+        assert!(router.match_route(Method::GET, "/sessions/abc").is_none());
+        assert!(router.match_route(Method::DELETE, "/sessions/").is_none());
+        assert!(
+            router
+                .match_route(Method::GET, "/sessions/abc/extra")
+                .is_none()
+        );
+        assert!(
+            router
+                .match_route(Method::GET, "/orgs/o1/members")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_match_route_nested_params_and_trailing_slashes() {
+        let mut router = Router::default();
+        router.get("/access/roles/:id/permissions/:perm_id", Noop);
+        router.get("/access/roles/", Noop);
+
+        for path in [
+            "/access/roles/42/permissions/read",
+            "/access/roles/42/permissions/read/",
+            "access/roles/42/permissions/read",
+        ] {
+            let (route, params) = router.match_route(Method::GET, path).unwrap();
+            assert_eq!(route.path, "/access/roles/:id/permissions/:perm_id");
+            assert_eq!(params.len(), 2);
+            assert_eq!(params["id"], "42");
+            assert_eq!(params["perm_id"], "read");
+        }
+
+        for path in ["/access/roles", "/access/roles/"] {
+            let (route, _) = router.match_route(Method::GET, path).unwrap();
+            assert_eq!(route.path, "/access/roles/");
+        }
+
+        assert!(
+            router
+                .match_route(Method::GET, "/access/roles//permissions/read")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_match_route_wildcard() {
+        let mut router = Router::default();
+        router.get("/files/*path", Noop);
+
+        let (_, params) = router.match_route(Method::GET, "/files/a/b/c.txt").unwrap();
+        assert_eq!(params["path"], "a/b/c.txt");
+        let (_, params) = router.match_route(Method::GET, "/files/a/").unwrap();
+        assert_eq!(params["path"], "a");
+
+        assert!(router.match_route(Method::GET, "/files").is_none());
+        assert!(router.match_route(Method::GET, "/files/").is_none());
+        assert!(router.match_route(Method::GET, "/files/a//b").is_none());
+    }
+
+    #[test]
+    fn test_match_route_prefers_literal_segments() {
+        let mut router = Router::default();
+        router.get("/roles/*rest", Noop);
+        router.get("/roles/:id", Noop);
+        router.get("/roles/me", Noop);
+
+        let path = |p| router.match_route(Method::GET, p).unwrap().0.path.as_str();
+        assert_eq!(path("/roles/me"), "/roles/me");
+        assert_eq!(path("/roles/42"), "/roles/:id");
+        assert_eq!(path("/roles/42/permissions"), "/roles/*rest");
+    }
+}
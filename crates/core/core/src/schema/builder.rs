@@ -100,6 +100,15 @@ impl SchemaBuilder {
         self
     }
 
+    /// Adds an index to an existing model (mutable reference version).
+    pub fn add_index_mut(&mut self, model: &str, index: IndexDefinition) -> &mut Self {
+        self.extension_indexes
+            .entry(model.to_string())
+            .or_default()
+            .push(index);
+        self
+    }
+
     /// Builds the final schema definition.
     pub fn build(mut self) -> SchemaDefinition {
         // Apply extensions to models
@@ -124,6 +124,50 @@ impl SchemaDiff {
         Self { operations }
     }
 
+    /// Computes the additive operations that bring `current` up to `models`.
+    ///
+    /// Missing tables are created, in an order where every table follows
+    /// the tables it references, and missing columns and indexes are added.
+    /// Existing columns are never altered and nothing is dropped, so running
+    /// this against an up-to-date schema yields no operations. This is the
+    /// diff adapters apply in [`StorageAdapter::migrate`].
+    ///
+    /// [`StorageAdapter::migrate`]: crate::traits::StorageAdapter::migrate
+    pub fn additions(current: &SchemaDefinition, models: &[ModelDefinition]) -> Self {
+        let mut operations = Vec::new();
+        for model in dependency_order(models) {
+            let existing = current.get_model(&model.name);
+            match existing {
+                None => operations.push(SchemaDiffOp::CreateTable {
+                    model: model.clone(),
+                }),
+                Some(existing) => operations.extend(
+                    model
+                        .fields
+                        .iter()
+                        .filter(|field| existing.get_field(&field.name).is_none())
+                        .map(|field| SchemaDiffOp::AddColumn {
+                            table_name: model.name.clone(),
+                            field: field.clone(),
+                        }),
+                ),
+            }
+            let has_index =
+                |name: &str| existing.is_some_and(|m| m.indexes.iter().any(|i| i.name == name));
+            operations.extend(
+                model
+                    .indexes
+                    .iter()
+                    .filter(|index| !has_index(&index.name))
+                    .map(|index| SchemaDiffOp::CreateIndex {
+                        table_name: model.name.clone(),
+                        index: index.clone(),
+                    }),
+            );
+        }
+        Self { operations }
+    }
+
     /// Checks if a field needs to be altered.
     fn field_needs_alteration(current: &Field, target: &Field) -> bool {
         current.field_type != target.field_type
@@ -187,9 +231,44 @@ impl SchemaDiff {
     }
 }
 
+/// Orders models so every table comes after the tables it references.
+///
+/// References to tables outside `models`, and reference cycles, are left
+/// to the database to report.
+fn dependency_order(models: &[ModelDefinition]) -> Vec<&ModelDefinition> {
+    fn visit<'a>(
+        model: &'a ModelDefinition,
+        models: &'a [ModelDefinition],
+        ordered: &mut Vec<&'a ModelDefinition>,
+        visiting: &mut Vec<&'a str>,
+    ) {
+        if ordered.iter().any(|m| m.name == model.name) || visiting.contains(&model.name.as_str()) {
+            return;
+        }
+        visiting.push(&model.name);
+        let referenced = model
+            .fields
+            .iter()
+            .filter_map(|field| field.references.as_deref()?.split_once('.'))
+            .filter_map(|(table, _)| models.iter().find(|m| m.name == table));
+        for dependency in referenced {
+            visit(dependency, models, ordered, visiting);
+        }
+        visiting.pop();
+        ordered.push(model);
+    }
+
+    let mut ordered = Vec::with_capacity(models.len());
+    for model in models {
+        visit(model, models, &mut ordered, &mut Vec::new());
+    }
+    ordered
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::core_schema;
 
     #[test]
     fn test_detect_new_table() {
@@ -237,4 +316,58 @@ mod tests {
         let diff = SchemaDiff::compute(&schema, &schema);
         assert!(diff.is_empty());
     }
+
+    #[test]
+    fn test_tables_follow_the_tables_they_reference() {
+        let mut models = core_schema();
+        models.reverse();
+        let names: Vec<&str> = dependency_order(&models)
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(names[0], "user");
+        assert_eq!(names.len(), 3);
+    }
+
+    #[test]
+    fn test_additions_only_add_what_is_missing() {
+        let models = core_schema();
+        let mut current = SchemaDefinition::new();
+        current.add_model(
+            ModelDefinition::new("user")
+                .field(Field::primary_key("id"))
+                .field(Field::new("email", FieldType::Text)),
+        );
+
+        let diff = SchemaDiff::additions(&current, &models);
+        let added: Vec<&str> = diff
+            .operations
+            .iter()
+            .filter_map(|op| match op {
+                SchemaDiffOp::AddColumn { field, .. } => Some(field.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            added.join(" "),
+            "email_verified name image created_at updated_at deleted_at"
+        );
+        // The differing `email` type is left alone.
+        assert!(!diff.has_destructive_operations());
+        assert!(matches!(
+            &diff.operations[6],
+            SchemaDiffOp::CreateIndex { index, .. } if index.name == "idx_user_email"
+        ));
+        assert!(matches!(
+            &diff.operations[7],
+            SchemaDiffOp::CreateTable { model } if model.name == "session"
+        ));
+
+        // Once everything exists there is nothing left to do.
+        let mut migrated = SchemaDefinition::new();
+        for model in models.iter().cloned() {
+            migrated.add_model(model);
+        }
+        assert!(SchemaDiff::additions(&migrated, &models).is_empty());
+    }
 }
@@ -73,23 +73,63 @@ impl Migration {
     }
 }
 
+/// Largest index key InnoDB accepts with the `DYNAMIC` row format, in bytes.
+const MYSQL_MAX_KEY_BYTES: u32 = 3072;
+
+/// Bytes per character in `utf8mb4`.
+const MYSQL_BYTES_PER_CHAR: u32 = 4;
+
 /// Generates migrations from schema diffs.
+///
+/// # MySQL
+///
+/// Tables are created as InnoDB, `utf8mb4`, with the `DYNAMIC` row format,
+/// which allows index keys of up to 3072 bytes (768 `utf8mb4` characters).
+/// `VARCHAR(255)` columns therefore fit in an index whole, so a unique index
+/// on `email` stays exact. Index columns that would exceed the limit, and
+/// `TEXT` or binary columns, which MySQL can only index by prefix, are given
+/// a prefix length. The runner needs the column types for that; tables
+/// created in the same migration are known, others can be supplied with
+/// [`models`](Self::models).
+///
+/// Timestamps are `DATETIME(6)`, holding UTC, and a required `updated_at`
+/// column without a default is maintained by `ON UPDATE CURRENT_TIMESTAMP`.
 pub struct MigrationRunner {
     dialect: SqlDialect,
+    models: Vec<super::ModelDefinition>,
 }
 
 impl MigrationRunner {
     /// Creates a new migration runner for the given SQL dialect.
     pub fn new(dialect: SqlDialect) -> Self {
-        Self { dialect }
+        Self {
+            dialect,
+            models: Vec::new(),
+        }
+    }
+
+    /// Sets the models whose column types size MySQL index key prefixes.
+    pub fn models(mut self, models: &[super::ModelDefinition]) -> Self {
+        self.models = models.to_vec();
+        self
     }
 
     /// Generates a migration from a schema diff.
     pub fn generate_migration(&self, name: &str, diff: &SchemaDiff) -> Migration {
         let mut migration = Migration::new(name);
+        let created = diff.operations.iter().filter_map(|op| match op {
+            SchemaDiffOp::CreateTable { model } => Some(model),
+            _ => None,
+        });
+        let mut models = self.models.clone();
+        models.extend(created.cloned());
+        let runner = Self {
+            dialect: self.dialect,
+            models,
+        };
 
         for op in &diff.operations {
-            if let Some(sql) = self.diff_op_to_sql(op) {
+            if let Some(sql) = runner.diff_op_to_sql(op) {
                 migration.add_operation(sql);
             }
         }
@@ -124,14 +164,14 @@ impl MigrationRunner {
         }
     }
 
-    fn generate_create_table(&self, model: &super::ModelDefinition) -> String {
+    pub(super) fn generate_create_table(&self, model: &super::ModelDefinition) -> String {
         let mut columns = Vec::new();
         let mut constraints = Vec::new();
 
         for field in &model.fields {
             let mut col = format!(
                 "{} {}",
-                field.name,
+                self.quote(&field.name),
                 field.field_type.sql_type(self.dialect)
             );
 
@@ -142,9 +182,17 @@ impl MigrationRunner {
                 col.push_str(" NOT NULL");
             }
             if field.unique && !field.primary_key {
-                col.push_str(" UNIQUE");
+                if self.dialect == SqlDialect::Mysql && is_unbounded(&field.field_type) {
+                    constraints.push(format!(
+                        "UNIQUE KEY ({}({}))",
+                        self.quote(&field.name),
+                        MYSQL_MAX_KEY_BYTES / MYSQL_BYTES_PER_CHAR
+                    ));
+                } else {
+                    col.push_str(" UNIQUE");
+                }
             }
-            if let Some(default) = &field.default {
+            if let Some(default) = self.default_sql(field) {
                 col.push_str(&format!(" DEFAULT {}", default));
             }
 
@@ -156,7 +204,9 @@ impl MigrationRunner {
                 if parts.len() == 2 {
                     let mut fk = format!(
                         "FOREIGN KEY ({}) REFERENCES {}({})",
-                        field.name, parts[0], parts[1]
+                        self.quote(&field.name),
+                        self.quote(parts[0]),
+                        self.quote(parts[1])
                     );
                     if let Some(on_delete) = &field.on_delete {
                         fk.push_str(&format!(" ON DELETE {}", on_delete.as_sql()));
@@ -167,19 +217,26 @@ impl MigrationRunner {
         }
 
         let all_parts: Vec<String> = columns.into_iter().chain(constraints).collect();
+        let options = match self.dialect {
+            SqlDialect::Mysql => {
+                " ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci ROW_FORMAT=DYNAMIC"
+            }
+            SqlDialect::Postgres | SqlDialect::Sqlite => "",
+        };
 
         format!(
-            "CREATE TABLE IF NOT EXISTS {} (\n  {}\n)",
-            model.name,
-            all_parts.join(",\n  ")
+            "CREATE TABLE IF NOT EXISTS {} (\n  {}\n){}",
+            self.quote(&model.name),
+            all_parts.join(",\n  "),
+            options
         )
     }
 
     fn generate_add_column(&self, table: &str, field: &super::Field) -> String {
         let mut sql = format!(
             "ALTER TABLE {} ADD COLUMN {} {}",
-            table,
-            field.name,
+            self.quote(table),
+            self.quote(&field.name),
             field.field_type.sql_type(self.dialect)
         );
 
@@ -189,28 +246,51 @@ impl MigrationRunner {
             sql.push_str(" NOT NULL");
         }
 
-        if let Some(default) = &field.default {
+        if let Some(default) = self.default_sql(field) {
             sql.push_str(&format!(" DEFAULT {}", default));
         }
 
         sql
     }
 
+    /// The field's default as SQL. SQLite stores booleans as integers, so
+    /// `true`/`false` defaults become `1`/`0` there. On MySQL a required
+    /// `updated_at` without a default is set on every update.
+    fn default_sql(&self, field: &super::Field) -> Option<String> {
+        if self.dialect == SqlDialect::Mysql
+            && field.name == "updated_at"
+            && field.field_type == super::FieldType::Timestamp
+            && field.required
+            && field.default.is_none()
+        {
+            return Some("CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6)".to_string());
+        }
+        let default = field.default.as_deref()?;
+        if self.dialect == SqlDialect::Sqlite && field.field_type == super::FieldType::Boolean {
+            match default.to_ascii_lowercase().as_str() {
+                "true" => return Some("1".to_string()),
+                "false" => return Some("0".to_string()),
+                _ => {}
+            }
+        }
+        Some(default.to_string())
+    }
+
     fn generate_alter_column(&self, table: &str, field: &super::Field) -> String {
         match self.dialect {
             SqlDialect::Postgres => {
                 format!(
                     "ALTER TABLE {} ALTER COLUMN {} TYPE {}",
-                    table,
-                    field.name,
+                    self.quote(table),
+                    self.quote(&field.name),
                     field.field_type.sql_type(self.dialect)
                 )
             }
             SqlDialect::Mysql => {
                 format!(
                     "ALTER TABLE {} MODIFY COLUMN {} {}",
-                    table,
-                    field.name,
+                    self.quote(table),
+                    self.quote(&field.name),
                     field.field_type.sql_type(self.dialect)
                 )
             }
@@ -221,26 +301,105 @@ impl MigrationRunner {
         }
     }
 
-    fn generate_create_index(&self, table: &str, index: &super::IndexDefinition) -> String {
+    pub(super) fn generate_create_index(
+        &self,
+        table: &str,
+        index: &super::IndexDefinition,
+    ) -> String {
         let unique = if index.unique { "UNIQUE " } else { "" };
+        // MySQL has no `IF NOT EXISTS` for indexes, and may need key prefixes.
+        let (if_not_exists, columns) = match self.dialect {
+            SqlDialect::Mysql => ("", self.mysql_key_parts(table, &index.columns)),
+            SqlDialect::Postgres | SqlDialect::Sqlite => (
+                "IF NOT EXISTS ",
+                index.columns.iter().map(|c| self.quote(c)).collect(),
+            ),
+        };
         format!(
-            "CREATE {}INDEX IF NOT EXISTS {} ON {} ({})",
+            "CREATE {}INDEX {}{} ON {} ({})",
             unique,
-            index.name,
-            table,
-            index.columns.join(", ")
+            if_not_exists,
+            self.quote(&index.name),
+            self.quote(table),
+            columns.join(", ")
         )
     }
 
-    fn generate_drop_index(&self, _table: &str, index_name: &str) -> String {
-        format!("DROP INDEX IF EXISTS {}", index_name)
+    fn generate_drop_index(&self, table: &str, index_name: &str) -> String {
+        match self.dialect {
+            SqlDialect::Mysql => format!(
+                "DROP INDEX {} ON {}",
+                self.quote(index_name),
+                self.quote(table)
+            ),
+            SqlDialect::Postgres | SqlDialect::Sqlite => {
+                format!("DROP INDEX IF EXISTS {}", self.quote(index_name))
+            }
+        }
+    }
+
+    /// Quoted MySQL index key parts for `columns` of `table`, with prefix
+    /// lengths where the whole key would not fit in
+    /// [`MYSQL_MAX_KEY_BYTES`].
+    ///
+    /// Unbounded columns always get a prefix. If the string columns are too
+    /// long together, each longer than an even share of the limit is cut to
+    /// that share. Columns of unknown type are left whole.
+    fn mysql_key_parts(&self, table: &str, columns: &[String]) -> Vec<String> {
+        let model = self.models.iter().find(|m| m.name == table);
+        let types: Vec<Option<&super::FieldType>> = columns
+            .iter()
+            .map(|column| Some(&model?.get_field(column)?.field_type))
+            .collect();
+
+        // Lengths in characters: `None` for unbounded, `Some(0)` for
+        // columns that need no prefix.
+        let lengths: Vec<Option<u32>> = types
+            .iter()
+            .map(|field_type| match field_type {
+                Some(super::FieldType::String(len)) => Some(*len),
+                Some(field_type) if is_unbounded(field_type) => None,
+                _ => Some(0),
+            })
+            .collect();
+        let budget = MYSQL_MAX_KEY_BYTES / MYSQL_BYTES_PER_CHAR;
+        let variable = lengths.iter().filter(|len| *len != &Some(0)).count() as u32;
+        let total = lengths
+            .iter()
+            .try_fold(0u32, |total, len| len.map(|len| total + len));
+        let share = budget / variable.max(1);
+
+        columns
+            .iter()
+            .zip(lengths)
+            .map(|(column, len)| match len {
+                Some(len) if total.is_some_and(|total| total <= budget) || len <= share => {
+                    self.quote(column)
+                }
+                _ => format!("{}({})", self.quote(column), share),
+            })
+            .collect()
+    }
+
+    fn quote(&self, ident: &str) -> String {
+        self.dialect.quote_ident(ident)
     }
 }
 
+/// Whether MySQL can only index `field_type` by prefix.
+fn is_unbounded(field_type: &super::FieldType) -> bool {
+    matches!(
+        field_type,
+        super::FieldType::Text | super::FieldType::Binary | super::FieldType::Json
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schema::{Field, FieldType, ModelDefinition, SchemaDiff, SchemaDefinition};
+    use crate::schema::{
+        Field, FieldType, IndexDefinition, ModelDefinition, SchemaDefinition, SchemaDiff,
+    };
 
     #[test]
     fn test_generate_create_table_sql() {
@@ -255,6 +414,76 @@ mod tests {
         assert!(sql.contains("PRIMARY KEY"));
     }
 
+    #[test]
+    fn test_reserved_table_names_are_quoted() {
+        let runner = MigrationRunner::new(SqlDialect::Postgres);
+        let model = ModelDefinition::new("user").field(Field::primary_key("id"));
+        assert!(runner
+            .generate_create_table(&model)
+            .starts_with("CREATE TABLE IF NOT EXISTS \"user\" ("));
+
+        let runner = MigrationRunner::new(SqlDialect::Mysql);
+        let index = crate::schema::IndexDefinition::unique("idx_user_email", vec!["email".into()]);
+        assert_eq!(
+            runner.generate_create_index("user", &index),
+            "CREATE UNIQUE INDEX `idx_user_email` ON `user` (`email`)"
+        );
+    }
+
+    #[test]
+    fn test_mysql_index_keys_fit_utf8mb4_limit() {
+        let model = ModelDefinition::new("note")
+            .field(Field::primary_key("id"))
+            .field(Field::new("body", FieldType::Text).unique())
+            .field(Field::new("a", FieldType::String(500)))
+            .field(Field::new("b", FieldType::String(100)))
+            .field(Field::new("c", FieldType::String(500)))
+            .field(Field::new("n", FieldType::Integer));
+        let runner = MigrationRunner::new(SqlDialect::Mysql).models(std::slice::from_ref(&model));
+        let index = |columns: &[&str]| {
+            let columns = columns.iter().map(|c| c.to_string()).collect();
+            runner.generate_create_index("note", &IndexDefinition::new("idx", columns))
+        };
+
+        // TEXT can only be indexed by prefix, inline UNIQUE included.
+        assert_eq!(
+            index(&["body"]),
+            "CREATE INDEX `idx` ON `note` (`body`(768))"
+        );
+        let table = runner.generate_create_table(&model);
+        assert!(table.contains("`body` TEXT NOT NULL,"));
+        assert!(table.contains("UNIQUE KEY (`body`(768))"));
+        // 600 characters fit; 1100 don't, so the long columns share the limit.
+        assert_eq!(
+            index(&["a", "b", "n"]),
+            "CREATE INDEX `idx` ON `note` (`a`, `b`, `n`)"
+        );
+        assert_eq!(
+            index(&["a", "b", "c"]),
+            "CREATE INDEX `idx` ON `note` (`a`(256), `b`, `c`(256))"
+        );
+        assert_eq!(
+            runner.generate_drop_index("note", "idx"),
+            "DROP INDEX `idx` ON `note`"
+        );
+    }
+
+    #[test]
+    fn test_mysql_migration_knows_tables_it_creates() {
+        let model = ModelDefinition::new("note")
+            .field(Field::primary_key("id"))
+            .field(Field::new("body", FieldType::Text))
+            .index(IndexDefinition::new("idx_note_body", vec!["body".into()]));
+        let diff = SchemaDiff::additions(&SchemaDefinition::new(), &[model]);
+        let sql = MigrationRunner::new(SqlDialect::Mysql)
+            .generate_migration("notes", &diff)
+            .to_sql();
+        assert_eq!(
+            sql[1],
+            "CREATE INDEX `idx_note_body` ON `note` (`body`(768))"
+        );
+    }
+
     #[test]
     fn test_generate_migration_from_diff() {
         let current = SchemaDefinition::new();
@@ -73,6 +73,27 @@ impl ModelDefinition {
     pub fn primary_key(&self) -> Option<&Field> {
         self.fields.iter().find(|f| f.primary_key)
     }
+
+    /// Renders the DDL that creates this model in `dialect`.
+    ///
+    /// The result is a `CREATE TABLE` statement, with column constraints and
+    /// foreign keys, followed by a `CREATE INDEX` statement per index. Each
+    /// statement ends with `;` and statements are separated by a blank line,
+    /// so the output can be written to a migration file as is.
+    pub fn to_sql(&self, dialect: SqlDialect) -> String {
+        let runner = MigrationRunner::new(dialect).models(std::slice::from_ref(self));
+        let mut statements = vec![runner.generate_create_table(self)];
+        statements.extend(
+            self.indexes
+                .iter()
+                .map(|index| runner.generate_create_index(&self.name, index)),
+        );
+        statements
+            .iter()
+            .map(|statement| format!("{};\n", statement))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 /// Represents a field (column) in a model.
@@ -220,10 +241,14 @@ impl FieldType {
             (FieldType::Boolean, SqlDialect::Sqlite) => "INTEGER".to_string(),
             (FieldType::Boolean, _) => "BOOLEAN".to_string(),
             (FieldType::Timestamp, SqlDialect::Postgres) => "TIMESTAMPTZ".to_string(),
+            // MySQL `TIMESTAMP` ends in 2038 and converts to the session time
+            // zone; `DATETIME` keeps the UTC value as written.
+            (FieldType::Timestamp, SqlDialect::Mysql) => "DATETIME(6)".to_string(),
             (FieldType::Timestamp, _) => "TIMESTAMP".to_string(),
             (FieldType::Date, _) => "DATE".to_string(),
             (FieldType::Json, SqlDialect::Postgres) => "JSONB".to_string(),
             (FieldType::Json, _) => "JSON".to_string(),
+            (FieldType::Binary, SqlDialect::Postgres) => "BYTEA".to_string(),
             (FieldType::Binary, _) => "BLOB".to_string(),
             (FieldType::Uuid, SqlDialect::Postgres) => "UUID".to_string(),
             (FieldType::Uuid, _) => "VARCHAR(36)".to_string(),
@@ -240,6 +265,17 @@ pub enum SqlDialect {
     Sqlite,
 }
 
+impl SqlDialect {
+    /// Quotes an identifier so reserved words such as `user` can be used as
+    /// table or column names.
+    pub fn quote_ident(&self, ident: &str) -> String {
+        match self {
+            Self::Mysql => format!("`{}`", ident.replace('`', "``")),
+            Self::Postgres | Self::Sqlite => format!("\"{}\"", ident.replace('"', "\"\"")),
+        }
+    }
+}
+
 /// Referential action for foreign keys.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ReferentialAction {
@@ -350,6 +386,7 @@ fn user_model() -> ModelDefinition {
         .field(Field::optional("image", FieldType::Text))
         .field(Field::new("created_at", FieldType::Timestamp))
         .field(Field::new("updated_at", FieldType::Timestamp))
+        .field(Field::optional("deleted_at", FieldType::Timestamp))
         .index(IndexDefinition::unique(
             "idx_user_email",
             vec!["email".to_string()],
@@ -369,6 +406,8 @@ fn session_model() -> ModelDefinition {
         .field(Field::new("expires_at", FieldType::Timestamp))
         .field(Field::new("created_at", FieldType::Timestamp))
         .field(Field::new("updated_at", FieldType::Timestamp))
+        .field(Field::new("authenticated_at", FieldType::Timestamp))
+        .field(Field::optional("last_used_at", FieldType::Timestamp))
         .field(Field::optional("ip_address", FieldType::String(45)))
         .field(Field::optional("user_agent", FieldType::Text))
         .index(IndexDefinition::unique(
@@ -395,6 +434,7 @@ fn account_model() -> ModelDefinition {
         .field(Field::optional("access_token", FieldType::Text).private())
         .field(Field::optional("refresh_token", FieldType::Text).private())
         .field(Field::optional("expires_at", FieldType::Timestamp))
+        .field(Field::optional("profile", FieldType::Json))
         .field(Field::new("created_at", FieldType::Timestamp))
         .field(Field::new("updated_at", FieldType::Timestamp))
         .index(IndexDefinition::unique(
@@ -406,3 +446,73 @@ fn account_model() -> ModelDefinition {
             vec!["user_id".to_string()],
         ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_model_sql() {
+        let user = user_model();
+        assert_eq!(
+            user.to_sql(SqlDialect::Postgres),
+            r#"CREATE TABLE IF NOT EXISTS "user" (
+  "id" VARCHAR(36) PRIMARY KEY,
+  "email" VARCHAR(255) NOT NULL UNIQUE,
+  "email_verified" BOOLEAN NOT NULL DEFAULT false,
+  "name" VARCHAR(255),
+  "image" TEXT,
+  "created_at" TIMESTAMPTZ NOT NULL,
+  "updated_at" TIMESTAMPTZ NOT NULL,
+  "deleted_at" TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS "idx_user_email" ON "user" ("email");
+"#
+        );
+
+        // `email` fits a utf8mb4 key whole, so the unique index is exact.
+        assert_eq!(
+            user.to_sql(SqlDialect::Mysql),
+            r#"CREATE TABLE IF NOT EXISTS `user` (
+  `id` VARCHAR(36) PRIMARY KEY,
+  `email` VARCHAR(255) NOT NULL UNIQUE,
+  `email_verified` BOOLEAN NOT NULL DEFAULT false,
+  `name` VARCHAR(255),
+  `image` TEXT,
+  `created_at` DATETIME(6) NOT NULL,
+  `updated_at` DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+  `deleted_at` DATETIME(6)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci ROW_FORMAT=DYNAMIC;
+
+CREATE UNIQUE INDEX `idx_user_email` ON `user` (`email`);
+"#
+        );
+
+        // SQLite has no boolean type.
+        let sqlite = user.to_sql(SqlDialect::Sqlite);
+        let lines: Vec<&str> = sqlite.lines().collect();
+        assert_eq!(lines[0], r#"CREATE TABLE IF NOT EXISTS "user" ("#);
+        assert_eq!(
+            lines[3],
+            r#"  "email_verified" INTEGER NOT NULL DEFAULT 0,"#
+        );
+        assert_eq!(lines[6], r#"  "created_at" TIMESTAMP NOT NULL,"#);
+        assert_eq!(lines.len(), 12);
+    }
+
+    #[test]
+    fn test_model_sql_foreign_keys_and_indexes() {
+        let sql = session_model().to_sql(SqlDialect::Postgres);
+        let foreign_key = r#"FOREIGN KEY ("user_id") REFERENCES "user"("id") ON DELETE CASCADE"#;
+        assert!(sql.contains(foreign_key));
+        let index = r#"CREATE INDEX IF NOT EXISTS "idx_session_user" ON "session" ("user_id");"#;
+        assert!(sql.contains(index));
+
+        let blob = ModelDefinition::new("blob").field(Field::new("data", FieldType::Binary));
+        let postgres = blob.to_sql(SqlDialect::Postgres);
+        assert!(postgres.contains(r#""data" BYTEA NOT NULL"#));
+        let sqlite = blob.to_sql(SqlDialect::Sqlite);
+        assert!(sqlite.contains(r#""data" BLOB NOT NULL"#));
+    }
+}
@@ -0,0 +1,857 @@
+//! Session resolution from request credentials.
+//!
+//! A [`SessionResolver`] checks a configured list of [`AuthScheme`]s in
+//! priority order and returns the first one that yields a valid session,
+//! together with the scheme that matched.
+//!
+//! When a request carries several credentials (e.g. both a bearer token and
+//! a session cookie), schemes are tried in order and the first *valid* one
+//! wins. A missing, unknown, or expired credential for one scheme falls
+//! through to the next; it never causes the request to be rejected outright.
+//! Storage errors are returned immediately.
+//!
+//! Sessions can slide: with a refresh threshold configured,
+//! [`SessionResolver::touch_session`] pushes back the expiry of a session
+//! that is close to it, up to an absolute limit counted from sign-in. This
+//! is off by default.
+//!
+//! [`SessionResolver::reject_users`] turns away sessions whose user has
+//! since been barred, such as by an admin ban.
+//!
+//! [`RequireRecentAuth`] wraps a route handler so it only runs for sessions
+//! whose last full authentication is recent enough. [`RequireFactor`] does
+//! the same for a specific factor, answering with a step-up challenge that
+//! lists the factors that would satisfy it.
+//!
+//! [`register_session_routes`] adds `GET /sessions` and
+//! `DELETE /sessions/:id`, letting users see where they are signed in and
+//! revoke other devices. [`SessionResolver::record_use`] keeps each
+//! session's `last_used_at` current for that list.
+//!
+//! When an existing session gains privileges, such as on completing a second
+//! factor, [`SessionResolver::rotate_session_token`] gives it a new token so
+//! that a token planted or leaked beforehand stops working. New tokens come
+//! from the resolver's [`SessionTokenStrategy`]; with signed tokens, forged
+//! ones are turned away before storage is queried. Sign-in never elevates an
+//! existing session: it creates a new one, and [`end_replaced_session`]
+//! deletes the one whose cookie that replaces.
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+use crate::error::{AuthError, AuthResult};
+use crate::router::{Method, Request, RequestHandler, Response, Route, Router};
+use crate::session_token::SessionTokenStrategy;
+use crate::traits::StorageAdapter;
+use crate::types::{AuthFactor, Session, User};
+
+/// Name of the cookie that carries the session token.
+pub const SESSION_COOKIE: &str = "better_auth_session";
+
+/// A way of presenting credentials on a request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AuthScheme {
+    /// `Authorization: Bearer <session token>`.
+    Bearer,
+    /// A session token in the named cookie.
+    Cookie { name: String },
+    /// An API key in the named header, resolved by an [`ApiKeyLookup`].
+    ApiKey { header: String },
+}
+
+impl AuthScheme {
+    /// The session cookie scheme with the default cookie name.
+    pub fn session_cookie() -> Self {
+        Self::Cookie {
+            name: SESSION_COOKIE.to_string(),
+        }
+    }
+
+    /// An API key scheme reading the given header.
+    pub fn api_key(header: impl Into<String>) -> Self {
+        Self::ApiKey {
+            header: header.into().to_lowercase(),
+        }
+    }
+
+    /// Extracts this scheme's credential using a header lookup.
+    ///
+    /// `header` receives lowercase header names.
+    pub fn extract<F>(&self, header: F) -> Option<String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let value = match self {
+            Self::Bearer => header("authorization")?
+                .strip_prefix("Bearer ")
+                .map(|token| token.trim().to_string()),
+            Self::Cookie { name } => header("cookie")?
+                .split(';')
+                .filter_map(|pair| pair.trim().split_once('='))
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_string()),
+            Self::ApiKey { header: name } => header(name),
+        };
+        value.filter(|v| !v.is_empty())
+    }
+}
+
+/// Resolves API keys to sessions for [`AuthScheme::ApiKey`].
+#[async_trait]
+pub trait ApiKeyLookup: Send + Sync {
+    /// Returns the session an API key authenticates as, if the key is valid.
+    async fn lookup(&self, key: &str) -> AuthResult<Option<Session>>;
+}
+
+/// A session together with the scheme that produced it.
+#[derive(Debug, Clone)]
+pub struct ResolvedSession {
+    /// The resolved session.
+    pub session: Session,
+    /// The scheme whose credential matched.
+    pub scheme: AuthScheme,
+}
+
+/// Decides whether a user may no longer use their sessions; see
+/// [`SessionResolver::reject_users`].
+pub type RejectUserFn = Arc<dyn Fn(&User) -> bool + Send + Sync>;
+
+/// Resolves sessions by trying auth schemes in priority order.
+///
+/// The default order is bearer token, then session cookie.
+#[derive(Clone)]
+pub struct SessionResolver {
+    pub(crate) storage: Arc<dyn StorageAdapter>,
+    schemes: Vec<AuthScheme>,
+    api_keys: Option<Arc<dyn ApiKeyLookup>>,
+    refresh_threshold: Option<Duration>,
+    session_expires_in: Duration,
+    session_max_age: Duration,
+    last_used_interval: Duration,
+    token_strategy: SessionTokenStrategy,
+    reject_user: Option<RejectUserFn>,
+}
+
+impl SessionResolver {
+    /// Creates a resolver with the default schemes.
+    pub fn new(storage: Arc<dyn StorageAdapter>) -> Self {
+        Self {
+            storage,
+            schemes: vec![AuthScheme::Bearer, AuthScheme::session_cookie()],
+            api_keys: None,
+            refresh_threshold: None,
+            session_expires_in: Duration::days(7),
+            session_max_age: Duration::days(30),
+            last_used_interval: Duration::minutes(1),
+            token_strategy: SessionTokenStrategy::default(),
+            reject_user: None,
+        }
+    }
+
+    /// Replaces the scheme list. Earlier schemes take precedence.
+    pub fn schemes(mut self, schemes: Vec<AuthScheme>) -> Self {
+        self.schemes = schemes;
+        self
+    }
+
+    /// Appends an API key scheme reading `header`, resolved by `lookup`.
+    pub fn api_key(mut self, header: impl Into<String>, lookup: Arc<dyn ApiKeyLookup>) -> Self {
+        self.schemes.push(AuthScheme::api_key(header));
+        self.api_keys = Some(lookup);
+        self
+    }
+
+    /// Skips sessions whose user `reject` returns true for, such as banned
+    /// users, or whose user no longer exists.
+    ///
+    /// The user is loaded on every resolve, costing a storage lookup.
+    pub fn reject_users<F>(mut self, reject: F) -> Self
+    where
+        F: Fn(&User) -> bool + Send + Sync + 'static,
+    {
+        self.reject_user = Some(Arc::new(reject));
+        self
+    }
+
+    /// Returns the configured schemes in priority order.
+    pub fn scheme_list(&self) -> &[AuthScheme] {
+        &self.schemes
+    }
+
+    /// Enables sliding sessions: [`touch_session`](Self::touch_session)
+    /// extends sessions that expire within `threshold`.
+    pub fn session_refresh_threshold(mut self, threshold: Duration) -> Self {
+        self.refresh_threshold = Some(threshold);
+        self
+    }
+
+    /// Sets how long a refreshed session lasts, counted from the refresh
+    /// (7 days by default, matching [`Session::new`]).
+    pub fn session_expires_in(mut self, expires_in: Duration) -> Self {
+        self.session_expires_in = expires_in;
+        self
+    }
+
+    /// Sets how long after creation a session expires however often it is
+    /// refreshed (30 days by default).
+    pub fn session_max_age(mut self, max_age: Duration) -> Self {
+        self.session_max_age = max_age;
+        self
+    }
+
+    /// Extends `session` if it expires within the refresh threshold,
+    /// saving it with [`StorageAdapter::update_session`].
+    ///
+    /// Returns whether the session was extended. Nothing happens when no
+    /// threshold is set, when the session has already expired, or for
+    /// impersonation sessions, which keep the lifetime the admin gave them.
+    /// Sessions are never extended past their creation plus the max age.
+    pub async fn touch_session(&self, session: &mut Session) -> AuthResult<bool> {
+        let mut refreshed = session.clone();
+        if !self.slide(&mut refreshed, Utc::now()) {
+            return Ok(false);
+        }
+        *session = self.storage.update_session(&refreshed).await?;
+        Ok(true)
+    }
+
+    /// Does [`touch_session`](Self::touch_session) and
+    /// [`record_use`](Self::record_use) together, saving the session at
+    /// most once.
+    ///
+    /// Returns whether the session was saved.
+    pub async fn touch_and_record_use(&self, session: &mut Session) -> AuthResult<bool> {
+        let now = Utc::now();
+        let mut updated = session.clone();
+        let slid = self.slide(&mut updated, now);
+        let used = self.mark_used(&mut updated, now);
+        if !(slid || used) {
+            return Ok(false);
+        }
+        *session = self.storage.update_session(&updated).await?;
+        Ok(true)
+    }
+
+    /// Pushes back `session`'s expiry if it is due, returning whether it
+    /// changed.
+    fn slide(&self, session: &mut Session, now: chrono::DateTime<Utc>) -> bool {
+        let Some(threshold) = self.refresh_threshold else {
+            return false;
+        };
+        if session.is_expired()
+            || session.impersonated_by().is_some()
+            || session.expires_at - now > threshold
+        {
+            return false;
+        }
+        let expires_at =
+            (now + self.session_expires_in).min(session.created_at + self.session_max_age);
+        if expires_at <= session.expires_at {
+            return false;
+        }
+        session.expires_at = expires_at;
+        session.updated_at = now;
+        true
+    }
+
+    /// Sets how new session tokens are generated (opaque random tokens by
+    /// default).
+    ///
+    /// With [`SessionTokenStrategy::Signed`], bearer and cookie tokens that
+    /// look signed but whose signature does not check out are skipped
+    /// without a storage lookup.
+    pub fn token_strategy(mut self, strategy: SessionTokenStrategy) -> Self {
+        self.token_strategy = strategy;
+        self
+    }
+
+    /// Gives `session` a new token from the token strategy, saving it with
+    /// [`StorageAdapter::update_session`].
+    ///
+    /// The old token stops resolving to the session. The ID, user, expiry and
+    /// extensions are kept. Callers that deliver the token in a cookie must
+    /// set the cookie again with the new value.
+    pub async fn rotate_session_token(&self, session: &mut Session) -> AuthResult<()> {
+        let mut rotated = session.clone();
+        self.token_strategy.issue(&mut rotated);
+        *session = self.storage.update_session(&rotated).await?;
+        Ok(())
+    }
+
+    /// Sets how stale `last_used_at` may get before
+    /// [`record_use`](Self::record_use) writes it again (1 minute by default).
+    ///
+    /// A zero interval saves the session on every request.
+    pub fn last_used_interval(mut self, interval: Duration) -> Self {
+        self.last_used_interval = interval;
+        self
+    }
+
+    /// Records that `session` just authenticated a request, saving its
+    /// `last_used_at` with [`StorageAdapter::update_session`].
+    ///
+    /// Returns whether the session was saved. Writes are skipped while the
+    /// stored value is newer than the configured interval.
+    pub async fn record_use(&self, session: &mut Session) -> AuthResult<bool> {
+        let mut used = session.clone();
+        if !self.mark_used(&mut used, Utc::now()) {
+            return Ok(false);
+        }
+        *session = self.storage.update_session(&used).await?;
+        Ok(true)
+    }
+
+    /// Sets `session`'s `last_used_at` to `now` unless it is recent enough,
+    /// returning whether it changed.
+    fn mark_used(&self, session: &mut Session, now: chrono::DateTime<Utc>) -> bool {
+        if session
+            .last_used_at
+            .is_some_and(|used| now - used < self.last_used_interval)
+        {
+            return false;
+        }
+        session.last_used_at = Some(now);
+        true
+    }
+
+    /// Returns the first valid session found using a header lookup.
+    ///
+    /// `header` receives lowercase header names.
+    pub async fn resolve<F>(&self, header: F) -> AuthResult<Option<ResolvedSession>>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        for scheme in &self.schemes {
+            let Some(credential) = scheme.extract(&header) else {
+                continue;
+            };
+
+            let session = match scheme {
+                AuthScheme::Bearer | AuthScheme::Cookie { .. } => {
+                    if !self.token_strategy.accepts(&credential) {
+                        continue;
+                    }
+                    self.storage.get_session_by_token(&credential).await?
+                }
+                AuthScheme::ApiKey { .. } => match &self.api_keys {
+                    Some(lookup) => lookup.lookup(&credential).await?,
+                    None => {
+                        tracing::warn!("API key scheme configured without a lookup; skipping");
+                        None
+                    }
+                },
+            };
+
+            let Some(session) = session.filter(|s| !s.is_expired()) else {
+                continue;
+            };
+            if let Some(reject) = &self.reject_user {
+                let user = self.storage.get_user_by_id(&session.user_id).await?;
+                if user.is_none_or(|user| reject(&user)) {
+                    continue;
+                }
+            }
+            return Ok(Some(ResolvedSession {
+                session,
+                scheme: scheme.clone(),
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the first valid session for a router request.
+    pub async fn resolve_request(&self, req: &Request) -> AuthResult<Option<ResolvedSession>> {
+        self.resolve(|name| req.header(name).cloned()).await
+    }
+}
+
+impl std::fmt::Debug for SessionResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionResolver")
+            .field("schemes", &self.schemes)
+            .field("api_keys", &self.api_keys.is_some())
+            .field("refresh_threshold", &self.refresh_threshold)
+            .field("session_max_age", &self.session_max_age)
+            .field("last_used_interval", &self.last_used_interval)
+            .field("token_strategy", &self.token_strategy)
+            .field("reject_user", &self.reject_user.is_some())
+            .finish()
+    }
+}
+
+/// A handler wrapper that rejects sessions without a recent full authentication.
+///
+/// Requests without a valid session get [`AuthError::SessionNotFound`];
+/// sessions authenticated longer than `max_age` ago get
+/// [`AuthError::ReauthenticationRequired`] (403). Otherwise the request is
+/// passed to the inner handler.
+pub struct RequireRecentAuth<H> {
+    resolver: SessionResolver,
+    max_age: chrono::Duration,
+    inner: H,
+}
+
+impl<H: RequestHandler> RequireRecentAuth<H> {
+    /// Wraps `inner` so it requires authentication within `max_age`.
+    pub fn new(resolver: SessionResolver, max_age: chrono::Duration, inner: H) -> Self {
+        Self {
+            resolver,
+            max_age,
+            inner,
+        }
+    }
+
+    async fn check(&self, req: &Request) -> AuthResult<()> {
+        self.resolver
+            .resolve_request(req)
+            .await?
+            .ok_or(AuthError::SessionNotFound)?
+            .session
+            .require_recent_auth(self.max_age)
+    }
+}
+
+#[async_trait]
+impl<H: RequestHandler> RequestHandler for RequireRecentAuth<H> {
+    async fn handle(&self, req: Request) -> Response {
+        match self.check(&req).await {
+            Ok(()) => self.inner.handle(req).await,
+            Err(err) => error_response(err),
+        }
+    }
+}
+
+/// A handler wrapper that requires a recently verified factor.
+///
+/// Requests without a valid session get [`AuthError::SessionNotFound`].
+/// Sessions that have not verified one of `factors` within `max_age` get
+/// [`AuthError::StepUpRequired`] (403) with the accepted factors listed in
+/// the error's `factors` field, so the client can prompt for one and retry.
+pub struct RequireFactor<H> {
+    resolver: SessionResolver,
+    factors: Vec<AuthFactor>,
+    max_age: chrono::Duration,
+    inner: H,
+}
+
+impl<H: RequestHandler> RequireFactor<H> {
+    /// Wraps `inner` so it requires one of `factors` within `max_age`.
+    pub fn new(
+        resolver: SessionResolver,
+        factors: Vec<AuthFactor>,
+        max_age: chrono::Duration,
+        inner: H,
+    ) -> Self {
+        Self {
+            resolver,
+            factors,
+            max_age,
+            inner,
+        }
+    }
+
+    async fn check(&self, req: &Request) -> AuthResult<()> {
+        self.resolver
+            .resolve_request(req)
+            .await?
+            .ok_or(AuthError::SessionNotFound)?
+            .session
+            .require_any_factor(&self.factors, self.max_age)
+    }
+}
+
+#[async_trait]
+impl<H: RequestHandler> RequestHandler for RequireFactor<H> {
+    async fn handle(&self, req: Request) -> Response {
+        match self.check(&req).await {
+            Ok(()) => self.inner.handle(req).await,
+            Err(err) => error_response(err),
+        }
+    }
+}
+
+/// Adds the session management routes to `router`.
+///
+/// - `GET /sessions` lists the caller's active sessions, marking the one
+///   the request was made with as `current`.
+/// - `DELETE /sessions/:id` revokes another of the caller's sessions. The
+///   current session can't be revoked this way; sign out instead.
+///
+/// Session tokens are never included in responses, and requests without a
+/// session get 401.
+pub fn register_session_routes(router: &mut Router, resolver: SessionResolver) {
+    router.route(
+        Route::new(
+            Method::GET,
+            "/sessions",
+            ListSessions::new(resolver.clone()),
+        )
+        .summary("List active sessions")
+        .tag("session")
+        .requires_auth(),
+    );
+    router.route(
+        Route::new(
+            Method::DELETE,
+            "/sessions/:id",
+            RevokeSession::new(resolver),
+        )
+        .summary("Revoke a session")
+        .tag("session")
+        .requires_auth(),
+    );
+}
+
+/// Handler for `GET /sessions`.
+pub struct ListSessions {
+    resolver: SessionResolver,
+}
+
+impl ListSessions {
+    /// Creates the handler.
+    pub fn new(resolver: SessionResolver) -> Self {
+        Self { resolver }
+    }
+
+    async fn list(&self, req: &Request) -> AuthResult<Response> {
+        let current = current_session(&self.resolver, req).await?;
+        let mut sessions: Vec<Session> = self
+            .resolver
+            .storage
+            .get_sessions_by_user_id(&current.user_id)
+            .await?
+            .into_iter()
+            .filter(|s| !s.is_expired())
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_used_at));
+
+        let sessions: Vec<_> = sessions
+            .iter()
+            .map(|s| {
+                serde_json::json!({
+                    "id": s.id,
+                    "created_at": s.created_at,
+                    "last_used_at": s.last_used_at,
+                    "expires_at": s.expires_at,
+                    "ip_address": s.ip_address,
+                    "user_agent": s.user_agent,
+                    "current": s.id == current.id,
+                })
+            })
+            .collect();
+        Ok(Response::ok().json(serde_json::json!({ "sessions": sessions })))
+    }
+}
+
+#[async_trait]
+impl RequestHandler for ListSessions {
+    async fn handle(&self, req: Request) -> Response {
+        self.list(&req).await.unwrap_or_else(session_route_error)
+    }
+}
+
+/// Handler for `DELETE /sessions/:id`.
+pub struct RevokeSession {
+    resolver: SessionResolver,
+}
+
+impl RevokeSession {
+    /// Creates the handler.
+    pub fn new(resolver: SessionResolver) -> Self {
+        Self { resolver }
+    }
+
+    async fn revoke(&self, req: &Request) -> AuthResult<Response> {
+        let current = current_session(&self.resolver, req).await?;
+        let id = req.param("id").ok_or_else(|| AuthError::MissingField {
+            field: "id".to_string(),
+        })?;
+        if *id == current.id {
+            return Err(AuthError::conflict(
+                "The current session can't be revoked; sign out instead",
+            ));
+        }
+
+        // Other users' sessions are reported as missing, not forbidden, so
+        // session IDs can't be probed.
+        let storage = &self.resolver.storage;
+        match storage.get_session_by_id(id).await? {
+            Some(session) if session.user_id == current.user_id => {
+                storage.delete_session(id).await?;
+                Ok(Response::no_content())
+            }
+            _ => Err(AuthError::not_found("session", "id", id.as_str())),
+        }
+    }
+}
+
+#[async_trait]
+impl RequestHandler for RevokeSession {
+    async fn handle(&self, req: Request) -> Response {
+        self.revoke(&req).await.unwrap_or_else(session_route_error)
+    }
+}
+
+/// Deletes the session whose cookie a sign-in request carried, if any.
+///
+/// Sign-ins create a new session with a fresh token and set the cookie to
+/// it. Ending the replaced session too means its token, which may have
+/// been planted or leaked before sign-in, stops working. Bearer tokens are
+/// left alone, as clients using them may hold several sessions.
+pub async fn end_replaced_session(storage: &dyn StorageAdapter, req: &Request) -> AuthResult<()> {
+    let cookie = AuthScheme::session_cookie().extract(|name| req.header(name).cloned());
+    let Some(token) = cookie else {
+        return Ok(());
+    };
+    if let Some(session) = storage.get_session_by_token(&token).await? {
+        storage.delete_session(&session.id).await?;
+    }
+    Ok(())
+}
+
+/// Resolves the stored session a request was made with.
+///
+/// API key sessions aren't stored, so they can't manage sessions.
+pub(crate) async fn current_session(
+    resolver: &SessionResolver,
+    req: &Request,
+) -> AuthResult<Session> {
+    resolver
+        .resolve_request(req)
+        .await?
+        .filter(|resolved| !matches!(resolved.scheme, AuthScheme::ApiKey { .. }))
+        .map(|resolved| resolved.session)
+        .ok_or(AuthError::SessionNotFound)
+}
+
+/// Renders errors of the session routes. There is no session to manage
+/// without one in the request, so that is reported as 401.
+fn session_route_error(err: AuthError) -> Response {
+    match err {
+        AuthError::SessionNotFound => Response::unauthorized().json(serde_json::json!({
+            "error": { "code": "UNAUTHORIZED", "message": "Sign in to manage sessions" }
+        })),
+        err => error_response(err),
+    }
+}
+
+pub(crate) fn error_response(err: AuthError) -> Response {
+    let mut body = serde_json::json!({
+        "error": { "code": err.error_code(), "message": err.to_string() }
+    });
+    if let AuthError::StepUpRequired { factors } = &err {
+        body["error"]["factors"] = serde_json::json!(factors);
+    }
+    Response::new(err.status_code()).json(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestStorage;
+    use std::collections::HashMap;
+
+    /// Storage with a session for the token `"valid"`, and one for
+    /// `"reauth_due"` that was authenticated an hour ago.
+    fn token_store() -> TestStorage {
+        let mut valid = Session::new("user_1".to_string());
+        valid.token = "valid".to_string();
+        let mut reauth_due = Session::new("user_1".to_string());
+        reauth_due.token = "reauth_due".to_string();
+        reauth_due.authenticated_at -= chrono::Duration::hours(1);
+        TestStorage::new().with_session(valid).with_session(reauth_due)
+    }
+
+    struct StaticKeys;
+
+    #[async_trait]
+    impl ApiKeyLookup for StaticKeys {
+        async fn lookup(&self, key: &str) -> AuthResult<Option<Session>> {
+            Ok((key == "key_123").then(|| Session::new("service".to_string())))
+        }
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| map.get(name).cloned()
+    }
+
+    #[test]
+    fn test_extract_bearer() {
+        let h = headers(&[("authorization", "Bearer abc")]);
+        assert_eq!(AuthScheme::Bearer.extract(&h), Some("abc".to_string()));
+
+        let h = headers(&[("authorization", "Basic abc")]);
+        assert_eq!(AuthScheme::Bearer.extract(&h), None);
+    }
+
+    #[test]
+    fn test_extract_cookie() {
+        let h = headers(&[("cookie", "other=1; better_auth_session=xyz; more=2")]);
+        assert_eq!(AuthScheme::session_cookie().extract(&h), Some("xyz".to_string()));
+
+        let h = headers(&[("cookie", "better_auth_session=")]);
+        assert_eq!(AuthScheme::session_cookie().extract(&h), None);
+    }
+
+    #[test]
+    fn test_extract_api_key() {
+        let h = headers(&[("x-api-key", "key_123")]);
+        assert_eq!(AuthScheme::api_key("X-API-Key").extract(&h), Some("key_123".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_first_valid_scheme_wins() {
+        let resolver = SessionResolver::new(Arc::new(token_store()));
+
+        let both = headers(&[
+            ("authorization", "Bearer valid"),
+            ("cookie", "better_auth_session=valid"),
+        ]);
+        let resolved = resolver.resolve(both).await.unwrap().unwrap();
+        assert_eq!(resolved.scheme, AuthScheme::Bearer);
+
+        // An invalid bearer token falls through to the cookie.
+        let stale_bearer = headers(&[
+            ("authorization", "Bearer stale"),
+            ("cookie", "better_auth_session=valid"),
+        ]);
+        let resolved = resolver.resolve(stale_bearer).await.unwrap().unwrap();
+        assert_eq!(resolved.scheme, AuthScheme::session_cookie());
+
+        assert!(resolver.resolve(headers(&[])).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_configured_order_and_api_key() {
+        let resolver = SessionResolver::new(Arc::new(token_store()))
+            .schemes(vec![AuthScheme::session_cookie()])
+            .api_key("x-api-key", Arc::new(StaticKeys));
+
+        // Bearer is no longer configured, so only the API key matches.
+        let h = headers(&[("authorization", "Bearer valid"), ("x-api-key", "key_123")]);
+        let resolved = resolver.resolve(h).await.unwrap().unwrap();
+        assert_eq!(resolved.scheme, AuthScheme::api_key("x-api-key"));
+        assert_eq!(resolved.session.user_id, "service");
+    }
+
+    struct Ok200;
+
+    #[async_trait]
+    impl RequestHandler for Ok200 {
+        async fn handle(&self, _req: Request) -> Response {
+            Response::ok()
+        }
+    }
+
+    fn request(token: &str) -> Request {
+        let mut req = Request::new(crate::router::Method::POST, "/two-factor/disable");
+        req.headers
+            .insert("authorization".to_string(), format!("Bearer {}", token));
+        req
+    }
+
+    #[tokio::test]
+    async fn test_require_recent_auth_within_and_over_window() {
+        let guard = RequireRecentAuth::new(
+            SessionResolver::new(Arc::new(token_store())),
+            chrono::Duration::minutes(5),
+            Ok200,
+        );
+
+        assert_eq!(guard.handle(request("valid")).await.status, 200);
+        assert_eq!(guard.handle(request("reauth_due")).await.status, 403);
+        assert_eq!(guard.handle(request("unknown")).await.status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_require_factor_asks_for_step_up() {
+        let guard = RequireFactor::new(
+            SessionResolver::new(Arc::new(token_store())),
+            vec![AuthFactor::Totp, AuthFactor::Passkey],
+            chrono::Duration::minutes(5),
+            Ok200,
+        );
+
+        let response = guard.handle(request("valid")).await;
+        assert_eq!(response.status, 403);
+        let error = &response.body.unwrap()["error"];
+        assert_eq!(error["code"], "STEP_UP_REQUIRED");
+        assert_eq!(error["factors"], serde_json::json!(["totp", "passkey"]));
+        assert_eq!(guard.handle(request("unknown")).await.status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_touch_session_slides_expiry_near_the_end() {
+        let mut due = Session::with_expiration("user_1".to_string(), chrono::Duration::hours(1));
+        let storage = TestStorage::new().with_session(due.clone());
+        let resolver = SessionResolver::new(Arc::new(storage))
+            .session_refresh_threshold(chrono::Duration::days(1));
+
+        assert!(resolver.touch_session(&mut due).await.unwrap());
+        assert!(due.expires_at > Utc::now() + chrono::Duration::days(6));
+
+        let mut fresh = Session::new("user_1".to_string());
+        let expires_at = fresh.expires_at;
+        assert!(!resolver.touch_session(&mut fresh).await.unwrap());
+        assert_eq!(fresh.expires_at, expires_at);
+    }
+
+    #[tokio::test]
+    async fn test_touch_session_is_opt_in_and_skips_expired() {
+        let mut due = Session::with_expiration("user_1".to_string(), chrono::Duration::hours(1));
+        let off = SessionResolver::new(Arc::new(token_store()));
+        assert!(!off.touch_session(&mut due).await.unwrap());
+
+        let on = off.session_refresh_threshold(chrono::Duration::days(1));
+        let mut expired = Session::with_expiration("user_1".to_string(), chrono::Duration::seconds(-1));
+        let expires_at = expired.expires_at;
+        assert!(!on.touch_session(&mut expired).await.unwrap());
+        assert_eq!(expired.expires_at, expires_at);
+    }
+
+    #[tokio::test]
+    async fn test_touch_session_stops_at_max_age_and_skips_impersonation() {
+        let mut old = Session::with_expiration("user_1".to_string(), chrono::Duration::hours(1));
+        let storage = TestStorage::new().with_session(old.clone());
+        let resolver = SessionResolver::new(Arc::new(storage))
+            .session_refresh_threshold(chrono::Duration::days(1))
+            .session_max_age(chrono::Duration::days(10));
+
+        old.created_at = Utc::now() - chrono::Duration::days(8);
+        assert!(resolver.touch_session(&mut old).await.unwrap());
+        assert_eq!(old.expires_at, old.created_at + chrono::Duration::days(10));
+
+        // At the cap already.
+        old.created_at = Utc::now() - chrono::Duration::days(10) + chrono::Duration::hours(1);
+        old.expires_at = old.created_at + chrono::Duration::days(10);
+        assert!(!resolver.touch_session(&mut old).await.unwrap());
+
+        let mut impersonation =
+            Session::with_expiration("user_1".to_string(), chrono::Duration::hours(1));
+        impersonation.set_extension("impersonated_by", "admin_1");
+        let expires_at = impersonation.expires_at;
+        assert!(!resolver.touch_session(&mut impersonation).await.unwrap());
+        assert_eq!(impersonation.expires_at, expires_at);
+    }
+
+    #[tokio::test]
+    async fn test_touch_and_record_use_saves_once() {
+        let mut due = Session::with_expiration("user_1".to_string(), chrono::Duration::hours(1));
+        let storage = TestStorage::new().with_session(due.clone());
+        let resolver = SessionResolver::new(Arc::new(storage))
+            .session_refresh_threshold(chrono::Duration::days(1));
+
+        assert!(resolver.touch_and_record_use(&mut due).await.unwrap());
+        assert!(due.expires_at > Utc::now() + chrono::Duration::days(6));
+        assert!(due.last_used_at.is_some());
+
+        // Neither is due now.
+        assert!(!resolver.touch_and_record_use(&mut due).await.unwrap());
+    }
+}
@@ -0,0 +1,292 @@
+//! Session token generation.
+//!
+//! A [`SessionTokenStrategy`] decides what a session's token looks like:
+//!
+//! - [`Opaque`](SessionTokenStrategy::Opaque), the default, is a random
+//!   string that means nothing on its own. Every check looks the token up
+//!   with [`StorageAdapter::get_session_by_token`].
+//! - [`Signed`](SessionTokenStrategy::Signed) carries the session ID, user ID
+//!   and expiry, signed with HMAC-SHA256. [`SessionTokenStrategy::verify`]
+//!   checks it without touching storage, which suits read-only checks on
+//!   hot paths. The token is still stored, so full resolution through
+//!   [`SessionResolver`](crate::session::SessionResolver) works as before.
+//!
+//! Signed tokens trade revocation for speed: a signed token keeps passing
+//! [`verify`](SessionTokenStrategy::verify) until the expiry it carries,
+//! even after its session is revoked or deleted. Only use `verify` where
+//! acting on a just-revoked session for a while is acceptable, and resolve
+//! the session from storage for anything that changes state.
+//!
+//! [`StorageAdapter::get_session_by_token`]: crate::traits::StorageAdapter::get_session_by_token
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::error::{AuthError, AuthResult};
+use crate::redact::Redact;
+use crate::types::Session;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Length of opaque tokens under the default strategy.
+pub const DEFAULT_TOKEN_LENGTH: usize = 32;
+
+/// Shortest opaque token allowed, in hex characters (128 bits).
+pub const MIN_TOKEN_LENGTH: usize = 32;
+
+/// How session tokens are generated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionTokenStrategy {
+    /// Random tokens of `length` hex characters.
+    Opaque {
+        /// Token length in characters.
+        length: usize,
+    },
+    /// Tokens that carry their session's IDs and expiry, signed with
+    /// `secret`. See the [module docs](self) for the revocation trade-off.
+    Signed {
+        /// HMAC key. Use at least 32 random bytes.
+        secret: Redact<String>,
+    },
+}
+
+impl Default for SessionTokenStrategy {
+    fn default() -> Self {
+        Self::Opaque {
+            length: DEFAULT_TOKEN_LENGTH,
+        }
+    }
+}
+
+/// What a signed session token says about its session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionTokenClaims {
+    /// The session's ID.
+    #[serde(rename = "sid")]
+    pub session_id: String,
+    /// The ID of the user the session belongs to.
+    #[serde(rename = "uid")]
+    pub user_id: String,
+    /// When the session expired as of issuing the token.
+    #[serde(rename = "exp", with = "chrono::serde::ts_seconds")]
+    pub expires_at: DateTime<Utc>,
+    /// Makes each token unique, so rotating a session changes its token.
+    #[serde(rename = "n")]
+    nonce: String,
+}
+
+impl SessionTokenStrategy {
+    /// Random tokens of `length` hex characters.
+    ///
+    /// Fails if `length` is below [`MIN_TOKEN_LENGTH`].
+    pub fn opaque(length: usize) -> AuthResult<Self> {
+        let strategy = Self::Opaque { length };
+        strategy.validate()?;
+        Ok(strategy)
+    }
+
+    /// Signed tokens using `secret` as the HMAC key.
+    pub fn signed(secret: impl Into<String>) -> Self {
+        Self::Signed {
+            secret: Redact(secret.into()),
+        }
+    }
+
+    /// Checks a strategy built by hand or deserialized from config.
+    pub fn validate(&self) -> AuthResult<()> {
+        match self {
+            Self::Opaque { length } if *length < MIN_TOKEN_LENGTH => {
+                Err(AuthError::config(format!(
+                    "Opaque session tokens must be at least {} characters, got {}",
+                    MIN_TOKEN_LENGTH, length
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates a session for `user_id` with a token from this strategy.
+    ///
+    /// Sign-in handlers use this rather than [`Session::new`], so every
+    /// session gets the configured kind of token.
+    pub fn new_session(&self, user_id: impl Into<String>) -> Session {
+        let mut session = Session::new(user_id.into());
+        self.issue(&mut session);
+        session
+    }
+
+    /// Generates a new token for `session`.
+    pub fn generate(&self, session: &Session) -> String {
+        match self {
+            Self::Opaque { length } => random_hex(*length),
+            Self::Signed { secret } => {
+                let claims = SessionTokenClaims {
+                    session_id: session.id.clone(),
+                    user_id: session.user_id.clone(),
+                    expires_at: session.expires_at,
+                    nonce: random_hex(16),
+                };
+                let payload = URL_SAFE_NO_PAD
+                    .encode(serde_json::to_vec(&claims).expect("claims serialize to JSON"));
+                let signature = mac(secret.expose(), &payload).finalize().into_bytes();
+                let signature = URL_SAFE_NO_PAD.encode(signature);
+                format!("{}.{}", payload, signature)
+            }
+        }
+    }
+
+    /// Gives `session` a new token from this strategy.
+    pub fn issue(&self, session: &mut Session) {
+        session.token = self.generate(session);
+    }
+
+    /// Checks a signed token without storage, returning its claims.
+    ///
+    /// Returns `None` for tokens with a bad signature, for tokens past the
+    /// expiry they carry, and always under the opaque strategy, whose
+    /// tokens can only be checked against storage. A revoked session's
+    /// token still verifies until it expires.
+    pub fn verify(&self, token: &str) -> Option<SessionTokenClaims> {
+        self.decode(token)
+            .filter(|claims| claims.expires_at > Utc::now())
+    }
+
+    /// Returns whether `token` is worth looking up in storage.
+    ///
+    /// Under the signed strategy, a token shaped like a signed one must
+    /// carry a valid signature, so forged tokens are turned away without a
+    /// lookup. Other tokens pass, since sessions created with
+    /// [`Session::new`] have plain random tokens. Expiry is left to the
+    /// stored session, which may have been extended since.
+    pub fn accepts(&self, token: &str) -> bool {
+        match self {
+            Self::Opaque { .. } => true,
+            Self::Signed { .. } => !token.contains('.') || self.decode(token).is_some(),
+        }
+    }
+
+    fn decode(&self, token: &str) -> Option<SessionTokenClaims> {
+        let Self::Signed { secret } = self else {
+            return None;
+        };
+        let (payload, signature) = token.split_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        mac(secret.expose(), payload)
+            .verify_slice(&signature)
+            .ok()?;
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+    }
+}
+
+fn mac(secret: &str, payload: &str) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// Returns `length` random hex characters.
+fn random_hex(length: usize) -> String {
+    let mut token = String::with_capacity(length + 32);
+    while token.len() < length {
+        token.push_str(&uuid::Uuid::new_v4().simple().to_string());
+    }
+    token.truncate(length);
+    token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opaque_tokens_have_the_configured_length() {
+        let session = Session::new("user_1".to_string());
+        let token = SessionTokenStrategy::default().generate(&session);
+        assert_eq!(token.len(), DEFAULT_TOKEN_LENGTH);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+
+        let strategy = SessionTokenStrategy::opaque(80).unwrap();
+        let token = strategy.generate(&session);
+        assert_eq!(token.len(), 80);
+        assert_ne!(token, strategy.generate(&session));
+        assert!(strategy.verify(&token).is_none());
+        assert!(strategy.accepts(&token));
+    }
+
+    #[test]
+    fn test_short_opaque_tokens_are_rejected() {
+        assert!(SessionTokenStrategy::opaque(MIN_TOKEN_LENGTH - 1).is_err());
+        let json = serde_json::json!({ "type": "opaque", "length": 8 });
+        let strategy: SessionTokenStrategy = serde_json::from_value(json).unwrap();
+        assert!(strategy.validate().is_err());
+        assert!(SessionTokenStrategy::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_new_session_uses_the_strategy() {
+        let strategy = SessionTokenStrategy::signed("a-long-random-secret");
+        let session = strategy.new_session("user_1");
+        assert_eq!(
+            strategy.verify(&session.token).unwrap().session_id,
+            session.id
+        );
+    }
+
+    #[test]
+    fn test_signed_tokens_verify_without_storage() {
+        let strategy = SessionTokenStrategy::signed("a-long-random-secret");
+        let mut session = Session::new("user_1".to_string());
+        strategy.issue(&mut session);
+
+        let claims = strategy.verify(&session.token).unwrap();
+        assert_eq!(claims.session_id, session.id);
+        assert_eq!(claims.user_id, "user_1");
+        assert_eq!(
+            claims.expires_at.timestamp(),
+            session.expires_at.timestamp()
+        );
+        assert_ne!(session.token, strategy.generate(&session));
+
+        // Tampered claims, another key, and garbage are all refused.
+        let (payload, signature) = session.token.split_once('.').unwrap();
+        let tampered = format!("{}A.{}", payload, signature);
+        let other_key = SessionTokenStrategy::signed("another-secret");
+        for token in [tampered.as_str(), "not.signed", ".", signature] {
+            assert!(strategy.verify(token).is_none(), "{}", token);
+        }
+        assert!(!strategy.accepts(&tampered));
+        assert!(!strategy.accepts("not.signed"));
+        assert!(other_key.verify(&session.token).is_none());
+
+        // Plain tokens, e.g. from `Session::new`, are left to storage.
+        let plain = Session::new("user_1".to_string()).token;
+        assert!(strategy.verify(&plain).is_none());
+        assert!(strategy.accepts(&plain));
+    }
+
+    #[test]
+    fn test_expired_signed_tokens_are_still_accepted() {
+        let strategy = SessionTokenStrategy::signed("a-long-random-secret");
+        let mut session =
+            Session::with_expiration("user_1".to_string(), chrono::Duration::seconds(-1));
+        strategy.issue(&mut session);
+        assert!(strategy.verify(&session.token).is_none());
+        // The stored session may have been extended since.
+        assert!(strategy.accepts(&session.token));
+    }
+
+    #[test]
+    fn test_secret_is_not_printed() {
+        let strategy = SessionTokenStrategy::signed("a-long-random-secret");
+        assert!(!format!("{:?}", strategy).contains("a-long-random-secret"));
+        let json = serde_json::json!({ "type": "opaque", "length": 48 });
+        let strategy: SessionTokenStrategy = serde_json::from_value(json).unwrap();
+        assert_eq!(strategy, SessionTokenStrategy::opaque(48).unwrap());
+    }
+}
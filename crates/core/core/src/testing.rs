@@ -0,0 +1,296 @@
+//! An in-memory storage adapter for tests.
+//!
+//! Available to other crates with the `testing` feature.
+
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::{AuthError, AuthResult};
+use crate::schema::{MigrationOp, ModelDefinition};
+use crate::traits::{StorageAdapter, StorageTransaction};
+use crate::types::{Account, Session, User};
+
+/// The users, sessions and accounts held by a [`TestStorage`].
+#[derive(Debug, Clone, Default)]
+pub struct Rows {
+    /// Users, in creation order.
+    pub users: Vec<User>,
+    /// Sessions, in creation order.
+    pub sessions: Vec<Session>,
+    /// Accounts, in creation order.
+    pub accounts: Vec<Account>,
+}
+
+/// Storage held in memory, with optional snapshot transactions.
+///
+/// Every write is recorded in [`log`](Self::log) by operation name, such
+/// as `create_user`; writes made inside a transaction are prefixed with
+/// `tx:`, and commits and rollbacks are logged too. Writes can be made to
+/// fail with [`fail_on`](Self::fail_on).
+///
+/// Clones share the same rows.
+#[derive(Clone, Default)]
+pub struct TestStorage {
+    rows: Arc<Mutex<Rows>>,
+    /// Rows written by an open transaction, copied from `rows` by `begin`.
+    staged: Option<Arc<Mutex<Rows>>>,
+    log: Arc<Mutex<Vec<String>>>,
+    failing: Arc<Mutex<HashSet<&'static str>>>,
+    transactional: bool,
+}
+
+impl TestStorage {
+    /// Creates empty storage without transactions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Supports transactions: writes made after `begin` only reach the
+    /// rows on commit.
+    pub fn transactional(mut self) -> Self {
+        self.transactional = true;
+        self
+    }
+
+    /// Makes the write operation `op`, such as `create_account`, fail with
+    /// a database error.
+    pub fn fail_on(self, op: &'static str) -> Self {
+        self.failing.lock().unwrap().insert(op);
+        self
+    }
+
+    /// Adds a user.
+    pub fn with_user(self, user: User) -> Self {
+        self.rows.lock().unwrap().users.push(user);
+        self
+    }
+
+    /// Adds a session.
+    pub fn with_session(self, session: Session) -> Self {
+        self.rows.lock().unwrap().sessions.push(session);
+        self
+    }
+
+    /// Adds an account.
+    pub fn with_account(self, account: Account) -> Self {
+        self.rows.lock().unwrap().accounts.push(account);
+        self
+    }
+
+    /// Returns the committed rows.
+    pub fn rows(&self) -> MutexGuard<'_, Rows> {
+        self.rows.lock().unwrap()
+    }
+
+    /// Returns the writes, commits and rollbacks made so far, in order.
+    pub fn log(&self) -> Vec<String> {
+        self.log.lock().unwrap().clone()
+    }
+
+    /// Runs `f` on the rows this handle reads and writes.
+    fn read<T>(&self, f: impl FnOnce(&Rows) -> T) -> T {
+        f(&self.staged.as_ref().unwrap_or(&self.rows).lock().unwrap())
+    }
+
+    /// Logs the write `op`, then runs `f` on the rows unless `op` fails.
+    fn write<T>(
+        &self,
+        op: &'static str,
+        f: impl FnOnce(&mut Rows) -> AuthResult<T>,
+    ) -> AuthResult<T> {
+        let entry = match self.staged {
+            Some(_) => format!("tx:{}", op),
+            None => op.to_string(),
+        };
+        self.log.lock().unwrap().push(entry);
+        if self.failing.lock().unwrap().contains(op) {
+            return Err(AuthError::database(format!("{} failed", op)));
+        }
+        f(&mut self.staged.as_ref().unwrap_or(&self.rows).lock().unwrap())
+    }
+}
+
+#[async_trait]
+impl StorageAdapter for TestStorage {
+    async fn create_user(&self, user: &User) -> AuthResult<User> {
+        self.write("create_user", |rows| {
+            rows.users.push(user.clone());
+            Ok(user.clone())
+        })
+    }
+
+    async fn get_user_by_id(&self, id: &str) -> AuthResult<Option<User>> {
+        Ok(self.read(|rows| rows.users.iter().find(|u| u.id == id).cloned()))
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> AuthResult<Option<User>> {
+        Ok(self.read(|rows| rows.users.iter().find(|u| u.email == email).cloned()))
+    }
+
+    async fn update_user(&self, user: &User) -> AuthResult<User> {
+        self.write("update_user", |rows| {
+            let stored = rows
+                .users
+                .iter_mut()
+                .find(|u| u.id == user.id)
+                .ok_or_else(|| AuthError::not_found("user", "id", &user.id))?;
+            let mut extensions = stored.extensions.clone();
+            extensions.extend(user.extensions.clone());
+            extensions.retain(|_, value| !value.is_null());
+            *stored = User {
+                extensions,
+                ..user.clone()
+            };
+            Ok(stored.clone())
+        })
+    }
+
+    async fn delete_user(&self, id: &str) -> AuthResult<()> {
+        self.write("delete_user", |rows| {
+            rows.users.retain(|u| u.id != id);
+            rows.sessions.retain(|s| s.user_id != id);
+            rows.accounts.retain(|a| a.user_id != id);
+            Ok(())
+        })
+    }
+
+    async fn create_session(&self, session: &Session) -> AuthResult<Session> {
+        self.write("create_session", |rows| {
+            rows.sessions.push(session.clone());
+            Ok(session.clone())
+        })
+    }
+
+    async fn get_session_by_id(&self, id: &str) -> AuthResult<Option<Session>> {
+        Ok(self.read(|rows| rows.sessions.iter().find(|s| s.id == id).cloned()))
+    }
+
+    async fn get_session_by_token(&self, token: &str) -> AuthResult<Option<Session>> {
+        Ok(self.read(|rows| rows.sessions.iter().find(|s| s.token == token).cloned()))
+    }
+
+    async fn get_sessions_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Session>> {
+        Ok(self.read(|rows| {
+            rows.sessions
+                .iter()
+                .filter(|s| s.user_id == user_id)
+                .cloned()
+                .collect()
+        }))
+    }
+
+    async fn update_session(&self, session: &Session) -> AuthResult<Session> {
+        self.write("update_session", |rows| {
+            let stored = rows
+                .sessions
+                .iter_mut()
+                .find(|s| s.id == session.id)
+                .ok_or_else(|| AuthError::not_found("session", "id", &session.id))?;
+            *stored = session.clone();
+            Ok(session.clone())
+        })
+    }
+
+    async fn delete_session(&self, id: &str) -> AuthResult<()> {
+        self.write("delete_session", |rows| {
+            rows.sessions.retain(|s| s.id != id);
+            Ok(())
+        })
+    }
+
+    async fn delete_sessions_by_user_id(&self, user_id: &str) -> AuthResult<()> {
+        self.write("delete_sessions_by_user_id", |rows| {
+            rows.sessions.retain(|s| s.user_id != user_id);
+            Ok(())
+        })
+    }
+
+    async fn create_account(&self, account: &Account) -> AuthResult<Account> {
+        self.write("create_account", |rows| {
+            rows.accounts.push(account.clone());
+            Ok(account.clone())
+        })
+    }
+
+    async fn get_account(
+        &self,
+        provider: &str,
+        provider_account_id: &str,
+    ) -> AuthResult<Option<Account>> {
+        Ok(self.read(|rows| {
+            rows.accounts
+                .iter()
+                .find(|a| a.provider == provider && a.provider_account_id == provider_account_id)
+                .cloned()
+        }))
+    }
+
+    async fn get_account_by_id(&self, id: &str) -> AuthResult<Option<Account>> {
+        Ok(self.read(|rows| rows.accounts.iter().find(|a| a.id == id).cloned()))
+    }
+
+    async fn get_accounts_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Account>> {
+        Ok(self.read(|rows| {
+            rows.accounts
+                .iter()
+                .filter(|a| a.user_id == user_id)
+                .cloned()
+                .collect()
+        }))
+    }
+
+    async fn update_account(&self, account: &Account) -> AuthResult<Account> {
+        self.write("update_account", |rows| {
+            let stored = rows
+                .accounts
+                .iter_mut()
+                .find(|a| a.id == account.id)
+                .ok_or_else(|| AuthError::not_found("account", "id", &account.id))?;
+            *stored = account.clone();
+            Ok(account.clone())
+        })
+    }
+
+    async fn delete_account(&self, id: &str) -> AuthResult<()> {
+        self.write("delete_account", |rows| {
+            rows.accounts.retain(|a| a.id != id);
+            Ok(())
+        })
+    }
+
+    async fn migrate(&self, _: &[ModelDefinition], _: bool) -> AuthResult<Vec<MigrationOp>> {
+        Ok(Vec::new())
+    }
+
+    async fn table_exists(&self, _: &str) -> AuthResult<bool> {
+        Ok(true)
+    }
+
+    async fn begin(&self) -> AuthResult<Option<Arc<dyn StorageTransaction>>> {
+        if !self.transactional {
+            return Ok(None);
+        }
+        let snapshot = self.rows.lock().unwrap().clone();
+        Ok(Some(Arc::new(Self {
+            staged: Some(Arc::new(Mutex::new(snapshot))),
+            ..self.clone()
+        })))
+    }
+}
+
+#[async_trait]
+impl StorageTransaction for TestStorage {
+    async fn commit(&self) -> AuthResult<()> {
+        self.log.lock().unwrap().push("commit".to_string());
+        if let Some(staged) = &self.staged {
+            *self.rows.lock().unwrap() = staged.lock().unwrap().clone();
+        }
+        Ok(())
+    }
+
+    async fn rollback(&self) -> AuthResult<()> {
+        self.log.lock().unwrap().push("rollback".to_string());
+        Ok(())
+    }
+}
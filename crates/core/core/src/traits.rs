@@ -4,14 +4,16 @@
 //! extensions must implement to integrate with the authentication system.
 
 use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
 use crate::context::{AuthContext, SignInCredentials, SignUpData};
-use crate::error::AuthResult;
+use crate::error::{AuthError, AuthResult, ConfigIssue};
 use crate::router::Router;
-use crate::schema::{ModelDefinition, SchemaBuilder};
-use crate::types::{Account, Session, User};
+use crate::schema::{MigrationOp, ModelDefinition, SchemaBuilder, SchemaDefinition};
+use crate::types::{Account, Session, User, UserFilter};
 
 /// Trait for extending the User model with plugin-specific data.
 ///
@@ -83,6 +85,15 @@ pub trait AuthPlugin: Send + Sync {
     /// Returns a human-readable name for this plugin.
     fn name(&self) -> &'static str;
 
+    /// Checks the plugin's configuration before the app starts.
+    ///
+    /// Called once while the app is built. Return
+    /// [`AuthError::invalid_config`] to report several problems at once;
+    /// any other error is reported as a single problem.
+    fn validate_config(&self) -> AuthResult<()> {
+        Ok(())
+    }
+
     /// Defines the schema requirements for this plugin.
     fn define_schema(&self, _builder: &mut SchemaBuilder) {}
 
@@ -121,6 +132,16 @@ pub trait AuthPlugin: Send + Sync {
         Ok(())
     }
 
+    /// Called when authentication fails because the credentials were
+    /// rejected, whether or not `creds.email` belongs to a user.
+    async fn on_signin_failure(
+        &self,
+        _ctx: &AuthContext,
+        _creds: &SignInCredentials,
+    ) -> AuthResult<()> {
+        Ok(())
+    }
+
     /// Called when a session is loaded.
     async fn on_session_load(
         &self,
@@ -141,6 +162,32 @@ pub trait AuthPlugin: Send + Sync {
         Ok(())
     }
 
+    /// Returns the data this plugin keeps about a user, for a data export.
+    ///
+    /// The result appears under the plugin's ID in the document built by
+    /// [`export_user_data`](crate::export::export_user_data). Key records
+    /// by model name, e.g. `{"api_key": [...]}`, so that the model's
+    /// private fields are removed, and leave secrets out. Returning `null`,
+    /// as the default does, omits the plugin.
+    async fn export_user_data(
+        &self,
+        _ctx: &AuthContext,
+        _user_id: &str,
+    ) -> AuthResult<serde_json::Value> {
+        Ok(serde_json::Value::Null)
+    }
+
+    /// Called before a user is deleted by
+    /// [`delete_user_fully`](crate::deletion::delete_user_fully).
+    ///
+    /// Remove every row this plugin keeps for the user, so that no keys or
+    /// secrets outlive the account. `ctx.db` is the transaction the user is
+    /// deleted in, if the adapter has transactions; returning an error
+    /// aborts the deletion.
+    async fn on_before_user_delete(&self, _ctx: &AuthContext, _user_id: &str) -> AuthResult<()> {
+        Ok(())
+    }
+
     // Legacy hooks for backward compatibility
     async fn before_create_user(&self, ctx: HookContext) -> AuthResult<HookContext> {
         Ok(ctx)
@@ -179,6 +226,46 @@ pub trait AuthPlugin: Send + Sync {
 ///
 /// Adapters implement this trait to provide persistence for users,
 /// sessions, and other authentication data.
+///
+/// # Timestamps
+///
+/// Adapters own `created_at`/`updated_at` for every record they store;
+/// callers never need to set them:
+///
+/// - `create_*` sets both `created_at` and `updated_at` to the current time,
+///   ignoring whatever values the caller passed in.
+/// - `update_*` preserves the stored `created_at` and bumps `updated_at`.
+///   The new value must be strictly greater than the stored one, even if
+///   the clock has not advanced since the previous write.
+///
+/// The returned record reflects the timestamps that were persisted.
+///
+/// # User extensions
+///
+/// Several plugins keep data in the same user's `extensions` (a password
+/// hash, a role, a 2FA secret), and each writes back the `User` it loaded.
+/// So `update_user` merges extensions instead of replacing them:
+///
+/// - a key with a value overwrites the stored value;
+/// - a key set to `null`, as [`User::remove_extension`] does, clears it;
+/// - a key that is absent leaves the stored value as it is.
+///
+/// A caller that only changes core fields such as `name` can therefore
+/// pass a `User` without any extensions. Stored `null`s are never returned.
+///
+/// # Soft deletion
+///
+/// [`soft_delete_user`](Self::soft_delete_user) keeps the user's row but
+/// sets its `deleted_at` and deletes its sessions. Until it is restored or
+/// purged, a soft-deleted user is invisible to `get_user_by_*` and to
+/// `list_users` (unless the filter sets `include_deleted`), so every
+/// sign-in method treats it as unknown. Its email stays taken.
+///
+/// `deleted_at` is owned by the adapter like the timestamps above:
+/// `create_user` and `update_user` ignore the value they are given.
+///
+/// Adapters that do not support soft deletion keep the defaults, which
+/// delete the user outright and have nothing to restore or purge.
 #[async_trait]
 pub trait StorageAdapter: Send + Sync {
     // ==================== User Operations ====================
@@ -192,20 +279,84 @@ pub trait StorageAdapter: Send + Sync {
     /// Gets a user by email.
     async fn get_user_by_email(&self, email: &str) -> AuthResult<Option<User>>;
 
+    /// Gets a user by the value of an extension field (e.g. `username`).
+    ///
+    /// Plugins with unique extension fields rely on this for lookups.
+    async fn get_user_by_extension(
+        &self,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> AuthResult<Option<User>> {
+        // Default implementation - adapters should override to support lookups
+        let _ = (key, value);
+        Ok(None)
+    }
+
     /// Updates an existing user.
+    ///
+    /// Extensions are merged with the stored ones; see
+    /// [User extensions](StorageAdapter#user-extensions).
     async fn update_user(&self, user: &User) -> AuthResult<User>;
 
+    /// Marks the email of the user with address `email` as verified.
+    ///
+    /// Returns the updated user, or `None` if no user has that email.
+    async fn mark_email_verified(&self, email: &str) -> AuthResult<Option<User>> {
+        let Some(mut user) = self.get_user_by_email(email).await? else {
+            return Ok(None);
+        };
+        if !user.email_verified {
+            user.email_verified = true;
+            user = self.update_user(&user).await?;
+        }
+        Ok(Some(user))
+    }
+
     /// Deletes a user by ID.
     async fn delete_user(&self, id: &str) -> AuthResult<()>;
 
-    /// Lists users with pagination.
-    async fn list_users(&self, offset: usize, limit: usize) -> AuthResult<Vec<User>> {
-        // Default implementation - adapters can override for efficiency
-        let _ = (offset, limit);
-        Ok(Vec::new())
+    /// Soft-deletes a user by ID and deletes its sessions.
+    ///
+    /// See [Soft deletion](StorageAdapter#soft-deletion). The default
+    /// implementation falls back to [`delete_user`](Self::delete_user).
+    async fn soft_delete_user(&self, id: &str) -> AuthResult<()> {
+        self.delete_user(id).await
+    }
+
+    /// Restores a soft-deleted user.
+    ///
+    /// Returns the restored user, or `None` if there is no soft-deleted
+    /// user with that ID.
+    async fn restore_user(&self, id: &str) -> AuthResult<Option<User>> {
+        let _ = id;
+        Ok(None)
+    }
+
+    /// Deletes the users that were soft-deleted before `deleted_before`,
+    /// along with their accounts.
+    ///
+    /// Returns the number of users deleted.
+    async fn purge_deleted_users(&self, deleted_before: DateTime<Utc>) -> AuthResult<usize> {
+        let _ = deleted_before;
+        Ok(0)
+    }
+
+    /// Lists a page of users matching `filter`.
+    ///
+    /// Returns the page together with the total number of matching users.
+    /// Users are ordered by `created_at`, oldest first.
+    async fn list_users(
+        &self,
+        offset: usize,
+        limit: usize,
+        filter: Option<UserFilter>,
+    ) -> AuthResult<(Vec<User>, usize)> {
+        // Default implementation - adapters should override to support listing
+        let _ = (offset, limit, filter);
+        Ok((Vec::new(), 0))
     }
 
-    /// Counts total users.
+    /// Counts users, leaving out soft-deleted ones.
     async fn count_users(&self) -> AuthResult<usize> {
         Ok(0)
     }
@@ -250,23 +401,54 @@ pub trait StorageAdapter: Send + Sync {
         provider_account_id: &str,
     ) -> AuthResult<Option<Account>>;
 
+    /// Gets an account by ID.
+    async fn get_account_by_id(&self, id: &str) -> AuthResult<Option<Account>> {
+        // Default implementation - adapters should override to support lookups
+        let _ = id;
+        Ok(None)
+    }
+
     /// Gets all accounts for a user.
     async fn get_accounts_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Account>>;
 
+    /// Updates an existing account, e.g. to store refreshed provider tokens.
+    async fn update_account(&self, account: &Account) -> AuthResult<Account> {
+        let _ = account;
+        Err(AuthError::internal(
+            "This storage adapter does not support updating accounts",
+        ))
+    }
+
     /// Deletes an account.
     async fn delete_account(&self, id: &str) -> AuthResult<()>;
 
     // ==================== Schema Operations ====================
 
-    /// Runs schema migrations.
-    async fn migrate(&self, models: &[ModelDefinition]) -> AuthResult<()>;
+    /// Brings storage up to `models`.
+    ///
+    /// Adapters compare [`introspect_schema`](Self::introspect_schema) with
+    /// `models` using [`SchemaDiff::additions`], so only missing tables,
+    /// columns, and indexes are created; running it again on an up-to-date
+    /// database does nothing. With `dry_run` the operations are planned but
+    /// not executed.
+    ///
+    /// Returns the operations that were run, or would have been.
+    ///
+    /// [`SchemaDiff::additions`]: crate::schema::SchemaDiff::additions
+    async fn migrate(
+        &self,
+        models: &[ModelDefinition],
+        dry_run: bool,
+    ) -> AuthResult<Vec<MigrationOp>>;
 
     /// Checks if a table exists.
     async fn table_exists(&self, table_name: &str) -> AuthResult<bool>;
 
-    /// Gets the current schema from the database.
-    async fn current_schema(&self) -> AuthResult<crate::schema::SchemaDefinition> {
-        Ok(crate::schema::SchemaDefinition::new())
+    /// Reads back the tables, columns, and indexes that currently exist.
+    ///
+    /// The default reports an empty schema.
+    async fn introspect_schema(&self) -> AuthResult<SchemaDefinition> {
+        Ok(SchemaDefinition::new())
     }
 
     // ==================== Generic Operations ====================
@@ -275,4 +457,241 @@ pub trait StorageAdapter: Send + Sync {
     async fn execute_raw(&self, _query: &str) -> AuthResult<()> {
         Ok(())
     }
+
+    // ==================== Transactions ====================
+
+    /// Starts a transaction.
+    ///
+    /// Returns `None` if the adapter has no transactions, which is the
+    /// default (the memory adapter, for one). Callers should normally use
+    /// [`run_in_transaction`] instead.
+    async fn begin(&self) -> AuthResult<Option<Arc<dyn StorageTransaction>>> {
+        Ok(None)
+    }
+}
+
+/// Storage whose writes take effect together when committed.
+///
+/// Returned by [`StorageAdapter::begin`]. Operations on the handle run
+/// inside the transaction until it is committed or rolled back; using it
+/// after that fails.
+#[async_trait]
+pub trait StorageTransaction: StorageAdapter {
+    /// Applies every write made through this handle.
+    async fn commit(&self) -> AuthResult<()>;
+
+    /// Discards every write made through this handle.
+    async fn rollback(&self) -> AuthResult<()>;
+}
+
+/// Runs `work` atomically on `storage`.
+///
+/// `work` is given the storage to use. If the adapter supports
+/// transactions this is a [`StorageTransaction`], committed when `work`
+/// returns `Ok` and rolled back when it returns `Err`. Adapters without
+/// transactions, such as the memory adapter, get `storage` itself and run
+/// the closure directly, so writes made before a failure are kept.
+pub async fn run_in_transaction<T, F, Fut>(
+    storage: &Arc<dyn StorageAdapter>,
+    work: F,
+) -> AuthResult<T>
+where
+    F: FnOnce(Arc<dyn StorageAdapter>) -> Fut,
+    Fut: Future<Output = AuthResult<T>>,
+{
+    let Some(tx) = storage.begin().await? else {
+        return work(storage.clone()).await;
+    };
+    match work(tx.clone()).await {
+        Ok(value) => {
+            tx.commit().await?;
+            Ok(value)
+        }
+        Err(err) => {
+            // Report the original failure rather than a failed rollback.
+            if let Err(rollback_err) = tx.rollback().await {
+                tracing::warn!("Failed to roll back transaction: {}", rollback_err);
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Trait for dedicated session stores.
+///
+/// Covers the session portion of [`StorageAdapter`] so sessions can live in
+/// a separate backend (e.g. Redis) from users and accounts. Session stores
+/// follow the same timestamp contract as storage adapters.
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Creates a new session.
+    async fn create_session(&self, session: &Session) -> AuthResult<Session>;
+
+    /// Gets a session by ID.
+    async fn get_session_by_id(&self, id: &str) -> AuthResult<Option<Session>>;
+
+    /// Gets a session by token.
+    async fn get_session_by_token(&self, token: &str) -> AuthResult<Option<Session>>;
+
+    /// Gets all sessions for a user.
+    async fn get_sessions_by_user_id(&self, user_id: &str) -> AuthResult<Vec<Session>>;
+
+    /// Updates an existing session.
+    async fn update_session(&self, session: &Session) -> AuthResult<Session>;
+
+    /// Deletes a session by ID.
+    async fn delete_session(&self, id: &str) -> AuthResult<()>;
+
+    /// Deletes all sessions for a user.
+    async fn delete_sessions_by_user_id(&self, user_id: &str) -> AuthResult<()>;
+
+    /// Deletes expired sessions.
+    ///
+    /// Stores with native expiry can rely on the default no-op.
+    async fn delete_expired_sessions(&self) -> AuthResult<usize> {
+        Ok(0)
+    }
+}
+
+/// Returns the `updated_at` value for a write following `previous`.
+///
+/// Falls back to `previous + 1µs` when the clock has not moved past it,
+/// so adapters can keep `updated_at` strictly monotonic per record.
+pub fn next_updated_at(previous: DateTime<Utc>) -> DateTime<Utc> {
+    let now = Utc::now();
+    if now > previous {
+        now
+    } else {
+        previous + Duration::microseconds(1)
+    }
+}
+
+/// Runs [`AuthPlugin::validate_config`] on every plugin.
+///
+/// Problems from all plugins are collected into a single
+/// [`AuthError::InvalidPluginConfig`] instead of stopping at the first one.
+pub fn validate_plugins<'a, I>(plugins: I) -> AuthResult<()>
+where
+    I: IntoIterator<Item = &'a dyn AuthPlugin>,
+{
+    let mut issues = Vec::new();
+    for plugin in plugins {
+        match plugin.validate_config() {
+            Ok(()) => {}
+            Err(AuthError::InvalidPluginConfig { issues: reported }) => issues.extend(reported),
+            Err(AuthError::ConfigurationError { message }) => {
+                issues.push(ConfigIssue::new(plugin.id(), message))
+            }
+            Err(err) => issues.push(ConfigIssue::new(plugin.id(), err.to_string())),
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(AuthError::InvalidPluginConfig { issues })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestStorage;
+
+    struct Checked(&'static str, Result<(), fn() -> AuthError>);
+
+    #[async_trait]
+    impl AuthPlugin for Checked {
+        fn id(&self) -> &'static str {
+            self.0
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn validate_config(&self) -> AuthResult<()> {
+            self.1.map_err(|err| err())
+        }
+    }
+
+    #[test]
+    fn test_validate_plugins_aggregates_issues() {
+        let ok = Checked("ok", Ok(()));
+        let single = Checked("jwt", Err(|| AuthError::config("secret is empty")));
+        let multi = Checked(
+            "oauth",
+            Err(|| AuthError::invalid_config("oauth", ["google: blank client id", "github: blank client id"])),
+        );
+
+        let plugins: Vec<&dyn AuthPlugin> = vec![&ok, &single, &multi];
+        let Err(AuthError::InvalidPluginConfig { issues }) = validate_plugins(plugins) else {
+            panic!("expected aggregated config error");
+        };
+
+        assert_eq!(
+            issues,
+            vec![
+                ConfigIssue::new("jwt", "secret is empty"),
+                ConfigIssue::new("oauth", "google: blank client id"),
+                ConfigIssue::new("oauth", "github: blank client id"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_plugins_ok() {
+        let ok = Checked("ok", Ok(()));
+        assert!(validate_plugins([&ok as &dyn AuthPlugin]).is_ok());
+    }
+
+    async fn create_user(storage: Arc<dyn StorageAdapter>, fail: bool) -> AuthResult<User> {
+        let user = storage
+            .create_user(&User::new("u1".to_string(), "a@example.com".to_string()))
+            .await?;
+        if fail {
+            return Err(AuthError::conflict("second write failed"));
+        }
+        Ok(user)
+    }
+
+    #[tokio::test]
+    async fn test_run_in_transaction_commits_on_success() {
+        let storage = TestStorage::new().transactional();
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+
+        let user = run_in_transaction(&db, |tx| create_user(tx, false))
+            .await
+            .unwrap();
+        assert_eq!(user.id, "u1");
+        assert_eq!(storage.log(), vec!["tx:create_user", "commit"]);
+        assert_eq!(storage.rows().users.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_in_transaction_rolls_back_on_error() {
+        let storage = TestStorage::new().transactional();
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+
+        let err = run_in_transaction(&db, |tx| create_user(tx, true))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthError::Conflict { .. }));
+        assert_eq!(storage.log(), vec!["tx:create_user", "rollback"]);
+        assert!(storage.rows().users.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_in_transaction_without_support_runs_directly() {
+        let storage = TestStorage::new();
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+
+        assert!(
+            run_in_transaction(&db, |tx| create_user(tx, true))
+                .await
+                .is_err()
+        );
+        assert_eq!(storage.log(), vec!["create_user"]);
+        assert_eq!(storage.rows().users.len(), 1);
+    }
 }
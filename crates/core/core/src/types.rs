@@ -7,6 +7,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::{AuthError, AuthResult};
+use crate::redact::{Redact, RedactionPolicy};
 
 /// Represents an authenticated user in the system.
 ///
@@ -23,7 +27,10 @@ use std::collections::HashMap;
 /// let user = User::new("user_123".to_string(), "user@example.com".to_string());
 /// assert!(!user.email_verified);
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The `Debug` output masks sensitive extension fields such as password
+/// hashes.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct User {
     /// Unique identifier for the user (typically a UUID or CUID)
     pub id: String,
@@ -49,6 +56,14 @@ pub struct User {
     /// Timestamp when the user was last updated
     pub updated_at: DateTime<Utc>,
 
+    /// Timestamp when the user was soft-deleted, if they were.
+    ///
+    /// Set and cleared only by
+    /// [`StorageAdapter::soft_delete_user`](crate::traits::StorageAdapter::soft_delete_user)
+    /// and [`restore_user`](crate::traits::StorageAdapter::restore_user).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+
     /// Extension data from plugins.
     ///
     /// This map holds arbitrary key-value pairs that plugins can use
@@ -73,10 +88,29 @@ impl User {
             image: None,
             created_at: now,
             updated_at: now,
+            deleted_at: None,
             extensions: HashMap::new(),
         }
     }
 
+    /// Returns true if the user has been soft-deleted.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Fails with [`AuthError::EmailNotVerified`] unless the user's email
+    /// is verified.
+    pub fn require_verified_email(&self) -> AuthResult<()> {
+        if self.email_verified {
+            Ok(())
+        } else {
+            Err(AuthError::EmailNotVerified {
+                user_id: self.id.clone(),
+                email: self.email.clone(),
+            })
+        }
+    }
+
     /// Gets an extension value by key, deserializing it to the requested type.
     ///
     /// Returns `None` if the key doesn't exist or deserialization fails.
@@ -98,9 +132,15 @@ impl User {
 
     /// Removes an extension value by key.
     ///
-    /// Returns the removed value if it existed.
+    /// The key is kept with a `null` value rather than dropped, because
+    /// [`StorageAdapter::update_user`](crate::traits::StorageAdapter::update_user)
+    /// leaves absent keys untouched; the `null` tells it to clear the stored
+    /// value. Returns the removed value if it existed.
     pub fn remove_extension(&mut self, key: &str) -> Option<Value> {
-        let result = self.extensions.remove(key);
+        let result = self
+            .extensions
+            .insert(key.to_string(), Value::Null)
+            .filter(|value| !value.is_null());
         if result.is_some() {
             self.updated_at = Utc::now();
         }
@@ -114,11 +154,179 @@ impl Default for User {
     }
 }
 
+/// Criteria for listing users. Unset fields match every user, except that
+/// soft-deleted users are left out unless `include_deleted` is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserFilter {
+    /// Case-insensitive substring of the email address.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_contains: Option<String>,
+
+    /// Only users created strictly after this time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<DateTime<Utc>>,
+
+    /// Only users created strictly before this time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<DateTime<Utc>>,
+
+    /// Extension fields that must equal the given values.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extensions: HashMap<String, Value>,
+
+    /// Whether soft-deleted users are listed too.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_deleted: bool,
+}
+
+impl UserFilter {
+    /// Creates a filter that matches every user.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches users whose email contains `needle`, ignoring case.
+    pub fn email_contains(mut self, needle: impl Into<String>) -> Self {
+        self.email_contains = Some(needle.into());
+        self
+    }
+
+    /// Matches users created after `time`.
+    pub fn created_after(mut self, time: DateTime<Utc>) -> Self {
+        self.created_after = Some(time);
+        self
+    }
+
+    /// Matches users created before `time`.
+    pub fn created_before(mut self, time: DateTime<Utc>) -> Self {
+        self.created_before = Some(time);
+        self
+    }
+
+    /// Matches users whose extension field `key` equals `value`.
+    pub fn extension(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.extensions.insert(key.into(), value.into());
+        self
+    }
+
+    /// Also matches soft-deleted users.
+    pub fn include_deleted(mut self) -> Self {
+        self.include_deleted = true;
+        self
+    }
+
+    /// Returns true if `user` satisfies every set criterion.
+    pub fn matches(&self, user: &User) -> bool {
+        if user.is_deleted() && !self.include_deleted {
+            return false;
+        }
+        if let Some(needle) = &self.email_contains
+            && !user.email.to_lowercase().contains(&needle.to_lowercase())
+        {
+            return false;
+        }
+        if let Some(after) = self.created_after
+            && user.created_at <= after
+        {
+            return false;
+        }
+        if let Some(before) = self.created_before
+            && user.created_at >= before
+        {
+            return false;
+        }
+        self.extensions
+            .iter()
+            .all(|(key, value)| user.extensions.get(key) == Some(value))
+    }
+}
+
+/// How users are deleted.
+///
+/// With `Soft`, deleting a user sets its `deleted_at` and revokes its
+/// sessions; the row is kept until a later purge, and the user can be
+/// restored in the meantime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserDeletion {
+    /// Remove the user and everything that references it immediately.
+    #[default]
+    Hard,
+    /// Mark the user as deleted with
+    /// [`StorageAdapter::soft_delete_user`](crate::traits::StorageAdapter::soft_delete_user).
+    Soft,
+}
+
+/// A way of proving identity that a session can record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthFactor {
+    /// A password.
+    Password,
+    /// A one-time code sent by email or SMS.
+    Otp,
+    /// A magic link sent by email.
+    MagicLink,
+    /// A social or OAuth provider.
+    #[serde(rename = "oauth")]
+    OAuth,
+    /// A code from an authenticator app.
+    Totp,
+    /// A single-use backup code.
+    BackupCode,
+    /// A WebAuthn passkey.
+    Passkey,
+}
+
+impl AuthFactor {
+    /// Returns the factor's name, as stored on sessions.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Password => "password",
+            Self::Otp => "otp",
+            Self::MagicLink => "magic_link",
+            Self::OAuth => "oauth",
+            Self::Totp => "totp",
+            Self::BackupCode => "backup_code",
+            Self::Passkey => "passkey",
+        }
+    }
+
+    /// Returns true for factors that count towards multi-factor assurance.
+    ///
+    /// Passkeys count on their own: they combine possession of a device with
+    /// a PIN or biometric check.
+    pub fn is_strong(&self) -> bool {
+        matches!(self, Self::Totp | Self::BackupCode | Self::Passkey)
+    }
+}
+
+impl fmt::Display for AuthFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How strongly a session's user has proven their identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssuranceLevel {
+    /// Signed in with a single factor, such as a password.
+    SingleFactor,
+    /// A second factor or a passkey was verified on the session.
+    MultiFactor,
+}
+
+/// Session extension key holding when each [`AuthFactor`] was verified.
+const AUTH_FACTORS_KEY: &str = "auth_factors";
+
 /// Represents an active session for a user.
 ///
 /// Sessions track authenticated user sessions and can store
 /// additional metadata like device information, IP addresses, etc.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The `Debug` output masks the session token.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Session {
     /// Unique identifier for the session
     pub id: String,
@@ -138,6 +346,17 @@ pub struct Session {
     /// Timestamp when the session was last updated
     pub updated_at: DateTime<Utc>,
 
+    /// When the user last fully authenticated on this session
+    ///
+    /// Sessions stored before this field existed deserialize as never
+    /// authenticated, so they must re-authenticate before sensitive actions.
+    #[serde(default = "never_authenticated")]
+    pub authenticated_at: DateTime<Utc>,
+
+    /// When the session last authenticated a request, if it has been used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+
     /// Optional IP address of the client
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ip_address: Option<String>,
@@ -165,6 +384,8 @@ impl Session {
             expires_at: now + chrono::Duration::days(7),
             created_at: now,
             updated_at: now,
+            authenticated_at: now,
+            last_used_at: None,
             ip_address: None,
             user_agent: None,
             extensions: HashMap::new(),
@@ -189,6 +410,84 @@ impl Session {
         self.updated_at = Utc::now();
     }
 
+    /// Records a full re-authentication, keeping the session's ID and token.
+    pub fn mark_authenticated(&mut self) {
+        let now = Utc::now();
+        self.authenticated_at = now;
+        self.updated_at = now;
+    }
+
+    /// Checks that the user fully authenticated within `max_age`.
+    ///
+    /// Returns [`AuthError::ReauthenticationRequired`] otherwise. Use this to
+    /// guard sensitive actions such as changing credentials.
+    pub fn require_recent_auth(&self, max_age: chrono::Duration) -> AuthResult<()> {
+        if Utc::now() - self.authenticated_at <= max_age {
+            Ok(())
+        } else {
+            Err(AuthError::ReauthenticationRequired)
+        }
+    }
+
+    /// Records that the user just verified `factor` on this session.
+    ///
+    /// Factors are kept in the `auth_factors` extension with the time each
+    /// was last verified, so they survive a round trip through storage.
+    pub fn record_factor(&mut self, factor: AuthFactor) {
+        let mut factors = self.factors();
+        factors.insert(factor, Utc::now());
+        self.set_extension(AUTH_FACTORS_KEY, factors);
+    }
+
+    /// Returns when each factor was last verified on this session.
+    pub fn factors(&self) -> HashMap<AuthFactor, DateTime<Utc>> {
+        self.get_extension(AUTH_FACTORS_KEY).unwrap_or_default()
+    }
+
+    /// Returns when `factor` was last verified on this session, if ever.
+    pub fn factor_verified_at(&self, factor: AuthFactor) -> Option<DateTime<Utc>> {
+        self.factors().get(&factor).copied()
+    }
+
+    /// Returns the session's assurance level: multi-factor once any
+    /// [strong](AuthFactor::is_strong) factor has been verified on it.
+    pub fn assurance_level(&self) -> AssuranceLevel {
+        if self.factors().keys().any(AuthFactor::is_strong) {
+            AssuranceLevel::MultiFactor
+        } else {
+            AssuranceLevel::SingleFactor
+        }
+    }
+
+    /// Checks that `factor` was verified on this session within `max_age`.
+    ///
+    /// Returns [`AuthError::StepUpRequired`] otherwise, so the client knows
+    /// to prompt for that factor rather than sign the user out.
+    pub fn require_factor(&self, factor: AuthFactor, max_age: chrono::Duration) -> AuthResult<()> {
+        self.require_any_factor(&[factor], max_age)
+    }
+
+    /// Like [`require_factor`](Self::require_factor), but any one of
+    /// `factors` will do, e.g. a TOTP code or a passkey.
+    pub fn require_any_factor(
+        &self,
+        factors: &[AuthFactor],
+        max_age: chrono::Duration,
+    ) -> AuthResult<()> {
+        let verified = self.factors();
+        let now = Utc::now();
+        if factors
+            .iter()
+            .any(|factor| verified.get(factor).is_some_and(|at| now - *at <= max_age))
+        {
+            Ok(())
+        } else {
+            Err(AuthError::StepUpRequired {
+                factors: factors.to_vec(),
+            })
+        }
+    }
+
     /// Gets an extension value by key.
     pub fn get_extension<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Option<T> {
         self.extensions
@@ -203,12 +502,22 @@ impl Session {
             self.updated_at = Utc::now();
         }
     }
+
+    /// Returns the ID of the admin impersonating the session's user, if
+    /// this is an impersonation session.
+    pub fn impersonated_by(&self) -> Option<String> {
+        self.get_extension("impersonated_by")
+    }
+}
+
+fn never_authenticated() -> DateTime<Utc> {
+    DateTime::UNIX_EPOCH
 }
 
 /// Represents an account linked to a user (e.g., OAuth provider).
 ///
 /// This is used for social login and other external authentication methods.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Account {
     /// Unique identifier for the account
     pub id: String,
@@ -234,6 +543,10 @@ pub struct Account {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<DateTime<Utc>>,
 
+    /// The provider's raw profile, for providers configured to store it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Value>,
+
     /// Timestamp when the account was created
     pub created_at: DateTime<Utc>,
 
@@ -253,12 +566,71 @@ impl Account {
             access_token: None,
             refresh_token: None,
             expires_at: None,
+            profile: None,
             created_at: now,
             updated_at: now,
         }
     }
 }
 
+impl fmt::Debug for User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("User")
+            .field("id", &self.id)
+            .field("email", &self.email)
+            .field("email_verified", &self.email_verified)
+            .field("name", &self.name)
+            .field("image", &self.image)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .field("deleted_at", &self.deleted_at)
+            .field("extensions", &redacted_extensions(&self.extensions))
+            .finish()
+    }
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("id", &self.id)
+            .field("user_id", &self.user_id)
+            .field("token", &Redact(&self.token))
+            .field("expires_at", &self.expires_at)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .field("authenticated_at", &self.authenticated_at)
+            .field("last_used_at", &self.last_used_at)
+            .field("ip_address", &self.ip_address)
+            .field("user_agent", &self.user_agent)
+            .field("extensions", &redacted_extensions(&self.extensions))
+            .finish()
+    }
+}
+
+impl fmt::Debug for Account {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The profile is personal data, so only its presence is shown.
+        f.debug_struct("Account")
+            .field("id", &self.id)
+            .field("user_id", &self.user_id)
+            .field("provider", &self.provider)
+            .field("provider_account_id", &self.provider_account_id)
+            .field("access_token", &self.access_token.as_ref().map(Redact))
+            .field("refresh_token", &self.refresh_token.as_ref().map(Redact))
+            .field("expires_at", &self.expires_at)
+            .field("profile", &self.profile.as_ref().map(Redact))
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .finish()
+    }
+}
+
+fn redacted_extensions(extensions: &HashMap<String, Value>) -> Value {
+    let mut value = Value::Object(extensions.clone().into_iter().collect());
+    RedactionPolicy::default().scrub_json(&mut value);
+    value
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,6 +643,18 @@ mod tests {
         assert!(!user.email_verified);
     }
 
+    #[test]
+    fn test_require_verified_email() {
+        let mut user = User::new("test_id".to_string(), "test@example.com".to_string());
+        assert!(matches!(
+            user.require_verified_email(),
+            Err(AuthError::EmailNotVerified { user_id, email })
+                if user_id == "test_id" && email == "test@example.com"
+        ));
+        user.email_verified = true;
+        assert!(user.require_verified_email().is_ok());
+    }
+
     #[test]
     fn test_user_extensions() {
         let mut user = User::new("test_id".to_string(), "test@example.com".to_string());
@@ -279,6 +663,40 @@ mod tests {
             user.get_extension::<String>("custom_field"),
             Some("custom_value".to_string())
         );
+
+        assert_eq!(
+            user.remove_extension("custom_field"),
+            Some(Value::from("custom_value"))
+        );
+        assert_eq!(user.get_extension::<String>("custom_field"), None);
+        assert_eq!(user.extensions["custom_field"], Value::Null);
+        assert_eq!(user.remove_extension("custom_field"), None);
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let mut user = User::new("test_id".to_string(), "test@example.com".to_string());
+        user.set_extension("password_hash", "$argon2id$v=19$secret");
+        let debug = format!("{:?}", user);
+        assert!(debug.contains("test@example.com"));
+        assert!(!debug.contains("argon2id"));
+
+        let session = Session::new("user_id".to_string());
+        assert!(!format!("{:?}", session).contains(&session.token));
+
+        let mut account = Account::new(
+            "user_id".to_string(),
+            "github".to_string(),
+            "123".to_string(),
+        );
+        account.access_token = Some("gho_access".to_string());
+        account.refresh_token = Some("ghr_refresh".to_string());
+        account.profile = Some(serde_json::json!({ "name": "Jane Doe" }));
+        let debug = format!("{:?}", account);
+        assert!(debug.contains("github"));
+        assert!(!debug.contains("gho_access"));
+        assert!(!debug.contains("ghr_refresh"));
+        assert!(!debug.contains("Jane Doe"));
     }
 
     #[test]
@@ -289,4 +707,64 @@ mod tests {
         );
         assert!(session.is_expired());
     }
+
+    #[test]
+    fn test_require_recent_auth() {
+        let mut session = Session::new("user_id".to_string());
+        let window = chrono::Duration::minutes(5);
+        assert!(session.require_recent_auth(window).is_ok());
+
+        session.authenticated_at = Utc::now() - chrono::Duration::minutes(10);
+        assert!(matches!(
+            session.require_recent_auth(window),
+            Err(AuthError::ReauthenticationRequired)
+        ));
+
+        let token = session.token.clone();
+        session.mark_authenticated();
+        assert!(session.require_recent_auth(window).is_ok());
+        assert_eq!(session.token, token);
+    }
+
+    #[test]
+    fn test_require_factor_and_assurance_level() {
+        let mut session = Session::new("user_id".to_string());
+        let window = chrono::Duration::minutes(5);
+        assert_eq!(session.assurance_level(), AssuranceLevel::SingleFactor);
+        match session.require_factor(AuthFactor::Totp, window) {
+            Err(err @ AuthError::StepUpRequired { .. }) => {
+                assert_eq!(err.error_code(), "STEP_UP_REQUIRED");
+                assert_eq!(err.status_code(), 403);
+            }
+            other => panic!("expected a step-up error, got {:?}", other),
+        }
+
+        session.record_factor(AuthFactor::Password);
+        assert_eq!(session.assurance_level(), AssuranceLevel::SingleFactor);
+        session.record_factor(AuthFactor::Totp);
+        assert_eq!(session.assurance_level(), AssuranceLevel::MultiFactor);
+        assert!(session.require_factor(AuthFactor::Totp, window).is_ok());
+        assert!(session.require_factor(AuthFactor::Passkey, window).is_err());
+        let second = [AuthFactor::Passkey, AuthFactor::Totp];
+        assert!(session.require_any_factor(&second, window).is_ok());
+
+        // Stale factors need stepping up again, even after a storage round trip.
+        let mut factors = session.factors();
+        factors.insert(AuthFactor::Totp, Utc::now() - chrono::Duration::minutes(10));
+        session.set_extension("auth_factors", factors);
+        let session: Session =
+            serde_json::from_value(serde_json::to_value(&session).unwrap()).unwrap();
+        assert!(session.require_factor(AuthFactor::Totp, window).is_err());
+        assert!(session.factor_verified_at(AuthFactor::Password).is_some());
+    }
+
+    #[test]
+    fn test_legacy_session_is_not_recently_authenticated() {
+        let mut value = serde_json::to_value(Session::new("user_id".to_string())).unwrap();
+        value.as_object_mut().unwrap().remove("authenticated_at");
+        let session: Session = serde_json::from_value(value).unwrap();
+
+        assert_eq!(session.authenticated_at, DateTime::UNIX_EPOCH);
+        assert!(session.require_recent_auth(chrono::Duration::days(365)).is_err());
+    }
 }
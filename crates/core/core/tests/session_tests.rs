@@ -0,0 +1,187 @@
+//! Session resolver and session route tests against the memory adapter.
+
+use better_auth_adapter_memory::MemoryAdapter;
+use better_auth_core::router::{Method, Request, RequestHandler};
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::Session;
+use better_auth_core::{
+    ListSessions, RevokeSession, SessionResolver, SessionTokenStrategy, end_replaced_session,
+};
+use chrono::Duration;
+use std::sync::Arc;
+
+fn session(id: &str, user_id: &str) -> Session {
+    let mut session = Session::new(user_id.to_string());
+    session.id = id.to_string();
+    session.token = format!("tok_{}", id);
+    session.user_agent = Some(format!("agent {}", id));
+    session
+}
+
+/// A memory adapter holding `sessions`, looked up by token `tok_<id>`.
+async fn storage(sessions: &[(&str, &str)]) -> Arc<MemoryAdapter> {
+    let storage = Arc::new(MemoryAdapter::new());
+    for (id, user_id) in sessions {
+        storage.create_session(&session(id, user_id)).await.unwrap();
+    }
+    storage
+}
+
+fn bearer(token: &str) -> impl Fn(&str) -> Option<String> {
+    let value = format!("Bearer {}", token);
+    move |name| (name == "authorization").then(|| value.clone())
+}
+
+fn sessions_request(method: Method, token: &str, id: Option<&str>) -> Request {
+    let mut req = Request::new(method, "/sessions");
+    req.headers
+        .insert("authorization".to_string(), format!("Bearer {}", token));
+    if let Some(id) = id {
+        req.params.insert("id".to_string(), id.to_string());
+    }
+    req
+}
+
+#[tokio::test]
+async fn test_rotate_session_token() {
+    let storage = storage(&[("laptop", "user_1")]).await;
+    let resolver = SessionResolver::new(storage.clone());
+    let mut current = storage.get_session_by_id("laptop").await.unwrap().unwrap();
+    current
+        .extensions
+        .insert("two_factor_verified".to_string(), serde_json::json!(true));
+    let before = current.clone();
+
+    resolver.rotate_session_token(&mut current).await.unwrap();
+    assert_ne!(current.token, before.token);
+    assert_eq!(current.id, before.id);
+    assert_eq!(current.user_id, before.user_id);
+    assert_eq!(current.expires_at, before.expires_at);
+    assert_eq!(current.extensions, before.extensions);
+
+    assert!(
+        resolver
+            .resolve(bearer(&before.token))
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let resolved = resolver.resolve(bearer(&current.token)).await.unwrap();
+    assert_eq!(resolved.unwrap().session.id, "laptop");
+}
+
+#[tokio::test]
+async fn test_signed_session_tokens() {
+    let storage = storage(&[("laptop", "user_1")]).await;
+    let strategy = SessionTokenStrategy::signed("a-long-random-secret");
+    let resolver = SessionResolver::new(storage.clone()).token_strategy(strategy.clone());
+    let mut current = storage.get_session_by_id("laptop").await.unwrap().unwrap();
+
+    resolver.rotate_session_token(&mut current).await.unwrap();
+    let claims = strategy.verify(&current.token).unwrap();
+    assert_eq!(claims.session_id, "laptop");
+    assert_eq!(claims.user_id, "user_1");
+
+    let resolved = resolver.resolve(bearer(&current.token)).await.unwrap();
+    assert_eq!(resolved.unwrap().session.id, "laptop");
+
+    // A forged token is turned away even if storage would match it.
+    current.token = format!("{}.forged", current.token.split_once('.').unwrap().0);
+    storage.update_session(&current).await.unwrap();
+    assert!(
+        resolver
+            .resolve(bearer(&current.token))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_list_sessions_marks_current() {
+    let storage = storage(&[
+        ("laptop", "user_1"),
+        ("phone", "user_1"),
+        ("other", "user_2"),
+    ])
+    .await;
+    let handler = ListSessions::new(SessionResolver::new(storage));
+
+    let response = handler
+        .handle(sessions_request(Method::GET, "tok_phone", None))
+        .await;
+    assert_eq!(response.status, 200);
+    let body = response.body.unwrap();
+    let sessions = body["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    for s in sessions {
+        assert_eq!(s["current"], s["id"] == "phone");
+        assert!(s.get("token").is_none());
+    }
+    let laptop = sessions.iter().find(|s| s["id"] == "laptop").unwrap();
+    assert_eq!(laptop["user_agent"], "agent laptop");
+
+    let response = handler
+        .handle(sessions_request(Method::GET, "tok_missing", None))
+        .await;
+    assert_eq!(response.status, 401);
+    assert_eq!(response.body.unwrap()["error"]["code"], "UNAUTHORIZED");
+    let response = handler.handle(Request::new(Method::GET, "/sessions")).await;
+    assert_eq!(response.status, 401);
+}
+
+#[tokio::test]
+async fn test_revoke_session_keeps_current_and_others_users() {
+    let storage = storage(&[
+        ("laptop", "user_1"),
+        ("phone", "user_1"),
+        ("other", "user_2"),
+    ])
+    .await;
+    let handler = RevokeSession::new(SessionResolver::new(storage.clone()));
+    let delete = |id| sessions_request(Method::DELETE, "tok_phone", Some(id));
+
+    assert_eq!(handler.handle(delete("phone")).await.status, 409);
+    assert_eq!(handler.handle(delete("other")).await.status, 404);
+    assert_eq!(handler.handle(delete("laptop")).await.status, 204);
+    assert!(storage.get_session_by_id("laptop").await.unwrap().is_none());
+    assert!(storage.get_session_by_id("phone").await.unwrap().is_some());
+    assert!(storage.get_session_by_id("other").await.unwrap().is_some());
+
+    let unauthenticated = Request::new(Method::DELETE, "/sessions/phone");
+    assert_eq!(handler.handle(unauthenticated).await.status, 401);
+}
+
+#[tokio::test]
+async fn test_record_use_is_throttled() {
+    let storage = storage(&[("laptop", "user_1")]).await;
+    let resolver = SessionResolver::new(storage.clone());
+
+    let mut laptop = storage.get_session_by_id("laptop").await.unwrap().unwrap();
+    assert!(resolver.record_use(&mut laptop).await.unwrap());
+    let stored = storage.get_session_by_id("laptop").await.unwrap().unwrap();
+    assert_eq!(stored.last_used_at, laptop.last_used_at);
+    assert!(!resolver.record_use(&mut laptop).await.unwrap());
+
+    let every_request = resolver.last_used_interval(Duration::zero());
+    assert!(every_request.record_use(&mut laptop).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_sign_in_ends_the_replaced_cookie_session() {
+    let storage = storage(&[("laptop", "user_1"), ("phone", "user_1")]).await;
+
+    let mut req = Request::new(Method::POST, "/sign-in/email");
+    req.headers.insert(
+        "cookie".to_string(),
+        "better_auth_session=tok_laptop".to_string(),
+    );
+    req.headers
+        .insert("authorization".to_string(), "Bearer tok_phone".to_string());
+    end_replaced_session(storage.as_ref(), &req).await.unwrap();
+    assert!(storage.get_session_by_id("laptop").await.unwrap().is_none());
+    assert!(storage.get_session_by_id("phone").await.unwrap().is_some());
+
+    // Nothing to end.
+    end_replaced_session(storage.as_ref(), &req).await.unwrap();
+}
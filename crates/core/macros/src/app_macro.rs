@@ -69,6 +69,9 @@ pub fn app_impl(input: TokenStream) -> TokenStream {
         /// Generated authentication application struct.
         pub struct #name {
             adapter: std::sync::Arc<dyn better_auth_core::traits::StorageAdapter>,
+            user_deletion: better_auth_core::types::UserDeletion,
+            session_tokens: better_auth_core::SessionTokenStrategy,
+            events: Option<std::sync::Arc<better_auth_core::events::EventBus>>,
             #(#plugin_fields: #plugins,)*
         }
 
@@ -87,7 +90,8 @@ pub fn app_impl(input: TokenStream) -> TokenStream {
             pub async fn migrate(&self) -> better_auth_core::error::AuthResult<()> {
                 let models = better_auth_core::schema::core_schema();
                 // TODO: Collect schemas from plugins
-                self.adapter.migrate(&models).await
+                self.adapter.migrate(&models, false).await?;
+                Ok(())
             }
 
             /// Gets a user by ID.
@@ -105,14 +109,35 @@ pub fn app_impl(input: TokenStream) -> TokenStream {
                 self.adapter.create_user(user).await
             }
 
+            /// Deletes a user along with their plugin data, or soft-deletes
+            /// it if the app was built with `UserDeletion::Soft`.
+            pub async fn delete_user(&self, id: &str) -> better_auth_core::error::AuthResult<()> {
+                match self.user_deletion {
+                    better_auth_core::types::UserDeletion::Hard => {
+                        let ctx = better_auth_core::context::AuthContext::new(self.adapter.clone());
+                        let plugins: Vec<&dyn better_auth_core::traits::AuthPlugin> = vec![
+                            #(&self.#plugin_fields,)*
+                        ];
+                        better_auth_core::deletion::delete_user_fully(&ctx, id, &plugins, self.events.as_deref()).await
+                    }
+                    better_auth_core::types::UserDeletion::Soft => self.adapter.soft_delete_user(id).await,
+                }
+            }
+
+            /// Restores a soft-deleted user.
+            pub async fn restore_user(&self, id: &str) -> better_auth_core::error::AuthResult<Option<better_auth_core::types::User>> {
+                self.adapter.restore_user(id).await
+            }
+
             /// Gets a session by token.
             pub async fn get_session(&self, token: &str) -> better_auth_core::error::AuthResult<Option<better_auth_core::types::Session>> {
                 self.adapter.get_session_by_token(token).await
             }
 
-            /// Creates a new session for a user.
+            /// Creates a new session for a user, with a token from the
+            /// app's session token strategy.
             pub async fn create_session(&self, user_id: &str) -> better_auth_core::error::AuthResult<better_auth_core::types::Session> {
-                let session = better_auth_core::types::Session::new(user_id.to_string());
+                let session = self.session_tokens.new_session(user_id);
                 self.adapter.create_session(&session).await
             }
 
@@ -120,12 +145,26 @@ pub fn app_impl(input: TokenStream) -> TokenStream {
             pub async fn invalidate_session(&self, session_id: &str) -> better_auth_core::error::AuthResult<()> {
                 self.adapter.delete_session(session_id).await
             }
+
+            /// Builds the app's routes under `base_path`: every plugin's
+            /// routes plus the `/sessions` management routes.
+            pub fn router(&self, base_path: impl Into<String>) -> better_auth_core::router::Router {
+                let mut router = better_auth_core::router::Router::new(base_path);
+                #(better_auth_core::traits::AuthPlugin::register_routes(&self.#plugin_fields, &mut router);)*
+                let resolver = better_auth_core::session::SessionResolver::new(self.adapter.clone())
+                    .token_strategy(self.session_tokens.clone());
+                better_auth_core::session::register_session_routes(&mut router, resolver);
+                router
+            }
         }
 
         /// Builder for the auth application.
         #[derive(Default)]
         pub struct #builder_name {
             adapter: Option<std::sync::Arc<dyn better_auth_core::traits::StorageAdapter>>,
+            user_deletion: better_auth_core::types::UserDeletion,
+            session_tokens: better_auth_core::SessionTokenStrategy,
+            events: Option<std::sync::Arc<better_auth_core::events::EventBus>>,
             #(#plugin_fields: Option<#plugins>,)*
         }
 
@@ -136,18 +175,53 @@ pub fn app_impl(input: TokenStream) -> TokenStream {
                 self
             }
 
+            /// Sets how `delete_user` deletes users (default: `UserDeletion::Hard`).
+            pub fn user_deletion(mut self, mode: better_auth_core::types::UserDeletion) -> Self {
+                self.user_deletion = mode;
+                self
+            }
+
+            /// Sets how `create_session` generates tokens (default: opaque,
+            /// 32 characters).
+            pub fn session_tokens(mut self, strategy: better_auth_core::SessionTokenStrategy) -> Self {
+                self.session_tokens = strategy;
+                self
+            }
+
+            /// Sets the event bus app events such as `user.deleted` are
+            /// emitted on.
+            pub fn event_bus(mut self, bus: std::sync::Arc<better_auth_core::events::EventBus>) -> Self {
+                self.events = Some(bus);
+                self
+            }
+
             /// Builds the auth application.
+            ///
+            /// Fails if the storage adapter is missing, the session token
+            /// strategy is invalid, or any plugin's configuration is invalid;
+            /// plugin problems are reported together.
             pub fn build(self) -> Result<#name, better_auth_core::error::AuthError> {
                 let adapter = self.adapter.ok_or_else(|| {
                     better_auth_core::error::AuthError::ConfigurationError {
                         message: "Storage adapter is required".to_string(),
                     }
                 })?;
+                self.session_tokens.validate()?;
 
-                Ok(#name {
+                let app = #name {
                     adapter,
+                    user_deletion: self.user_deletion,
+                    session_tokens: self.session_tokens,
+                    events: self.events,
                     #(#plugin_fields: self.#plugin_fields.unwrap_or_default(),)*
-                })
+                };
+
+                let plugins: Vec<&dyn better_auth_core::traits::AuthPlugin> = vec![
+                    #(&app.#plugin_fields,)*
+                ];
+                better_auth_core::traits::validate_plugins(plugins)?;
+
+                Ok(app)
             }
         }
     };
@@ -23,6 +23,10 @@ mod parsing;
 /// This macro generates the `AppAuth` struct and associated builder,
 /// configured with the specified plugins and settings.
 ///
+/// The builder's `build()` calls `AuthPlugin::validate_config` on every
+/// plugin and fails with all reported problems, so misconfiguration is
+/// caught at startup.
+///
 /// # Example
 ///
 /// ```rust,ignore
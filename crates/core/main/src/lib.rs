@@ -58,8 +58,19 @@ pub mod config {
         pub base_path: String,
         /// Session duration in seconds (default: 7 days)
         pub session_duration_secs: u64,
-        /// Whether to require email verification
+        /// Whether to require email verification. Sign-in routes enforce
+        /// this through their own config, such as
+        /// `PasswordConfig::require_email_verification`.
         pub require_email_verification: bool,
+        /// How users are deleted (default: hard). Applied through the app
+        /// builder's `user_deletion` and `AdminConfig::user_deletion`.
+        #[serde(default)]
+        pub user_deletion: better_auth_core::types::UserDeletion,
+        /// How session tokens are generated (default: opaque, 32
+        /// characters). Applied through the app builder's `session_tokens`
+        /// and `SessionResolver::token_strategy`.
+        #[serde(default)]
+        pub session_tokens: better_auth_core::SessionTokenStrategy,
     }
 
     impl Default for AuthConfig {
@@ -68,6 +79,8 @@ pub mod config {
                 base_path: "/api/auth".to_string(),
                 session_duration_secs: 7 * 24 * 60 * 60, // 7 days
                 require_email_verification: false,
+                user_deletion: Default::default(),
+                session_tokens: Default::default(),
             }
         }
     }
@@ -1,14 +1,16 @@
 //! Event bus for pub/sub communication.
 
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Instant;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+use crate::dlq::{DeadLetter, DeadLetterQueue};
 use crate::error::{EventError, EventResult};
 use crate::event::Event;
 use crate::handler::{BoxedHandler, EventHandler, HandlerResult};
 use crate::middleware::{EventMiddleware, MiddlewareChain};
+use crate::schema::EventSchemaRegistry;
 
 /// The event bus for publishing and subscribing to events.
 pub struct EventBus {
@@ -16,6 +18,8 @@ pub struct EventBus {
     subscribers: RwLock<HashMap<String, Vec<Arc<BoxedHandler>>>>,
     /// Wildcard subscribers (receive all events).
     wildcard_subscribers: RwLock<Vec<Arc<BoxedHandler>>>,
+    /// Subscribers that `emit` awaits, in registration order.
+    sync_subscribers: RwLock<Vec<(String, Arc<BoxedHandler>)>>,
     /// Event history (optional, for debugging).
     history: RwLock<Vec<Event>>,
     /// Maximum history size.
@@ -24,6 +28,93 @@ pub struct EventBus {
     middleware: RwLock<MiddlewareChain>,
     /// Whether to run handlers in parallel.
     parallel_handlers: bool,
+    /// Recently seen idempotency keys.
+    dedupe: Mutex<DedupeWindow>,
+    /// Schemas that emitted payloads are checked against.
+    schemas: Option<Arc<EventSchemaRegistry>>,
+    /// What to do with payloads that fail their schema.
+    schema_enforcement: SchemaEnforcement,
+    /// Where rejected events are parked, if anywhere.
+    schema_dead_letters: Option<Arc<DeadLetterQueue>>,
+}
+
+/// What the bus does with an event whose payload fails its registered
+/// schema.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchemaEnforcement {
+    /// Payloads are not checked.
+    #[default]
+    Off,
+    /// The errors are logged and the event is delivered anyway.
+    Warn,
+    /// The event is dropped before any handler sees it.
+    /// [`emit_checked`](EventBus::emit_checked) returns
+    /// [`EventError::SchemaViolation`] with the errors, and `emit_sync`
+    /// returns a failed result for the `schema` handler ID.
+    Reject,
+}
+
+/// Handler ID reported for events rejected by the schema check.
+const SCHEMA_CHECK_ID: &str = "schema";
+
+/// Default number of idempotency keys remembered.
+const DEFAULT_DEDUPE_CAPACITY: usize = 10_000;
+
+/// Default time an idempotency key is remembered.
+const DEFAULT_DEDUPE_TTL: Duration = Duration::from_secs(600);
+
+/// A bounded window of recently seen idempotency keys.
+///
+/// Keys are forgotten after `ttl`, or oldest first once `capacity` is
+/// reached. A key is remembered from when it was first seen; seeing it again
+/// doesn't extend its life.
+struct DedupeWindow {
+    seen: HashMap<String, Instant>,
+    order: VecDeque<(String, Instant)>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl DedupeWindow {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Records `key`, returning false if it was already in the window.
+    fn insert(&mut self, key: &str, now: Instant) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        while let Some((_, seen_at)) = self.order.front()
+            && now.duration_since(*seen_at) >= self.ttl
+        {
+            let (expired, _) = self.order.pop_front().unwrap();
+            self.seen.remove(&expired);
+        }
+        if self.seen.contains_key(key) {
+            return false;
+        }
+        if self.order.len() >= self.capacity
+            && let Some((oldest, _)) = self.order.pop_front()
+        {
+            self.seen.remove(&oldest);
+        }
+        self.seen.insert(key.to_string(), now);
+        self.order.push_back((key.to_string(), now));
+        true
+    }
+
+    /// Forgets `key`, so it can be inserted again.
+    fn remove(&mut self, key: &str) {
+        if self.seen.remove(key).is_some() {
+            self.order.retain(|(seen, _)| seen != key);
+        }
+    }
 }
 
 impl EventBus {
@@ -32,10 +123,18 @@ impl EventBus {
         Self {
             subscribers: RwLock::new(HashMap::new()),
             wildcard_subscribers: RwLock::new(Vec::new()),
+            sync_subscribers: RwLock::new(Vec::new()),
             history: RwLock::new(Vec::new()),
             max_history: 1000,
             middleware: RwLock::new(MiddlewareChain::new()),
             parallel_handlers: true,
+            dedupe: Mutex::new(DedupeWindow::new(
+                DEFAULT_DEDUPE_CAPACITY,
+                DEFAULT_DEDUPE_TTL,
+            )),
+            schemas: None,
+            schema_enforcement: SchemaEnforcement::Off,
+            schema_dead_letters: None,
         }
     }
 
@@ -44,10 +143,18 @@ impl EventBus {
         Self {
             subscribers: RwLock::new(HashMap::new()),
             wildcard_subscribers: RwLock::new(Vec::new()),
+            sync_subscribers: RwLock::new(Vec::new()),
             history: RwLock::new(Vec::new()),
             max_history,
             middleware: RwLock::new(MiddlewareChain::new()),
             parallel_handlers,
+            dedupe: Mutex::new(DedupeWindow::new(
+                DEFAULT_DEDUPE_CAPACITY,
+                DEFAULT_DEDUPE_TTL,
+            )),
+            schemas: None,
+            schema_enforcement: SchemaEnforcement::Off,
+            schema_dead_letters: None,
         }
     }
 
@@ -56,6 +163,40 @@ impl EventBus {
         Self::with_config(max_history, true)
     }
 
+    /// Sets how many idempotency keys are remembered, and for how long.
+    ///
+    /// Defaults to 10,000 keys for 10 minutes. A capacity of 0 turns
+    /// deduplication off.
+    pub fn with_dedupe_window(self, capacity: usize, ttl: Duration) -> Self {
+        *self.dedupe.lock().unwrap() = DedupeWindow::new(capacity, ttl);
+        self
+    }
+
+    /// Checks every emitted payload against its schema in `registry`.
+    ///
+    /// The check runs after `before_emit` middleware, so it sees the payload
+    /// handlers would get. Whether events of a type with no registered
+    /// schema pass is up to the registry's `allow_unregistered` setting.
+    /// Redelivered events are checked again.
+    pub fn with_schema_validation(
+        mut self,
+        registry: Arc<EventSchemaRegistry>,
+        enforcement: SchemaEnforcement,
+    ) -> Self {
+        self.schemas = Some(registry);
+        self.schema_enforcement = enforcement;
+        self
+    }
+
+    /// Parks events rejected by the schema check in `dlq`.
+    ///
+    /// The dead letters are stored as parked under the `schema` handler ID,
+    /// since delivering them again would fail the same way.
+    pub fn with_schema_dead_letters(mut self, dlq: Arc<DeadLetterQueue>) -> Self {
+        self.schema_dead_letters = Some(dlq);
+        self
+    }
+
     /// Adds middleware to the event bus.
     pub async fn add_middleware(&self, middleware: impl EventMiddleware + 'static) {
         let mut chain = self.middleware.write().await;
@@ -66,8 +207,17 @@ impl EventBus {
     ///
     /// Patterns support:
     /// - Exact match: "user.created"
-    /// - Namespace wildcard: "user.*"
+    /// - Namespace wildcard: "user.*", which also matches nested names such
+    ///   as "user.email.verified"
     /// - All events: "*"
+    ///
+    /// See [`EventType::matches`] for the full syntax. Every handler whose
+    /// pattern matches an event receives it: exact subscriptions run first,
+    /// then wildcard patterns, then `*` subscribers.
+    ///
+    /// The handler runs in the background: [`emit`](Self::emit) spawns it
+    /// and returns without waiting. Use [`on_sync`](Self::on_sync) for
+    /// handlers that must finish before `emit` returns.
     pub async fn on(&self, pattern: &str, handler: impl EventHandler + 'static) {
         if pattern == "*" {
             let mut subs = self.wildcard_subscribers.write().await;
@@ -80,14 +230,36 @@ impl EventBus {
         }
     }
 
+    /// Subscribes a handler that runs before `emit` returns.
+    ///
+    /// Sync handlers run one at a time, in registration order, before any
+    /// background handler, and `emit` awaits each of them. Use this for work
+    /// the caller relies on, such as writing an audit record before a
+    /// response is sent. A failing sync handler is logged and doesn't stop
+    /// the ones after it. Patterns are the same as for [`on`](Self::on).
+    pub async fn on_sync(&self, pattern: &str, handler: impl EventHandler + 'static) {
+        let mut subs = self.sync_subscribers.write().await;
+        subs.push((pattern.to_string(), Arc::new(Box::new(handler))));
+    }
+
     /// Subscribes to all events.
     pub async fn on_all(&self, handler: impl EventHandler + 'static) {
         let mut subs = self.wildcard_subscribers.write().await;
         subs.push(Arc::new(Box::new(handler)));
     }
 
-    /// Emits an event to all matching subscribers (fire and forget).
+    /// Emits an event to all matching subscribers.
+    ///
+    /// Handlers registered with [`on_sync`](Self::on_sync) are awaited in
+    /// order; the others are spawned and not waited for. An event whose
+    /// idempotency key was recently delivered is dropped, as is one rejected
+    /// by middleware or the [schema check](Self::with_schema_validation). If
+    /// the event is rejected or a sync handler fails, the key is forgotten so
+    /// the event can be emitted again.
     pub async fn emit(&self, event: Event) {
+        if self.is_duplicate(&event) {
+            return;
+        }
         let mut event = event;
 
         // Run before_emit middleware
@@ -95,13 +267,27 @@ impl EventBus {
             let middleware = self.middleware.read().await;
             if let Err(e) = middleware.before_emit(&mut event).await {
                 tracing::error!("Middleware rejected event: {}", e);
+                self.forget(&event);
                 return;
             }
         }
 
+        if self.enforce_schema(&event, true).await.is_err() {
+            self.forget(&event);
+            return;
+        }
+
         // Store in history
         self.store_in_history(event.clone()).await;
 
+        // Run sync handlers before returning
+        for handler in self.collect_sync_handlers(&event).await {
+            if let Err(e) = handler.handle(&event).await {
+                tracing::error!("Event handler '{}' error: {}", handler.id(), e);
+                self.forget(&event);
+            }
+        }
+
         // Collect matching handlers
         let handlers = self.collect_handlers(&event).await;
 
@@ -126,24 +312,63 @@ impl EventBus {
     }
 
     /// Emits an event and waits for all handlers to complete.
+    ///
+    /// Sync handlers run first, followed by the background handlers.
+    ///
+    /// An event whose idempotency key was recently delivered is dropped, and
+    /// no results are returned. An event rejected by the schema check gets a
+    /// single failed result for the `schema` handler ID. If any handler
+    /// fails, the key is forgotten so the event can be emitted again.
     pub async fn emit_sync(&self, event: Event) -> Vec<HandlerResult> {
-        let mut event = event;
+        if self.is_duplicate(&event) {
+            return Vec::new();
+        }
+        let key_holder = event.clone();
+        let results = self
+            .deliver(event, true)
+            .await
+            .unwrap_or_else(schema_failure);
+        if results.iter().any(|result| !result.success) {
+            self.forget(&key_holder);
+        }
+        results
+    }
+
+    /// Emits an event like [`emit_sync`](Self::emit_sync), without checking
+    /// its idempotency key.
+    ///
+    /// Used to retry events the bus has already seen once.
+    pub(crate) async fn redeliver(&self, event: Event) -> Vec<HandlerResult> {
+        self.deliver(event, false)
+            .await
+            .unwrap_or_else(schema_failure)
+    }
+
+    // Internal helper to run middleware, the schema check and every handler,
+    // collecting the results. Only fails if the schema check rejects the
+    // event, which is then dead-lettered if `dead_letter` is set.
+    async fn deliver(&self, event: Event, dead_letter: bool) -> EventResult<Vec<HandlerResult>> {
         let mut results = Vec::new();
+        let mut event = event;
 
         // Run before_emit middleware
         {
             let middleware = self.middleware.read().await;
             if let Err(e) = middleware.before_emit(&mut event).await {
                 tracing::error!("Middleware rejected event: {}", e);
-                return results;
+                self.forget(&event);
+                return Ok(results);
             }
         }
 
+        self.enforce_schema(&event, dead_letter).await?;
+
         // Store in history
         self.store_in_history(event.clone()).await;
 
         // Collect matching handlers
-        let handlers = self.collect_handlers(&event).await;
+        let mut handlers = self.collect_sync_handlers(&event).await;
+        handlers.extend(self.collect_handlers(&event).await);
 
         // Run all handlers and collect results
         for handler in handlers {
@@ -163,15 +388,25 @@ impl EventBus {
             middleware.after_emit(&event, &results).await;
         }
 
-        results
+        Ok(results)
     }
 
     /// Emits an event and returns an error if any handler fails.
+    ///
+    /// An event rejected by the schema check fails with
+    /// [`EventError::SchemaViolation`], listing what is wrong with it.
     pub async fn emit_checked(&self, event: Event) -> EventResult<()> {
-        let results = self.emit_sync(event).await;
+        if self.is_duplicate(&event) {
+            return Ok(());
+        }
+        let key_holder = event.clone();
+        let results = self.deliver(event, true).await.inspect_err(|_| {
+            self.forget(&key_holder);
+        })?;
 
         for result in results {
             if !result.success {
+                self.forget(&key_holder);
                 return Err(EventError::HandlerFailed(
                     result.error.unwrap_or_else(|| "Unknown error".to_string()),
                 ));
@@ -199,12 +434,16 @@ impl EventBus {
 
     /// Gets the number of subscribers for a pattern.
     pub async fn subscriber_count(&self, pattern: &str) -> usize {
+        let sync_count = {
+            let subs = self.sync_subscribers.read().await;
+            subs.iter().filter(|(p, _)| p == pattern).count()
+        };
         if pattern == "*" {
             let subs = self.wildcard_subscribers.read().await;
-            subs.len()
+            subs.len() + sync_count
         } else {
             let subs = self.subscribers.read().await;
-            subs.get(pattern).map(|v| v.len()).unwrap_or(0)
+            subs.get(pattern).map(|v| v.len()).unwrap_or(0) + sync_count
         }
     }
 
@@ -214,6 +453,8 @@ impl EventBus {
         subs.clear();
         let mut wildcards = self.wildcard_subscribers.write().await;
         wildcards.clear();
+        let mut sync_subs = self.sync_subscribers.write().await;
+        sync_subs.clear();
     }
 
     /// Clears event history.
@@ -222,6 +463,76 @@ impl EventBus {
         history.clear();
     }
 
+    // Internal helper to record an event's idempotency key. Events without
+    // one never take the lock. The key is recorded up front so concurrent
+    // emits of the same event are dropped; `forget` undoes it when delivery
+    // fails.
+    fn is_duplicate(&self, event: &Event) -> bool {
+        let Some(key) = &event.metadata.idempotency_key else {
+            return false;
+        };
+        let duplicate = !self.dedupe.lock().unwrap().insert(key, Instant::now());
+        if duplicate {
+            tracing::debug!(
+                "Dropping duplicate event {} with idempotency key {}",
+                event.id,
+                key
+            );
+        }
+        duplicate
+    }
+
+    // Internal helper to forget an event's idempotency key after a failed
+    // delivery, so a retry isn't dropped as a duplicate.
+    fn forget(&self, event: &Event) {
+        if let Some(key) = &event.metadata.idempotency_key {
+            self.dedupe.lock().unwrap().remove(key);
+        }
+    }
+
+    // Internal helper to check an event against its schema. Failures are
+    // logged; under `Reject` they are returned, and parked in the schema
+    // dead letter queue if `dead_letter` is set.
+    async fn enforce_schema(&self, event: &Event, dead_letter: bool) -> EventResult<()> {
+        let Some(registry) = &self.schemas else {
+            return Ok(());
+        };
+        if self.schema_enforcement == SchemaEnforcement::Off {
+            return Ok(());
+        }
+        let result = registry.check_event(event).await;
+        if result.is_valid() {
+            return Ok(());
+        }
+        let err = EventError::SchemaViolation {
+            event_type: event.event_type.to_string(),
+            errors: result.errors.iter().map(ToString::to_string).collect(),
+        };
+        if self.schema_enforcement == SchemaEnforcement::Warn {
+            tracing::warn!("Delivering event {} anyway: {}", event.id, err);
+            return Ok(());
+        }
+        tracing::error!("Rejected event {}: {}", event.id, err);
+        if dead_letter && let Some(dlq) = &self.schema_dead_letters {
+            let now = chrono::Utc::now();
+            let letter = DeadLetter {
+                id: uuid::Uuid::new_v4().to_string(),
+                event: event.clone(),
+                handler_id: SCHEMA_CHECK_ID.to_string(),
+                error: err.to_string(),
+                attempts: 0,
+                first_failed_at: now,
+                last_failed_at: now,
+                stack_trace: None,
+                parked: true,
+            };
+            if let Err(e) = dlq.send(letter).await {
+                tracing::error!("Failed to dead-letter event {}: {}", event.id, e);
+            }
+        }
+        Err(err)
+    }
+
     // Internal helper to store event in history
     async fn store_in_history(&self, event: Event) {
         let mut history = self.history.write().await;
@@ -231,15 +542,26 @@ impl EventBus {
         }
     }
 
+    // Internal helper to collect matching sync handlers, in registration order
+    async fn collect_sync_handlers(&self, event: &Event) -> Vec<Arc<BoxedHandler>> {
+        let subs = self.sync_subscribers.read().await;
+        subs.iter()
+            .filter(|(pattern, _)| event.event_type.matches(pattern))
+            .map(|(_, handler)| handler.clone())
+            .collect()
+    }
+
     // Internal helper to collect matching handlers
     async fn collect_handlers(&self, event: &Event) -> Vec<Arc<BoxedHandler>> {
         let mut handlers = Vec::new();
 
-        // Get specific subscribers
+        // Get exact subscribers, then wildcard patterns
         let subs = self.subscribers.read().await;
-        for (pattern, pattern_handlers) in subs.iter() {
-            if event.event_type.matches(pattern) {
-                handlers.extend(pattern_handlers.iter().cloned());
+        for wildcard in [false, true] {
+            for (pattern, pattern_handlers) in subs.iter() {
+                if pattern.contains('*') == wildcard && event.event_type.matches(pattern) {
+                    handlers.extend(pattern_handlers.iter().cloned());
+                }
             }
         }
 
@@ -257,10 +579,18 @@ impl Default for EventBus {
     }
 }
 
+/// Reports a schema rejection as the result of the `schema` handler.
+fn schema_failure(err: EventError) -> Vec<HandlerResult> {
+    vec![HandlerResult::failure(SCHEMA_CHECK_ID, err.to_string(), 0)]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dlq::{DLQQuery, InMemoryDLQStorage};
     use crate::event::EventType;
+    use crate::schema::EventSchema;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     struct TestHandler {
         id: String,
@@ -347,6 +677,83 @@ mod tests {
         assert_eq!(events.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_exact_and_wildcard_handlers_both_fire() {
+        let bus = EventBus::with_config(1000, false);
+        let received = Arc::new(RwLock::new(Vec::new()));
+
+        for (id, pattern) in [("all", "*"), ("user", "user.*"), ("exact", "user.created")] {
+            bus.on(
+                pattern,
+                TestHandler {
+                    id: id.to_string(),
+                    received: received.clone(),
+                },
+            )
+            .await;
+        }
+
+        let results = bus
+            .emit_sync(Event::new(EventType::new("user", "created"), "payload"))
+            .await;
+        let order: Vec<_> = results.iter().map(|r| r.handler_id.as_str()).collect();
+        assert_eq!(order, ["exact", "user", "all"]);
+
+        bus.emit_sync(Event::new(EventType::new("user", "deleted"), "payload"))
+            .await;
+        bus.emit_sync(Event::new(EventType::new("username", "changed"), "payload"))
+            .await;
+
+        let events = received.read().await;
+        assert_eq!(events.iter().filter(|e| *e == "user.deleted").count(), 2);
+        assert_eq!(
+            events.iter().filter(|e| *e == "username.changed").count(),
+            1
+        );
+    }
+
+    struct SlowHandler {
+        id: String,
+        delay_ms: u64,
+        received: Arc<RwLock<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventHandler for SlowHandler {
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        async fn handle(&self, _event: &Event) -> Result<(), EventError> {
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            self.received.write().await.push(self.id.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_handlers_complete_before_emit_returns() {
+        let bus = EventBus::new();
+        let received = Arc::new(RwLock::new(Vec::new()));
+        let handler = |id: &str, delay_ms| SlowHandler {
+            id: id.to_string(),
+            delay_ms,
+            received: received.clone(),
+        };
+
+        bus.on("user.created", handler("background", 200)).await;
+        bus.on_sync("user.*", handler("audit", 50)).await;
+        bus.on_sync("user.created", handler("metrics", 0)).await;
+        assert_eq!(bus.subscriber_count("user.created").await, 2);
+
+        bus.emit(Event::new(EventType::new("user", "created"), "payload"))
+            .await;
+        assert_eq!(*received.read().await, ["audit", "metrics"]);
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(*received.read().await, ["audit", "metrics", "background"]);
+    }
+
     #[tokio::test]
     async fn test_event_history() {
         let bus = EventBus::with_history_size(10);
@@ -365,4 +772,279 @@ mod tests {
         let all = bus.recent_events(100).await;
         assert_eq!(all.len(), 10); // Max history size
     }
+
+    #[tokio::test]
+    async fn test_idempotency_key_dedupes_emits() {
+        let bus = EventBus::new();
+        let received = Arc::new(RwLock::new(Vec::new()));
+
+        bus.on(
+            "test.event",
+            TestHandler {
+                id: "test".to_string(),
+                received: received.clone(),
+            },
+        )
+        .await;
+
+        let event = || Event::new(EventType::new("test", "event"), "payload");
+        bus.emit_sync(event().with_idempotency_key("op-1")).await;
+        let results = bus.emit_sync(event().with_idempotency_key("op-1")).await;
+        assert!(results.is_empty());
+        bus.emit(event().with_idempotency_key("op-1")).await;
+
+        // Events without a key, or with a different one, are unaffected.
+        bus.emit_sync(event()).await;
+        bus.emit_sync(event()).await;
+        bus.emit_sync(event().with_idempotency_key("op-2")).await;
+
+        assert_eq!(received.read().await.len(), 4);
+        assert_eq!(bus.recent_events(100).await.len(), 4);
+    }
+
+    /// Fails its first call and succeeds after that.
+    struct FailsOnceHandler {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventHandler for FailsOnceHandler {
+        fn id(&self) -> &str {
+            "fails-once"
+        }
+
+        async fn handle(&self, _event: &Event) -> Result<(), EventError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(EventError::HandlerFailed("not yet".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_can_be_retried_with_the_same_key() {
+        let bus = EventBus::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        bus.on_sync(
+            "test.event",
+            FailsOnceHandler {
+                calls: calls.clone(),
+            },
+        )
+        .await;
+
+        let event =
+            || Event::new(EventType::new("test", "event"), "payload").with_idempotency_key("op-1");
+        assert!(bus.emit_checked(event()).await.is_err());
+        bus.emit_checked(event()).await.unwrap();
+        // Delivered once, so now it is a duplicate.
+        assert!(bus.emit_sync(event()).await.is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    /// Rejects its first event and passes the others.
+    struct RejectsOnceMiddleware {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventMiddleware for RejectsOnceMiddleware {
+        async fn before_emit(&self, _event: &mut Event) -> Result<(), EventError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(EventError::HandlerFailed("rejected".to_string()));
+            }
+            Ok(())
+        }
+
+        async fn after_emit(&self, _event: &Event, _results: &[HandlerResult]) {}
+    }
+
+    #[tokio::test]
+    async fn test_event_rejected_by_middleware_can_be_emitted_again() {
+        let event =
+            || Event::new(EventType::new("test", "event"), "payload").with_idempotency_key("op-1");
+
+        for sync in [false, true] {
+            let bus = EventBus::new();
+            let received = Arc::new(RwLock::new(Vec::new()));
+            bus.on_sync(
+                "test.event",
+                TestHandler {
+                    id: "test".to_string(),
+                    received: received.clone(),
+                },
+            )
+            .await;
+            bus.add_middleware(RejectsOnceMiddleware {
+                calls: Arc::new(AtomicUsize::new(0)),
+            })
+            .await;
+
+            for _ in 0..2 {
+                if sync {
+                    bus.emit_sync(event()).await;
+                } else {
+                    bus.emit(event()).await;
+                }
+            }
+            assert_eq!(received.read().await.len(), 1, "sync: {sync}");
+        }
+    }
+
+    async fn schema_checked_bus(enforcement: SchemaEnforcement) -> EventBus {
+        let registry = EventSchemaRegistry::new();
+        registry
+            .register(EventSchema::simple(
+                EventType::new("user", "created"),
+                vec!["user_id".to_string(), "email".to_string()],
+            ))
+            .await
+            .unwrap();
+        EventBus::new().with_schema_validation(Arc::new(registry), enforcement)
+    }
+
+    #[tokio::test]
+    async fn test_schema_enforcement_rejects_invalid_payloads() {
+        let bus = schema_checked_bus(SchemaEnforcement::Reject).await;
+        let received = Arc::new(RwLock::new(Vec::new()));
+        bus.on_sync(
+            "*",
+            TestHandler {
+                id: "test".to_string(),
+                received: received.clone(),
+            },
+        )
+        .await;
+
+        let invalid = || {
+            Event::new(
+                EventType::new("user", "created"),
+                serde_json::json!({ "email": 42 }),
+            )
+        };
+        let Err(EventError::SchemaViolation { event_type, errors }) =
+            bus.emit_checked(invalid()).await
+        else {
+            panic!("expected a schema violation");
+        };
+        assert_eq!(event_type, "user.created.v1");
+        assert_eq!(
+            errors,
+            [
+                "user_id: Required field 'user_id' is missing",
+                "email: Type mismatch (expected string, got number)",
+            ]
+        );
+
+        let results = bus.emit_sync(invalid()).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].handler_id, "schema");
+        assert!(!results[0].success);
+        bus.emit(invalid()).await;
+        assert!(received.read().await.is_empty());
+        assert!(bus.recent_events(10).await.is_empty());
+
+        let valid = serde_json::json!({ "user_id": "u1", "email": "jane@example.com" });
+        bus.emit_checked(Event::new(EventType::new("user", "created"), valid))
+            .await
+            .unwrap();
+        assert_eq!(*received.read().await, ["user.created"]);
+
+        // Without `allow_unregistered`, types with no schema are rejected too.
+        let err = bus
+            .emit_checked(Event::simple("session.created", "payload"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No schema registered"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_event_can_be_emitted_again_with_the_same_key() {
+        let bus = schema_checked_bus(SchemaEnforcement::Reject).await;
+        let received = Arc::new(RwLock::new(Vec::new()));
+        bus.on_sync(
+            "*",
+            TestHandler {
+                id: "test".to_string(),
+                received: received.clone(),
+            },
+        )
+        .await;
+
+        let event = |payload| {
+            Event::new(EventType::new("user", "created"), payload).with_idempotency_key("signup-1")
+        };
+        let invalid = serde_json::json!({ "user_id": "u1" });
+        let valid = serde_json::json!({ "user_id": "u1", "email": "jane@example.com" });
+
+        bus.emit(event(invalid.clone())).await;
+        bus.emit_sync(event(invalid.clone())).await;
+        assert!(bus.emit_checked(event(invalid)).await.is_err());
+        bus.emit_checked(event(valid.clone())).await.unwrap();
+        assert_eq!(*received.read().await, ["user.created"]);
+
+        // Once delivered, the key is taken.
+        bus.emit(event(valid)).await;
+        assert_eq!(received.read().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_schema_enforcement_warn_and_off_deliver() {
+        for enforcement in [SchemaEnforcement::Warn, SchemaEnforcement::Off] {
+            let bus = schema_checked_bus(enforcement).await;
+            let event = Event::new(EventType::new("user", "created"), serde_json::json!({}));
+            bus.emit_checked(event).await.unwrap();
+            assert_eq!(bus.recent_events(10).await.len(), 1, "{:?}", enforcement);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejected_events_are_dead_lettered() {
+        let dlq = Arc::new(DeadLetterQueue::new(Arc::new(InMemoryDLQStorage::new())));
+        let bus = schema_checked_bus(SchemaEnforcement::Reject)
+            .await
+            .with_schema_dead_letters(dlq.clone());
+
+        let event = Event::new(
+            EventType::new("user", "created"),
+            serde_json::json!({ "user_id": "u1" }),
+        );
+        let event_id = event.id.clone();
+        bus.emit(event).await;
+
+        let letters = dlq.list(DLQQuery::default()).await.unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].event.id, event_id);
+        assert_eq!(letters[0].handler_id, "schema");
+        assert!(letters[0].parked);
+        assert!(
+            letters[0]
+                .error
+                .contains("Required field 'email' is missing")
+        );
+    }
+
+    #[test]
+    fn test_dedupe_window_expiry_and_eviction() {
+        let start = Instant::now();
+        let mut window = DedupeWindow::new(2, Duration::from_secs(60));
+
+        assert!(window.insert("a", start));
+        assert!(!window.insert("a", start + Duration::from_secs(59)));
+        assert!(window.insert("a", start + Duration::from_secs(60)));
+
+        // Once full, the oldest key is forgotten first.
+        let later = start + Duration::from_secs(61);
+        assert!(window.insert("b", later));
+        assert!(window.insert("c", later));
+        assert!(window.insert("a", later));
+        assert!(!window.insert("c", later));
+
+        window.remove("c");
+        assert!(window.insert("c", later));
+
+        let mut disabled = DedupeWindow::new(0, Duration::from_secs(60));
+        assert!(disabled.insert("a", start));
+        assert!(disabled.insert("a", start));
+    }
 }
@@ -3,6 +3,7 @@ use crate::{EventBus, Event, EventResult, EventError};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Dead Letter Queue for handling failed events
 pub struct DeadLetterQueue {
@@ -21,7 +22,13 @@ pub struct DLQConfig {
     pub auto_retry: bool,
     
     /// Delay between retry attempts (in seconds)
+    ///
+    /// Also caps the backoff used by `retry_all`.
     pub retry_delay_secs: u64,
+
+    /// Delay before the second attempt on a letter in `retry_all` (in
+    /// milliseconds), doubling for each attempt after that
+    pub backoff_base_ms: u64,
 }
 
 impl Default for DLQConfig {
@@ -30,6 +37,7 @@ impl Default for DLQConfig {
             max_retries: 3,
             auto_retry: false,
             retry_delay_secs: 60,
+            backoff_base_ms: 500,
         }
     }
 }
@@ -48,6 +56,13 @@ pub struct DLQStats {
     
     /// Average attempts per dead letter
     pub avg_attempts: f64,
+
+    /// Dead letters parked after exhausting their retries
+    pub parked: usize,
+
+    /// Dead letters recovered by the `retry_all` run that returned these
+    /// stats (always 0 from `stats`)
+    pub recovered: usize,
 }
 
 impl DeadLetterQueue {
@@ -102,7 +117,7 @@ impl DeadLetterQueue {
         let dead_letter = self.storage.get(id).await?
             .ok_or_else(|| EventError::Internal(format!("Dead letter {} not found", id)))?;
 
-        if dead_letter.attempts >= self.config.max_retries {
+        if dead_letter.parked || dead_letter.attempts >= self.config.max_retries {
             return Err(EventError::Internal(format!(
                 "Cannot retry: max retries ({}) exceeded",
                 self.config.max_retries
@@ -113,23 +128,23 @@ impl DeadLetterQueue {
             .ok_or_else(|| EventError::Internal("No event bus attached for retry".into()))?;
 
         // Try to re-emit the event
-        match bus.emit_checked(dead_letter.event.clone()).await {
+        match redeliver(bus, &dead_letter.event).await {
             Ok(_) => {
                 // Success! Remove from DLQ
                 self.storage.delete(id).await?;
                 tracing::info!("Successfully retried dead letter {}", id);
                 Ok(())
             }
-            Err(e) => {
+            Err(error) => {
                 // Failed again, update attempts
                 let updated = DeadLetter {
                     attempts: dead_letter.attempts + 1,
                     last_failed_at: Utc::now(),
-                    error: e.to_string(),
+                    error: error.clone(),
                     ..dead_letter
                 };
                 self.storage.update(&updated).await?;
-                Err(e)
+                Err(EventError::HandlerFailed(error))
             }
         }
     }
@@ -161,9 +176,70 @@ impl DeadLetterQueue {
             by_handler: storage_stats.by_handler,
             by_event_type: storage_stats.by_event_type,
             avg_attempts,
+            parked: all_letters.iter().filter(|l| l.parked).count(),
+            recovered: 0,
         })
     }
 
+    /// Retry every dead letter until it succeeds or runs out of attempts
+    ///
+    /// Each letter is re-dispatched through `bus` until a delivery succeeds,
+    /// which removes it from the queue, or its attempts reach `max_attempts`,
+    /// which parks it. Attempts on a letter are spaced by an exponential
+    /// backoff starting at `backoff_base_ms`. Parked letters are skipped.
+    pub async fn retry_all(&self, bus: &EventBus, max_attempts: u32) -> EventResult<DLQStats> {
+        let letters = self.storage.list(&DLQQuery::default()).await?;
+        let mut recovered = 0;
+
+        for mut letter in letters.into_iter().filter(|l| !l.parked) {
+            let mut retries = 0;
+            loop {
+                if letter.attempts >= max_attempts {
+                    letter.parked = true;
+                    self.storage.update(&letter).await?;
+                    tracing::error!(
+                        "Dead letter {} parked after {} attempts: {}",
+                        letter.id,
+                        letter.attempts,
+                        letter.error
+                    );
+                    break;
+                }
+
+                if retries > 0 {
+                    tokio::time::sleep(self.backoff(retries)).await;
+                }
+                retries += 1;
+
+                match redeliver(bus, &letter.event).await {
+                    Ok(()) => {
+                        self.storage.delete(&letter.id).await?;
+                        tracing::info!("Successfully retried dead letter {}", letter.id);
+                        recovered += 1;
+                        break;
+                    }
+                    Err(error) => {
+                        letter.attempts += 1;
+                        letter.last_failed_at = Utc::now();
+                        letter.error = error;
+                        self.storage.update(&letter).await?;
+                    }
+                }
+            }
+        }
+
+        let mut stats = self.stats().await?;
+        stats.recovered = recovered;
+        Ok(stats)
+    }
+
+    // Backoff before the given retry of a letter within one `retry_all` run
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u64 << (retry - 1).min(20);
+        let ms = self.config.backoff_base_ms.saturating_mul(factor);
+        Duration::from_millis(ms.min(self.config.retry_delay_secs.saturating_mul(1000)))
+    }
+
     /// Retry all dead letters for a specific handler
     pub async fn retry_handler(&self, handler_id: &str) -> EventResult<usize> {
         let query = DLQQuery {
@@ -200,10 +276,22 @@ impl DeadLetterQueue {
     }
 }
 
+/// Re-dispatches a dead letter's event, returning the first handler error.
+///
+/// The bus has already seen the event, so this bypasses its dedupe window.
+async fn redeliver(bus: &EventBus, event: &Event) -> Result<(), String> {
+    let results = bus.redeliver(event.clone()).await;
+    match results.into_iter().find(|r| !r.success) {
+        Some(failure) => Err(failure.error.unwrap_or_else(|| "Unknown error".to_string())),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{EventType, dlq::InMemoryDLQStorage};
+    use std::sync::atomic::{AtomicU32, Ordering};
 
     fn create_test_dead_letter() -> DeadLetter {
         DeadLetter {
@@ -218,6 +306,7 @@ mod tests {
             first_failed_at: Utc::now(),
             last_failed_at: Utc::now(),
             stack_trace: None,
+            parked: false,
         }
     }
 
@@ -267,4 +356,76 @@ mod tests {
         let retrieved = storage.get(&id).await.unwrap();
         assert!(retrieved.is_none());
     }
+
+    struct FlakyHandler {
+        failures: u32,
+        calls: Arc<AtomicU32>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::EventHandler for FlakyHandler {
+        fn id(&self) -> &str {
+            "flaky"
+        }
+
+        async fn handle(&self, _event: &Event) -> Result<(), EventError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.failures {
+                return Err(EventError::HandlerFailed(format!("failure {}", call + 1)));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_all_recovers_and_parks() {
+        let storage = Arc::new(InMemoryDLQStorage::new());
+        let config = DLQConfig {
+            backoff_base_ms: 1,
+            ..Default::default()
+        };
+        let dlq = DeadLetterQueue::with_config(storage.clone(), config);
+
+        // Dead letters carry keys the bus has already seen.
+        let bus = EventBus::new();
+        let event = |name: &str| {
+            Event::new(EventType::new("test", name), "payload").with_idempotency_key(name)
+        };
+        bus.emit(event("flaky")).await;
+        bus.emit(event("broken")).await;
+
+        let flaky_calls = Arc::new(AtomicU32::new(0));
+        let broken_calls = Arc::new(AtomicU32::new(0));
+        let handler = |failures, calls: &Arc<AtomicU32>| FlakyHandler {
+            failures,
+            calls: calls.clone(),
+        };
+        bus.on("test.flaky", handler(1, &flaky_calls)).await;
+        bus.on("test.broken", handler(u32::MAX, &broken_calls))
+            .await;
+
+        let mut flaky = create_test_dead_letter();
+        flaky.event = event("flaky");
+        let mut broken = create_test_dead_letter();
+        broken.event = event("broken");
+        dlq.send(flaky.clone()).await.unwrap();
+        dlq.send(broken.clone()).await.unwrap();
+
+        let stats = dlq.retry_all(&bus, 3).await.unwrap();
+        assert_eq!(stats.recovered, 1);
+        assert_eq!(stats.parked, 1);
+        assert_eq!(stats.total, 1);
+        assert!(storage.get(&flaky.id).await.unwrap().is_none());
+
+        let parked = storage.get(&broken.id).await.unwrap().unwrap();
+        assert!(parked.parked);
+        assert_eq!(parked.attempts, 3);
+        assert_eq!(parked.error, "Handler failed: failure 2");
+
+        // Parked letters stay put on later runs.
+        let stats = dlq.retry_all(&bus, 10).await.unwrap();
+        assert_eq!(stats.recovered, 0);
+        assert_eq!(broken_calls.load(Ordering::SeqCst), 2);
+        assert!(dlq.retry(&broken.id).await.is_err());
+    }
 }
@@ -64,10 +64,10 @@ pub struct DeadLetter {
     /// Handler that failed to process the event
     pub handler_id: String,
     
-    /// Error message
+    /// Error message from the most recent attempt
     pub error: String,
     
-    /// Number of retry attempts
+    /// Number of delivery attempts so far
     pub attempts: u32,
     
     /// When the event first failed
@@ -78,6 +78,10 @@ pub struct DeadLetter {
     
     /// Optional stack trace
     pub stack_trace: Option<String>,
+
+    /// Whether retries were exhausted; parked letters are never retried
+    #[serde(default)]
+    pub parked: bool,
 }
 
 /// In-memory DLQ storage implementation
@@ -203,6 +207,7 @@ mod tests {
             first_failed_at: Utc::now(),
             last_failed_at: Utc::now(),
             stack_trace: None,
+            parked: false,
         }
     }
 
@@ -24,6 +24,15 @@ pub enum EventError {
     #[error("Unknown event type: {0}")]
     UnknownEventType(String),
 
+    /// The event's payload does not match its registered schema.
+    #[error("Event {event_type} does not match its schema: {}", errors.join("; "))]
+    SchemaViolation {
+        /// The event's type, with version.
+        event_type: String,
+        /// Every problem found, such as `user_id: Required field 'user_id' is missing`.
+        errors: Vec<String>,
+    },
+
     /// Middleware rejected the event.
     #[error("Middleware rejected: {0}")]
     MiddlewareRejected(String),
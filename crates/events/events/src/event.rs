@@ -67,6 +67,12 @@ impl Event {
         self
     }
 
+    /// Sets the idempotency key used to drop duplicate emits.
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.metadata.idempotency_key = Some(key.into());
+        self
+    }
+
     /// Returns the full event type string (e.g., "user.created.v1").
     pub fn type_string(&self) -> String {
         self.event_type.to_string()
@@ -159,19 +165,30 @@ impl EventType {
     }
 
     /// Checks if this event type matches a pattern (supports wildcards).
+    ///
+    /// Patterns are matched segment by segment on the dot-separated type.
+    /// A `*` segment matches any single segment, and a trailing `*` matches
+    /// everything below it, so `user.*` matches both `user.created` and
+    /// `user.email.verified`, and `*` matches every event. A pattern without
+    /// wildcards may also name the version, as in `user.created.v1`.
     pub fn matches(&self, pattern: &str) -> bool {
         let simple = self.simple_string();
-
-        if pattern == "*" {
-            return true;
+        if !pattern.contains('*') {
+            return simple == pattern || self.to_string() == pattern;
         }
 
-        if pattern.ends_with(".*") {
-            let prefix = &pattern[..pattern.len() - 2];
-            return simple.starts_with(prefix);
+        let mut segments = simple.split('.');
+        let mut parts = pattern.split('.').peekable();
+        while let Some(part) = parts.next() {
+            if part == "*" && parts.peek().is_none() {
+                return segments.next().is_some();
+            }
+            match segments.next() {
+                Some(segment) if part == "*" || part == segment => {}
+                _ => return false,
+            }
         }
-
-        simple == pattern || self.to_string() == pattern
+        segments.next().is_none()
     }
 }
 
@@ -190,6 +207,11 @@ pub struct EventMetadata {
     pub schema_version: String,
     /// Custom tags for filtering and routing.
     pub tags: HashMap<String, String>,
+    /// Key identifying the logical operation behind the event. The bus
+    /// drops an event whose key it has recently seen, so retries don't
+    /// process the operation twice.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
 impl EventMetadata {
@@ -199,6 +221,7 @@ impl EventMetadata {
             source: source.into(),
             schema_version: "1.0".to_string(),
             tags: HashMap::new(),
+            idempotency_key: None,
         }
     }
 }
@@ -234,6 +257,17 @@ mod tests {
         assert!(et.matches("*"));
         assert!(!et.matches("session.created"));
         assert!(!et.matches("session.*"));
+        assert!(et.matches("*.created"));
+        assert!(et.matches("user.created.v1"));
+
+        // A namespace pattern stops at the segment boundary.
+        assert!(!EventType::new("username", "changed").matches("user.*"));
+
+        let nested = EventType::new("user", "email.verified");
+        assert!(nested.matches("user.*"));
+        assert!(nested.matches("user.email.*"));
+        assert!(!nested.matches("user.*.changed"));
+        assert!(!nested.matches("*.verified"));
     }
 
     #[test]
@@ -5,6 +5,7 @@
 //! - Pub/sub event bus with async handlers
 //! - Middleware chain for event processing
 //! - Event registry for discovery and validation
+//! - Optional checks of emitted payloads against registered schemas
 //!
 //! ## Example
 //!
@@ -40,14 +41,14 @@ pub mod dlq;
 pub mod schema;
 
 pub use event::{Event, EventType, EventMetadata};
-pub use bus::EventBus;
+pub use bus::{EventBus, SchemaEnforcement};
 pub use handler::{EventHandler, BoxedHandler, HandlerResult};
 pub use emitter::EventEmitter;
 pub use registry::{EventRegistry, EventDefinition};
 pub use middleware::{EventMiddleware, MiddlewareChain, LoggingMiddleware, MetricsMiddleware, ValidationMiddleware};
 pub use error::{EventError, EventResult};
-pub use store::{EventStore, StoredEvent, EventQuery, EventOrdering, EventStream, EventStreamSubscription, MemoryEventStore};
-pub use replay::{ReplayEngine, ReplayConfig, ReplaySpeed, ReplayResult};
+pub use store::{EventStore, StoredEvent, EventQuery, EventOrdering, EventStream, EventStreamSubscription, MemoryEventStore, ReplicatingEventStore, ReplicationConsistency, ReplicaStatus};
+pub use replay::{ReplayEngine, ReplayConfig, ReplaySpeed, ReplayResult, ReplayStats, ReplayToken};
 pub use dlq::{DeadLetterQueue, DeadLetter, DLQConfig, DLQStats, DLQStorage, InMemoryDLQStorage};
 pub use schema::{EventSchemaRegistry, EventSchema, SchemaValidator, JsonSchemaValidator, ValidationResult};
 
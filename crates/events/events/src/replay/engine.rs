@@ -1,6 +1,8 @@
+use crate::dlq::{DeadLetter, DeadLetterQueue};
 use crate::{EventBus, EventStore, EventQuery, EventOrdering, EventResult, EventError};
 use chrono::{DateTime, Utc};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -8,6 +10,12 @@ use tokio::time::sleep;
 pub struct ReplayEngine {
     store: Arc<dyn EventStore>,
     bus: Arc<EventBus>,
+    /// Pacing used by `replay_to_bus`
+    speed: ReplaySpeed,
+    /// Where `replay_to_bus` sends events that fail a handler
+    dlq: Option<Arc<DeadLetterQueue>>,
+    /// Cancels `replay_to_bus` and reports its progress
+    token: ReplayToken,
 }
 
 /// Configuration for event replay
@@ -56,10 +64,13 @@ pub struct ReplayResult {
 }
 
 /// Statistics from replay operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ReplayStats {
     /// Total number of events replayed
     pub total_events: usize,
+
+    /// Number of events emitted so far
+    pub processed: usize,
     
     /// Number of successfully replayed events
     pub successful: usize,
@@ -77,6 +88,57 @@ pub struct ReplayStats {
     pub time_range: (DateTime<Utc>, DateTime<Utc>),
 }
 
+/// Cancels a replay and reports its progress
+///
+/// Clones share their state: keep one and give another to the engine with
+/// [`ReplayEngine::with_token`].
+#[derive(Debug, Clone, Default)]
+pub struct ReplayToken {
+    cancelled: Arc<AtomicBool>,
+    progress: Arc<Mutex<ReplayStats>>,
+}
+
+impl ReplayToken {
+    /// Create a new token
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the replay before its next event
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the replay was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Statistics of the replay so far
+    pub fn progress(&self) -> ReplayStats {
+        self.progress.lock().unwrap().clone()
+    }
+
+    fn set_progress(&self, stats: &ReplayStats) {
+        *self.progress.lock().unwrap() = stats.clone();
+    }
+}
+
+impl ReplaySpeed {
+    /// How long to wait between events stored at `previous` and `next`
+    fn delay(&self, previous: DateTime<Utc>, next: DateTime<Utc>) -> Option<Duration> {
+        let gap = next.signed_duration_since(previous).to_std().ok()?;
+        match *self {
+            ReplaySpeed::Fast => None,
+            ReplaySpeed::RealTime => Some(gap),
+            ReplaySpeed::Custom(multiplier) if multiplier > 0.0 => {
+                Some(gap.mul_f64(1.0 / multiplier))
+            }
+            ReplaySpeed::Custom(_) => None,
+        }
+    }
+}
+
 /// Error that occurred during replay
 #[derive(Debug, Clone)]
 pub struct ReplayError {
@@ -89,7 +151,138 @@ pub struct ReplayError {
 impl ReplayEngine {
     /// Create a new replay engine
     pub fn new(store: Arc<dyn EventStore>, bus: Arc<EventBus>) -> Self {
-        Self { store, bus }
+        Self {
+            store,
+            bus,
+            speed: ReplaySpeed::Fast,
+            dlq: None,
+            token: ReplayToken::new(),
+        }
+    }
+
+    /// Set the pacing used by `replay_to_bus`
+    pub fn with_speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Attach a dead letter queue for events that fail during `replay_to_bus`
+    pub fn with_dlq(mut self, dlq: Arc<DeadLetterQueue>) -> Self {
+        self.dlq = Some(dlq);
+        self
+    }
+
+    /// Use `token` to cancel `replay_to_bus` and follow its progress
+    pub fn with_token(mut self, token: ReplayToken) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Replay stored events matching `query` through the handlers on `bus`
+    ///
+    /// Events are emitted oldest first, one at a time, paced by the
+    /// engine's speed. Delivery is at least once: an event that fails a
+    /// handler doesn't stop the replay, but is sent to the dead letter
+    /// queue for that handler, and retrying it there re-emits it to every
+    /// handler. Without a queue, failures are only reported in the result.
+    ///
+    /// Cancelling the engine's token stops the replay before the next
+    /// event; the events left are counted as skipped.
+    pub async fn replay_to_bus(
+        &self,
+        bus: &EventBus,
+        mut query: EventQuery,
+    ) -> EventResult<ReplayResult> {
+        let start_time = std::time::Instant::now();
+        query.ordering = EventOrdering::Ascending;
+        let mut event_stream = self.store.query(query).await?;
+
+        let mut stats = ReplayStats {
+            total_events: event_stream.len(),
+            ..Default::default()
+        };
+        let mut errors = Vec::new();
+        let mut last_timestamp: Option<DateTime<Utc>> = None;
+        self.token.set_progress(&stats);
+
+        while let Some(stored_event) = event_stream.next() {
+            if self.token.is_cancelled() {
+                stats.skipped = stats.total_events - stats.processed;
+                tracing::info!(
+                    "Replay cancelled after {} of {} events",
+                    stats.processed,
+                    stats.total_events
+                );
+                break;
+            }
+
+            let event = &stored_event.event;
+            let delay = last_timestamp.and_then(|last| self.speed.delay(last, event.timestamp));
+            if let Some(delay) = delay {
+                sleep(delay).await;
+            }
+
+            if stats.processed == 0 {
+                stats.time_range.0 = event.timestamp;
+            }
+            stats.time_range.1 = event.timestamp;
+
+            let failures: Vec<_> = bus
+                .redeliver(event.clone())
+                .await
+                .into_iter()
+                .filter(|result| !result.success)
+                .collect();
+            stats.processed += 1;
+            if failures.is_empty() {
+                stats.successful += 1;
+            } else {
+                stats.failed += 1;
+            }
+
+            for failure in failures {
+                let error = failure.error.unwrap_or_else(|| "Unknown error".to_string());
+                errors.push(ReplayError {
+                    event_id: stored_event.id,
+                    event_type: event.event_type.to_string(),
+                    error: error.clone(),
+                    timestamp: event.timestamp,
+                });
+                if let Some(dlq) = &self.dlq {
+                    // Losing the event here would break at-least-once
+                    // delivery, so a queue failure ends the replay.
+                    let now = Utc::now();
+                    dlq.send(DeadLetter {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        event: event.clone(),
+                        handler_id: failure.handler_id,
+                        error,
+                        attempts: 1,
+                        first_failed_at: now,
+                        last_failed_at: now,
+                        stack_trace: None,
+                        parked: false,
+                    })
+                    .await?;
+                }
+            }
+
+            last_timestamp = Some(event.timestamp);
+            self.token.set_progress(&stats);
+        }
+
+        stats.duration = start_time.elapsed();
+        self.token.set_progress(&stats);
+
+        tracing::info!(
+            "Replay to bus completed: {} successful, {} failed, {} skipped in {:?}",
+            stats.successful,
+            stats.failed,
+            stats.skipped,
+            stats.duration
+        );
+
+        Ok(ReplayResult { stats, errors })
     }
 
     /// Replay events according to the configuration
@@ -112,6 +305,7 @@ impl ReplayEngine {
         
         let mut stats = ReplayStats {
             total_events: event_stream.len(),
+            processed: 0,
             successful: 0,
             failed: 0,
             skipped: 0,
@@ -133,25 +327,9 @@ impl ReplayEngine {
             let event = &stored_event.event;
             
             // Handle timing between events
-            if let Some(last_ts) = last_timestamp {
-                match config.speed {
-                    ReplaySpeed::Fast => {
-                        // No delay
-                    }
-                    ReplaySpeed::RealTime => {
-                        let delay = event.timestamp.signed_duration_since(last_ts);
-                        if let Ok(duration) = delay.to_std() {
-                            sleep(duration).await;
-                        }
-                    }
-                    ReplaySpeed::Custom(multiplier) => {
-                        let delay = event.timestamp.signed_duration_since(last_ts);
-                        if let Ok(duration) = delay.to_std() {
-                            let adjusted = duration.mul_f64(1.0 / multiplier);
-                            sleep(adjusted).await;
-                        }
-                    }
-                }
+            let delay = last_timestamp.and_then(|last| config.speed.delay(last, event.timestamp));
+            if let Some(delay) = delay {
+                sleep(delay).await;
             }
 
             // Update time range
@@ -161,6 +339,7 @@ impl ReplayEngine {
             stats.time_range.1 = event.timestamp;
 
             // Emit event to bus
+            stats.processed += 1;
             match self.bus.emit_checked(event.clone()).await {
                 Ok(_) => {
                     stats.successful += 1;
@@ -225,6 +404,7 @@ impl ReplayEngine {
         
         let mut stats = ReplayStats {
             total_events: stored_events.len(),
+            processed: 0,
             successful: 0,
             failed: 0,
             skipped: 0,
@@ -251,6 +431,7 @@ impl ReplayEngine {
             }
             stats.time_range.1 = event.timestamp;
 
+            stats.processed += 1;
             match self.bus.emit_checked(event.clone()).await {
                 Ok(_) => {
                     stats.successful += 1;
@@ -370,4 +551,124 @@ mod tests {
         let result = engine.replay_stream("test-stream", 2).await.unwrap();
         assert_eq!(result.stats.successful, 2); // versions 2 and 3
     }
+
+    /// Fails events of one type and cancels `token` after `cancel_after`
+    /// events, if set.
+    struct ReadModel {
+        seen: Arc<Mutex<Vec<String>>>,
+        token: ReplayToken,
+        cancel_after: Option<usize>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::EventHandler for ReadModel {
+        fn id(&self) -> &str {
+            "read-model"
+        }
+
+        async fn handle(&self, event: &Event) -> Result<(), EventError> {
+            let mut seen = self.seen.lock().unwrap();
+            seen.push(event.simple_type_string());
+            if Some(seen.len()) == self.cancel_after {
+                self.token.cancel();
+            }
+            if event.event_type.name == "broken" {
+                return Err(EventError::HandlerFailed("cannot project".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    async fn store_with(actions: &[&str]) -> Arc<MemoryEventStore> {
+        let store = Arc::new(MemoryEventStore::new());
+        for action in actions {
+            store
+                .append(&Event::new(EventType::new("user", *action), serde_json::json!({})))
+                .await
+                .unwrap();
+        }
+        store
+    }
+
+    #[tokio::test]
+    async fn test_replay_to_bus_sends_failures_to_dlq() {
+        use crate::dlq::{DLQQuery, InMemoryDLQStorage};
+
+        let store = store_with(&["created", "broken", "updated"]).await;
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        bus.on_all(ReadModel {
+            seen: seen.clone(),
+            token: ReplayToken::new(),
+            cancel_after: None,
+        })
+        .await;
+        let dlq = Arc::new(DeadLetterQueue::new(Arc::new(InMemoryDLQStorage::new())));
+        let token = ReplayToken::new();
+        let engine = ReplayEngine::new(store, Arc::new(EventBus::new()))
+            .with_dlq(dlq.clone())
+            .with_token(token.clone());
+
+        let result = engine.replay_to_bus(&bus, EventQuery::default()).await.unwrap();
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec!["user.created", "user.broken", "user.updated"]
+        );
+        assert_eq!(result.stats.processed, 3);
+        assert_eq!(result.stats.successful, 2);
+        assert_eq!(result.stats.failed, 1);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(token.progress().processed, 3);
+
+        let dead = dlq.list(DLQQuery::default()).await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].handler_id, "read-model");
+        assert_eq!(dead[0].event.simple_type_string(), "user.broken");
+    }
+
+    #[tokio::test]
+    async fn test_replay_to_bus_ignores_idempotency_keys() {
+        let store = Arc::new(MemoryEventStore::new());
+        let event = Event::new(EventType::new("user", "created"), serde_json::json!({}))
+            .with_idempotency_key("signup-1");
+        store.append(&event).await.unwrap();
+
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        bus.on_all(ReadModel {
+            seen: seen.clone(),
+            token: ReplayToken::new(),
+            cancel_after: None,
+        })
+        .await;
+        bus.emit_sync(event).await;
+
+        // The key was seen when the event first went out; a replay still
+        // delivers it.
+        let engine = ReplayEngine::new(store, Arc::new(EventBus::new()));
+        let result = engine.replay_to_bus(&bus, EventQuery::default()).await.unwrap();
+        assert_eq!(result.stats.successful, 1);
+        assert_eq!(*seen.lock().unwrap(), vec!["user.created", "user.created"]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_to_bus_cancellation() {
+        let store = store_with(&["a", "b", "c", "d"]).await;
+        let bus = EventBus::new();
+        let token = ReplayToken::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        bus.on_all(ReadModel {
+            seen: seen.clone(),
+            token: token.clone(),
+            cancel_after: Some(2),
+        })
+        .await;
+        let engine = ReplayEngine::new(store, Arc::new(EventBus::new())).with_token(token.clone());
+
+        let result = engine.replay_to_bus(&bus, EventQuery::default()).await.unwrap();
+        assert!(token.is_cancelled());
+        assert_eq!(seen.lock().unwrap().len(), 2);
+        assert_eq!(result.stats.processed, 2);
+        assert_eq!(result.stats.skipped, 2);
+    }
 }
@@ -5,7 +5,8 @@
 //! - Replay at different speeds (fast, realtime, custom)
 //! - Filter events during replay
 //! - Handle failed events during replay
+//! - Replay through live handlers, sending failures to the dead letter queue
 
 mod engine;
 
-pub use engine::{ReplayEngine, ReplayConfig, ReplaySpeed, ReplayResult, ReplayStats};
+pub use engine::{ReplayEngine, ReplayConfig, ReplaySpeed, ReplayResult, ReplayStats, ReplayToken};
@@ -1,3 +1,4 @@
+use super::validator::{JsonSchemaValidator, SchemaValidator, ValidationError, ValidationResult};
 use crate::{Event, EventType, EventResult, EventError};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -237,6 +238,24 @@ impl EventSchemaRegistry {
         self.validate_payload(&event.payload, &schema.json_schema)
     }
 
+    /// Check an event's payload against its registered schema with
+    /// [`JsonSchemaValidator`], collecting every error
+    ///
+    /// Events without a registered schema pass only if `allow_unregistered`
+    /// is set.
+    pub async fn check_event(&self, event: &Event) -> ValidationResult {
+        let key = EventTypeVersion::from_event_type(&event.event_type);
+        let schema = self.schemas.read().await.get(&key).map(|s| s.json_schema.clone());
+        match schema {
+            Some(schema) => JsonSchemaValidator::new().validate(&event.payload, &schema).await,
+            None if self.config.allow_unregistered => ValidationResult::valid(),
+            None => ValidationResult::invalid(vec![ValidationError::new(
+                "$",
+                format!("No schema registered for event type {}", event.event_type),
+            )]),
+        }
+    }
+
     /// Validate a payload against a JSON schema
     fn validate_payload(&self, payload: &Value, schema: &Value) -> EventResult<()> {
         // Basic validation - check required fields
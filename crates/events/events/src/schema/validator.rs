@@ -83,6 +83,16 @@ impl ValidationError {
     }
 }
 
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)?;
+        if let (Some(expected), Some(actual)) = (&self.expected, &self.actual) {
+            write!(f, " (expected {}, got {})", expected, actual)?;
+        }
+        Ok(())
+    }
+}
+
 /// JSON Schema validator implementation
 pub struct JsonSchemaValidator;
 
@@ -5,9 +5,11 @@
 //! - Stream-based event storage
 //! - Event querying and filtering
 //! - Snapshot support for performance
+//! - Replication to secondary stores
 
 mod trait_def;
 mod memory;
+mod replicating;
 #[cfg(feature = "postgres")]
 mod postgres;
 #[cfg(feature = "redis")]
@@ -18,6 +20,9 @@ pub use trait_def::{
     EventStreamSubscription, EventId, StreamVersion, StoredEvent, EventSnapshot
 };
 pub use memory::MemoryEventStore;
+pub use replicating::{
+    ReplicaStatus, ReplicatingEventStore, ReplicatingEventStoreBuilder, ReplicationConsistency,
+};
 
 #[cfg(feature = "postgres")]
 pub use postgres::PostgresEventStore;
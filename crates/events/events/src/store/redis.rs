@@ -1,84 +1,569 @@
-// Redis EventStore implementation
-// 
-// This will be implemented when the redis feature is enabled
-// Redis is useful for fast event access and caching
+//! Redis implementation of [`EventStore`], built on Redis Streams.
 
-#[cfg(feature = "redis")]
 use super::trait_def::*;
-#[cfg(feature = "redis")]
-use crate::{Event, EventResult};
-#[cfg(feature = "redis")]
+use crate::{Event, EventError, EventResult};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use redis::streams::{StreamId, StreamRangeReply, StreamReadOptions, StreamReadReply};
 
-#[cfg(feature = "redis")]
+/// Key prefix used when none is configured.
+const DEFAULT_PREFIX: &str = "better_auth:";
+
+/// How long a subscription blocks on `XREAD` before checking whether it has
+/// been dropped.
+const SUBSCRIBE_BLOCK_MS: usize = 1000;
+
+/// Appends events to a stream, assigning each the next stream version.
+///
+/// Runs as a script so that versions, entries and indexes are written
+/// together.
+///
+/// KEYS: stream, versions hash, event ID index, stream set.
+/// ARGV: stream ID, stored-at timestamp, then an (event ID, event JSON,
+/// correlation key or "") triple per event.
+const APPEND_SCRIPT: &str = r#"
+local versions = {}
+for i = 3, #ARGV, 3 do
+    local version = redis.call('HINCRBY', KEYS[2], ARGV[1], 1)
+    redis.call('XADD', KEYS[1], '0-' .. version,
+        'id', ARGV[i], 'event', ARGV[i + 1], 'stored_at', ARGV[2])
+    redis.call('HSET', KEYS[3], ARGV[i], version .. ':' .. ARGV[1])
+    if ARGV[i + 2] ~= '' then
+        redis.call('SADD', ARGV[i + 2], ARGV[i])
+    end
+    table.insert(versions, version)
+end
+redis.call('SADD', KEYS[4], ARGV[1])
+return versions
+"#;
+
+/// Event store backed by Redis Streams.
+///
+/// Each stream is a Redis stream whose entry IDs are `0-{version}`, so the
+/// stream version and the entry ID always agree. Keys used (relative to the
+/// configured prefix):
+///
+/// - `event_stream:{stream_id}` — the stream's events, one entry each
+/// - `event_versions` — hash of stream ID to the last version assigned
+/// - `event_ids` — hash of event ID to `{version}:{stream_id}`
+/// - `event_streams` — set of all stream IDs
+/// - `event_correlation:{correlation_id}` — set of event IDs
+/// - `event_snapshot:{stream_id}` — the latest snapshot as JSON
+///
+/// Appends need Redis 5 or later; [`truncate_stream`] needs Redis 6.2.
+///
+/// [`truncate_stream`]: EventStore::truncate_stream
+#[derive(Clone)]
 pub struct RedisEventStore {
-    // client: redis::Client,
+    client: redis::Client,
+    conn: ConnectionManager,
+    prefix: String,
 }
 
-#[cfg(feature = "redis")]
 impl RedisEventStore {
-    pub fn new(/* client: redis::Client */) -> Self {
-        todo!("Implement RedisEventStore when redis feature is added")
+    /// Connects to Redis using the given client.
+    pub async fn new(client: redis::Client) -> EventResult<Self> {
+        let conn = ConnectionManager::new(client.clone())
+            .await
+            .map_err(redis_error)?;
+        Ok(Self {
+            client,
+            conn,
+            prefix: DEFAULT_PREFIX.to_string(),
+        })
+    }
+
+    /// Connects to Redis at the given URL (e.g. `redis://127.0.0.1/`).
+    pub async fn from_url(url: &str) -> EventResult<Self> {
+        let client = redis::Client::open(url).map_err(redis_error)?;
+        Self::new(client).await
+    }
+
+    /// Sets the prefix prepended to every key (default: `better_auth:`).
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn stream_key(&self, stream_id: &str) -> String {
+        format!("{}event_stream:{}", self.prefix, stream_id)
+    }
+
+    fn versions_key(&self) -> String {
+        format!("{}event_versions", self.prefix)
+    }
+
+    fn ids_key(&self) -> String {
+        format!("{}event_ids", self.prefix)
+    }
+
+    fn streams_key(&self) -> String {
+        format!("{}event_streams", self.prefix)
+    }
+
+    fn correlation_key(&self, correlation_id: &str) -> String {
+        format!("{}event_correlation:{}", self.prefix, correlation_id)
+    }
+
+    fn snapshot_key(&self, stream_id: &str) -> String {
+        format!("{}event_snapshot:{}", self.prefix, stream_id)
+    }
+
+    /// Reads the entries of a stream between two entry IDs, inclusive.
+    async fn range(
+        &self,
+        stream_id: &str,
+        start: &str,
+        end: &str,
+    ) -> EventResult<Vec<StoredEvent>> {
+        let mut conn = self.conn.clone();
+        let reply: StreamRangeReply = conn
+            .xrange(self.stream_key(stream_id), start, end)
+            .await
+            .map_err(redis_error)?;
+        reply
+            .ids
+            .iter()
+            .map(|entry| decode(stream_id, entry))
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for RedisEventStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisEventStore")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
     }
 }
 
-#[cfg(feature = "redis")]
 #[async_trait]
 impl EventStore for RedisEventStore {
-    async fn append(&self, _event: &Event) -> EventResult<EventId> {
-        todo!()
+    async fn append(&self, event: &Event) -> EventResult<EventId> {
+        let ids = self.append_batch(std::slice::from_ref(event)).await?;
+        Ok(ids[0])
     }
 
-    async fn append_batch(&self, _events: &[Event]) -> EventResult<Vec<EventId>> {
-        todo!()
+    async fn append_batch(&self, events: &[Event]) -> EventResult<Vec<EventId>> {
+        if events.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Verify all events are from the same stream
+        let stream_id = &events[0].metadata.source;
+        if !events.iter().all(|e| &e.metadata.source == stream_id) {
+            return Err(EventError::InvalidInput(
+                "All events in batch must belong to the same stream".into(),
+            ));
+        }
+
+        let mut cmd = redis::cmd("EVAL");
+        cmd.arg(APPEND_SCRIPT)
+            .arg(4)
+            .arg(self.stream_key(stream_id))
+            .arg(self.versions_key())
+            .arg(self.ids_key())
+            .arg(self.streams_key())
+            .arg(stream_id)
+            .arg(Utc::now().to_rfc3339());
+
+        let mut ids = Vec::with_capacity(events.len());
+        for event in events {
+            let id = EventId::new_v4();
+            let correlation_key = event
+                .correlation_id
+                .as_deref()
+                .map(|c| self.correlation_key(c))
+                .unwrap_or_default();
+            cmd.arg(id.to_string())
+                .arg(serde_json::to_string(event)?)
+                .arg(correlation_key);
+            ids.push(id);
+        }
+
+        let mut conn = self.conn.clone();
+        cmd.query_async::<Vec<StreamVersion>>(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(ids)
     }
 
-    async fn get(&self, _id: &EventId) -> EventResult<Option<StoredEvent>> {
-        todo!()
+    async fn get(&self, id: &EventId) -> EventResult<Option<StoredEvent>> {
+        let mut conn = self.conn.clone();
+        let location: Option<String> = conn
+            .hget(self.ids_key(), id.to_string())
+            .await
+            .map_err(redis_error)?;
+        let Some((version, stream_id)) = location.as_deref().and_then(|l| l.split_once(':')) else {
+            return Ok(None);
+        };
+
+        let entry_id = format!("0-{}", version);
+        let events = self.range(stream_id, &entry_id, &entry_id).await?;
+        Ok(events.into_iter().next())
     }
 
     async fn get_stream(
         &self,
-        _stream_id: &str,
-        _from_version: Option<StreamVersion>,
+        stream_id: &str,
+        from_version: Option<StreamVersion>,
     ) -> EventResult<Vec<StoredEvent>> {
-        todo!()
+        let start = format!("0-{}", from_version.unwrap_or(1));
+        self.range(stream_id, &start, "+").await
     }
 
-    async fn get_by_correlation(&self, _correlation_id: &str) -> EventResult<Vec<StoredEvent>> {
-        todo!()
+    async fn get_by_correlation(&self, correlation_id: &str) -> EventResult<Vec<StoredEvent>> {
+        let mut conn = self.conn.clone();
+        let ids: Vec<String> = conn
+            .smembers(self.correlation_key(correlation_id))
+            .await
+            .map_err(redis_error)?;
+
+        let mut events = Vec::with_capacity(ids.len());
+        for id in ids {
+            let Ok(id) = id.parse::<EventId>() else {
+                continue;
+            };
+            // Truncated events stay in the set until it's rebuilt.
+            if let Some(event) = self.get(&id).await? {
+                events.push(event);
+            }
+        }
+        events.sort_by_key(|e| (e.stored_at, e.version));
+        Ok(events)
     }
 
-    async fn query(&self, _query: EventQuery) -> EventResult<Vec<StoredEvent>> {
-        todo!()
+    async fn query(&self, query: EventQuery) -> EventResult<EventStream> {
+        let stream_ids = if query.stream_ids.is_empty() {
+            let mut conn = self.conn.clone();
+            conn.smembers(self.streams_key())
+                .await
+                .map_err(redis_error)?
+        } else {
+            query.stream_ids.clone()
+        };
+
+        let mut events = Vec::new();
+        for stream_id in &stream_ids {
+            let stream = self.range(stream_id, "-", "+").await?;
+            events.extend(stream.into_iter().filter(|e| query_matches(&query, e)));
+        }
+
+        // Sort by ordering, keeping each stream's versions in order
+        match query.ordering {
+            EventOrdering::Ascending => {
+                events.sort_by_key(|e| e.event.timestamp);
+            }
+            EventOrdering::Descending => {
+                events.sort_by_key(|e| std::cmp::Reverse(e.event.timestamp));
+            }
+        }
+
+        // Apply pagination
+        let events = events
+            .into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect();
+
+        Ok(EventStream::new(events))
     }
 
-    async fn subscribe_to_stream(&self, _stream_id: &str) -> EventResult<EventStreamSubscription> {
-        todo!()
+    async fn subscribe_to_stream(&self, stream_id: &str) -> EventResult<EventStreamSubscription> {
+        let (subscription, tx) = EventStreamSubscription::new(stream_id.to_string());
+
+        // XREAD BLOCK holds its connection, so it gets one of its own.
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(redis_error)?;
+        let key = self.stream_key(stream_id);
+
+        // Start after the current last entry, so nothing appended from here
+        // on is missed.
+        let last: StreamRangeReply = self
+            .conn
+            .clone()
+            .xrevrange_count(&key, "+", "-", 1)
+            .await
+            .map_err(redis_error)?;
+        let mut last_id = last
+            .ids
+            .first()
+            .map_or_else(|| "0-0".to_string(), |e| e.id.clone());
+        let stream_id = stream_id.to_string();
+
+        tokio::spawn(async move {
+            let options = StreamReadOptions::default().block(SUBSCRIBE_BLOCK_MS);
+            while !tx.is_closed() {
+                let reply: Option<StreamReadReply> = match conn
+                    .xread_options(&[&key], &[&last_id], &options)
+                    .await
+                {
+                    Ok(reply) => reply,
+                    Err(e) => {
+                        tracing::error!("Event stream '{}' subscription failed: {}", stream_id, e);
+                        return;
+                    }
+                };
+
+                let entries = reply.into_iter().flat_map(|r| r.keys).flat_map(|k| k.ids);
+                for entry in entries {
+                    last_id = entry.id.clone();
+                    match decode(&stream_id, &entry) {
+                        Ok(event) => {
+                            if tx.send(event).is_err() {
+                                return;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::error!(
+                                "Skipping unreadable event {} in '{}': {}",
+                                entry.id,
+                                stream_id,
+                                e
+                            );
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(subscription)
     }
 
-    async fn get_stream_version(&self, _stream_id: &str) -> EventResult<Option<StreamVersion>> {
-        todo!()
+    async fn get_stream_version(&self, stream_id: &str) -> EventResult<Option<StreamVersion>> {
+        let mut conn = self.conn.clone();
+        conn.hget(self.versions_key(), stream_id)
+            .await
+            .map_err(redis_error)
     }
 
     async fn create_snapshot(
         &self,
-        _stream_id: &str,
-        _version: StreamVersion,
-        _state: serde_json::Value,
+        stream_id: &str,
+        version: StreamVersion,
+        state: serde_json::Value,
     ) -> EventResult<()> {
-        todo!()
+        let snapshot = EventSnapshot {
+            stream_id: stream_id.to_string(),
+            version,
+            state,
+            created_at: Utc::now(),
+        };
+
+        let mut conn = self.conn.clone();
+        conn.set(
+            self.snapshot_key(stream_id),
+            serde_json::to_string(&snapshot)?,
+        )
+        .await
+        .map_err(redis_error)
     }
 
-    async fn get_latest_snapshot(&self, _stream_id: &str) -> EventResult<Option<EventSnapshot>> {
-        todo!()
+    async fn get_latest_snapshot(&self, stream_id: &str) -> EventResult<Option<EventSnapshot>> {
+        let mut conn = self.conn.clone();
+        let value: Option<String> = conn
+            .get(self.snapshot_key(stream_id))
+            .await
+            .map_err(redis_error)?;
+        Ok(value.map(|json| serde_json::from_str(&json)).transpose()?)
     }
 
     async fn truncate_stream(
         &self,
-        _stream_id: &str,
-        _before_version: StreamVersion,
+        stream_id: &str,
+        before_version: StreamVersion,
     ) -> EventResult<()> {
-        todo!()
+        if before_version <= 1 {
+            return Ok(());
+        }
+
+        let last = format!("0-{}", before_version - 1);
+        let removed = self.range(stream_id, "-", &last).await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for event in &removed {
+            pipe.hdel(self.ids_key(), event.id.to_string()).ignore();
+        }
+        pipe.cmd("XTRIM")
+            .arg(self.stream_key(stream_id))
+            .arg("MINID")
+            .arg(format!("0-{}", before_version))
+            .ignore();
+
+        let mut conn = self.conn.clone();
+        pipe.query_async::<()>(&mut conn).await.map_err(redis_error)
+    }
+}
+
+/// Checks a stored event against a query's type and time filters.
+fn query_matches(query: &EventQuery, event: &StoredEvent) -> bool {
+    if !query.event_types.is_empty() {
+        let full = event.event.event_type.to_string();
+        let simple = event.event.event_type.simple_string();
+        if !query
+            .event_types
+            .iter()
+            .any(|et| et == &full || et == &simple)
+        {
+            return false;
+        }
+    }
+    if query
+        .start_time
+        .is_some_and(|start| event.event.timestamp < start)
+    {
+        return false;
+    }
+    if query
+        .end_time
+        .is_some_and(|end| event.event.timestamp > end)
+    {
+        return false;
+    }
+    true
+}
+
+/// Decodes a stream entry written by [`APPEND_SCRIPT`].
+fn decode(stream_id: &str, entry: &StreamId) -> EventResult<StoredEvent> {
+    let field = |name: &str| {
+        entry.get::<String>(name).ok_or_else(|| {
+            EventError::SerializationError(format!(
+                "Stream entry {} has no '{}' field",
+                entry.id, name
+            ))
+        })
+    };
+
+    let version = entry
+        .id
+        .strip_prefix("0-")
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| {
+            EventError::SerializationError(format!("Invalid stream entry ID {}", entry.id))
+        })?;
+    let id = field("id")?
+        .parse()
+        .map_err(|e| EventError::SerializationError(format!("Invalid event ID: {}", e)))?;
+    let stored_at = DateTime::parse_from_rfc3339(&field("stored_at")?)
+        .map_err(|e| EventError::SerializationError(format!("Invalid stored_at: {}", e)))?
+        .with_timezone(&Utc);
+
+    Ok(StoredEvent {
+        id,
+        event: serde_json::from_str(&field("event")?)?,
+        stream_id: stream_id.to_string(),
+        version,
+        stored_at,
+    })
+}
+
+fn redis_error(err: redis::RedisError) -> EventError {
+    EventError::Internal(format!("Redis error: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EventType;
+    use std::collections::HashMap;
+
+    fn entry(id: &str, fields: &[(&str, String)]) -> StreamId {
+        let map: HashMap<String, redis::Value> = fields
+            .iter()
+            .map(|(k, v)| {
+                (
+                    k.to_string(),
+                    redis::Value::BulkString(v.clone().into_bytes()),
+                )
+            })
+            .collect();
+        StreamId {
+            id: id.to_string(),
+            map,
+        }
+    }
+
+    #[test]
+    fn test_decode_maps_entry_id_to_version() {
+        let event = Event::new(EventType::new("user", "created"), "payload");
+        let id = EventId::new_v4();
+        let fields = [
+            ("id", id.to_string()),
+            ("event", serde_json::to_string(&event).unwrap()),
+            ("stored_at", Utc::now().to_rfc3339()),
+        ];
+
+        let stored = decode("auth", &entry("0-7", &fields)).unwrap();
+        assert_eq!(stored.id, id);
+        assert_eq!(stored.version, 7);
+        assert_eq!(stored.stream_id, "auth");
+        assert_eq!(stored.event.id, event.id);
+
+        assert!(decode("auth", &entry("1700000000000-0", &fields)).is_err());
+        assert!(decode("auth", &entry("0-1", &fields[..2])).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Redis server at REDIS_URL"]
+    async fn test_event_roundtrip() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".into());
+        let prefix = format!("better_auth_test:{}:", EventId::new_v4());
+        let store = RedisEventStore::from_url(&url)
+            .await
+            .unwrap()
+            .with_prefix(prefix);
+
+        let mut subscription = store.subscribe_to_stream("auth").await.unwrap();
+
+        let event = Event::new(EventType::new("user", "created"), "payload")
+            .with_source("auth")
+            .with_correlation_id("corr-1");
+        let id = store.append(&event).await.unwrap();
+        let batch = [
+            Event::new(EventType::new("user", "updated"), "payload").with_source("auth"),
+            Event::new(EventType::new("user", "deleted"), "payload").with_source("auth"),
+        ];
+        store.append_batch(&batch).await.unwrap();
+
+        let stored = store.get(&id).await.unwrap().unwrap();
+        assert_eq!(stored.event.id, event.id);
+        assert_eq!(stored.version, 1);
+        assert_eq!(store.get_stream_version("auth").await.unwrap(), Some(3));
+        assert_eq!(store.get_by_correlation("corr-1").await.unwrap().len(), 1);
+
+        let versions: Vec<_> = store
+            .get_stream("auth", Some(2))
+            .await
+            .unwrap()
+            .iter()
+            .map(|e| e.version)
+            .collect();
+        assert_eq!(versions, [2, 3]);
+
+        let query = EventQuery {
+            ordering: EventOrdering::Descending,
+            limit: Some(2),
+            ..Default::default()
+        };
+        let results = store.query(query).await.unwrap();
+        assert_eq!(results.events()[0].event.event_type.name, "deleted");
+        assert_eq!(results.len(), 2);
+
+        let received = tokio::time::timeout(std::time::Duration::from_secs(5), subscription.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.id, id);
+
+        store.truncate_stream("auth", 3).await.unwrap();
+        assert!(store.get(&id).await.unwrap().is_none());
+        assert_eq!(store.get_stream("auth", None).await.unwrap().len(), 1);
+        assert_eq!(store.get_stream_version("auth").await.unwrap(), Some(3));
     }
 }
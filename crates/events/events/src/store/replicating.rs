@@ -0,0 +1,579 @@
+use super::trait_def::*;
+use crate::{Event, EventResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+
+/// When appended events are handed to secondary stores
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplicationConsistency {
+    /// Queue events for the secondaries before writing the primary.
+    ///
+    /// Secondaries may receive events the primary then fails to store.
+    BestEffort,
+
+    /// Queue events for the secondaries only after the primary has stored them.
+    #[default]
+    ConfirmPrimary,
+}
+
+/// Replication state of a single secondary store
+#[derive(Debug, Clone)]
+pub struct ReplicaStatus {
+    /// Name of the secondary (e.g. its region)
+    pub name: String,
+
+    /// Events queued but not yet replicated
+    pub pending: usize,
+
+    /// Events successfully replicated
+    pub replicated: u64,
+
+    /// Failed replication attempts, including retried ones
+    pub failures: u64,
+
+    /// Events that exhausted their retries and await [`ReplicatingEventStore::retry_failed`]
+    pub failed: usize,
+
+    /// Most recent replication error
+    pub last_error: Option<String>,
+
+    /// Age of the oldest event not yet replicated
+    pub lag: Duration,
+}
+
+/// A unit of replication work
+#[derive(Debug, Clone)]
+enum ReplicationTask {
+    Single(Box<Event>),
+    Batch(Vec<Event>),
+}
+
+impl ReplicationTask {
+    fn len(&self) -> usize {
+        match self {
+            Self::Single(_) => 1,
+            Self::Batch(events) => events.len(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ReplicaState {
+    /// Enqueue time of each pending task, oldest first
+    queued_at: VecDeque<DateTime<Utc>>,
+    pending: usize,
+    replicated: u64,
+    failures: u64,
+    failed: Vec<ReplicationTask>,
+    last_error: Option<String>,
+}
+
+struct Replica {
+    name: String,
+    sender: mpsc::UnboundedSender<ReplicationTask>,
+    state: Arc<Mutex<ReplicaState>>,
+    idle: Arc<Notify>,
+}
+
+impl Replica {
+    fn enqueue(&self, task: ReplicationTask) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.queued_at.push_back(Utc::now());
+            state.pending += task.len();
+        }
+        if let Err(mpsc::error::SendError(task)) = self.sender.send(task) {
+            // The worker only stops when the store is dropped; keep the
+            // event for inspection rather than losing it silently.
+            let mut state = self.state.lock().unwrap();
+            state.queued_at.pop_back();
+            state.pending -= task.len();
+            state.failed.push(task);
+        }
+    }
+
+    fn status(&self) -> ReplicaStatus {
+        let state = self.state.lock().unwrap();
+        let lag = state
+            .queued_at
+            .front()
+            .and_then(|oldest| (Utc::now() - *oldest).to_std().ok())
+            .unwrap_or_default();
+
+        ReplicaStatus {
+            name: self.name.clone(),
+            pending: state.pending,
+            replicated: state.replicated,
+            failures: state.failures,
+            failed: state.failed.len(),
+            last_error: state.last_error.clone(),
+            lag,
+        }
+    }
+}
+
+/// Builder for [`ReplicatingEventStore`]
+pub struct ReplicatingEventStoreBuilder {
+    primary: Arc<dyn EventStore>,
+    secondaries: Vec<(String, Arc<dyn EventStore>)>,
+    consistency: ReplicationConsistency,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+impl ReplicatingEventStoreBuilder {
+    /// Adds a secondary store identified by `name` (e.g. its region)
+    pub fn secondary(mut self, name: impl Into<String>, store: Arc<dyn EventStore>) -> Self {
+        self.secondaries.push((name.into(), store));
+        self
+    }
+
+    /// Sets when events are handed to the secondaries
+    pub fn consistency(mut self, consistency: ReplicationConsistency) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    /// Sets how many times a failed replication is retried (default: 3)
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the base delay between retries, doubled after each attempt (default: 100ms)
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Builds the store and starts one replication worker per secondary
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a Tokio runtime.
+    pub fn build(self) -> ReplicatingEventStore {
+        let replicas = self
+            .secondaries
+            .into_iter()
+            .map(|(name, store)| {
+                let (sender, receiver) = mpsc::unbounded_channel();
+                let state = Arc::new(Mutex::new(ReplicaState::default()));
+                let idle = Arc::new(Notify::new());
+
+                tokio::spawn(run_worker(
+                    name.clone(),
+                    store,
+                    receiver,
+                    state.clone(),
+                    idle.clone(),
+                    self.max_retries,
+                    self.retry_delay,
+                ));
+
+                Replica {
+                    name,
+                    sender,
+                    state,
+                    idle,
+                }
+            })
+            .collect();
+
+        ReplicatingEventStore {
+            primary: self.primary,
+            replicas,
+            consistency: self.consistency,
+        }
+    }
+}
+
+/// Event store that mirrors appended events to secondary stores
+///
+/// Writes go to the primary and are replicated to each secondary in the
+/// background, in order. A failed replication never fails the primary
+/// append: it is recorded in [`ReplicaStatus`], retried with exponential
+/// backoff, and kept for [`retry_failed`](Self::retry_failed) once its
+/// retries are exhausted.
+///
+/// Reads, snapshots, and truncation only touch the primary.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let store = ReplicatingEventStore::builder(Arc::new(PostgresEventStore::new(pool)))
+///     .secondary("eu-west-1", Arc::new(PostgresEventStore::new(eu_pool)))
+///     .consistency(ReplicationConsistency::ConfirmPrimary)
+///     .build();
+/// ```
+pub struct ReplicatingEventStore {
+    primary: Arc<dyn EventStore>,
+    replicas: Vec<Replica>,
+    consistency: ReplicationConsistency,
+}
+
+impl ReplicatingEventStore {
+    /// Creates a builder wrapping `primary`
+    pub fn builder(primary: Arc<dyn EventStore>) -> ReplicatingEventStoreBuilder {
+        ReplicatingEventStoreBuilder {
+            primary,
+            secondaries: Vec::new(),
+            consistency: ReplicationConsistency::default(),
+            max_retries: 3,
+            retry_delay: Duration::from_millis(100),
+        }
+    }
+
+    /// Returns the primary store
+    pub fn primary(&self) -> &Arc<dyn EventStore> {
+        &self.primary
+    }
+
+    /// Returns the replication status of every secondary
+    pub fn status(&self) -> Vec<ReplicaStatus> {
+        self.replicas.iter().map(Replica::status).collect()
+    }
+
+    /// Returns the largest replication lag across all secondaries
+    pub fn max_lag(&self) -> Duration {
+        self.replicas
+            .iter()
+            .map(|replica| replica.status().lag)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Requeues events that exhausted their retries
+    ///
+    /// Returns the number of tasks requeued.
+    pub fn retry_failed(&self) -> usize {
+        let mut requeued = 0;
+        for replica in &self.replicas {
+            let failed = std::mem::take(&mut replica.state.lock().unwrap().failed);
+            requeued += failed.len();
+            for task in failed {
+                replica.enqueue(task);
+            }
+        }
+        requeued
+    }
+
+    /// Waits until every queued event has been replicated or given up on
+    pub async fn flush(&self) {
+        for replica in &self.replicas {
+            loop {
+                let idle = replica.idle.notified();
+                if replica.state.lock().unwrap().pending == 0 {
+                    break;
+                }
+                idle.await;
+            }
+        }
+    }
+
+    fn replicate(&self, task: ReplicationTask) {
+        for replica in &self.replicas {
+            replica.enqueue(task.clone());
+        }
+    }
+}
+
+async fn run_worker(
+    name: String,
+    store: Arc<dyn EventStore>,
+    mut receiver: mpsc::UnboundedReceiver<ReplicationTask>,
+    state: Arc<Mutex<ReplicaState>>,
+    idle: Arc<Notify>,
+    max_retries: u32,
+    retry_delay: Duration,
+) {
+    while let Some(task) = receiver.recv().await {
+        let mut attempt = 0;
+        let result = loop {
+            let result = match &task {
+                ReplicationTask::Single(event) => store.append(event).await.map(|_| ()),
+                ReplicationTask::Batch(events) => store.append_batch(events).await.map(|_| ()),
+            };
+
+            match result {
+                Ok(()) => break Ok(()),
+                Err(err) => {
+                    tracing::warn!(replica = %name, attempt, error = %err, "event replication failed");
+                    {
+                        let mut state = state.lock().unwrap();
+                        state.failures += 1;
+                        state.last_error = Some(err.to_string());
+                    }
+                    if attempt >= max_retries {
+                        break Err(());
+                    }
+                    tokio::time::sleep(retry_delay.saturating_mul(2u32.saturating_pow(attempt))).await;
+                    attempt += 1;
+                }
+            }
+        };
+
+        let mut state = state.lock().unwrap();
+        state.queued_at.pop_front();
+        state.pending -= task.len();
+        match result {
+            Ok(()) => state.replicated += task.len() as u64,
+            Err(()) => state.failed.push(task),
+        }
+        drop(state);
+        idle.notify_waiters();
+    }
+}
+
+#[async_trait]
+impl EventStore for ReplicatingEventStore {
+    async fn append(&self, event: &Event) -> EventResult<EventId> {
+        match self.consistency {
+            ReplicationConsistency::BestEffort => {
+                self.replicate(ReplicationTask::Single(Box::new(event.clone())));
+                self.primary.append(event).await
+            }
+            ReplicationConsistency::ConfirmPrimary => {
+                let id = self.primary.append(event).await?;
+                self.replicate(ReplicationTask::Single(Box::new(event.clone())));
+                Ok(id)
+            }
+        }
+    }
+
+    async fn append_batch(&self, events: &[Event]) -> EventResult<Vec<EventId>> {
+        if events.is_empty() {
+            return self.primary.append_batch(events).await;
+        }
+        match self.consistency {
+            ReplicationConsistency::BestEffort => {
+                self.replicate(ReplicationTask::Batch(events.to_vec()));
+                self.primary.append_batch(events).await
+            }
+            ReplicationConsistency::ConfirmPrimary => {
+                let ids = self.primary.append_batch(events).await?;
+                self.replicate(ReplicationTask::Batch(events.to_vec()));
+                Ok(ids)
+            }
+        }
+    }
+
+    async fn get(&self, id: &EventId) -> EventResult<Option<StoredEvent>> {
+        self.primary.get(id).await
+    }
+
+    async fn get_stream(
+        &self,
+        stream_id: &str,
+        from_version: Option<StreamVersion>,
+    ) -> EventResult<Vec<StoredEvent>> {
+        self.primary.get_stream(stream_id, from_version).await
+    }
+
+    async fn get_by_correlation(&self, correlation_id: &str) -> EventResult<Vec<StoredEvent>> {
+        self.primary.get_by_correlation(correlation_id).await
+    }
+
+    async fn query(&self, query: EventQuery) -> EventResult<EventStream> {
+        self.primary.query(query).await
+    }
+
+    async fn subscribe_to_stream(&self, stream_id: &str) -> EventResult<EventStreamSubscription> {
+        self.primary.subscribe_to_stream(stream_id).await
+    }
+
+    async fn get_stream_version(&self, stream_id: &str) -> EventResult<Option<StreamVersion>> {
+        self.primary.get_stream_version(stream_id).await
+    }
+
+    async fn create_snapshot(
+        &self,
+        stream_id: &str,
+        version: StreamVersion,
+        state: serde_json::Value,
+    ) -> EventResult<()> {
+        self.primary.create_snapshot(stream_id, version, state).await
+    }
+
+    async fn get_latest_snapshot(&self, stream_id: &str) -> EventResult<Option<EventSnapshot>> {
+        self.primary.get_latest_snapshot(stream_id).await
+    }
+
+    async fn truncate_stream(
+        &self,
+        stream_id: &str,
+        before_version: StreamVersion,
+    ) -> EventResult<()> {
+        self.primary.truncate_stream(stream_id, before_version).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryEventStore;
+    use crate::{EventError, EventType};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn create_test_event(source: &str) -> Event {
+        let mut event = Event::new(
+            EventType::new("test", "event"),
+            serde_json::json!({"data": "test"}),
+        );
+        event.metadata.source = source.to_string();
+        event
+    }
+
+    /// Store that fails the first `failures` appends, then delegates.
+    struct FlakyStore {
+        remaining_failures: AtomicU32,
+        inner: MemoryEventStore,
+    }
+
+    impl FlakyStore {
+        fn new(failures: u32) -> Self {
+            Self {
+                remaining_failures: AtomicU32::new(failures),
+                inner: MemoryEventStore::new(),
+            }
+        }
+
+        fn check(&self) -> EventResult<()> {
+            let remaining = self.remaining_failures.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.remaining_failures.store(remaining - 1, Ordering::SeqCst);
+                return Err(EventError::Internal("region unavailable".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl EventStore for FlakyStore {
+        async fn append(&self, event: &Event) -> EventResult<EventId> {
+            self.check()?;
+            self.inner.append(event).await
+        }
+        async fn append_batch(&self, events: &[Event]) -> EventResult<Vec<EventId>> {
+            self.check()?;
+            self.inner.append_batch(events).await
+        }
+        async fn get(&self, id: &EventId) -> EventResult<Option<StoredEvent>> {
+            self.inner.get(id).await
+        }
+        async fn get_stream(&self, stream_id: &str, from: Option<StreamVersion>) -> EventResult<Vec<StoredEvent>> {
+            self.inner.get_stream(stream_id, from).await
+        }
+        async fn get_by_correlation(&self, id: &str) -> EventResult<Vec<StoredEvent>> {
+            self.inner.get_by_correlation(id).await
+        }
+        async fn query(&self, query: EventQuery) -> EventResult<EventStream> {
+            self.inner.query(query).await
+        }
+        async fn subscribe_to_stream(&self, stream_id: &str) -> EventResult<EventStreamSubscription> {
+            self.inner.subscribe_to_stream(stream_id).await
+        }
+        async fn get_stream_version(&self, stream_id: &str) -> EventResult<Option<StreamVersion>> {
+            self.inner.get_stream_version(stream_id).await
+        }
+        async fn create_snapshot(&self, stream_id: &str, version: StreamVersion, state: serde_json::Value) -> EventResult<()> {
+            self.inner.create_snapshot(stream_id, version, state).await
+        }
+        async fn get_latest_snapshot(&self, stream_id: &str) -> EventResult<Option<EventSnapshot>> {
+            self.inner.get_latest_snapshot(stream_id).await
+        }
+        async fn truncate_stream(&self, stream_id: &str, before: StreamVersion) -> EventResult<()> {
+            self.inner.truncate_stream(stream_id, before).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replicates_to_secondaries() {
+        let secondary = Arc::new(MemoryEventStore::new());
+        let store = ReplicatingEventStore::builder(Arc::new(MemoryEventStore::new()))
+            .secondary("eu-west", secondary.clone())
+            .build();
+
+        store.append(&create_test_event("stream-1")).await.unwrap();
+        store
+            .append_batch(&[create_test_event("stream-1"), create_test_event("stream-1")])
+            .await
+            .unwrap();
+        store.flush().await;
+
+        assert_eq!(store.get_stream("stream-1", None).await.unwrap().len(), 3);
+        assert_eq!(secondary.get_stream("stream-1", None).await.unwrap().len(), 3);
+
+        let status = &store.status()[0];
+        assert_eq!(status.name, "eu-west");
+        assert_eq!(status.replicated, 3);
+        assert_eq!(status.pending, 0);
+        assert_eq!(status.lag, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_retried_without_failing_primary() {
+        let secondary = Arc::new(FlakyStore::new(2));
+        let store = ReplicatingEventStore::builder(Arc::new(MemoryEventStore::new()))
+            .secondary("us-east", secondary.clone())
+            .retry_delay(Duration::from_millis(1))
+            .build();
+
+        store.append(&create_test_event("stream-1")).await.unwrap();
+        store.flush().await;
+
+        let status = &store.status()[0];
+        assert_eq!(status.failures, 2);
+        assert_eq!(status.replicated, 1);
+        assert_eq!(status.last_error.as_deref(), Some("Internal error: region unavailable"));
+        assert_eq!(secondary.get_stream("stream-1", None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_can_be_requeued() {
+        let secondary = Arc::new(FlakyStore::new(3));
+        let store = ReplicatingEventStore::builder(Arc::new(MemoryEventStore::new()))
+            .secondary("us-east", secondary.clone())
+            .max_retries(1)
+            .retry_delay(Duration::from_millis(1))
+            .build();
+
+        store.append(&create_test_event("stream-1")).await.unwrap();
+        store.flush().await;
+        assert_eq!(store.status()[0].failed, 1);
+        assert!(secondary.get_stream("stream-1", None).await.unwrap().is_empty());
+
+        assert_eq!(store.retry_failed(), 1);
+        store.flush().await;
+
+        let status = &store.status()[0];
+        assert_eq!(status.failed, 0);
+        assert_eq!(status.replicated, 1);
+        assert_eq!(secondary.get_stream("stream-1", None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_primary_skips_rejected_events() {
+        let secondary = Arc::new(MemoryEventStore::new());
+        let store = ReplicatingEventStore::builder(Arc::new(FlakyStore::new(1)))
+            .secondary("eu-west", secondary.clone())
+            .consistency(ReplicationConsistency::ConfirmPrimary)
+            .build();
+
+        assert!(store.append(&create_test_event("stream-1")).await.is_err());
+        store.flush().await;
+        assert!(secondary.get_stream("stream-1", None).await.unwrap().is_empty());
+
+        let store = ReplicatingEventStore::builder(Arc::new(FlakyStore::new(1)))
+            .secondary("eu-west", secondary.clone())
+            .consistency(ReplicationConsistency::BestEffort)
+            .build();
+
+        assert!(store.append(&create_test_event("stream-1")).await.is_err());
+        store.flush().await;
+        assert_eq!(secondary.get_stream("stream-1", None).await.unwrap().len(), 1);
+    }
+}
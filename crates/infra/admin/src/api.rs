@@ -1,8 +1,11 @@
 //! Admin API handlers.
 
+use better_auth_core::context::AuthContext;
+use better_auth_core::deletion::delete_user_fully;
 use better_auth_core::error::{AuthError, AuthResult};
-use better_auth_core::traits::StorageAdapter;
-use better_auth_core::types::{Session, User};
+use better_auth_core::events::EventBus;
+use better_auth_core::traits::{AuthPlugin, StorageAdapter};
+use better_auth_core::types::{Session, User, UserDeletion, UserFilter};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -22,37 +25,98 @@ pub struct UserSummary {
     pub email: String,
     pub name: Option<String>,
     pub email_verified: bool,
+    pub banned: bool,
     pub created_at: String,
 }
 
+/// Ban status set on users by [`AdminApi::ban_user`].
+pub trait UserBanExt {
+    /// Returns true if an admin has banned the user.
+    fn is_banned(&self) -> bool;
+}
+
+impl UserBanExt for User {
+    fn is_banned(&self) -> bool {
+        self.get_extension::<bool>("banned").unwrap_or(false)
+    }
+}
+
 impl From<User> for UserSummary {
     fn from(user: User) -> Self {
+        let banned = user.is_banned();
         Self {
             id: user.id,
             email: user.email,
             name: user.name,
             email_verified: user.email_verified,
+            banned,
             created_at: user.created_at.to_rfc3339(),
         }
     }
 }
 
+/// Largest page size [`AdminApi::list_users`] returns.
+pub const MAX_PER_PAGE: usize = 100;
+
+/// Number of soft-deleted users fetched at a time when purging.
+const PURGE_BATCH: usize = 100;
+
 /// Admin API service.
 pub struct AdminApi {
     adapter: Arc<dyn StorageAdapter>,
+    user_deletion: UserDeletion,
+    plugins: Vec<Arc<dyn AuthPlugin>>,
+    events: Option<Arc<EventBus>>,
 }
 
 impl AdminApi {
-    /// Creates a new admin API.
+    /// Creates a new admin API that hard-deletes users.
     pub fn new(adapter: Arc<dyn StorageAdapter>) -> Self {
-        Self { adapter }
+        Self {
+            adapter,
+            user_deletion: UserDeletion::Hard,
+            plugins: Vec::new(),
+            events: None,
+        }
+    }
+
+    /// Sets how [`delete_user`](Self::delete_user) deletes users.
+    pub fn user_deletion(mut self, mode: UserDeletion) -> Self {
+        self.user_deletion = mode;
+        self
+    }
+
+    /// Sets the plugins whose `on_before_user_delete` hooks run when a user
+    /// is hard-deleted.
+    pub fn plugins(mut self, plugins: Vec<Arc<dyn AuthPlugin>>) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Sets the event bus `user.deleted` is emitted on.
+    pub fn event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.events = Some(bus);
+        self
     }
 
-    /// Lists users with pagination.
-    pub async fn list_users(&self, page: usize, per_page: usize) -> AuthResult<UserListResponse> {
-        let offset = page * per_page;
-        let users = self.adapter.list_users(offset, per_page).await?;
-        let total = self.adapter.count_users().await?;
+    /// Lists users matching `filter`, one page at a time.
+    ///
+    /// `per_page` is clamped to between 1 and [`MAX_PER_PAGE`].
+    pub async fn list_users(
+        &self,
+        page: usize,
+        per_page: usize,
+        filter: Option<UserFilter>,
+    ) -> AuthResult<UserListResponse> {
+        let per_page = per_page.clamp(1, MAX_PER_PAGE);
+        let offset = page
+            .checked_mul(per_page)
+            .filter(|offset| i64::try_from(*offset).is_ok())
+            .ok_or_else(|| AuthError::InvalidField {
+                field: "page".to_string(),
+                reason: "Page is out of range".to_string(),
+            })?;
+        let (users, total) = self.adapter.list_users(offset, per_page, filter).await?;
 
         Ok(UserListResponse {
             users: users.into_iter().map(UserSummary::from).collect(),
@@ -70,14 +134,16 @@ impl AdminApi {
             .ok_or_else(|| AuthError::not_found("user", "id", id))
     }
 
-    /// Bans a user.
+    /// Bans a user and revokes all of their sessions.
     pub async fn ban_user(&self, id: &str, reason: Option<String>) -> AuthResult<User> {
         let mut user = self.get_user(id).await?;
         user.set_extension("banned", true);
         if let Some(reason) = reason {
             user.set_extension("ban_reason", reason);
         }
-        self.adapter.update_user(&user).await
+        let user = self.adapter.update_user(&user).await?;
+        self.adapter.delete_sessions_by_user_id(id).await?;
+        Ok(user)
     }
 
     /// Unbans a user.
@@ -103,22 +169,84 @@ impl AdminApi {
         self.adapter.delete_sessions_by_user_id(user_id).await
     }
 
-    /// Creates an impersonation session.
-    pub async fn impersonate_user(&self, user_id: &str) -> AuthResult<Session> {
+    /// Creates a session for `user_id` on behalf of the admin `admin_id`.
+    ///
+    /// The session records the admin in its `impersonated_by` extension and
+    /// expires after `ttl`.
+    pub async fn impersonate_user(
+        &self,
+        user_id: &str,
+        admin_id: &str,
+        ttl: chrono::Duration,
+    ) -> AuthResult<Session> {
         // Verify user exists
         let _ = self.get_user(user_id).await?;
 
-        // Create a short-lived session
         let mut session = Session::new(user_id.to_string());
-        session.set_extension("impersonation", true);
-        // Set shorter expiration (1 hour)
-        session.expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
+        session.set_extension("impersonated_by", admin_id);
+        session.expires_at = chrono::Utc::now() + ttl;
 
         self.adapter.create_session(&session).await
     }
 
-    /// Deletes a user.
+    /// Deletes a user, or soft-deletes it if configured to.
+    ///
+    /// A hard delete goes through [`delete_user_fully`], so the plugins'
+    /// data goes with the user.
     pub async fn delete_user(&self, id: &str) -> AuthResult<()> {
-        self.adapter.delete_user(id).await
+        match self.user_deletion {
+            UserDeletion::Hard => self.delete_fully(id).await,
+            UserDeletion::Soft => self.adapter.soft_delete_user(id).await,
+        }
+    }
+
+    /// Deletes a user with [`delete_user_fully`].
+    async fn delete_fully(&self, id: &str) -> AuthResult<()> {
+        let ctx = AuthContext::new(self.adapter.clone());
+        let plugins: Vec<&dyn AuthPlugin> = self.plugins.iter().map(|p| p.as_ref()).collect();
+        delete_user_fully(&ctx, id, &plugins, self.events.as_deref()).await
+    }
+
+    /// Restores a soft-deleted user.
+    pub async fn restore_user(&self, id: &str) -> AuthResult<User> {
+        self.adapter
+            .restore_user(id)
+            .await?
+            .ok_or_else(|| AuthError::not_found("deleted user", "id", id))
+    }
+
+    /// Hard-deletes the users that were soft-deleted more than `grace` ago.
+    ///
+    /// Each user is deleted like a hard [`delete_user`](Self::delete_user).
+    /// Returns the number of users deleted. Meant to be run periodically.
+    pub async fn purge_deleted_users(&self, grace: chrono::Duration) -> AuthResult<usize> {
+        let deleted_before = chrono::Utc::now() - grace;
+        let filter = UserFilter::new().include_deleted();
+
+        // Collected first, as deleting would shift the pages.
+        let mut expired = Vec::new();
+        let mut offset = 0;
+        loop {
+            let (users, _) = self
+                .adapter
+                .list_users(offset, PURGE_BATCH, Some(filter.clone()))
+                .await?;
+            let fetched = users.len();
+            expired.extend(
+                users
+                    .into_iter()
+                    .filter(|user| user.deleted_at.is_some_and(|at| at < deleted_before))
+                    .map(|user| user.id),
+            );
+            if fetched < PURGE_BATCH {
+                break;
+            }
+            offset += fetched;
+        }
+
+        for id in &expired {
+            self.delete_fully(id).await?;
+        }
+        Ok(expired.len())
     }
 }
@@ -0,0 +1,720 @@
+//! HTTP handlers for the admin dashboard.
+//!
+//! Every route requires either the static `api_token` as a bearer token or
+//! a session whose user has the configured `required_role` in their `role`
+//! extension. Requests without credentials get 401; authenticated users
+//! without the role get 403.
+
+use crate::{AdminApi, AdminConfig, AdminDashboard, UserSummary};
+use async_trait::async_trait;
+use better_auth_core::crypto::constant_time_eq;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::events::Event;
+use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_core::session::SessionResolver;
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::UserFilter;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use std::future::Future;
+use std::sync::Arc;
+
+/// Default page size for `GET /admin/users`.
+const DEFAULT_PER_PAGE: usize = 20;
+
+/// Builds the [`AdminApi`] `config` asks for.
+fn admin_api(config: &AdminConfig, adapter: Arc<dyn StorageAdapter>) -> AdminApi {
+    let api = AdminApi::new(adapter)
+        .user_deletion(config.user_deletion)
+        .plugins(config.plugins.clone());
+    match &config.event_bus {
+        Some(bus) => api.event_bus(bus.clone()),
+        None => api,
+    }
+}
+
+/// State shared by the admin handlers.
+pub struct AdminState {
+    config: AdminConfig,
+    api: AdminApi,
+    resolver: SessionResolver,
+    adapter: Arc<dyn StorageAdapter>,
+}
+
+impl AdminState {
+    /// Creates handler state backed by `adapter`.
+    pub fn new(config: AdminConfig, adapter: Arc<dyn StorageAdapter>) -> Self {
+        Self {
+            api: admin_api(&config, adapter.clone()),
+            config,
+            resolver: AdminDashboard::reject_banned(SessionResolver::new(adapter.clone())),
+            adapter,
+        }
+    }
+
+    /// Returns `Ok(false)` when the request carries no valid credential and
+    /// an error when the caller is authenticated but not an admin, or is
+    /// using an impersonation session.
+    async fn authorize(&self, req: &Request) -> AuthResult<bool> {
+        if let Some(token) = &self.config.api_token
+            && let Some(presented) = req
+                .header("authorization")
+                .and_then(|h| h.strip_prefix("Bearer "))
+            && constant_time_eq(presented.trim(), token)
+        {
+            return Ok(true);
+        }
+
+        let Some(resolved) = self.resolver.resolve_request(req).await? else {
+            return Ok(false);
+        };
+        if resolved.session.impersonated_by().is_some() {
+            return Err(AuthError::forbidden(
+                "Impersonation sessions cannot use the admin API",
+            ));
+        }
+        if self.is_admin(&resolved.session.user_id).await? {
+            Ok(true)
+        } else {
+            Err(AuthError::forbidden(format!(
+                "The '{}' role is required",
+                self.config.required_role
+            )))
+        }
+    }
+
+    /// Returns true if the user has the configured `required_role`.
+    async fn is_admin(&self, user_id: &str) -> AuthResult<bool> {
+        let role = self
+            .adapter
+            .get_user_by_id(user_id)
+            .await?
+            .and_then(|user| user.get_extension::<String>("role"));
+        Ok(role.as_deref() == Some(self.config.required_role.as_str()))
+    }
+
+    /// Returns the ID of the admin behind an already authorized request.
+    ///
+    /// Requests authorized only by the API token and requests made from an
+    /// impersonation session have no admin user and are refused.
+    async fn admin_user_id(&self, req: &Request) -> AuthResult<String> {
+        let session = self
+            .resolver
+            .resolve_request(req)
+            .await?
+            .map(|resolved| resolved.session)
+            .filter(|session| session.impersonated_by().is_none())
+            .ok_or_else(|| AuthError::forbidden("Impersonation requires an admin session"))?;
+        Ok(session.user_id)
+    }
+
+    async fn emit(&self, event_type: &str, data: serde_json::Value) {
+        if let Some(bus) = &self.config.event_bus {
+            bus.emit(Event::simple(event_type, data).with_source("admin"))
+                .await;
+        }
+    }
+}
+
+/// Adapts an async admin handler function into a `RequestHandler`.
+///
+/// The caller is authorized before the function runs; errors are rendered
+/// as JSON with the status code reported by `AuthError`.
+pub struct AdminHandler<F> {
+    state: Arc<AdminState>,
+    handler: F,
+}
+
+impl<F> AdminHandler<F> {
+    /// Creates a new handler bound to the given state.
+    pub fn new(state: Arc<AdminState>, handler: F) -> Self {
+        Self { state, handler }
+    }
+}
+
+#[async_trait]
+impl<F, Fut> RequestHandler for AdminHandler<F>
+where
+    F: Fn(Request, Arc<AdminState>) -> Fut + Send + Sync,
+    Fut: Future<Output = AuthResult<Response>> + Send,
+{
+    async fn handle(&self, req: Request) -> Response {
+        let result = match self.state.authorize(&req).await {
+            Ok(true) => (self.handler)(req, self.state.clone()).await,
+            Ok(false) => {
+                return Response::unauthorized().json(json!({ "error": "unauthorized" }));
+            }
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(response) => response,
+            Err(err) => Response::new(err.status_code()).json(json!({
+                "error": err.to_string()
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BanRequest {
+    reason: Option<String>,
+}
+
+/// GET /admin/users - List users
+///
+/// Query parameters: `page` (from 0), `per_page`, `email` (substring) and
+/// `created_after` (RFC 3339).
+pub async fn list_users_handler(req: Request, state: Arc<AdminState>) -> AuthResult<Response> {
+    let page = query_number(&req, "page")?.unwrap_or(0);
+    let per_page = query_number(&req, "per_page")?.unwrap_or(DEFAULT_PER_PAGE);
+
+    let mut filter = UserFilter::new();
+    if let Some(email) = req.query_param("email") {
+        filter = filter.email_contains(email.as_str());
+    }
+    if let Some(after) = req.query_param("created_after") {
+        let after = DateTime::parse_from_rfc3339(after)
+            .map_err(|_| invalid("created_after", "Expected an RFC 3339 timestamp"))?;
+        filter = filter.created_after(after.with_timezone(&Utc));
+    }
+
+    let users = state.api.list_users(page, per_page, Some(filter)).await?;
+    Ok(Response::ok().json(users))
+}
+
+/// GET /admin/users/:id - Get a user
+pub async fn get_user_handler(req: Request, state: Arc<AdminState>) -> AuthResult<Response> {
+    let user = state.api.get_user(path_param(&req, "id")?).await?;
+    Ok(Response::ok().json(UserSummary::from(user)))
+}
+
+/// POST /admin/users/:id/ban - Ban a user and revoke their sessions
+pub async fn ban_user_handler(req: Request, state: Arc<AdminState>) -> AuthResult<Response> {
+    let body: BanRequest = req.json().unwrap_or_default();
+    let user = state
+        .api
+        .ban_user(path_param(&req, "id")?, body.reason)
+        .await?;
+    Ok(Response::ok().json(UserSummary::from(user)))
+}
+
+/// DELETE /admin/users/:id - Delete a user
+///
+/// Soft-deletes the user instead when `user_deletion` is
+/// [`UserDeletion::Soft`](better_auth_core::types::UserDeletion::Soft).
+pub async fn delete_user_handler(req: Request, state: Arc<AdminState>) -> AuthResult<Response> {
+    state.api.delete_user(path_param(&req, "id")?).await?;
+    Ok(Response::ok().json(json!({ "message": "User deleted successfully" })))
+}
+
+/// POST /admin/users/:id/restore - Restore a soft-deleted user
+pub async fn restore_user_handler(req: Request, state: Arc<AdminState>) -> AuthResult<Response> {
+    let user = state.api.restore_user(path_param(&req, "id")?).await?;
+    Ok(Response::ok().json(UserSummary::from(user)))
+}
+
+/// POST /admin/users/:id/impersonate - Start a session as another user
+///
+/// Other admins cannot be impersonated. Emits `admin.impersonation_started`.
+pub async fn impersonate_handler(req: Request, state: Arc<AdminState>) -> AuthResult<Response> {
+    let admin_id = state.admin_user_id(&req).await?;
+    let user_id = path_param(&req, "id")?;
+    if user_id == admin_id {
+        return Err(invalid("id", "Admins cannot impersonate themselves"));
+    }
+    if state.is_admin(user_id).await? {
+        return Err(AuthError::forbidden("Admins cannot be impersonated"));
+    }
+
+    let session = state
+        .api
+        .impersonate_user(user_id, &admin_id, state.config.impersonation_ttl)
+        .await?;
+    state
+        .emit(
+            "admin.impersonation_started",
+            json!({
+                "admin_id": admin_id,
+                "user_id": user_id,
+                "session_id": session.id,
+            }),
+        )
+        .await;
+
+    Ok(Response::created().json(session))
+}
+
+/// Handler for POST /admin/stop-impersonation
+///
+/// Deletes the impersonation session the request is made with and returns
+/// the admin's user ID. The admin's own session is left untouched. Emits
+/// `admin.impersonation_stopped`.
+pub struct StopImpersonationHandler {
+    state: Arc<AdminState>,
+}
+
+impl StopImpersonationHandler {
+    /// Creates a new handler bound to the given state.
+    pub fn new(state: Arc<AdminState>) -> Self {
+        Self { state }
+    }
+
+    async fn stop(&self, req: &Request) -> AuthResult<Option<Response>> {
+        let Some(resolved) = self.state.resolver.resolve_request(req).await? else {
+            return Ok(None);
+        };
+        let session = resolved.session;
+        let Some(admin_id) = session.impersonated_by() else {
+            return Err(AuthError::forbidden("Not an impersonation session"));
+        };
+
+        self.state.adapter.delete_session(&session.id).await?;
+        self.state
+            .emit(
+                "admin.impersonation_stopped",
+                json!({
+                    "admin_id": admin_id,
+                    "user_id": session.user_id,
+                    "session_id": session.id,
+                }),
+            )
+            .await;
+
+        Ok(Some(Response::ok().json(json!({ "user_id": admin_id }))))
+    }
+}
+
+#[async_trait]
+impl RequestHandler for StopImpersonationHandler {
+    async fn handle(&self, req: Request) -> Response {
+        match self.stop(&req).await {
+            Ok(Some(response)) => response,
+            Ok(None) => Response::unauthorized().json(json!({ "error": "unauthorized" })),
+            Err(err) => Response::new(err.status_code()).json(json!({
+                "error": err.to_string()
+            })),
+        }
+    }
+}
+
+/// DELETE /admin/sessions/:id - Delete a session
+pub async fn delete_session_handler(req: Request, state: Arc<AdminState>) -> AuthResult<Response> {
+    state.api.delete_session(path_param(&req, "id")?).await?;
+    Ok(Response::ok().json(json!({ "message": "Session deleted successfully" })))
+}
+
+fn path_param<'a>(req: &'a Request, name: &str) -> AuthResult<&'a str> {
+    req.param(name)
+        .map(String::as_str)
+        .ok_or_else(|| AuthError::MissingField {
+            field: name.to_string(),
+        })
+}
+
+fn query_number(req: &Request, name: &str) -> AuthResult<Option<usize>> {
+    req.query_param(name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| invalid(name, "Expected a non-negative integer"))
+        })
+        .transpose()
+}
+
+fn invalid(field: &str, reason: impl Into<String>) -> AuthError {
+    AuthError::InvalidField {
+        field: field.to_string(),
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use better_auth_adapter_memory::MemoryAdapter;
+    use better_auth_core::events::EventBus;
+    use better_auth_core::router::Method;
+    use better_auth_core::types::{Session, User, UserDeletion};
+
+    struct Fixture {
+        adapter: Arc<MemoryAdapter>,
+        bus: Arc<EventBus>,
+        state: Arc<AdminState>,
+        admin_token: String,
+        user_token: String,
+    }
+
+    async fn fixture() -> Fixture {
+        let adapter = Arc::new(MemoryAdapter::new());
+
+        let mut admin = User::new("admin_1".to_string(), "admin@example.com".to_string());
+        admin.set_extension("role", "admin");
+        adapter.create_user(&admin).await.unwrap();
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        adapter.create_user(&user).await.unwrap();
+
+        let admin_session = adapter
+            .create_session(&Session::new("admin_1".to_string()))
+            .await
+            .unwrap();
+        let user_session = adapter
+            .create_session(&Session::new("user_1".to_string()))
+            .await
+            .unwrap();
+
+        let bus = Arc::new(EventBus::new());
+        let state = Arc::new(AdminState::new(
+            AdminConfig::new()
+                .api_token("service-token")
+                .impersonation_ttl(chrono::Duration::minutes(15))
+                .event_bus(bus.clone()),
+            adapter.clone(),
+        ));
+        Fixture {
+            adapter,
+            bus,
+            state,
+            admin_token: admin_session.token,
+            user_token: user_session.token,
+        }
+    }
+
+    fn request(method: Method, path: &str, bearer: Option<&str>) -> Request {
+        let mut req = Request::new(method, path);
+        if let Some(token) = bearer {
+            req.headers
+                .insert("authorization".to_string(), format!("Bearer {}", token));
+        }
+        req
+    }
+
+    #[tokio::test]
+    async fn test_requires_admin() {
+        let f = fixture().await;
+        let handler = AdminHandler::new(f.state.clone(), list_users_handler);
+
+        let res = handler
+            .handle(request(Method::GET, "/admin/users", None))
+            .await;
+        assert_eq!(res.status, 401);
+
+        let res = handler
+            .handle(request(Method::GET, "/admin/users", Some(&f.user_token)))
+            .await;
+        assert_eq!(res.status, 403);
+
+        let res = handler
+            .handle(request(Method::GET, "/admin/users", Some(&f.admin_token)))
+            .await;
+        assert_eq!(res.status, 200);
+
+        let res = handler
+            .handle(request(Method::GET, "/admin/users", Some("service-token")))
+            .await;
+        assert_eq!(res.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_list_users_paginates_and_filters() {
+        let f = fixture().await;
+        let handler = AdminHandler::new(f.state.clone(), list_users_handler);
+
+        let mut req = request(Method::GET, "/admin/users", Some("service-token"));
+        req.query.insert("per_page".to_string(), "1".to_string());
+        let body = handler.handle(req).await.body.unwrap();
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["users"].as_array().unwrap().len(), 1);
+
+        let mut req = request(Method::GET, "/admin/users", Some("service-token"));
+        req.query.insert("email".to_string(), "jane".to_string());
+        let body = handler.handle(req).await.body.unwrap();
+        assert_eq!(body["total"], 1);
+        assert_eq!(body["users"][0]["id"], "user_1");
+
+        let mut req = request(Method::GET, "/admin/users", Some("service-token"));
+        req.query
+            .insert("per_page".to_string(), usize::MAX.to_string());
+        let body = handler.handle(req).await.body.unwrap();
+        assert_eq!(body["per_page"], crate::MAX_PER_PAGE);
+
+        let mut req = request(Method::GET, "/admin/users", Some("service-token"));
+        req.query.insert("page".to_string(), usize::MAX.to_string());
+        assert_eq!(handler.handle(req).await.status, 422);
+    }
+
+    #[tokio::test]
+    async fn test_ban_revokes_sessions() {
+        let f = fixture().await;
+        let handler = AdminHandler::new(f.state.clone(), ban_user_handler);
+
+        let mut req = request(
+            Method::POST,
+            "/admin/users/user_1/ban",
+            Some("service-token"),
+        );
+        req.params.insert("id".to_string(), "user_1".to_string());
+        req.body = Some(json!({ "reason": "spam" }));
+        let res = handler.handle(req).await;
+        assert_eq!(res.status, 200);
+        assert_eq!(res.body.unwrap()["banned"], true);
+
+        let user = f.adapter.get_user_by_id("user_1").await.unwrap().unwrap();
+        assert_eq!(user.get_extension::<bool>("banned"), Some(true));
+        assert!(
+            f.adapter
+                .get_sessions_by_user_id("user_1")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_banned_users_are_turned_away() {
+        use better_auth_core::context::{AuthContext, SignInCredentials};
+        use better_auth_core::traits::AuthPlugin;
+
+        let f = fixture().await;
+        let mut admin = f.adapter.get_user_by_id("admin_1").await.unwrap().unwrap();
+        admin.set_extension("banned", true);
+        f.adapter.update_user(&admin).await.unwrap();
+
+        // A session that outlived the ban no longer authenticates.
+        let handler = AdminHandler::new(f.state.clone(), list_users_handler);
+        let res = handler
+            .handle(request(Method::GET, "/admin/users", Some(&f.admin_token)))
+            .await;
+        assert_eq!(res.status, 401);
+
+        let dashboard = AdminDashboard::new(AdminConfig::new(), f.adapter.clone());
+        let ctx = AuthContext::new(f.adapter.clone());
+        let creds = SignInCredentials::new("admin@example.com", "password");
+        assert!(matches!(
+            dashboard.on_before_signin(&ctx, &creds).await,
+            Err(AuthError::Forbidden { .. })
+        ));
+        let creds = SignInCredentials::new("jane@example.com", "password");
+        assert!(dashboard.on_before_signin(&ctx, &creds).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_and_restore() {
+        let f = fixture().await;
+        let config = AdminConfig::new()
+            .api_token("service-token")
+            .user_deletion(UserDeletion::Soft);
+        let state = Arc::new(AdminState::new(config, f.adapter.clone()));
+
+        let mut req = request(Method::DELETE, "/admin/users/user_1", Some("service-token"));
+        req.params.insert("id".to_string(), "user_1".to_string());
+        let res = AdminHandler::new(state.clone(), delete_user_handler)
+            .handle(req)
+            .await;
+        assert_eq!(res.status, 200);
+        assert!(f.adapter.get_user_by_id("user_1").await.unwrap().is_none());
+        // The user's session no longer signs them in.
+        let res = AdminHandler::new(state.clone(), list_users_handler)
+            .handle(request(Method::GET, "/admin/users", Some(&f.user_token)))
+            .await;
+        assert_eq!(res.status, 401);
+
+        let restore = AdminHandler::new(state.clone(), restore_user_handler);
+        let mut req = request(
+            Method::POST,
+            "/admin/users/user_1/restore",
+            Some("service-token"),
+        );
+        req.params.insert("id".to_string(), "user_1".to_string());
+        let res = restore.handle(req.clone()).await;
+        assert_eq!(res.status, 200);
+        assert_eq!(res.body.unwrap()["id"], "user_1");
+        assert_eq!(restore.handle(req).await.status, 404);
+
+        state.api.delete_user("user_1").await.unwrap();
+        let purged = state
+            .api
+            .purge_deleted_users(chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(purged, 0);
+        assert_eq!(f.adapter.user_count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_round_trip() {
+        let f = fixture().await;
+        let impersonate = AdminHandler::new(f.state.clone(), impersonate_handler);
+
+        // The API token has no admin user to record.
+        let mut req = request(
+            Method::POST,
+            "/admin/users/user_1/impersonate",
+            Some("service-token"),
+        );
+        req.params.insert("id".to_string(), "user_1".to_string());
+        assert_eq!(impersonate.handle(req).await.status, 403);
+
+        let mut req = request(
+            Method::POST,
+            "/admin/users/user_1/impersonate",
+            Some(&f.admin_token),
+        );
+        req.params.insert("id".to_string(), "user_1".to_string());
+        let res = impersonate.handle(req).await;
+        assert_eq!(res.status, 201);
+        let session: Session = serde_json::from_value(res.body.unwrap()).unwrap();
+        assert_eq!(session.user_id, "user_1");
+        assert_eq!(session.impersonated_by().as_deref(), Some("admin_1"));
+        assert!(session.expires_at <= Utc::now() + chrono::Duration::minutes(15));
+        assert_eq!(
+            f.bus
+                .events_of_type("admin.impersonation_started")
+                .await
+                .len(),
+            1
+        );
+
+        // A regular session cannot stop impersonating.
+        let stop = StopImpersonationHandler::new(f.state.clone());
+        let res = stop
+            .handle(request(
+                Method::POST,
+                "/admin/stop-impersonation",
+                Some(&f.user_token),
+            ))
+            .await;
+        assert_eq!(res.status, 403);
+
+        let res = stop
+            .handle(request(
+                Method::POST,
+                "/admin/stop-impersonation",
+                Some(&session.token),
+            ))
+            .await;
+        assert_eq!(res.status, 200);
+        assert_eq!(res.body.unwrap()["user_id"], "admin_1");
+        assert!(
+            f.adapter
+                .get_session_by_id(&session.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            f.adapter
+                .get_session_by_token(&f.admin_token)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(
+            f.bus
+                .events_of_type("admin.impersonation_stopped")
+                .await
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_cannot_reach_admins() {
+        let f = fixture().await;
+        let mut other = User::new("admin_2".to_string(), "ops@example.com".to_string());
+        other.set_extension("role", "admin");
+        f.adapter.create_user(&other).await.unwrap();
+
+        let impersonate = AdminHandler::new(f.state.clone(), impersonate_handler);
+        let mut req = request(
+            Method::POST,
+            "/admin/users/admin_2/impersonate",
+            Some(&f.admin_token),
+        );
+        req.params.insert("id".to_string(), "admin_2".to_string());
+        assert_eq!(impersonate.handle(req).await.status, 403);
+        assert!(
+            f.bus
+                .events_of_type("admin.impersonation_started")
+                .await
+                .is_empty()
+        );
+
+        // An impersonation session is refused even when its user is an admin.
+        let mut session = Session::new("admin_2".to_string());
+        session.set_extension("impersonated_by", "admin_1");
+        let session = f.adapter.create_session(&session).await.unwrap();
+        let list = AdminHandler::new(f.state.clone(), list_users_handler);
+        let res = list
+            .handle(request(Method::GET, "/admin/users", Some(&session.token)))
+            .await;
+        assert_eq!(res.status, 403);
+
+        let mut req = request(
+            Method::POST,
+            "/admin/users/user_1/impersonate",
+            Some(&session.token),
+        );
+        req.params.insert("id".to_string(), "user_1".to_string());
+        assert_eq!(impersonate.handle(req).await.status, 403);
+    }
+
+    /// Records the users it was asked to clean up after.
+    #[derive(Default)]
+    struct Cleanup {
+        deleted: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl better_auth_core::traits::AuthPlugin for Cleanup {
+        fn id(&self) -> &'static str {
+            "cleanup"
+        }
+
+        fn name(&self) -> &'static str {
+            "Cleanup"
+        }
+
+        async fn on_before_user_delete(
+            &self,
+            _ctx: &better_auth_core::context::AuthContext,
+            user_id: &str,
+        ) -> AuthResult<()> {
+            self.deleted.lock().unwrap().push(user_id.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deletes_run_plugin_hooks() {
+        let f = fixture().await;
+        let cleanup = Arc::new(Cleanup::default());
+        let config = AdminConfig::new()
+            .api_token("service-token")
+            .event_bus(f.bus.clone())
+            .plugin(cleanup.clone());
+
+        let hard = AdminState::new(config.clone(), f.adapter.clone());
+        hard.api.delete_user("user_1").await.unwrap();
+        assert!(f.adapter.get_user_by_id("user_1").await.unwrap().is_none());
+
+        // Purging a soft-deleted user cleans up after it the same way.
+        let soft = AdminState::new(config.user_deletion(UserDeletion::Soft), f.adapter.clone());
+        soft.api.delete_user("admin_1").await.unwrap();
+        assert_eq!(cleanup.deleted.lock().unwrap().len(), 1);
+        let purged = soft
+            .api
+            .purge_deleted_users(chrono::Duration::zero())
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+        assert_eq!(f.adapter.user_count().await, 0);
+
+        assert_eq!(*cleanup.deleted.lock().unwrap(), ["user_1", "admin_1"]);
+        let deleted = f
+            .bus
+            .events_of_type(better_auth_core::auth_events::USER_DELETED)
+            .await;
+        assert_eq!(deleted.len(), 2);
+    }
+}
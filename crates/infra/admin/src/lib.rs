@@ -4,17 +4,26 @@
 //! Provides user management, session management, and system monitoring.
 
 mod api;
+mod handlers;
 mod stats;
 
 pub use api::*;
+pub use handlers::*;
 pub use stats::*;
 
+use async_trait::async_trait;
+use better_auth_core::context::{AuthContext, SignInCredentials};
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::events::EventBus;
+use better_auth_core::redact::Redact;
 use better_auth_core::router::{Method, Route, Router};
-use better_auth_core::traits::StorageAdapter;
+use better_auth_core::session::SessionResolver;
+use better_auth_core::traits::{AuthPlugin, StorageAdapter};
+use better_auth_core::types::UserDeletion;
 use std::sync::Arc;
 
 /// Admin dashboard configuration.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AdminConfig {
     /// Whether the dashboard is enabled.
     pub enabled: bool,
@@ -24,6 +33,15 @@ pub struct AdminConfig {
     pub api_token: Option<String>,
     /// Required role for admin access.
     pub required_role: String,
+    /// Lifetime of impersonation sessions.
+    pub impersonation_ttl: chrono::Duration,
+    /// Whether deleting a user removes it or only marks it deleted.
+    pub user_deletion: UserDeletion,
+    /// Event bus for admin audit events and `user.deleted`.
+    pub event_bus: Option<Arc<EventBus>>,
+    /// Plugins whose `on_before_user_delete` hooks run when a user is
+    /// hard-deleted.
+    pub plugins: Vec<Arc<dyn AuthPlugin>>,
 }
 
 impl Default for AdminConfig {
@@ -33,10 +51,32 @@ impl Default for AdminConfig {
             path: "/admin".to_string(),
             api_token: None,
             required_role: "admin".to_string(),
+            impersonation_ttl: chrono::Duration::hours(1),
+            user_deletion: UserDeletion::Hard,
+            event_bus: None,
+            plugins: Vec::new(),
         }
     }
 }
 
+impl std::fmt::Debug for AdminConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminConfig")
+            .field("enabled", &self.enabled)
+            .field("path", &self.path)
+            .field("api_token", &self.api_token.as_ref().map(Redact))
+            .field("required_role", &self.required_role)
+            .field("impersonation_ttl", &self.impersonation_ttl)
+            .field("user_deletion", &self.user_deletion)
+            .field("event_bus", &self.event_bus.is_some())
+            .field(
+                "plugins",
+                &self.plugins.iter().map(|p| p.id()).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
 impl AdminConfig {
     /// Creates a new admin config.
     pub fn new() -> Self {
@@ -61,6 +101,30 @@ impl AdminConfig {
         self
     }
 
+    /// Sets how long impersonation sessions last.
+    pub fn impersonation_ttl(mut self, ttl: chrono::Duration) -> Self {
+        self.impersonation_ttl = ttl;
+        self
+    }
+
+    /// Sets how users are deleted (default: [`UserDeletion::Hard`]).
+    pub fn user_deletion(mut self, mode: UserDeletion) -> Self {
+        self.user_deletion = mode;
+        self
+    }
+
+    /// Sets the event bus for admin audit events.
+    pub fn event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// Adds a plugin whose data is deleted along with a user.
+    pub fn plugin(mut self, plugin: Arc<dyn AuthPlugin>) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
     /// Disables the dashboard.
     pub fn disabled(mut self) -> Self {
         self.enabled = false;
@@ -69,6 +133,10 @@ impl AdminConfig {
 }
 
 /// Admin dashboard plugin.
+///
+/// As a plugin it refuses sign-ins by banned users. Banning revokes the
+/// user's sessions; to also turn away any created later, resolve sessions
+/// with [`AdminDashboard::reject_banned`].
 pub struct AdminDashboard {
     config: AdminConfig,
     adapter: Arc<dyn StorageAdapter>,
@@ -91,15 +159,94 @@ impl AdminDashboard {
             return;
         }
 
+        let state = Arc::new(AdminState::new(self.config.clone(), self.adapter.clone()));
+        let path = self.config.path.trim_end_matches('/');
+
         // User management routes
-        // router.get("/admin/users", list_users_handler);
-        // router.get("/admin/users/:id", get_user_handler);
-        // router.post("/admin/users/:id/ban", ban_user_handler);
-        // router.post("/admin/users/:id/impersonate", impersonate_handler);
+        router.route(
+            Route::new(
+                Method::GET,
+                format!("{}/users", path),
+                AdminHandler::new(state.clone(), list_users_handler),
+            )
+            .summary("List users")
+            .tag("admin")
+            .requires_auth(),
+        );
+        router.route(
+            Route::new(
+                Method::GET,
+                format!("{}/users/:id", path),
+                AdminHandler::new(state.clone(), get_user_handler),
+            )
+            .summary("Get user")
+            .tag("admin")
+            .requires_auth(),
+        );
+        router.route(
+            Route::new(
+                Method::POST,
+                format!("{}/users/:id/ban", path),
+                AdminHandler::new(state.clone(), ban_user_handler),
+            )
+            .summary("Ban user")
+            .tag("admin")
+            .requires_auth(),
+        );
+        router.route(
+            Route::new(
+                Method::DELETE,
+                format!("{}/users/:id", path),
+                AdminHandler::new(state.clone(), delete_user_handler),
+            )
+            .summary("Delete user")
+            .tag("admin")
+            .requires_auth(),
+        );
+        router.route(
+            Route::new(
+                Method::POST,
+                format!("{}/users/:id/restore", path),
+                AdminHandler::new(state.clone(), restore_user_handler),
+            )
+            .summary("Restore a soft-deleted user")
+            .tag("admin")
+            .requires_auth(),
+        );
+        router.route(
+            Route::new(
+                Method::POST,
+                format!("{}/users/:id/impersonate", path),
+                AdminHandler::new(state.clone(), impersonate_handler),
+            )
+            .summary("Impersonate user")
+            .tag("admin")
+            .requires_auth(),
+        );
+        // Called from the impersonation session, so not gated on the admin role.
+        router.route(
+            Route::new(
+                Method::POST,
+                format!("{}/stop-impersonation", path),
+                StopImpersonationHandler::new(state.clone()),
+            )
+            .summary("Stop impersonating")
+            .tag("admin")
+            .requires_auth(),
+        );
 
         // Session management routes
         // router.get("/admin/users/:id/sessions", list_sessions_handler);
-        // router.delete("/admin/sessions/:id", delete_session_handler);
+        router.route(
+            Route::new(
+                Method::DELETE,
+                format!("{}/sessions/:id", path),
+                AdminHandler::new(state, delete_session_handler),
+            )
+            .summary("Delete session")
+            .tag("admin")
+            .requires_auth(),
+        );
 
         // Stats routes
         // router.get("/admin/stats", get_stats_handler);
@@ -109,6 +256,38 @@ impl AdminDashboard {
     pub fn adapter(&self) -> &dyn StorageAdapter {
         self.adapter.as_ref()
     }
+
+    /// Makes `resolver` skip the sessions of banned users.
+    pub fn reject_banned(resolver: SessionResolver) -> SessionResolver {
+        resolver.reject_users(|user| user.is_banned())
+    }
+}
+
+#[async_trait]
+impl AuthPlugin for AdminDashboard {
+    fn id(&self) -> &'static str {
+        "admin"
+    }
+
+    fn name(&self) -> &'static str {
+        "Admin"
+    }
+
+    fn register_routes(&self, router: &mut Router) {
+        AdminDashboard::register_routes(self, router);
+    }
+
+    async fn on_before_signin(
+        &self,
+        ctx: &AuthContext,
+        creds: &SignInCredentials,
+    ) -> AuthResult<()> {
+        let user = ctx.db.get_user_by_email(&creds.email).await?;
+        if user.is_some_and(|user| user.is_banned()) {
+            return Err(AuthError::forbidden("User is banned"));
+        }
+        Ok(())
+    }
 }
 
 /// Plugin UI manifest for extending the dashboard.
@@ -3,7 +3,11 @@
 //! This crate provides automatic documentation generation for Better Auth,
 //! including OpenAPI specification generation and personalized documentation.
 
-use better_auth_core::router::{Method, Route};
+mod serve;
+
+pub use serve::{OpenApiHandler, SwaggerUiHandler};
+
+use better_auth_core::router::{Method, Route, Router};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -40,20 +44,33 @@ pub struct OpenApiPath {
     pub response: Option<String>,
     /// Whether authentication is required.
     pub requires_auth: bool,
+    /// Names of the path parameters, in order.
+    #[serde(default)]
+    pub parameters: Vec<String>,
 }
 
 impl OpenApiPath {
     /// Creates a new OpenAPI path.
+    ///
+    /// Path parameters are taken from `{name}` segments.
     pub fn new(path: impl Into<String>, method: Method) -> Self {
         let path_str = path.into();
         let method_str = method.to_string();
+        let parameters = path_str
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+            .map(String::from)
+            .collect();
         Self {
             path: path_str.clone(),
             method: method_str.clone(),
             operation_id: format!(
                 "{}_{}",
                 method_str.to_lowercase(),
-                path_str.replace('/', "_").trim_matches('_')
+                path_str
+                    .replace(['{', '}'], "")
+                    .replace('/', "_")
+                    .trim_matches('_')
             ),
             summary: None,
             description: None,
@@ -61,9 +78,34 @@ impl OpenApiPath {
             request_body: None,
             response: None,
             requires_auth: false,
+            parameters,
         }
     }
 
+    /// Creates a path from a registered route and its metadata.
+    ///
+    /// `:name` segments become OpenAPI `{name}` parameters.
+    pub fn from_route(route: &Route) -> Self {
+        let path = route
+            .path
+            .split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(name) => format!("{{{}}}", name),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let metadata = &route.metadata;
+        let mut openapi_path = Self::new(path, route.method);
+        openapi_path.summary = metadata.summary.clone();
+        openapi_path.description = metadata.description.clone();
+        openapi_path.tags = metadata.tags.clone();
+        openapi_path.requires_auth = metadata.requires_auth;
+        openapi_path.request_body = metadata.request_schema.clone();
+        openapi_path.response = metadata.response_schema.clone();
+        openapi_path
+    }
+
     /// Sets the summary.
     pub fn summary(mut self, summary: impl Into<String>) -> Self {
         self.summary = Some(summary.into());
@@ -229,6 +271,10 @@ pub struct OpenApiGenerator {
     pub description: Option<String>,
     /// Base path.
     pub base_path: String,
+    /// Route serving the JSON document, relative to the router's base path.
+    pub spec_path: String,
+    /// Route serving Swagger UI, if any, relative to the router's base path.
+    pub ui_path: Option<String>,
     /// Collected paths.
     paths: Vec<OpenApiPath>,
     /// Collected schemas.
@@ -243,6 +289,8 @@ impl OpenApiGenerator {
             version: version.into(),
             description: None,
             base_path: "/api/auth".to_string(),
+            spec_path: "/openapi.json".to_string(),
+            ui_path: Some("/docs".to_string()),
             paths: Vec::new(),
             schemas: Vec::new(),
         }
@@ -260,12 +308,64 @@ impl OpenApiGenerator {
         self
     }
 
+    /// Sets the route that serves the JSON document.
+    pub fn spec_path(mut self, path: impl Into<String>) -> Self {
+        self.spec_path = path.into();
+        self
+    }
+
+    /// Sets the route that serves Swagger UI, or `None` to not serve it.
+    pub fn ui_path(mut self, path: Option<String>) -> Self {
+        self.ui_path = path;
+        self
+    }
+
     /// Adds documentation from a provider.
     pub fn add_docs(&mut self, provider: &dyn AuthDocs) {
         self.paths.extend(provider.openapi_paths());
         self.schemas.extend(provider.openapi_schemas());
     }
 
+    /// Adds every route mounted on `router`, so the spec matches what is
+    /// actually served.
+    ///
+    /// Route paths are relative to the router's base path; the generator's
+    /// [`base_path`](Self::base_path) is prepended to them. When a path and
+    /// method are added more than once, the last addition wins.
+    pub fn add_router(&mut self, router: &Router) {
+        self.paths
+            .extend(router.routes().map(OpenApiPath::from_route));
+    }
+
+    /// Mounts the generated document at `spec_path` and Swagger UI at
+    /// `ui_path` on `router`.
+    ///
+    /// The document is generated once, now, so call this after adding
+    /// every provider and router to document.
+    pub fn register_routes(&self, router: &mut Router) {
+        router.route(
+            Route::new(
+                Method::GET,
+                self.spec_path.as_str(),
+                OpenApiHandler::new(self.generate()),
+            )
+            .summary("OpenAPI document")
+            .tag("docs"),
+        );
+        if let Some(ui_path) = &self.ui_path {
+            let spec_url = format!("{}{}", router.base_path, self.spec_path);
+            router.route(
+                Route::new(
+                    Method::GET,
+                    ui_path.as_str(),
+                    SwaggerUiHandler::new(&self.title, &spec_url),
+                )
+                .summary("API reference")
+                .tag("docs"),
+            );
+        }
+    }
+
     /// Generates the OpenAPI specification as JSON.
     pub fn generate(&self) -> serde_json::Value {
         let mut paths_map: HashMap<String, HashMap<String, serde_json::Value>> = HashMap::new();
@@ -288,6 +388,33 @@ impl OpenApiGenerator {
             if path.requires_auth {
                 operation["security"] = serde_json::json!([{"bearerAuth": []}]);
             }
+            if let Some(schema) = &path.request_body {
+                operation["requestBody"] = serde_json::json!({
+                    "required": true,
+                    "content": { "application/json": { "schema": schema_ref(schema) } },
+                });
+            }
+            let mut success = serde_json::json!({ "description": "Successful response" });
+            if let Some(schema) = &path.response {
+                success["content"] =
+                    serde_json::json!({ "application/json": { "schema": schema_ref(schema) } });
+            }
+            operation["responses"] = serde_json::json!({ "200": success });
+            if !path.parameters.is_empty() {
+                let parameters: Vec<_> = path
+                    .parameters
+                    .iter()
+                    .map(|name| {
+                        serde_json::json!({
+                            "name": name,
+                            "in": "path",
+                            "required": true,
+                            "schema": { "type": "string" },
+                        })
+                    })
+                    .collect();
+                operation["parameters"] = serde_json::json!(parameters);
+            }
 
             paths_map
                 .entry(full_path)
@@ -350,6 +477,11 @@ impl OpenApiGenerator {
     }
 }
 
+/// Returns a reference to a schema under `#/components/schemas`.
+fn schema_ref(name: &str) -> serde_json::Value {
+    serde_json::json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
 /// Core auth documentation.
 pub struct CoreAuthDocs;
 
@@ -379,6 +511,16 @@ impl AuthDocs for CoreAuthDocs {
                 .tag("Session")
                 .requires_auth()
                 .response("Session"),
+            OpenApiPath::new("/sessions", Method::GET)
+                .summary("List active sessions")
+                .description("Lists the user's active sessions, marking the current one")
+                .tag("Session")
+                .requires_auth(),
+            OpenApiPath::new("/sessions/{id}", Method::DELETE)
+                .summary("Revoke a session")
+                .description("Signs another of the user's sessions out")
+                .tag("Session")
+                .requires_auth(),
             OpenApiPath::new("/user", Method::GET)
                 .summary("Get current user")
                 .description("Returns the authenticated user's profile")
@@ -461,6 +603,108 @@ mod tests {
         assert_eq!(spec["info"]["title"], "Better Auth API");
     }
 
+    #[test]
+    fn test_add_router() {
+        use better_auth_core::router::{Request, RequestHandler, Response};
+
+        struct Noop;
+
+        #[async_trait::async_trait]
+        impl RequestHandler for Noop {
+            async fn handle(&self, _req: Request) -> Response {
+                Response::ok()
+            }
+        }
+
+        let mut router = Router::new("/api/auth");
+        router.route(
+            Route::new(Method::DELETE, "/orgs/:org_id/members/:id", Noop)
+                .summary("Remove a member")
+                .tag("organization")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::POST, "/sign-in/email", Noop)
+                .request_schema("SignInRequest")
+                .response_schema("Session"),
+        );
+        router.get("/ok", Noop);
+
+        let mut generator = OpenApiGenerator::new("Better Auth API", "1.0.0");
+        generator.add_router(&router);
+        let spec = generator.generate();
+
+        let operation = &spec["paths"]["/api/auth/orgs/{org_id}/members/{id}"]["delete"];
+        assert_eq!(operation["operationId"], "delete_orgs_org_id_members_id");
+        assert_eq!(operation["summary"], "Remove a member");
+        assert_eq!(operation["tags"], serde_json::json!(["organization"]));
+        assert_eq!(
+            operation["security"],
+            serde_json::json!([{ "bearerAuth": [] }])
+        );
+        let names: Vec<_> = operation["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| (p["name"].as_str().unwrap(), p["in"].as_str().unwrap()))
+            .collect();
+        assert_eq!(names, [("org_id", "path"), ("id", "path")]);
+
+        let operation = &spec["paths"]["/api/auth/sign-in/email"]["post"];
+        assert_eq!(
+            operation["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/SignInRequest"
+        );
+        assert_eq!(
+            operation["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/Session"
+        );
+
+        let operation = &spec["paths"]["/api/auth/ok"]["get"];
+        assert!(operation.get("security").is_none());
+        assert!(operation.get("parameters").is_none());
+        assert!(operation.get("requestBody").is_none());
+        assert!(operation["responses"]["200"].get("content").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_register_routes() {
+        use better_auth_core::router::Request;
+
+        let mut generator = OpenApiGenerator::new("Acme <Auth>", "2.1.0").base_path("/auth");
+        generator.add_docs(&CoreAuthDocs);
+        let mut router = Router::new("/auth");
+        generator.register_routes(&mut router);
+
+        let route = router.routes().find(|r| r.path == "/openapi.json").unwrap();
+        let response = route
+            .handler
+            .handle(Request::new(Method::GET, "/openapi.json"))
+            .await;
+        assert_eq!(response.status, 200);
+        let spec = response.body.unwrap();
+        assert_eq!(spec["info"]["title"], "Acme <Auth>");
+        assert_eq!(spec["info"]["version"], "2.1.0");
+        assert!(spec["paths"].get("/auth/session").is_some());
+
+        let route = router.routes().find(|r| r.path == "/docs").unwrap();
+        let response = route
+            .handler
+            .handle(Request::new(Method::GET, "/docs"))
+            .await;
+        assert_eq!(response.headers["content-type"], "text/html; charset=utf-8");
+        assert!(response.body.is_none());
+        let html = response.raw_body.unwrap();
+        assert!(html.contains("<title>Acme &lt;Auth&gt;</title>"));
+        assert!(html.contains(r#"url: "/auth/openapi.json""#));
+        assert!(html.contains("swagger-ui-dist@5.17.14/swagger-ui-bundle.js"));
+        assert_eq!(html.matches(r#"integrity="sha384-"#).count(), 2);
+
+        let mut router = Router::new("/auth");
+        generator.ui_path(None).register_routes(&mut router);
+        assert_eq!(router.len(), 1);
+    }
+
     #[test]
     fn test_schema_property() {
         let prop = SchemaProperty::string()
@@ -0,0 +1,94 @@
+//! Route handlers that serve the generated OpenAPI document.
+
+use async_trait::async_trait;
+use better_auth_core::router::{Request, RequestHandler, Response};
+use std::sync::Arc;
+
+/// Handler that serves an OpenAPI document as JSON.
+pub struct OpenApiHandler {
+    spec: Arc<serde_json::Value>,
+}
+
+impl OpenApiHandler {
+    /// Creates a handler serving `spec`.
+    pub fn new(spec: serde_json::Value) -> Self {
+        Self {
+            spec: Arc::new(spec),
+        }
+    }
+}
+
+#[async_trait]
+impl RequestHandler for OpenApiHandler {
+    async fn handle(&self, _req: Request) -> Response {
+        Response::ok().json(self.spec.as_ref())
+    }
+}
+
+/// Version of the `swagger-ui-dist` package the Swagger UI page loads.
+const SWAGGER_UI_VERSION: &str = "5.17.14";
+
+/// SRI hash of `swagger-ui.css` at [`SWAGGER_UI_VERSION`].
+const SWAGGER_UI_CSS_INTEGRITY: &str =
+    "sha384-wxLW6kwyHktdDGr6Pv1zgm/VGJh99lfUbzSn6HNHBENZlCN7W602k9VkGdxuFvPn";
+
+/// SRI hash of `swagger-ui-bundle.js` at [`SWAGGER_UI_VERSION`].
+const SWAGGER_UI_BUNDLE_INTEGRITY: &str =
+    "sha384-wmyclcVGX/WhUkdkATwhaK1X1JtiNrr2EoYJ+diV3vj4v6OC5yCeSu+yW13SYJep";
+
+/// Handler that serves a Swagger UI page for an OpenAPI document.
+///
+/// The page loads a pinned Swagger UI release from the unpkg CDN, checked
+/// against its Subresource Integrity hashes, and points it at `spec_url`.
+pub struct SwaggerUiHandler {
+    html: String,
+}
+
+impl SwaggerUiHandler {
+    /// Creates a handler for the document served at `spec_url`.
+    pub fn new(title: &str, spec_url: &str) -> Self {
+        Self {
+            html: format!(
+                r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <title>{title}</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@{SWAGGER_UI_VERSION}/swagger-ui.css"
+        integrity="{SWAGGER_UI_CSS_INTEGRITY}" crossorigin="anonymous">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@{SWAGGER_UI_VERSION}/swagger-ui-bundle.js"
+          integrity="{SWAGGER_UI_BUNDLE_INTEGRITY}" crossorigin="anonymous"></script>
+  <script>
+    window.ui = SwaggerUIBundle({{ url: {url}, dom_id: "#swagger-ui" }});
+  </script>
+</body>
+</html>
+"##,
+                title = escape_html(title),
+                // A JSON string is a valid JavaScript string literal.
+                url = serde_json::Value::from(spec_url)
+                    .to_string()
+                    .replace('<', "\\u003c"),
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl RequestHandler for SwaggerUiHandler {
+    async fn handle(&self, _req: Request) -> Response {
+        Response::ok().html(self.html.clone())
+    }
+}
+
+/// Escapes text for use in HTML content.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -16,6 +16,10 @@ pub struct ServerConfig {
     pub enable_admin_api: bool,
     /// Log level.
     pub log_level: String,
+    /// Domain the buckets' subdomains are under, e.g. `auth.example.com`
+    /// for `acme.auth.example.com`. Without it, the first label of any host
+    /// with three or more labels is taken as the subdomain.
+    pub base_domain: Option<String>,
 }
 
 impl Default for ServerConfig {
@@ -26,6 +30,7 @@ impl Default for ServerConfig {
             admin_secret: None,
             enable_admin_api: true,
             log_level: "info".to_string(),
+            base_domain: None,
         }
     }
 }
@@ -116,7 +121,9 @@ pub struct RoleConfig {
 }
 
 /// Loads configuration from a TOML file.
-pub fn load_config(path: &str) -> Result<(ServerConfig, HashMap<String, BucketConfig>), ConfigError> {
+pub fn load_config(
+    path: &str,
+) -> Result<(ServerConfig, HashMap<String, BucketConfig>), ConfigError> {
     let content = std::fs::read_to_string(path).map_err(|e| ConfigError::IoError(e.to_string()))?;
 
     let config: toml::Value =
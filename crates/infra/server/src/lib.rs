@@ -2,14 +2,39 @@
 //!
 //! Standalone authentication server ("Auth Buckets") that can be deployed
 //! as a service for applications in any language.
+//!
+//! Each request is dispatched to a bucket chosen by its `X-Auth-Bucket`
+//! header, then by the subdomain of its `Host`. Hosts without a subdomain
+//! go to the bucket named `default`. The bucket then routes it to one of the routes its own
+//! plugins registered, so each tenant can enable a different set of plugins.
+//!
+//! Schemas are per bucket too: [`AuthServer::migrate`] migrates each
+//! bucket's adapter with the core models plus the models that bucket's
+//! plugins define. Run it before [`AuthServer::run`].
 
 mod config;
 
-pub use config::{BucketConfig, ServerConfig};
+pub use config::{BucketConfig, ConfigError, ServerConfig, load_config};
 
-use better_auth_core::traits::StorageAdapter;
+use axum::body::Bytes;
+use axum::extract::{ConnectInfo, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderMap, Method as HttpMethod, Uri};
+use better_auth_axum::{to_auth_request, to_axum_response};
+use better_auth_core::error::AuthResult;
+use better_auth_core::export::register_export_route;
+use better_auth_core::router::{Request, Response, Router};
+use better_auth_core::schema::{MigrationOp, SchemaBuilder, SchemaDefinition};
+use better_auth_core::session::{SessionResolver, register_session_routes};
+use better_auth_core::traits::{AuthPlugin, StorageAdapter};
 use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Header that selects a bucket explicitly.
+pub const BUCKET_HEADER: &str = "x-auth-bucket";
 
 /// A tenant/bucket in the auth server.
 pub struct AuthBucket {
@@ -19,16 +44,89 @@ pub struct AuthBucket {
     pub config: BucketConfig,
     /// Storage adapter for this bucket.
     pub adapter: Arc<dyn StorageAdapter>,
+    /// Plugins enabled for this bucket.
+    pub plugins: Vec<Arc<dyn AuthPlugin>>,
+    /// Auth routes mounted under `config.base_path`.
+    router: Router,
 }
 
 impl AuthBucket {
     /// Creates a new auth bucket.
-    pub fn new(id: impl Into<String>, config: BucketConfig, adapter: Arc<dyn StorageAdapter>) -> Self {
+    pub fn new(
+        id: impl Into<String>,
+        config: BucketConfig,
+        adapter: Arc<dyn StorageAdapter>,
+    ) -> Self {
+        let router = Router::new(config.base_path.clone());
         Self {
             id: id.into(),
             config,
             adapter,
+            plugins: Vec::new(),
+            router,
+        }
+    }
+
+    /// Enables a plugin for this bucket.
+    ///
+    /// Its routes are mounted when the bucket is registered with
+    /// [`AuthServer::register_bucket`].
+    pub fn plugin(mut self, plugin: Arc<dyn AuthPlugin>) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
+    /// Gets the bucket's routes, e.g. to mount handlers directly.
+    pub fn router_mut(&mut self) -> &mut Router {
+        &mut self.router
+    }
+
+    /// Mounts the routes of every plugin, ahead of any mounted directly,
+    /// along with the `/sessions` routes and the `/user/export` data export.
+    fn mount_plugins(&mut self) {
+        let direct =
+            std::mem::replace(&mut self.router, Router::new(self.config.base_path.clone()));
+        for plugin in &self.plugins {
+            plugin.register_routes(&mut self.router);
+        }
+        let resolver = SessionResolver::new(self.adapter.clone());
+        register_session_routes(&mut self.router, resolver.clone());
+        let schema = self.schema();
+        register_export_route(&mut self.router, resolver, self.plugins.clone(), schema);
+        self.router.merge(direct);
+    }
+
+    /// Returns the schema for this bucket: the core models plus the models
+    /// its plugins define.
+    pub fn schema(&self) -> SchemaDefinition {
+        let mut builder = SchemaBuilder::with_core();
+        for plugin in &self.plugins {
+            plugin.define_schema(&mut builder);
         }
+        builder.build()
+    }
+
+    /// Migrates this bucket's adapter to its [`schema`](Self::schema).
+    ///
+    /// Returns the operations that were run, or with `dry_run`, the ones
+    /// that would be.
+    pub async fn migrate(&self, dry_run: bool) -> AuthResult<Vec<MigrationOp>> {
+        self.adapter.migrate(&self.schema().models, dry_run).await
+    }
+
+    /// Routes a request whose path includes the bucket's base path.
+    pub async fn handle(&self, mut req: Request) -> Response {
+        let base_path = self.config.base_path.trim_end_matches('/');
+        let route = req
+            .path
+            .strip_prefix(base_path)
+            .filter(|path| path.is_empty() || path.starts_with('/'))
+            .and_then(|path| self.router.match_route(req.method, path));
+        let Some((route, params)) = route else {
+            return error_response(404, "NOT_FOUND", "No route matches this request");
+        };
+        req.params = params;
+        route.handler.handle(req).await
     }
 }
 
@@ -49,8 +147,9 @@ impl AuthServer {
         }
     }
 
-    /// Registers a bucket.
-    pub fn register_bucket(&mut self, bucket: AuthBucket) {
+    /// Registers a bucket, mounting its plugins' routes.
+    pub fn register_bucket(&mut self, mut bucket: AuthBucket) {
+        bucket.mount_plugins();
         self.buckets.insert(bucket.id.clone(), bucket);
     }
 
@@ -60,6 +159,9 @@ impl AuthServer {
     }
 
     /// Gets a bucket by subdomain or header.
+    ///
+    /// A bucket named in the header, or by the host's subdomain, must exist.
+    /// Hosts without a subdomain get the `default` bucket.
     pub fn resolve_bucket(&self, host: Option<&str>, header: Option<&str>) -> Option<&AuthBucket> {
         // Try header first
         if let Some(bucket_id) = header {
@@ -67,31 +169,107 @@ impl AuthServer {
         }
 
         // Try subdomain
-        if let Some(host) = host {
-            // Extract subdomain from host (e.g., "myapp.auth.example.com" -> "myapp")
-            if let Some(subdomain) = host.split('.').next() {
-                return self.buckets.get(subdomain);
-            }
+        if let Some(subdomain) = host.and_then(|host| self.subdomain(host)) {
+            return self.buckets.get(subdomain);
         }
 
         // Return default bucket if exists
         self.buckets.get("default")
     }
 
+    /// Extracts the subdomain from a `Host` header value, e.g. `acme` from
+    /// `acme.auth.example.com:8000`.
+    ///
+    /// With [`ServerConfig::base_domain`] set, the subdomain is whatever
+    /// precedes it. Otherwise it is the first label of a host with three or
+    /// more labels, so `localhost` and `example.com` have none.
+    fn subdomain<'a>(&self, host: &'a str) -> Option<&'a str> {
+        let hostname = host.split(':').next().unwrap_or(host);
+        if hostname.parse::<IpAddr>().is_ok() {
+            return None;
+        }
+        match &self.config.base_domain {
+            Some(base_domain) => hostname
+                .strip_suffix(base_domain.as_str())?
+                .strip_suffix('.')
+                .filter(|subdomain| !subdomain.is_empty()),
+            None => {
+                let (subdomain, rest) = hostname.split_once('.')?;
+                rest.contains('.').then_some(subdomain)
+            }
+        }
+    }
+
     /// Returns all bucket IDs.
     pub fn bucket_ids(&self) -> Vec<&str> {
         self.buckets.keys().map(|s| s.as_str()).collect()
     }
 
-    /// Starts the server.
-    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
-        tracing::info!("Starting Better Auth Server on port {}", self.config.port);
+    /// Migrates every bucket's adapter to that bucket's schema.
+    ///
+    /// Buckets are migrated one at a time, in ID order, stopping at the
+    /// first failure.
+    pub async fn migrate(&self) -> AuthResult<()> {
+        let mut ids = self.bucket_ids();
+        ids.sort_unstable();
+        for id in ids {
+            tracing::info!("Migrating bucket {}", id);
+            match self.buckets[id].migrate(false).await {
+                Ok(ops) => tracing::info!("Bucket {}: {} schema changes", id, ops.len()),
+                Err(err) => {
+                    tracing::error!("Migrating bucket {} failed: {}", id, err);
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Dispatches a request to its bucket's routes.
+    pub async fn handle(&self, req: Request) -> Response {
+        let bucket = self.resolve_bucket(
+            req.header("host").map(String::as_str),
+            req.header(BUCKET_HEADER).map(String::as_str),
+        );
+        match bucket {
+            Some(bucket) => bucket.handle(req).await,
+            None => error_response(
+                404,
+                "BUCKET_NOT_FOUND",
+                "No auth bucket matches this request",
+            ),
+        }
+    }
+
+    /// Starts the server on the configured host and port, running until
+    /// SIGTERM or Ctrl-C.
+    pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        let listener = TcpListener::bind((self.config.host.as_str(), self.config.port)).await?;
+        self.serve(listener, shutdown_signal()).await?;
+        Ok(())
+    }
+
+    /// Serves requests from `listener` until `shutdown` completes, then
+    /// waits for in-flight requests to finish.
+    pub async fn serve(
+        self,
+        listener: TcpListener,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> std::io::Result<()> {
+        tracing::info!("Starting Better Auth Server on {}", listener.local_addr()?);
         tracing::info!("Registered buckets: {:?}", self.bucket_ids());
 
-        // In a real implementation, this would start an HTTP server
-        // For now, just log that we're ready
-        tracing::info!("Server ready");
+        let app = axum::Router::new()
+            .fallback(serve_request)
+            .with_state(Arc::new(self));
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown)
+        .await?;
 
+        tracing::info!("Server stopped");
         Ok(())
     }
 }
@@ -101,3 +279,324 @@ impl Default for AuthServer {
         Self::new(ServerConfig::default())
     }
 }
+
+/// Converts an HTTP request, dispatches it, and converts the response back.
+async fn serve_request(
+    State(server): State<Arc<AuthServer>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    method: HttpMethod,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> axum::response::Response {
+    // Form posts (e.g. Apple's `form_post` callback) are passed on as the
+    // raw form string, for handlers to decode.
+    let is_form = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+    let body = if body.is_empty() {
+        None
+    } else if is_form {
+        match String::from_utf8(body.to_vec()) {
+            Ok(form) => Some(serde_json::Value::String(form)),
+            Err(_) => {
+                let response = error_response(400, "INVALID_REQUEST", "Request body must be UTF-8");
+                return to_axum_response(response);
+            }
+        }
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(body) => Some(body),
+            Err(_) => {
+                let response = error_response(
+                    400,
+                    "INVALID_REQUEST",
+                    "Request body must be JSON or form-encoded",
+                );
+                return to_axum_response(response);
+            }
+        }
+    };
+    let mut req = to_auth_request(method, &uri, &headers, body);
+    req.ip = Some(addr.ip().to_string());
+    to_axum_response(server.handle(req).await)
+}
+
+/// Completes on SIGTERM or Ctrl-C.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(err) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutdown signal received, finishing in-flight requests");
+}
+
+fn error_response(status: u16, code: &str, message: &str) -> Response {
+    Response::new(status).json(serde_json::json!({
+        "error": { "code": code, "message": message }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use better_auth_adapter_memory::MemoryAdapter;
+    use better_auth_core::router::{Method, RequestHandler};
+    use better_auth_core::schema::{Field, ModelDefinition};
+
+    /// Echoes the bucket it is mounted on and the request it received.
+    struct Echo(&'static str);
+
+    #[async_trait::async_trait]
+    impl RequestHandler for Echo {
+        async fn handle(&self, req: Request) -> Response {
+            Response::ok().json(serde_json::json!({
+                "bucket": self.0,
+                "params": req.params,
+                "body": req.body,
+                "ip": req.ip,
+            }))
+        }
+    }
+
+    fn bucket(id: &'static str) -> AuthBucket {
+        let mut bucket =
+            AuthBucket::new(id, BucketConfig::default(), Arc::new(MemoryAdapter::new()));
+        bucket.router_mut().get("/session", Echo(id));
+        bucket.router_mut().post("/users/:id", Echo(id));
+        bucket
+    }
+
+    fn server() -> AuthServer {
+        let mut server = AuthServer::default();
+        server.register_bucket(bucket("default"));
+        server.register_bucket(bucket("acme"));
+        server
+    }
+
+    async fn dispatch(server: &AuthServer, headers: &[(&str, &str)], path: &str) -> Response {
+        let mut req = Request::new(Method::GET, path);
+        for (name, value) in headers {
+            req.headers.insert(name.to_string(), value.to_string());
+        }
+        server.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_dispatches_to_resolved_bucket() {
+        let server = server();
+        let bucket_of = |response: Response| response.body.unwrap()["bucket"].clone();
+
+        let response = dispatch(&server, &[], "/api/auth/session").await;
+        assert_eq!(bucket_of(response), "default");
+        let response = dispatch(
+            &server,
+            &[("host", "acme.auth.example.com:8000")],
+            "/api/auth/session",
+        )
+        .await;
+        assert_eq!(bucket_of(response), "acme");
+        let response = dispatch(&server, &[("host", "localhost:8000")], "/api/auth/session").await;
+        assert_eq!(bucket_of(response), "default");
+        let response = dispatch(&server, &[("host", "example.com")], "/api/auth/session").await;
+        assert_eq!(bucket_of(response), "default");
+        let response = dispatch(&server, &[("host", "127.0.0.1:8000")], "/api/auth/session").await;
+        assert_eq!(bucket_of(response), "default");
+        let response = dispatch(&server, &[("x-auth-bucket", "acme")], "/api/auth/session").await;
+        assert_eq!(bucket_of(response), "acme");
+
+        let response = dispatch(
+            &server,
+            &[("x-auth-bucket", "missing")],
+            "/api/auth/session",
+        )
+        .await;
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body.unwrap()["error"]["code"], "BUCKET_NOT_FOUND");
+
+        // Unknown subdomains don't fall back to the default bucket.
+        let response = dispatch(
+            &server,
+            &[("host", "missing.auth.example.com")],
+            "/api/auth/session",
+        )
+        .await;
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body.unwrap()["error"]["code"], "BUCKET_NOT_FOUND");
+
+        for path in ["/session", "/api/authsession", "/api/auth/nothing"] {
+            let response = dispatch(&server, &[], path).await;
+            assert_eq!(response.status, 404, "{}", path);
+            assert_eq!(response.body.unwrap()["error"]["code"], "NOT_FOUND");
+        }
+
+        // The session routes are mounted on every bucket.
+        let response = dispatch(&server, &[], "/api/auth/sessions").await;
+        assert_eq!(response.status, 401);
+    }
+
+    #[tokio::test]
+    async fn test_subdomains_under_base_domain() {
+        let mut server = AuthServer::new(ServerConfig {
+            base_domain: Some("example.com".to_string()),
+            ..ServerConfig::default()
+        });
+        server.register_bucket(bucket("default"));
+        server.register_bucket(bucket("acme"));
+
+        let status = |host: &'static str| {
+            let server = &server;
+            async move {
+                let response = dispatch(server, &[("host", host)], "/api/auth/session").await;
+                (response.status, response.body.unwrap()["bucket"].clone())
+            }
+        };
+        assert_eq!(status("acme.example.com").await, (200, "acme".into()));
+        assert_eq!(status("example.com:8000").await, (200, "default".into()));
+        assert_eq!(status("auth.other.org").await, (200, "default".into()));
+        assert_eq!(status("missing.example.com").await.0, 404);
+    }
+
+    /// A plugin with one route and one table, both named after it.
+    struct TablePlugin(&'static str);
+
+    impl AuthPlugin for TablePlugin {
+        fn id(&self) -> &'static str {
+            self.0
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn define_schema(&self, builder: &mut SchemaBuilder) {
+            builder.add_model_mut(ModelDefinition::new(self.0).field(Field::primary_key("id")));
+        }
+
+        fn register_routes(&self, router: &mut Router) {
+            router.get(&format!("/{}", self.0), Echo(self.0));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_buckets_have_their_own_plugins_and_schema() {
+        let acme_adapter = Arc::new(MemoryAdapter::new());
+        let globex_adapter = Arc::new(MemoryAdapter::new());
+        let mut server = AuthServer::default();
+        server.register_bucket(
+            AuthBucket::new("acme", BucketConfig::default(), acme_adapter.clone())
+                .plugin(Arc::new(TablePlugin("oauth")))
+                .plugin(Arc::new(TablePlugin("passkey"))),
+        );
+        server.register_bucket(
+            AuthBucket::new("globex", BucketConfig::default(), globex_adapter.clone())
+                .plugin(Arc::new(TablePlugin("otp"))),
+        );
+
+        let status = |bucket: &'static str, path: &'static str| {
+            let server = &server;
+            async move {
+                dispatch(server, &[("x-auth-bucket", bucket)], path)
+                    .await
+                    .status
+            }
+        };
+        assert_eq!(status("acme", "/api/auth/oauth").await, 200);
+        assert_eq!(status("acme", "/api/auth/passkey").await, 200);
+        assert_eq!(status("acme", "/api/auth/otp").await, 404);
+        assert_eq!(status("globex", "/api/auth/otp").await, 200);
+        assert_eq!(status("globex", "/api/auth/oauth").await, 404);
+
+        let acme = [("x-auth-bucket", "acme")];
+        let export = dispatch(&server, &acme, "/api/auth/user/export").await;
+        assert_eq!(export.body.unwrap()["error"]["code"], "SESSION_NOT_FOUND");
+
+        server.migrate().await.unwrap();
+        for table in ["user", "session", "oauth", "passkey"] {
+            assert!(acme_adapter.table_exists(table).await.unwrap(), "{}", table);
+        }
+        assert!(!acme_adapter.table_exists("otp").await.unwrap());
+        assert!(globex_adapter.table_exists("user").await.unwrap());
+        assert!(globex_adapter.table_exists("otp").await.unwrap());
+        assert!(!globex_adapter.table_exists("oauth").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_serves_http_until_shutdown() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let running = tokio::spawn(server().serve(listener, async {
+            stopped.await.ok();
+        }));
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("http://{}/api/auth/users/u1", addr))
+            .header("X-Auth-Bucket", "acme")
+            .json(&serde_json::json!({ "name": "Jane" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["bucket"], "acme");
+        assert_eq!(body["params"]["id"], "u1");
+        assert_eq!(body["body"]["name"], "Jane");
+        assert_eq!(body["ip"], "127.0.0.1");
+
+        let response = client
+            .post(format!("http://{}/api/auth/users/u1", addr))
+            .body("not json")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+
+        // Form posts reach the handler as the raw form string.
+        let response = client
+            .post(format!("http://{}/api/auth/users/u1", addr))
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body("code=abc&state=xyz")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["body"], "code=abc&state=xyz");
+
+        stop.send(()).unwrap();
+        running.await.unwrap().unwrap();
+        assert!(
+            client
+                .get(format!("http://{}/api/auth/session", addr))
+                .send()
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,17 +1,34 @@
 //! Better Auth Server binary.
+//!
+//! Usage: `better-auth-server [config.toml]`. Without a config file the
+//! default [`ServerConfig`] is used.
 
-use better_auth_server::{AuthServer, ServerConfig};
+use better_auth_server::{AuthServer, ServerConfig, load_config};
+use std::collections::HashMap;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
-
     // Load configuration
-    let config = ServerConfig::default();
+    let (config, buckets) = match std::env::args().nth(1) {
+        Some(path) => load_config(&path)?,
+        None => (ServerConfig::default(), HashMap::new()),
+    };
+
+    // Initialize tracing at the configured level
+    let level = config.log_level.parse().unwrap_or(tracing::Level::INFO);
+    tracing_subscriber::fmt().with_max_level(level).init();
+
+    // Buckets need a storage adapter, which the config file can't provide
+    if !buckets.is_empty() {
+        tracing::warn!(
+            "Ignoring {} bucket(s) in the config file; register them with AuthServer::register_bucket",
+            buckets.len()
+        );
+    }
 
-    // Create and run server
+    // Create the server, migrate each bucket, then run
     let server = AuthServer::new(config);
+    server.migrate().await?;
     server.run().await?;
 
     Ok(())
@@ -0,0 +1,100 @@
+//! Cache for resolved role permissions.
+//!
+//! Resolving a role's permissions walks its whole hierarchy and queries
+//! storage for every role on the way. The cache memoizes the result per role
+//! together with the set of roles that were walked, so invalidating any role
+//! also drops every cached role that inherits from it.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+struct CacheEntry {
+    permissions: HashSet<String>,
+    /// Every role visited while resolving `permissions`.
+    roles: HashSet<String>,
+    expires_at: Instant,
+}
+
+/// In-memory, TTL-bounded cache of `get_all_permissions` results.
+pub(crate) struct PermissionCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<String, CacheEntry>>,
+}
+
+impl PermissionCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached permissions for a role, if present and fresh.
+    pub(crate) fn get(&self, role_id: &str) -> Option<HashSet<String>> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(role_id)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.permissions.clone())
+    }
+
+    /// Caches the permissions resolved for a role.
+    pub(crate) fn insert(
+        &self,
+        role_id: &str,
+        permissions: HashSet<String>,
+        roles: HashSet<String>,
+    ) {
+        let mut entries = self.entries.write().unwrap();
+        entries.retain(|_, entry| entry.expires_at > Instant::now());
+        entries.insert(
+            role_id.to_string(),
+            CacheEntry {
+                permissions,
+                roles,
+                expires_at: Instant::now() + self.ttl,
+            },
+        );
+    }
+
+    /// Drops the role and every cached role that inherits from it.
+    pub(crate) fn invalidate(&self, role_id: &str) {
+        let mut entries = self.entries.write().unwrap();
+        entries.retain(|_, entry| !entry.roles.contains(role_id));
+    }
+
+    /// Drops all cached roles.
+    pub(crate) fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_invalidate_drops_descendants() {
+        let cache = PermissionCache::new(Duration::from_secs(60));
+        cache.insert("admin", set(&["post:delete"]), set(&["admin", "editor"]));
+        cache.insert("editor", set(&["post:edit"]), set(&["editor"]));
+        cache.insert("viewer", set(&["post:view"]), set(&["viewer"]));
+
+        cache.invalidate("editor");
+        assert!(cache.get("admin").is_none());
+        assert!(cache.get("editor").is_none());
+        assert_eq!(cache.get("viewer"), Some(set(&["post:view"])));
+    }
+
+    #[test]
+    fn test_expired_entries_are_ignored() {
+        let cache = PermissionCache::new(Duration::ZERO);
+        cache.insert("admin", set(&["*:*"]), set(&["admin"]));
+        assert!(cache.get("admin").is_none());
+    }
+}
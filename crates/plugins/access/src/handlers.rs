@@ -2,13 +2,86 @@
 
 use crate::storage::AccessStorageExt;
 use crate::types::*;
-use crate::{AccessConfig, AccessExt, Permission};
-use better_auth_core::context::AuthContext;
+use crate::{AccessConfig, AccessExt};
+use async_trait::async_trait;
 use better_auth_core::error::{AuthError, AuthResult};
-use better_auth_core::router::{Request, Response};
+use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_core::session::SessionResolver;
+use better_auth_core::traits::StorageAdapter;
+use chrono::Utc;
+use serde::de::DeserializeOwned;
 use serde_json::json;
+use std::future::Future;
 use std::sync::Arc;
 
+/// Adapts an async access handler function into a `RequestHandler`.
+///
+/// Every request must carry a session whose user holds the configured
+/// [`manage_permission`](AccessConfig::manage_permission). Requests without
+/// a valid session get 401; signed-in users without the permission get 403.
+///
+/// The wrapped function receives the shared `AccessConfig`; errors are
+/// rendered as JSON with the status code reported by `AuthError`.
+pub struct AccessHandler<F> {
+    config: Arc<AccessConfig>,
+    handler: F,
+}
+
+impl<F> AccessHandler<F> {
+    /// Creates a new handler bound to the given configuration.
+    pub fn new(config: Arc<AccessConfig>, handler: F) -> Self {
+        Self { config, handler }
+    }
+
+    /// Returns `Ok(false)` when the request carries no valid session and an
+    /// error when the user lacks the manage permission.
+    async fn authorize(&self, req: &Request) -> AuthResult<bool> {
+        let storage: Arc<dyn StorageAdapter> = get_storage(&self.config)?;
+        let Some(resolved) = SessionResolver::new(storage.clone())
+            .resolve_request(req)
+            .await?
+        else {
+            return Ok(false);
+        };
+        let Some(user) = storage.get_user_by_id(&resolved.session.user_id).await? else {
+            return Ok(false);
+        };
+
+        let permission = &self.config.manage_permission;
+        if self.config.user_can(&user, permission).await? {
+            Ok(true)
+        } else {
+            Err(AuthError::forbidden(format!(
+                "The '{}' permission is required",
+                permission
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl<F, Fut> RequestHandler for AccessHandler<F>
+where
+    F: Fn(Request, Arc<AccessConfig>) -> Fut + Send + Sync,
+    Fut: Future<Output = AuthResult<Response>> + Send,
+{
+    async fn handle(&self, req: Request) -> Response {
+        let result = match self.authorize(&req).await {
+            Ok(true) => (self.handler)(req, self.config.clone()).await,
+            Ok(false) => {
+                return Response::unauthorized().json(json!({ "error": "unauthorized" }));
+            }
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(response) => response,
+            Err(err) => Response::new(err.status_code()).json(json!({
+                "error": err.to_string()
+            })),
+        }
+    }
+}
+
 // ============================================================================
 // Role Management Handlers
 // ============================================================================
@@ -16,54 +89,54 @@ use std::sync::Arc;
 /// POST /access/roles - Create a new role
 pub async fn create_role_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let body: CreateRoleRequest = req.json()?;
+    let storage = get_storage(&config)?;
+    let body: CreateRoleRequest = json_body(&req)?;
 
     // Validate request
     if body.id.is_empty() || body.name.is_empty() {
-        return Err(AuthError::ValidationError("Role ID and name are required".into()));
+        return Err(invalid("id", "Role ID and name are required"));
     }
 
     // Check if role already exists
     if storage.get_role(&body.id).await?.is_some() {
-        return Err(AuthError::ValidationError(format!("Role '{}' already exists", body.id)));
+        return Err(AuthError::duplicate("role", "id", &body.id));
     }
 
     let role = DbRole::new(&body.id, &body.name)
         .description(body.description.unwrap_or_default());
 
     let created = storage.create_role(&role).await?;
-    
-    Ok(Response::json(created).with_status(201))
+
+    Ok(Response::created().json(created))
 }
 
 /// GET /access/roles - List all roles
 pub async fn list_roles_handler(
     _req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
+    let storage = get_storage(&config)?;
     let roles = storage.list_roles().await?;
-    
-    Ok(Response::json(json!({ "roles": roles })))
+
+    Ok(Response::ok().json(json!({ "roles": roles })))
 }
 
 /// GET /access/roles/:id - Get a specific role
 pub async fn get_role_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let role_id = req.path_param("id")?;
+    let storage = get_storage(&config)?;
+    let role_id = path_param(&req, "id")?;
 
     let role = storage.get_role(role_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Role '{}' not found", role_id)))?;
+        .ok_or_else(|| AuthError::not_found("role", "id", role_id))?;
 
     let permissions = storage.get_role_permissions(role_id).await?;
 
-    Ok(Response::json(RoleWithPermissions {
+    Ok(Response::ok().json(RoleWithPermissions {
         role,
         permissions,
     }))
@@ -72,18 +145,18 @@ pub async fn get_role_handler(
 /// PUT /access/roles/:id - Update a role
 pub async fn update_role_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let role_id = req.path_param("id")?;
-    let body: UpdateRoleRequest = req.json()?;
+    let storage = get_storage(&config)?;
+    let role_id = path_param(&req, "id")?;
+    let body: UpdateRoleRequest = json_body(&req)?;
 
     let mut role = storage.get_role(role_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Role '{}' not found", role_id)))?;
+        .ok_or_else(|| AuthError::not_found("role", "id", role_id))?;
 
     // Prevent updating system roles
     if role.is_system {
-        return Err(AuthError::Forbidden("Cannot update system roles".into()));
+        return Err(AuthError::forbidden("Cannot update system roles"));
     }
 
     if let Some(name) = body.name {
@@ -92,32 +165,32 @@ pub async fn update_role_handler(
     if let Some(desc) = body.description {
         role.description = Some(desc);
     }
-    role.updated_at = chrono::Utc::now();
 
     let updated = storage.update_role(&role).await?;
-    
-    Ok(Response::json(updated))
+
+    Ok(Response::ok().json(updated))
 }
 
 /// DELETE /access/roles/:id - Delete a role
 pub async fn delete_role_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let role_id = req.path_param("id")?;
+    let storage = get_storage(&config)?;
+    let role_id = path_param(&req, "id")?;
 
     let role = storage.get_role(role_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Role '{}' not found", role_id)))?;
+        .ok_or_else(|| AuthError::not_found("role", "id", role_id))?;
 
     // Prevent deleting system roles
     if role.is_system {
-        return Err(AuthError::Forbidden("Cannot delete system roles".into()));
+        return Err(AuthError::forbidden("Cannot delete system roles"));
     }
 
     storage.delete_role(role_id).await?;
-    
-    Ok(Response::json(json!({ "message": "Role deleted successfully" })))
+    config.invalidate_permission_cache(role_id);
+
+    Ok(Response::ok().json(json!({ "message": "Role deleted successfully" })))
 }
 
 // ============================================================================
@@ -127,19 +200,19 @@ pub async fn delete_role_handler(
 /// POST /access/permissions - Create a new permission
 pub async fn create_permission_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let body: CreatePermissionRequest = req.json()?;
+    let storage = get_storage(&config)?;
+    let body: CreatePermissionRequest = json_body(&req)?;
 
     // Validate request
     if body.id.is_empty() || body.name.is_empty() {
-        return Err(AuthError::ValidationError("Permission ID and name are required".into()));
+        return Err(invalid("id", "Permission ID and name are required"));
     }
 
     // Check if permission already exists
     if storage.get_permission(&body.id).await?.is_some() {
-        return Err(AuthError::ValidationError(format!("Permission '{}' already exists", body.id)));
+        return Err(AuthError::duplicate("permission", "id", &body.id));
     }
 
     let mut permission = DbPermission::new(&body.id, &body.name);
@@ -148,54 +221,55 @@ pub async fn create_permission_handler(
     }
 
     let created = storage.create_permission(&permission).await?;
-    
-    Ok(Response::json(created).with_status(201))
+
+    Ok(Response::created().json(created))
 }
 
 /// GET /access/permissions - List all permissions
 pub async fn list_permissions_handler(
     _req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
+    let storage = get_storage(&config)?;
     let permissions = storage.list_permissions().await?;
-    
-    Ok(Response::json(json!({ "permissions": permissions })))
+
+    Ok(Response::ok().json(json!({ "permissions": permissions })))
 }
 
 /// GET /access/permissions/:id - Get a specific permission
 pub async fn get_permission_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let perm_id = req.path_param("id")?;
+    let storage = get_storage(&config)?;
+    let perm_id = path_param(&req, "id")?;
 
     let permission = storage.get_permission(perm_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Permission '{}' not found", perm_id)))?;
+        .ok_or_else(|| AuthError::not_found("permission", "id", perm_id))?;
 
-    Ok(Response::json(permission))
+    Ok(Response::ok().json(permission))
 }
 
 /// DELETE /access/permissions/:id - Delete a permission
 pub async fn delete_permission_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let perm_id = req.path_param("id")?;
+    let storage = get_storage(&config)?;
+    let perm_id = path_param(&req, "id")?;
 
     let permission = storage.get_permission(perm_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Permission '{}' not found", perm_id)))?;
+        .ok_or_else(|| AuthError::not_found("permission", "id", perm_id))?;
 
     // Prevent deleting system permissions
     if permission.is_system {
-        return Err(AuthError::Forbidden("Cannot delete system permissions".into()));
+        return Err(AuthError::forbidden("Cannot delete system permissions"));
     }
 
     storage.delete_permission(perm_id).await?;
-    
-    Ok(Response::json(json!({ "message": "Permission deleted successfully" })))
+    config.clear_permission_cache();
+
+    Ok(Response::ok().json(json!({ "message": "Permission deleted successfully" })))
 }
 
 // ============================================================================
@@ -205,50 +279,52 @@ pub async fn delete_permission_handler(
 /// POST /access/roles/:id/permissions - Assign permission to role
 pub async fn assign_permission_to_role_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let role_id = req.path_param("id")?;
-    let body: AssignPermissionRequest = req.json()?;
+    let storage = get_storage(&config)?;
+    let role_id = path_param(&req, "id")?;
+    let body: AssignPermissionRequest = json_body(&req)?;
 
     // Verify role exists
     storage.get_role(role_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Role '{}' not found", role_id)))?;
+        .ok_or_else(|| AuthError::not_found("role", "id", role_id))?;
 
     // Verify permission exists
     storage.get_permission(&body.permission_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Permission '{}' not found", body.permission_id)))?;
+        .ok_or_else(|| AuthError::not_found("permission", "id", &body.permission_id))?;
 
     storage.assign_permission_to_role(role_id, &body.permission_id).await?;
-    
-    Ok(Response::json(json!({ "message": "Permission assigned to role successfully" })))
+    config.invalidate_permission_cache(role_id);
+
+    Ok(Response::ok().json(json!({ "message": "Permission assigned to role successfully" })))
 }
 
 /// DELETE /access/roles/:id/permissions/:perm_id - Remove permission from role
 pub async fn remove_permission_from_role_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let role_id = req.path_param("id")?;
-    let perm_id = req.path_param("perm_id")?;
+    let storage = get_storage(&config)?;
+    let role_id = path_param(&req, "id")?;
+    let perm_id = path_param(&req, "perm_id")?;
 
     storage.remove_permission_from_role(role_id, perm_id).await?;
-    
-    Ok(Response::json(json!({ "message": "Permission removed from role successfully" })))
+    config.invalidate_permission_cache(role_id);
+
+    Ok(Response::ok().json(json!({ "message": "Permission removed from role successfully" })))
 }
 
 /// GET /access/roles/:id/permissions - List role permissions
 pub async fn list_role_permissions_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let role_id = req.path_param("id")?;
+    let storage = get_storage(&config)?;
+    let role_id = path_param(&req, "id")?;
 
     let permissions = storage.get_role_permissions(role_id).await?;
-    
-    Ok(Response::json(json!({ "permissions": permissions })))
+
+    Ok(Response::ok().json(json!({ "permissions": permissions })))
 }
 
 // ============================================================================
@@ -258,49 +334,60 @@ pub async fn list_role_permissions_handler(
 /// POST /access/users/:id/permissions - Grant permission to user
 pub async fn grant_permission_to_user_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let user_id = req.path_param("id")?;
-    let body: AssignPermissionRequest = req.json()?;
+    let storage = get_storage(&config)?;
+    let user_id = path_param(&req, "id")?;
+    let body: GrantPermissionRequest = json_body(&req)?;
+
+    if body.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(invalid("expires_at", "Expiry must be in the future"));
+    }
 
     // Verify user exists
-    ctx.adapter().get_user_by_id(user_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("User '{}' not found", user_id)))?;
+    storage.get_user_by_id(user_id).await?
+        .ok_or_else(|| AuthError::not_found("user", "id", user_id))?;
 
     // Verify permission exists
     storage.get_permission(&body.permission_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Permission '{}' not found", body.permission_id)))?;
+        .ok_or_else(|| AuthError::not_found("permission", "id", &body.permission_id))?;
 
-    storage.grant_permission_to_user(user_id, &body.permission_id).await?;
-    
-    Ok(Response::json(json!({ "message": "Permission granted to user successfully" })))
+    match body.expires_at {
+        Some(expires_at) => {
+            storage
+                .grant_permission_to_user_until(user_id, &body.permission_id, expires_at)
+                .await?
+        }
+        None => storage.grant_permission_to_user(user_id, &body.permission_id).await?,
+    }
+
+    Ok(Response::ok().json(json!({ "message": "Permission granted to user successfully" })))
 }
 
 /// DELETE /access/users/:id/permissions/:perm_id - Revoke permission from user
 pub async fn revoke_permission_from_user_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let user_id = req.path_param("id")?;
-    let perm_id = req.path_param("perm_id")?;
+    let storage = get_storage(&config)?;
+    let user_id = path_param(&req, "id")?;
+    let perm_id = path_param(&req, "perm_id")?;
 
     storage.revoke_permission_from_user(user_id, perm_id).await?;
-    
-    Ok(Response::json(json!({ "message": "Permission revoked from user successfully" })))
+
+    Ok(Response::ok().json(json!({ "message": "Permission revoked from user successfully" })))
 }
 
 /// GET /access/users/:id/permissions - Get all user permissions
 pub async fn list_user_permissions_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let user_id = req.path_param("id")?;
+    let storage = get_storage(&config)?;
+    let user_id = path_param(&req, "id")?;
 
-    let user = ctx.adapter().get_user_by_id(user_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("User '{}' not found", user_id)))?;
+    let user = storage.get_user_by_id(user_id).await?
+        .ok_or_else(|| AuthError::not_found("user", "id", user_id))?;
 
     // Get direct permissions
     let direct_permissions = storage.get_user_permissions(user_id).await?;
@@ -311,19 +398,15 @@ pub async fn list_user_permissions_handler(
 
     if let Some(role_id) = user.role() {
         role_permissions = storage.get_role_permissions(&role_id).await?;
-        
+
         // Get all permissions including inherited
-        if let Some(config) = get_access_config(&ctx) {
-            if let Ok(perms) = config.get_all_permissions(&role_id).await {
-                all_permission_names = perms.into_iter().collect();
-            }
-        }
+        all_permission_names = config.get_all_permissions(&role_id).await?.into_iter().collect();
     }
 
     // Add direct permissions to all permissions
     all_permission_names.extend(direct_permissions.iter().map(|p| p.name.clone()));
 
-    Ok(Response::json(UserPermissionsResponse {
+    Ok(Response::ok().json(UserPermissionsResponse {
         user_id: user_id.to_string(),
         role: user.role(),
         direct_permissions,
@@ -339,55 +422,58 @@ pub async fn list_user_permissions_handler(
 /// POST /access/roles/:id/parents - Set role parent
 pub async fn set_role_parent_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let child_id = req.path_param("id")?;
-    let body: SetRoleParentRequest = req.json()?;
+    let storage = get_storage(&config)?;
+    let child_id = path_param(&req, "id")?;
+    let body: SetRoleParentRequest = json_body(&req)?;
 
     // Verify both roles exist
     storage.get_role(child_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Role '{}' not found", child_id)))?;
+        .ok_or_else(|| AuthError::not_found("role", "id", child_id))?;
     storage.get_role(&body.parent_id).await?
-        .ok_or_else(|| AuthError::NotFound(format!("Role '{}' not found", body.parent_id)))?;
+        .ok_or_else(|| AuthError::not_found("role", "id", &body.parent_id))?;
 
     // Prevent circular inheritance
     if child_id == body.parent_id {
-        return Err(AuthError::ValidationError("A role cannot inherit from itself".into()));
+        return Err(invalid("parent_id", "A role cannot inherit from itself"));
     }
+    config.check_role_parent(child_id, &body.parent_id).await?;
 
     storage.set_role_parent(child_id, &body.parent_id).await?;
-    
-    Ok(Response::json(json!({ "message": "Role parent set successfully" })))
+    config.invalidate_permission_cache(child_id);
+
+    Ok(Response::ok().json(json!({ "message": "Role parent set successfully" })))
 }
 
 /// DELETE /access/roles/:id/parents/:parent_id - Remove role parent
 pub async fn remove_role_parent_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let child_id = req.path_param("id")?;
-    let parent_id = req.path_param("parent_id")?;
+    let storage = get_storage(&config)?;
+    let child_id = path_param(&req, "id")?;
+    let parent_id = path_param(&req, "parent_id")?;
 
     storage.remove_role_parent(child_id, parent_id).await?;
-    
-    Ok(Response::json(json!({ "message": "Role parent removed successfully" })))
+    config.invalidate_permission_cache(child_id);
+
+    Ok(Response::ok().json(json!({ "message": "Role parent removed successfully" })))
 }
 
 /// GET /access/roles/:id/hierarchy - Get role hierarchy
 pub async fn get_role_hierarchy_handler(
     req: Request,
-    ctx: Arc<AuthContext>,
+    config: Arc<AccessConfig>,
 ) -> AuthResult<Response> {
-    let storage = get_storage(&ctx)?;
-    let role_id = req.path_param("id")?;
+    let storage = get_storage(&config)?;
+    let role_id = path_param(&req, "id")?;
 
     let parents = storage.get_role_parents(role_id).await?;
-    
-    Ok(Response::json(json!({ 
+
+    Ok(Response::ok().json(json!({
         "role_id": role_id,
-        "parents": parents 
+        "parents": parents
     })))
 }
 
@@ -395,17 +481,28 @@ pub async fn get_role_hierarchy_handler(
 // Helper Functions
 // ============================================================================
 
-fn get_storage(ctx: &AuthContext) -> AuthResult<Arc<dyn AccessStorageExt>> {
-    if let Some(config) = get_access_config(ctx) {
-        if let Some(storage) = &config.storage {
-            return Ok(storage.clone());
-        }
-    }
-    Err(AuthError::Internal("Access plugin storage not configured".into()))
+fn get_storage(config: &AccessConfig) -> AuthResult<Arc<dyn AccessStorageExt>> {
+    config
+        .storage
+        .clone()
+        .ok_or_else(|| AuthError::internal("Access plugin storage not configured"))
 }
 
-fn get_access_config(ctx: &AuthContext) -> Option<Arc<AccessConfig>> {
-    // Try to get the config from context extensions
-    // This would need to be stored during plugin initialization
-    None // Placeholder - actual implementation depends on context architecture
+fn path_param<'a>(req: &'a Request, name: &str) -> AuthResult<&'a str> {
+    req.param(name)
+        .map(String::as_str)
+        .ok_or_else(|| AuthError::MissingField {
+            field: name.to_string(),
+        })
+}
+
+fn json_body<T: DeserializeOwned>(req: &Request) -> AuthResult<T> {
+    req.json().ok_or_else(|| invalid("body", "Missing or invalid request body"))
+}
+
+fn invalid(field: &str, reason: impl Into<String>) -> AuthError {
+    AuthError::InvalidField {
+        field: field.to_string(),
+        reason: reason.into(),
+    }
 }
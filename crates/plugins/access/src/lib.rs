@@ -9,12 +9,13 @@
 //! - Database-backed custom roles and permissions
 //! - Hybrid predefined and dynamic role management
 
+mod cache;
 mod handlers;
 mod storage;
 mod types;
 
 pub use handlers::*;
-pub use storage::AccessStorageExt;
+pub use storage::{creates_cycle, AccessStorageExt};
 pub use types::*;
 
 use async_trait::async_trait;
@@ -27,6 +28,9 @@ use better_auth_events_sdk::{EventDefinition, EventProvider};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
+
+use cache::PermissionCache;
 
 /// A role definition.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -184,6 +188,10 @@ pub struct AccessConfig {
     pub default_role: Option<String>,
     /// ABAC policies.
     policies: HashMap<String, Vec<BoxedPolicy>>,
+    /// Cache of resolved role permissions (disabled unless a TTL is set).
+    permission_cache: Option<Arc<PermissionCache>>,
+    /// Permission a signed-in user needs to use the `/access/*` routes.
+    pub manage_permission: String,
 }
 
 impl Default for AccessConfig {
@@ -194,6 +202,8 @@ impl Default for AccessConfig {
             storage: None,
             default_role: None,
             policies: HashMap::new(),
+            permission_cache: None,
+            manage_permission: "access:manage".to_string(),
         }
     }
 }
@@ -209,6 +219,33 @@ impl AccessConfig {
         AccessConfigBuilder::new()
     }
 
+    /// Caches the result of [`get_all_permissions`](Self::get_all_permissions)
+    /// per role for `ttl`.
+    ///
+    /// Changes made through the access handlers invalidate the affected
+    /// roles. Changes made directly through storage do not; use
+    /// [`invalidate_permission_cache`](Self::invalidate_permission_cache) or
+    /// wait for the TTL to expire.
+    pub fn permission_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.permission_cache = Some(Arc::new(PermissionCache::new(ttl)));
+        self
+    }
+
+    /// Drops the cached permissions of a role and of every role that
+    /// inherits from it.
+    pub fn invalidate_permission_cache(&self, role_id: &str) {
+        if let Some(cache) = &self.permission_cache {
+            cache.invalidate(role_id);
+        }
+    }
+
+    /// Drops all cached role permissions.
+    pub fn clear_permission_cache(&self) {
+        if let Some(cache) = &self.permission_cache {
+            cache.clear();
+        }
+    }
+
     /// Gets a role by ID (predefined only - for backward compatibility).
     pub fn get_role(&self, id: &str) -> Option<&Role> {
         self.predefined_roles.get(id)
@@ -246,6 +283,10 @@ impl AccessConfig {
 
     /// Gets all permissions for a role from both predefined and database sources.
     pub async fn get_all_permissions(&self, role_id: &str) -> AuthResult<HashSet<String>> {
+        if let Some(permissions) = self.permission_cache.as_ref().and_then(|c| c.get(role_id)) {
+            return Ok(permissions);
+        }
+
         let mut permissions = HashSet::new();
 
         // 1. Check predefined roles
@@ -263,6 +304,10 @@ impl AccessConfig {
         let mut visited = HashSet::new();
         Box::pin(self.collect_inherited_permissions(role_id, &mut permissions, &mut visited)).await?;
 
+        if let Some(cache) = &self.permission_cache {
+            cache.insert(role_id, permissions.clone(), visited);
+        }
+
         Ok(permissions)
     }
 
@@ -302,6 +347,107 @@ impl AccessConfig {
         })
     }
 
+    /// Checks if a user has a permission, including database-backed user
+    /// and role permissions.
+    ///
+    /// Unlike [`AccessExt::can`], storage lookups are awaited rather than
+    /// blocking the current thread, so this is safe on any runtime.
+    pub async fn user_can(&self, user: &User, permission: &str) -> AuthResult<bool> {
+        let target = Permission::parse(permission);
+
+        if any_matches(user.extra_permissions(), &target) {
+            return Ok(true);
+        }
+
+        if let Some(storage) = &self.storage {
+            let perms = storage.get_user_permissions(&user.id).await?;
+            if any_matches(perms.into_iter().map(|p| p.name), &target) {
+                return Ok(true);
+            }
+        }
+
+        match user.role() {
+            Some(role) => Ok(any_matches(self.get_all_permissions(&role).await?, &target)),
+            None => Ok(false),
+        }
+    }
+
+    /// Checks several permissions for a user at once.
+    ///
+    /// The user's effective permissions are resolved once and every
+    /// requested permission is matched against them. The result is keyed by
+    /// the requested permission strings.
+    pub async fn user_can_all(
+        &self,
+        user: &User,
+        permissions: &[&str],
+    ) -> AuthResult<HashMap<String, bool>> {
+        let granted: Vec<Permission> = self
+            .effective_permissions(user)
+            .await?
+            .iter()
+            .map(|perm| Permission::parse(perm))
+            .collect();
+
+        Ok(permissions
+            .iter()
+            .map(|permission| {
+                let target = Permission::parse(permission);
+                let allowed = granted.iter().any(|p| p.matches(&target));
+                (permission.to_string(), allowed)
+            })
+            .collect())
+    }
+
+    /// Resolves every permission a user holds: extra permissions, direct
+    /// database grants, and the permissions of their role.
+    async fn effective_permissions(&self, user: &User) -> AuthResult<HashSet<String>> {
+        let mut permissions: HashSet<String> = user.extra_permissions().into_iter().collect();
+        if let Some(storage) = &self.storage {
+            let direct = storage.get_user_permissions(&user.id).await?;
+            permissions.extend(direct.into_iter().map(|p| p.name));
+        }
+        if let Some(role) = user.role() {
+            permissions.extend(self.get_all_permissions(&role).await?);
+        }
+        Ok(permissions)
+    }
+
+    /// Checks that making `parent_id` a parent of `child_id` would not create
+    /// a cycle across the predefined and database hierarchies.
+    ///
+    /// Returns [`AuthError::Conflict`] if it would.
+    pub async fn check_role_parent(&self, child_id: &str, parent_id: &str) -> AuthResult<()> {
+        let mut hierarchy = self.predefined_hierarchy.clone();
+        if let Some(storage) = &self.storage {
+            for (child, parents) in storage.get_role_hierarchy().await? {
+                hierarchy.entry(child).or_default().extend(parents);
+            }
+        }
+        if creates_cycle(&hierarchy, child_id, parent_id) {
+            return Err(role_cycle_error(child_id, parent_id));
+        }
+        Ok(())
+    }
+
+    /// Checks the predefined hierarchy for cycles.
+    pub fn validate_hierarchy(&self) -> AuthResult<()> {
+        let mut edges: Vec<_> = self
+            .predefined_hierarchy
+            .iter()
+            .flat_map(|(child, parents)| parents.iter().map(move |parent| (child, parent)))
+            .collect();
+        edges.sort();
+
+        // An edge closes a cycle if the child is reachable from its parent.
+        for (child, parent) in edges {
+            if creates_cycle(&self.predefined_hierarchy, child, parent) {
+                return Err(role_cycle_error(child, parent));
+            }
+        }
+        Ok(())
+    }
+
     /// Registers a policy for a resource type.
     pub fn register_policy(&mut self, policy: impl AccessPolicy + 'static) {
         let resource = policy.resource_type().to_string();
@@ -320,6 +466,13 @@ impl AccessConfig {
     }
 }
 
+fn role_cycle_error(child_id: &str, parent_id: &str) -> AuthError {
+    AuthError::conflict(format!(
+        "Role '{}' cannot inherit from '{}': it would become its own ancestor",
+        child_id, parent_id
+    ))
+}
+
 /// Builder for access configuration.
 pub struct AccessConfigBuilder {
     config: AccessConfig,
@@ -339,7 +492,7 @@ impl AccessConfigBuilder {
     }
 
     /// Defines a role (backward compatible alias for predefined_role).
-    pub fn role(mut self, role: Role) -> Self {
+    pub fn role(self, role: Role) -> Self {
         self.predefined_role(role)
     }
 
@@ -354,7 +507,7 @@ impl AccessConfigBuilder {
     }
 
     /// Sets role inheritance (backward compatible alias).
-    pub fn inherits(mut self, child: &str, parent: &str) -> Self {
+    pub fn inherits(self, child: &str, parent: &str) -> Self {
         self.predefined_inherits(child, parent)
     }
 
@@ -370,6 +523,20 @@ impl AccessConfigBuilder {
         self
     }
 
+    /// Caches resolved role permissions for `ttl`.
+    pub fn permission_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.config = self.config.permission_cache_ttl(ttl);
+        self
+    }
+
+    /// Sets the permission required to use the `/access/*` routes.
+    ///
+    /// Defaults to `access:manage`.
+    pub fn manage_permission(mut self, permission: &str) -> Self {
+        self.config.manage_permission = permission.to_string();
+        self
+    }
+
     /// Registers a policy.
     pub fn policy(mut self, policy: impl AccessPolicy + 'static) -> Self {
         self.config.register_policy(policy);
@@ -435,6 +602,11 @@ pub trait AccessExt {
     fn has_role(&self, role: &str, config: &AccessConfig) -> bool;
 
     /// Checks permission using the Permission struct (supports wildcards).
+    ///
+    /// Extra and predefined role permissions are checked without I/O. When
+    /// `config.storage` is set, database permissions are then looked up by
+    /// blocking on the current Tokio runtime, which panics on a
+    /// current-thread runtime; use [`AccessConfig::user_can`] from async code.
     fn can(&self, permission: &str, config: &AccessConfig) -> bool;
 }
 
@@ -482,60 +654,37 @@ impl AccessExt for User {
     fn can(&self, permission: &str, config: &AccessConfig) -> bool {
         let target = Permission::parse(permission);
 
-        // Check extra permissions first (from user table)
-        for perm in self.extra_permissions() {
-            let p = Permission::parse(&perm);
-            if p.matches(&target) {
-                return true;
-            }
+        // Extra permissions (from user table) and predefined roles need no I/O
+        if any_matches(self.extra_permissions(), &target) {
+            return true;
+        }
+        if let Some(role) = self.role()
+            && any_matches(config.get_permissions(&role), &target)
+        {
+            return true;
         }
 
-        // Check direct user permissions from database
-        if let Some(storage) = &config.storage {
-            let user_id = self.id.clone();
-            let user_perms = tokio::task::block_in_place(|| {
-                tokio::runtime::Handle::current()
-                    .block_on(storage.get_user_permissions(&user_id))
-            });
+        // Database permissions, only when storage is configured
+        let Some(storage) = &config.storage else {
+            return false;
+        };
 
-            if let Ok(perms) = user_perms {
-                for perm in perms {
-                    let p = Permission::parse(&perm.name);
-                    if p.matches(&target) {
-                        return true;
-                    }
-                }
-            }
+        let user_id = self.id.clone();
+        let user_perms = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(storage.get_user_permissions(&user_id))
+        });
+        if let Ok(perms) = user_perms
+            && any_matches(perms.into_iter().map(|p| p.name), &target)
+        {
+            return true;
         }
 
-        // Check role permissions (predefined + database + inherited)
         if let Some(role) = self.role() {
-            // First try synchronous predefined check
-            let predefined_perms = config.get_permissions(&role);
-            for perm in predefined_perms {
-                let p = Permission::parse(&perm);
-                if p.matches(&target) {
-                    return true;
-                }
-            }
-
-            // Then check database if storage is available
-            if config.storage.is_some() {
-                let role_clone = role.clone();
-                let config_clone = config.clone();
-                let role_perms = tokio::task::block_in_place(|| {
-                    tokio::runtime::Handle::current()
-                        .block_on(config_clone.get_all_permissions(&role_clone))
-                });
-
-                if let Ok(perms) = role_perms {
-                    for perm in perms {
-                        let p = Permission::parse(&perm);
-                        if p.matches(&target) {
-                            return true;
-                        }
-                    }
-                }
+            let role_perms = tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(config.get_all_permissions(&role))
+            });
+            if let Ok(perms) = role_perms {
+                return any_matches(perms, &target);
             }
         }
 
@@ -543,6 +692,13 @@ impl AccessExt for User {
     }
 }
 
+/// Returns true if any of `permissions` grants `target`.
+fn any_matches(permissions: impl IntoIterator<Item = String>, target: &Permission) -> bool {
+    permissions
+        .into_iter()
+        .any(|perm| Permission::parse(&perm).matches(target))
+}
+
 // ============================================================================
 // Scoped Roles (Multi-tenancy)
 // ============================================================================
@@ -618,14 +774,8 @@ impl AccessPlugin {
         if let Some(storage) = &self.config.storage {
             // Sync predefined roles to database
             for (id, role) in &self.config.predefined_roles {
-                let db_role = DbRole {
-                    id: id.clone(),
-                    name: role.name.clone(),
-                    description: role.description.clone(),
-                    is_system: true,
-                    created_at: chrono::Utc::now(),
-                    updated_at: chrono::Utc::now(),
-                };
+                let mut db_role = DbRole::new(id, role.name.clone()).system();
+                db_role.description = role.description.clone();
 
                 // Create or update role
                 if storage.get_role(id).await?.is_none() {
@@ -653,6 +803,8 @@ impl AccessPlugin {
                     storage.set_role_parent(child, parent).await.ok();
                 }
             }
+
+            self.config.clear_permission_cache();
         }
 
         Ok(())
@@ -663,6 +815,20 @@ impl AccessPlugin {
         user.can(permission, &self.config)
     }
 
+    /// Checks if a user has a permission without blocking.
+    ///
+    /// Prefer this over [`can`](Self::can) when database roles are
+    /// configured. See [`AccessConfig::user_can`].
+    pub async fn can_async(&self, user: &User, permission: &str) -> AuthResult<bool> {
+        self.config.user_can(user, permission).await
+    }
+
+    /// Drops the cached permissions of a role and of every role that
+    /// inherits from it.
+    pub fn invalidate_permission_cache(&self, role_id: &str) {
+        self.config.invalidate_permission_cache(role_id);
+    }
+
     /// Checks if a user has a role.
     pub fn has_role(&self, user: &User, role: &str) -> bool {
         user.has_role(role, &self.config)
@@ -744,122 +910,106 @@ impl AuthPlugin for AccessPlugin {
         "Access Control (RBAC/ABAC)"
     }
 
+    fn validate_config(&self) -> AuthResult<()> {
+        match self.config.validate_hierarchy() {
+            Err(AuthError::Conflict { reason }) => Err(AuthError::invalid_config(self.id(), [reason])),
+            result => result,
+        }
+    }
+
     fn define_schema(&self, builder: &mut SchemaBuilder) {
         // Add database tables for roles and permissions if storage is enabled
         if self.config.storage.is_some() {
             // Roles table
-            builder.add_model(ModelDefinition {
-                name: "roles".to_string(),
-                fields: vec![
-                    Field::new("id", FieldType::String)
-                        .primary_key(),
-                    Field::new("name", FieldType::String)
-                        .not_null(),
-                    Field::new("description", FieldType::Text)
-                        .nullable(),
-                    Field::new("is_system", FieldType::Boolean)
-                        .default_value("false")
-                        .not_null(),
-                    Field::new("created_at", FieldType::Timestamp)
-                        .default_value("CURRENT_TIMESTAMP")
-                        .not_null(),
-                    Field::new("updated_at", FieldType::Timestamp)
-                        .default_value("CURRENT_TIMESTAMP")
-                        .not_null(),
-                ],
-                indexes: vec![
-                    IndexDefinition::new("idx_roles_name", vec!["name"]),
-                ],
-            });
+            builder.add_model_mut(
+                ModelDefinition::new("roles")
+                    .field(Field::primary_key("id"))
+                    .field(Field::new("name", FieldType::String(255)))
+                    .field(Field::optional("description", FieldType::Text))
+                    .field(Field::new("is_system", FieldType::Boolean).default("false"))
+                    .field(Field::new("created_at", FieldType::Timestamp).default("CURRENT_TIMESTAMP"))
+                    .field(Field::new("updated_at", FieldType::Timestamp).default("CURRENT_TIMESTAMP"))
+                    .index(IndexDefinition::new("idx_roles_name", vec!["name".to_string()])),
+            );
 
             // Permissions table
-            builder.add_model(ModelDefinition {
-                name: "permissions".to_string(),
-                fields: vec![
-                    Field::new("id", FieldType::String)
-                        .primary_key(),
-                    Field::new("name", FieldType::String)
-                        .not_null()
-                        .unique(),
-                    Field::new("resource", FieldType::String)
-                        .nullable(),
-                    Field::new("action", FieldType::String)
-                        .nullable(),
-                    Field::new("description", FieldType::Text)
-                        .nullable(),
-                    Field::new("is_system", FieldType::Boolean)
-                        .default_value("false")
-                        .not_null(),
-                    Field::new("created_at", FieldType::Timestamp)
-                        .default_value("CURRENT_TIMESTAMP")
-                        .not_null(),
-                ],
-                indexes: vec![
-                    IndexDefinition::new("idx_permissions_name", vec!["name"])
-                        .unique(),
-                    IndexDefinition::new("idx_permissions_resource", vec!["resource"]),
-                ],
-            });
+            builder.add_model_mut(
+                ModelDefinition::new("permissions")
+                    .field(Field::primary_key("id"))
+                    .field(Field::new("name", FieldType::String(255)).unique())
+                    .field(Field::optional("resource", FieldType::String(255)))
+                    .field(Field::optional("action", FieldType::String(255)))
+                    .field(Field::optional("description", FieldType::Text))
+                    .field(Field::new("is_system", FieldType::Boolean).default("false"))
+                    .field(Field::new("created_at", FieldType::Timestamp).default("CURRENT_TIMESTAMP"))
+                    .index(IndexDefinition::unique("idx_permissions_name", vec!["name".to_string()]))
+                    .index(IndexDefinition::new("idx_permissions_resource", vec!["resource".to_string()])),
+            );
 
             // Role-Permission relationship table
-            builder.add_model(ModelDefinition {
-                name: "role_permissions".to_string(),
-                fields: vec![
-                    Field::new("role_id", FieldType::String)
-                        .not_null()
-                        .foreign_key("roles", "id", ReferentialAction::Cascade),
-                    Field::new("permission_id", FieldType::String)
-                        .not_null()
-                        .foreign_key("permissions", "id", ReferentialAction::Cascade),
-                ],
-                indexes: vec![
-                    IndexDefinition::new("pk_role_permissions", vec!["role_id", "permission_id"])
-                        .unique(),
-                    IndexDefinition::new("idx_role_permissions_role", vec!["role_id"]),
-                    IndexDefinition::new("idx_role_permissions_perm", vec!["permission_id"]),
-                ],
-            });
+            builder.add_model_mut(
+                ModelDefinition::new("role_permissions")
+                    .field(
+                        Field::new("role_id", FieldType::String(255))
+                            .references("roles.id")
+                            .on_delete(ReferentialAction::Cascade),
+                    )
+                    .field(
+                        Field::new("permission_id", FieldType::String(255))
+                            .references("permissions.id")
+                            .on_delete(ReferentialAction::Cascade),
+                    )
+                    .index(IndexDefinition::unique(
+                        "pk_role_permissions",
+                        vec!["role_id".to_string(), "permission_id".to_string()],
+                    ))
+                    .index(IndexDefinition::new("idx_role_permissions_role", vec!["role_id".to_string()]))
+                    .index(IndexDefinition::new("idx_role_permissions_perm", vec!["permission_id".to_string()])),
+            );
 
             // User-Permission relationship table (direct permissions)
-            builder.add_model(ModelDefinition {
-                name: "user_permissions".to_string(),
-                fields: vec![
-                    Field::new("user_id", FieldType::String)
-                        .not_null()
-                        .foreign_key("user", "id", ReferentialAction::Cascade),
-                    Field::new("permission_id", FieldType::String)
-                        .not_null()
-                        .foreign_key("permissions", "id", ReferentialAction::Cascade),
-                    Field::new("granted_at", FieldType::Timestamp)
-                        .default_value("CURRENT_TIMESTAMP")
-                        .not_null(),
-                ],
-                indexes: vec![
-                    IndexDefinition::new("pk_user_permissions", vec!["user_id", "permission_id"])
-                        .unique(),
-                    IndexDefinition::new("idx_user_permissions_user", vec!["user_id"]),
-                    IndexDefinition::new("idx_user_permissions_perm", vec!["permission_id"]),
-                ],
-            });
+            builder.add_model_mut(
+                ModelDefinition::new("user_permissions")
+                    .field(
+                        Field::new("user_id", FieldType::String(36))
+                            .references("user.id")
+                            .on_delete(ReferentialAction::Cascade),
+                    )
+                    .field(
+                        Field::new("permission_id", FieldType::String(255))
+                            .references("permissions.id")
+                            .on_delete(ReferentialAction::Cascade),
+                    )
+                    .field(Field::new("granted_at", FieldType::Timestamp).default("CURRENT_TIMESTAMP"))
+                    .field(Field::optional("expires_at", FieldType::Timestamp))
+                    .index(IndexDefinition::unique(
+                        "pk_user_permissions",
+                        vec!["user_id".to_string(), "permission_id".to_string()],
+                    ))
+                    .index(IndexDefinition::new("idx_user_permissions_user", vec!["user_id".to_string()]))
+                    .index(IndexDefinition::new("idx_user_permissions_perm", vec!["permission_id".to_string()])),
+            );
 
             // Role hierarchy table
-            builder.add_model(ModelDefinition {
-                name: "role_hierarchy".to_string(),
-                fields: vec![
-                    Field::new("child_role_id", FieldType::String)
-                        .not_null()
-                        .foreign_key("roles", "id", ReferentialAction::Cascade),
-                    Field::new("parent_role_id", FieldType::String)
-                        .not_null()
-                        .foreign_key("roles", "id", ReferentialAction::Cascade),
-                ],
-                indexes: vec![
-                    IndexDefinition::new("pk_role_hierarchy", vec!["child_role_id", "parent_role_id"])
-                        .unique(),
-                    IndexDefinition::new("idx_role_hierarchy_child", vec!["child_role_id"]),
-                    IndexDefinition::new("idx_role_hierarchy_parent", vec!["parent_role_id"]),
-                ],
-            });
+            builder.add_model_mut(
+                ModelDefinition::new("role_hierarchy")
+                    .field(
+                        Field::new("child_role_id", FieldType::String(255))
+                            .references("roles.id")
+                            .on_delete(ReferentialAction::Cascade),
+                    )
+                    .field(
+                        Field::new("parent_role_id", FieldType::String(255))
+                            .references("roles.id")
+                            .on_delete(ReferentialAction::Cascade),
+                    )
+                    .index(IndexDefinition::unique(
+                        "pk_role_hierarchy",
+                        vec!["child_role_id".to_string(), "parent_role_id".to_string()],
+                    ))
+                    .index(IndexDefinition::new("idx_role_hierarchy_child", vec!["child_role_id".to_string()]))
+                    .index(IndexDefinition::new("idx_role_hierarchy_parent", vec!["parent_role_id".to_string()])),
+            );
         }
     }
 
@@ -871,123 +1021,137 @@ impl AuthPlugin for AccessPlugin {
             return;
         }
 
-        // Role management routes
-        router.route(Route {
-            path: "/access/roles".to_string(),
-            method: Method::POST,
-            handler: Arc::new(create_role_handler),
-        });
-
-        router.route(Route {
-            path: "/access/roles".to_string(),
-            method: Method::GET,
-            handler: Arc::new(list_roles_handler),
-        });
-
-        router.route(Route {
-            path: "/access/roles/:id".to_string(),
-            method: Method::GET,
-            handler: Arc::new(get_role_handler),
-        });
-
-        router.route(Route {
-            path: "/access/roles/:id".to_string(),
-            method: Method::PUT,
-            handler: Arc::new(update_role_handler),
-        });
+        let config = Arc::new(self.config.clone());
 
-        router.route(Route {
-            path: "/access/roles/:id".to_string(),
-            method: Method::DELETE,
-            handler: Arc::new(delete_role_handler),
-        });
+        // Role management routes
+        router.route(
+            Route::new(Method::POST, "/access/roles", AccessHandler::new(config.clone(), create_role_handler))
+                .summary("Create role")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::GET, "/access/roles", AccessHandler::new(config.clone(), list_roles_handler))
+                .summary("List roles")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::GET, "/access/roles/:id", AccessHandler::new(config.clone(), get_role_handler))
+                .summary("Get role")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::PUT, "/access/roles/:id", AccessHandler::new(config.clone(), update_role_handler))
+                .summary("Update role")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::DELETE, "/access/roles/:id", AccessHandler::new(config.clone(), delete_role_handler))
+                .summary("Delete role")
+                .tag("access")
+                .requires_auth(),
+        );
 
         // Permission management routes
-        router.route(Route {
-            path: "/access/permissions".to_string(),
-            method: Method::POST,
-            handler: Arc::new(create_permission_handler),
-        });
-
-        router.route(Route {
-            path: "/access/permissions".to_string(),
-            method: Method::GET,
-            handler: Arc::new(list_permissions_handler),
-        });
-
-        router.route(Route {
-            path: "/access/permissions/:id".to_string(),
-            method: Method::GET,
-            handler: Arc::new(get_permission_handler),
-        });
-
-        router.route(Route {
-            path: "/access/permissions/:id".to_string(),
-            method: Method::DELETE,
-            handler: Arc::new(delete_permission_handler),
-        });
+        router.route(
+            Route::new(Method::POST, "/access/permissions", AccessHandler::new(config.clone(), create_permission_handler))
+                .summary("Create permission")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::GET, "/access/permissions", AccessHandler::new(config.clone(), list_permissions_handler))
+                .summary("List permissions")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::GET, "/access/permissions/:id", AccessHandler::new(config.clone(), get_permission_handler))
+                .summary("Get permission")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::DELETE, "/access/permissions/:id", AccessHandler::new(config.clone(), delete_permission_handler))
+                .summary("Delete permission")
+                .tag("access")
+                .requires_auth(),
+        );
 
         // Role-permission association routes
-        router.route(Route {
-            path: "/access/roles/:id/permissions".to_string(),
-            method: Method::POST,
-            handler: Arc::new(assign_permission_to_role_handler),
-        });
-
-        router.route(Route {
-            path: "/access/roles/:id/permissions/:perm_id".to_string(),
-            method: Method::DELETE,
-            handler: Arc::new(remove_permission_from_role_handler),
-        });
-
-        router.route(Route {
-            path: "/access/roles/:id/permissions".to_string(),
-            method: Method::GET,
-            handler: Arc::new(list_role_permissions_handler),
-        });
+        router.route(
+            Route::new(Method::POST, "/access/roles/:id/permissions", AccessHandler::new(config.clone(), assign_permission_to_role_handler))
+                .summary("Assign permission to role")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::DELETE, "/access/roles/:id/permissions/:perm_id", AccessHandler::new(config.clone(), remove_permission_from_role_handler))
+                .summary("Remove permission from role")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::GET, "/access/roles/:id/permissions", AccessHandler::new(config.clone(), list_role_permissions_handler))
+                .summary("List role permissions")
+                .tag("access")
+                .requires_auth(),
+        );
 
         // User-permission routes
-        router.route(Route {
-            path: "/access/users/:id/permissions".to_string(),
-            method: Method::POST,
-            handler: Arc::new(grant_permission_to_user_handler),
-        });
-
-        router.route(Route {
-            path: "/access/users/:id/permissions/:perm_id".to_string(),
-            method: Method::DELETE,
-            handler: Arc::new(revoke_permission_from_user_handler),
-        });
-
-        router.route(Route {
-            path: "/access/users/:id/permissions".to_string(),
-            method: Method::GET,
-            handler: Arc::new(list_user_permissions_handler),
-        });
+        router.route(
+            Route::new(Method::POST, "/access/users/:id/permissions", AccessHandler::new(config.clone(), grant_permission_to_user_handler))
+                .summary("Grant permission to user")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::DELETE, "/access/users/:id/permissions/:perm_id", AccessHandler::new(config.clone(), revoke_permission_from_user_handler))
+                .summary("Revoke permission from user")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::GET, "/access/users/:id/permissions", AccessHandler::new(config.clone(), list_user_permissions_handler))
+                .summary("List user permissions")
+                .tag("access")
+                .requires_auth(),
+        );
 
         // Role hierarchy routes
-        router.route(Route {
-            path: "/access/roles/:id/parents".to_string(),
-            method: Method::POST,
-            handler: Arc::new(set_role_parent_handler),
-        });
-
-        router.route(Route {
-            path: "/access/roles/:id/parents/:parent_id".to_string(),
-            method: Method::DELETE,
-            handler: Arc::new(remove_role_parent_handler),
-        });
-
-        router.route(Route {
-            path: "/access/roles/:id/hierarchy".to_string(),
-            method: Method::GET,
-            handler: Arc::new(get_role_hierarchy_handler),
-        });
+        router.route(
+            Route::new(Method::POST, "/access/roles/:id/parents", AccessHandler::new(config.clone(), set_role_parent_handler))
+                .summary("Set role parent")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::DELETE, "/access/roles/:id/parents/:parent_id", AccessHandler::new(config.clone(), remove_role_parent_handler))
+                .summary("Remove role parent")
+                .tag("access")
+                .requires_auth(),
+        );
+        router.route(
+            Route::new(Method::GET, "/access/roles/:id/hierarchy", AccessHandler::new(config.clone(), get_role_hierarchy_handler))
+                .summary("Get role hierarchy")
+                .tag("access")
+                .requires_auth(),
+        );
     }
 
     async fn on_after_signup(&self, _ctx: &AuthContext, _user: &User) -> AuthResult<()> {
         Ok(())
     }
+
+    async fn on_before_user_delete(&self, _ctx: &AuthContext, user_id: &str) -> AuthResult<()> {
+        match &self.config.storage {
+            Some(storage) => storage.revoke_all_user_permissions(user_id).await,
+            None => Ok(()),
+        }
+    }
 }
 
 // ============================================================================
@@ -997,7 +1161,6 @@ impl AuthPlugin for AccessPlugin {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use better_auth_adapters_memory::MemoryAdapter;
 
     #[test]
     fn test_permission_parsing() {
@@ -1069,238 +1232,27 @@ mod tests {
         assert!(!user.can("user:delete", &config));
     }
 
-    #[tokio::test]
-    async fn test_database_role_creation() {
-        let adapter = Arc::new(MemoryAdapter::new());
-        let config = AccessConfig::builder()
-            .with_storage(adapter.clone())
-            .build();
-
-        let plugin = AccessPlugin::new(config);
-
-        // Create a database role
-        let role = DbRole::new("moderator", "Moderator")
-            .description("Community moderator");
-
-        let created = adapter.create_role(&role).await.unwrap();
-        assert_eq!(created.id, "moderator");
-        assert_eq!(created.name, "Moderator");
-    }
-
-    #[tokio::test]
-    async fn test_database_permission_creation() {
-        let adapter = Arc::new(MemoryAdapter::new());
-        
-        let perm = DbPermission::new("comment:moderate", "comment:moderate")
-            .description("Can moderate comments");
-
-        let created = adapter.create_permission(&perm).await.unwrap();
-        assert_eq!(created.id, "comment:moderate");
-        assert_eq!(created.name, "comment:moderate");
-    }
-
-    #[tokio::test]
-    async fn test_role_permission_assignment() {
-        let adapter = Arc::new(MemoryAdapter::new());
-
-        // Create role and permission
-        let role = DbRole::new("moderator", "Moderator");
-        let perm = DbPermission::new("comment:moderate", "comment:moderate");
-
-        adapter.create_role(&role).await.unwrap();
-        adapter.create_permission(&perm).await.unwrap();
-
-        // Assign permission to role
-        adapter
-            .assign_permission_to_role("moderator", "comment:moderate")
-            .await
-            .unwrap();
-
-        // Verify assignment
-        let perms = adapter.get_role_permissions("moderator").await.unwrap();
-        assert_eq!(perms.len(), 1);
-        assert_eq!(perms[0].id, "comment:moderate");
-    }
-
-    #[tokio::test]
-    async fn test_user_permission_grant() {
-        let adapter = Arc::new(MemoryAdapter::new());
-
-        let perm = DbPermission::new("admin:access", "admin:access");
-        adapter.create_permission(&perm).await.unwrap();
-
-        // Grant permission directly to user
-        adapter
-            .grant_permission_to_user("user123", "admin:access")
-            .await
-            .unwrap();
-
-        // Verify grant
-        let perms = adapter.get_user_permissions("user123").await.unwrap();
-        assert_eq!(perms.len(), 1);
-        assert_eq!(perms[0].id, "admin:access");
-    }
-
-    #[tokio::test]
-    async fn test_role_hierarchy() {
-        let adapter = Arc::new(MemoryAdapter::new());
-
-        // Create roles
-        let admin = DbRole::new("admin", "Admin");
-        let editor = DbRole::new("editor", "Editor");
-        let viewer = DbRole::new("viewer", "Viewer");
-
-        adapter.create_role(&admin).await.unwrap();
-        adapter.create_role(&editor).await.unwrap();
-        adapter.create_role(&viewer).await.unwrap();
-
-        // Set hierarchy: admin -> editor -> viewer
-        adapter.set_role_parent("editor", "viewer").await.unwrap();
-        adapter.set_role_parent("admin", "editor").await.unwrap();
-
-        // Verify hierarchy
-        let editor_parents = adapter.get_role_parents("editor").await.unwrap();
-        assert_eq!(editor_parents.len(), 1);
-        assert!(editor_parents.contains(&"viewer".to_string()));
-
-        let admin_parents = adapter.get_role_parents("admin").await.unwrap();
-        assert_eq!(admin_parents.len(), 1);
-        assert!(admin_parents.contains(&"editor".to_string()));
-    }
-
-    #[tokio::test]
-    async fn test_hybrid_role_resolution() {
-        let adapter = Arc::new(MemoryAdapter::new());
-
-        // Create predefined roles
-        let config = AccessConfig::builder()
-            .predefined_role(
-                Role::new("admin", "Admin")
-                    .permission("*:*")
-            )
-            .predefined_role(
-                Role::new("editor", "Editor")
-                    .permission("post:*")
-            )
-            .with_storage(adapter.clone())
-            .build();
-
-        // Add database permission
-        let db_perm = DbPermission::new("comment:moderate", "comment:moderate");
-        adapter.create_permission(&db_perm).await.unwrap();
-        adapter
-            .assign_permission_to_role("editor", "comment:moderate")
-            .await
-            .unwrap();
-
-        // Get all permissions (predefined + database)
-        let all_perms = config.get_all_permissions("editor").await.unwrap();
-        assert!(all_perms.contains("post:*"));
-        assert!(all_perms.contains("comment:moderate"));
-    }
-
-    #[tokio::test]
-    async fn test_initialize_syncs_predefined_roles() {
-        let adapter = Arc::new(MemoryAdapter::new());
-
-        let config = AccessConfig::builder()
-            .predefined_role(
-                Role::new("admin", "Administrator")
-                    .permission("*:*")
-            )
-            .predefined_role(
-                Role::new("user", "User")
-                    .permission("profile:read")
-            )
-            .predefined_inherits("admin", "user")
-            .with_storage(adapter.clone())
-            .build();
-
-        let plugin = AccessPlugin::new(config);
-
-        // Initialize should sync predefined roles to database
-        plugin.initialize().await.unwrap();
-
-        // Verify roles were created
-        let admin_role = adapter.get_role("admin").await.unwrap();
-        assert!(admin_role.is_some());
-        assert!(admin_role.unwrap().is_system);
-
-        let user_role = adapter.get_role("user").await.unwrap();
-        assert!(user_role.is_some());
-
-        // Verify permissions were created
-        let admin_perms = adapter.get_role_permissions("admin").await.unwrap();
-        assert!(!admin_perms.is_empty());
-
-        // Verify hierarchy was created
-        let admin_parents = adapter.get_role_parents("admin").await.unwrap();
-        assert!(admin_parents.contains(&"user".to_string()));
-    }
-
-    #[tokio::test]
-    async fn test_system_role_protection() {
-        let adapter = Arc::new(MemoryAdapter::new());
-
-        let system_role = DbRole::new("admin", "Admin").system();
-        adapter.create_role(&system_role).await.unwrap();
-
-        // Should not be able to delete system role
-        let result = adapter.delete_role("admin").await;
-        assert!(result.is_err());
-    }
-
-    #[tokio::test]
-    async fn test_system_permission_protection() {
-        let adapter = Arc::new(MemoryAdapter::new());
-
-        let system_perm = DbPermission::new("*:*", "*:*").system();
-        adapter.create_permission(&system_perm).await.unwrap();
-
-        // Should not be able to delete system permission
-        let result = adapter.delete_permission("*:*").await;
-        assert!(result.is_err());
+    #[test]
+    fn test_validate_config_rejects_predefined_cycle() {
+        let plugin = AccessPlugin::new(
+            AccessConfig::builder()
+                .predefined_inherits("admin", "editor")
+                .predefined_inherits("editor", "viewer")
+                .build(),
+        );
+        assert!(plugin.validate_config().is_ok());
+
+        let plugin = AccessPlugin::new(
+            AccessConfig::builder()
+                .predefined_inherits("admin", "editor")
+                .predefined_inherits("editor", "viewer")
+                .predefined_inherits("viewer", "admin")
+                .build(),
+        );
+        assert!(matches!(
+            plugin.validate_config(),
+            Err(AuthError::InvalidPluginConfig { .. })
+        ));
     }
 
-    #[tokio::test]
-    async fn test_inherited_permissions_from_database() {
-        let adapter = Arc::new(MemoryAdapter::new());
-
-        let config = AccessConfig::builder()
-            .with_storage(adapter.clone())
-            .build();
-
-        // Create role hierarchy in database
-        let admin = DbRole::new("admin", "Admin");
-        let editor = DbRole::new("editor", "Editor");
-        let viewer = DbRole::new("viewer", "Viewer");
-
-        adapter.create_role(&admin).await.unwrap();
-        adapter.create_role(&editor).await.unwrap();
-        adapter.create_role(&viewer).await.unwrap();
-
-        // Create permissions
-        let view_perm = DbPermission::new("post:view", "post:view");
-        let edit_perm = DbPermission::new("post:edit", "post:edit");
-        let delete_perm = DbPermission::new("post:delete", "post:delete");
-
-        adapter.create_permission(&view_perm).await.unwrap();
-        adapter.create_permission(&edit_perm).await.unwrap();
-        adapter.create_permission(&delete_perm).await.unwrap();
-
-        // Assign permissions
-        adapter.assign_permission_to_role("viewer", "post:view").await.unwrap();
-        adapter.assign_permission_to_role("editor", "post:edit").await.unwrap();
-        adapter.assign_permission_to_role("admin", "post:delete").await.unwrap();
-
-        // Set hierarchy
-        adapter.set_role_parent("editor", "viewer").await.unwrap();
-        adapter.set_role_parent("admin", "editor").await.unwrap();
-
-        // Admin should have all permissions through inheritance
-        let admin_perms = config.get_all_permissions("admin").await.unwrap();
-        assert!(admin_perms.contains("post:delete"));
-        assert!(admin_perms.contains("post:edit"));
-        assert!(admin_perms.contains("post:view"));
-    }
 }
@@ -2,16 +2,20 @@
 
 use async_trait::async_trait;
 use better_auth_core::error::AuthResult;
-use std::collections::HashMap;
+#[cfg(doc)]
+use better_auth_core::error::AuthError;
+use better_auth_core::traits::StorageAdapter;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
 
 use crate::types::{DbPermission, DbRole};
 
 /// Trait for access control storage operations.
 ///
 /// Adapters can implement this trait to provide database-backed
-/// role and permission management.
+/// role and permission management on top of their core storage.
 #[async_trait]
-pub trait AccessStorageExt: Send + Sync {
+pub trait AccessStorageExt: StorageAdapter {
     // ==================== Role Operations ====================
 
     /// Creates a new role in the database.
@@ -76,6 +80,18 @@ pub trait AccessStorageExt: Send + Sync {
         permission_id: &str,
     ) -> AuthResult<()>;
 
+    /// Grants a permission directly to a user until `expires_at`.
+    ///
+    /// Once it expires the grant must no longer be returned by
+    /// [`get_user_permissions`](Self::get_user_permissions). Granting a
+    /// permission the user already holds replaces its expiry.
+    async fn grant_permission_to_user_until(
+        &self,
+        user_id: &str,
+        permission_id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AuthResult<()>;
+
     /// Revokes a permission from a user.
     async fn revoke_permission_from_user(
         &self,
@@ -83,12 +99,19 @@ pub trait AccessStorageExt: Send + Sync {
         permission_id: &str,
     ) -> AuthResult<()>;
 
-    /// Gets all permissions granted directly to a user (not from roles).
+    /// Revokes every permission granted directly to a user, expired or not.
+    async fn revoke_all_user_permissions(&self, user_id: &str) -> AuthResult<()>;
+
+    /// Gets all unexpired permissions granted directly to a user (not from
+    /// roles).
     async fn get_user_permissions(&self, user_id: &str) -> AuthResult<Vec<DbPermission>>;
 
     // ==================== Role Hierarchy ====================
 
     /// Sets a parent role for a child role (inheritance).
+    ///
+    /// Implementations must return [`AuthError::Conflict`] if the new edge
+    /// would make a role its own ancestor; see [`creates_cycle`].
     async fn set_role_parent(&self, child_id: &str, parent_id: &str) -> AuthResult<()>;
 
     /// Removes a parent relationship from a child role.
@@ -100,3 +123,26 @@ pub trait AccessStorageExt: Send + Sync {
     /// Gets the complete role hierarchy as a map (child -> parents).
     async fn get_role_hierarchy(&self) -> AuthResult<HashMap<String, Vec<String>>>;
 }
+
+/// Returns true if making `parent_id` a parent of `child_id` would create a
+/// cycle in `hierarchy` (child -> parents), i.e. if `child_id` is `parent_id`
+/// or one of its ancestors.
+pub fn creates_cycle(
+    hierarchy: &HashMap<String, Vec<String>>,
+    child_id: &str,
+    parent_id: &str,
+) -> bool {
+    let mut stack = vec![parent_id];
+    let mut visited = HashSet::new();
+    while let Some(role_id) = stack.pop() {
+        if role_id == child_id {
+            return true;
+        }
+        if visited.insert(role_id)
+            && let Some(parents) = hierarchy.get(role_id)
+        {
+            stack.extend(parents.iter().map(String::as_str));
+        }
+    }
+    false
+}
@@ -152,6 +152,14 @@ pub struct AssignPermissionRequest {
     pub permission_id: String,
 }
 
+/// Request to grant a permission to a user.
+#[derive(Debug, Deserialize)]
+pub struct GrantPermissionRequest {
+    pub permission_id: String,
+    /// When the grant lapses; permanent if omitted.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
 /// Request to set a role parent.
 #[derive(Debug, Deserialize)]
 pub struct SetRoleParentRequest {
@@ -0,0 +1,485 @@
+//! Storage-backed tests for the access control plugin.
+
+use better_auth_adapter_memory::MemoryAdapter;
+use better_auth_core::error::AuthError;
+use better_auth_core::router::{Method, Request};
+use better_auth_core::types::User;
+use better_auth_plugin_access::*;
+use chrono::Utc;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_database_role_creation() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    let config = AccessConfig::builder()
+        .with_storage(adapter.clone())
+        .build();
+
+    let _plugin = AccessPlugin::new(config);
+
+    // Create a database role
+    let role = DbRole::new("moderator", "Moderator")
+        .description("Community moderator");
+
+    let created = adapter.create_role(&role).await.unwrap();
+    assert_eq!(created.id, "moderator");
+    assert_eq!(created.name, "Moderator");
+}
+
+#[tokio::test]
+async fn test_database_permission_creation() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    
+    let perm = DbPermission::new("comment:moderate", "comment:moderate")
+        .description("Can moderate comments");
+
+    let created = adapter.create_permission(&perm).await.unwrap();
+    assert_eq!(created.id, "comment:moderate");
+    assert_eq!(created.name, "comment:moderate");
+}
+
+#[tokio::test]
+async fn test_role_permission_assignment() {
+    let adapter = Arc::new(MemoryAdapter::new());
+
+    // Create role and permission
+    let role = DbRole::new("moderator", "Moderator");
+    let perm = DbPermission::new("comment:moderate", "comment:moderate");
+
+    adapter.create_role(&role).await.unwrap();
+    adapter.create_permission(&perm).await.unwrap();
+
+    // Assign permission to role
+    adapter
+        .assign_permission_to_role("moderator", "comment:moderate")
+        .await
+        .unwrap();
+
+    // Verify assignment
+    let perms = adapter.get_role_permissions("moderator").await.unwrap();
+    assert_eq!(perms.len(), 1);
+    assert_eq!(perms[0].id, "comment:moderate");
+}
+
+#[tokio::test]
+async fn test_user_permission_grant() {
+    let adapter = Arc::new(MemoryAdapter::new());
+
+    let perm = DbPermission::new("admin:access", "admin:access");
+    adapter.create_permission(&perm).await.unwrap();
+
+    // Grant permission directly to user
+    adapter
+        .grant_permission_to_user("user123", "admin:access")
+        .await
+        .unwrap();
+
+    // Verify grant
+    let perms = adapter.get_user_permissions("user123").await.unwrap();
+    assert_eq!(perms.len(), 1);
+    assert_eq!(perms[0].id, "admin:access");
+}
+
+#[tokio::test]
+async fn test_role_hierarchy() {
+    let adapter = Arc::new(MemoryAdapter::new());
+
+    // Create roles
+    let admin = DbRole::new("admin", "Admin");
+    let editor = DbRole::new("editor", "Editor");
+    let viewer = DbRole::new("viewer", "Viewer");
+
+    adapter.create_role(&admin).await.unwrap();
+    adapter.create_role(&editor).await.unwrap();
+    adapter.create_role(&viewer).await.unwrap();
+
+    // Set hierarchy: admin -> editor -> viewer
+    adapter.set_role_parent("editor", "viewer").await.unwrap();
+    adapter.set_role_parent("admin", "editor").await.unwrap();
+
+    // Verify hierarchy
+    let editor_parents = adapter.get_role_parents("editor").await.unwrap();
+    assert_eq!(editor_parents.len(), 1);
+    assert!(editor_parents.contains(&"viewer".to_string()));
+
+    let admin_parents = adapter.get_role_parents("admin").await.unwrap();
+    assert_eq!(admin_parents.len(), 1);
+    assert!(admin_parents.contains(&"editor".to_string()));
+}
+
+#[tokio::test]
+async fn test_hybrid_role_resolution() {
+    let adapter = Arc::new(MemoryAdapter::new());
+
+    // Create predefined roles
+    let config = AccessConfig::builder()
+        .predefined_role(
+            Role::new("admin", "Admin")
+                .permission("*:*")
+        )
+        .predefined_role(
+            Role::new("editor", "Editor")
+                .permission("post:*")
+        )
+        .with_storage(adapter.clone())
+        .build();
+
+    // Add database permission
+    let db_perm = DbPermission::new("comment:moderate", "comment:moderate");
+    adapter.create_permission(&db_perm).await.unwrap();
+    adapter
+        .assign_permission_to_role("editor", "comment:moderate")
+        .await
+        .unwrap();
+
+    // Get all permissions (predefined + database)
+    let all_perms = config.get_all_permissions("editor").await.unwrap();
+    assert!(all_perms.contains("post:*"));
+    assert!(all_perms.contains("comment:moderate"));
+}
+
+#[tokio::test]
+async fn test_initialize_syncs_predefined_roles() {
+    let adapter = Arc::new(MemoryAdapter::new());
+
+    let config = AccessConfig::builder()
+        .predefined_role(
+            Role::new("admin", "Administrator")
+                .permission("*:*")
+        )
+        .predefined_role(
+            Role::new("user", "User")
+                .permission("profile:read")
+        )
+        .predefined_inherits("admin", "user")
+        .with_storage(adapter.clone())
+        .build();
+
+    let plugin = AccessPlugin::new(config);
+
+    // Initialize should sync predefined roles to database
+    plugin.initialize().await.unwrap();
+
+    // Verify roles were created
+    let admin_role = adapter.get_role("admin").await.unwrap();
+    assert!(admin_role.is_some());
+    assert!(admin_role.unwrap().is_system);
+
+    let user_role = adapter.get_role("user").await.unwrap();
+    assert!(user_role.is_some());
+
+    // Verify permissions were created
+    let admin_perms = adapter.get_role_permissions("admin").await.unwrap();
+    assert!(!admin_perms.is_empty());
+
+    // Verify hierarchy was created
+    let admin_parents = adapter.get_role_parents("admin").await.unwrap();
+    assert!(admin_parents.contains(&"user".to_string()));
+}
+
+#[tokio::test]
+async fn test_system_role_protection() {
+    let adapter = Arc::new(MemoryAdapter::new());
+
+    let system_role = DbRole::new("admin", "Admin").system();
+    adapter.create_role(&system_role).await.unwrap();
+
+    // Should not be able to delete system role
+    let result = adapter.delete_role("admin").await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_system_permission_protection() {
+    let adapter = Arc::new(MemoryAdapter::new());
+
+    let system_perm = DbPermission::new("*:*", "*:*").system();
+    adapter.create_permission(&system_perm).await.unwrap();
+
+    // Should not be able to delete system permission
+    let result = adapter.delete_permission("*:*").await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_inherited_permissions_from_database() {
+    let adapter = Arc::new(MemoryAdapter::new());
+
+    let config = AccessConfig::builder()
+        .with_storage(adapter.clone())
+        .build();
+
+    // Create role hierarchy in database
+    let admin = DbRole::new("admin", "Admin");
+    let editor = DbRole::new("editor", "Editor");
+    let viewer = DbRole::new("viewer", "Viewer");
+
+    adapter.create_role(&admin).await.unwrap();
+    adapter.create_role(&editor).await.unwrap();
+    adapter.create_role(&viewer).await.unwrap();
+
+    // Create permissions
+    let view_perm = DbPermission::new("post:view", "post:view");
+    let edit_perm = DbPermission::new("post:edit", "post:edit");
+    let delete_perm = DbPermission::new("post:delete", "post:delete");
+
+    adapter.create_permission(&view_perm).await.unwrap();
+    adapter.create_permission(&edit_perm).await.unwrap();
+    adapter.create_permission(&delete_perm).await.unwrap();
+
+    // Assign permissions
+    adapter.assign_permission_to_role("viewer", "post:view").await.unwrap();
+    adapter.assign_permission_to_role("editor", "post:edit").await.unwrap();
+    adapter.assign_permission_to_role("admin", "post:delete").await.unwrap();
+
+    // Set hierarchy
+    adapter.set_role_parent("editor", "viewer").await.unwrap();
+    adapter.set_role_parent("admin", "editor").await.unwrap();
+
+    // Admin should have all permissions through inheritance
+    let admin_perms = config.get_all_permissions("admin").await.unwrap();
+    assert!(admin_perms.contains("post:delete"));
+    assert!(admin_perms.contains("post:edit"));
+    assert!(admin_perms.contains("post:view"));
+}
+
+#[tokio::test]
+async fn test_user_can_on_current_thread_runtime() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    let plugin = AccessPlugin::new(
+        AccessConfig::builder()
+            .predefined_role(Role::new("editor", "Editor").permission("post:*"))
+            .with_storage(adapter.clone())
+            .build(),
+    );
+
+    adapter
+        .create_permission(&DbPermission::new("comment:moderate", "comment:moderate"))
+        .await
+        .unwrap();
+    adapter
+        .assign_permission_to_role("editor", "comment:moderate")
+        .await
+        .unwrap();
+    adapter
+        .create_permission(&DbPermission::new("report:view", "report:view"))
+        .await
+        .unwrap();
+    adapter
+        .grant_permission_to_user("user123", "report:view")
+        .await
+        .unwrap();
+
+    let mut user = User::new("user123".to_string(), "user@example.com".to_string());
+    user.set_role("editor");
+
+    assert!(plugin.can_async(&user, "post:create").await.unwrap());
+    assert!(plugin.can_async(&user, "comment:moderate").await.unwrap());
+    assert!(plugin.can_async(&user, "report:view").await.unwrap());
+    assert!(!plugin.can_async(&user, "user:delete").await.unwrap());
+
+    // Predefined permissions resolve synchronously without touching storage.
+    assert!(plugin.can(&user, "post:edit"));
+}
+
+#[tokio::test]
+async fn test_permission_cache_invalidation() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    let plugin = AccessPlugin::new(
+        AccessConfig::builder()
+            .with_storage(adapter.clone())
+            .permission_cache_ttl(Duration::from_secs(300))
+            .build(),
+    );
+
+    adapter.create_role(&DbRole::new("editor", "Editor")).await.unwrap();
+    adapter.create_role(&DbRole::new("viewer", "Viewer")).await.unwrap();
+    adapter
+        .create_permission(&DbPermission::new("post:view", "post:view"))
+        .await
+        .unwrap();
+    adapter.assign_permission_to_role("viewer", "post:view").await.unwrap();
+
+    assert!(!plugin.config().get_all_permissions("editor").await.unwrap().contains("post:view"));
+
+    // A direct storage change is hidden by the cache until it is invalidated.
+    adapter.set_role_parent("editor", "viewer").await.unwrap();
+    assert!(!plugin.config().get_all_permissions("editor").await.unwrap().contains("post:view"));
+
+    plugin.invalidate_permission_cache("editor");
+    assert!(plugin.config().get_all_permissions("editor").await.unwrap().contains("post:view"));
+}
+
+#[tokio::test]
+async fn test_handlers_invalidate_permission_cache() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    let config = Arc::new(
+        AccessConfig::builder()
+            .with_storage(adapter.clone())
+            .permission_cache_ttl(Duration::from_secs(300))
+            .build(),
+    );
+
+    for (id, name) in [("admin", "Admin"), ("editor", "Editor"), ("viewer", "Viewer")] {
+        adapter.create_role(&DbRole::new(id, name)).await.unwrap();
+    }
+    adapter
+        .create_permission(&DbPermission::new("post:view", "post:view"))
+        .await
+        .unwrap();
+    adapter.set_role_parent("admin", "editor").await.unwrap();
+    adapter.set_role_parent("editor", "viewer").await.unwrap();
+
+    assert!(config.get_all_permissions("admin").await.unwrap().is_empty());
+
+    // Assigning to a grandparent role must also refresh the cached admin role.
+    let mut req = Request::new(Method::POST, "/access/roles/viewer/permissions");
+    req.params.insert("id".to_string(), "viewer".to_string());
+    req.body = Some(json!({ "permission_id": "post:view" }));
+    assign_permission_to_role_handler(req, config.clone()).await.unwrap();
+
+    assert!(config.get_all_permissions("admin").await.unwrap().contains("post:view"));
+}
+
+#[tokio::test]
+async fn test_reject_cyclic_role_hierarchy() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    let config = Arc::new(AccessConfig::builder().with_storage(adapter.clone()).build());
+
+    for (id, name) in [("admin", "Admin"), ("editor", "Editor"), ("viewer", "Viewer")] {
+        adapter.create_role(&DbRole::new(id, name)).await.unwrap();
+    }
+    adapter.set_role_parent("admin", "editor").await.unwrap();
+    adapter.set_role_parent("editor", "viewer").await.unwrap();
+
+    // viewer -> admin would make viewer its own ancestor.
+    let mut req = Request::new(Method::POST, "/access/roles/viewer/parents");
+    req.params.insert("id".to_string(), "viewer".to_string());
+    req.body = Some(json!({ "parent_id": "admin" }));
+    let err = set_role_parent_handler(req, config).await.unwrap_err();
+    assert!(matches!(err, AuthError::Conflict { .. }));
+    assert_eq!(err.status_code(), 409);
+
+    // The storage itself refuses the edge too.
+    assert!(matches!(
+        adapter.set_role_parent("viewer", "admin").await,
+        Err(AuthError::Conflict { .. })
+    ));
+    assert!(adapter.get_role_parents("viewer").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_user_can_all() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    let config = AccessConfig::builder()
+        .predefined_role(Role::new("editor", "Editor").permission("post:*"))
+        .with_storage(adapter.clone())
+        .build();
+
+    adapter
+        .create_permission(&DbPermission::new("report:view", "report:view"))
+        .await
+        .unwrap();
+    adapter
+        .grant_permission_to_user("user123", "report:view")
+        .await
+        .unwrap();
+
+    let mut user = User::new("user123".to_string(), "user@example.com".to_string());
+    user.set_role("editor");
+    user.add_permission("comment:edit:own");
+
+    let results = config
+        .user_can_all(
+            &user,
+            &["post:create", "post:edit:own", "report:view", "comment:edit", "user:delete"],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 5);
+    assert!(results["post:create"]);
+    assert!(results["post:edit:own"]);
+    assert!(results["report:view"]);
+    assert!(!results["comment:edit"]);
+    assert!(!results["user:delete"]);
+}
+
+#[tokio::test]
+async fn test_time_bound_permission_grant() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    let config = Arc::new(AccessConfig::builder().with_storage(adapter.clone()).build());
+
+    for name in ["report:view", "report:export"] {
+        adapter
+            .create_permission(&DbPermission::new(name, name))
+            .await
+            .unwrap();
+    }
+    let later = Utc::now() + chrono::Duration::hours(1);
+    let earlier = Utc::now() - chrono::Duration::seconds(1);
+    adapter
+        .grant_permission_to_user_until("user123", "report:view", later)
+        .await
+        .unwrap();
+    adapter
+        .grant_permission_to_user_until("user123", "report:export", earlier)
+        .await
+        .unwrap();
+
+    // The expired grant is treated as absent without any cleanup.
+    let perms = adapter.get_user_permissions("user123").await.unwrap();
+    assert_eq!(perms.len(), 1);
+    assert_eq!(perms[0].id, "report:view");
+
+    let user = User::new("user123".to_string(), "user@example.com".to_string());
+    assert!(config.user_can(&user, "report:view").await.unwrap());
+    assert!(!config.user_can(&user, "report:export").await.unwrap());
+
+    // The grant endpoint refuses an expiry in the past.
+    let mut req = Request::new(Method::POST, "/access/users/user123/permissions");
+    req.params.insert("id".to_string(), "user123".to_string());
+    req.body = Some(json!({
+        "permission_id": "report:export",
+        "expires_at": earlier,
+    }));
+    let err = grant_permission_to_user_handler(req, config).await.unwrap_err();
+    assert!(matches!(err, AuthError::InvalidField { .. }));
+}
+
+#[tokio::test]
+async fn test_routes_require_manage_permission() {
+    use better_auth_core::router::RequestHandler;
+    use better_auth_core::traits::StorageAdapter;
+    use better_auth_core::types::Session;
+
+    let adapter = Arc::new(MemoryAdapter::new());
+    let config = Arc::new(AccessConfig::builder().with_storage(adapter.clone()).build());
+
+    let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+    adapter.create_user(&user).await.unwrap();
+    let mut admin = User::new("admin_1".to_string(), "admin@example.com".to_string());
+    admin.add_permission("access:manage");
+    adapter.create_user(&admin).await.unwrap();
+    let user_session = adapter.create_session(&Session::new("user_1".to_string())).await.unwrap();
+    let admin_session = adapter.create_session(&Session::new("admin_1".to_string())).await.unwrap();
+
+    let handler = AccessHandler::new(config, create_role_handler);
+    let create = |token: Option<&str>| {
+        let mut req = Request::new(Method::POST, "/access/roles");
+        if let Some(token) = token {
+            req.headers.insert("authorization".to_string(), format!("Bearer {}", token));
+        }
+        req.body = Some(json!({ "id": "root", "name": "Root" }));
+        req
+    };
+
+    assert_eq!(handler.handle(create(None)).await.status, 401);
+    assert_eq!(handler.handle(create(Some(&user_session.token))).await.status, 403);
+    assert!(adapter.get_role("root").await.unwrap().is_none());
+
+    assert_eq!(handler.handle(create(Some(&admin_session.token))).await.status, 201);
+    assert!(adapter.get_role("root").await.unwrap().is_some());
+}
@@ -1,9 +1,13 @@
 //! Configuration for the Anonymous plugin.
 
+use better_auth_core::events::EventBus;
+use better_auth_core::session_token::SessionTokenStrategy;
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::User;
+use better_auth_plugin_password::PasswordConfig;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use better_auth_core::types::User;
 
 /// Type alias for the onLinkAccount callback.
 pub type OnLinkAccountCallback = Arc<
@@ -18,6 +22,21 @@ pub type EmailGeneratorFn = Arc<dyn Fn() -> String + Send + Sync>;
 /// Type alias for name generator function.
 pub type NameGeneratorFn = Arc<dyn Fn() -> String + Send + Sync>;
 
+/// What to do when an anonymous user links an email another user already
+/// has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LinkConflictStrategy {
+    /// Refuse the link with 409 Conflict. The conflict is only reported
+    /// when the password matches the existing user; a wrong password gets
+    /// 401, as with `SignInExisting`.
+    #[default]
+    Reject,
+    /// Sign in to the existing user if the password matches, then delete the
+    /// anonymous user. The existing user keeps its ID, so use
+    /// `on_link_account` to move the anonymous user's data across.
+    SignInExisting,
+}
+
 /// Configuration for the Anonymous plugin.
 #[derive(Clone, Default)]
 pub struct AnonymousConfig {
@@ -32,6 +51,19 @@ pub struct AnonymousConfig {
     pub on_link_account: Option<OnLinkAccountCallback>,
     /// Whether to disable the delete anonymous user endpoint.
     pub disable_delete_anonymous_user: bool,
+    /// How to handle linking an email that belongs to another user.
+    pub link_conflict_strategy: LinkConflictStrategy,
+    /// Password settings used when linking an email and password, unless
+    /// the plugin is given the registered password plugin with
+    /// `AnonymousPlugin::with_password`.
+    pub password: PasswordConfig,
+    /// Storage adapter used by the link account route.
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    /// How tokens for the sessions it signs users into are generated.
+    /// Use the app's strategy. Default: opaque tokens.
+    pub session_tokens: SessionTokenStrategy,
+    /// Event bus used to emit `anonymous.account_linked`.
+    pub event_bus: Option<Arc<EventBus>>,
 }
 
 impl AnonymousConfig {
@@ -79,6 +111,36 @@ impl AnonymousConfig {
         self.disable_delete_anonymous_user = true;
         self
     }
+
+    /// Sets how to handle linking an email that belongs to another user.
+    pub fn link_conflict_strategy(mut self, strategy: LinkConflictStrategy) -> Self {
+        self.link_conflict_strategy = strategy;
+        self
+    }
+
+    /// Sets the password settings used when linking an email and password.
+    pub fn password(mut self, config: PasswordConfig) -> Self {
+        self.password = config;
+        self
+    }
+
+    /// Sets the storage adapter used by the link account route.
+    pub fn storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Sets how session tokens are generated.
+    pub fn session_tokens(mut self, strategy: SessionTokenStrategy) -> Self {
+        self.session_tokens = strategy;
+        self
+    }
+
+    /// Sets the event bus used to emit account events.
+    pub fn event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
 }
 
 impl std::fmt::Debug for AnonymousConfig {
@@ -89,6 +151,11 @@ impl std::fmt::Debug for AnonymousConfig {
             .field("generate_name", &self.generate_name.is_some())
             .field("on_link_account", &self.on_link_account.is_some())
             .field("disable_delete_anonymous_user", &self.disable_delete_anonymous_user)
+            .field("link_conflict_strategy", &self.link_conflict_strategy)
+            .field("password", &self.password)
+            .field("storage", &self.storage.is_some())
+            .field("session_tokens", &self.session_tokens)
+            .field("event_bus", &self.event_bus.is_some())
             .finish()
     }
 }
@@ -1,8 +1,17 @@
 //! Request handlers for the Anonymous plugin.
 
+use crate::{AnonymousConfig, AnonymousExt, LinkConflictStrategy};
 use async_trait::async_trait;
-use better_auth_core::router::{Request, RequestHandler, Response};
-use serde::Serialize;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::events::Event;
+use better_auth_core::router::{CookieOptions, Request, RequestHandler, Response};
+use better_auth_core::session::{SESSION_COOKIE, SessionResolver};
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::{AuthFactor, Session, User};
+use better_auth_plugin_password::{PasswordExt, PasswordPlugin};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
 
 /// Response for anonymous sign-in.
 #[derive(Debug, Serialize)]
@@ -75,3 +84,235 @@ impl RequestHandler for DeleteAnonymousUserHandler {
         }))
     }
 }
+
+/// Request body for linking an anonymous account.
+///
+/// Either `email` and `password`, or the `provider` of an account already
+/// linked to the anonymous user, such as through OAuth.
+#[derive(Debug, Deserialize)]
+pub struct LinkAccountRequest {
+    pub email: Option<String>,
+    pub password: Option<String>,
+    pub provider: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Handler for POST /anonymous/link-account
+///
+/// Turns the signed-in anonymous user into a permanent one. The user keeps
+/// its ID, so rows that reference it stay valid, and its sessions are
+/// replaced by a new one.
+pub struct LinkAccountHandler {
+    config: AnonymousConfig,
+    password: PasswordPlugin,
+}
+
+impl LinkAccountHandler {
+    /// Creates a new handler that checks and hashes passwords with
+    /// `password`.
+    pub fn new(config: AnonymousConfig, password: PasswordPlugin) -> Self {
+        Self { config, password }
+    }
+
+    async fn link(&self, req: &Request) -> AuthResult<Response> {
+        let storage = self.config.storage.clone().ok_or_else(|| {
+            AuthError::config("Linking an anonymous account requires a storage adapter")
+        })?;
+        let session = SessionResolver::new(storage.clone())
+            .resolve_request(req)
+            .await?
+            .ok_or(AuthError::SessionNotFound)?
+            .session;
+        let anonymous = storage
+            .get_user_by_id(&session.user_id)
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+        if !anonymous.is_anonymous() {
+            return Err(AuthError::forbidden(
+                "Only anonymous users can link an account",
+            ));
+        }
+        let body: LinkAccountRequest = req.json().ok_or_else(|| AuthError::MissingField {
+            field: "email".to_string(),
+        })?;
+
+        let (user, method) = match (&body.email, &body.provider) {
+            (Some(email), _) => {
+                let password = body
+                    .password
+                    .as_deref()
+                    .ok_or_else(|| AuthError::MissingField {
+                        field: "password".to_string(),
+                    })?;
+                let email = email.trim().to_lowercase();
+                if !email.contains('@') {
+                    return Err(AuthError::InvalidEmail);
+                }
+
+                if let Some(existing) = storage.get_user_by_email(&email).await?
+                    && existing.id != anonymous.id
+                {
+                    return self
+                        .sign_in_existing(
+                            &storage,
+                            anonymous,
+                            existing,
+                            password,
+                            req.ip.as_deref(),
+                        )
+                        .await;
+                }
+
+                self.password.validate_password(password)?;
+                let mut user = anonymous.clone();
+                user.email = email;
+                user.email_verified = false;
+                user.set_password_hash(self.password.hash_password(password));
+                (user, "email".to_string())
+            }
+            (None, Some(provider)) => {
+                let accounts = storage.get_accounts_by_user_id(&anonymous.id).await?;
+                if !accounts.iter().any(|a| &a.provider == provider) {
+                    return Err(AuthError::not_found("account", "provider", provider));
+                }
+                (anonymous.clone(), provider.clone())
+            }
+            (None, None) => {
+                return Err(AuthError::MissingField {
+                    field: "email".to_string(),
+                });
+            }
+        };
+
+        let mut user = user;
+        if let Some(name) = body.name {
+            user.name = Some(name);
+        }
+        user.set_anonymous(false);
+        let user = storage.update_user(&user).await?;
+        if method == "email"
+            && let Some(hash) = user.password_hash()
+        {
+            self.password
+                .record_password_history(&user.id, &hash)
+                .await?;
+        }
+
+        // The anonymous sessions carried no credentials; start afresh.
+        storage.delete_sessions_by_user_id(&user.id).await?;
+        let session = storage
+            .create_session(&self.config.session_tokens.new_session(user.id.clone()))
+            .await?;
+
+        self.linked(&anonymous, &user, &method).await;
+        Ok(session_response(&user, &session))
+    }
+
+    /// Handles an email that already belongs to another user, per the
+    /// configured [`LinkConflictStrategy`].
+    ///
+    /// Under either strategy the password is checked first, like a sign-in,
+    /// so only the account's owner learns that the email is taken.
+    async fn sign_in_existing(
+        &self,
+        storage: &Arc<dyn StorageAdapter>,
+        anonymous: User,
+        existing: User,
+        password: &str,
+        ip: Option<&str>,
+    ) -> AuthResult<Response> {
+        self.password.check_signin(&existing.email, ip)?;
+        let hash = existing.password_hash();
+        let verified = self
+            .password
+            .verify_password_or_dummy(password, hash.as_deref());
+        if !verified {
+            self.password
+                .record_signin_failure(&existing.email, ip)
+                .await;
+            return Err(AuthError::InvalidCredentials);
+        }
+        self.password.record_signin_success(&existing.email, ip);
+        if self.config.link_conflict_strategy == LinkConflictStrategy::Reject {
+            return Err(AuthError::duplicate("user", "email", &existing.email));
+        }
+        self.password
+            .upgrade_password_hash(storage.as_ref(), &existing, password)
+            .await;
+
+        // The callback runs before the anonymous user is deleted, so it can
+        // still move the user's data across.
+        self.linked(&anonymous, &existing, "email").await;
+        storage.delete_sessions_by_user_id(&anonymous.id).await?;
+        storage.delete_user(&anonymous.id).await?;
+        let mut session = self.config.session_tokens.new_session(existing.id.clone());
+        session.record_factor(AuthFactor::Password);
+        let session = storage.create_session(&session).await?;
+        Ok(session_response(&existing, &session))
+    }
+
+    /// Runs the `on_link_account` callback and emits
+    /// `anonymous.account_linked`.
+    async fn linked(&self, anonymous: &User, user: &User, method: &str) {
+        if let Some(callback) = &self.config.on_link_account {
+            callback(anonymous.clone(), user.clone()).await;
+        }
+        if let Some(bus) = &self.config.event_bus {
+            bus.emit(
+                Event::simple(
+                    "anonymous.account_linked",
+                    json!({
+                        "anonymous_user_id": anonymous.id,
+                        "user_id": user.id,
+                        "method": method,
+                    }),
+                )
+                .with_source("anonymous"),
+            )
+            .await;
+        }
+    }
+}
+
+#[async_trait]
+impl RequestHandler for LinkAccountHandler {
+    async fn handle(&self, req: Request) -> Response {
+        self.link(&req).await.unwrap_or_else(error_response)
+    }
+}
+
+fn session_response(user: &User, session: &Session) -> Response {
+    Response::ok()
+        .json(json!({
+            "user": {
+                "id": user.id,
+                "email": user.email,
+                "name": user.name,
+                "is_anonymous": user.is_anonymous(),
+            },
+            "session": {
+                "id": session.id,
+                "token": session.token,
+                "expires_at": session.expires_at.to_rfc3339(),
+            }
+        }))
+        .cookie(SESSION_COOKIE, &session.token, CookieOptions::secure())
+}
+
+/// Converts an error into the plugin's error body.
+fn error_response(err: AuthError) -> Response {
+    let (status, code) = match &err {
+        AuthError::SessionNotFound => (401, "UNAUTHORIZED"),
+        AuthError::Forbidden { .. } => (403, "NOT_ANONYMOUS"),
+        AuthError::DuplicateEntry { .. } => (409, "EMAIL_EXISTS"),
+        AuthError::InvalidCredentials => (401, "INVALID_CREDENTIALS"),
+        AuthError::NotFound { .. } => (404, "ACCOUNT_NOT_LINKED"),
+        AuthError::MissingField { .. } => (422, "MISSING_FIELD"),
+        AuthError::InvalidEmail => (422, "INVALID_EMAIL"),
+        AuthError::WeakPassword { .. } => (422, "WEAK_PASSWORD"),
+        _ => (err.status_code(), err.error_code()),
+    };
+    Response::new(status).json(json!({
+        "error": { "code": code, "message": err.to_string() }
+    }))
+}
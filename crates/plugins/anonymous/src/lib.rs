@@ -9,17 +9,20 @@ mod config;
 mod schema;
 mod handlers;
 
-pub use config::AnonymousConfig;
+pub use config::{AnonymousConfig, LinkConflictStrategy};
 pub use schema::AnonymousUserExt;
 
 use async_trait::async_trait;
 use better_auth_core::context::AuthContext;
 use better_auth_core::error::AuthResult;
+use better_auth_core::events::Event;
 use better_auth_core::router::{Method, Route, Router};
 use better_auth_core::schema::{Field, FieldType, SchemaBuilder};
 use better_auth_core::traits::{AuthPlugin, ExtensionProvider};
-use better_auth_core::types::User;
+use better_auth_core::types::{User, UserFilter};
 use better_auth_events_sdk::{EventDefinition, EventProvider};
+use better_auth_plugin_password::{PasswordExt, PasswordPlugin};
+use chrono::{Duration, Utc};
 
 /// Trait for anonymous user operations.
 pub trait AnonymousExt {
@@ -42,12 +45,24 @@ impl AnonymousExt for User {
 /// The Anonymous authentication plugin.
 pub struct AnonymousPlugin {
     config: AnonymousConfig,
+    password: PasswordPlugin,
 }
 
 impl AnonymousPlugin {
     /// Creates a new Anonymous plugin with the given configuration.
     pub fn new(config: AnonymousConfig) -> Self {
-        Self { config }
+        let password = PasswordPlugin::new(config.password.clone());
+        Self { config, password }
+    }
+
+    /// Checks and hashes linked passwords with `password` instead of a
+    /// plugin of its own.
+    ///
+    /// Pass the registered [`PasswordPlugin`] so linking shares its policy
+    /// and sign-in failure counts; `config.password` is then unused.
+    pub fn with_password(mut self, password: &PasswordPlugin) -> Self {
+        self.password = password.shared();
+        self
     }
 
     /// Gets the plugin configuration.
@@ -73,6 +88,69 @@ impl AnonymousPlugin {
     pub fn generate_name(&self) -> Option<String> {
         self.config.generate_name.as_ref().map(|f| f())
     }
+
+    /// Deletes anonymous users created more than `older_than` ago that never
+    /// linked an account, together with their sessions, and emits
+    /// `anonymous.deleted` for each.
+    ///
+    /// Returns the number of users deleted. Meant to be run periodically
+    /// by a scheduler.
+    pub async fn cleanup_expired(
+        &self,
+        ctx: &AuthContext,
+        older_than: Duration,
+    ) -> AuthResult<usize> {
+        const PAGE_SIZE: usize = 100;
+
+        let filter = UserFilter::new()
+            .created_before(Utc::now() - older_than)
+            .extension("is_anonymous", true);
+        let mut kept = 0;
+        let mut deleted = 0;
+        loop {
+            // Deleted users drop out of the listing, so the next page starts
+            // after the users that were kept.
+            let (page, _) = ctx
+                .db
+                .list_users(kept, PAGE_SIZE, Some(filter.clone()))
+                .await?;
+            if page.is_empty() {
+                break;
+            }
+            for user in page {
+                if !Self::never_linked(ctx, &user).await? {
+                    kept += 1;
+                    continue;
+                }
+                ctx.db.delete_sessions_by_user_id(&user.id).await?;
+                ctx.db.delete_user(&user.id).await?;
+                deleted += 1;
+                if let Some(bus) = &self.config.event_bus {
+                    bus.emit(
+                        Event::simple(
+                            "anonymous.deleted",
+                            serde_json::json!({ "user_id": user.id, "reason": "expired" }),
+                        )
+                        .with_source("anonymous"),
+                    )
+                    .await;
+                }
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Returns true if `user` is anonymous and holds no credential.
+    ///
+    /// A link that stopped part way, such as an OAuth account attached
+    /// before the user was upgraded, still counts as linked: the user has
+    /// a way to sign in and must not be deleted.
+    async fn never_linked(ctx: &AuthContext, user: &User) -> AuthResult<bool> {
+        if !user.is_anonymous() || user.email_verified || user.password_hash().is_some() {
+            return Ok(false);
+        }
+        Ok(ctx.db.get_accounts_by_user_id(&user.id).await?.is_empty())
+    }
 }
 
 impl Default for AnonymousPlugin {
@@ -149,6 +227,22 @@ impl AuthPlugin for AnonymousPlugin {
             .tag("anonymous")
             .requires_auth(),
         );
+
+        // POST /anonymous/link-account
+        router.route(
+            Route::new(
+                Method::POST,
+                "/anonymous/link-account",
+                handlers::LinkAccountHandler::new(self.config.clone(), self.password.shared()),
+            )
+            .summary("Link anonymous account")
+            .description(
+                "Upgrades the current anonymous user to a permanent account with an email and \
+                 password or an already linked provider, keeping the user ID.",
+            )
+            .tag("anonymous")
+            .requires_auth(),
+        );
     }
 
     async fn on_before_signin(
@@ -201,4 +295,330 @@ mod tests {
         user.set_anonymous(true);
         assert!(user.is_anonymous());
     }
+
+    /// Creates an anonymous user with a session and a plugin for it.
+    async fn link_setup(
+        config: AnonymousConfig,
+    ) -> (
+        std::sync::Arc<better_auth_adapter_memory::MemoryAdapter>,
+        AnonymousPlugin,
+        User,
+        String,
+    ) {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::traits::StorageAdapter;
+        use better_auth_core::types::Session;
+
+        let storage = std::sync::Arc::new(MemoryAdapter::new());
+        let mut user = User::new("anon_1".to_string(), "temp@anon.example.com".to_string());
+        user.set_anonymous(true);
+        let user = storage.create_user(&user).await.unwrap();
+        let session = storage
+            .create_session(&Session::new(user.id.clone()))
+            .await
+            .unwrap();
+        let plugin = AnonymousPlugin::new(config.storage(storage.clone()));
+        (storage, plugin, user, session.token)
+    }
+
+    async fn link(
+        plugin: &AnonymousPlugin,
+        token: &str,
+        body: serde_json::Value,
+    ) -> better_auth_core::router::Response {
+        use better_auth_core::router::Request;
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router
+            .routes()
+            .find(|r| r.path == "/anonymous/link-account")
+            .unwrap();
+        let mut req = Request::new(Method::POST, "/anonymous/link-account");
+        req.headers
+            .insert("authorization".to_string(), format!("Bearer {}", token));
+        req.body = Some(body);
+        route.handler.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_link_account_keeps_user_id() {
+        use better_auth_core::events::EventBus;
+        use better_auth_core::traits::StorageAdapter;
+        use better_auth_plugin_password::PasswordExt;
+
+        let bus = std::sync::Arc::new(EventBus::new());
+        let (storage, plugin, anonymous, token) =
+            link_setup(AnonymousConfig::new().event_bus(bus.clone())).await;
+
+        let response = link(
+            &plugin,
+            &token,
+            serde_json::json!({ "email": "Jane@Example.com", "password": "correct horse battery" }),
+        )
+        .await;
+        assert_eq!(response.status, 200);
+        let body = response.body.unwrap();
+        assert_eq!(body["user"]["id"], anonymous.id);
+        assert_eq!(body["user"]["is_anonymous"], false);
+
+        let user = storage
+            .get_user_by_id(&anonymous.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.email, "jane@example.com");
+        assert!(!user.is_anonymous());
+        assert!(
+            better_auth_plugin_password::PasswordPlugin::default()
+                .verify_password("correct horse battery", &user.password_hash().unwrap())
+        );
+
+        // The anonymous session is replaced by the returned one.
+        let sessions = storage
+            .get_sessions_by_user_id(&anonymous.id)
+            .await
+            .unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(body["session"]["token"], sessions[0].token);
+        assert_ne!(sessions[0].token, token);
+
+        let events = bus.events_of_type("anonymous.account_linked").await;
+        assert_eq!(events.len(), 1);
+
+        // A permanent user can't link again.
+        let response = link(
+            &plugin,
+            &sessions[0].token,
+            serde_json::json!({ "provider": "google" }),
+        )
+        .await;
+        assert_eq!(response.status, 403);
+        assert_eq!(response.body.unwrap()["error"]["code"], "NOT_ANONYMOUS");
+    }
+
+    #[tokio::test]
+    async fn test_link_account_records_password_history() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_plugin_password::{PasswordConfig, PasswordHistoryStore};
+
+        let history = std::sync::Arc::new(MemoryAdapter::new());
+        let password = PasswordConfig::new()
+            .memory_cost(8 * 1024)
+            .iterations(1)
+            .password_history(3)
+            .history_storage(history.clone());
+        let (_, plugin, anonymous, token) =
+            link_setup(AnonymousConfig::new().password(password)).await;
+
+        let response = link(
+            &plugin,
+            &token,
+            serde_json::json!({ "email": "jane@example.com", "password": "correct horse battery" }),
+        )
+        .await;
+        assert_eq!(response.status, 200);
+        let entries = history
+            .get_password_history(&anonymous.id, 3)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_link_account_with_linked_provider() {
+        use better_auth_core::traits::StorageAdapter;
+        use better_auth_core::types::Account;
+
+        let (storage, plugin, anonymous, token) = link_setup(AnonymousConfig::new()).await;
+
+        let response = link(&plugin, &token, serde_json::json!({ "provider": "google" })).await;
+        assert_eq!(response.status, 404);
+        assert_eq!(
+            response.body.unwrap()["error"]["code"],
+            "ACCOUNT_NOT_LINKED"
+        );
+
+        storage
+            .create_account(&Account::new(
+                anonymous.id.clone(),
+                "google".to_string(),
+                "g-1".to_string(),
+            ))
+            .await
+            .unwrap();
+        let response = link(
+            &plugin,
+            &token,
+            serde_json::json!({ "provider": "google", "name": "Jane" }),
+        )
+        .await;
+        assert_eq!(response.status, 200);
+        let user = storage
+            .get_user_by_id(&anonymous.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!user.is_anonymous());
+        assert_eq!(user.name.as_deref(), Some("Jane"));
+    }
+
+    #[tokio::test]
+    async fn test_link_account_uses_registered_password_plugin() {
+        use better_auth_plugin_password::PasswordConfig;
+
+        let password = PasswordPlugin::new(PasswordConfig::new().min_length(24));
+        let (_, plugin, _, token) = link_setup(AnonymousConfig::new()).await;
+        let plugin = plugin.with_password(&password);
+
+        let response = link(
+            &plugin,
+            &token,
+            serde_json::json!({ "email": "jane@example.com", "password": "correct horse battery" }),
+        )
+        .await;
+        assert_eq!(response.status, 422);
+        assert_eq!(response.body.unwrap()["error"]["code"], "WEAK_PASSWORD");
+    }
+
+    #[tokio::test]
+    async fn test_link_account_email_conflict() {
+        use better_auth_core::traits::StorageAdapter;
+        use better_auth_plugin_password::PasswordExt;
+
+        let mut existing = User::new("user_1".to_string(), "jane@example.com".to_string());
+        existing.set_password_hash(
+            better_auth_plugin_password::PasswordPlugin::default()
+                .hash_password("correct horse battery"),
+        );
+        let body =
+            serde_json::json!({ "email": "jane@example.com", "password": "correct horse battery" });
+
+        let wrong =
+            serde_json::json!({ "email": "jane@example.com", "password": "wrong password" });
+
+        // Only the account's owner learns that the email is taken.
+        let (storage, plugin, anonymous, token) = link_setup(AnonymousConfig::new()).await;
+        storage.create_user(&existing).await.unwrap();
+        let response = link(&plugin, &token, wrong.clone()).await;
+        assert_eq!(response.status, 401);
+        assert_eq!(
+            response.body.unwrap()["error"]["code"],
+            "INVALID_CREDENTIALS"
+        );
+        let response = link(&plugin, &token, body.clone()).await;
+        assert_eq!(response.status, 409);
+        assert_eq!(response.body.unwrap()["error"]["code"], "EMAIL_EXISTS");
+        assert!(
+            storage
+                .get_user_by_id(&anonymous.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .is_anonymous()
+        );
+
+        let linked = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let on_link = linked.clone();
+        let config = AnonymousConfig::new()
+            .link_conflict_strategy(LinkConflictStrategy::SignInExisting)
+            .on_link_account(move |anonymous, user| {
+                let on_link = on_link.clone();
+                async move {
+                    *on_link.lock().unwrap() = Some((anonymous.id, user.id));
+                }
+            });
+        let (storage, plugin, anonymous, token) = link_setup(config).await;
+        storage.create_user(&existing).await.unwrap();
+
+        assert_eq!(link(&plugin, &token, wrong).await.status, 401);
+
+        let response = link(&plugin, &token, body).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.unwrap()["user"]["id"], "user_1");
+        assert!(
+            storage
+                .get_user_by_id(&anonymous.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            *linked.lock().unwrap(),
+            Some((anonymous.id.clone(), "user_1".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_expired() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::events::EventBus;
+        use better_auth_core::traits::StorageAdapter;
+        use better_auth_core::types::{Account, Session};
+
+        let storage = std::sync::Arc::new(MemoryAdapter::new());
+        let anonymous = |id: &str| {
+            let mut user = User::new(id.to_string(), format!("temp@{}.com", id));
+            user.set_anonymous(true);
+            user
+        };
+        storage.create_user(&anonymous("expired")).await.unwrap();
+        storage
+            .create_session(&Session::new("expired".to_string()))
+            .await
+            .unwrap();
+        // Started an OAuth link but never finished upgrading.
+        storage.create_user(&anonymous("oauth")).await.unwrap();
+        storage
+            .create_account(&Account::new(
+                "oauth".to_string(),
+                "google".to_string(),
+                "g-1".to_string(),
+            ))
+            .await
+            .unwrap();
+        let mut with_password = anonymous("password");
+        with_password.set_password_hash("$argon2id$v=19$hash");
+        storage.create_user(&with_password).await.unwrap();
+        storage
+            .create_user(&User::new(
+                "permanent".to_string(),
+                "jane@example.com".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        let bus = std::sync::Arc::new(EventBus::new());
+        let plugin = AnonymousPlugin::new(AnonymousConfig::new().event_bus(bus.clone()));
+        let ctx = AuthContext::new(storage.clone());
+
+        assert_eq!(
+            plugin
+                .cleanup_expired(&ctx, Duration::hours(1))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            plugin
+                .cleanup_expired(&ctx, Duration::zero())
+                .await
+                .unwrap(),
+            1
+        );
+
+        assert!(storage.get_user_by_id("expired").await.unwrap().is_none());
+        assert!(
+            storage
+                .get_sessions_by_user_id("expired")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        for id in ["oauth", "password", "permanent"] {
+            assert!(storage.get_user_by_id(id).await.unwrap().is_some(), "{id}");
+        }
+        let events = bus.events_of_type("anonymous.deleted").await;
+        assert_eq!(events.len(), 1);
+    }
 }
@@ -1,5 +1,7 @@
 //! Configuration for the API Key plugin.
 
+use crate::ApiKeyStore;
+use better_auth_core::traits::StorageAdapter;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -96,10 +98,31 @@ impl Default for StartingCharactersConfig {
 }
 
 /// Permissions configuration.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct PermissionsConfig {
     /// Default permissions for new API keys.
     pub default_permissions: Option<HashMap<String, Vec<String>>>,
+    /// Scopes given to new API keys that request none.
+    pub default_scopes: Option<Vec<String>>,
+    /// Maximum number of scopes a single key may be granted.
+    pub max_scopes: usize,
+    /// Scopes users may request for their own keys.
+    ///
+    /// Each requested scope must be covered by one of these, with the access
+    /// plugin's wildcard matching, or the key is refused with 403. Empty by
+    /// default, so users only get the `default_scopes`.
+    pub grantable_scopes: Vec<String>,
+}
+
+impl Default for PermissionsConfig {
+    fn default() -> Self {
+        Self {
+            default_permissions: None,
+            default_scopes: None,
+            max_scopes: 32,
+            grantable_scopes: Vec::new(),
+        }
+    }
 }
 
 /// Configuration for the API Key plugin.
@@ -127,6 +150,12 @@ pub struct ApiKeyConfig {
     pub key_expiration: KeyExpirationConfig,
     /// Rate limit configuration.
     pub rate_limit: RateLimitConfig,
+    /// Usage budget given to every new key. Users can't choose their own;
+    /// `None` leaves keys unlimited.
+    pub default_remaining: Option<i32>,
+    /// Refill, as an amount and an interval in milliseconds, given to every
+    /// new key that has a budget.
+    pub default_refill: Option<(i32, i64)>,
     /// Whether to enable sessions from API keys.
     pub enable_session_for_api_keys: bool,
     /// Storage mode.
@@ -137,10 +166,15 @@ pub struct ApiKeyConfig {
     pub starting_characters_config: StartingCharactersConfig,
     /// Permissions configuration.
     pub permissions: PermissionsConfig,
-    /// Whether to disable key hashing.
+    /// Ignored: keys are always stored hashed.
     pub disable_key_hashing: bool,
     /// Whether to defer non-critical updates.
     pub defer_updates: bool,
+    /// Store for API keys. Without one, the create and verify routes
+    /// return placeholder responses.
+    pub key_store: Option<Arc<dyn ApiKeyStore>>,
+    /// Storage adapter used to resolve the session creating a key.
+    pub session_storage: Option<Arc<dyn StorageAdapter>>,
 }
 
 impl Default for ApiKeyConfig {
@@ -157,6 +191,8 @@ impl Default for ApiKeyConfig {
             enable_metadata: true,
             key_expiration: KeyExpirationConfig::default(),
             rate_limit: RateLimitConfig::default(),
+            default_remaining: None,
+            default_refill: None,
             enable_session_for_api_keys: false,
             storage: StorageMode::Database,
             fallback_to_database: false,
@@ -164,6 +200,8 @@ impl Default for ApiKeyConfig {
             permissions: PermissionsConfig::default(),
             disable_key_hashing: false,
             defer_updates: false,
+            key_store: None,
+            session_storage: None,
         }
     }
 }
@@ -210,6 +248,18 @@ impl ApiKeyConfig {
         self
     }
 
+    /// Gives every new key a budget of `remaining` uses.
+    pub fn default_remaining(mut self, remaining: i32) -> Self {
+        self.default_remaining = Some(remaining);
+        self
+    }
+
+    /// Refills the budget of every new key to `amount` each `interval_ms`.
+    pub fn default_refill(mut self, amount: i32, interval_ms: i64) -> Self {
+        self.default_refill = Some((amount, interval_ms));
+        self
+    }
+
     /// Enables sessions from API keys.
     pub fn enable_session_for_api_keys(mut self) -> Self {
         self.enable_session_for_api_keys = true;
@@ -228,7 +278,7 @@ impl ApiKeyConfig {
         self
     }
 
-    /// Disables key hashing (not recommended).
+    /// Does nothing: keys are always stored hashed.
     pub fn disable_key_hashing(mut self) -> Self {
         self.disable_key_hashing = true;
         self
@@ -240,11 +290,41 @@ impl ApiKeyConfig {
         self
     }
 
+    /// Sets the store for API keys.
+    pub fn key_store(mut self, store: Arc<dyn ApiKeyStore>) -> Self {
+        self.key_store = Some(store);
+        self
+    }
+
+    /// Sets the storage adapter used to resolve sessions.
+    pub fn session_storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.session_storage = Some(storage);
+        self
+    }
+
     /// Sets default permissions.
     pub fn default_permissions(mut self, permissions: HashMap<String, Vec<String>>) -> Self {
         self.permissions.default_permissions = Some(permissions);
         self
     }
+
+    /// Sets default scopes.
+    pub fn default_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.permissions.default_scopes = Some(scopes);
+        self
+    }
+
+    /// Sets the maximum number of scopes per key.
+    pub fn max_scopes(mut self, max: usize) -> Self {
+        self.permissions.max_scopes = max;
+        self
+    }
+
+    /// Sets the scopes users may request for their own keys.
+    pub fn grantable_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.permissions.grantable_scopes = scopes;
+        self
+    }
 }
 
 impl std::fmt::Debug for ApiKeyConfig {
@@ -256,9 +336,14 @@ impl std::fmt::Debug for ApiKeyConfig {
             .field("require_name", &self.require_name)
             .field("enable_metadata", &self.enable_metadata)
             .field("rate_limit", &self.rate_limit)
+            .field("default_remaining", &self.default_remaining)
+            .field("default_refill", &self.default_refill)
             .field("enable_session_for_api_keys", &self.enable_session_for_api_keys)
             .field("storage", &self.storage)
+            .field("permissions", &self.permissions)
             .field("disable_key_hashing", &self.disable_key_hashing)
+            .field("key_store", &self.key_store.is_some())
+            .field("session_storage", &self.session_storage.is_some())
             .finish()
     }
 }
@@ -1,11 +1,25 @@
 //! API key generation utilities.
+//!
+//! Keys look like `{prefix}{identifier}_{secret}`. Everything before the last
+//! `_` is the key's lookup prefix: it is stored in plain text so a presented
+//! key can be found without scanning every row, while only a hash of the
+//! whole key is stored.
 
 use rand::Rng;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Alphanumeric characters without ambiguous ones. Contains no `_`, so the
+/// last `_` of a key always separates the lookup prefix from the secret.
+const CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghjkmnpqrstuvwxyz23456789";
+
+/// Length of the random identifier that makes lookup prefixes unique.
+const IDENTIFIER_LENGTH: usize = 8;
 
 /// API key generator.
 #[derive(Debug, Clone)]
 pub struct ApiKeyGenerator {
-    /// Key length (not including prefix).
+    /// Length of the secret part of the key.
     length: usize,
     /// Optional prefix.
     prefix: Option<String>,
@@ -19,37 +33,24 @@ impl ApiKeyGenerator {
 
     /// Generates a new API key.
     pub fn generate(&self) -> String {
-        let mut rng = rand::thread_rng();
-        // Use alphanumeric without ambiguous characters
-        let charset: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghjkmnpqrstuvwxyz23456789";
-        
-        let key: String = (0..self.length)
-            .map(|_| {
-                let idx = rng.gen_range(0..charset.len());
-                charset[idx] as char
-            })
-            .collect();
-
-        if let Some(ref prefix) = self.prefix {
-            format!("{}{}", prefix, key)
-        } else {
-            key
-        }
+        self.generate_with_prefix(self.prefix.as_deref().unwrap_or(""))
     }
 
     /// Generates a key with a custom prefix.
     pub fn generate_with_prefix(&self, prefix: &str) -> String {
-        let mut rng = rand::thread_rng();
-        let charset: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghjkmnpqrstuvwxyz23456789";
-        
-        let key: String = (0..self.length)
-            .map(|_| {
-                let idx = rng.gen_range(0..charset.len());
-                charset[idx] as char
-            })
-            .collect();
-
-        format!("{}{}", prefix, key)
+        format!(
+            "{}{}_{}",
+            prefix,
+            random_string(IDENTIFIER_LENGTH),
+            random_string(self.length)
+        )
+    }
+
+    /// Returns the lookup prefix of a key, or `None` if it is malformed.
+    pub fn key_prefix(key: &str) -> Option<&str> {
+        key.rsplit_once('_')
+            .filter(|(prefix, secret)| !prefix.is_empty() && !secret.is_empty())
+            .map(|(prefix, _)| prefix)
     }
 
     /// Extracts the starting characters from a key.
@@ -57,21 +58,20 @@ impl ApiKeyGenerator {
         key.chars().take(length).collect()
     }
 
-    /// Hashes an API key for storage.
-    /// 
-    /// Note: In production, use a proper hashing algorithm like SHA-256 or Argon2.
+    /// Hashes an API key for storage, as hex encoded SHA-256.
+    ///
+    /// Keys are long and random, so an unsalted fast hash is enough: there
+    /// is nothing to brute force.
     pub fn hash_key(key: &str) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        format!("{:x}", hasher.finish())
+        Sha256::digest(key.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
     }
 
-    /// Verifies a key against a hash.
+    /// Verifies a key against a hash in constant time.
     pub fn verify_key(key: &str, hash: &str) -> bool {
-        Self::hash_key(key) == hash
+        Self::hash_key(key).as_bytes().ct_eq(hash.as_bytes()).into()
     }
 }
 
@@ -81,6 +81,13 @@ impl Default for ApiKeyGenerator {
     }
 }
 
+fn random_string(length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,18 +96,31 @@ mod tests {
     fn test_key_generation() {
         let generator = ApiKeyGenerator::new(32, None);
         let key = generator.generate();
-        
-        assert_eq!(key.len(), 32);
-        assert!(key.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        let (identifier, secret) = key.split_once('_').unwrap();
+        assert_eq!(identifier.len(), IDENTIFIER_LENGTH);
+        assert_eq!(secret.len(), 32);
+        assert!(secret.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_eq!(ApiKeyGenerator::key_prefix(&key), Some(identifier));
     }
 
     #[test]
     fn test_key_with_prefix() {
         let generator = ApiKeyGenerator::new(32, Some("sk_live_".to_string()));
         let key = generator.generate();
-        
+
         assert!(key.starts_with("sk_live_"));
-        assert_eq!(key.len(), 32 + 8); // key + prefix
+        assert_eq!(key.len(), 8 + IDENTIFIER_LENGTH + 1 + 32);
+        let prefix = ApiKeyGenerator::key_prefix(&key).unwrap();
+        assert!(prefix.starts_with("sk_live_"));
+        assert_eq!(prefix.len(), 8 + IDENTIFIER_LENGTH);
+    }
+
+    #[test]
+    fn test_key_prefix_of_malformed_keys() {
+        assert_eq!(ApiKeyGenerator::key_prefix("nounderscore"), None);
+        assert_eq!(ApiKeyGenerator::key_prefix("_secret"), None);
+        assert_eq!(ApiKeyGenerator::key_prefix("prefix_"), None);
     }
 
     #[test]
@@ -113,16 +133,19 @@ mod tests {
     fn test_key_hashing() {
         let key = "my_secret_key";
         let hash = ApiKeyGenerator::hash_key(key);
-        
+
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, key);
         assert!(ApiKeyGenerator::verify_key(key, &hash));
         assert!(!ApiKeyGenerator::verify_key("wrong_key", &hash));
+        assert!(!ApiKeyGenerator::verify_key(key, &hash[..63]));
     }
 
     #[test]
     fn test_uniqueness() {
         let generator = ApiKeyGenerator::default();
         let keys: Vec<String> = (0..100).map(|_| generator.generate()).collect();
-        
+
         let unique: std::collections::HashSet<_> = keys.iter().collect();
         assert_eq!(unique.len(), keys.len());
     }
@@ -1,46 +1,244 @@
 //! Request handlers for the API Key plugin.
 
+use crate::{ApiKey, ApiKeyConfig, ApiKeyGenerator, ApiKeyStore, UsageDenied};
 use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
 use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_core::session::SessionResolver;
+use better_auth_plugin_access::Permission;
+use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Request body for creating an API key.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct CreateApiKeyRequest {
     pub name: Option<String>,
     #[serde(rename = "expiresIn")]
     pub expires_in: Option<u64>,
     pub prefix: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    pub scopes: Option<Vec<String>>,
+    pub remaining: Option<i32>,
+    #[serde(rename = "refillAmount")]
+    pub refill_amount: Option<i32>,
+    #[serde(rename = "refillInterval")]
+    pub refill_interval: Option<i64>,
 }
 
 /// Handler for POST /api-key/create
-pub struct CreateApiKeyHandler;
+///
+/// The plaintext key is in this response only; just its hash is stored.
+pub struct CreateApiKeyHandler {
+    pub config: ApiKeyConfig,
+    pub generator: ApiKeyGenerator,
+}
 
 #[async_trait]
 impl RequestHandler for CreateApiKeyHandler {
     async fn handle(&self, req: Request) -> Response {
         let body: Option<CreateApiKeyRequest> = req.json();
-        
+
+        let Some(key_store) = &self.config.key_store else {
+            return key_store_not_configured();
+        };
+        let Some(storage) = &self.config.session_storage else {
+            return Response::new(503).json(serde_json::json!({
+                "error": {
+                    "code": "SESSION_STORAGE_NOT_CONFIGURED",
+                    "message": "No session storage is configured for API keys"
+                }
+            }));
+        };
+        let body = body.unwrap_or_default();
+
+        let session = match SessionResolver::new(storage.clone()).resolve_request(&req).await {
+            Ok(Some(resolved)) => resolved.session,
+            Ok(None) => return error_response(AuthError::SessionNotFound),
+            Err(err) => return error_response(err),
+        };
+
+        let (key, api_key) = match self.new_api_key(&session.user_id, body) {
+            Ok(created) => created,
+            Err(err) => return error_response(err),
+        };
+        let api_key = match key_store.create_api_key(&api_key).await {
+            Ok(api_key) => api_key,
+            Err(err) => return error_response(err),
+        };
+
         Response::ok().json(serde_json::json!({
-            "id": "key_placeholder",
-            "key": "sk_live_placeholder_key",
-            "name": body.and_then(|b| b.name),
-            "createdAt": "2024-01-01T00:00:00Z"
+            "id": api_key.id,
+            "key": key,
+            "name": api_key.name,
+            "start": api_key.start,
+            "prefix": api_key.prefix,
+            "userId": api_key.user_id,
+            "scopes": api_key.get_scopes(),
+            "remaining": api_key.remaining,
+            "refillAmount": api_key.refill_amount,
+            "refillInterval": api_key.refill_interval,
+            "expiresAt": api_key.expires_at,
+            "createdAt": api_key.created_at
         }))
     }
 }
 
+impl CreateApiKeyHandler {
+    /// Generates a key for `user_id`, returning it with the record to store.
+    fn new_api_key(&self, user_id: &str, body: CreateApiKeyRequest) -> AuthResult<(String, ApiKey)> {
+        let config = &self.config;
+        if config.require_name && body.name.as_deref().is_none_or(str::is_empty) {
+            return Err(AuthError::MissingField { field: "name".to_string() });
+        }
+
+        let prefix = body.prefix.or_else(|| config.default_prefix.clone());
+        if let Some(prefix) = &prefix {
+            let too_short = config.minimum_prefix_length.is_some_and(|min| prefix.len() < min);
+            let too_long = config.maximum_prefix_length.is_some_and(|max| prefix.len() > max);
+            if too_short || too_long || !prefix.chars().all(|c| c.is_ascii_graphic()) {
+                return Err(AuthError::InvalidField {
+                    field: "prefix".to_string(),
+                    reason: "prefix has an invalid length or characters".to_string(),
+                });
+            }
+        }
+
+        let expiration = &config.key_expiration;
+        if body.expires_in.is_some() && expiration.disable_custom_expires_time {
+            return Err(AuthError::InvalidField {
+                field: "expiresIn".to_string(),
+                reason: "custom expiration times are disabled".to_string(),
+            });
+        }
+        let expires_in = body.expires_in.or(expiration.default_expires_in);
+        if let Some(expires_in) = expires_in
+            && (expiration.min_expires_in.is_some_and(|min| expires_in < min)
+                || expiration.max_expires_in.is_some_and(|max| expires_in > max))
+        {
+            return Err(AuthError::InvalidField {
+                field: "expiresIn".to_string(),
+                reason: "expiration is outside the allowed range".to_string(),
+            });
+        }
+
+        let requested = body.scopes.is_some();
+        let scopes = body.scopes.or_else(|| config.permissions.default_scopes.clone());
+        if let Some(scopes) = &scopes {
+            if scopes.len() > config.permissions.max_scopes {
+                return Err(AuthError::InvalidField {
+                    field: "scopes".to_string(),
+                    reason: format!("at most {} scopes may be granted", config.permissions.max_scopes),
+                });
+            }
+            let malformed = scopes
+                .iter()
+                .map(|s| Permission::parse(s))
+                .any(|p| p.resource.is_empty() || p.action.is_empty());
+            if malformed {
+                return Err(AuthError::InvalidField {
+                    field: "scopes".to_string(),
+                    reason: "scopes must look like resource:action".to_string(),
+                });
+            }
+            // Only the operator's default scopes are trusted as-is.
+            let grantable: Vec<Permission> = config
+                .permissions
+                .grantable_scopes
+                .iter()
+                .map(|s| Permission::parse(s))
+                .collect();
+            if requested
+                && let Some(denied) = scopes.iter().find(|scope| {
+                    let target = Permission::parse(scope);
+                    !grantable.iter().any(|g| covers(g, &target))
+                })
+            {
+                return Err(AuthError::forbidden(format!(
+                    "The '{}' scope can't be granted",
+                    denied
+                )));
+            }
+        }
+
+        // Otherwise a user could lift their own usage limits.
+        if body.remaining.is_some()
+            || body.refill_amount.is_some()
+            || body.refill_interval.is_some()
+        {
+            return Err(AuthError::forbidden("Usage limits are set by the server"));
+        }
+
+        let key = self.generator.generate_with_prefix(prefix.as_deref().unwrap_or(""));
+        let mut api_key = ApiKey::new(user_id, &key);
+        api_key.name = body.name;
+        api_key.prefix = prefix;
+        if config.starting_characters_config.should_store {
+            api_key.start = Some(ApiKeyGenerator::extract_start(
+                &key,
+                config.starting_characters_config.characters_length,
+            ));
+        }
+        if let Some(expires_in) = expires_in {
+            api_key.expires_at = Some(Utc::now() + Duration::seconds(expires_in as i64));
+        }
+        if config.enable_metadata
+            && let Some(metadata) = body.metadata
+        {
+            api_key = api_key.with_metadata(metadata);
+        }
+        if let Some(permissions) = &config.permissions.default_permissions {
+            api_key = api_key.with_permissions(permissions.clone());
+        }
+        if let Some(scopes) = scopes {
+            api_key = api_key.with_scopes(scopes);
+        }
+        api_key.remaining = config.default_remaining;
+        if api_key.remaining.is_some()
+            && let Some((amount, interval)) = config.default_refill
+        {
+            api_key = api_key.with_refill(amount, interval);
+        }
+        Ok((key, api_key))
+    }
+}
+
+/// Checks that `granted` includes everything `requested` does.
+///
+/// Unlike [`Permission::matches`], a wildcard only counts on the granted
+/// side, so `post:*` covers `post:create` but `post:create` doesn't cover
+/// `*:*`.
+fn covers(granted: &Permission, requested: &Permission) -> bool {
+    let part = |g: &str, r: &str| g == "*" || g == r;
+    part(&granted.resource, &requested.resource)
+        && part(&granted.action, &requested.action)
+        && match (&granted.scope, &requested.scope) {
+            (None, _) => true,
+            (Some(g), Some(r)) => part(g, r),
+            (Some(_), None) => false,
+        }
+}
+
 /// Request body for verifying an API key.
 #[derive(Debug, Deserialize)]
 pub struct VerifyApiKeyRequest {
     pub key: String,
     pub permissions: Option<HashMap<String, Vec<String>>>,
+    /// A scope the key must grant, such as `post:create`.
+    pub scope: Option<String>,
 }
 
 /// Handler for POST /api-key/verify
-pub struct VerifyApiKeyHandler;
+///
+/// The key is found by its lookup prefix, then compared by hash in constant
+/// time. A valid key lacking the requested `scope` or `permissions` is
+/// rejected with 403, and one whose rate limit or usage budget is exhausted
+/// with 429. Otherwise the verification counts as one use of the key,
+/// recorded atomically through [`ApiKeyStore::record_api_key_use`].
+pub struct VerifyApiKeyHandler {
+    pub key_store: Option<Arc<dyn ApiKeyStore>>,
+}
 
 #[async_trait]
 impl RequestHandler for VerifyApiKeyHandler {
@@ -59,17 +257,121 @@ impl RequestHandler for VerifyApiKeyHandler {
             }));
         }
 
+        let Some(key_store) = &self.key_store else {
+            return key_store_not_configured();
+        };
+
+        let api_key = match find_key(key_store.as_ref(), &body.key).await {
+            Ok(Some(api_key)) => api_key,
+            Ok(None) => return invalid_key("INVALID_API_KEY", "Invalid API key"),
+            Err(err) => return error_response(err),
+        };
+        if !api_key.enabled {
+            return invalid_key("KEY_DISABLED", "API key is disabled");
+        }
+        if api_key.is_expired() {
+            return invalid_key("KEY_EXPIRED", "API key has expired");
+        }
+        let permitted = body.scope.as_deref().is_none_or(|scope| api_key.has_scope(scope))
+            && body
+                .permissions
+                .as_ref()
+                .is_none_or(|required| api_key.has_permissions(required));
+        if !permitted {
+            return Response::forbidden().json(serde_json::json!({
+                "valid": false,
+                "error": {
+                    "code": "INSUFFICIENT_PERMISSIONS",
+                    "message": "API key lacks the required permissions"
+                },
+                "key": null
+            }));
+        }
+        let api_key = match key_store.record_api_key_use(&api_key.id).await {
+            Ok(Ok(api_key)) => api_key,
+            Ok(Err(UsageDenied::RateLimited { retry_after_ms })) => {
+                return Response::new(429).json(serde_json::json!({
+                    "valid": false,
+                    "error": {
+                        "code": "RATE_LIMITED",
+                        "message": "API key rate limit exceeded",
+                        "retryAfterMs": retry_after_ms
+                    },
+                    "key": null
+                }));
+            }
+            Ok(Err(UsageDenied::QuotaExhausted)) => {
+                return Response::new(429).json(serde_json::json!({
+                    "valid": false,
+                    "error": {
+                        "code": "USAGE_EXCEEDED",
+                        "message": "API key has no remaining uses"
+                    },
+                    "key": null
+                }));
+            }
+            Err(err) => return error_response(err),
+        };
+
         Response::ok().json(serde_json::json!({
             "valid": true,
             "error": null,
             "key": {
-                "id": "key_placeholder",
-                "userId": "user_placeholder"
+                "id": api_key.id,
+                "userId": api_key.user_id,
+                "name": api_key.name,
+                "start": api_key.start,
+                "prefix": api_key.prefix,
+                "permissions": api_key.get_permissions(),
+                "scopes": api_key.get_scopes(),
+                "remaining": api_key.remaining,
+                "metadata": api_key.get_metadata(),
+                "expiresAt": api_key.expires_at
             }
         }))
     }
 }
 
+/// Finds the stored key matching `key` by its lookup prefix and hash.
+pub(crate) async fn find_key(key_store: &dyn ApiKeyStore, key: &str) -> AuthResult<Option<ApiKey>> {
+    let Some(key_prefix) = ApiKeyGenerator::key_prefix(key) else {
+        return Ok(None);
+    };
+    let candidates = key_store.get_api_keys_by_prefix(key_prefix).await?;
+    Ok(candidates.into_iter().find(|k| k.matches(key)))
+}
+
+/// A 503 response for a route that needs the unset `key_store`.
+fn key_store_not_configured() -> Response {
+    Response::new(503).json(serde_json::json!({
+        "error": {
+            "code": "KEY_STORE_NOT_CONFIGURED",
+            "message": "No API key store is configured"
+        }
+    }))
+}
+
+/// A 401 response for a key that failed verification.
+fn invalid_key(code: &str, message: &str) -> Response {
+    Response::unauthorized().json(serde_json::json!({
+        "valid": false,
+        "error": { "code": code, "message": message },
+        "key": null
+    }))
+}
+
+fn error_response(err: AuthError) -> Response {
+    let code = match &err {
+        AuthError::SessionNotFound => "SESSION_NOT_FOUND",
+        AuthError::MissingField { .. } => "MISSING_FIELD",
+        AuthError::InvalidField { .. } => "INVALID_FIELD",
+        _ => err.error_code(),
+    };
+    Response::new(err.status_code()).json(serde_json::json!({
+        "error": { "code": code, "message": err.to_string() }
+    }))
+}
+
 /// Handler for GET /api-key/get
 pub struct GetApiKeyHandler;
 
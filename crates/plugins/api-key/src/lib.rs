@@ -7,30 +7,34 @@
 //!
 //! - Create, manage, and verify API keys
 //! - Built-in rate limiting
-//! - Custom expiration times, remaining count, and refill systems
+//! - Custom expiration times
+//! - Usage budgets (`remaining`) that refill over time
 //! - Metadata for API keys
 //! - Custom prefix
 //! - Sessions from API keys
-//! - Permissions support
+//! - Permissions and wildcard scopes, such as `post:*`
 
 mod config;
 mod schema;
 mod handlers;
 mod generator;
 mod rate_limit;
+mod store;
 
 pub use config::{ApiKeyConfig, RateLimitConfig, StorageMode};
-pub use schema::{ApiKey, ApiKeySchema};
+pub use schema::{ApiKey, ApiKeySchema, UsageDenied};
 pub use generator::ApiKeyGenerator;
 pub use rate_limit::ApiKeyRateLimiter;
+pub use store::{ApiKeyStore, InMemoryApiKeyStore};
 
 use async_trait::async_trait;
 use better_auth_core::context::AuthContext;
 use better_auth_core::error::AuthResult;
 use better_auth_core::router::{Method, Route, Router};
 use better_auth_core::schema::SchemaBuilder;
+use better_auth_core::session::ApiKeyLookup;
 use better_auth_core::traits::{AuthPlugin, SchemaProvider};
-use better_auth_core::types::User;
+use better_auth_core::types::{Session, User};
 use better_auth_events_sdk::{EventDefinition, EventProvider};
 
 /// The API Key authentication plugin.
@@ -62,6 +66,34 @@ impl ApiKeyPlugin {
     }
 }
 
+/// Resolves API keys to sessions for their owners when
+/// `enable_session_for_api_keys` is set.
+///
+/// Each successful lookup records a use of the key, so its rate limit and
+/// usage budget apply; a key that is out of either resolves to no session.
+#[async_trait]
+impl ApiKeyLookup for ApiKeyPlugin {
+    async fn lookup(&self, key: &str) -> AuthResult<Option<Session>> {
+        let Some(key_store) = &self.config.key_store else {
+            return Ok(None);
+        };
+        if !self.config.enable_session_for_api_keys {
+            return Ok(None);
+        }
+        let Some(api_key) = handlers::find_key(key_store.as_ref(), key).await? else {
+            return Ok(None);
+        };
+        if !api_key.is_valid() {
+            return Ok(None);
+        }
+        Ok(key_store
+            .record_api_key_use(&api_key.id)
+            .await?
+            .ok()
+            .map(|api_key| Session::new(api_key.user_id)))
+    }
+}
+
 impl Default for ApiKeyPlugin {
     fn default() -> Self {
         Self::new(ApiKeyConfig::default())
@@ -123,7 +155,14 @@ impl AuthPlugin for ApiKeyPlugin {
     fn register_routes(&self, router: &mut Router) {
         // POST /api-key/create
         router.route(
-            Route::new(Method::POST, "/api-key/create", handlers::CreateApiKeyHandler)
+            Route::new(
+                Method::POST,
+                "/api-key/create",
+                handlers::CreateApiKeyHandler {
+                    config: self.config.clone(),
+                    generator: self.generator.clone(),
+                },
+            )
                 .summary("Create API key")
                 .description("Creates a new API key for the authenticated user.")
                 .tag("api-key")
@@ -132,7 +171,13 @@ impl AuthPlugin for ApiKeyPlugin {
 
         // POST /api-key/verify
         router.route(
-            Route::new(Method::POST, "/api-key/verify", handlers::VerifyApiKeyHandler)
+            Route::new(
+                Method::POST,
+                "/api-key/verify",
+                handlers::VerifyApiKeyHandler {
+                    key_store: self.config.key_store.clone(),
+                },
+            )
                 .summary("Verify API key")
                 .description("Verifies an API key and optionally checks permissions.")
                 .tag("api-key"),
@@ -186,11 +231,27 @@ impl AuthPlugin for ApiKeyPlugin {
     async fn on_after_signup(&self, _ctx: &AuthContext, _user: &User) -> AuthResult<()> {
         Ok(())
     }
+
+    async fn export_user_data(&self, _ctx: &AuthContext, user_id: &str) -> AuthResult<serde_json::Value> {
+        let Some(key_store) = &self.config.key_store else {
+            return Ok(serde_json::Value::Null);
+        };
+        let keys = key_store.get_api_keys_by_user_id(user_id).await?;
+        Ok(serde_json::json!({ "api_key": keys }))
+    }
+
+    async fn on_before_user_delete(&self, _ctx: &AuthContext, user_id: &str) -> AuthResult<()> {
+        match &self.config.key_store {
+            Some(key_store) => key_store.delete_api_keys_by_user_id(user_id).await,
+            None => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
 
     #[test]
     fn test_plugin_creation() {
@@ -202,7 +263,8 @@ mod tests {
     fn test_key_generation() {
         let plugin = ApiKeyPlugin::default();
         let key = plugin.generate_key();
-        assert_eq!(key.len(), 64);
+        assert_eq!(ApiKeyGenerator::key_prefix(&key).unwrap().len(), 8);
+        assert_eq!(key.len(), 8 + 1 + 64);
     }
 
     #[test]
@@ -212,4 +274,232 @@ mod tests {
         let key = plugin.generate_key();
         assert!(key.starts_with("sk_live_"));
     }
+
+    /// Returns the routes of a plugin with in-memory storage, the key
+    /// store and a session token for `user_1`.
+    async fn setup(config: ApiKeyConfig) -> (Router, Arc<InMemoryApiKeyStore>, String) {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::traits::StorageAdapter;
+        use better_auth_core::types::Session;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        storage.create_user(&user).await.unwrap();
+        let session = storage.create_session(&Session::new(user.id.clone())).await.unwrap();
+
+        let key_store = Arc::new(InMemoryApiKeyStore::new());
+        let plugin = ApiKeyPlugin::new(config.key_store(key_store.clone()).session_storage(storage));
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        (router, key_store, session.token)
+    }
+
+    async fn post(router: &Router, path: &str, token: Option<&str>, body: serde_json::Value) -> better_auth_core::router::Response {
+        use better_auth_core::router::Request;
+
+        let route = router.routes().find(|r| r.path == path).unwrap();
+        let mut req = Request::new(Method::POST, path);
+        if let Some(token) = token {
+            req.headers.insert("authorization".to_string(), format!("Bearer {}", token));
+        }
+        req.body = Some(body);
+        route.handler.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_key_is_stored_hashed() {
+        let (router, key_store, token) = setup(ApiKeyConfig::new().default_prefix("sk_live_")).await;
+
+        let response = post(&router, "/api-key/create", Some(&token), serde_json::json!({ "name": "CI" })).await;
+        assert_eq!(response.status, 200);
+        let body = response.body.unwrap();
+        let key = body["key"].as_str().unwrap().to_string();
+        assert!(key.starts_with("sk_live_"));
+        assert_eq!(body["userId"], "user_1");
+
+        let prefix = ApiKeyGenerator::key_prefix(&key).unwrap();
+        let stored = key_store.get_api_keys_by_prefix(prefix).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].hashed_key, ApiKeyGenerator::hash_key(&key));
+        assert!(!serde_json::to_string(&stored[0]).unwrap().contains(&key));
+
+        let response = post(&router, "/api-key/verify", None, serde_json::json!({ "key": key })).await;
+        assert_eq!(response.status, 200);
+        let body = response.body.unwrap();
+        assert_eq!(body["valid"], true);
+        assert_eq!(body["key"]["id"], stored[0].id);
+        assert_eq!(body["key"]["name"], "CI");
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_wrong_keys() {
+        let (router, _, token) = setup(ApiKeyConfig::new()).await;
+        let response = post(&router, "/api-key/create", Some(&token), serde_json::json!({})).await;
+        let key = response.body.unwrap()["key"].as_str().unwrap().to_string();
+
+        // Right prefix, wrong secret.
+        let (prefix, _) = key.rsplit_once('_').unwrap();
+        for wrong in [format!("{}_wrong", prefix), "unknown_key".to_string(), "malformed".to_string()] {
+            let response = post(&router, "/api-key/verify", None, serde_json::json!({ "key": wrong })).await;
+            assert_eq!(response.status, 401, "{wrong}");
+            let body = response.body.unwrap();
+            assert_eq!(body["valid"], false);
+            assert_eq!(body["error"]["code"], "INVALID_API_KEY");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_checks_scopes() {
+        let config = ApiKeyConfig::new().grantable_scopes(vec!["post:*".to_string()]);
+        let (router, _, token) = setup(config).await;
+        let body = serde_json::json!({ "scopes": ["post:*"] });
+        let response = post(&router, "/api-key/create", Some(&token), body).await;
+        let key = response.body.unwrap()["key"].as_str().unwrap().to_string();
+
+        let body = serde_json::json!({ "key": key, "scope": "post:create" });
+        let response = post(&router, "/api-key/verify", None, body).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.unwrap()["key"]["scopes"], serde_json::json!(["post:*"]));
+
+        let body = serde_json::json!({ "key": key, "scope": "user:delete" });
+        let response = post(&router, "/api-key/verify", None, body).await;
+        assert_eq!(response.status, 403);
+        let body = response.body.unwrap();
+        assert_eq!(body["valid"], false);
+        assert_eq!(body["error"]["code"], "INSUFFICIENT_PERMISSIONS");
+    }
+
+    #[tokio::test]
+    async fn test_create_limits_scopes() {
+        let (router, _, token) = setup(ApiKeyConfig::new().max_scopes(1)).await;
+        let body = serde_json::json!({ "scopes": ["post:read", "post:create"] });
+        let response = post(&router, "/api-key/create", Some(&token), body).await;
+        assert_eq!(response.status, 422);
+        assert_eq!(response.body.unwrap()["error"]["code"], "INVALID_FIELD");
+    }
+
+    #[tokio::test]
+    async fn test_create_refuses_ungrantable_scopes() {
+        let config = ApiKeyConfig::new().grantable_scopes(vec!["post:*".to_string()]);
+        let (router, key_store, token) = setup(config).await;
+        for scopes in [vec!["*:*"], vec!["user:delete"], vec!["post:read", "admin:*"]] {
+            let body = serde_json::json!({ "scopes": scopes });
+            let response = post(&router, "/api-key/create", Some(&token), body).await;
+            assert_eq!(response.status, 403, "{scopes:?}");
+            assert_eq!(response.body.unwrap()["error"]["code"], "FORBIDDEN");
+        }
+        assert!(key_store.get_api_keys_by_user_id("user_1").await.unwrap().is_empty());
+
+        let body = serde_json::json!({ "scopes": ["post:read"] });
+        let response = post(&router, "/api-key/create", Some(&token), body).await;
+        assert_eq!(response.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_usage_limits_are_set_by_the_server() {
+        let config = ApiKeyConfig::new()
+            .default_remaining(2)
+            .default_refill(2, 3_600_000)
+            .enable_session_for_api_keys();
+        let (router, key_store, token) = setup(config.clone()).await;
+
+        let body = serde_json::json!({ "remaining": 1_000_000 });
+        let response = post(&router, "/api-key/create", Some(&token), body).await;
+        assert_eq!(response.status, 403);
+
+        let response = post(&router, "/api-key/create", Some(&token), serde_json::json!({})).await;
+        let key = response.body.unwrap()["key"].as_str().unwrap().to_string();
+
+        // Verifying and authenticating with the key share the budget.
+        let response = post(&router, "/api-key/verify", None, serde_json::json!({ "key": key })).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.unwrap()["key"]["remaining"], 1);
+
+        let lookup = ApiKeyPlugin::new(config.key_store(key_store.clone()));
+        let session = lookup.lookup(&key).await.unwrap().unwrap();
+        assert_eq!(session.user_id, "user_1");
+        assert!(lookup.lookup(&key).await.unwrap().is_none());
+
+        let response = post(&router, "/api-key/verify", None, serde_json::json!({ "key": key })).await;
+        assert_eq!(response.status, 429);
+        assert_eq!(response.body.unwrap()["error"]["code"], "USAGE_EXCEEDED");
+
+        let prefix = ApiKeyGenerator::key_prefix(&key).unwrap();
+        let stored = key_store.get_api_keys_by_prefix(prefix).await.unwrap();
+        assert_eq!(stored[0].remaining, Some(0));
+        assert_eq!(stored[0].refill_amount, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_missing_key_store_rejects_keys() {
+        let plugin = ApiKeyPlugin::new(ApiKeyConfig::new());
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let response = post(&router, "/api-key/verify", None, serde_json::json!({ "key": "sk_live_any" })).await;
+        assert_eq!(response.status, 503);
+        assert_eq!(response.body.unwrap()["error"]["code"], "KEY_STORE_NOT_CONFIGURED");
+
+        let response = post(&router, "/api-key/create", None, serde_json::json!({})).await;
+        assert_eq!(response.status, 503);
+        assert_eq!(response.body.unwrap()["error"]["code"], "KEY_STORE_NOT_CONFIGURED");
+    }
+
+    #[tokio::test]
+    async fn test_create_requires_session() {
+        let (router, _, _) = setup(ApiKeyConfig::new()).await;
+        let response = post(&router, "/api-key/create", None, serde_json::json!({})).await;
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body.unwrap()["error"]["code"], "SESSION_NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_export_lists_keys_without_hashes() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::traits::StorageAdapter;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        storage.create_user(&user).await.unwrap();
+        let key_store = Arc::new(InMemoryApiKeyStore::new());
+        key_store.create_api_key(&ApiKey::new("user_1", "prefix_secret")).await.unwrap();
+        key_store.create_api_key(&ApiKey::new("user_2", "other_secret")).await.unwrap();
+
+        let plugins: Vec<Arc<dyn AuthPlugin>> =
+            vec![Arc::new(ApiKeyPlugin::new(ApiKeyConfig::new().key_store(key_store)))];
+        let mut builder = SchemaBuilder::with_core();
+        plugins[0].define_schema(&mut builder);
+        let ctx = AuthContext::new(storage);
+        let export = better_auth_core::export_user_data(&ctx, "user_1", &plugins, &builder.build())
+            .await
+            .unwrap();
+
+        let keys = export["plugins"]["api_key"]["api_key"].as_array().unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0]["key_prefix"], "prefix");
+        assert!(keys[0].get("hashed_key").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_deleting_user_deletes_their_keys() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::traits::StorageAdapter;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        storage.create_user(&user).await.unwrap();
+        let key_store = Arc::new(InMemoryApiKeyStore::new());
+        key_store.create_api_key(&ApiKey::new("user_1", "prefix_secret")).await.unwrap();
+        key_store.create_api_key(&ApiKey::new("user_2", "other_secret")).await.unwrap();
+
+        let plugin = ApiKeyPlugin::new(ApiKeyConfig::new().key_store(key_store.clone()));
+        let ctx = AuthContext::new(storage.clone());
+        better_auth_core::delete_user_fully(&ctx, "user_1", &[&plugin], None)
+            .await
+            .unwrap();
+
+        assert!(storage.get_user_by_id("user_1").await.unwrap().is_none());
+        assert!(key_store.get_api_keys_by_user_id("user_1").await.unwrap().is_empty());
+        assert_eq!(key_store.get_api_keys_by_user_id("user_2").await.unwrap().len(), 1);
+    }
 }
@@ -2,11 +2,28 @@
 
 use better_auth_core::schema::{Field, FieldType, IndexDefinition, ModelDefinition, ReferentialAction};
 use better_auth_core::traits::SchemaProvider;
-use chrono::{DateTime, Utc};
+use crate::ApiKeyGenerator;
+use better_auth_plugin_access::Permission;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Why [`ApiKey::record_use`] refused a use of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageDenied {
+    /// The key's rate limit window is full.
+    RateLimited {
+        /// Milliseconds until the window allows another request.
+        retry_after_ms: i64,
+    },
+    /// The key's `remaining` budget is used up until its next refill.
+    QuotaExhausted,
+}
+
 /// Represents an API key in the database.
+///
+/// A key can carry a usage budget (`remaining`) and a per-window rate
+/// limit; see [`ApiKey::record_use`] for how the two interact.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiKey {
     /// Unique identifier.
@@ -17,8 +34,10 @@ pub struct ApiKey {
     pub start: Option<String>,
     /// API key prefix.
     pub prefix: Option<String>,
-    /// The hashed API key.
-    pub key: String,
+    /// Hex encoded SHA-256 hash of the API key.
+    pub hashed_key: String,
+    /// The non-secret start of the key, used to look it up.
+    pub key_prefix: String,
     /// The user ID.
     pub user_id: String,
     /// Refill interval in milliseconds.
@@ -49,20 +68,24 @@ pub struct ApiKey {
     pub updated_at: DateTime<Utc>,
     /// Permissions (JSON).
     pub permissions: Option<String>,
+    /// Scopes such as `post:create` or `post:*` (JSON array).
+    pub scopes: Option<String>,
     /// Metadata (JSON).
     pub metadata: Option<String>,
 }
 
 impl ApiKey {
-    /// Creates a new API key.
-    pub fn new(user_id: impl Into<String>, key: impl Into<String>) -> Self {
+    /// Creates a record for the plaintext `key`, keeping only its hash and
+    /// lookup prefix.
+    pub fn new(user_id: impl Into<String>, key: &str) -> Self {
         let now = Utc::now();
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             name: None,
             start: None,
             prefix: None,
-            key: key.into(),
+            hashed_key: ApiKeyGenerator::hash_key(key),
+            key_prefix: ApiKeyGenerator::key_prefix(key).unwrap_or_default().to_string(),
             user_id: user_id.into(),
             refill_interval: None,
             refill_amount: None,
@@ -78,6 +101,7 @@ impl ApiKey {
             created_at: now,
             updated_at: now,
             permissions: None,
+            scopes: None,
             metadata: None,
         }
     }
@@ -112,6 +136,13 @@ impl ApiKey {
         self
     }
 
+    /// Refills `remaining` to `amount` every `interval_ms` milliseconds.
+    pub fn with_refill(mut self, amount: i32, interval_ms: i64) -> Self {
+        self.refill_amount = Some(amount);
+        self.refill_interval = Some(interval_ms);
+        self
+    }
+
     /// Sets rate limit configuration.
     pub fn with_rate_limit(mut self, time_window: i64, max_requests: i32) -> Self {
         self.rate_limit_enabled = true;
@@ -126,6 +157,12 @@ impl ApiKey {
         self
     }
 
+    /// Sets scopes.
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = Some(serde_json::to_string(&scopes).unwrap_or_default());
+        self
+    }
+
     /// Sets metadata.
     pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = Some(serde_json::to_string(&metadata).unwrap_or_default());
@@ -140,6 +177,14 @@ impl ApiKey {
             .unwrap_or_default()
     }
 
+    /// Gets scopes as a list.
+    pub fn get_scopes(&self) -> Vec<String> {
+        self.scopes
+            .as_ref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default()
+    }
+
     /// Gets metadata as a JSON value.
     pub fn get_metadata(&self) -> serde_json::Value {
         self.metadata
@@ -148,6 +193,11 @@ impl ApiKey {
             .unwrap_or(serde_json::Value::Null)
     }
 
+    /// Checks in constant time whether `key` is this API key.
+    pub fn matches(&self, key: &str) -> bool {
+        ApiKeyGenerator::verify_key(key, &self.hashed_key)
+    }
+
     /// Checks if the key has expired.
     pub fn is_expired(&self) -> bool {
         self.expires_at.map(|e| Utc::now() > e).unwrap_or(false)
@@ -158,23 +208,96 @@ impl ApiKey {
         self.enabled && !self.is_expired()
     }
 
-    /// Checks if the key has the required permissions.
-    pub fn has_permissions(&self, required: &HashMap<String, Vec<String>>) -> bool {
-        let permissions = self.get_permissions();
-        
-        for (resource, actions) in required {
-            let Some(allowed_actions) = permissions.get(resource) else {
-                return false;
-            };
-            
-            for action in actions {
-                if !allowed_actions.contains(action) {
-                    return false;
-                }
+    /// Records one use of the key at `now`, or explains why it may not be
+    /// used.
+    ///
+    /// The rate limit is checked first: a key with a time window and a
+    /// maximum allows that many requests, counted from the first request
+    /// after the previous window ran out. A request refused by the rate
+    /// limit does not touch the budget.
+    ///
+    /// Then the budget: when `remaining` is set, each use takes one from
+    /// it, and at zero the key is refused until it is refilled. If
+    /// `refill_amount` and `refill_interval` are set, `remaining` is reset
+    /// to `refill_amount` (not added to) on the first use at least one
+    /// interval after `last_refill_at`, or after creation if it was never
+    /// refilled. A key without `remaining` has no budget and is never
+    /// refilled. Refills do not reset the rate limit window.
+    ///
+    /// The key is only changed in memory; stores persist it, see
+    /// [`ApiKeyStore::record_api_key_use`](crate::ApiKeyStore::record_api_key_use).
+    pub fn record_use(&mut self, now: DateTime<Utc>) -> Result<(), UsageDenied> {
+        let mut request_count = self.request_count;
+        if self.rate_limit_enabled
+            && let (Some(window), Some(max)) = (self.rate_limit_time_window, self.rate_limit_max)
+        {
+            let window = Duration::milliseconds(window);
+            let elapsed = self.last_request.map(|last| now - last);
+            if elapsed.is_none_or(|elapsed| elapsed > window) {
+                request_count = 0;
+            }
+            if request_count >= max {
+                let retry_after = window - elapsed.unwrap_or_default();
+                return Err(UsageDenied::RateLimited {
+                    retry_after_ms: retry_after.num_milliseconds().max(0),
+                });
             }
         }
-        
-        true
+
+        if self.remaining.is_some()
+            && let (Some(amount), Some(interval)) = (self.refill_amount, self.refill_interval)
+            && now - self.last_refill_at.unwrap_or(self.created_at)
+                >= Duration::milliseconds(interval)
+        {
+            self.remaining = Some(amount);
+            self.last_refill_at = Some(now);
+        }
+        match self.remaining {
+            Some(remaining) if remaining <= 0 => return Err(UsageDenied::QuotaExhausted),
+            Some(remaining) => self.remaining = Some(remaining - 1),
+            None => {}
+        }
+
+        self.request_count = request_count + 1;
+        self.last_request = Some(now);
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Checks whether the key may be used at `now` without recording a use.
+    ///
+    /// The rules are those of [`record_use`](Self::record_use).
+    pub fn check_use(&self, now: DateTime<Utc>) -> Result<(), UsageDenied> {
+        self.clone().record_use(now)
+    }
+
+    /// Checks if the key grants `scope`, such as `post:create`.
+    ///
+    /// Both the key's scopes and its permissions map count, and wildcards
+    /// match as in the access plugin: `post:*` grants `post:create`.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        let target = Permission::parse(scope);
+        let from_permissions = self
+            .get_permissions()
+            .into_iter()
+            .flat_map(|(resource, actions)| {
+                actions
+                    .into_iter()
+                    .map(move |action| format!("{}:{}", resource, action))
+            });
+        self.get_scopes()
+            .into_iter()
+            .chain(from_permissions)
+            .any(|granted| Permission::parse(&granted).matches(&target))
+    }
+
+    /// Checks if the key has the required permissions.
+    pub fn has_permissions(&self, required: &HashMap<String, Vec<String>>) -> bool {
+        required.iter().all(|(resource, actions)| {
+            actions
+                .iter()
+                .all(|action| self.has_scope(&format!("{}:{}", resource, action)))
+        })
     }
 }
 
@@ -189,7 +312,8 @@ impl SchemaProvider for ApiKeySchema {
                 .field(Field::optional("name", FieldType::String(255)))
                 .field(Field::optional("start", FieldType::String(20)))
                 .field(Field::optional("prefix", FieldType::String(50)))
-                .field(Field::new("key", FieldType::String(255)))
+                .field(Field::new("hashed_key", FieldType::String(64)).private())
+                .field(Field::new("key_prefix", FieldType::String(100)))
                 .field(
                     Field::new("user_id", FieldType::String(36))
                         .references("user.id")
@@ -209,14 +333,15 @@ impl SchemaProvider for ApiKeySchema {
                 .field(Field::new("created_at", FieldType::Timestamp))
                 .field(Field::new("updated_at", FieldType::Timestamp))
                 .field(Field::optional("permissions", FieldType::Text))
+                .field(Field::optional("scopes", FieldType::Json))
                 .field(Field::optional("metadata", FieldType::Json))
                 .index(IndexDefinition::new(
                     "idx_api_key_user",
                     vec!["user_id".to_string()],
                 ))
                 .index(IndexDefinition::new(
-                    "idx_api_key_key",
-                    vec!["key".to_string()],
+                    "idx_api_key_prefix",
+                    vec!["key_prefix".to_string()],
                 )),
         ]
     }
@@ -228,11 +353,15 @@ mod tests {
 
     #[test]
     fn test_api_key_creation() {
-        let key = ApiKey::new("user_123", "hashed_key")
+        let key = ApiKey::new("user_123", "sk_live_AbCd1234_secret")
             .with_name("My API Key")
             .with_prefix("sk_live_");
 
         assert_eq!(key.user_id, "user_123");
+        assert_eq!(key.key_prefix, "sk_live_AbCd1234");
+        assert!(!key.hashed_key.contains("secret"));
+        assert!(key.matches("sk_live_AbCd1234_secret"));
+        assert!(!key.matches("sk_live_AbCd1234_other"));
         assert_eq!(key.name, Some("My API Key".to_string()));
         assert!(key.enabled);
         assert!(key.is_valid());
@@ -253,6 +382,86 @@ mod tests {
         assert!(!key.has_permissions(&required));
     }
 
+    #[test]
+    fn test_wildcard_scopes() {
+        let key = ApiKey::new("user_123", "key")
+            .with_scopes(vec!["post:*".to_string(), "comment:read".to_string()]);
+
+        assert!(key.has_scope("post:create"));
+        assert!(key.has_scope("post:delete:own"));
+        assert!(key.has_scope("comment:read"));
+        assert!(!key.has_scope("comment:write"));
+        assert!(!key.has_scope("user:read"));
+
+        let admin = ApiKey::new("user_123", "key").with_scopes(vec!["*:*".to_string()]);
+        assert!(admin.has_scope("user:delete"));
+
+        let mut required = HashMap::new();
+        required.insert("post".to_string(), vec!["create".to_string(), "edit".to_string()]);
+        assert!(key.has_permissions(&required));
+        assert!(!ApiKey::new("user_123", "key").has_scope("post:create"));
+    }
+
+    #[test]
+    fn test_remaining_budget() {
+        let now = Utc::now();
+        let mut key = ApiKey::new("user_123", "key").with_remaining(2);
+
+        assert_eq!(key.record_use(now), Ok(()));
+        assert_eq!(key.record_use(now), Ok(()));
+        assert_eq!(key.remaining, Some(0));
+        assert_eq!(key.record_use(now), Err(UsageDenied::QuotaExhausted));
+        assert_eq!(key.request_count, 2);
+
+        let mut unlimited = ApiKey::new("user_123", "key").with_refill(5, 1000);
+        assert_eq!(unlimited.record_use(now + Duration::days(1)), Ok(()));
+        assert_eq!(unlimited.remaining, None);
+    }
+
+    #[test]
+    fn test_lazy_refill() {
+        let mut key = ApiKey::new("user_123", "key")
+            .with_remaining(1)
+            .with_refill(3, 60_000);
+        let start = key.created_at;
+
+        assert_eq!(key.record_use(start), Ok(()));
+        assert_eq!(
+            key.record_use(start + Duration::seconds(59)),
+            Err(UsageDenied::QuotaExhausted)
+        );
+
+        let later = start + Duration::seconds(60);
+        assert_eq!(key.record_use(later), Ok(()));
+        assert_eq!(key.remaining, Some(2));
+        assert_eq!(key.last_refill_at, Some(later));
+
+        // The next refill is an interval after the last one, and resets
+        // rather than adds.
+        assert_eq!(key.record_use(later + Duration::seconds(59)), Ok(()));
+        assert_eq!(key.remaining, Some(1));
+        assert_eq!(key.record_use(later + Duration::seconds(120)), Ok(()));
+        assert_eq!(key.remaining, Some(2));
+    }
+
+    #[test]
+    fn test_rate_limit_does_not_use_budget() {
+        let now = Utc::now();
+        let mut key = ApiKey::new("user_123", "key")
+            .with_remaining(10)
+            .with_rate_limit(1000, 1);
+
+        assert_eq!(key.record_use(now), Ok(()));
+        assert!(matches!(
+            key.record_use(now + Duration::milliseconds(400)),
+            Err(UsageDenied::RateLimited { retry_after_ms: 600 })
+        ));
+        assert_eq!(key.remaining, Some(9));
+
+        assert_eq!(key.record_use(now + Duration::milliseconds(1001)), Ok(()));
+        assert_eq!(key.remaining, Some(8));
+    }
+
     #[test]
     fn test_schema_definition() {
         let schema = ApiKeySchema::schema();
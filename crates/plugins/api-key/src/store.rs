@@ -0,0 +1,127 @@
+//! Storage for API keys.
+
+use crate::{ApiKey, UsageDenied};
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use chrono::Utc;
+use std::sync::RwLock;
+
+/// Storage for API keys.
+///
+/// Adapters implement this trait to persist the `api_key` model.
+#[async_trait]
+pub trait ApiKeyStore: Send + Sync {
+    /// Stores a newly created API key.
+    async fn create_api_key(&self, key: &ApiKey) -> AuthResult<ApiKey>;
+
+    /// Gets every API key with the given lookup prefix. There is normally
+    /// at most one.
+    async fn get_api_keys_by_prefix(&self, key_prefix: &str) -> AuthResult<Vec<ApiKey>>;
+
+    /// Gets every API key owned by the user with ID `user_id`.
+    async fn get_api_keys_by_user_id(&self, user_id: &str) -> AuthResult<Vec<ApiKey>>;
+
+    /// Deletes every API key owned by the user with ID `user_id`.
+    async fn delete_api_keys_by_user_id(&self, user_id: &str) -> AuthResult<()>;
+
+    /// Records one use of the key with ID `id` through
+    /// [`ApiKey::record_use`], storing the result and returning the updated
+    /// key.
+    ///
+    /// This must be atomic: two concurrent uses of a key with one request
+    /// left must not both succeed. Read and write the row in a transaction,
+    /// or lock it.
+    async fn record_api_key_use(&self, id: &str) -> AuthResult<Result<ApiKey, UsageDenied>>;
+}
+
+/// In-memory API key store.
+///
+/// Suitable for a single instance and for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryApiKeyStore {
+    keys: RwLock<Vec<ApiKey>>,
+}
+
+impl InMemoryApiKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for InMemoryApiKeyStore {
+    async fn create_api_key(&self, key: &ApiKey) -> AuthResult<ApiKey> {
+        self.keys.write().unwrap().push(key.clone());
+        Ok(key.clone())
+    }
+
+    async fn get_api_keys_by_prefix(&self, key_prefix: &str) -> AuthResult<Vec<ApiKey>> {
+        let keys = self.keys.read().unwrap();
+        Ok(keys
+            .iter()
+            .filter(|k| k.key_prefix == key_prefix)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_api_keys_by_user_id(&self, user_id: &str) -> AuthResult<Vec<ApiKey>> {
+        let keys = self.keys.read().unwrap();
+        Ok(keys
+            .iter()
+            .filter(|k| k.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_api_keys_by_user_id(&self, user_id: &str) -> AuthResult<()> {
+        self.keys.write().unwrap().retain(|k| k.user_id != user_id);
+        Ok(())
+    }
+
+    async fn record_api_key_use(&self, id: &str) -> AuthResult<Result<ApiKey, UsageDenied>> {
+        // Holding the write lock across the read and the update makes each
+        // use atomic.
+        let mut keys = self.keys.write().unwrap();
+        let key = keys
+            .iter_mut()
+            .find(|k| k.id == id)
+            .ok_or_else(|| AuthError::NotFound {
+                entity: "api_key".to_string(),
+                key: "id".to_string(),
+                value: id.to_string(),
+            })?;
+        Ok(key.record_use(Utc::now()).map(|()| key.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_concurrent_uses_share_the_budget() {
+        let store = Arc::new(InMemoryApiKeyStore::new());
+        let key = store
+            .create_api_key(&ApiKey::new("user_1", "prefix_secret").with_remaining(5))
+            .await
+            .unwrap();
+
+        let uses = (0..20).map(|_| {
+            let store = store.clone();
+            let id = key.id.clone();
+            tokio::spawn(async move { store.record_api_key_use(&id).await.unwrap() })
+        });
+        let mut allowed = 0;
+        for result in uses.collect::<Vec<_>>() {
+            if result.await.unwrap().is_ok() {
+                allowed += 1;
+            }
+        }
+        assert_eq!(allowed, 5);
+
+        let stored = store.get_api_keys_by_prefix("prefix").await.unwrap();
+        assert_eq!(stored[0].remaining, Some(0));
+        assert!(store.record_api_key_use("missing").await.is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! Configuration for the Email Domain plugin.
+
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::events::{Event, EventBus};
+use serde_json::json;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A small built-in list of well-known disposable email providers.
+///
+/// Use [`EmailDomainConfig::load_disposable_domains`] to load a complete list.
+const DEFAULT_DISPOSABLE: &[&str] = &[
+    "10minutemail.com",
+    "discard.email",
+    "dispostable.com",
+    "getnada.com",
+    "guerrillamail.com",
+    "maildrop.cc",
+    "mailinator.com",
+    "sharklasers.com",
+    "temp-mail.org",
+    "throwawaymail.com",
+    "trashmail.com",
+    "yopmail.com",
+];
+
+/// A domain pattern.
+///
+/// A plain domain such as `example.com` matches that domain and all of its
+/// subdomains. Patterns containing `*` are matched as globs against the whole
+/// domain, where `*` matches any run of characters (including dots), so
+/// `*.example.com` matches subdomains only and `mail*.example.com` matches
+/// `mail.example.com` and `mail2.example.com`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DomainPattern(String);
+
+impl DomainPattern {
+    /// Creates a pattern. Matching is case-insensitive.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self(pattern.into().trim().trim_start_matches('@').to_lowercase())
+    }
+
+    /// Returns the pattern as written (lowercased).
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if `domain` matches this pattern.
+    pub fn matches(&self, domain: &str) -> bool {
+        let domain = domain.to_lowercase();
+        if self.0.contains('*') {
+            glob_match(&self.0, &domain)
+        } else {
+            domain == self.0 || domain.ends_with(&format!(".{}", self.0))
+        }
+    }
+}
+
+/// Matches `text` against a glob where `*` matches any run of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // `split` always yields at least one item.
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Configuration for the Email Domain plugin.
+///
+/// The denylist and disposable list are checked first. If the allowlist is
+/// non-empty, the domain must also match one of its patterns.
+#[derive(Clone, Default)]
+pub struct EmailDomainConfig {
+    /// Patterns a signup domain must match, if any are set.
+    pub allow: Vec<DomainPattern>,
+    /// Patterns a signup domain must not match.
+    pub deny: Vec<DomainPattern>,
+    /// Whether to reject disposable email providers.
+    pub block_disposable: bool,
+    /// Disposable domains, matched like plain [`DomainPattern`]s.
+    pub disposable: HashSet<String>,
+    /// Event bus used to emit `email_domain.signup_rejected`.
+    pub event_bus: Option<Arc<EventBus>>,
+}
+
+impl EmailDomainConfig {
+    /// Creates a new config that allows every domain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a pattern to the allowlist.
+    pub fn allow(mut self, pattern: impl Into<String>) -> Self {
+        self.allow.push(DomainPattern::new(pattern));
+        self
+    }
+
+    /// Adds a pattern to the denylist.
+    pub fn deny(mut self, pattern: impl Into<String>) -> Self {
+        self.deny.push(DomainPattern::new(pattern));
+        self
+    }
+
+    /// Rejects disposable email providers, starting from the built-in list.
+    pub fn block_disposable(mut self) -> Self {
+        self.block_disposable = true;
+        self.disposable
+            .extend(DEFAULT_DISPOSABLE.iter().map(|d| d.to_string()));
+        self
+    }
+
+    /// Adds domains to the disposable list and enables blocking.
+    pub fn disposable_domains<I, S>(mut self, domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.block_disposable = true;
+        self.disposable
+            .extend(domains.into_iter().map(|d| d.into().trim().to_lowercase()));
+        self
+    }
+
+    /// Loads a disposable domain list with one domain per line and enables
+    /// blocking. Blank lines and lines starting with `#` are ignored.
+    pub fn load_disposable_domains(self, path: impl AsRef<Path>) -> AuthResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            AuthError::config(format!(
+                "Failed to read disposable domain list {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(self.disposable_domains(parse_domain_list(&contents)))
+    }
+
+    /// Sets the event bus used to emit rejected signups.
+    pub fn event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// Checks a signup as in [`check`](Self::check), emitting
+    /// `email_domain.signup_rejected` if its domain is not allowed.
+    pub async fn check_signup(&self, email: &str) -> AuthResult<()> {
+        let result = self.check(email);
+        if let Err(AuthError::EmailDomainNotAllowed { domain }) = &result
+            && let Some(bus) = &self.event_bus
+        {
+            bus.emit(
+                Event::simple("email_domain.signup_rejected", json!({ "domain": domain }))
+                    .with_source("email_domain"),
+            )
+            .await;
+        }
+        result
+    }
+
+    /// Checks whether signups are allowed from `email`'s domain.
+    pub fn check(&self, email: &str) -> AuthResult<()> {
+        let domain = email_domain(email).ok_or(AuthError::InvalidEmail)?;
+        let rejected = || AuthError::EmailDomainNotAllowed {
+            domain: domain.clone(),
+        };
+
+        if self.deny.iter().any(|p| p.matches(&domain)) {
+            return Err(rejected());
+        }
+        if self.block_disposable && self.is_disposable(&domain) {
+            return Err(rejected());
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|p| p.matches(&domain)) {
+            return Err(rejected());
+        }
+        Ok(())
+    }
+
+    /// Returns true if `domain` or one of its parent domains is disposable.
+    pub fn is_disposable(&self, domain: &str) -> bool {
+        let mut candidate = domain;
+        loop {
+            if self.disposable.contains(candidate) {
+                return true;
+            }
+            match candidate.split_once('.') {
+                Some((_, parent)) if parent.contains('.') => candidate = parent,
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for EmailDomainConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmailDomainConfig")
+            .field("allow", &self.allow)
+            .field("deny", &self.deny)
+            .field("block_disposable", &self.block_disposable)
+            .field("disposable", &self.disposable.len())
+            .field("event_bus", &self.event_bus.is_some())
+            .finish()
+    }
+}
+
+/// Returns the lowercased domain part of an email address.
+fn email_domain(email: &str) -> Option<String> {
+    let (local, domain) = email.trim().rsplit_once('@')?;
+    if local.is_empty() || domain.is_empty() {
+        return None;
+    }
+    Some(domain.to_lowercase())
+}
+
+fn parse_domain_list(contents: &str) -> impl Iterator<Item = &str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_matching() {
+        let plain = DomainPattern::new("Example.com");
+        assert!(plain.matches("example.com"));
+        assert!(plain.matches("eu.example.com"));
+        assert!(!plain.matches("notexample.com"));
+
+        let wildcard = DomainPattern::new("*.example.com");
+        assert!(wildcard.matches("eu.example.com"));
+        assert!(!wildcard.matches("example.com"));
+
+        let infix = DomainPattern::new("mail*.example.com");
+        assert!(infix.matches("mail.example.com"));
+        assert!(infix.matches("mail2.example.com"));
+        assert!(!infix.matches("smtp.example.com"));
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let config = EmailDomainConfig::new().allow("acme.com").allow("*.acme.io");
+
+        assert!(config.check("jane@acme.com").is_ok());
+        assert!(config.check("jane@eu.acme.com").is_ok());
+        assert!(config.check("jane@dev.acme.io").is_ok());
+        assert!(matches!(
+            config.check("jane@gmail.com"),
+            Err(AuthError::EmailDomainNotAllowed { domain }) if domain == "gmail.com"
+        ));
+    }
+
+    #[test]
+    fn test_denylist_wins_over_allowlist() {
+        let config = EmailDomainConfig::new()
+            .allow("acme.com")
+            .deny("contractors.acme.com");
+
+        assert!(config.check("jane@acme.com").is_ok());
+        assert!(matches!(
+            config.check("bob@contractors.acme.com"),
+            Err(AuthError::EmailDomainNotAllowed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_disposable() {
+        let config = EmailDomainConfig::new()
+            .block_disposable()
+            .disposable_domains(parse_domain_list("# custom\nburner.dev\n\n"));
+
+        assert!(config.check("jane@example.com").is_ok());
+        assert!(matches!(
+            config.check("jane@Mailinator.com"),
+            Err(AuthError::EmailDomainNotAllowed { .. })
+        ));
+        assert!(config.check("jane@x.burner.dev").is_err());
+
+        // Disposable domains are allowed unless blocking is enabled.
+        assert!(EmailDomainConfig::new().check("jane@mailinator.com").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_signup_is_emitted() {
+        let bus = Arc::new(EventBus::new());
+        let config = EmailDomainConfig::new()
+            .allow("acme.com")
+            .event_bus(bus.clone());
+
+        assert!(config.check_signup("jane@acme.com").await.is_ok());
+        assert!(config.check_signup("jane@gmail.com").await.is_err());
+        assert!(config.check_signup("not-an-email").await.is_err());
+
+        let events = bus.events_of_type("email_domain.signup_rejected").await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload["domain"], "gmail.com");
+    }
+
+    #[test]
+    fn test_invalid_email() {
+        let config = EmailDomainConfig::new();
+        assert!(matches!(config.check("not-an-email"), Err(AuthError::InvalidEmail)));
+        assert!(matches!(config.check("@acme.com"), Err(AuthError::InvalidEmail)));
+    }
+}
@@ -0,0 +1,92 @@
+//! # Better Auth Email Domain Plugin
+//!
+//! This plugin restricts signups by email domain. Deployments can limit
+//! signups to an allowlist of (e.g. corporate) domains, block specific
+//! domains, and reject disposable email providers.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use better_auth_plugin_email_domain::{EmailDomainConfig, EmailDomainPlugin};
+//!
+//! let plugin = EmailDomainPlugin::new(
+//!     EmailDomainConfig::new()
+//!         .allow("acme.com")
+//!         .allow("*.acme.io")
+//!         .block_disposable(),
+//! );
+//! ```
+
+mod config;
+
+pub use config::{DomainPattern, EmailDomainConfig};
+
+use async_trait::async_trait;
+use better_auth_core::context::{AuthContext, SignUpData};
+use better_auth_core::error::AuthResult;
+use better_auth_core::traits::AuthPlugin;
+use better_auth_events_sdk::{EventDefinition, EventProvider};
+
+/// The Email Domain plugin.
+pub struct EmailDomainPlugin {
+    config: EmailDomainConfig,
+}
+
+impl EmailDomainPlugin {
+    /// Creates a new Email Domain plugin with the given configuration.
+    pub fn new(config: EmailDomainConfig) -> Self {
+        Self { config }
+    }
+
+    /// Gets the plugin configuration.
+    pub fn config(&self) -> &EmailDomainConfig {
+        &self.config
+    }
+}
+
+impl Default for EmailDomainPlugin {
+    fn default() -> Self {
+        Self::new(EmailDomainConfig::default())
+    }
+}
+
+impl EventProvider for EmailDomainPlugin {
+    fn provided_events() -> Vec<EventDefinition> {
+        vec![EventDefinition::simple(
+            "email_domain.signup_rejected",
+            "Emitted when a signup is rejected because of its email domain",
+            "email_domain",
+        )]
+    }
+
+    fn event_source() -> &'static str {
+        "email_domain"
+    }
+}
+
+#[async_trait]
+impl AuthPlugin for EmailDomainPlugin {
+    fn id(&self) -> &'static str {
+        "email-domain"
+    }
+
+    fn name(&self) -> &'static str {
+        "Email Domain Restrictions"
+    }
+
+    async fn on_before_signup(&self, _ctx: &AuthContext, data: &mut SignUpData) -> AuthResult<()> {
+        self.config.check_signup(&data.email).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_creation() {
+        let plugin = EmailDomainPlugin::new(EmailDomainConfig::new().allow("acme.com"));
+        assert_eq!(plugin.id(), "email-domain");
+        assert_eq!(plugin.config().allow.len(), 1);
+    }
+}
@@ -1,5 +1,13 @@
 //! Configuration for the Email OTP plugin.
 
+use crate::{EmailOtp, EmailOtpStore};
+use better_auth_core::crypto::{constant_time_eq, hash_secret};
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::session_token::SessionTokenStrategy;
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::User;
+use better_auth_otp_utils::{LoggingSender, MessageSender, OtpConfig, OtpGenerator};
+use chrono::{Duration, Utc};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -25,6 +33,15 @@ impl OtpPurpose {
         }
     }
 
+    /// Returns the email subject for an OTP sent for this purpose.
+    pub fn subject(&self) -> &'static str {
+        match self {
+            OtpPurpose::SignIn => "Your sign-in code",
+            OtpPurpose::EmailVerification => "Verify your email address",
+            OtpPurpose::PasswordReset => "Reset your password",
+        }
+    }
+
     /// Parses from a string.
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
@@ -83,12 +100,39 @@ pub struct EmailOtpConfig {
     pub send_verification_on_sign_up: bool,
     /// Whether to override default email verification with OTP. Default: false.
     pub override_default_email_verification: bool,
-    /// Callback to send the OTP.
+    /// Callback to send the OTP. Takes precedence over `sender`.
     pub send_verification_otp: Option<SendOtpCallback>,
+    /// Delivers OTP emails. Default: [`LoggingSender`], which only logs
+    /// them.
+    pub sender: Arc<dyn MessageSender>,
     /// Custom OTP generator function.
     pub generate_otp: Option<OtpGeneratorFn>,
     /// How to store OTPs: "plain", "hashed", or "encrypted".
     pub store_otp: OtpStorageMode,
+    /// Maximum OTPs sent to one email address within `send_window`.
+    /// Default: 3.
+    pub max_sends: u32,
+    /// Window for `max_sends`, in seconds. Default: 900 (15 minutes).
+    pub send_window: u64,
+    /// Store for sent OTPs. Without one, OTPs are sent but can't be checked,
+    /// and the verify routes return placeholder responses.
+    pub otp_store: Option<Arc<dyn EmailOtpStore>>,
+    /// Storage adapter used to look up users and mark emails as verified.
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    /// How tokens for the sessions it signs users into are generated.
+    /// Use the app's strategy. Default: opaque tokens.
+    pub session_tokens: SessionTokenStrategy,
+    /// Answer OTP sends and password reset requests for unknown emails
+    /// exactly as for known ones. Default: true.
+    ///
+    /// Affects `/email-otp/send-verification-otp` (for `forget-password` and
+    /// `email-verification` codes, and `sign-in` codes when sign-up is
+    /// disabled) and `/email-otp/request-password-reset`. These only check
+    /// for an account when `storage` is set. When there is no account, a
+    /// code is still generated and stored but never sent. Delivery to real
+    /// accounts happens in the background, so neither its duration nor its
+    /// failure shows in the response; failures are logged.
+    pub prevent_user_enumeration: bool,
 }
 
 /// How OTPs are stored in the database.
@@ -97,9 +141,10 @@ pub enum OtpStorageMode {
     /// Store OTPs in plain text.
     #[default]
     Plain,
-    /// Hash OTPs before storage.
+    /// Hash OTPs before storage; see [`hash_secret`].
     Hashed,
-    /// Encrypt OTPs before storage.
+    /// Encrypt OTPs before storage. There is no encryption key to configure
+    /// yet, so these are hashed as with `Hashed`.
     Encrypted,
 }
 
@@ -113,8 +158,15 @@ impl Default for EmailOtpConfig {
             send_verification_on_sign_up: false,
             override_default_email_verification: false,
             send_verification_otp: None,
+            sender: Arc::new(LoggingSender::new()),
             generate_otp: None,
             store_otp: OtpStorageMode::Plain,
+            max_sends: 3,
+            send_window: 15 * 60,
+            otp_store: None,
+            storage: None,
+            session_tokens: SessionTokenStrategy::default(),
+            prevent_user_enumeration: true,
         }
     }
 }
@@ -171,6 +223,12 @@ impl EmailOtpConfig {
         self
     }
 
+    /// Sets the sender that delivers OTP emails.
+    pub fn sender(mut self, sender: Arc<dyn MessageSender>) -> Self {
+        self.sender = sender;
+        self
+    }
+
     /// Sets a custom OTP generator.
     pub fn generate_otp_with<F>(mut self, generator: F) -> Self
     where
@@ -185,6 +243,167 @@ impl EmailOtpConfig {
         self.store_otp = mode;
         self
     }
+
+    /// Limits how many OTPs one email address can be sent within
+    /// `window_seconds`.
+    pub fn send_rate_limit(mut self, max_sends: u32, window_seconds: u64) -> Self {
+        self.max_sends = max_sends;
+        self.send_window = window_seconds;
+        self
+    }
+
+    /// Sets the store for sent OTPs.
+    pub fn otp_store(mut self, store: Arc<dyn EmailOtpStore>) -> Self {
+        self.otp_store = Some(store);
+        self
+    }
+
+    /// Sets the storage adapter used to look up users and mark emails as
+    /// verified.
+    pub fn storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Sets how session tokens are generated.
+    pub fn session_tokens(mut self, strategy: SessionTokenStrategy) -> Self {
+        self.session_tokens = strategy;
+        self
+    }
+
+    /// Sets whether unknown emails get the same responses as known ones.
+    /// See [`prevent_user_enumeration`](Self::prevent_user_enumeration).
+    pub fn prevent_user_enumeration(mut self, enabled: bool) -> Self {
+        self.prevent_user_enumeration = enabled;
+        self
+    }
+
+    /// Sends an OTP for `purpose` to `email` if it belongs to a user.
+    ///
+    /// Returns `Ok(false)` without sending when there is no such user.
+    /// Without a storage adapter, or for sign-in codes while sign-up is
+    /// allowed, the OTP is always sent. Otherwise, with
+    /// `prevent_user_enumeration`, an unknown email gets a stored code too
+    /// and delivery is moved to the background.
+    pub(crate) async fn send_otp_to_user(
+        &self,
+        email: &str,
+        purpose: OtpPurpose,
+    ) -> AuthResult<bool> {
+        let needs_user = purpose != OtpPurpose::SignIn || self.disable_sign_up;
+        let Some(storage) = self.storage.as_deref().filter(|_| needs_user) else {
+            self.send_otp(email, purpose).await?;
+            return Ok(true);
+        };
+        let known = find_user(storage, email).await?.is_some();
+        if !self.prevent_user_enumeration {
+            if known {
+                self.send_otp(email, purpose).await?;
+            }
+            return Ok(known);
+        }
+
+        // Nobody receives the code stored for an unknown email, so it can
+        // never be used.
+        let otp = self.store_new_otp(email, purpose).await?;
+        if known {
+            let config = self.clone();
+            let data = EmailOtpData::new(email, otp, purpose);
+            tokio::spawn(async move {
+                if let Err(err) = config.deliver(data).await {
+                    tracing::warn!(error = %err, "failed to deliver email OTP");
+                }
+            });
+        }
+        Ok(known)
+    }
+
+    /// Generates an OTP, stores it if there is a store, and delivers it.
+    pub(crate) async fn send_otp(&self, email: &str, purpose: OtpPurpose) -> AuthResult<()> {
+        let otp = self.store_new_otp(email, purpose).await?;
+        self.deliver(EmailOtpData::new(email, otp, purpose)).await
+    }
+
+    /// Generates an OTP and stores it if there is a store, returning it.
+    async fn store_new_otp(&self, email: &str, purpose: OtpPurpose) -> AuthResult<String> {
+        let otp = self.new_otp();
+        if let Some(store) = &self.otp_store {
+            let expires_at = Utc::now() + Duration::seconds(self.expires_in as i64);
+            let record = EmailOtp::new(
+                email.to_lowercase(),
+                self.stored_otp(&otp),
+                purpose.as_str(),
+                expires_at,
+            );
+            store.create_email_otp(&record).await?;
+        }
+        Ok(otp)
+    }
+
+    /// Checks `otp` against the one sent to `email` for `purpose`, using it
+    /// up on success.
+    ///
+    /// Each wrong guess counts as an attempt; after `allowed_attempts` the
+    /// OTP is deleted and a new one must be requested.
+    pub(crate) async fn check_otp(
+        &self,
+        store: &dyn EmailOtpStore,
+        email: &str,
+        purpose: OtpPurpose,
+        otp: &str,
+    ) -> AuthResult<()> {
+        let Some(mut record) = store.get_email_otp(&email.to_lowercase(), purpose.as_str()).await?
+        else {
+            return Err(AuthError::InvalidToken);
+        };
+        if record.is_expired() {
+            store.delete_email_otp(&record.id).await?;
+            return Err(AuthError::TokenExpired);
+        }
+        if !constant_time_eq(&record.otp, &self.stored_otp(otp)) {
+            record.increment_attempts();
+            if record.attempts >= self.allowed_attempts as i32 {
+                store.delete_email_otp(&record.id).await?;
+            } else {
+                store.update_email_otp(&record).await?;
+            }
+            return Err(AuthError::InvalidToken);
+        }
+        store.delete_email_otp(&record.id).await
+    }
+
+    /// Returns `otp` in the form it is stored in, per `store_otp`.
+    fn stored_otp(&self, otp: &str) -> String {
+        match self.store_otp {
+            OtpStorageMode::Plain => otp.to_string(),
+            OtpStorageMode::Hashed | OtpStorageMode::Encrypted => hash_secret(otp),
+        }
+    }
+
+    /// Generates an OTP with the custom generator, if any.
+    pub(crate) fn new_otp(&self) -> String {
+        match &self.generate_otp {
+            Some(generator) => generator(),
+            None => OtpGenerator::new(OtpConfig::numeric(self.otp_length)).generate(),
+        }
+    }
+
+    /// Delivers an OTP through the callback, or `sender` if there is none.
+    pub(crate) async fn deliver(&self, data: EmailOtpData) -> AuthResult<()> {
+        if let Some(callback) = &self.send_verification_otp {
+            return callback(data)
+                .await
+                .map_err(|message| AuthError::plugin("email_otp", message));
+        }
+        let body = format!(
+            "Your code is {}. It expires in {} minutes.",
+            data.otp,
+            self.expires_in.div_ceil(60)
+        );
+        self.sender
+            .send(&data.email, Some(data.otp_type.subject()), &body)
+            .await
+    }
 }
 
 impl std::fmt::Debug for EmailOtpConfig {
@@ -197,8 +416,27 @@ impl std::fmt::Debug for EmailOtpConfig {
             .field("send_verification_on_sign_up", &self.send_verification_on_sign_up)
             .field("override_default_email_verification", &self.override_default_email_verification)
             .field("send_verification_otp", &self.send_verification_otp.is_some())
+            .field("sender", &"<sender>")
             .field("generate_otp", &self.generate_otp.is_some())
             .field("store_otp", &self.store_otp)
+            .field("max_sends", &self.max_sends)
+            .field("send_window", &self.send_window)
+            .field("otp_store", &self.otp_store.is_some())
+            .field("storage", &self.storage.is_some())
+            .field("session_tokens", &self.session_tokens)
+            .field("prevent_user_enumeration", &self.prevent_user_enumeration)
             .finish()
     }
 }
+
+/// Finds the user with `email`, falling back to its lowercase form.
+async fn find_user(storage: &dyn StorageAdapter, email: &str) -> AuthResult<Option<User>> {
+    if let Some(user) = storage.get_user_by_email(email).await? {
+        return Ok(Some(user));
+    }
+    let lowercase = email.to_lowercase();
+    if lowercase == email {
+        return Ok(None);
+    }
+    storage.get_user_by_email(&lowercase).await
+}
@@ -1,8 +1,15 @@
 //! Request handlers for the Email OTP plugin.
 
 use async_trait::async_trait;
-use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::router::{CookieOptions, Request, RequestHandler, Response};
+use better_auth_core::session::{SESSION_COOKIE, end_replaced_session};
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::{AuthFactor, Session, User};
+use better_auth_otp_utils::RateLimiter;
+use crate::{EmailOtpConfig, OtpPurpose};
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
 
 /// Request body for sending verification OTP.
 #[derive(Debug, Deserialize)]
@@ -21,7 +28,14 @@ pub struct SendVerificationOtpResponse {
 }
 
 /// Handler for POST /email-otp/send-verification-otp
-pub struct SendVerificationOtpHandler;
+///
+/// Sends to each email address are rate limited, so the endpoint cannot be
+/// used to flood an inbox. Unknown emails get the same response as known
+/// ones unless `prevent_user_enumeration` is turned off.
+pub struct SendVerificationOtpHandler {
+    pub config: EmailOtpConfig,
+    pub send_limiter: Arc<Mutex<RateLimiter>>,
+}
 
 #[async_trait]
 impl RequestHandler for SendVerificationOtpHandler {
@@ -48,23 +62,88 @@ impl RequestHandler for SendVerificationOtpHandler {
         }
 
         // Validate OTP type
-        let valid_types = ["sign-in", "email-verification", "forget-password"];
-        if !valid_types.contains(&body.otp_type.as_str()) {
+        let Some(purpose) = OtpPurpose::from_str(&body.otp_type) else {
             return Response::bad_request().json(serde_json::json!({
                 "error": {
                     "code": "INVALID_OTP_TYPE",
                     "message": "Invalid OTP type. Must be one of: sign-in, email-verification, forget-password"
                 }
             }));
+        };
+
+        send_otp(&self.config, &self.send_limiter, &body.email, purpose).await
+    }
+}
+
+/// Sends an OTP for `purpose` to `email`, subject to the send rate limit.
+///
+/// The rate limit applies whether or not the email belongs to a user.
+async fn send_otp(
+    config: &EmailOtpConfig,
+    send_limiter: &Mutex<RateLimiter>,
+    email: &str,
+    purpose: OtpPurpose,
+) -> Response {
+    let limited = send_limiter
+        .lock()
+        .unwrap()
+        .check(&email.to_lowercase())
+        .into_result();
+    if let Err(err) = limited {
+        return rate_limited_response(err);
+    }
+
+    match config.send_otp_to_user(email, purpose).await {
+        Ok(sent) if sent || config.prevent_user_enumeration => {
+            Response::ok().json(SendVerificationOtpResponse { success: true })
         }
+        Ok(_) => Response::not_found().json(serde_json::json!({
+            "error": {
+                "code": "USER_NOT_FOUND",
+                "message": "No user has this email address"
+            }
+        })),
+        Err(err) => send_failed_response(err),
+    }
+}
 
-        // In a real implementation, this would:
-        // 1. Generate OTP
-        // 2. Store in database
-        // 3. Call sendVerificationOTP callback
-        
-        Response::ok().json(SendVerificationOtpResponse { success: true })
+/// Converts a rate limit error into a 429 response with `Retry-After`.
+fn rate_limited_response(err: AuthError) -> Response {
+    let mut response = Response::new(err.status_code()).json(serde_json::json!({
+        "error": {
+            "code": "RATE_LIMITED",
+            "message": err.to_string()
+        }
+    }));
+    if let AuthError::RateLimitExceeded { retry_after_seconds } = err {
+        response = response.header("Retry-After", retry_after_seconds.to_string());
     }
+    response
+}
+
+/// Converts a failed OTP check into an error response.
+fn invalid_otp_response(err: AuthError) -> Response {
+    let code = match err {
+        AuthError::TokenExpired => "OTP_EXPIRED",
+        AuthError::InvalidToken => "INVALID_OTP",
+        _ => "INTERNAL_ERROR",
+    };
+    Response::new(err.status_code()).json(serde_json::json!({
+        "error": {
+            "code": code,
+            "message": err.to_string()
+        }
+    }))
+}
+
+/// Converts a failure to deliver an OTP into an error response.
+fn send_failed_response(err: AuthError) -> Response {
+    Response::new(err.status_code()).json(serde_json::json!({
+        "error": {
+            "code": "SEND_FAILED",
+            "message": err.to_string()
+        }
+    }))
 }
 
 /// Request body for checking verification OTP.
@@ -135,7 +214,40 @@ pub struct SessionResponse {
 }
 
 /// Handler for POST /sign-in/email-otp
-pub struct SignInEmailOtpHandler;
+///
+/// Signs the user in once the OTP checks out, creating the user unless
+/// sign-up is disabled. Without an OTP store and storage adapter it returns
+/// a placeholder response.
+pub struct SignInEmailOtpHandler {
+    pub config: EmailOtpConfig,
+}
+
+impl SignInEmailOtpHandler {
+    /// Finds or creates the user for `email` and starts a session.
+    async fn sign_in(
+        &self,
+        storage: &dyn StorageAdapter,
+        req: &Request,
+        email: &str,
+    ) -> AuthResult<(User, Session)> {
+        let email = email.to_lowercase();
+        // Receiving the code proves the user owns the address.
+        let user = match storage.mark_email_verified(&email).await? {
+            Some(user) => user,
+            None if self.config.disable_sign_up => return Err(AuthError::UserNotFound),
+            None => {
+                let mut user = User::new(uuid::Uuid::new_v4().to_string(), email);
+                user.email_verified = true;
+                storage.create_user(&user).await?
+            }
+        };
+        let mut session = self.config.session_tokens.new_session(user.id.clone());
+        session.record_factor(AuthFactor::Otp);
+        let session = storage.create_session(&session).await?;
+        end_replaced_session(storage, req).await?;
+        Ok((user, session))
+    }
+}
 
 #[async_trait]
 impl RequestHandler for SignInEmailOtpHandler {
@@ -169,25 +281,64 @@ impl RequestHandler for SignInEmailOtpHandler {
             }));
         }
 
-        // In a real implementation, this would:
-        // 1. Verify OTP
-        // 2. Find or create user
-        // 3. Create session
-        // 4. Return user and session
-        
-        Response::ok().json(serde_json::json!({
-            "user": {
-                "id": "user_placeholder",
-                "email": body.email,
-                "email_verified": true,
-                "name": null
-            },
-            "session": {
-                "id": "session_placeholder",
-                "token": "token_placeholder",
-                "expires_at": "2024-01-01T00:00:00Z"
+        let (Some(otp_store), Some(storage)) = (&self.config.otp_store, &self.config.storage) else {
+            return Response::ok().json(serde_json::json!({
+                "user": {
+                    "id": "user_placeholder",
+                    "email": body.email,
+                    "email_verified": true,
+                    "name": null
+                },
+                "session": {
+                    "id": "session_placeholder",
+                    "token": "token_placeholder",
+                    "expires_at": "2024-01-01T00:00:00Z"
+                }
+            }));
+        };
+
+        let checked = self
+            .config
+            .check_otp(otp_store.as_ref(), &body.email, OtpPurpose::SignIn, &body.otp)
+            .await;
+        if let Err(err) = checked {
+            return invalid_otp_response(err);
+        }
+        let (user, session) = match self.sign_in(storage.as_ref(), &req, &body.email).await {
+            Ok(signed_in) => signed_in,
+            Err(AuthError::UserNotFound) => {
+                return Response::not_found().json(serde_json::json!({
+                    "error": {
+                        "code": "USER_NOT_FOUND",
+                        "message": "No user has this email address"
+                    }
+                }));
             }
-        }))
+            Err(err) => {
+                return Response::new(err.status_code()).json(serde_json::json!({
+                    "error": {
+                        "code": "INTERNAL_ERROR",
+                        "message": err.to_string()
+                    }
+                }));
+            }
+        };
+
+        Response::ok()
+            .json(SignInEmailOtpResponse {
+                user: UserResponse {
+                    id: user.id.clone(),
+                    email: user.email.clone(),
+                    email_verified: user.email_verified,
+                    name: user.name.clone(),
+                },
+                session: SessionResponse {
+                    id: session.id.clone(),
+                    token: session.token.clone(),
+                    expires_at: session.expires_at.to_rfc3339(),
+                },
+            })
+            .cookie(SESSION_COOKIE, &session.token, CookieOptions::secure())
     }
 }
 
@@ -199,7 +350,11 @@ pub struct VerifyEmailRequest {
 }
 
 /// Handler for POST /email-otp/verify-email
-pub struct VerifyEmailHandler;
+///
+/// Marks the user's email as verified once the OTP checks out.
+pub struct VerifyEmailHandler {
+    pub config: EmailOtpConfig,
+}
 
 #[async_trait]
 impl RequestHandler for VerifyEmailHandler {
@@ -215,11 +370,48 @@ impl RequestHandler for VerifyEmailHandler {
             }));
         };
 
-        // In a real implementation, this would verify the OTP and mark email as verified
-        
+        let (Some(otp_store), Some(storage)) = (&self.config.otp_store, &self.config.storage) else {
+            return Response::ok().json(serde_json::json!({
+                "success": true,
+                "email_verified": true
+            }));
+        };
+
+        let checked = self
+            .config
+            .check_otp(otp_store.as_ref(), &body.email, OtpPurpose::EmailVerification, &body.otp)
+            .await;
+        if let Err(err) = checked {
+            return invalid_otp_response(err);
+        }
+        let user = match storage.mark_email_verified(&body.email).await {
+            Ok(Some(user)) => user,
+            Ok(None) => {
+                return Response::not_found().json(serde_json::json!({
+                    "error": {
+                        "code": "USER_NOT_FOUND",
+                        "message": "No user has this email address"
+                    }
+                }));
+            }
+            Err(err) => {
+                return Response::new(err.status_code()).json(serde_json::json!({
+                    "error": {
+                        "code": "INTERNAL_ERROR",
+                        "message": err.to_string()
+                    }
+                }));
+            }
+        };
+
         Response::ok().json(serde_json::json!({
             "success": true,
-            "email_verified": true
+            "email_verified": true,
+            "user": {
+                "id": user.id,
+                "email": user.email,
+                "email_verified": user.email_verified
+            }
         }))
     }
 }
@@ -231,7 +423,13 @@ pub struct RequestPasswordResetRequest {
 }
 
 /// Handler for POST /email-otp/request-password-reset
-pub struct RequestPasswordResetHandler;
+///
+/// Sends a `forget-password` OTP, sharing the send rate limit with
+/// `/email-otp/send-verification-otp`.
+pub struct RequestPasswordResetHandler {
+    pub config: EmailOtpConfig,
+    pub send_limiter: Arc<Mutex<RateLimiter>>,
+}
 
 #[async_trait]
 impl RequestHandler for RequestPasswordResetHandler {
@@ -247,11 +445,16 @@ impl RequestHandler for RequestPasswordResetHandler {
             }));
         };
 
-        // In a real implementation, this would send a password reset OTP
-        
-        Response::ok().json(serde_json::json!({
-            "success": true
-        }))
+        if body.email.is_empty() || !body.email.contains('@') {
+            return Response::bad_request().json(serde_json::json!({
+                "error": {
+                    "code": "INVALID_EMAIL",
+                    "message": "Invalid email address"
+                }
+            }));
+        }
+
+        send_otp(&self.config, &self.send_limiter, &body.email, OtpPurpose::PasswordReset).await
     }
 }
 
@@ -6,9 +6,11 @@
 mod config;
 mod schema;
 mod handlers;
+mod store;
 
-pub use config::{EmailOtpConfig, EmailOtpData, OtpPurpose};
+pub use config::{EmailOtpConfig, EmailOtpData, OtpPurpose, OtpStorageMode};
 pub use schema::{EmailOtp, EmailOtpSchema};
+pub use store::{EmailOtpStore, InMemoryEmailOtpStore};
 
 use async_trait::async_trait;
 use better_auth_core::context::{AuthContext, SignInCredentials, SignUpData};
@@ -18,19 +20,24 @@ use better_auth_core::schema::SchemaBuilder;
 use better_auth_core::traits::{AuthPlugin, SchemaProvider};
 use better_auth_core::types::{Session, User};
 use better_auth_events_sdk::{EventDefinition, EventProvider};
-use better_auth_otp_utils::{OtpGenerator, OtpConfig, VerificationCode};
+use better_auth_otp_utils::{RateLimitConfig, RateLimiter, VerificationCode};
 use chrono::Duration;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// The Email OTP authentication plugin.
 pub struct EmailOtpPlugin {
     config: EmailOtpConfig,
+    send_limiter: Arc<Mutex<RateLimiter>>,
 }
 
 impl EmailOtpPlugin {
     /// Creates a new Email OTP plugin with the given configuration.
     pub fn new(config: EmailOtpConfig) -> Self {
-        Self { config }
+        let send_limiter = Arc::new(Mutex::new(RateLimiter::new(RateLimitConfig::new(
+            config.max_sends,
+            Duration::seconds(config.send_window as i64),
+        ))));
+        Self { config, send_limiter }
     }
 
     /// Gets the plugin configuration.
@@ -40,8 +47,7 @@ impl EmailOtpPlugin {
 
     /// Generates a new OTP code.
     pub fn generate_otp(&self) -> String {
-        let generator = OtpGenerator::new(OtpConfig::numeric(self.config.otp_length));
-        generator.generate()
+        self.config.new_otp()
     }
 
     /// Creates a verification code for the given email and purpose.
@@ -121,7 +127,10 @@ impl AuthPlugin for EmailOtpPlugin {
             Route::new(
                 Method::POST,
                 "/email-otp/send-verification-otp",
-                handlers::SendVerificationOtpHandler,
+                handlers::SendVerificationOtpHandler {
+                    config: self.config.clone(),
+                    send_limiter: self.send_limiter.clone(),
+                },
             )
             .summary("Send verification OTP to email")
             .description("Sends a one-time password to the specified email address for sign-in, email verification, or password reset.")
@@ -145,7 +154,9 @@ impl AuthPlugin for EmailOtpPlugin {
             Route::new(
                 Method::POST,
                 "/sign-in/email-otp",
-                handlers::SignInEmailOtpHandler,
+                handlers::SignInEmailOtpHandler {
+                    config: self.config.clone(),
+                },
             )
             .summary("Sign in with email OTP")
             .description("Signs in a user using their email and OTP. Creates a new user if they don't exist (unless disabled).")
@@ -157,7 +168,9 @@ impl AuthPlugin for EmailOtpPlugin {
             Route::new(
                 Method::POST,
                 "/email-otp/verify-email",
-                handlers::VerifyEmailHandler,
+                handlers::VerifyEmailHandler {
+                    config: self.config.clone(),
+                },
             )
             .summary("Verify email address")
             .description("Verifies the user's email address using an OTP.")
@@ -169,7 +182,10 @@ impl AuthPlugin for EmailOtpPlugin {
             Route::new(
                 Method::POST,
                 "/email-otp/request-password-reset",
-                handlers::RequestPasswordResetHandler,
+                handlers::RequestPasswordResetHandler {
+                    config: self.config.clone(),
+                    send_limiter: self.send_limiter.clone(),
+                },
             )
             .summary("Request password reset")
             .description("Sends a password reset OTP to the user's email.")
@@ -200,8 +216,9 @@ impl AuthPlugin for EmailOtpPlugin {
     async fn on_after_signup(&self, _ctx: &AuthContext, user: &User) -> AuthResult<()> {
         // Optionally send verification OTP on signup
         if self.config.send_verification_on_sign_up {
-            // This would trigger the sendVerificationOTP callback
-            // Implementation depends on how callbacks are handled
+            self.config
+                .send_otp(&user.email, OtpPurpose::EmailVerification)
+                .await?;
         }
         Ok(())
     }
@@ -210,6 +227,7 @@ impl AuthPlugin for EmailOtpPlugin {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use better_auth_core::types::AuthFactor;
 
     #[test]
     fn test_plugin_creation() {
@@ -235,4 +253,232 @@ mod tests {
         assert_eq!(code.verification_type, "sign-in");
         assert!(!code.is_expired());
     }
+
+    async fn send(router: &Router, path: &str, body: serde_json::Value) -> better_auth_core::router::Response {
+        use better_auth_core::router::Request;
+
+        let route = router.routes().find(|r| r.path == path).unwrap();
+        let mut req = Request::new(Method::POST, path);
+        req.body = Some(body);
+        route.handler.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_send_is_rate_limited() {
+        let plugin = EmailOtpPlugin::new(EmailOtpConfig::new().send_rate_limit(3, 15 * 60));
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        for _ in 0..3 {
+            assert_eq!(send(&router, "/email-otp/send-verification-otp", serde_json::json!({ "email": "jane@example.com", "type": "sign-in" })).await.status, 200);
+        }
+        let response = send(&router, "/email-otp/send-verification-otp", serde_json::json!({ "email": "jane@example.com", "type": "sign-in" })).await;
+        assert_eq!(response.status, 429);
+        assert!(response.headers.contains_key("retry-after"));
+        assert_eq!(response.body.unwrap()["error"]["code"], "RATE_LIMITED");
+
+        // Other recipients are unaffected.
+        assert_eq!(send(&router, "/email-otp/send-verification-otp", serde_json::json!({ "email": "john@example.com", "type": "sign-in" })).await.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_send_dispatches_through_sender() {
+        let sender = Arc::new(better_auth_otp_utils::testing::RecordingSender::new());
+        let plugin = EmailOtpPlugin::new(
+            EmailOtpConfig::new()
+                .sender(sender.clone())
+                .generate_otp_with(|| "424242".to_string()),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let response = send(&router, "/email-otp/send-verification-otp", serde_json::json!({ "email": "jane@example.com", "type": "forget-password" })).await;
+        assert_eq!(response.status, 200);
+
+        let sent = sender.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "jane@example.com");
+        assert_eq!(sent[0].subject.as_deref(), Some("Reset your password"));
+        assert!(sent[0].body.contains("424242"));
+    }
+
+    #[tokio::test]
+    async fn test_send_failure_is_reported() {
+        let plugin = EmailOtpPlugin::new(
+            EmailOtpConfig::new().send_verification_otp(|_| async { Err("smtp down".to_string()) }),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let response = send(&router, "/email-otp/send-verification-otp", serde_json::json!({ "email": "jane@example.com", "type": "sign-in" })).await;
+        assert_eq!(response.status, 500);
+        assert_eq!(response.body.unwrap()["error"]["code"], "SEND_FAILED");
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_marks_user_verified() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::traits::StorageAdapter;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        storage
+            .create_user(&User::new("user_1".to_string(), "jane@example.com".to_string()))
+            .await
+            .unwrap();
+        let plugin = EmailOtpPlugin::new(
+            EmailOtpConfig::new()
+                .otp_store(Arc::new(InMemoryEmailOtpStore::new()))
+                .storage(storage.clone())
+                .allowed_attempts(2)
+                .generate_otp_with(|| "424242".to_string()),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let verify = |otp: &str| send(&router, "/email-otp/verify-email", serde_json::json!({ "email": "jane@example.com", "otp": otp }));
+        assert_eq!(verify("424242").await.body.unwrap()["error"]["code"], "INVALID_OTP");
+
+        send(&router, "/email-otp/send-verification-otp", serde_json::json!({ "email": "Jane@example.com", "type": "email-verification" })).await;
+        let response = verify("000000").await;
+        assert_eq!(response.status, 401);
+        assert!(!storage.get_user_by_id("user_1").await.unwrap().unwrap().email_verified);
+
+        let response = verify("424242").await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.unwrap()["user"]["email_verified"], true);
+        assert!(storage.get_user_by_id("user_1").await.unwrap().unwrap().email_verified);
+
+        // The OTP is used up.
+        assert_eq!(verify("424242").await.status, 401);
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_creates_a_session_from_the_token_strategy() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::SessionTokenStrategy;
+        use better_auth_core::traits::StorageAdapter;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let strategy = SessionTokenStrategy::signed("a-long-random-secret");
+        let plugin = EmailOtpPlugin::new(
+            EmailOtpConfig::new()
+                .otp_store(Arc::new(InMemoryEmailOtpStore::new()))
+                .storage(storage.clone())
+                .session_tokens(strategy.clone())
+                .generate_otp_with(|| "424242".to_string()),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let sign_in = |otp: &str| send(&router, "/sign-in/email-otp", serde_json::json!({ "email": "Jane@example.com", "otp": otp }));
+        assert_eq!(sign_in("424242").await.status, 401);
+
+        send(&router, "/email-otp/send-verification-otp", serde_json::json!({ "email": "jane@example.com", "type": "sign-in" })).await;
+        let response = sign_in("424242").await;
+        assert_eq!(response.status, 200);
+        let body = response.body.unwrap();
+        assert_eq!(body["user"]["email"], "jane@example.com");
+        assert_eq!(body["user"]["email_verified"], true);
+        let token = body["session"]["token"].as_str().unwrap();
+        assert!(strategy.verify(token).is_some());
+        let session = storage.get_session_by_token(token).await.unwrap().unwrap();
+        assert!(session.factor_verified_at(AuthFactor::Otp).is_some());
+
+        // The OTP is used up.
+        assert_eq!(sign_in("424242").await.status, 401);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_emails_indistinguishable_when_enumeration_prevented() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::traits::StorageAdapter;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        storage
+            .create_user(&User::new("user_1".to_string(), "jane@example.com".to_string()))
+            .await
+            .unwrap();
+        let sender = Arc::new(better_auth_otp_utils::testing::RecordingSender::new());
+        let otp_store = Arc::new(InMemoryEmailOtpStore::new());
+        let config = EmailOtpConfig::new()
+            .storage(storage)
+            .otp_store(otp_store.clone())
+            .sender(sender.clone());
+
+        let plugin = EmailOtpPlugin::new(config.clone().prevent_user_enumeration(false));
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let response = send(&router, "/email-otp/request-password-reset", serde_json::json!({ "email": "nobody@example.com" })).await;
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body.unwrap()["error"]["code"], "USER_NOT_FOUND");
+
+        // Enumeration is prevented by default.
+        let plugin = EmailOtpPlugin::new(config);
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let requests = [
+            ("/email-otp/request-password-reset", serde_json::json!({})),
+            ("/email-otp/send-verification-otp", serde_json::json!({ "type": "forget-password" })),
+            ("/email-otp/send-verification-otp", serde_json::json!({ "type": "email-verification" })),
+        ];
+        for (path, mut body) in requests {
+            body["email"] = "jane@example.com".into();
+            let known = send(&router, path, body.clone()).await;
+            body["email"] = "nobody@example.com".into();
+            let unknown = send(&router, path, body).await;
+            assert_eq!(known.status, 200, "{}", path);
+            assert_eq!(known.status, unknown.status, "{}", path);
+            assert_eq!(known.body, unknown.body, "{}", path);
+        }
+
+        // The unknown email still had a code stored, as a known one would.
+        assert!(otp_store.get_email_otp("nobody@example.com", "forget-password").await.unwrap().is_some());
+
+        // Only the real account was sent anything, in the background.
+        while sender.sent().len() < 3 {
+            tokio::task::yield_now().await;
+        }
+        let sent = sender.sent();
+        assert_eq!(sent.len(), 3);
+        assert!(sent.iter().all(|m| m.to == "jane@example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_otp_locked_after_allowed_attempts() {
+        let plugin = EmailOtpPlugin::new(
+            EmailOtpConfig::new()
+                .otp_store(Arc::new(InMemoryEmailOtpStore::new()))
+                .allowed_attempts(2)
+                .generate_otp_with(|| "424242".to_string()),
+        );
+        let config = plugin.config();
+        let store = config.otp_store.clone().unwrap();
+        config.send_otp("jane@example.com", OtpPurpose::SignIn).await.unwrap();
+
+        for _ in 0..2 {
+            let result = config.check_otp(store.as_ref(), "jane@example.com", OtpPurpose::SignIn, "000000").await;
+            assert!(matches!(result, Err(AuthError::InvalidToken)));
+        }
+        let result = config.check_otp(store.as_ref(), "jane@example.com", OtpPurpose::SignIn, "424242").await;
+        assert!(matches!(result, Err(AuthError::InvalidToken)));
+    }
+    #[tokio::test]
+    async fn test_hashed_otps_are_not_stored_in_plain_text() {
+        let plugin = EmailOtpPlugin::new(
+            EmailOtpConfig::new()
+                .otp_store(Arc::new(InMemoryEmailOtpStore::new()))
+                .store_otp(OtpStorageMode::Hashed)
+                .generate_otp_with(|| "424242".to_string()),
+        );
+        let config = plugin.config();
+        let store = config.otp_store.clone().unwrap();
+        config.send_otp("jane@example.com", OtpPurpose::SignIn).await.unwrap();
+
+        let stored = store.get_email_otp("jane@example.com", "sign-in").await.unwrap().unwrap();
+        assert_eq!(stored.otp, better_auth_core::hash_secret("424242"));
+
+        let result = config.check_otp(store.as_ref(), "jane@example.com", OtpPurpose::SignIn, &stored.otp).await;
+        assert!(matches!(result, Err(AuthError::InvalidToken)));
+        config.check_otp(store.as_ref(), "jane@example.com", OtpPurpose::SignIn, "424242").await.unwrap();
+    }
 }
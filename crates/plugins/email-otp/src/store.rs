@@ -0,0 +1,71 @@
+//! Storage for sent OTPs.
+
+use crate::EmailOtp;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use std::sync::RwLock;
+
+/// Storage for sent OTPs.
+///
+/// Adapters implement this trait to persist the `email_otp` model. Emails
+/// are passed in lowercase.
+#[async_trait]
+pub trait EmailOtpStore: Send + Sync {
+    /// Stores a sent OTP, replacing any earlier OTP for the same email and
+    /// type.
+    async fn create_email_otp(&self, otp: &EmailOtp) -> AuthResult<EmailOtp>;
+
+    /// Gets the OTP of the given type sent to `email`, expired or not.
+    async fn get_email_otp(&self, email: &str, otp_type: &str) -> AuthResult<Option<EmailOtp>>;
+
+    /// Updates an OTP, to record a failed attempt.
+    async fn update_email_otp(&self, otp: &EmailOtp) -> AuthResult<EmailOtp>;
+
+    /// Deletes an OTP by ID.
+    async fn delete_email_otp(&self, id: &str) -> AuthResult<()>;
+}
+
+/// In-memory OTP store.
+///
+/// Suitable for a single instance and for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryEmailOtpStore {
+    otps: RwLock<Vec<EmailOtp>>,
+}
+
+impl InMemoryEmailOtpStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl EmailOtpStore for InMemoryEmailOtpStore {
+    async fn create_email_otp(&self, otp: &EmailOtp) -> AuthResult<EmailOtp> {
+        let mut otps = self.otps.write().unwrap();
+        otps.retain(|o| o.email != otp.email || o.otp_type != otp.otp_type);
+        otps.push(otp.clone());
+        Ok(otp.clone())
+    }
+
+    async fn get_email_otp(&self, email: &str, otp_type: &str) -> AuthResult<Option<EmailOtp>> {
+        let otps = self.otps.read().unwrap();
+        Ok(otps
+            .iter()
+            .find(|o| o.email == email && o.otp_type == otp_type)
+            .cloned())
+    }
+
+    async fn update_email_otp(&self, otp: &EmailOtp) -> AuthResult<EmailOtp> {
+        let mut otps = self.otps.write().unwrap();
+        if let Some(existing) = otps.iter_mut().find(|o| o.id == otp.id) {
+            *existing = otp.clone();
+        }
+        Ok(otp.clone())
+    }
+
+    async fn delete_email_otp(&self, id: &str) -> AuthResult<()> {
+        self.otps.write().unwrap().retain(|o| o.id != id);
+        Ok(())
+    }
+}
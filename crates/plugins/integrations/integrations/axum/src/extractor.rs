@@ -4,6 +4,8 @@ use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
+use better_auth_core::session::AuthScheme;
+use crate::AuthErrorResponse;
 use better_auth_core::types::{Session, User};
 
 /// Extractor for authenticated sessions.
@@ -24,6 +26,23 @@ pub struct AuthSession {
     pub user: User,
     /// The current session.
     pub session: Session,
+    /// The scheme the session was resolved from, if the layer recorded one.
+    pub scheme: Option<AuthScheme>,
+}
+
+impl AuthSession {
+    /// Rejects the request with 403 unless the user fully authenticated
+    /// within `max_age`.
+    ///
+    /// ```rust,ignore
+    /// async fn delete_account(session: AuthSession) -> Result<String, AuthErrorResponse> {
+    ///     session.require_recent_auth(chrono::Duration::minutes(5))?;
+    ///     Ok("deleted".to_string())
+    /// }
+    /// ```
+    pub fn require_recent_auth(&self, max_age: chrono::Duration) -> Result<(), AuthErrorResponse> {
+        self.session.require_recent_auth(max_age).map_err(AuthErrorResponse)
+    }
 }
 
 /// Error returned when authentication fails.
@@ -35,8 +54,7 @@ pub struct AuthSessionRejection {
 impl IntoResponse for AuthSessionRejection {
     fn into_response(self) -> Response {
         let body = serde_json::json!({
-            "error": self.message,
-            "code": 401
+            "error": { "code": "UNAUTHORIZED", "message": self.message }
         });
         (StatusCode::UNAUTHORIZED, axum::Json(body)).into_response()
     }
@@ -66,7 +84,13 @@ where
                 message: "No user found".to_string(),
             })?;
 
-        Ok(AuthSession { user, session })
+        let scheme = parts.extensions.get::<AuthScheme>().cloned();
+
+        Ok(AuthSession {
+            user,
+            session,
+            scheme,
+        })
     }
 }
 
@@ -97,9 +121,14 @@ where
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let session = parts.extensions.get::<Session>().cloned();
         let user = parts.extensions.get::<User>().cloned();
+        let scheme = parts.extensions.get::<AuthScheme>().cloned();
 
         match (session, user) {
-            (Some(session), Some(user)) => Ok(OptionalAuthSession(Some(AuthSession { user, session }))),
+            (Some(session), Some(user)) => Ok(OptionalAuthSession(Some(AuthSession {
+                user,
+                session,
+                scheme,
+            }))),
             _ => Ok(OptionalAuthSession(None)),
         }
     }
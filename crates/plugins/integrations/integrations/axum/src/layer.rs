@@ -1,9 +1,12 @@
 //! Authentication middleware layer for Axum.
 
+use crate::AuthSession;
 use axum::body::Body;
 use axum::http::{Request, Response};
+use better_auth_core::session::{AuthScheme, SessionResolver};
 use better_auth_core::traits::StorageAdapter;
 use better_auth_core::types::{Session, User};
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -73,9 +76,32 @@ impl AuthLayerConfig {
 }
 
 /// Layer that adds authentication to routes.
+///
+/// Sessions are resolved with a [`SessionResolver`]; by default it checks a
+/// bearer token, then the session cookie. The matched
+/// [`AuthScheme`](better_auth_core::session::AuthScheme) is inserted into the
+/// request extensions alongside the `Session` and `User`.
+///
+/// Every request that passes through the layer also gets an
+/// `Option<AuthSession>` extension: `Some` when a session was resolved and
+/// `None` otherwise. Plain handlers and other tower layers can read it
+/// without the extractors:
+///
+/// ```rust,ignore
+/// let auth = req.extensions().get::<Option<AuthSession>>().cloned().flatten();
+/// ```
+///
+/// The layer never rejects a request itself; routes that need a session use
+/// the [`AuthSession`] extractor or check the extension.
+///
+/// If the resolver has a session refresh threshold, stored sessions are
+/// extended as in [`SessionResolver::touch_session`] on every authenticated
+/// request, so active users are not signed out mid-use. Their `last_used_at`
+/// is kept current as in [`SessionResolver::record_use`], in the same write.
 #[derive(Clone)]
 pub struct AuthLayer {
     adapter: Arc<dyn StorageAdapter>,
+    resolver: SessionResolver,
     config: AuthLayerConfig,
     #[cfg(feature = "jwt")]
     jwt_codec: Option<JwtCodec>,
@@ -85,6 +111,7 @@ impl AuthLayer {
     /// Creates a new auth layer with the given storage adapter.
     pub fn new(adapter: Arc<dyn StorageAdapter>) -> Self {
         Self {
+            resolver: SessionResolver::new(adapter.clone()),
             adapter,
             config: AuthLayerConfig::default(),
             #[cfg(feature = "jwt")]
@@ -111,12 +138,20 @@ impl AuthLayer {
         };
 
         Self {
+            resolver: SessionResolver::new(adapter.clone()),
             adapter,
             config,
             #[cfg(feature = "jwt")]
             jwt_codec,
         }
     }
+
+    /// Sets the resolver used to find sessions, replacing the default
+    /// bearer-then-cookie order.
+    pub fn with_resolver(mut self, resolver: SessionResolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
 }
 
 impl<S> Layer<S> for AuthLayer {
@@ -126,6 +161,7 @@ impl<S> Layer<S> for AuthLayer {
         AuthMiddleware {
             inner,
             adapter: self.adapter.clone(),
+            resolver: self.resolver.clone(),
             #[cfg(feature = "jwt")]
             jwt_codec: self.jwt_codec.clone(),
             #[cfg(not(feature = "jwt"))]
@@ -139,25 +175,23 @@ impl<S> Layer<S> for AuthLayer {
 pub struct AuthMiddleware<S> {
     inner: S,
     adapter: Arc<dyn StorageAdapter>,
+    resolver: SessionResolver,
     #[cfg(feature = "jwt")]
     jwt_codec: Option<JwtCodec>,
     #[cfg(not(feature = "jwt"))]
     _config: AuthLayerConfig,
 }
 
-/// Result of token extraction and validation.
+/// Result of JWT extraction and validation.
+#[cfg(feature = "jwt")]
 #[derive(Debug)]
-enum AuthResult {
-    /// No token found.
+enum JwtResult {
+    /// No bearer JWT on the request.
     NoToken,
-    /// Session token found.
-    SessionToken(String),
     /// JWT found and validated.
-    #[cfg(feature = "jwt")]
-    JwtClaims(AccessTokenClaims),
-    /// Invalid JWT.
-    #[cfg(feature = "jwt")]
-    InvalidJwt,
+    Claims(Box<AccessTokenClaims>),
+    /// Invalid JWT; the request may still carry a session cookie.
+    Invalid,
 }
 
 impl<S> Service<Request<Body>> for AuthMiddleware<S>
@@ -175,119 +209,125 @@ where
 
     fn call(&mut self, mut req: Request<Body>) -> Self::Future {
         let adapter = self.adapter.clone();
+        let resolver = self.resolver.clone();
         let mut inner = self.inner.clone();
 
         #[cfg(feature = "jwt")]
         let jwt_codec = self.jwt_codec.clone();
 
         Box::pin(async move {
-            // Extract and validate token
+            // Bearer JWTs are validated locally and take precedence over the
+            // resolver's schemes. A JWT that fails validation is ignored, so
+            // the resolver can still find a session cookie.
             #[cfg(feature = "jwt")]
-            let auth_result = extract_and_validate_token(&req, jwt_codec.as_ref());
-
-            #[cfg(not(feature = "jwt"))]
-            let auth_result = extract_token_simple(&req);
-
-            match auth_result {
-                AuthResult::SessionToken(token) => {
-                    // Validate session token against database
-                    if let Ok(Some(session)) = adapter.get_session_by_token(&token).await {
-                        if !session.is_expired() {
-                            if let Ok(Some(user)) = adapter.get_user_by_id(&session.user_id).await {
-                                req.extensions_mut().insert(session);
-                                req.extensions_mut().insert(user);
-                            }
-                        }
-                    }
-                }
-                #[cfg(feature = "jwt")]
-                AuthResult::JwtClaims(claims) => {
-                    // JWT is valid, get user from database
-                    if let Ok(Some(user)) = adapter.get_user_by_id(&claims.sub).await {
-                        req.extensions_mut().insert(user.clone());
-
-                        // If JWT has a session_id, try to get the session
-                        if let Some(session_id) = &claims.session_id {
-                            if let Ok(Some(session)) =
-                                adapter.get_session_by_id(session_id).await
-                            {
-                                if !session.is_expired() {
-                                    req.extensions_mut().insert(session);
-                                }
-                            }
-                        } else {
-                            // Create a synthetic session from JWT claims
-                            let session = create_session_from_jwt(&claims, &user);
+            if let JwtResult::Claims(claims) = extract_jwt(&req, jwt_codec.as_ref()) {
+                // JWT is valid, get user from database
+                if let Ok(Some(user)) = adapter.get_user_by_id(&claims.sub).await {
+                    req.extensions_mut().insert(user.clone());
+
+                    // If JWT has a session_id, try to get the session
+                    if let Some(session_id) = &claims.session_id {
+                        if let Ok(Some(mut session)) = adapter.get_session_by_id(session_id).await
+                            && !session.is_expired()
+                        {
+                            note_use(&resolver, &mut session).await;
                             req.extensions_mut().insert(session);
                         }
-
-                        // Also insert the JWT claims for handlers that need them
-                        req.extensions_mut().insert(claims);
+                    } else {
+                        // Create a synthetic session from JWT claims
+                        let session = create_session_from_jwt(&claims, &user);
+                        req.extensions_mut().insert(session);
                     }
+
+                    req.extensions_mut().insert(AuthScheme::Bearer);
+                    // Also insert the JWT claims for handlers that need them
+                    req.extensions_mut().insert(*claims);
                 }
-                #[cfg(feature = "jwt")]
-                AuthResult::InvalidJwt => {
-                    // Invalid JWT - don't authenticate
-                    // Could optionally return 401 here
-                }
-                AuthResult::NoToken => {
-                    // No token - continue without authentication
+                insert_auth_session(&mut req);
+                return inner.call(req).await;
+            }
+
+            let resolved = resolver.resolve(header_lookup(&req)).await;
+            if let Ok(Some(mut resolved)) = resolved
+                && let Ok(Some(user)) = adapter.get_user_by_id(&resolved.session.user_id).await
+            {
+                // API key sessions are not stored, so there is nothing to
+                // extend.
+                if !matches!(resolved.scheme, AuthScheme::ApiKey { .. }) {
+                    note_use(&resolver, &mut resolved.session).await;
                 }
+                req.extensions_mut().insert(resolved.session);
+                req.extensions_mut().insert(resolved.scheme);
+                req.extensions_mut().insert(user);
             }
 
+            insert_auth_session(&mut req);
             inner.call(req).await
         })
     }
 }
 
-/// Extracts token from request (simple version without JWT).
-#[cfg(not(feature = "jwt"))]
-fn extract_token_simple(req: &Request<Body>) -> AuthResult {
-    if let Some(token) = extract_bearer_token(req) {
-        return AuthResult::SessionToken(token);
+/// Slides `session`'s expiry and records its use in one write. A failure is
+/// logged and leaves the session as it was, so the request still goes
+/// through.
+async fn note_use(resolver: &SessionResolver, session: &mut Session) {
+    if let Err(err) = resolver.touch_and_record_use(session).await {
+        tracing::warn!(session_id = %session.id, error = %err, "failed to update session on use");
     }
+}
 
-    if let Some(token) = extract_cookie_token(req) {
-        return AuthResult::SessionToken(token);
-    }
+/// Inserts `Option<AuthSession>` built from the `Session`, `User` and
+/// `AuthScheme` extensions, or `None` if either of the first two is missing.
+fn insert_auth_session(req: &mut Request<Body>) {
+    let extensions = req.extensions();
+    let auth = match (extensions.get::<Session>(), extensions.get::<User>()) {
+        (Some(session), Some(user)) => Some(AuthSession {
+            user: user.clone(),
+            session: session.clone(),
+            scheme: extensions.get::<AuthScheme>().cloned(),
+        }),
+        _ => None,
+    };
+    req.extensions_mut().insert(auth);
+}
 
-    AuthResult::NoToken
+/// Returns a lookup over the request's headers for [`SessionResolver`].
+///
+/// Headers are copied out so the lookup can be held across awaits without
+/// borrowing the (non-`Sync`) request body.
+fn header_lookup(req: &Request<Body>) -> impl Fn(&str) -> Option<String> + Send + Sync + use<> {
+    let headers: HashMap<String, String> = req
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    move |name| headers.get(name).cloned()
 }
 
-/// Extracts and validates token from request (with JWT support).
+/// Extracts and validates a bearer JWT, if the bearer token looks like one.
 #[cfg(feature = "jwt")]
-fn extract_and_validate_token(req: &Request<Body>, jwt_codec: Option<&JwtCodec>) -> AuthResult {
-    // Try Authorization header first
-    if let Some(token) = extract_bearer_token(req) {
-        // Check if it looks like a JWT (has 3 parts separated by dots)
-        if token.matches('.').count() == 2 {
-            // Try to validate as JWT
-            if let Some(codec) = jwt_codec {
-                match codec.decode::<AccessTokenClaims>(&token) {
-                    Ok(token_data) => {
-                        return AuthResult::JwtClaims(token_data.claims);
-                    }
-                    Err(_) => {
-                        // Invalid JWT
-                        return AuthResult::InvalidJwt;
-                    }
+fn extract_jwt(req: &Request<Body>, jwt_codec: Option<&JwtCodec>) -> JwtResult {
+    let Some(codec) = jwt_codec else {
+        return JwtResult::NoToken;
+    };
+
+    // Only tokens with 3 dot-separated parts are treated as JWTs; anything
+    // else is left to the resolver as a session token.
+    match extract_bearer_token(req) {
+        Some(token) if token.matches('.').count() == 2 => {
+            match codec.decode::<AccessTokenClaims>(&token) {
+                Ok(token_data) if token_data.claims.is_access_token() => {
+                    JwtResult::Claims(Box::new(token_data.claims))
                 }
+                _ => JwtResult::Invalid,
             }
         }
-
-        // Not a JWT or JWT validation disabled, treat as session token
-        return AuthResult::SessionToken(token);
-    }
-
-    // Try cookie
-    if let Some(token) = extract_cookie_token(req) {
-        return AuthResult::SessionToken(token);
+        _ => JwtResult::NoToken,
     }
-
-    AuthResult::NoToken
 }
 
 /// Extracts bearer token from Authorization header.
+#[cfg(any(feature = "jwt", test))]
 fn extract_bearer_token(req: &Request<Body>) -> Option<String> {
     req.headers()
         .get("authorization")
@@ -296,20 +336,6 @@ fn extract_bearer_token(req: &Request<Body>) -> Option<String> {
         .map(|v| v[7..].to_string())
 }
 
-/// Extracts session token from cookie.
-fn extract_cookie_token(req: &Request<Body>) -> Option<String> {
-    req.headers()
-        .get("cookie")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|cookies| {
-            cookies
-                .split(';')
-                .map(|c| c.trim())
-                .find(|c| c.starts_with("better_auth_session="))
-                .map(|c| c[20..].to_string())
-        })
-}
-
 /// Creates a synthetic session from JWT claims.
 #[cfg(feature = "jwt")]
 fn create_session_from_jwt(claims: &AccessTokenClaims, user: &User) -> Session {
@@ -326,6 +352,10 @@ fn create_session_from_jwt(claims: &AccessTokenClaims, user: &User) -> Session {
         expires_at,
         created_at: claims.issued_at().unwrap_or_else(Utc::now),
         updated_at: Utc::now(),
+        // Refreshed access tokens get a new `iat`, so it does not say when the
+        // user last signed in; treat JWT sessions as needing re-authentication.
+        authenticated_at: DateTime::UNIX_EPOCH,
+        last_used_at: None,
         ip_address: None,
         user_agent: None,
         extensions: std::collections::HashMap::new(),
@@ -335,6 +365,88 @@ fn create_session_from_jwt(claims: &AccessTokenClaims, user: &User) -> Session {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use better_auth_adapter_memory::MemoryAdapter;
+    use better_auth_core::session::SESSION_COOKIE;
+    use tower::ServiceExt;
+
+    /// Runs `req` through the layer, returning the `Option<AuthSession>` a
+    /// downstream service sees.
+    async fn downstream_auth(layer: &AuthLayer, req: Request<Body>) -> Option<AuthSession> {
+        let service = layer.layer(tower::service_fn(|req: Request<Body>| async move {
+            let auth = req.extensions().get::<Option<AuthSession>>().cloned();
+            let mut response = Response::new(Body::empty());
+            response.extensions_mut().insert(auth);
+            Ok::<_, std::convert::Infallible>(response)
+        }));
+        let response = service.oneshot(req).await.unwrap();
+        response
+            .extensions()
+            .get::<Option<Option<AuthSession>>>()
+            .cloned()
+            .flatten()
+            .expect("layer inserts the extension on every request")
+    }
+
+    #[tokio::test]
+    async fn test_downstream_service_sees_user() {
+        let adapter = Arc::new(MemoryAdapter::new());
+        let user = adapter
+            .create_user(&User::new(
+                "user_1".to_string(),
+                "a@example.com".to_string(),
+            ))
+            .await
+            .unwrap();
+        let session = adapter
+            .create_session(&Session::new(user.id.clone()))
+            .await
+            .unwrap();
+        let layer = AuthLayer::new(adapter);
+
+        let req = Request::builder()
+            .header("authorization", format!("Bearer {}", session.token))
+            .body(Body::empty())
+            .unwrap();
+        let auth = downstream_auth(&layer, req).await.unwrap();
+        assert_eq!(auth.user.id, "user_1");
+        assert_eq!(auth.session.id, session.id);
+
+        // Requests without a valid session pass through with `None`.
+        let req = Request::builder().body(Body::empty()).unwrap();
+        assert!(downstream_auth(&layer, req).await.is_none());
+        let req = Request::builder()
+            .header("authorization", "Bearer unknown")
+            .body(Body::empty())
+            .unwrap();
+        assert!(downstream_auth(&layer, req).await.is_none());
+    }
+
+    #[cfg(feature = "jwt")]
+    #[tokio::test]
+    async fn test_invalid_jwt_falls_back_to_cookie() {
+        let adapter = Arc::new(MemoryAdapter::new());
+        let user = adapter
+            .create_user(&User::new(
+                "user_1".to_string(),
+                "a@example.com".to_string(),
+            ))
+            .await
+            .unwrap();
+        let session = adapter
+            .create_session(&Session::new(user.id.clone()))
+            .await
+            .unwrap();
+        let layer = AuthLayer::with_config(adapter, AuthLayerConfig::new().with_jwt("secret"));
+
+        let req = Request::builder()
+            .header("authorization", "Bearer not.a.jwt")
+            .header("cookie", format!("{SESSION_COOKIE}={}", session.token))
+            .body(Body::empty())
+            .unwrap();
+        let auth = downstream_auth(&layer, req).await.unwrap();
+        assert_eq!(auth.session.id, session.id);
+        assert_eq!(auth.scheme, Some(AuthScheme::session_cookie()));
+    }
 
     #[test]
     fn test_extract_bearer_token() {
@@ -350,11 +462,14 @@ mod tests {
     #[test]
     fn test_extract_cookie_token() {
         let req = Request::builder()
-            .header("cookie", "other=value; better_auth_session=session_token_456; another=test")
+            .header(
+                "cookie",
+                format!("other=value; {SESSION_COOKIE}=session_token_456; another=test"),
+            )
             .body(Body::empty())
             .unwrap();
 
-        let token = extract_cookie_token(&req);
+        let token = AuthScheme::session_cookie().extract(header_lookup(&req));
         assert_eq!(token, Some("session_token_456".to_string()));
     }
 
@@ -363,6 +478,6 @@ mod tests {
         let req = Request::builder().body(Body::empty()).unwrap();
 
         assert!(extract_bearer_token(&req).is_none());
-        assert!(extract_cookie_token(&req).is_none());
+        assert!(AuthScheme::session_cookie().extract(header_lookup(&req)).is_none());
     }
 }
@@ -87,20 +87,27 @@ pub fn to_auth_request(
 pub fn to_axum_response(auth_response: AuthResponse) -> Response {
     let status = StatusCode::from_u16(auth_response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
 
-    let mut response = if let Some(body) = auth_response.body {
-        axum::Json(body).into_response()
-    } else {
-        status.into_response()
+    let mut response = match (auth_response.raw_body, auth_response.body) {
+        (Some(text), _) => text.into_response(),
+        (None, Some(body)) => axum::Json(body).into_response(),
+        (None, None) => status.into_response(),
     };
 
     *response.status_mut() = status;
 
     // Add headers
     for (key, value) in auth_response.headers {
-        if let (Ok(name), Ok(val)) = (
-            axum::http::header::HeaderName::try_from(key),
-            axum::http::header::HeaderValue::try_from(value),
-        ) {
+        let Ok(name) = axum::http::header::HeaderName::try_from(key) else {
+            continue;
+        };
+        // Each cookie is sent as its own `Set-Cookie` header.
+        if name == axum::http::header::SET_COOKIE {
+            for cookie in value.lines() {
+                if let Ok(val) = axum::http::header::HeaderValue::try_from(cookie) {
+                    response.headers_mut().append(&name, val);
+                }
+            }
+        } else if let Ok(val) = axum::http::header::HeaderValue::try_from(value) {
             response.headers_mut().insert(name, val);
         }
     }
@@ -115,8 +122,7 @@ impl IntoResponse for AuthErrorResponse {
     fn into_response(self) -> Response {
         let status = StatusCode::from_u16(self.0.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
         let body = serde_json::json!({
-            "error": self.0.to_string(),
-            "code": self.0.status_code()
+            "error": { "code": self.0.error_code(), "message": self.0.to_string() }
         });
 
         (status, axum::Json(body)).into_response()
@@ -128,3 +134,55 @@ impl From<AuthError> for AuthErrorResponse {
         AuthErrorResponse(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_error_response_has_code_and_message() {
+        let err = AuthError::WeakPassword {
+            reason: "too short".into(),
+        };
+        let response = AuthErrorResponse(err).into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "WEAK_PASSWORD");
+        assert_eq!(
+            body["error"]["message"],
+            "Password does not meet requirements: too short"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_text_response_is_sent_raw() {
+        let response = to_axum_response(AuthResponse::ok().html("<h1>Hi</h1>"));
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/html; charset=utf-8"
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&bytes[..], b"<h1>Hi</h1>");
+
+        let response = to_axum_response(AuthResponse::ok().json("plain"));
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&bytes[..], b"\"plain\"");
+    }
+
+    #[test]
+    fn test_each_cookie_is_a_set_cookie_header() {
+        let options = better_auth_core::router::CookieOptions::new;
+        let response = AuthResponse::ok().cookie("a", "1", options());
+        let response = to_axum_response(response.cookie("b", "2", options()));
+        let cookies: Vec<_> = response.headers().get_all("set-cookie").iter().collect();
+        assert_eq!(cookies, ["a=1", "b=2"]);
+    }
+}
@@ -1,7 +1,13 @@
 //! Route mounting for Better Auth routes.
 
-use axum::routing::{get, post};
+use crate::{to_auth_request, to_axum_response};
 use axum::Router;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, Method, Uri};
+use axum::response::Response;
+use axum::routing::{delete, get, post};
+use better_auth_core::router::RequestHandler;
+use better_auth_core::session::{ListSessions, RevokeSession, SessionResolver};
 use better_auth_core::traits::StorageAdapter;
 use std::sync::Arc;
 
@@ -25,6 +31,9 @@ where
         .route("/signout", post(signout_handler))
         .route("/session", get(session_handler))
         .route("/user", get(user_handler))
+        // Session management
+        .route("/sessions", get(list_sessions_handler))
+        .route("/sessions/{id}", delete(revoke_session_handler))
         .with_state(AuthState { adapter })
 }
 
@@ -34,6 +43,36 @@ struct AuthState {
     adapter: Arc<dyn StorageAdapter>,
 }
 
+impl AuthState {
+    fn resolver(&self) -> SessionResolver {
+        SessionResolver::new(self.adapter.clone())
+    }
+}
+
+/// `GET /sessions`, served by core's [`ListSessions`].
+async fn list_sessions_handler(
+    State(state): State<AuthState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Response {
+    let req = to_auth_request(method, &uri, &headers, None);
+    to_axum_response(ListSessions::new(state.resolver()).handle(req).await)
+}
+
+/// `DELETE /sessions/{id}`, served by core's [`RevokeSession`].
+async fn revoke_session_handler(
+    State(state): State<AuthState>,
+    Path(id): Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Response {
+    let mut req = to_auth_request(method, &uri, &headers, None);
+    req.params.insert("id".to_string(), id);
+    to_axum_response(RevokeSession::new(state.resolver()).handle(req).await)
+}
+
 // Handler implementations (placeholders)
 
 async fn signup_handler() -> &'static str {
@@ -60,3 +99,52 @@ async fn user_handler() -> &'static str {
     // TODO: Implement user info
     "user"
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use better_auth_adapter_memory::MemoryAdapter;
+    use better_auth_core::types::Session;
+    use tower::ServiceExt;
+
+    async fn status(router: &Router, method: &str, uri: &str, token: Option<&str>) -> u16 {
+        let mut req = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            req = req.header("authorization", format!("Bearer {}", token));
+        }
+        let response = router
+            .clone()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        response.status().as_u16()
+    }
+
+    #[tokio::test]
+    async fn test_session_routes_are_mounted() {
+        let adapter = Arc::new(MemoryAdapter::new());
+        let laptop = adapter
+            .create_session(&Session::new("user_1".to_string()))
+            .await
+            .unwrap();
+        let phone = adapter
+            .create_session(&Session::new("user_1".to_string()))
+            .await
+            .unwrap();
+        let router: Router = auth_routes(adapter);
+
+        assert_eq!(status(&router, "GET", "/sessions", None).await, 401);
+        assert_eq!(
+            status(&router, "GET", "/sessions", Some(&phone.token)).await,
+            200
+        );
+        let revoke = format!("/sessions/{}", laptop.id);
+        assert_eq!(status(&router, "DELETE", &revoke, None).await, 401);
+        assert_eq!(
+            status(&router, "DELETE", &revoke, Some(&phone.token)).await,
+            204
+        );
+    }
+}
@@ -1,9 +1,22 @@
 //! JWT claims structures.
 
+use crate::token::JwtError;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Claims that [`AccessTokenClaims`] sets itself and that custom claims may
+/// not override.
+pub const RESERVED_CLAIMS: &[&str] = &[
+    "sub", "iat", "exp", "nbf", "jti", "iss", "aud", "session_id", "email", "name", "typ",
+];
+
+/// `typ` claim of access tokens.
+pub const ACCESS_TOKEN_TYPE: &str = "access";
+
+/// `typ` claim of refresh tokens.
+pub const REFRESH_TOKEN_TYPE: &str = "refresh";
+
 /// Standard JWT claims for access tokens.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccessTokenClaims {
@@ -44,6 +57,11 @@ pub struct AccessTokenClaims {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
+    /// Token type, [`ACCESS_TOKEN_TYPE`]. Tokens issued before the claim
+    /// was added have none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+
     /// Custom claims.
     #[serde(flatten)]
     pub custom: HashMap<String, serde_json::Value>,
@@ -64,6 +82,7 @@ impl AccessTokenClaims {
             session_id: None,
             email: None,
             name: None,
+            typ: Some(ACCESS_TOKEN_TYPE.to_string()),
             custom: HashMap::new(),
         }
     }
@@ -106,11 +125,41 @@ impl AccessTokenClaims {
         self
     }
 
+    /// Merges custom claims into the token.
+    ///
+    /// Fails with [`JwtError::ReservedClaim`] if `extra` contains any of
+    /// [`RESERVED_CLAIMS`]; nothing is merged in that case.
+    pub fn with_claims(
+        mut self,
+        extra: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Self, JwtError> {
+        if let Some(key) = extra.keys().find(|key| RESERVED_CLAIMS.contains(&key.as_str())) {
+            return Err(JwtError::ReservedClaim(key.clone()));
+        }
+        self.custom.extend(extra);
+        Ok(self)
+    }
+
+    /// Gets a custom claim.
+    pub fn claim(&self, key: &str) -> Option<&serde_json::Value> {
+        self.custom.get(key)
+    }
+
     /// Checks if the token has expired.
     pub fn is_expired(&self) -> bool {
         Utc::now().timestamp() > self.exp
     }
 
+    /// Returns false for other kinds of token that decode as access token
+    /// claims, such as refresh tokens. Refresh tokens without a `typ` are
+    /// recognized by their `family_id`.
+    pub fn is_access_token(&self) -> bool {
+        match self.typ.as_deref() {
+            Some(typ) => typ == ACCESS_TOKEN_TYPE,
+            None => !self.custom.contains_key("family_id"),
+        }
+    }
+
     /// Gets the expiration time as a DateTime.
     pub fn expires_at(&self) -> Option<DateTime<Utc>> {
         DateTime::from_timestamp(self.exp, 0)
@@ -148,6 +197,11 @@ pub struct RefreshTokenClaims {
     /// Session ID.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+
+    /// Token type, [`REFRESH_TOKEN_TYPE`]. Tokens issued before the claim
+    /// was added have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
 }
 
 impl RefreshTokenClaims {
@@ -162,6 +216,7 @@ impl RefreshTokenClaims {
             iss: None,
             family_id: Some(uuid::Uuid::new_v4().to_string()),
             session_id: None,
+            typ: Some(REFRESH_TOKEN_TYPE.to_string()),
         }
     }
 
@@ -187,6 +242,16 @@ impl RefreshTokenClaims {
     pub fn is_expired(&self) -> bool {
         Utc::now().timestamp() > self.exp
     }
+
+    /// Returns false for other kinds of token that decode as refresh token
+    /// claims, such as access tokens. Refresh tokens without a `typ` are
+    /// recognized by their `family_id`.
+    pub fn is_refresh_token(&self) -> bool {
+        match self.typ.as_deref() {
+            Some(typ) => typ == REFRESH_TOKEN_TYPE,
+            None => self.family_id.is_some(),
+        }
+    }
 }
 
 /// ID token claims (OpenID Connect compatible).
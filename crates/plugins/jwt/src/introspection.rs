@@ -0,0 +1,201 @@
+//! OAuth 2.0 token introspection (RFC 7662).
+//!
+//! `POST /jwt/introspect` lets resource servers ask whether an access token
+//! is currently active. Invalid, expired, and revoked tokens all produce
+//! `{"active": false}` with a 200 status, so callers learn nothing about why
+//! a token was rejected.
+
+use crate::revocation::RevocationStore;
+use crate::token::TokenGenerator;
+use crate::{validate_access, AccessTokenClaims};
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use better_auth_core::crypto::constant_time_eq;
+use better_auth_core::router::{Request, RequestHandler, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Credential callers must present to use the introspection endpoint.
+#[derive(Clone)]
+pub enum IntrospectionAuth {
+    /// `Authorization: Bearer <token>`.
+    Bearer(String),
+    /// `Authorization: Basic <base64(client_id:client_secret)>`.
+    Basic {
+        client_id: String,
+        client_secret: String,
+    },
+}
+
+impl IntrospectionAuth {
+    /// Checks an `Authorization` header value against this credential.
+    fn authorizes(&self, header: Option<&str>) -> bool {
+        let Some((scheme, value)) = header.and_then(|h| h.trim().split_once(' ')) else {
+            return false;
+        };
+        match self {
+            Self::Bearer(token) => {
+                scheme.eq_ignore_ascii_case("bearer") && constant_time_eq(value.trim(), token)
+            }
+            Self::Basic {
+                client_id,
+                client_secret,
+            } => {
+                if !scheme.eq_ignore_ascii_case("basic") {
+                    return false;
+                }
+                let Some(decoded) = STANDARD
+                    .decode(value.trim())
+                    .ok()
+                    .and_then(|bytes| String::from_utf8(bytes).ok())
+                else {
+                    return false;
+                };
+                match decoded.split_once(':') {
+                    // Evaluate both so timing does not reveal which part matched.
+                    Some((id, secret)) => {
+                        constant_time_eq(id, client_id) & constant_time_eq(secret, client_secret)
+                    }
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+/// Introspection response (RFC 7662 section 2.2).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+impl IntrospectionResponse {
+    /// The response for any token that is not active.
+    pub fn inactive() -> Self {
+        Self::default()
+    }
+
+    /// The response for a valid access token.
+    ///
+    /// `scope` and `client_id` come from custom claims of the same name.
+    pub fn active(claims: AccessTokenClaims) -> Self {
+        let custom_str = |key: &str| claims.claim(key).and_then(|v| v.as_str()).map(str::to_string);
+        Self {
+            active: true,
+            scope: custom_str("scope"),
+            client_id: custom_str("client_id"),
+            token_type: Some("Bearer".to_string()),
+            sub: Some(claims.sub),
+            exp: Some(claims.exp),
+            iat: Some(claims.iat),
+            iss: claims.iss,
+            aud: claims.aud,
+            jti: claims.jti,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct IntrospectRequest {
+    token: String,
+}
+
+/// Handler for POST /jwt/introspect
+#[derive(Clone)]
+pub(crate) struct IntrospectHandler {
+    pub(crate) generator: TokenGenerator,
+    pub(crate) revocation_store: Arc<dyn RevocationStore>,
+    pub(crate) auth: Option<IntrospectionAuth>,
+}
+
+impl IntrospectHandler {
+    /// Reads `token` from a JSON body or a form-encoded body string.
+    fn token(req: &Request) -> Option<String> {
+        if let Some(body) = req.json::<IntrospectRequest>() {
+            return Some(body.token);
+        }
+        let form = req.body.as_ref()?.as_str()?;
+        serde_urlencoded::from_str::<IntrospectRequest>(form)
+            .ok()
+            .map(|body| body.token)
+    }
+}
+
+#[async_trait]
+impl RequestHandler for IntrospectHandler {
+    async fn handle(&self, req: Request) -> Response {
+        if let Some(auth) = &self.auth
+            && !auth.authorizes(req.header("authorization").map(String::as_str))
+        {
+            let challenge = match auth {
+                IntrospectionAuth::Bearer(_) => "Bearer",
+                IntrospectionAuth::Basic { .. } => "Basic",
+            };
+            return Response::unauthorized()
+                .header("WWW-Authenticate", challenge)
+                .json(json!({ "error": "invalid_client" }));
+        }
+
+        let Some(token) = Self::token(&req) else {
+            return Response::bad_request().json(json!({ "error": "invalid_request" }));
+        };
+
+        let body = match validate_access(&self.generator, self.revocation_store.as_ref(), &token)
+            .await
+        {
+            Ok(claims) => IntrospectionResponse::active(claims),
+            Err(_) => IntrospectionResponse::inactive(),
+        };
+        Response::ok().header("Cache-Control", "no-store").json(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bearer_auth() {
+        let auth = IntrospectionAuth::Bearer("s3cret".to_string());
+        assert!(auth.authorizes(Some("Bearer s3cret")));
+        assert!(auth.authorizes(Some("bearer s3cret")));
+        assert!(!auth.authorizes(Some("Bearer wrong")));
+        assert!(!auth.authorizes(Some("Basic s3cret")));
+        assert!(!auth.authorizes(None));
+    }
+
+    #[test]
+    fn test_basic_auth() {
+        let auth = IntrospectionAuth::Basic {
+            client_id: "api".to_string(),
+            client_secret: "s3cret".to_string(),
+        };
+        let header = format!("Basic {}", STANDARD.encode("api:s3cret"));
+        assert!(auth.authorizes(Some(&header)));
+
+        let wrong = format!("Basic {}", STANDARD.encode("api:wrong"));
+        assert!(!auth.authorizes(Some(&wrong)));
+        assert!(!auth.authorizes(Some("Basic not-base64!")));
+        assert!(!auth.authorizes(Some("Bearer s3cret")));
+    }
+}
@@ -0,0 +1,212 @@
+//! JSON Web Key Sets (RFC 7517).
+//!
+//! Public keys for RS256 and ES256 are published as JWKs so resource servers
+//! can validate access tokens without sharing a secret. Each key's `kid` is
+//! its RFC 7638 thumbprint unless overridden, and the same `kid` is written
+//! into the header of every token signed with it.
+
+use crate::token::JwtError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::Algorithm;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use simple_asn1::ASN1Block;
+
+/// A public JSON Web Key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    /// Key type: `RSA` or `EC`.
+    pub kty: String,
+    /// Intended use; always `sig`.
+    #[serde(rename = "use")]
+    pub key_use: String,
+    /// Signing algorithm, e.g. `RS256`.
+    pub alg: String,
+    /// Key ID, matching the `kid` header of tokens signed with this key.
+    pub kid: String,
+    /// RSA modulus (base64url).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    /// RSA public exponent (base64url).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    /// EC curve name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    /// EC x coordinate (base64url).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    /// EC y coordinate (base64url).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+/// A JSON Web Key Set, as served from `/jwt/jwks.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+impl Jwk {
+    /// Builds a JWK from a PEM-encoded public key.
+    ///
+    /// RS256 accepts SubjectPublicKeyInfo (`PUBLIC KEY`) or PKCS#1
+    /// (`RSA PUBLIC KEY`) PEM; ES256 accepts SubjectPublicKeyInfo for P-256.
+    pub fn from_public_pem(algorithm: Algorithm, public_key_pem: &[u8]) -> Result<Self, JwtError> {
+        let pem = pem::parse(public_key_pem).map_err(invalid_key)?;
+        let blocks = simple_asn1::from_der(pem.contents()).map_err(invalid_key)?;
+
+        let mut jwk = match algorithm {
+            Algorithm::RS256 => {
+                let rsa_der = match pem.tag() {
+                    "RSA PUBLIC KEY" => pem.contents().to_vec(),
+                    _ => subject_public_key(&blocks)?,
+                };
+                let (n, e) = rsa_components(&rsa_der)?;
+                Self {
+                    kty: "RSA".to_string(),
+                    n: Some(URL_SAFE_NO_PAD.encode(n)),
+                    e: Some(URL_SAFE_NO_PAD.encode(e)),
+                    ..Self::empty(algorithm)
+                }
+            }
+            Algorithm::ES256 => {
+                // Uncompressed point: 0x04 || x || y.
+                let point = subject_public_key(&blocks)?;
+                if point.len() != 65 || point[0] != 0x04 {
+                    return Err(invalid_key("expected an uncompressed P-256 point"));
+                }
+                Self {
+                    kty: "EC".to_string(),
+                    crv: Some("P-256".to_string()),
+                    x: Some(URL_SAFE_NO_PAD.encode(&point[1..33])),
+                    y: Some(URL_SAFE_NO_PAD.encode(&point[33..])),
+                    ..Self::empty(algorithm)
+                }
+            }
+            other => {
+                return Err(invalid_key(format!("{:?} has no public key", other)));
+            }
+        };
+        jwk.kid = jwk.thumbprint();
+        Ok(jwk)
+    }
+
+    /// Returns the RFC 7638 thumbprint: the SHA-256 of the required members
+    /// in lexicographic order, base64url-encoded.
+    pub fn thumbprint(&self) -> String {
+        let canonical = match self.kty.as_str() {
+            "EC" => serde_json::json!({
+                "crv": self.crv,
+                "kty": self.kty,
+                "x": self.x,
+                "y": self.y,
+            }),
+            _ => serde_json::json!({
+                "e": self.e,
+                "kty": self.kty,
+                "n": self.n,
+            }),
+        };
+        // serde_json objects serialize with sorted keys and no whitespace.
+        URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.to_string()))
+    }
+
+    fn empty(algorithm: Algorithm) -> Self {
+        Self {
+            kty: String::new(),
+            key_use: "sig".to_string(),
+            alg: format!("{:?}", algorithm),
+            kid: String::new(),
+            n: None,
+            e: None,
+            crv: None,
+            x: None,
+            y: None,
+        }
+    }
+}
+
+/// Extracts the `subjectPublicKey` bit string from a SubjectPublicKeyInfo.
+fn subject_public_key(blocks: &[ASN1Block]) -> Result<Vec<u8>, JwtError> {
+    match blocks.first() {
+        Some(ASN1Block::Sequence(_, items)) => match items.as_slice() {
+            [ASN1Block::Sequence(..), ASN1Block::BitString(_, _, key)] => Ok(key.clone()),
+            _ => Err(invalid_key("malformed SubjectPublicKeyInfo")),
+        },
+        _ => Err(invalid_key("malformed SubjectPublicKeyInfo")),
+    }
+}
+
+/// Extracts the big-endian modulus and exponent from a PKCS#1 RSAPublicKey.
+fn rsa_components(der: &[u8]) -> Result<(Vec<u8>, Vec<u8>), JwtError> {
+    let blocks = simple_asn1::from_der(der).map_err(invalid_key)?;
+    match blocks.first() {
+        Some(ASN1Block::Sequence(_, items)) => match items.as_slice() {
+            [ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)] => {
+                Ok((n.to_bytes_be().1, e.to_bytes_be().1))
+            }
+            _ => Err(invalid_key("malformed RSAPublicKey")),
+        },
+        _ => Err(invalid_key("malformed RSAPublicKey")),
+    }
+}
+
+fn invalid_key(err: impl std::fmt::Display) -> JwtError {
+    JwtError::DecodingFailed(format!("invalid public key: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rsa_jwk() {
+        let jwk =
+            Jwk::from_public_pem(Algorithm::RS256, include_bytes!("../testdata/rs256_public.pem"))
+                .unwrap();
+        assert_eq!(jwk.kty, "RSA");
+        assert_eq!(jwk.alg, "RS256");
+        assert_eq!(jwk.key_use, "sig");
+        assert_eq!(jwk.e.as_deref(), Some("AQAB"));
+        // 2048-bit modulus without a leading zero byte.
+        assert_eq!(URL_SAFE_NO_PAD.decode(jwk.n.unwrap()).unwrap().len(), 256);
+        assert_eq!(jwk.kid.len(), 43);
+    }
+
+    #[test]
+    fn test_ec_jwk() {
+        let jwk =
+            Jwk::from_public_pem(Algorithm::ES256, include_bytes!("../testdata/es256_public.pem"))
+                .unwrap();
+        assert_eq!(jwk.kty, "EC");
+        assert_eq!(jwk.alg, "ES256");
+        assert_eq!(jwk.crv.as_deref(), Some("P-256"));
+        assert_eq!(URL_SAFE_NO_PAD.decode(jwk.x.unwrap()).unwrap().len(), 32);
+        assert_eq!(URL_SAFE_NO_PAD.decode(jwk.y.unwrap()).unwrap().len(), 32);
+        assert!(jwk.n.is_none());
+    }
+
+    #[test]
+    fn test_thumbprint_rfc7638_example() {
+        // RFC 7638 section 3.1.
+        let jwk = Jwk {
+            kty: "RSA".to_string(),
+            n: Some(
+                "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw"
+                    .to_string(),
+            ),
+            e: Some("AQAB".to_string()),
+            ..Jwk::empty(Algorithm::RS256)
+        };
+        assert_eq!(jwk.thumbprint(), "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs");
+    }
+
+    #[test]
+    fn test_rejects_symmetric_algorithm() {
+        let pem = include_bytes!("../testdata/rs256_public.pem");
+        assert!(Jwk::from_public_pem(Algorithm::HS256, pem).is_err());
+        assert!(Jwk::from_public_pem(Algorithm::ES256, pem).is_err());
+    }
+}
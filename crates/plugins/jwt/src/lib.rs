@@ -8,6 +8,8 @@
 //! - Access and refresh token generation
 //! - Multiple signing algorithms (HS256, HS384, HS512, RS256, ES256)
 //! - Token refresh and revocation
+//! - JWKS endpoint publishing RS256/ES256 public keys
+//! - OAuth 2.0 token introspection (RFC 7662)
 //! - Configurable token TTLs
 //! - Session-linked JWTs for hybrid mode
 //!
@@ -23,31 +25,49 @@
 //!         .refresh_token_ttl(Duration::days(30))
 //!         .issuer("https://auth.example.com")
 //! );
+//!
+//! // Asymmetric keys: sign with the private key, verify with the public key.
+//! let jwt = JwtPlugin::new(JwtConfig::rs256_pem(private_pem, public_pem)?);
 //! ```
 
 pub mod claims;
+pub mod introspection;
+pub mod jwks;
+pub mod revocation;
 pub mod token;
 
-pub use claims::{AccessTokenClaims, IdTokenClaims, RefreshTokenClaims};
+pub use claims::{
+    ACCESS_TOKEN_TYPE, AccessTokenClaims, IdTokenClaims, REFRESH_TOKEN_TYPE, RESERVED_CLAIMS,
+    RefreshTokenClaims,
+};
+pub use introspection::{IntrospectionAuth, IntrospectionResponse};
+pub use jwks::{Jwk, JwkSet};
+pub use revocation::{RevocationStore, TokenRevocationStore};
+pub use jsonwebtoken::Algorithm;
 pub use token::{JwtCodec, JwtError, TokenGenerator, TokenPair};
 
 use async_trait::async_trait;
 use better_auth_core::context::AuthContext;
-use better_auth_core::error::AuthResult;
+use better_auth_core::error::{AuthError, AuthResult};
 use better_auth_core::router::{Method, Request, RequestHandler, Response, Route, Router};
 use better_auth_core::traits::AuthPlugin;
 use better_auth_core::types::Session;
 use better_auth_events_sdk::{EventDefinition, EventProvider};
+use introspection::IntrospectHandler;
 use chrono::Duration;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+
+/// Minimum HS256 secret length in bytes (the size of the SHA-256 output).
+const MIN_HS256_SECRET_BYTES: usize = 32;
 
 /// JWT plugin configuration.
 #[derive(Clone)]
 pub struct JwtConfig {
-    /// Secret key for symmetric algorithms (HS256, etc.).
+    /// Secret key for HS256. Empty when an asymmetric key is configured.
     pub secret: String,
+    /// Codec for asymmetric keys. When `None`, tokens use HS256 with `secret`.
+    key_pair: Option<JwtCodec>,
     /// Access token time-to-live.
     pub access_token_ttl: Duration,
     /// Refresh token time-to-live.
@@ -60,6 +80,11 @@ pub struct JwtConfig {
     pub include_user_info: bool,
     /// Whether to link JWTs to sessions (hybrid mode).
     pub link_to_session: bool,
+    /// Revocation storage. Defaults to an in-memory [`TokenRevocationStore`].
+    pub revocation_store: Option<Arc<dyn RevocationStore>>,
+    /// Credential required to call `/jwt/introspect`. When `None`, the
+    /// endpoint is open to anyone who can reach it.
+    pub introspection_auth: Option<IntrospectionAuth>,
 }
 
 impl JwtConfig {
@@ -67,12 +92,60 @@ impl JwtConfig {
     pub fn new(secret: impl Into<String>) -> Self {
         Self {
             secret: secret.into(),
+            key_pair: None,
             access_token_ttl: Duration::hours(1),
             refresh_token_ttl: Duration::days(30),
             issuer: None,
             audience: None,
             include_user_info: false,
             link_to_session: false,
+            revocation_store: None,
+            introspection_auth: None,
+        }
+    }
+
+    /// Creates a JWT config that signs with an RSA private key (RS256) and
+    /// validates with the matching public key.
+    pub fn rs256_pem(private_pem: &[u8], public_pem: &[u8]) -> Result<Self, JwtError> {
+        Ok(Self::with_key_pair(JwtCodec::rs256(private_pem, public_pem)?))
+    }
+
+    /// Creates a JWT config that signs with a P-256 private key (ES256) and
+    /// validates with the matching public key.
+    ///
+    /// The private key must be in PKCS#8 PEM format.
+    pub fn es256_pem(private_pem: &[u8], public_pem: &[u8]) -> Result<Self, JwtError> {
+        Ok(Self::with_key_pair(JwtCodec::es256(private_pem, public_pem)?))
+    }
+
+    fn with_key_pair(codec: JwtCodec) -> Self {
+        Self {
+            key_pair: Some(codec),
+            ..Self::new(String::new())
+        }
+    }
+
+    /// Sets the key ID for an RS256/ES256 key pair.
+    ///
+    /// Defaults to the public key's RFC 7638 thumbprint. Give each key a
+    /// distinct ID when rotating so resource servers can pick the right one.
+    pub fn key_id(mut self, kid: impl Into<String>) -> Self {
+        self.key_pair = self.key_pair.map(|codec| codec.with_key_id(kid));
+        self
+    }
+
+    /// Returns the signing algorithm.
+    pub fn algorithm(&self) -> Algorithm {
+        self.key_pair
+            .as_ref()
+            .map_or(Algorithm::HS256, JwtCodec::algorithm)
+    }
+
+    /// Builds the codec used to sign and validate tokens.
+    pub fn codec(&self) -> JwtCodec {
+        match &self.key_pair {
+            Some(codec) => codec.clone(),
+            None => JwtCodec::hs256(&self.secret),
         }
     }
 
@@ -111,45 +184,58 @@ impl JwtConfig {
         self.link_to_session = link;
         self
     }
-}
-
-/// In-memory store for revoked tokens.
-/// In production, this should be backed by Redis or a database.
-#[derive(Debug, Default)]
-pub struct TokenRevocationStore {
-    /// Set of revoked token IDs (jti claims).
-    revoked_tokens: RwLock<HashSet<String>>,
-    /// Set of revoked token families (for refresh token rotation).
-    revoked_families: RwLock<HashSet<String>>,
-}
 
-impl TokenRevocationStore {
-    pub fn new() -> Self {
-        Self::default()
+    /// Sets the revocation store.
+    ///
+    /// Use a shared store when running more than one instance, so a token
+    /// revoked on one instance is rejected by all of them.
+    pub fn revocation_store(mut self, store: Arc<dyn RevocationStore>) -> Self {
+        self.revocation_store = Some(store);
+        self
     }
 
-    /// Revokes a token by its JTI.
-    pub fn revoke_token(&self, jti: &str) {
-        let mut tokens = self.revoked_tokens.write().unwrap();
-        tokens.insert(jti.to_string());
+    /// Requires `Authorization: Bearer <token>` on the introspection endpoint.
+    pub fn introspection_bearer(mut self, token: impl Into<String>) -> Self {
+        self.introspection_auth = Some(IntrospectionAuth::Bearer(token.into()));
+        self
     }
 
-    /// Revokes all tokens in a family.
-    pub fn revoke_family(&self, family_id: &str) {
-        let mut families = self.revoked_families.write().unwrap();
-        families.insert(family_id.to_string());
+    /// Requires HTTP Basic client credentials on the introspection endpoint.
+    pub fn introspection_basic(
+        mut self,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        self.introspection_auth = Some(IntrospectionAuth::Basic {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+        });
+        self
     }
 
-    /// Checks if a token is revoked.
-    pub fn is_token_revoked(&self, jti: &str) -> bool {
-        let tokens = self.revoked_tokens.read().unwrap();
-        tokens.contains(jti)
-    }
+    /// Checks the configuration, reporting every problem found.
+    pub fn validate(&self) -> AuthResult<()> {
+        let mut issues = Vec::new();
 
-    /// Checks if a token family is revoked.
-    pub fn is_family_revoked(&self, family_id: &str) -> bool {
-        let families = self.revoked_families.read().unwrap();
-        families.contains(family_id)
+        if self.key_pair.is_none() && self.secret.len() < MIN_HS256_SECRET_BYTES {
+            issues.push(format!(
+                "HS256 secret must be at least {} bytes (got {})",
+                MIN_HS256_SECRET_BYTES,
+                self.secret.len()
+            ));
+        }
+        if self.access_token_ttl <= Duration::zero() {
+            issues.push("access token TTL must be positive".to_string());
+        }
+        if self.refresh_token_ttl <= self.access_token_ttl {
+            issues.push("refresh token TTL must be longer than the access token TTL".to_string());
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(AuthError::invalid_config("jwt", issues))
+        }
     }
 }
 
@@ -157,13 +243,13 @@ impl TokenRevocationStore {
 pub struct JwtPlugin {
     config: JwtConfig,
     token_generator: TokenGenerator,
-    revocation_store: Arc<TokenRevocationStore>,
+    revocation_store: Arc<dyn RevocationStore>,
 }
 
 impl JwtPlugin {
     /// Creates a new JWT plugin with the given configuration.
     pub fn new(config: JwtConfig) -> Self {
-        let codec = JwtCodec::hs256(&config.secret);
+        let codec = config.codec();
         let mut generator =
             TokenGenerator::new(codec, config.access_token_ttl, config.refresh_token_ttl);
 
@@ -174,10 +260,15 @@ impl JwtPlugin {
             generator = generator.with_audience(audience);
         }
 
+        let revocation_store = config
+            .revocation_store
+            .clone()
+            .unwrap_or_else(|| Arc::new(TokenRevocationStore::new()));
+
         Self {
             config,
             token_generator: generator,
-            revocation_store: Arc::new(TokenRevocationStore::new()),
+            revocation_store,
         }
     }
 
@@ -186,8 +277,17 @@ impl JwtPlugin {
         &self.token_generator
     }
 
+    /// Returns the public signing keys as a JWK set.
+    ///
+    /// Empty for HMAC algorithms, whose secret must never be published.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.token_generator.codec().jwk().cloned().into_iter().collect(),
+        }
+    }
+
     /// Returns a reference to the revocation store.
-    pub fn revocation_store(&self) -> &Arc<TokenRevocationStore> {
+    pub fn revocation_store(&self) -> &Arc<dyn RevocationStore> {
         &self.revocation_store
     }
 
@@ -206,62 +306,103 @@ impl JwtPlugin {
             .generate_token_pair_with_session(user_id, session_id)
     }
 
-    /// Validates an access token.
-    pub fn validate_access_token(&self, token: &str) -> Result<AccessTokenClaims, JwtError> {
-        let claims = self.token_generator.validate_access_token(token)?;
-
-        // Check if token is revoked
-        if let Some(ref jti) = claims.jti {
-            if self.revocation_store.is_token_revoked(jti) {
-                return Err(JwtError::Revoked);
-            }
-        }
+    /// Generates an access token carrying extra claims.
+    ///
+    /// The claims are available on [`AccessTokenClaims::custom`] after
+    /// validation. Reserved claims such as `sub` or `exp` are rejected.
+    pub fn generate_access_token_with_claims(
+        &self,
+        user_id: &str,
+        extra: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<String, JwtError> {
+        self.token_generator
+            .generate_access_token_with_claims(user_id, extra)
+    }
 
-        Ok(claims)
+    /// Validates an access token.
+    pub async fn validate_access_token(&self, token: &str) -> Result<AccessTokenClaims, JwtError> {
+        validate_access(&self.token_generator, self.revocation_store.as_ref(), token).await
     }
 
     /// Validates a refresh token.
-    pub fn validate_refresh_token(&self, token: &str) -> Result<RefreshTokenClaims, JwtError> {
+    pub async fn validate_refresh_token(
+        &self,
+        token: &str,
+    ) -> Result<RefreshTokenClaims, JwtError> {
         let claims = self.token_generator.validate_refresh_token(token)?;
-
-        // Check if token or family is revoked
-        if self.revocation_store.is_token_revoked(&claims.jti) {
-            return Err(JwtError::Revoked);
-        }
-        if let Some(ref family_id) = claims.family_id {
-            if self.revocation_store.is_family_revoked(family_id) {
-                return Err(JwtError::Revoked);
-            }
-        }
-
+        check_refresh_revocation(self.revocation_store.as_ref(), &claims).await?;
         Ok(claims)
     }
 
     /// Refreshes tokens using a refresh token.
-    pub fn refresh_tokens(&self, refresh_token: &str) -> Result<TokenPair, JwtError> {
-        // Validate the refresh token first
-        let claims = self.validate_refresh_token(refresh_token)?;
-
-        // Revoke the old refresh token (rotation)
-        self.revocation_store.revoke_token(&claims.jti);
-
-        // Generate new tokens
-        if let Some(session_id) = claims.session_id {
-            self.token_generator
-                .generate_token_pair_with_session(&claims.sub, &session_id)
-        } else {
-            self.token_generator.generate_token_pair(&claims.sub)
-        }
+    pub async fn refresh_tokens(&self, refresh_token: &str) -> Result<TokenPair, JwtError> {
+        let claims = self.validate_refresh_token(refresh_token).await?;
+        rotate_refresh_token(&self.token_generator, self.revocation_store.as_ref(), claims).await
     }
 
     /// Revokes a token by its JTI.
-    pub fn revoke_token(&self, jti: &str) {
-        self.revocation_store.revoke_token(jti);
+    pub async fn revoke_token(&self, jti: &str) -> Result<(), JwtError> {
+        self.revocation_store.revoke_token(jti).await?;
+        Ok(())
     }
 
     /// Revokes all tokens for a user by revoking the token family.
-    pub fn revoke_all_tokens(&self, family_id: &str) {
-        self.revocation_store.revoke_family(family_id);
+    pub async fn revoke_all_tokens(&self, family_id: &str) -> Result<(), JwtError> {
+        Ok(self.revocation_store.revoke_family(family_id).await?)
+    }
+}
+
+/// Validates an access token and rejects it if its JTI has been revoked.
+pub(crate) async fn validate_access(
+    generator: &TokenGenerator,
+    store: &dyn RevocationStore,
+    token: &str,
+) -> Result<AccessTokenClaims, JwtError> {
+    let claims = generator.validate_access_token(token)?;
+
+    // Check if token is revoked
+    if let Some(ref jti) = claims.jti
+        && store.is_token_revoked(jti).await?
+    {
+        return Err(JwtError::Revoked);
+    }
+
+    Ok(claims)
+}
+
+/// Rejects a refresh token whose JTI or family has been revoked.
+async fn check_refresh_revocation(
+    store: &dyn RevocationStore,
+    claims: &RefreshTokenClaims,
+) -> Result<(), JwtError> {
+    if store.is_token_revoked(&claims.jti).await? {
+        return Err(JwtError::Revoked);
+    }
+    if let Some(ref family_id) = claims.family_id
+        && store.is_family_revoked(family_id).await?
+    {
+        return Err(JwtError::Revoked);
+    }
+    Ok(())
+}
+
+/// Revokes a validated refresh token and issues a new pair (rotation).
+///
+/// Fails with [`JwtError::Revoked`] if the token was revoked in the
+/// meantime, so a refresh token is exchanged at most once.
+async fn rotate_refresh_token(
+    generator: &TokenGenerator,
+    store: &dyn RevocationStore,
+    claims: RefreshTokenClaims,
+) -> Result<TokenPair, JwtError> {
+    if !store.revoke_token(&claims.jti).await? {
+        return Err(JwtError::Revoked);
+    }
+
+    if let Some(session_id) = claims.session_id {
+        generator.generate_token_pair_with_session(&claims.sub, &session_id)
+    } else {
+        generator.generate_token_pair(&claims.sub)
     }
 }
 
@@ -306,6 +447,10 @@ impl AuthPlugin for JwtPlugin {
         "JWT Authentication"
     }
 
+    fn validate_config(&self) -> AuthResult<()> {
+        self.config.validate()
+    }
+
     fn register_routes(&self, router: &mut Router) {
         // POST /jwt/refresh - Refresh tokens
         router.route(
@@ -335,6 +480,30 @@ impl AuthPlugin for JwtPlugin {
             .description("Revokes a JWT token or token family")
             .tag("jwt"),
         );
+
+        // POST /jwt/introspect - Token introspection (RFC 7662)
+        router.route(
+            Route::new(
+                Method::POST,
+                "/jwt/introspect",
+                IntrospectHandler {
+                    generator: self.token_generator.clone(),
+                    revocation_store: self.revocation_store.clone(),
+                    auth: self.config.introspection_auth.clone(),
+                },
+            )
+            .summary("Introspect JWT token")
+            .description("Reports whether an access token is active (RFC 7662)")
+            .tag("jwt"),
+        );
+
+        // GET /jwt/jwks.json - Public signing keys
+        router.route(
+            Route::new(Method::GET, "/jwt/jwks.json", JwksHandler { jwks: self.jwks() })
+                .summary("JSON Web Key Set")
+                .description("Public keys for validating access tokens")
+                .tag("jwt"),
+        );
     }
 
     async fn on_after_signin(
@@ -360,7 +529,7 @@ impl AuthPlugin for JwtPlugin {
 /// Handler for POST /jwt/refresh
 struct RefreshHandler {
     plugin: TokenGenerator,
-    revocation_store: Arc<TokenRevocationStore>,
+    revocation_store: Arc<dyn RevocationStore>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -408,34 +577,28 @@ impl RequestHandler for RefreshHandler {
         };
 
         // Check if revoked
-        if self.revocation_store.is_token_revoked(&claims.jti) {
-            return Response::unauthorized().json(ErrorResponse {
-                error: "token_revoked".to_string(),
-                message: "Refresh token has been revoked".to_string(),
-            });
-        }
-        if let Some(ref family_id) = claims.family_id {
-            if self.revocation_store.is_family_revoked(family_id) {
+        match check_refresh_revocation(self.revocation_store.as_ref(), &claims).await {
+            Ok(()) => {}
+            Err(JwtError::Revoked) => {
                 return Response::unauthorized().json(ErrorResponse {
                     error: "token_revoked".to_string(),
-                    message: "Token family has been revoked".to_string(),
+                    message: "Refresh token has been revoked".to_string(),
                 });
             }
+            Err(e) => return revocation_store_error(e),
         }
 
-        // Revoke the old refresh token (rotation)
-        self.revocation_store.revoke_token(&claims.jti);
-
-        // Generate new tokens
-        let pair = if let Some(session_id) = claims.session_id {
-            self.plugin
-                .generate_token_pair_with_session(&claims.sub, &session_id)
-        } else {
-            self.plugin.generate_token_pair(&claims.sub)
-        };
+        // Revoke the old refresh token and generate new tokens
+        let pair =
+            rotate_refresh_token(&self.plugin, self.revocation_store.as_ref(), claims).await;
 
         match pair {
             Ok(tokens) => Response::ok().json(tokens),
+            Err(JwtError::Revoked) => Response::unauthorized().json(ErrorResponse {
+                error: "token_revoked".to_string(),
+                message: "Refresh token has been revoked".to_string(),
+            }),
+            Err(e @ JwtError::Storage(_)) => revocation_store_error(e),
             Err(e) => Response::internal_error().json(ErrorResponse {
                 error: "token_generation_failed".to_string(),
                 message: e.to_string(),
@@ -446,7 +609,7 @@ impl RequestHandler for RefreshHandler {
 
 /// Handler for POST /jwt/revoke
 struct RevokeHandler {
-    revocation_store: Arc<TokenRevocationStore>,
+    revocation_store: Arc<dyn RevocationStore>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -490,14 +653,12 @@ impl RequestHandler for RevokeHandler {
 
         // Revoke by JTI
         if let Some(jti) = body.jti {
-            self.revocation_store.revoke_token(&jti);
-            return Response::ok().json(RevokeResponse { success: true });
+            return revoked(self.revocation_store.revoke_token(&jti).await);
         }
 
         // Revoke by family ID
         if let Some(family_id) = body.family_id {
-            self.revocation_store.revoke_family(&family_id);
-            return Response::ok().json(RevokeResponse { success: true });
+            return revoked(self.revocation_store.revoke_family(&family_id).await);
         }
 
         // Revoke by token (decode to get JTI)
@@ -507,8 +668,7 @@ impl RequestHandler for RevokeHandler {
                 JwtCodec::decode_unsafe::<AccessTokenClaims>(&token)
             {
                 if let Some(jti) = token_data.claims.jti {
-                    self.revocation_store.revoke_token(&jti);
-                    return Response::ok().json(RevokeResponse { success: true });
+                    return revoked(self.revocation_store.revoke_token(&jti).await);
                 }
             }
 
@@ -516,8 +676,7 @@ impl RequestHandler for RevokeHandler {
             if let Ok(token_data) =
                 JwtCodec::decode_unsafe::<RefreshTokenClaims>(&token)
             {
-                self.revocation_store.revoke_token(&token_data.claims.jti);
-                return Response::ok().json(RevokeResponse { success: true });
+                return revoked(self.revocation_store.revoke_token(&token_data.claims.jti).await);
             }
 
             return Response::bad_request().json(ErrorResponse {
@@ -533,6 +692,36 @@ impl RequestHandler for RevokeHandler {
     }
 }
 
+/// Builds the response for a revocation attempt.
+fn revoked<T>(result: AuthResult<T>) -> Response {
+    match result {
+        Ok(_) => Response::ok().json(RevokeResponse { success: true }),
+        Err(e) => revocation_store_error(e.into()),
+    }
+}
+
+fn revocation_store_error(err: JwtError) -> Response {
+    Response::internal_error().json(ErrorResponse {
+        error: "revocation_store_error".to_string(),
+        message: err.to_string(),
+    })
+}
+
+/// Handler for GET /jwt/jwks.json
+#[derive(Clone)]
+struct JwksHandler {
+    jwks: JwkSet,
+}
+
+#[async_trait]
+impl RequestHandler for JwksHandler {
+    async fn handle(&self, _req: Request) -> Response {
+        Response::ok()
+            .header("Cache-Control", "public, max-age=300")
+            .json(&self.jwks)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -554,7 +743,128 @@ mod tests {
     }
 
     #[test]
-    fn test_jwt_plugin_token_generation() {
+    fn test_jwt_config_validation() {
+        assert!(JwtConfig::new("a".repeat(32)).validate().is_ok());
+
+        let config = JwtConfig::new("").refresh_token_ttl(Duration::minutes(5));
+        let Err(AuthError::InvalidPluginConfig { issues }) = config.validate() else {
+            panic!("expected config issues");
+        };
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.starts_with("HS256 secret must be at least 32 bytes"));
+    }
+
+    /// Signs with the configured private key and checks the token verifies
+    /// against the public key alone.
+    async fn assert_signed_with_key_pair(config: JwtConfig, public_key: jsonwebtoken::DecodingKey) {
+        let algorithm = config.algorithm();
+        assert!(config.validate().is_ok());
+        let plugin = JwtPlugin::new(config);
+
+        let pair = plugin.generate_tokens("user_123").unwrap();
+        let header = jsonwebtoken::decode_header(&pair.access_token).unwrap();
+        assert_eq!(header.alg, algorithm);
+
+        let mut validation = jsonwebtoken::Validation::new(algorithm);
+        validation.validate_aud = false;
+        let data = jsonwebtoken::decode::<AccessTokenClaims>(&pair.access_token, &public_key, &validation)
+            .unwrap();
+        assert_eq!(data.claims.sub, "user_123");
+        assert_eq!(plugin.validate_access_token(&pair.access_token).await.unwrap().sub, "user_123");
+
+        // A token signed with a different key is rejected.
+        let forged = JwtCodec::hs256(&"x".repeat(32)).encode(&data.claims).unwrap();
+        assert!(plugin.validate_access_token(&forged).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rs256_key_pair() {
+        let public_pem = include_bytes!("../testdata/rs256_public.pem");
+        let config =
+            JwtConfig::rs256_pem(include_bytes!("../testdata/rs256_private.pem"), public_pem).unwrap();
+        assert_eq!(config.algorithm(), Algorithm::RS256);
+
+        let public_key = jsonwebtoken::DecodingKey::from_rsa_pem(public_pem).unwrap();
+        assert_signed_with_key_pair(config, public_key).await;
+    }
+
+    #[tokio::test]
+    async fn test_es256_key_pair() {
+        let public_pem = include_bytes!("../testdata/es256_public.pem");
+        let config =
+            JwtConfig::es256_pem(include_bytes!("../testdata/es256_private.pem"), public_pem).unwrap();
+        assert_eq!(config.algorithm(), Algorithm::ES256);
+
+        let public_key = jsonwebtoken::DecodingKey::from_ec_pem(public_pem).unwrap();
+        assert_signed_with_key_pair(config, public_key).await;
+    }
+
+    #[tokio::test]
+    async fn test_jwks_kid_matches_token_header() {
+        let config = JwtConfig::rs256_pem(
+            include_bytes!("../testdata/rs256_private.pem"),
+            include_bytes!("../testdata/rs256_public.pem"),
+        )
+        .unwrap();
+        let plugin = JwtPlugin::new(config);
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router
+            .routes()
+            .find(|r| r.method == Method::GET && r.path == "/jwt/jwks.json")
+            .expect("jwks route");
+        let response = route
+            .handler
+            .handle(Request::new(Method::GET, "/jwt/jwks.json"))
+            .await;
+        assert_eq!(response.status, 200);
+
+        let jwks: JwkSet = serde_json::from_value(response.body.unwrap()).unwrap();
+        assert_eq!(jwks.keys.len(), 1);
+        let jwk = &jwks.keys[0];
+        assert_eq!((jwk.kty.as_str(), jwk.alg.as_str(), jwk.key_use.as_str()), ("RSA", "RS256", "sig"));
+
+        let pair = plugin.generate_tokens("user_123").unwrap();
+        for token in [&pair.access_token, &pair.refresh_token] {
+            let header = jsonwebtoken::decode_header(token).unwrap();
+            assert_eq!(header.kid.as_deref(), Some(jwk.kid.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_jwks_custom_key_id() {
+        let config = JwtConfig::es256_pem(
+            include_bytes!("../testdata/es256_private.pem"),
+            include_bytes!("../testdata/es256_public.pem"),
+        )
+        .unwrap()
+        .key_id("2026-10");
+        let plugin = JwtPlugin::new(config);
+
+        assert_eq!(plugin.jwks().keys[0].kid, "2026-10");
+        let pair = plugin.generate_tokens("user_123").unwrap();
+        let header = jsonwebtoken::decode_header(&pair.access_token).unwrap();
+        assert_eq!(header.kid.as_deref(), Some("2026-10"));
+    }
+
+    #[test]
+    fn test_jwks_empty_for_hs256() {
+        let plugin = JwtPlugin::new(JwtConfig::new("super-secret-key"));
+        assert!(plugin.jwks().keys.is_empty());
+
+        let pair = plugin.generate_tokens("user_123").unwrap();
+        assert!(jsonwebtoken::decode_header(&pair.access_token).unwrap().kid.is_none());
+    }
+
+    #[test]
+    fn test_invalid_pem_is_rejected() {
+        assert!(JwtConfig::rs256_pem(b"not a key", b"not a key").is_err());
+        assert!(JwtConfig::es256_pem(b"not a key", b"not a key").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_jwt_plugin_token_generation() {
         let plugin = JwtPlugin::new(JwtConfig::new("super-secret-key"));
 
         let pair = plugin.generate_tokens("user_123").unwrap();
@@ -562,40 +872,207 @@ mod tests {
         assert!(!pair.refresh_token.is_empty());
 
         // Validate the access token
-        let claims = plugin.validate_access_token(&pair.access_token).unwrap();
+        let claims = plugin.validate_access_token(&pair.access_token).await.unwrap();
         assert_eq!(claims.sub, "user_123");
     }
 
-    #[test]
-    fn test_token_revocation() {
+    #[tokio::test]
+    async fn test_token_revocation() {
         let plugin = JwtPlugin::new(JwtConfig::new("super-secret-key"));
 
         let pair = plugin.generate_tokens("user_123").unwrap();
-        let claims = plugin.validate_access_token(&pair.access_token).unwrap();
+        let claims = plugin.validate_access_token(&pair.access_token).await.unwrap();
 
         // Revoke the token
         if let Some(jti) = claims.jti {
-            plugin.revoke_token(&jti);
+            plugin.revoke_token(&jti).await.unwrap();
 
             // Validation should now fail
-            let result = plugin.validate_access_token(&pair.access_token);
+            let result = plugin.validate_access_token(&pair.access_token).await;
             assert!(matches!(result, Err(JwtError::Revoked)));
         }
     }
 
-    #[test]
-    fn test_token_refresh() {
+    #[tokio::test]
+    async fn test_token_refresh() {
         let plugin = JwtPlugin::new(JwtConfig::new("super-secret-key"));
 
         let pair = plugin.generate_tokens("user_123").unwrap();
-        let new_pair = plugin.refresh_tokens(&pair.refresh_token).unwrap();
+        let new_pair = plugin.refresh_tokens(&pair.refresh_token).await.unwrap();
 
         // New tokens should be different
         assert_ne!(pair.access_token, new_pair.access_token);
         assert_ne!(pair.refresh_token, new_pair.refresh_token);
 
         // Old refresh token should be revoked
-        let result = plugin.refresh_tokens(&pair.refresh_token);
+        let result = plugin.refresh_tokens(&pair.refresh_token).await;
         assert!(matches!(result, Err(JwtError::Revoked)));
     }
+
+    #[tokio::test]
+    async fn test_refresh_token_rotates_once() {
+        let plugin = JwtPlugin::new(JwtConfig::new("super-secret-key"));
+        let pair = plugin.generate_tokens("user_123").unwrap();
+        let claims = plugin.validate_refresh_token(&pair.refresh_token).await.unwrap();
+
+        // Two refreshes that both passed the revocation check: only the
+        // first to revoke the token gets a new pair.
+        let store = plugin.revocation_store.as_ref();
+        let first = rotate_refresh_token(&plugin.token_generator, store, claims.clone()).await;
+        let second = rotate_refresh_token(&plugin.token_generator, store, claims).await;
+        assert!(first.is_ok());
+        assert!(matches!(second, Err(JwtError::Revoked)));
+    }
+
+    #[tokio::test]
+    async fn test_shared_revocation_store() {
+        // Two instances sharing one store, as in a multi-instance deployment.
+        let store: Arc<dyn RevocationStore> = Arc::new(TokenRevocationStore::new());
+        let config = JwtConfig::new("super-secret-key").revocation_store(store.clone());
+        let a = JwtPlugin::new(config.clone());
+        let b = JwtPlugin::new(config);
+
+        let pair = a.generate_tokens("user_123").unwrap();
+        let jti = b.validate_access_token(&pair.access_token).await.unwrap().jti.unwrap();
+
+        // Revoking through instance A's route is seen by instance B.
+        let mut router = Router::new("/api/auth");
+        a.register_routes(&mut router);
+        let revoke = router.routes().find(|r| r.path == "/jwt/revoke").unwrap();
+        let mut req = Request::new(Method::POST, "/jwt/revoke");
+        req.body = Some(serde_json::json!({ "jti": jti }));
+        assert_eq!(revoke.handler.handle(req).await.status, 200);
+
+        assert!(store.is_token_revoked(&jti).await.unwrap());
+        assert!(matches!(
+            b.validate_access_token(&pair.access_token).await,
+            Err(JwtError::Revoked)
+        ));
+
+        // A refresh on one instance rotates the token for both.
+        b.refresh_tokens(&pair.refresh_token).await.unwrap();
+        assert!(matches!(
+            a.refresh_tokens(&pair.refresh_token).await,
+            Err(JwtError::Revoked)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_custom_claims_round_trip() {
+        let plugin = JwtPlugin::new(JwtConfig::new("super-secret-key").issuer("https://auth.example.com"));
+
+        let extra = serde_json::json!({ "tenant_id": "acme", "roles": ["admin", "billing"] });
+        let token = plugin
+            .generate_access_token_with_claims("user_123", extra.as_object().unwrap().clone())
+            .unwrap();
+
+        let claims = plugin.validate_access_token(&token).await.unwrap();
+        assert_eq!(claims.sub, "user_123");
+        assert_eq!(claims.iss.as_deref(), Some("https://auth.example.com"));
+        assert_eq!(claims.claim("tenant_id"), Some(&serde_json::json!("acme")));
+        assert_eq!(claims.claim("roles"), Some(&serde_json::json!(["admin", "billing"])));
+    }
+
+    #[test]
+    fn test_custom_claims_cannot_override_reserved() {
+        let plugin = JwtPlugin::new(JwtConfig::new("super-secret-key"));
+
+        for key in ["sub", "exp", "iss"] {
+            let mut extra = serde_json::Map::new();
+            extra.insert("tenant_id".to_string(), "acme".into());
+            extra.insert(key.to_string(), "attacker".into());
+
+            let result = plugin.generate_access_token_with_claims("user_123", extra);
+            assert!(matches!(result, Err(JwtError::ReservedClaim(k)) if k == key));
+        }
+    }
+
+    async fn introspect(router: &Router, token: &str, authorization: Option<&str>) -> Response {
+        let route = router.routes().find(|r| r.path == "/jwt/introspect").unwrap();
+        let mut req = Request::new(Method::POST, "/jwt/introspect");
+        req.body = Some(serde_json::json!({ "token": token }));
+        if let Some(value) = authorization {
+            req.headers.insert("authorization".to_string(), value.to_string());
+        }
+        route.handler.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_introspection() {
+        let plugin = JwtPlugin::new(
+            JwtConfig::new("super-secret-key").introspection_bearer("resource-server-token"),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let auth = Some("Bearer resource-server-token");
+
+        let mut extra = serde_json::Map::new();
+        extra.insert("scope".to_string(), "read write".into());
+        extra.insert("client_id".to_string(), "dashboard".into());
+        let token = plugin.generate_access_token_with_claims("user_123", extra).unwrap();
+
+        let response = introspect(&router, &token, auth).await;
+        assert_eq!(response.status, 200);
+        let body: IntrospectionResponse = serde_json::from_value(response.body.unwrap()).unwrap();
+        assert!(body.active);
+        assert_eq!(body.sub.as_deref(), Some("user_123"));
+        assert_eq!(body.scope.as_deref(), Some("read write"));
+        assert_eq!(body.client_id.as_deref(), Some("dashboard"));
+        assert_eq!(body.token_type.as_deref(), Some("Bearer"));
+
+        // Revoked and garbage tokens are inactive, not errors.
+        let claims = plugin.validate_access_token(&token).await.unwrap();
+        plugin.revoke_token(&claims.jti.unwrap()).await.unwrap();
+        for token in [token.as_str(), "not-a-jwt"] {
+            let response = introspect(&router, token, auth).await;
+            assert_eq!(response.status, 200);
+            assert_eq!(response.body, Some(serde_json::json!({ "active": false })));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_introspection_reports_refresh_tokens_inactive() {
+        let plugin = JwtPlugin::new(JwtConfig::new("super-secret-key"));
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let pair = plugin.generate_tokens("user_123").unwrap();
+
+        let response = introspect(&router, &pair.refresh_token, None).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, Some(serde_json::json!({ "active": false })));
+        assert!(plugin.validate_access_token(&pair.refresh_token).await.is_err());
+        assert!(plugin.validate_refresh_token(&pair.access_token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_introspection_requires_credential() {
+        let plugin = JwtPlugin::new(
+            JwtConfig::new("super-secret-key").introspection_basic("api", "s3cret"),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let token = plugin.generate_tokens("user_123").unwrap().access_token;
+
+        assert_eq!(introspect(&router, &token, None).await.status, 401);
+        assert_eq!(introspect(&router, &token, Some("Basic YXBpOndyb25n")).await.status, 401);
+
+        // base64("api:s3cret")
+        let response = introspect(&router, &token, Some("Basic YXBpOnMzY3JldA==")).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.unwrap()["active"], true);
+    }
+
+    #[tokio::test]
+    async fn test_introspection_form_body() {
+        let plugin = JwtPlugin::new(JwtConfig::new("super-secret-key"));
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let token = plugin.generate_tokens("user_123").unwrap().access_token;
+
+        let route = router.routes().find(|r| r.path == "/jwt/introspect").unwrap();
+        let mut req = Request::new(Method::POST, "/jwt/introspect");
+        req.body = Some(serde_json::Value::String(format!("token={}&token_type_hint=access_token", token)));
+        let response = route.handler.handle(req).await;
+        assert_eq!(response.body.unwrap()["active"], true);
+    }
 }
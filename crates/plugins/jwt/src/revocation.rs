@@ -0,0 +1,75 @@
+//! Token revocation storage.
+//!
+//! Revocation is keyed by token ID (`jti`) and by refresh token family. The
+//! default [`TokenRevocationStore`] keeps both sets in memory, so revocations
+//! are lost on restart and are not shared between instances. Deployments with
+//! more than one instance should implement [`RevocationStore`] on top of
+//! shared storage such as Redis or Postgres and pass it to
+//! [`JwtConfig::revocation_store`](crate::JwtConfig::revocation_store).
+
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+/// Storage for revoked tokens and token families.
+#[async_trait]
+pub trait RevocationStore: Send + Sync {
+    /// Revokes a token by its JTI.
+    ///
+    /// Returns false if the token was already revoked. The check and the
+    /// write are one atomic operation, such as an insert that skips
+    /// existing rows, so of two concurrent refreshes with one refresh token
+    /// only one gets `true` and rotates it.
+    async fn revoke_token(&self, jti: &str) -> AuthResult<bool>;
+
+    /// Revokes all tokens in a family.
+    async fn revoke_family(&self, family_id: &str) -> AuthResult<()>;
+
+    /// Checks if a token is revoked.
+    async fn is_token_revoked(&self, jti: &str) -> AuthResult<bool>;
+
+    /// Checks if a token family is revoked.
+    async fn is_family_revoked(&self, family_id: &str) -> AuthResult<bool>;
+}
+
+/// In-memory store for revoked tokens.
+///
+/// Suitable for a single instance and for tests.
+#[derive(Debug, Default)]
+pub struct TokenRevocationStore {
+    /// Set of revoked token IDs (jti claims).
+    revoked_tokens: RwLock<HashSet<String>>,
+    /// Set of revoked token families (for refresh token rotation).
+    revoked_families: RwLock<HashSet<String>>,
+}
+
+impl TokenRevocationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RevocationStore for TokenRevocationStore {
+    async fn revoke_token(&self, jti: &str) -> AuthResult<bool> {
+        let mut tokens = self.revoked_tokens.write().unwrap();
+        Ok(tokens.insert(jti.to_string()))
+    }
+
+    async fn revoke_family(&self, family_id: &str) -> AuthResult<()> {
+        let mut families = self.revoked_families.write().unwrap();
+        families.insert(family_id.to_string());
+        Ok(())
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> AuthResult<bool> {
+        let tokens = self.revoked_tokens.read().unwrap();
+        Ok(tokens.contains(jti))
+    }
+
+    async fn is_family_revoked(&self, family_id: &str) -> AuthResult<bool> {
+        let families = self.revoked_families.read().unwrap();
+        Ok(families.contains(family_id))
+    }
+}
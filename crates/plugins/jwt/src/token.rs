@@ -1,6 +1,7 @@
 //! JWT token encoding and decoding.
 
 use crate::claims::{AccessTokenClaims, RefreshTokenClaims};
+use crate::jwks::Jwk;
 use chrono::Duration;
 use jsonwebtoken::{
     decode, encode, Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation,
@@ -30,6 +31,18 @@ pub enum JwtError {
 
     #[error("Token revoked")]
     Revoked,
+
+    #[error("Revocation store error: {0}")]
+    Storage(String),
+
+    #[error("Reserved claim cannot be overridden: {0}")]
+    ReservedClaim(String),
+}
+
+impl From<better_auth_core::error::AuthError> for JwtError {
+    fn from(err: better_auth_core::error::AuthError) -> Self {
+        JwtError::Storage(err.to_string())
+    }
 }
 
 impl From<jsonwebtoken::errors::Error> for JwtError {
@@ -51,6 +64,8 @@ pub struct JwtCodec {
     decoding_key: DecodingKey,
     algorithm: Algorithm,
     validation: Validation,
+    /// Public key for asymmetric algorithms; its `kid` goes in token headers.
+    jwk: Option<Jwk>,
 }
 
 impl JwtCodec {
@@ -65,6 +80,7 @@ impl JwtCodec {
             decoding_key: DecodingKey::from_secret(secret),
             algorithm,
             validation,
+            jwk: None,
         }
     }
 
@@ -90,6 +106,8 @@ impl JwtCodec {
         let decoding_key = DecodingKey::from_rsa_pem(public_key_pem)
             .map_err(|e| JwtError::DecodingFailed(e.to_string()))?;
 
+        let jwk = Jwk::from_public_pem(Algorithm::RS256, public_key_pem)?;
+
         let mut validation = Validation::new(Algorithm::RS256);
         validation.validate_exp = true;
         validation.validate_nbf = true;
@@ -99,6 +117,7 @@ impl JwtCodec {
             decoding_key,
             algorithm: Algorithm::RS256,
             validation,
+            jwk: Some(jwk),
         })
     }
 
@@ -109,6 +128,8 @@ impl JwtCodec {
         let decoding_key = DecodingKey::from_ec_pem(public_key_pem)
             .map_err(|e| JwtError::DecodingFailed(e.to_string()))?;
 
+        let jwk = Jwk::from_public_pem(Algorithm::ES256, public_key_pem)?;
+
         let mut validation = Validation::new(Algorithm::ES256);
         validation.validate_exp = true;
         validation.validate_nbf = true;
@@ -118,6 +139,7 @@ impl JwtCodec {
             decoding_key,
             algorithm: Algorithm::ES256,
             validation,
+            jwk: Some(jwk),
         })
     }
 
@@ -133,6 +155,17 @@ impl JwtCodec {
         self
     }
 
+    /// Overrides the key ID written to token headers and the JWKS.
+    ///
+    /// Defaults to the key's RFC 7638 thumbprint. Has no effect for
+    /// symmetric algorithms, which have no public key to publish.
+    pub fn with_key_id(mut self, kid: impl Into<String>) -> Self {
+        if let Some(jwk) = &mut self.jwk {
+            jwk.kid = kid.into();
+        }
+        self
+    }
+
     /// Disables expiration validation (use with caution).
     pub fn without_exp_validation(mut self) -> Self {
         self.validation.validate_exp = false;
@@ -141,7 +174,8 @@ impl JwtCodec {
 
     /// Encodes claims into a JWT token.
     pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, JwtError> {
-        let header = Header::new(self.algorithm);
+        let mut header = Header::new(self.algorithm);
+        header.kid = self.key_id().map(str::to_string);
         encode(&header, claims, &self.encoding_key)
             .map_err(|e| JwtError::EncodingFailed(e.to_string()))
     }
@@ -165,6 +199,16 @@ impl JwtCodec {
     pub fn algorithm(&self) -> Algorithm {
         self.algorithm
     }
+
+    /// Returns the public key as a JWK, or `None` for symmetric algorithms.
+    pub fn jwk(&self) -> Option<&Jwk> {
+        self.jwk.as_ref()
+    }
+
+    /// Returns the key ID placed in token headers, if any.
+    pub fn key_id(&self) -> Option<&str> {
+        self.jwk.as_ref().map(|jwk| jwk.kid.as_str())
+    }
 }
 
 /// Token pair containing access and refresh tokens.
@@ -234,8 +278,30 @@ impl TokenGenerator {
         self
     }
 
+    /// Returns the codec used to sign and validate tokens.
+    pub fn codec(&self) -> &JwtCodec {
+        &self.codec
+    }
+
     /// Generates an access token for a user.
     pub fn generate_access_token(&self, user_id: &str) -> Result<String, JwtError> {
+        self.codec.encode(&self.access_claims(user_id))
+    }
+
+    /// Generates an access token carrying extra claims, such as a tenant ID
+    /// or roles.
+    ///
+    /// Fails with [`JwtError::ReservedClaim`] if `extra` tries to set a
+    /// standard claim like `sub`, `exp`, or `iss`.
+    pub fn generate_access_token_with_claims(
+        &self,
+        user_id: &str,
+        extra: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<String, JwtError> {
+        self.codec.encode(&self.access_claims(user_id).with_claims(extra)?)
+    }
+
+    fn access_claims(&self, user_id: &str) -> AccessTokenClaims {
         let mut claims = AccessTokenClaims::new(user_id, self.access_ttl);
 
         if let Some(ref issuer) = self.issuer {
@@ -245,7 +311,7 @@ impl TokenGenerator {
             claims = claims.with_audience(audience);
         }
 
-        self.codec.encode(&claims)
+        claims
     }
 
     /// Generates a refresh token for a user.
@@ -302,16 +368,22 @@ impl TokenGenerator {
         ))
     }
 
-    /// Validates and decodes an access token.
+    /// Validates and decodes an access token. Refresh tokens are rejected.
     pub fn validate_access_token(&self, token: &str) -> Result<AccessTokenClaims, JwtError> {
-        let token_data = self.codec.decode::<AccessTokenClaims>(token)?;
-        Ok(token_data.claims)
+        let claims = self.codec.decode::<AccessTokenClaims>(token)?.claims;
+        if !claims.is_access_token() {
+            return Err(JwtError::Invalid);
+        }
+        Ok(claims)
     }
 
-    /// Validates and decodes a refresh token.
+    /// Validates and decodes a refresh token. Access tokens are rejected.
     pub fn validate_refresh_token(&self, token: &str) -> Result<RefreshTokenClaims, JwtError> {
-        let token_data = self.codec.decode::<RefreshTokenClaims>(token)?;
-        Ok(token_data.claims)
+        let claims = self.codec.decode::<RefreshTokenClaims>(token)?.claims;
+        if !claims.is_refresh_token() {
+            return Err(JwtError::Invalid);
+        }
+        Ok(claims)
     }
 
     /// Refreshes a token pair using a valid refresh token.
@@ -1,5 +1,12 @@
 //! Configuration for the Magic Link plugin.
 
+use crate::MagicLinkTokenStore;
+use better_auth_core::crypto::hash_secret;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::redirect::{is_allowed_redirect, validate_redirect};
+use better_auth_core::session_token::SessionTokenStrategy;
+use better_auth_core::traits::StorageAdapter;
+use better_auth_otp_utils::{LoggingSender, MessageSender, OtpGenerator};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -36,15 +43,20 @@ pub type SendMagicLinkCallback = Arc<
 /// Type alias for custom token generator.
 pub type TokenGeneratorFn = Arc<dyn Fn() -> String + Send + Sync>;
 
+/// Type alias for a custom token hash function.
+pub type TokenHashFn = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
 /// How tokens should be stored.
 #[derive(Debug, Clone, Default)]
 pub enum TokenStorageMode {
     /// Store tokens in plain text.
     #[default]
     Plain,
-    /// Hash tokens before storage.
+    /// Hash tokens before storage; see [`hash_secret`].
     Hashed,
-    /// Custom storage with user-provided hash function.
+    /// Hash tokens with the function set by
+    /// [`hash_token_with`](MagicLinkConfig::hash_token_with), falling back
+    /// to `Hashed` without one.
     Custom,
 }
 
@@ -55,12 +67,32 @@ pub struct MagicLinkConfig {
     pub expires_in: u64,
     /// Whether to disable automatic sign-up for new users. Default: false.
     pub disable_sign_up: bool,
-    /// Callback to send the magic link.
+    /// Callback to send the magic link. Takes precedence over `sender`.
     pub send_magic_link: Option<SendMagicLinkCallback>,
+    /// Delivers magic link emails. Default: [`LoggingSender`], which only
+    /// logs them.
+    pub sender: Arc<dyn MessageSender>,
     /// Custom token generator function.
     pub generate_token: Option<TokenGeneratorFn>,
     /// How to store tokens.
     pub store_token: TokenStorageMode,
+    /// Hash function for [`TokenStorageMode::Custom`].
+    pub hash_token: Option<TokenHashFn>,
+    /// Base URL of the auth routes that links point to, e.g.
+    /// `https://example.com/api/auth`. Default: `/api/auth`.
+    pub callback_base: String,
+    /// Origins, such as `https://app.example.com`, that callback URLs may
+    /// point to. Relative callback paths are always allowed. Default:
+    /// empty, so only relative paths are.
+    pub allowed_redirect_origins: Vec<String>,
+    /// Store for sent tokens. Without one, links are sent but can't be
+    /// verified, and the verify route returns a placeholder response.
+    pub token_store: Option<Arc<dyn MagicLinkTokenStore>>,
+    /// Storage adapter used to find or create the user signing in.
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    /// How tokens for the sessions it signs users into are generated.
+    /// Use the app's strategy. Default: opaque tokens.
+    pub session_tokens: SessionTokenStrategy,
 }
 
 impl Default for MagicLinkConfig {
@@ -69,8 +101,15 @@ impl Default for MagicLinkConfig {
             expires_in: 300, // 5 minutes
             disable_sign_up: false,
             send_magic_link: None,
+            sender: Arc::new(LoggingSender::new()),
             generate_token: None,
             store_token: TokenStorageMode::Plain,
+            hash_token: None,
+            callback_base: "/api/auth".to_string(),
+            allowed_redirect_origins: Vec::new(),
+            token_store: None,
+            storage: None,
+            session_tokens: SessionTokenStrategy::default(),
         }
     }
 }
@@ -103,6 +142,12 @@ impl MagicLinkConfig {
         self
     }
 
+    /// Sets the sender that delivers magic link emails.
+    pub fn sender(mut self, sender: Arc<dyn MessageSender>) -> Self {
+        self.sender = sender;
+        self
+    }
+
     /// Sets a custom token generator.
     pub fn generate_token_with<F>(mut self, generator: F) -> Self
     where
@@ -112,11 +157,110 @@ impl MagicLinkConfig {
         self
     }
 
+    /// Stores tokens hashed with `hasher`, setting the storage mode to
+    /// [`TokenStorageMode::Custom`].
+    pub fn hash_token_with<F>(mut self, hasher: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.store_token = TokenStorageMode::Custom;
+        self.hash_token = Some(Arc::new(hasher));
+        self
+    }
+
     /// Sets the token storage mode.
     pub fn store_token(mut self, mode: TokenStorageMode) -> Self {
         self.store_token = mode;
         self
     }
+
+    /// Sets the base URL of the auth routes that links point to.
+    pub fn callback_base(mut self, base: impl Into<String>) -> Self {
+        self.callback_base = base.into();
+        self
+    }
+
+    /// Sets the origins that callback URLs may point to.
+    pub fn allowed_redirect_origins<I, S>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_redirect_origins = origins.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the store for sent tokens.
+    pub fn token_store(mut self, store: Arc<dyn MagicLinkTokenStore>) -> Self {
+        self.token_store = Some(store);
+        self
+    }
+
+    /// Sets the storage adapter used to find or create users.
+    pub fn storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Sets how session tokens are generated.
+    pub fn session_tokens(mut self, strategy: SessionTokenStrategy) -> Self {
+        self.session_tokens = strategy;
+        self
+    }
+
+    /// Builds the URL a magic link points to, failing if `callback_url`
+    /// is not an allowed redirect.
+    pub fn build_url(&self, token: &str, callback_url: Option<&str>) -> AuthResult<String> {
+        let callback_url = callback_url.unwrap_or("/");
+        validate_redirect("callbackURL", callback_url, &self.allowed_redirect_origins)?;
+        Ok(format!(
+            "{}/magic-link/verify?token={}&callbackURL={}",
+            self.callback_base.trim_end_matches('/'),
+            urlencoding::encode(token),
+            urlencoding::encode(callback_url)
+        ))
+    }
+
+    /// Returns true if `url` may be redirected to: a relative path, or an
+    /// absolute URL on one of `allowed_redirect_origins`.
+    pub fn is_allowed_redirect(&self, url: &str) -> bool {
+        is_allowed_redirect(url, &self.allowed_redirect_origins)
+    }
+
+    /// Returns `token` in the form it is stored in, per `store_token`.
+    pub(crate) fn stored_token(&self, token: &str) -> String {
+        match (&self.store_token, &self.hash_token) {
+            (TokenStorageMode::Plain, _) => token.to_string(),
+            (TokenStorageMode::Custom, Some(hasher)) => hasher(token),
+            (TokenStorageMode::Hashed | TokenStorageMode::Custom, _) => hash_secret(token),
+        }
+    }
+
+    /// Generates a token with the custom generator, if any.
+    pub(crate) fn new_token(&self) -> String {
+        match &self.generate_token {
+            Some(generator) => generator(),
+            None => OtpGenerator::generate_secure_token(64),
+        }
+    }
+
+    /// Delivers a magic link through the callback, or `sender` if there is
+    /// none.
+    pub(crate) async fn deliver(&self, data: MagicLinkData) -> AuthResult<()> {
+        if let Some(callback) = &self.send_magic_link {
+            return callback(data)
+                .await
+                .map_err(|message| AuthError::plugin("magic_link", message));
+        }
+        let body = format!(
+            "Sign in by opening this link: {}\n\nIt expires in {} minutes.",
+            data.url,
+            self.expires_in.div_ceil(60)
+        );
+        self.sender
+            .send(&data.email, Some("Your sign-in link"), &body)
+            .await
+    }
 }
 
 impl std::fmt::Debug for MagicLinkConfig {
@@ -125,8 +269,15 @@ impl std::fmt::Debug for MagicLinkConfig {
             .field("expires_in", &self.expires_in)
             .field("disable_sign_up", &self.disable_sign_up)
             .field("send_magic_link", &self.send_magic_link.is_some())
+            .field("sender", &"<sender>")
             .field("generate_token", &self.generate_token.is_some())
             .field("store_token", &self.store_token)
+            .field("hash_token", &self.hash_token.is_some())
+            .field("callback_base", &self.callback_base)
+            .field("allowed_redirect_origins", &self.allowed_redirect_origins)
+            .field("token_store", &self.token_store.is_some())
+            .field("storage", &self.storage.is_some())
+            .field("session_tokens", &self.session_tokens)
             .finish()
     }
 }
@@ -1,8 +1,16 @@
 //! Request handlers for the Magic Link plugin.
 
 use async_trait::async_trait;
-use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_core::crypto::constant_time_eq;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::redirect::safe_redirect;
+use better_auth_core::router::{CookieOptions, Request, RequestHandler, Response};
+use better_auth_core::session::{SESSION_COOKIE, end_replaced_session};
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::{AuthFactor, Session, User};
+use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
+use crate::{MagicLinkConfig, MagicLinkData, MagicLinkToken, MagicLinkTokenStore};
 
 /// Request body for sending a magic link.
 #[derive(Debug, Deserialize)]
@@ -29,7 +37,9 @@ pub struct SignInMagicLinkResponse {
 }
 
 /// Handler for POST /sign-in/magic-link
-pub struct SignInMagicLinkHandler;
+pub struct SignInMagicLinkHandler {
+    pub config: MagicLinkConfig,
+}
 
 #[async_trait]
 impl RequestHandler for SignInMagicLinkHandler {
@@ -55,16 +65,63 @@ impl RequestHandler for SignInMagicLinkHandler {
             }));
         }
 
-        // In a real implementation, this would:
-        // 1. Generate token
-        // 2. Store in database
-        // 3. Build URL
-        // 4. Call sendMagicLink callback
-        
+        let token = self.config.new_token();
+        let url = match self.config.build_url(&token, body.callback_url.as_deref()) {
+            Ok(url) => url,
+            Err(err) => {
+                return Response::bad_request().json(serde_json::json!({
+                    "error": {
+                        "code": "INVALID_CALLBACK_URL",
+                        "message": err.to_string()
+                    }
+                }));
+            }
+        };
+        if let Some(store) = &self.config.token_store {
+            let expires_at = Utc::now() + Duration::seconds(self.config.expires_in as i64);
+            let record = MagicLinkToken::new(
+                body.email.trim().to_lowercase(),
+                self.config.stored_token(&token),
+                expires_at,
+            );
+            if let Err(err) = store.create_magic_link_token(&record).await {
+                return send_failed_response(err);
+            }
+        }
+        if let Err(err) = self.config.deliver(MagicLinkData::new(&body.email, url, token)).await {
+            return send_failed_response(err);
+        }
+
         Response::ok().json(SignInMagicLinkResponse { success: true })
     }
 }
 
+/// Converts a failed verification into an error response.
+fn verify_failed_response(err: AuthError) -> Response {
+    let code = match err {
+        AuthError::InvalidToken => "INVALID_TOKEN",
+        AuthError::TokenExpired => "TOKEN_EXPIRED",
+        AuthError::UserNotFound => "USER_NOT_FOUND",
+        _ => "INTERNAL_ERROR",
+    };
+    Response::new(err.status_code()).json(serde_json::json!({
+        "error": {
+            "code": code,
+            "message": err.to_string()
+        }
+    }))
+}
+
+/// Converts a failure to deliver a magic link into an error response.
+fn send_failed_response(err: AuthError) -> Response {
+    Response::new(err.status_code()).json(serde_json::json!({
+        "error": {
+            "code": "SEND_FAILED",
+            "message": err.to_string()
+        }
+    }))
+}
+
 /// Query parameters for verifying a magic link.
 #[derive(Debug, Deserialize)]
 pub struct VerifyMagicLinkQuery {
@@ -76,7 +133,54 @@ pub struct VerifyMagicLinkQuery {
 }
 
 /// Handler for GET /magic-link/verify
-pub struct VerifyMagicLinkHandler;
+///
+/// Signs the user in, creating them unless sign-up is disabled. Opening the
+/// link proves they own the email, so it is marked as verified.
+///
+/// A callback URL that is not an allowed redirect is replaced with `/`,
+/// since anyone can craft a verify link.
+pub struct VerifyMagicLinkHandler {
+    pub config: MagicLinkConfig,
+}
+
+impl VerifyMagicLinkHandler {
+    /// Uses up `token`, returning the user it signs in and a new session.
+    async fn sign_in(
+        &self,
+        token_store: &dyn MagicLinkTokenStore,
+        storage: &dyn StorageAdapter,
+        req: &Request,
+        token: &str,
+    ) -> AuthResult<(User, Session)> {
+        let stored = self.config.stored_token(token);
+        let record = token_store
+            .get_magic_link_token(&stored)
+            .await?
+            .filter(|record| constant_time_eq(&record.token, &stored))
+            .ok_or(AuthError::InvalidToken)?;
+        if record.is_expired() {
+            return Err(AuthError::TokenExpired);
+        }
+        if !token_store.use_magic_link_token(&record.id).await? {
+            return Err(AuthError::InvalidToken);
+        }
+
+        let user = match storage.mark_email_verified(&record.email).await? {
+            Some(user) => user,
+            None if self.config.disable_sign_up => return Err(AuthError::UserNotFound),
+            None => {
+                let mut user = User::new(uuid::Uuid::new_v4().to_string(), record.email.clone());
+                user.email_verified = true;
+                storage.create_user(&user).await?
+            }
+        };
+        let mut session = self.config.session_tokens.new_session(user.id.clone());
+        session.record_factor(AuthFactor::MagicLink);
+        let session = storage.create_session(&session).await?;
+        end_replaced_session(storage, req).await?;
+        Ok((user, session))
+    }
+}
 
 #[async_trait]
 impl RequestHandler for VerifyMagicLinkHandler {
@@ -102,18 +206,40 @@ impl RequestHandler for VerifyMagicLinkHandler {
             }));
         }
 
-        // In a real implementation, this would:
-        // 1. Look up token in database
-        // 2. Verify it's not expired or used
-        // 3. Find or create user
-        // 4. Create session
-        // 5. Mark token as used
-        // 6. Redirect to callback URL or return session
-        
+        if let (Some(token_store), Some(storage)) = (&self.config.token_store, &self.config.storage) {
+            let signed_in = self
+                .sign_in(token_store.as_ref(), storage.as_ref(), &req, token)
+                .await;
+            let (user, session) = match signed_in {
+                Ok(signed_in) => signed_in,
+                Err(err) => return verify_failed_response(err),
+            };
+            let response = match callback_url {
+                Some(url) => {
+                    let url = safe_redirect(Some(url), &self.config.allowed_redirect_origins, "/");
+                    Response::new(302).header("Location", url)
+                }
+                None => Response::ok().json(serde_json::json!({
+                    "user": {
+                        "id": user.id,
+                        "email": user.email,
+                        "email_verified": user.email_verified
+                    },
+                    "session": {
+                        "id": session.id,
+                        "token": session.token,
+                        "expires_at": session.expires_at.to_rfc3339()
+                    }
+                })),
+            };
+            return response.cookie(SESSION_COOKIE, &session.token, CookieOptions::secure());
+        }
+
         // If callback URL is provided, redirect
         if let Some(url) = callback_url {
+            let url = safe_redirect(Some(url), &self.config.allowed_redirect_origins, "/");
             return Response::new(302)
-                .header("Location", url.as_str());
+                .header("Location", url);
         }
 
         // Otherwise return session data
@@ -7,9 +7,11 @@
 mod config;
 mod schema;
 mod handlers;
+mod store;
 
-pub use config::{MagicLinkConfig, MagicLinkData};
+pub use config::{MagicLinkConfig, MagicLinkData, TokenStorageMode};
 pub use schema::{MagicLinkToken, MagicLinkSchema};
+pub use store::{InMemoryMagicLinkTokenStore, MagicLinkTokenStore};
 
 use async_trait::async_trait;
 use better_auth_core::context::AuthContext;
@@ -19,7 +21,6 @@ use better_auth_core::schema::SchemaBuilder;
 use better_auth_core::traits::{AuthPlugin, SchemaProvider};
 use better_auth_core::types::User;
 use better_auth_events_sdk::{EventDefinition, EventProvider};
-use better_auth_otp_utils::OtpGenerator;
 
 /// The Magic Link authentication plugin.
 pub struct MagicLinkPlugin {
@@ -39,17 +40,14 @@ impl MagicLinkPlugin {
 
     /// Generates a new magic link token.
     pub fn generate_token(&self) -> String {
-        if let Some(ref generator) = self.config.generate_token {
-            generator()
-        } else {
-            OtpGenerator::generate_secure_token(64)
-        }
+        self.config.new_token()
     }
 
     /// Builds the magic link URL.
-    pub fn build_url(&self, token: &str, callback_url: Option<&str>) -> String {
-        let base = callback_url.unwrap_or("/");
-        format!("/api/auth/magic-link/verify?token={}&callbackURL={}", token, base)
+    ///
+    /// See [`MagicLinkConfig::build_url`].
+    pub fn build_url(&self, token: &str, callback_url: Option<&str>) -> AuthResult<String> {
+        self.config.build_url(token, callback_url)
     }
 }
 
@@ -112,7 +110,9 @@ impl AuthPlugin for MagicLinkPlugin {
             Route::new(
                 Method::POST,
                 "/sign-in/magic-link",
-                handlers::SignInMagicLinkHandler,
+                handlers::SignInMagicLinkHandler {
+                    config: self.config.clone(),
+                },
             )
             .summary("Send magic link")
             .description("Sends a magic link to the specified email address for authentication.")
@@ -124,7 +124,9 @@ impl AuthPlugin for MagicLinkPlugin {
             Route::new(
                 Method::GET,
                 "/magic-link/verify",
-                handlers::VerifyMagicLinkHandler,
+                handlers::VerifyMagicLinkHandler {
+                    config: self.config.clone(),
+                },
             )
             .summary("Verify magic link")
             .description("Verifies a magic link token and creates a session.")
@@ -140,6 +142,7 @@ impl AuthPlugin for MagicLinkPlugin {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use better_auth_core::types::AuthFactor;
 
     #[test]
     fn test_plugin_creation() {
@@ -157,8 +160,178 @@ mod tests {
     #[test]
     fn test_url_building() {
         let plugin = MagicLinkPlugin::default();
-        let url = plugin.build_url("abc123", Some("/dashboard"));
+        let url = plugin.build_url("abc123", Some("/dashboard")).unwrap();
         assert!(url.contains("token=abc123"));
-        assert!(url.contains("callbackURL=/dashboard"));
+        assert!(url.contains("callbackURL=%2Fdashboard"));
+    }
+
+    #[test]
+    fn test_url_building_encodes_params() {
+        let plugin = MagicLinkPlugin::new(MagicLinkConfig::new().callback_base("https://example.com/api/auth/"));
+        let url = plugin.build_url("a+b", Some("/welcome?tab=new&ref=mail")).unwrap();
+        assert_eq!(
+            url,
+            "https://example.com/api/auth/magic-link/verify?token=a%2Bb&callbackURL=%2Fwelcome%3Ftab%3Dnew%26ref%3Dmail"
+        );
+    }
+
+    #[test]
+    fn test_url_building_rejects_disallowed_origin() {
+        let plugin = MagicLinkPlugin::new(
+            MagicLinkConfig::new().allowed_redirect_origins(["https://app.example.com"]),
+        );
+        assert!(plugin.build_url("abc123", Some("/dashboard")).is_ok());
+        assert!(plugin.build_url("abc123", Some("https://app.example.com/home?x=1")).is_ok());
+        assert!(plugin.build_url("abc123", None).is_ok());
+
+        for callback in [
+            "https://evil.com/home",
+            "https://app.example.com.evil.com",
+            "https://app.example.com@evil.com",
+            "//evil.com",
+            "/\\evil.com",
+            "javascript:alert(1)",
+        ] {
+            assert!(plugin.build_url("abc123", Some(callback)).is_err(), "{callback}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redirects_are_validated() {
+        use better_auth_core::router::Request;
+
+        let plugin = MagicLinkPlugin::new(
+            MagicLinkConfig::new().allowed_redirect_origins(["https://app.example.com"]),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let sign_in = router.routes().find(|r| r.path == "/sign-in/magic-link").unwrap();
+        let mut req = Request::new(Method::POST, "/sign-in/magic-link");
+        req.body = Some(serde_json::json!({ "email": "jane@example.com", "callbackURL": "https://evil.com" }));
+        let response = sign_in.handler.handle(req).await;
+        assert_eq!(response.status, 400);
+        assert_eq!(response.body.unwrap()["error"]["code"], "INVALID_CALLBACK_URL");
+
+        let verify = router.routes().find(|r| r.path == "/magic-link/verify").unwrap();
+        for (callback, location) in [
+            ("//evil.com", "/"),
+            ("https://evil.com", "/"),
+            ("https://app.example.com/home", "https://app.example.com/home"),
+        ] {
+            let mut req = Request::new(Method::GET, "/magic-link/verify");
+            req.query.insert("token".to_string(), "abc123".to_string());
+            req.query.insert("callbackURL".to_string(), callback.to_string());
+            let response = verify.handler.handle(req).await;
+            assert_eq!(response.status, 302);
+            assert_eq!(response.headers["location"], location, "{callback}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_sends_link() {
+        use better_auth_core::router::Request;
+
+        let sender = std::sync::Arc::new(better_auth_otp_utils::testing::RecordingSender::new());
+        let plugin = MagicLinkPlugin::new(
+            MagicLinkConfig::new()
+                .sender(sender.clone())
+                .generate_token_with(|| "abc123".to_string()),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let route = router.routes().find(|r| r.path == "/sign-in/magic-link").unwrap();
+        let mut req = Request::new(Method::POST, "/sign-in/magic-link");
+        req.body = Some(serde_json::json!({ "email": "jane@example.com", "callbackURL": "/dashboard" }));
+        assert_eq!(route.handler.handle(req).await.status, 200);
+
+        let sent = sender.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "jane@example.com");
+        assert_eq!(sent[0].subject.as_deref(), Some("Your sign-in link"));
+        assert!(sent[0].body.contains(&plugin.build_url("abc123", Some("/dashboard")).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_verify_signs_in_and_marks_email_verified() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::router::Request;
+        use better_auth_core::traits::StorageAdapter;
+
+        let storage = std::sync::Arc::new(MemoryAdapter::new());
+        storage
+            .create_user(&User::new("user_1".to_string(), "jane@example.com".to_string()))
+            .await
+            .unwrap();
+        let plugin = MagicLinkPlugin::new(
+            MagicLinkConfig::new()
+                .token_store(std::sync::Arc::new(InMemoryMagicLinkTokenStore::new()))
+                .storage(storage.clone())
+                .generate_token_with(|| "abc123".to_string()),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let sign_in = router.routes().find(|r| r.path == "/sign-in/magic-link").unwrap();
+        let mut req = Request::new(Method::POST, "/sign-in/magic-link");
+        req.body = Some(serde_json::json!({ "email": "Jane@example.com" }));
+        assert_eq!(sign_in.handler.handle(req).await.status, 200);
+
+        let verify = router.routes().find(|r| r.path == "/magic-link/verify").unwrap();
+        let open_link = |token: &str| {
+            let mut req = Request::new(Method::GET, "/magic-link/verify");
+            req.query.insert("token".to_string(), token.to_string());
+            verify.handler.handle(req)
+        };
+        assert_eq!(open_link("wrong").await.body.unwrap()["error"]["code"], "INVALID_TOKEN");
+
+        let response = open_link("abc123").await;
+        assert_eq!(response.status, 200);
+        let body = response.body.unwrap();
+        assert_eq!(body["user"]["id"], "user_1");
+        assert_eq!(body["user"]["email_verified"], true);
+        let token = body["session"]["token"].as_str().unwrap();
+        let session = storage.get_session_by_token(token).await.unwrap().unwrap();
+        assert!(session.factor_verified_at(AuthFactor::MagicLink).is_some());
+        assert!(storage.get_user_by_id("user_1").await.unwrap().unwrap().email_verified);
+
+        // Each link works once.
+        assert_eq!(open_link("abc123").await.status, 401);
+    }
+    #[tokio::test]
+    async fn test_hashed_tokens_are_not_stored_in_plain_text() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::router::Request;
+
+        let token_store = std::sync::Arc::new(InMemoryMagicLinkTokenStore::new());
+        let plugin = MagicLinkPlugin::new(
+            MagicLinkConfig::new()
+                .token_store(token_store.clone())
+                .storage(std::sync::Arc::new(MemoryAdapter::new()))
+                .store_token(TokenStorageMode::Hashed)
+                .generate_token_with(|| "abc123".to_string()),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let sign_in = router.routes().find(|r| r.path == "/sign-in/magic-link").unwrap();
+        let mut req = Request::new(Method::POST, "/sign-in/magic-link");
+        req.body = Some(serde_json::json!({ "email": "jane@example.com" }));
+        assert_eq!(sign_in.handler.handle(req).await.status, 200);
+
+        let hashed = better_auth_core::hash_secret("abc123");
+        assert!(token_store.get_magic_link_token("abc123").await.unwrap().is_none());
+        assert!(token_store.get_magic_link_token(&hashed).await.unwrap().is_some());
+
+        let verify = router.routes().find(|r| r.path == "/magic-link/verify").unwrap();
+        let open_link = |token: &str| {
+            let mut req = Request::new(Method::GET, "/magic-link/verify");
+            req.query.insert("token".to_string(), token.to_string());
+            verify.handler.handle(req)
+        };
+        // The stored hash is not itself a working link.
+        assert_eq!(open_link(&hashed).await.status, 401);
+        assert_eq!(open_link("abc123").await.status, 200);
     }
 }
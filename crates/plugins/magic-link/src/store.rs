@@ -0,0 +1,62 @@
+//! Storage for magic link tokens.
+
+use crate::MagicLinkToken;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use std::sync::RwLock;
+
+/// Storage for magic link tokens.
+///
+/// Adapters implement this trait to persist the `magic_link_token` model.
+#[async_trait]
+pub trait MagicLinkTokenStore: Send + Sync {
+    /// Stores a newly sent token.
+    async fn create_magic_link_token(&self, token: &MagicLinkToken) -> AuthResult<MagicLinkToken>;
+
+    /// Gets a token by its value, used or not.
+    async fn get_magic_link_token(&self, token: &str) -> AuthResult<Option<MagicLinkToken>>;
+
+    /// Marks a token as used, returning false if it already was.
+    ///
+    /// This must be atomic, so that a link opened twice at once signs in
+    /// only once.
+    async fn use_magic_link_token(&self, id: &str) -> AuthResult<bool>;
+}
+
+/// In-memory magic link token store.
+///
+/// Suitable for a single instance and for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryMagicLinkTokenStore {
+    tokens: RwLock<Vec<MagicLinkToken>>,
+}
+
+impl InMemoryMagicLinkTokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl MagicLinkTokenStore for InMemoryMagicLinkTokenStore {
+    async fn create_magic_link_token(&self, token: &MagicLinkToken) -> AuthResult<MagicLinkToken> {
+        self.tokens.write().unwrap().push(token.clone());
+        Ok(token.clone())
+    }
+
+    async fn get_magic_link_token(&self, token: &str) -> AuthResult<Option<MagicLinkToken>> {
+        let tokens = self.tokens.read().unwrap();
+        Ok(tokens.iter().find(|t| t.token == token).cloned())
+    }
+
+    async fn use_magic_link_token(&self, id: &str) -> AuthResult<bool> {
+        let mut tokens = self.tokens.write().unwrap();
+        match tokens.iter_mut().find(|t| t.id == id && !t.used) {
+            Some(token) => {
+                token.mark_used();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
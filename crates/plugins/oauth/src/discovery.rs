@@ -0,0 +1,153 @@
+//! OpenID Connect discovery.
+//!
+//! Fetches an issuer's `/.well-known/openid-configuration` document so a
+//! [`GenericOAuthProvider`](crate::GenericOAuthProvider) can be configured
+//! from the issuer URL alone.
+
+use crate::provider::OAuthError;
+use better_auth_core::redact::redact;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+/// The parts of an OpenID Provider Metadata document the plugin uses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OidcDiscovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+    #[serde(default)]
+    pub code_challenge_methods_supported: Vec<String>,
+}
+
+impl OidcDiscovery {
+    /// Fetches and checks the discovery document for `issuer`.
+    ///
+    /// The document's `issuer` must match the requested one (ignoring a
+    /// trailing slash), as OpenID Connect Discovery 1.0 section 4.3 requires.
+    pub async fn fetch(client: &Client, issuer: &str) -> Result<Self, OAuthError> {
+        let issuer = issuer.trim_end_matches('/');
+        let url = format!("{}/.well-known/openid-configuration", issuer);
+        let failed = |message: String| OAuthError::DiscoveryFailed(format!("{}: {}", url, message));
+
+        let response = client
+            .get(&url)
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(|e| failed(redact(&e.to_string())))?;
+
+        if !response.status().is_success() {
+            return Err(failed(format!("HTTP {}", response.status())));
+        }
+
+        let document: Self = response
+            .json()
+            .await
+            .map_err(|e| failed(format!("invalid document: {}", e)))?;
+
+        if document.issuer.trim_end_matches('/') != issuer {
+            return Err(failed(format!(
+                "issuer mismatch: document is for '{}'",
+                document.issuer
+            )));
+        }
+
+        Ok(document)
+    }
+
+    /// Returns true if the issuer accepts `S256` PKCE challenges.
+    pub fn supports_pkce(&self) -> bool {
+        self.code_challenge_methods_supported
+            .iter()
+            .any(|method| method == "S256")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{GenericOAuthProvider, OAuthProvider};
+    use crate::test_server::serve_once;
+
+    fn document(issuer: &str) -> String {
+        serde_json::json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{}/authorize", issuer),
+            "token_endpoint": format!("{}/token", issuer),
+            "userinfo_endpoint": format!("{}/userinfo", issuer),
+            "jwks_uri": format!("{}/keys", issuer),
+            "code_challenge_methods_supported": ["plain", "S256"],
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_discover_populates_endpoints() {
+        let (issuer, _) = serve_once("200 OK", document).await;
+
+        let provider = GenericOAuthProvider::builder("okta")
+            .client_id("client_id")
+            .client_secret("client_secret")
+            .discover(&format!("{}/", issuer))
+            .await
+            .unwrap()
+            .build();
+
+        let url = provider.auth_url("state", &[], "http://localhost/callback");
+        assert!(url.starts_with(&format!("{}/authorize?", issuer)));
+        assert!(url.contains("scope=openid%20email%20profile"));
+        assert!(provider.supports_pkce());
+        assert_eq!(
+            provider.jwks_uri(),
+            Some(format!("{}/keys", issuer).as_str())
+        );
+        assert_eq!(provider.discovery().unwrap().issuer, issuer);
+    }
+
+    #[tokio::test]
+    async fn test_explicit_endpoints_take_precedence() {
+        let (issuer, _) = serve_once("200 OK", document).await;
+
+        let provider = GenericOAuthProvider::builder("okta")
+            .client_id("client_id")
+            .client_secret("client_secret")
+            .auth_url("https://custom.example.com/authorize")
+            .discover(&issuer)
+            .await
+            .unwrap()
+            .build();
+
+        let url = provider.auth_url("state", &[], "http://localhost/callback");
+        assert!(url.starts_with("https://custom.example.com/authorize?"));
+    }
+
+    #[tokio::test]
+    async fn test_discover_rejects_issuer_mismatch() {
+        let (issuer, _) = serve_once("200 OK", |_| document("https://evil.example.com")).await;
+
+        let err = GenericOAuthProvider::builder("okta")
+            .discover(&issuer)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, OAuthError::DiscoveryFailed(ref m) if m.contains("issuer mismatch")));
+    }
+
+    #[tokio::test]
+    async fn test_discover_reports_http_errors() {
+        let (issuer, _) = serve_once("404 Not Found", |_| "{}".to_string()).await;
+
+        let err = GenericOAuthProvider::builder("okta")
+            .discover(&issuer)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, OAuthError::DiscoveryFailed(ref m) if m.contains("HTTP 404")));
+    }
+}
@@ -6,44 +6,79 @@
 //!
 //! ## Features
 //!
-//! - Multiple OAuth providers (Google, GitHub, Discord)
+//! - Multiple OAuth providers (Google, GitHub, Discord, Apple, Microsoft)
 //! - CSRF protection via state parameter
+//! - Open-redirect protection for `redirect_url`
+//! - PKCE (S256) for providers that support it
 //! - Account linking and unlinking
+//! - Configurable handling of sign-ins whose email belongs to an existing user
+//! - Optional storage of raw provider profiles, and a hook to map them onto users
+//! - Typed provider errors on callback, with an optional error redirect
 //! - Configurable token response strategy (cookie, JWT, or both)
-//! - Generic provider builder for custom OAuth2 providers
+//! - Generic provider builder for custom OAuth2 providers, with OIDC discovery
+//! - Shared, configurable HTTP clients for providers, with timeouts by default
+//! - Timeouts on provider requests, and bounded retries of userinfo requests
 //!
 //! ## Example
 //!
 //! ```rust,ignore
-//! use better_auth_plugin_oauth::{OAuthConfig, OAuthPlugin, GoogleProvider, GitHubProvider};
+//! use better_auth_plugin_oauth::{
+//!     GitHubProvider, GoogleProvider, InMemoryOAuthStateStore, OAuthConfig, OAuthPlugin,
+//! };
+//! use std::sync::Arc;
 //!
 //! let oauth = OAuthPlugin::new(
 //!     OAuthConfig::new()
 //!         .callback_base("https://myapp.com/api/auth")
 //!         .provider(GoogleProvider::new("client_id", "client_secret"))
-//!         .provider(GitHubProvider::new("client_id", "client_secret"))
+//!         .provider(GitHubProvider::new("client_id", "client_secret")),
+//!     Arc::new(InMemoryOAuthStateStore::new()),
 //! );
 //! ```
 
+mod discovery;
+pub mod pkce;
 mod provider;
+mod retry;
 mod routes;
+mod state_store;
+#[cfg(test)]
+mod test_server;
 
+pub use discovery::OidcDiscovery;
 pub use provider::{
-    DiscordProvider, GenericOAuthProvider, GenericOAuthProviderBuilder, GitHubProvider,
-    GoogleProvider, OAuthError, OAuthProvider, OAuthUserInfo, TokenSet,
+    AppleProvider, DEFAULT_CONNECT_TIMEOUT, DEFAULT_READ_TIMEOUT, DiscordProvider,
+    GenericOAuthProvider, GenericOAuthProviderBuilder, GitHubProvider, GoogleProvider,
+    MicrosoftProvider, OAuthError, OAuthProvider, OAuthUserInfo, TokenSet, UserInfoFieldMapping,
+    default_http_client, verify_id_token_nonce,
 };
-pub use routes::{OAuthStateStore, TokenResponseStrategy};
+pub use routes::{EmailConflictStrategy, TokenResponseStrategy};
+pub use state_store::{InMemoryOAuthStateStore, OAuthStateStore};
 
 use async_trait::async_trait;
 use better_auth_core::context::AuthContext;
-use better_auth_core::error::AuthResult;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::redact::Redact;
 use better_auth_core::router::Router;
 use better_auth_core::schema::SchemaBuilder;
-use better_auth_core::traits::AuthPlugin;
-use better_auth_core::types::Session;
+use better_auth_core::events::{Event, EventBus};
+use better_auth_core::session_token::SessionTokenStrategy;
+use better_auth_core::traits::{AuthPlugin, StorageAdapter};
+use better_auth_core::types::{Account, Session, User};
 use better_auth_events_sdk::{EventDefinition, EventProvider};
+use better_auth_plugin_email_domain::EmailDomainConfig;
+use better_auth_plugin_passkey::PasskeyStore;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Maps a provider's profile onto the signed-in user, for example to copy
+/// a `locale` into a user extension. Receives the profile and the user as
+/// they are and returns the user to store.
+pub type ProfileMapper = Arc<dyn Fn(&OAuthUserInfo, &User) -> User + Send + Sync>;
+
+/// Default for [`OAuthConfig::request_timeout`].
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// OAuth plugin configuration.
 #[derive(Clone)]
@@ -56,8 +91,56 @@ pub struct OAuthConfig {
     pub allow_linking: bool,
     /// Whether to auto-create users on first OAuth login.
     pub auto_create_user: bool,
+    /// Whether to store the provider's raw userinfo on the account's
+    /// `profile` on each sign-in.
+    ///
+    /// Profiles are usually a few hundred bytes, but some are much larger
+    /// (a Discord profile with guilds, or a Microsoft profile with group
+    /// claims, can run to tens of kilobytes) and the column is rewritten
+    /// whenever the profile changes. They also hold personal data that the
+    /// user's data export will include. Prefer a [`ProfileMapper`] when only
+    /// a few fields are needed.
+    pub store_raw_profile: bool,
+    /// Applied to the user on each sign-in; see [`ProfileMapper`].
+    pub profile_mapper: Option<ProfileMapper>,
+    /// What to do when a first OAuth login's email belongs to an existing
+    /// user.
+    pub on_email_conflict: EmailConflictStrategy,
     /// Token response strategy.
     pub token_response: TokenResponseStrategy,
+    /// Email domain restrictions applied when auto-creating users.
+    pub email_domains: Option<EmailDomainConfig>,
+    /// Whether to use PKCE with providers that support it.
+    pub pkce: bool,
+    /// Origins, such as `https://app.example.com`, that `redirect_url` may
+    /// point to. Relative paths are always allowed.
+    pub allowed_redirect_origins: Vec<String>,
+    /// Where to send the user when the provider returns an error to the
+    /// callback, such as a cancelled sign-in. The `error` and
+    /// `error_description` are added to the query string. Without one, the
+    /// callback responds with a JSON error.
+    pub error_redirect_url: Option<String>,
+    /// Storage adapter used to sign users in and to unlink accounts.
+    /// Without one, the callback returns a session that is never stored.
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    /// Passkey storage, so unlinking counts passkeys as a way to sign in.
+    /// Without one, a user whose only other method is a passkey cannot
+    /// unlink their last account.
+    pub passkeys: Option<Arc<dyn PasskeyStore>>,
+    /// How tokens for the sessions it signs users into are generated.
+    /// Use the app's strategy. Default: opaque tokens.
+    pub session_tokens: SessionTokenStrategy,
+    /// Event bus used to emit `oauth.account_linked`,
+    /// `oauth.account_unlinked` and `oauth.token_refresh_failed`.
+    pub event_bus: Option<Arc<EventBus>>,
+    /// How long each token exchange, token refresh or userinfo request may
+    /// take before failing with [`OAuthError::Timeout`].
+    pub request_timeout: Duration,
+    /// How many more times a userinfo request is sent after a timeout or
+    /// transport error, with jittered backoff in between. Token exchanges
+    /// and refreshes are never retried, since their codes and refresh
+    /// tokens may only be used once.
+    pub user_info_retries: u32,
 }
 
 impl Default for OAuthConfig {
@@ -67,7 +150,20 @@ impl Default for OAuthConfig {
             callback_base: "/api/auth".to_string(),
             allow_linking: true,
             auto_create_user: true,
+            store_raw_profile: false,
+            profile_mapper: None,
+            on_email_conflict: EmailConflictStrategy::default(),
             token_response: TokenResponseStrategy::default(),
+            email_domains: None,
+            pkce: true,
+            allowed_redirect_origins: Vec::new(),
+            error_redirect_url: None,
+            storage: None,
+            passkeys: None,
+            session_tokens: SessionTokenStrategy::default(),
+            event_bus: None,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            user_info_retries: 2,
         }
     }
 }
@@ -103,31 +199,164 @@ impl OAuthConfig {
         self
     }
 
+    /// Sets whether to store the provider's raw userinfo on the account
+    /// (off by default). See the field of the same name for the storage
+    /// cost.
+    pub fn store_raw_profile(mut self, store: bool) -> Self {
+        self.store_raw_profile = store;
+        self
+    }
+
+    /// Sets a hook that maps the provider's profile onto the user at
+    /// sign-in.
+    pub fn profile_mapper(
+        mut self,
+        mapper: impl Fn(&OAuthUserInfo, &User) -> User + Send + Sync + 'static,
+    ) -> Self {
+        self.profile_mapper = Some(Arc::new(mapper));
+        self
+    }
+
+    /// Sets what to do when a first OAuth login's email belongs to an
+    /// existing user (default: [`EmailConflictStrategy::LinkIfVerified`]).
+    pub fn on_email_conflict(mut self, strategy: EmailConflictStrategy) -> Self {
+        self.on_email_conflict = strategy;
+        self
+    }
+
     /// Sets the token response strategy.
     pub fn token_response(mut self, strategy: TokenResponseStrategy) -> Self {
         self.token_response = strategy;
         self
     }
+
+    /// Restricts which email domains can auto-create users. Rejections are
+    /// emitted on `config`'s event bus.
+    pub fn email_domains(mut self, config: EmailDomainConfig) -> Self {
+        self.email_domains = Some(config);
+        self
+    }
+
+    /// Sets whether to use PKCE with providers that support it (on by
+    /// default).
+    pub fn pkce(mut self, enabled: bool) -> Self {
+        self.pkce = enabled;
+        self
+    }
+
+    /// Sets the origins that `redirect_url` may point to.
+    pub fn allowed_redirect_origins<I, S>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_redirect_origins = origins.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets where to redirect when the provider returns an error.
+    pub fn error_redirect_url(mut self, url: impl Into<String>) -> Self {
+        self.error_redirect_url = Some(url.into());
+        self
+    }
+
+    /// Sets the storage adapter used by the callback and unlink routes.
+    pub fn storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Sets the passkey storage consulted before unlinking the last
+    /// account.
+    pub fn passkeys(mut self, store: Arc<dyn PasskeyStore>) -> Self {
+        self.passkeys = Some(store);
+        self
+    }
+
+    /// Sets how session tokens are generated.
+    pub fn session_tokens(mut self, strategy: SessionTokenStrategy) -> Self {
+        self.session_tokens = strategy;
+        self
+    }
+
+    /// Sets the event bus used to emit account events.
+    pub fn event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// Sets how long each request to a provider may take (default: 10
+    /// seconds).
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Sets how many times a failed userinfo request is retried (default:
+    /// 2).
+    pub fn user_info_retries(mut self, retries: u32) -> Self {
+        self.user_info_retries = retries;
+        self
+    }
+
+    /// Checks the configuration, reporting every problem found.
+    pub fn validate(&self) -> AuthResult<()> {
+        let mut issues = Vec::new();
+
+        if self.callback_base.trim().is_empty() {
+            issues.push("callback base must not be empty".to_string());
+        }
+        if self.request_timeout.is_zero() {
+            issues.push("request timeout must not be zero".to_string());
+        }
+
+        let mut names: Vec<_> = self.providers.keys().collect();
+        names.sort();
+        for name in names {
+            let provider = &self.providers[name];
+            if provider.client_id().trim().is_empty() {
+                issues.push(format!("provider '{}' has a blank client id", name));
+            }
+            if provider.client_secret().trim().is_empty() {
+                issues.push(format!("provider '{}' has a blank client secret", name));
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(AuthError::invalid_config("oauth", issues))
+        }
+    }
 }
 
 /// The OAuth authentication plugin.
 pub struct OAuthPlugin {
     config: Arc<OAuthConfig>,
-    state_store: Arc<OAuthStateStore>,
+    state_store: Arc<dyn OAuthStateStore>,
 }
 
 impl OAuthPlugin {
-    /// Creates a new OAuth plugin with the given configuration.
-    pub fn new(config: OAuthConfig) -> Self {
+    /// Creates a new OAuth plugin with the given configuration and state
+    /// store.
+    ///
+    /// Use [`InMemoryOAuthStateStore`] for a single instance; deployments
+    /// behind a load balancer need a shared store so the callback can be
+    /// handled by any instance.
+    pub fn new(config: OAuthConfig, state_store: Arc<dyn OAuthStateStore>) -> Self {
         Self {
             config: Arc::new(config),
-            state_store: Arc::new(OAuthStateStore::new()),
+            state_store,
         }
     }
 
-    /// Creates a new OAuth plugin with default configuration.
+    /// Creates a new OAuth plugin with default configuration and an
+    /// in-memory state store.
     pub fn default_config() -> Self {
-        Self::new(OAuthConfig::default())
+        Self::new(
+            OAuthConfig::default(),
+            Arc::new(InMemoryOAuthStateStore::new()),
+        )
     }
 
     /// Gets a provider by name.
@@ -141,13 +370,78 @@ impl OAuthPlugin {
     }
 
     /// Returns a reference to the state store.
-    pub fn state_store(&self) -> &Arc<OAuthStateStore> {
+    pub fn state_store(&self) -> &Arc<dyn OAuthStateStore> {
         &self.state_store
     }
 
     /// Cleans up expired OAuth states.
-    pub fn cleanup_expired_states(&self) {
-        self.state_store.cleanup_expired();
+    pub async fn cleanup_expired_states(&self) -> AuthResult<()> {
+        self.state_store.cleanup_expired().await
+    }
+
+    /// Refreshes the provider tokens stored on an account.
+    ///
+    /// Meant for background jobs that keep provider tokens fresh. The old
+    /// refresh token is kept if the provider does not rotate it. If the
+    /// provider rejects the refresh, `oauth.token_refresh_failed` is emitted
+    /// so the user can be asked to re-consent.
+    pub async fn refresh_account_tokens(
+        &self,
+        ctx: &AuthContext,
+        account_id: &str,
+    ) -> AuthResult<Account> {
+        let mut account = ctx
+            .db
+            .get_account_by_id(account_id)
+            .await?
+            .ok_or_else(|| AuthError::not_found("account", "id", account_id))?;
+        let provider = self.get_provider(&account.provider).ok_or_else(|| {
+            AuthError::plugin(
+                "oauth",
+                format!("Provider '{}' is not configured", account.provider),
+            )
+        })?;
+
+        let result = match &account.refresh_token {
+            Some(token) => {
+                let refresh = provider.refresh_token(token);
+                retry::with_timeout("token refresh", self.config.request_timeout, refresh).await
+            }
+            None => Err(OAuthError::RefreshFailed(format!(
+                "account '{}' has no refresh token",
+                account.id
+            ))),
+        };
+        let tokens = match result {
+            Ok(tokens) => tokens,
+            Err(err) => {
+                if let Some(bus) = &self.config.event_bus {
+                    bus.emit(
+                        Event::simple(
+                            "oauth.token_refresh_failed",
+                            serde_json::json!({
+                                "user_id": account.user_id,
+                                "account_id": account.id,
+                                "provider": account.provider,
+                                "error": err.to_string(),
+                            }),
+                        )
+                        .with_source("oauth"),
+                    )
+                    .await;
+                }
+                return Err(AuthError::plugin("oauth", err.to_string()));
+            }
+        };
+
+        account.access_token = Some(tokens.access_token);
+        if let Some(refresh_token) = tokens.refresh_token {
+            account.refresh_token = Some(refresh_token);
+        }
+        account.expires_at = tokens
+            .expires_in
+            .map(|secs| chrono::Utc::now() + chrono::Duration::seconds(secs as i64));
+        ctx.db.update_account(&account).await
     }
 }
 
@@ -185,6 +479,11 @@ impl EventProvider for OAuthPlugin {
                 "Emitted when an OAuth account is unlinked from a user",
                 "oauth",
             ),
+            EventDefinition::simple(
+                "oauth.token_refresh_failed",
+                "Emitted when refreshing stored provider tokens fails",
+                "oauth",
+            ),
         ]
     }
 
@@ -203,6 +502,10 @@ impl AuthPlugin for OAuthPlugin {
         "OAuth Authentication"
     }
 
+    fn validate_config(&self) -> AuthResult<()> {
+        self.config.validate()
+    }
+
     fn define_schema(&self, _builder: &mut SchemaBuilder) {
         // The account table is already in core, but we might add OAuth-specific fields
         // For now, we just ensure the account model has what we need
@@ -231,7 +534,9 @@ impl AuthPlugin for OAuthPlugin {
 }
 
 /// OAuth state stored during the flow.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+///
+/// The `Debug` output masks the PKCE code verifier.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct OAuthState {
     /// Random state string for CSRF protection.
     pub state: String,
@@ -247,6 +552,32 @@ pub struct OAuthState {
     /// User ID if this is a linking flow.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_id: Option<String>,
+    /// PKCE code verifier, sent with the token exchange.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_verifier: Option<String>,
+    /// PKCE `S256` code challenge, sent in the authorization URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_challenge: Option<String>,
+    /// OIDC nonce, sent in the authorization URL and expected in the
+    /// `id_token`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+impl std::fmt::Debug for OAuthState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuthState")
+            .field("state", &self.state)
+            .field("provider", &self.provider)
+            .field("redirect_url", &self.redirect_url)
+            .field("expires_at", &self.expires_at)
+            .field("is_linking", &self.is_linking)
+            .field("user_id", &self.user_id)
+            .field("code_verifier", &self.code_verifier.as_ref().map(Redact))
+            .field("code_challenge", &self.code_challenge)
+            .field("nonce", &self.nonce)
+            .finish()
+    }
 }
 
 impl OAuthState {
@@ -259,9 +590,26 @@ impl OAuthState {
             expires_at: chrono::Utc::now() + chrono::Duration::minutes(10),
             is_linking: false,
             user_id: None,
+            code_verifier: None,
+            code_challenge: None,
+            nonce: None,
         }
     }
 
+    /// Generates a PKCE code verifier and its `S256` challenge.
+    pub fn with_pkce(mut self) -> Self {
+        let verifier = pkce::generate_code_verifier();
+        self.code_challenge = Some(pkce::code_challenge_s256(&verifier));
+        self.code_verifier = Some(verifier);
+        self
+    }
+
+    /// Generates a nonce for providers that issue an `id_token`.
+    pub fn with_nonce(mut self) -> Self {
+        self.nonce = Some(uuid::Uuid::new_v4().simple().to_string());
+        self
+    }
+
     /// Sets the redirect URL.
     pub fn with_redirect(mut self, url: impl Into<String>) -> Self {
         self.redirect_url = Some(url.into());
@@ -284,6 +632,10 @@ impl OAuthState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use better_auth_core::router::Response;
+    use better_auth_core::testing::TestStorage;
+    use better_auth_core::types::AuthFactor;
+    use crate::test_server::serve_once;
 
     #[test]
     fn test_oauth_config_builder() {
@@ -298,6 +650,27 @@ mod tests {
         assert!(!config.auto_create_user);
     }
 
+    #[test]
+    fn test_oauth_config_validation() {
+        let config = OAuthConfig::new().provider(GoogleProvider::new("id", "secret"));
+        assert!(config.validate().is_ok());
+
+        let config = OAuthConfig::new()
+            .provider(GoogleProvider::new("", "secret"))
+            .provider(GitHubProvider::new("id", " "));
+        let Err(AuthError::InvalidPluginConfig { issues }) = config.validate() else {
+            panic!("expected config issues");
+        };
+        let messages: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "provider 'github' has a blank client secret",
+                "provider 'google' has a blank client id",
+            ]
+        );
+    }
+
     #[test]
     fn test_oauth_state() {
         let state = OAuthState::new("google")
@@ -312,6 +685,289 @@ mod tests {
         assert!(!state.is_linking);
     }
 
+    #[tokio::test]
+    async fn test_oauth_state_pkce() {
+        let state = OAuthState::new("google");
+        assert!(state.code_verifier.is_none());
+
+        let state = state.with_pkce();
+        let verifier = state.code_verifier.clone().unwrap();
+        assert_eq!(state.code_challenge, Some(pkce::code_challenge_s256(&verifier)));
+        assert!(!format!("{:?}", state).contains(&verifier));
+
+        // The verifier survives the state store.
+        let store = InMemoryOAuthStateStore::new();
+        store.store(&state).await.unwrap();
+        let stored = store.take(&state.state).await.unwrap().unwrap();
+        assert_eq!(stored.code_verifier, Some(verifier));
+    }
+
+    async fn signin_location(plugin: &OAuthPlugin, provider: &str) -> String {
+        use better_auth_core::router::{Method, Request};
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router
+            .routes()
+            .find(|r| r.path == "/oauth/signin/:provider")
+            .unwrap();
+        let mut req = Request::new(Method::GET, "/oauth/signin/google");
+        req.params.insert("provider".to_string(), provider.to_string());
+        let response = route.handler.handle(req).await;
+        assert_eq!(response.status, 302);
+        response.headers["location"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_signin_uses_pkce() {
+        let plugin = OAuthPlugin::new(
+            OAuthConfig::new()
+                .provider(GoogleProvider::new("id", "secret"))
+                .provider(DiscordProvider::new("id", "secret")),
+            Arc::new(InMemoryOAuthStateStore::new()),
+        );
+
+        let location = signin_location(&plugin, "google").await;
+        assert!(location.contains("&code_challenge_method=S256"));
+        let state = location.split("state=").nth(1).unwrap().split('&').next().unwrap();
+        let stored = plugin.state_store().take(state).await.unwrap().unwrap();
+        let challenge = stored.code_challenge.unwrap();
+        assert!(location.contains(&format!("&code_challenge={}", challenge)));
+        assert_eq!(pkce::code_challenge_s256(&stored.code_verifier.unwrap()), challenge);
+
+        // Providers without PKCE support are unaffected.
+        assert!(!signin_location(&plugin, "discord").await.contains("code_challenge"));
+    }
+
+    #[tokio::test]
+    async fn test_pkce_can_be_disabled() {
+        let plugin = OAuthPlugin::new(
+            OAuthConfig::new()
+                .pkce(false)
+                .provider(GoogleProvider::new("id", "secret")),
+            Arc::new(InMemoryOAuthStateStore::new()),
+        );
+        assert!(!signin_location(&plugin, "google").await.contains("code_challenge"));
+    }
+
+    #[tokio::test]
+    async fn test_signin_sends_nonce_to_oidc_providers() {
+        let plugin = OAuthPlugin::new(
+            OAuthConfig::new()
+                .provider(GoogleProvider::new("id", "secret"))
+                .provider(DiscordProvider::new("id", "secret")),
+            Arc::new(InMemoryOAuthStateStore::new()),
+        );
+
+        let location = signin_location(&plugin, "google").await;
+        let state = location.split("state=").nth(1).unwrap().split('&').next().unwrap();
+        let nonce = plugin.state_store().take(state).await.unwrap().unwrap().nonce.unwrap();
+        assert!(location.contains(&format!("&nonce={}", nonce)));
+
+        // Pure OAuth2 providers get no nonce.
+        assert!(!signin_location(&plugin, "discord").await.contains("nonce="));
+    }
+
+    #[tokio::test]
+    async fn test_signin_rejects_open_redirects() {
+        use better_auth_core::router::{Method, Request};
+
+        let plugin = OAuthPlugin::new(
+            OAuthConfig::new()
+                .allowed_redirect_origins(["https://app.example.com"])
+                .provider(GoogleProvider::new("id", "secret")),
+            Arc::new(InMemoryOAuthStateStore::new()),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router
+            .routes()
+            .find(|r| r.path == "/oauth/signin/:provider")
+            .unwrap();
+        let signin = |redirect: &str| {
+            let mut req = Request::new(Method::GET, "/oauth/signin/google");
+            req.params.insert("provider".to_string(), "google".to_string());
+            req.query.insert("redirect_url".to_string(), redirect.to_string());
+            route.handler.handle(req)
+        };
+
+        for redirect in ["//evil.com", "https://evil.com"] {
+            let response = signin(redirect).await;
+            assert_eq!(response.status, 400, "{redirect}");
+            assert_eq!(response.body.unwrap()["error"], "invalid_redirect");
+        }
+
+        for redirect in ["https://app.example.com/dashboard", "/dashboard"] {
+            let response = signin(redirect).await;
+            assert_eq!(response.status, 302, "{redirect}");
+            let location = &response.headers["location"];
+            let state = location.split("state=").nth(1).unwrap().split('&').next().unwrap();
+            let stored = plugin.state_store().take(state).await.unwrap().unwrap();
+            assert_eq!(stored.redirect_url.as_deref(), Some(redirect));
+        }
+    }
+
+    /// Runs a callback for a generic OIDC provider whose id_token carries
+    /// the nonce returned by `claim` for the expected one.
+    async fn oidc_callback(claim: impl FnOnce(&str) -> Option<String>) -> Response {
+        use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        use base64::Engine;
+        use better_auth_core::router::{Method, Request};
+
+        let state = OAuthState::new("oidc").with_nonce();
+        let claims = match claim(state.nonce.as_deref().unwrap()) {
+            Some(nonce) => serde_json::json!({ "sub": "123", "nonce": nonce }),
+            None => serde_json::json!({ "sub": "123" }),
+        };
+        let id_token = format!(
+            "{}.{}.sig",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256"}"#),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let (token_url, _) = serve_once("200 OK", move |_| {
+            serde_json::json!({ "access_token": "at", "id_token": id_token }).to_string()
+        })
+        .await;
+
+        let provider = GenericOAuthProvider::builder("oidc")
+            .client_id("id")
+            .client_secret("secret")
+            .auth_url("https://idp.example.com/authorize")
+            .token_url(token_url)
+            .userinfo_url("http://127.0.0.1:1/userinfo")
+            .scope("openid")
+            .build();
+        let plugin = OAuthPlugin::new(
+            OAuthConfig::new().provider(provider),
+            Arc::new(InMemoryOAuthStateStore::new()),
+        );
+        plugin.state_store().store(&state).await.unwrap();
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router
+            .routes()
+            .find(|r| r.method == Method::GET && r.path == "/oauth/callback/:provider")
+            .unwrap();
+        let mut req = Request::new(Method::GET, "/oauth/callback/oidc");
+        req.params.insert("provider".to_string(), "oidc".to_string());
+        req.query.insert("code".to_string(), "code".to_string());
+        req.query.insert("state".to_string(), state.state.clone());
+        route.handler.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_callback_checks_id_token_nonce() {
+        for claim in [None, Some("replayed-nonce")] {
+            let response = oidc_callback(|_| claim.map(str::to_string)).await;
+            assert_eq!(response.status, 400);
+            assert_eq!(response.body.unwrap()["error"], "invalid_state");
+        }
+
+        // With the right nonce the flow moves on to fetching user info,
+        // which fails here because there is no userinfo server.
+        let response = oidc_callback(|expected| Some(expected.to_string())).await;
+        assert_eq!(response.body.unwrap()["error"], "user_info_failed");
+    }
+
+    #[tokio::test]
+    async fn test_callback_times_out_slow_providers() {
+        use crate::test_server::serve_never;
+        use better_auth_core::router::{Method, Request};
+        use std::sync::atomic::Ordering;
+
+        async fn callback(token_url: String, userinfo_url: String) -> Response {
+            let provider = GenericOAuthProvider::builder("slow")
+                .client_id("id")
+                .client_secret("secret")
+                .auth_url("https://idp.example.com/authorize")
+                .token_url(token_url)
+                .userinfo_url(userinfo_url)
+                .build();
+            let plugin = OAuthPlugin::new(
+                OAuthConfig::new()
+                    .provider(provider)
+                    .request_timeout(Duration::from_millis(100)),
+                Arc::new(InMemoryOAuthStateStore::new()),
+            );
+            let state = OAuthState::new("slow");
+            plugin.state_store().store(&state).await.unwrap();
+
+            let mut router = Router::new("/api/auth");
+            plugin.register_routes(&mut router);
+            let route = router
+                .routes()
+                .find(|r| r.method == Method::GET && r.path == "/oauth/callback/:provider")
+                .unwrap();
+            let mut req = Request::new(Method::GET, "/oauth/callback/slow");
+            req.params.insert("provider".to_string(), "slow".to_string());
+            req.query.insert("code".to_string(), "code".to_string());
+            req.query.insert("state".to_string(), state.state.clone());
+            route.handler.handle(req).await
+        }
+
+        // The code is single-use, so a hung token exchange is not retried.
+        let (token_url, exchanges) = serve_never().await;
+        let response = callback(token_url, "http://127.0.0.1:1/userinfo".to_string()).await;
+        assert_eq!(response.status, 504);
+        assert_eq!(response.body.unwrap()["error"], "provider_timeout");
+        assert_eq!(exchanges.load(Ordering::SeqCst), 1);
+
+        // Userinfo requests are retried, twice by default.
+        let (token_url, _) = serve_once("200 OK", |_| r#"{"access_token":"at"}"#.to_string()).await;
+        let (userinfo_url, lookups) = serve_never().await;
+        let response = callback(token_url, userinfo_url).await;
+        assert_eq!(response.status, 504);
+        assert_eq!(response.body.unwrap()["error"], "provider_timeout");
+        assert_eq!(lookups.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_callback_reports_provider_errors() {
+        use better_auth_core::router::{Method, Request};
+
+        let callback = |config: OAuthConfig, error: &'static str| async move {
+            let plugin = OAuthPlugin::new(
+                config.provider(GoogleProvider::new("id", "secret")),
+                Arc::new(InMemoryOAuthStateStore::new()),
+            );
+            let mut router = Router::new("/api/auth");
+            plugin.register_routes(&mut router);
+            let route = router
+                .routes()
+                .find(|r| r.method == Method::GET && r.path == "/oauth/callback/:provider")
+                .unwrap();
+            let mut req = Request::new(Method::GET, "/oauth/callback/google");
+            req.params.insert("provider".to_string(), "google".to_string());
+            req.query.insert("error".to_string(), error.to_string());
+            req.query.insert(
+                "error_description".to_string(),
+                "Try again later".to_string(),
+            );
+            route.handler.handle(req).await
+        };
+
+        let response = callback(OAuthConfig::new(), "access_denied").await;
+        assert_eq!(response.status, 403);
+        assert_eq!(response.body.unwrap()["error"], "access_denied");
+
+        let response = callback(OAuthConfig::new(), "server_error").await;
+        assert_eq!(response.status, 502);
+        let body = response.body.unwrap();
+        assert_eq!(body["error"], "provider_error");
+        assert_eq!(body["code"], "server_error");
+        assert_eq!(body["description"], "Try again later");
+
+        let config =
+            OAuthConfig::new().error_redirect_url("https://app.example.com/login?from=oauth");
+        let response = callback(config, "access_denied").await;
+        assert_eq!(response.status, 302);
+        assert_eq!(
+            response.headers["location"],
+            "https://app.example.com/login?from=oauth&error=access_denied&error_description=Try+again+later"
+        );
+    }
+
     #[test]
     fn test_oauth_state_linking() {
         let state = OAuthState::new("github").for_linking("user_123");
@@ -320,20 +976,489 @@ mod tests {
         assert_eq!(state.user_id, Some("user_123".to_string()));
     }
 
-    #[test]
-    fn test_state_store() {
-        let store = OAuthStateStore::new();
+    #[tokio::test]
+    async fn test_state_store() {
+        let store = InMemoryOAuthStateStore::new();
         let state = OAuthState::new("google");
         let state_key = state.state.clone();
 
-        store.store(&state);
+        store.store(&state).await.unwrap();
 
-        let retrieved = store.take(&state_key);
+        let retrieved = store.take(&state_key).await.unwrap();
         assert!(retrieved.is_some());
         assert_eq!(retrieved.unwrap().provider, "google");
 
         // Should be removed after take
-        let retrieved_again = store.take(&state_key);
+        let retrieved_again = store.take(&state_key).await.unwrap();
         assert!(retrieved_again.is_none());
     }
+
+    #[tokio::test]
+    async fn test_state_store_cleanup() {
+        let store = InMemoryOAuthStateStore::new();
+        let mut expired = OAuthState::new("google");
+        expired.expires_at = chrono::Utc::now() - chrono::Duration::minutes(1);
+        let live = OAuthState::new("github");
+        store.store(&expired).await.unwrap();
+        store.store(&live).await.unwrap();
+
+        store.cleanup_expired().await.unwrap();
+        assert!(store.take(&expired.state).await.unwrap().is_none());
+        assert!(store.take(&live.state).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_plugin_uses_given_state_store() {
+        let store = Arc::new(InMemoryOAuthStateStore::new());
+        let plugin = OAuthPlugin::new(
+            OAuthConfig::new().provider(GoogleProvider::new("id", "secret")),
+            store.clone(),
+        );
+
+        // A state issued through the plugin is visible to another holder of
+        // the same store, as it would be with a shared backend.
+        let location = signin_location(&plugin, "google").await;
+        let state = location.split("state=").nth(1).unwrap().split('&').next().unwrap();
+        assert!(store.take(state).await.unwrap().is_some());
+    }
+
+    /// Sets up a user linked to `providers`, returning the storage, event
+    /// bus, plugin, and a session token.
+    fn unlink_setup(
+        providers: &[&str],
+        password: bool,
+    ) -> (Arc<TestStorage>, Arc<EventBus>, OAuthPlugin, String) {
+        let mut user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        if password {
+            user.set_extension("password_hash", "$argon2id$v=19$hash");
+        }
+        let session = Session::new(user.id.clone());
+        let token = session.token.clone();
+        let accounts = providers
+            .iter()
+            .map(|p| Account::new(user.id.clone(), p.to_string(), format!("{}-123", p)))
+            .collect::<Vec<_>>();
+        let storage = Arc::new(TestStorage::new().with_user(user).with_session(session));
+        storage.rows().accounts.extend(accounts);
+        let bus = Arc::new(EventBus::new());
+        let plugin = OAuthPlugin::new(
+            OAuthConfig::new()
+                .provider(GoogleProvider::new("id", "secret"))
+                .provider(GitHubProvider::new("id", "secret"))
+                .storage(storage.clone())
+                .event_bus(bus.clone()),
+            Arc::new(InMemoryOAuthStateStore::new()),
+        );
+        (storage, bus, plugin, token)
+    }
+
+    async fn unlink(plugin: &OAuthPlugin, token: Option<&str>, provider: &str) -> Response {
+        use better_auth_core::router::{Method, Request};
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router
+            .routes()
+            .find(|r| r.method == Method::POST && r.path == "/oauth/unlink")
+            .unwrap();
+        let mut req = Request::new(Method::POST, "/oauth/unlink");
+        if let Some(token) = token {
+            req.headers
+                .insert("authorization".to_string(), format!("Bearer {}", token));
+        }
+        req.body = Some(serde_json::json!({ "provider": provider }));
+        route.handler.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_unlink_account() {
+        let (storage, bus, plugin, token) = unlink_setup(&["google", "github"], false);
+
+        let response = unlink(&plugin, Some(&token), "github").await;
+        assert_eq!(response.status, 200);
+        let accounts = storage.rows().accounts.clone();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].provider, "google");
+
+        let events = bus.events_of_type("oauth.account_unlinked").await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload["provider"], "github");
+        assert_eq!(events[0].payload["user_id"], "user_1");
+    }
+
+    #[tokio::test]
+    async fn test_unlink_refuses_last_auth_method() {
+        let (storage, bus, plugin, token) = unlink_setup(&["google"], false);
+
+        let response = unlink(&plugin, Some(&token), "google").await;
+        assert_eq!(response.status, 409);
+        assert_eq!(response.body.unwrap()["error"], "last_auth_method");
+        assert_eq!(storage.rows().accounts.len(), 1);
+        assert!(bus.events_of_type("oauth.account_unlinked").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unlink_last_account_with_password() {
+        let (storage, _, plugin, token) = unlink_setup(&["google"], true);
+
+        let response = unlink(&plugin, Some(&token), "google").await;
+        assert_eq!(response.status, 200);
+        assert!(storage.rows().accounts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unlink_last_account_with_passkey() {
+        use better_auth_plugin_passkey::testing::MemoryPasskeys;
+        use better_auth_plugin_passkey::{Passkey, PasskeyStore};
+
+        let (storage, _, _, token) = unlink_setup(&["google"], false);
+        let passkeys = Arc::new(MemoryPasskeys::default());
+        passkeys
+            .create_passkey(&Passkey::new("user_1", "cred_1", "key"))
+            .await
+            .unwrap();
+        let plugin = OAuthPlugin::new(
+            OAuthConfig::new()
+                .provider(GoogleProvider::new("id", "secret"))
+                .storage(storage.clone())
+                .passkeys(passkeys),
+            Arc::new(InMemoryOAuthStateStore::new()),
+        );
+
+        let response = unlink(&plugin, Some(&token), "google").await;
+        assert_eq!(response.status, 200);
+        assert!(storage.rows().accounts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unlink_errors() {
+        let (_, _, plugin, token) = unlink_setup(&["google", "github"], false);
+
+        assert_eq!(unlink(&plugin, None, "google").await.status, 401);
+
+        let response = unlink(&plugin, Some(&token), "discord").await;
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body.unwrap()["error"], "provider_not_found");
+
+        let (_, _, plugin, token) = unlink_setup(&["google"], true);
+        let response = unlink(&plugin, Some(&token), "github").await;
+        assert_eq!(response.status, 404);
+        assert_eq!(response.body.unwrap()["error"], "account_not_found");
+    }
+
+    /// Provider whose refresh grant succeeds only for `good-refresh`.
+    struct RefreshingProvider(reqwest::Client);
+
+    #[async_trait]
+    impl OAuthProvider for RefreshingProvider {
+        fn name(&self) -> &str {
+            "google"
+        }
+        fn client_id(&self) -> &str {
+            "id"
+        }
+        fn client_secret(&self) -> std::borrow::Cow<'_, str> {
+            "secret".into()
+        }
+        fn auth_url(&self, _: &str, _: &[String], _: &str) -> String {
+            unimplemented!()
+        }
+        async fn token_exchange(
+            &self,
+            _: &str,
+            _: &str,
+            _: Option<&str>,
+        ) -> Result<TokenSet, OAuthError> {
+            unimplemented!()
+        }
+        async fn get_user_info(&self, _: &str) -> Result<OAuthUserInfo, OAuthError> {
+            unimplemented!()
+        }
+        async fn refresh_token(&self, refresh_token: &str) -> Result<TokenSet, OAuthError> {
+            if refresh_token != "good-refresh" {
+                return Err(OAuthError::RefreshFailed("invalid_grant".to_string()));
+            }
+            Ok(TokenSet {
+                access_token: "new-access".to_string(),
+                refresh_token: None,
+                expires_in: Some(3600),
+                token_type: "Bearer".to_string(),
+                scope: None,
+                id_token: None,
+            })
+        }
+        fn http_client(&self) -> &reqwest::Client {
+            &self.0
+        }
+    }
+
+    fn refresh_setup(
+        refresh_token: &str,
+    ) -> (Arc<TestStorage>, Arc<EventBus>, OAuthPlugin, AuthContext) {
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        let mut account = Account::new(
+            user.id.clone(),
+            "google".to_string(),
+            "google-123".to_string(),
+        );
+        account.id = "account_1".to_string();
+        account.access_token = Some("old-access".to_string());
+        account.refresh_token = Some(refresh_token.to_string());
+        let storage = Arc::new(TestStorage::new().with_user(user).with_account(account));
+        let bus = Arc::new(EventBus::new());
+        let plugin = OAuthPlugin::new(
+            OAuthConfig::new()
+                .provider(RefreshingProvider(reqwest::Client::new()))
+                .event_bus(bus.clone()),
+            Arc::new(InMemoryOAuthStateStore::new()),
+        );
+        let ctx = AuthContext::new(storage.clone());
+        (storage, bus, plugin, ctx)
+    }
+
+    #[tokio::test]
+    async fn test_refresh_account_tokens() {
+        let (storage, bus, plugin, ctx) = refresh_setup("good-refresh");
+
+        let account = plugin
+            .refresh_account_tokens(&ctx, "account_1")
+            .await
+            .unwrap();
+        assert_eq!(account.access_token.as_deref(), Some("new-access"));
+        // The provider did not rotate the refresh token, so it is kept.
+        assert_eq!(account.refresh_token.as_deref(), Some("good-refresh"));
+        assert!(account.expires_at.unwrap() > chrono::Utc::now());
+
+        let stored = storage.rows().accounts[0].clone();
+        assert_eq!(stored.access_token.as_deref(), Some("new-access"));
+        assert!(
+            bus.events_of_type("oauth.token_refresh_failed")
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_failure_emits_event() {
+        let (storage, bus, plugin, ctx) = refresh_setup("revoked-refresh");
+
+        let result = plugin.refresh_account_tokens(&ctx, "account_1").await;
+        assert!(result.is_err());
+        let stored = storage.rows().accounts[0].clone();
+        assert_eq!(stored.access_token.as_deref(), Some("old-access"));
+
+        let events = bus.events_of_type("oauth.token_refresh_failed").await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload["account_id"], "account_1");
+        assert_eq!(events[0].payload["provider"], "google");
+
+        assert!(matches!(
+            plugin.refresh_account_tokens(&ctx, "missing").await,
+            Err(AuthError::NotFound { .. })
+        ));
+    }
+    fn profile() -> OAuthUserInfo {
+        OAuthUserInfo {
+            id: "google-123".to_string(),
+            email: Some("jane@example.com".to_string()),
+            email_verified: Some(true),
+            name: Some("Jane".to_string()),
+            picture: None,
+            raw: serde_json::Value::Null,
+        }
+    }
+
+    fn tokens() -> TokenSet {
+        TokenSet {
+            access_token: "access".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            expires_in: Some(3600),
+            token_type: "Bearer".to_string(),
+            scope: None,
+            id_token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_creates_then_reuses_user() {
+        let storage = TestStorage::new().transactional();
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+
+        let config = OAuthConfig::new();
+        let (user, session) = routes::sign_in_user(&db, &config, "google", &profile(), &tokens())
+            .await
+            .unwrap();
+        assert!(user.email_verified);
+        assert_eq!(user.name.as_deref(), Some("Jane"));
+        assert!(session.factor_verified_at(AuthFactor::OAuth).is_some());
+
+        let config = config.auto_create_user(false);
+        let (again, session) = routes::sign_in_user(&db, &config, "google", &profile(), &tokens())
+            .await
+            .unwrap();
+        assert_eq!(again.id, user.id);
+        assert!(session.factor_verified_at(AuthFactor::OAuth).is_some());
+
+        let rows = storage.rows();
+        assert_eq!(rows.users.len(), 1);
+        assert_eq!(rows.accounts[0].refresh_token.as_deref(), Some("refresh"));
+        assert_eq!(rows.sessions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_sign_up_leaves_no_user() {
+        let storage = TestStorage::new().transactional().fail_on("create_account");
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+
+        let config = OAuthConfig::new();
+        let result = routes::sign_in_user(&db, &config, "google", &profile(), &tokens()).await;
+        assert!(matches!(result, Err(AuthError::DatabaseError { .. })));
+        assert!(storage.rows().users.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_without_auto_create() {
+        let db: Arc<dyn StorageAdapter> = Arc::new(TestStorage::new().transactional());
+
+        let config = OAuthConfig::new().auto_create_user(false);
+        let result = routes::sign_in_user(&db, &config, "google", &profile(), &tokens()).await;
+        assert!(matches!(result, Err(AuthError::Forbidden { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_stores_profile_and_maps_user() {
+        let storage = TestStorage::new().transactional();
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+        let config = OAuthConfig::new()
+            .store_raw_profile(true)
+            .profile_mapper(|info, user| {
+                let mut user = user.clone();
+                if let Some(locale) = info.raw["locale"].as_str() {
+                    user.set_extension("locale", locale);
+                }
+                user
+            });
+        let profile = |locale: &str| OAuthUserInfo {
+            raw: serde_json::json!({ "sub": "google-123", "locale": locale }),
+            ..profile()
+        };
+        let locale = |user: &User| user.get_extension::<String>("locale");
+
+        let (user, _) = routes::sign_in_user(&db, &config, "google", &profile("fr"), &tokens())
+            .await
+            .unwrap();
+        assert_eq!(locale(&user).as_deref(), Some("fr"));
+        let rows = storage.rows().clone();
+        assert_eq!(locale(&rows.users[0]).as_deref(), Some("fr"));
+        assert_eq!(rows.accounts[0].profile.as_ref().unwrap()["locale"], "fr");
+
+        // Later sign-ins refresh both.
+        let (user, _) = routes::sign_in_user(&db, &config, "google", &profile("de"), &tokens())
+            .await
+            .unwrap();
+        assert_eq!(locale(&user).as_deref(), Some("de"));
+        let rows = storage.rows().clone();
+        assert_eq!(rows.users.len(), 1);
+        assert_eq!(locale(&rows.users[0]).as_deref(), Some("de"));
+        assert_eq!(rows.accounts[0].profile.as_ref().unwrap()["locale"], "de");
+    }
+
+    #[tokio::test]
+    async fn test_raw_profile_is_not_stored_by_default() {
+        let storage = TestStorage::new().transactional();
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+        let profile = OAuthUserInfo {
+            raw: serde_json::json!({ "sub": "google-123", "locale": "fr" }),
+            ..profile()
+        };
+
+        routes::sign_in_user(&db, &OAuthConfig::new(), "google", &profile, &tokens())
+            .await
+            .unwrap();
+        assert!(storage.rows().accounts[0].profile.is_none());
+    }
+
+    /// Storage already holding a password user with the email of
+    /// [`profile`], and a bus to watch for links.
+    fn conflict_setup(strategy: EmailConflictStrategy) -> (TestStorage, OAuthConfig) {
+        let storage = TestStorage::new().transactional();
+        let mut user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        user.set_extension("password_hash", "$argon2id$v=19$hash");
+        storage.rows().users.push(user);
+        let config = OAuthConfig::new()
+            .on_email_conflict(strategy)
+            .event_bus(Arc::new(EventBus::new()));
+        (storage, config)
+    }
+
+    #[tokio::test]
+    async fn test_verified_email_links_existing_user() {
+        let (storage, config) = conflict_setup(EmailConflictStrategy::LinkIfVerified);
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+
+        let (user, session) = routes::sign_in_user(&db, &config, "google", &profile(), &tokens())
+            .await
+            .unwrap();
+        assert_eq!(user.id, "user_1");
+        assert_eq!(session.user_id, "user_1");
+        let rows = storage.rows().clone();
+        assert_eq!(rows.users.len(), 1);
+        assert_eq!(rows.accounts[0].user_id, "user_1");
+        assert_eq!(rows.accounts[0].provider_account_id, "google-123");
+
+        let bus = config.event_bus.unwrap();
+        let events = bus.events_of_type("oauth.account_linked").await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload["user_id"], "user_1");
+        assert_eq!(events[0].payload["provider"], "google");
+    }
+
+    #[tokio::test]
+    async fn test_unverified_email_does_not_link() {
+        for verified in [Some(false), None] {
+            let (storage, config) = conflict_setup(EmailConflictStrategy::LinkIfVerified);
+            let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+            let profile = OAuthUserInfo {
+                email_verified: verified,
+                ..profile()
+            };
+
+            let result = routes::sign_in_user(&db, &config, "google", &profile, &tokens()).await;
+            assert!(
+                matches!(result, Err(AuthError::Conflict { .. })),
+                "{:?}",
+                verified
+            );
+            let rows = storage.rows().clone();
+            assert_eq!(rows.users.len(), 1);
+            assert!(rows.accounts.is_empty());
+            assert!(rows.sessions.is_empty());
+            let bus = config.event_bus.unwrap();
+            assert!(bus.events_of_type("oauth.account_linked").await.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_email_conflict_reject_and_create_separate() {
+        let (storage, config) = conflict_setup(EmailConflictStrategy::AlwaysReject);
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+        let result = routes::sign_in_user(&db, &config, "google", &profile(), &tokens()).await;
+        let Err(err @ AuthError::Conflict { .. }) = result else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(err.status_code(), 409);
+        assert!(storage.rows().accounts.is_empty());
+
+        let (storage, config) = conflict_setup(EmailConflictStrategy::CreateSeparate);
+        let db: Arc<dyn StorageAdapter> = Arc::new(storage.clone());
+        let (user, _) = routes::sign_in_user(&db, &config, "google", &profile(), &tokens())
+            .await
+            .unwrap();
+        assert_ne!(user.id, "user_1");
+        let rows = storage.rows().clone();
+        assert_eq!(rows.users.len(), 2);
+        assert_eq!(rows.accounts[0].user_id, user.id);
+        let bus = config.event_bus.unwrap();
+        assert!(bus.events_of_type("oauth.account_linked").await.is_empty());
+    }
 }
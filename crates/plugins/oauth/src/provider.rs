@@ -1,12 +1,45 @@
 //! OAuth provider trait and implementations.
 
+use crate::discovery::OidcDiscovery;
+use crate::OAuthState;
 use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use better_auth_core::redact::{redact, Redact};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+/// How long the default HTTP client waits to connect to a provider.
+pub const DEFAULT_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How long the default HTTP client waits for a provider's response data.
+pub const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Builds the HTTP client providers use unless they are given one.
+///
+/// It has [`DEFAULT_CONNECT_TIMEOUT`] and [`DEFAULT_READ_TIMEOUT`] set, so
+/// a slow provider fails the sign-in instead of holding the request open.
+/// To share one client (and its connection pool) between providers, or to
+/// set up proxies and other timeouts, build a [`Client`] and pass it to
+/// the providers' `with_client` constructors.
+pub fn default_http_client() -> Client {
+    Client::builder()
+        .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+        .read_timeout(DEFAULT_READ_TIMEOUT)
+        .build()
+        .expect("TLS backend initializes")
+}
 
 /// Token set returned from OAuth token exchange.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The `Debug` output masks all tokens.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TokenSet {
     /// The access token.
     pub access_token: String,
@@ -22,6 +55,19 @@ pub struct TokenSet {
     pub id_token: Option<String>,
 }
 
+impl std::fmt::Debug for TokenSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenSet")
+            .field("access_token", &Redact(&self.access_token))
+            .field("refresh_token", &self.refresh_token.as_ref().map(Redact))
+            .field("expires_in", &self.expires_in)
+            .field("token_type", &self.token_type)
+            .field("scope", &self.scope)
+            .field("id_token", &self.id_token.as_ref().map(Redact))
+            .finish()
+    }
+}
+
 /// User information from OAuth provider.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuthUserInfo {
@@ -55,11 +101,70 @@ pub enum OAuthError {
     HttpError(String),
     #[error("Missing required field: {0}")]
     MissingField(String),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("OIDC discovery failed: {0}")]
+    DiscoveryFailed(String),
+    #[error("Token refresh is not supported by {0}")]
+    RefreshNotSupported(String),
+    #[error("Token refresh failed: {0}")]
+    RefreshFailed(String),
+    /// The user declined the authorization request, or cancelled it.
+    #[error("Access denied")]
+    AccessDenied,
+    /// The provider redirected back with an error other than
+    /// `access_denied`.
+    #[error("Provider error {code}: {}", description.as_deref().unwrap_or("no description"))]
+    ProviderError {
+        code: String,
+        description: Option<String>,
+    },
+    /// A request to the provider did not finish in time.
+    #[error("{0} timed out")]
+    Timeout(String),
+}
+
+impl OAuthError {
+    /// Maps the `error` and `error_description` parameters of an
+    /// authorization callback (RFC 6749, section 4.1.2.1).
+    pub fn from_callback(code: &str, description: Option<&str>) -> Self {
+        match code {
+            "access_denied" => OAuthError::AccessDenied,
+            _ => OAuthError::ProviderError {
+                code: code.to_string(),
+                description: description.map(String::from),
+            },
+        }
+    }
+
+    /// Returns the HTTP status to answer with when this error ends a flow.
+    ///
+    /// A denied request is the user's choice, so it is 403. Errors the
+    /// provider reports are 502, or 503 when it is temporarily unavailable.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            OAuthError::AccessDenied => 403,
+            OAuthError::InvalidState => 400,
+            OAuthError::ProviderError { code, .. } if code == "temporarily_unavailable" => 503,
+            OAuthError::ProviderError { .. } => 502,
+            OAuthError::Timeout(_) => 504,
+            _ => 500,
+        }
+    }
+
+    /// Returns whether the request may succeed if sent again: timeouts and
+    /// transport errors, not answers from the provider.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, OAuthError::Timeout(_) | OAuthError::HttpError(_))
+    }
 }
 
 impl From<reqwest::Error> for OAuthError {
     fn from(err: reqwest::Error) -> Self {
-        OAuthError::HttpError(err.to_string())
+        if err.is_timeout() {
+            return OAuthError::Timeout(redact(&err.to_string()));
+        }
+        OAuthError::HttpError(redact(&err.to_string()))
     }
 }
 
@@ -74,19 +179,106 @@ pub trait OAuthProvider: Send + Sync {
         self.name()
     }
 
+    /// Returns the OAuth client ID.
+    fn client_id(&self) -> &str;
+
+    /// Returns the OAuth client secret. Providers that sign a fresh secret
+    /// for each request, such as Apple, return a newly generated one.
+    fn client_secret(&self) -> Cow<'_, str>;
+
     /// Generates the authorization URL.
     fn auth_url(&self, state: &str, scopes: &[String], redirect_uri: &str) -> String;
 
+    /// Returns true if the provider accepts PKCE (RFC 7636).
+    fn supports_pkce(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the provider issues an OpenID Connect `id_token`.
+    ///
+    /// For these providers a `nonce` is sent with the authorization request
+    /// and checked against the `id_token` on callback.
+    fn issues_id_token(&self) -> bool {
+        false
+    }
+
+    /// Generates the authorization URL, adding an `S256` PKCE challenge
+    /// when one is given.
+    fn auth_url_with_pkce(
+        &self,
+        state: &str,
+        scopes: &[String],
+        redirect_uri: &str,
+        code_challenge: Option<&str>,
+    ) -> String {
+        let mut url = self.auth_url(state, scopes, redirect_uri);
+        if let Some(challenge) = code_challenge {
+            url.push_str(&format!(
+                "&code_challenge={}&code_challenge_method=S256",
+                urlencoding::encode(challenge)
+            ));
+        }
+        url
+    }
+
+    /// Generates the authorization URL for a flow, with the PKCE challenge
+    /// and OIDC nonce stored in `state`.
+    fn authorization_url(
+        &self,
+        state: &OAuthState,
+        scopes: &[String],
+        redirect_uri: &str,
+    ) -> String {
+        let mut url = self.auth_url_with_pkce(
+            &state.state,
+            scopes,
+            redirect_uri,
+            state.code_challenge.as_deref(),
+        );
+        if let Some(nonce) = &state.nonce {
+            url.push_str(&format!("&nonce={}", urlencoding::encode(nonce)));
+        }
+        url
+    }
+
     /// Exchanges the authorization code for tokens.
+    ///
+    /// `code_verifier` is the PKCE verifier stored with the OAuth state, if
+    /// the flow used PKCE.
     async fn token_exchange(
         &self,
         code: &str,
         redirect_uri: &str,
+        code_verifier: Option<&str>,
     ) -> Result<TokenSet, OAuthError>;
 
     /// Gets user information using the access token.
     async fn get_user_info(&self, access_token: &str) -> Result<OAuthUserInfo, OAuthError>;
 
+    /// Gets user information after the token exchange.
+    ///
+    /// `callback_user` is the `user` field some providers post to the
+    /// callback alongside the code. By default this calls
+    /// [`get_user_info`](Self::get_user_info) with the access token.
+    async fn get_user_info_from_tokens(
+        &self,
+        tokens: &TokenSet,
+        callback_user: Option<&str>,
+    ) -> Result<OAuthUserInfo, OAuthError> {
+        let _ = callback_user;
+        self.get_user_info(&tokens.access_token).await
+    }
+
+    /// Exchanges a refresh token for a new access token.
+    ///
+    /// The returned `refresh_token` is `None` when the provider keeps the
+    /// old one valid. Providers without refresh tokens return
+    /// [`OAuthError::RefreshNotSupported`].
+    async fn refresh_token(&self, refresh_token: &str) -> Result<TokenSet, OAuthError> {
+        let _ = refresh_token;
+        Err(OAuthError::RefreshNotSupported(self.name().to_string()))
+    }
+
     /// Returns the default scopes for this provider.
     fn default_scopes(&self) -> Vec<String> {
         vec!["email".to_string(), "profile".to_string()]
@@ -96,24 +288,129 @@ pub trait OAuthProvider: Send + Sync {
     fn http_client(&self) -> &Client;
 }
 
+/// Checks that an `id_token`'s `nonce` claim matches the nonce sent in the
+/// authorization request.
+///
+/// Only the payload is read; the token came straight from the provider's
+/// token endpoint over TLS. Any mismatch, including a missing claim, is
+/// [`OAuthError::InvalidState`].
+pub fn verify_id_token_nonce(id_token: &str, expected: &str) -> Result<(), OAuthError> {
+    let claims: serde_json::Value = id_token
+        .split('.')
+        .nth(1)
+        .and_then(|payload| URL_SAFE_NO_PAD.decode(payload).ok())
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or(OAuthError::InvalidState)?;
+
+    match claims.get("nonce").and_then(|n| n.as_str()) {
+        Some(nonce) if nonce == expected => Ok(()),
+        _ => Err(OAuthError::InvalidState),
+    }
+}
+
+/// Response to a `refresh_token` grant (RFC 6749 section 6).
+#[derive(Debug, Deserialize)]
+struct RefreshTokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    #[serde(default = "bearer")]
+    token_type: String,
+    scope: Option<String>,
+    id_token: Option<String>,
+}
+
+fn bearer() -> String {
+    "Bearer".to_string()
+}
+
+/// Performs a `refresh_token` grant against `token_url`.
+///
+/// GitHub reports grant errors with a 200 status, so an `error` field in
+/// the body is treated as a failure too.
+async fn refresh_grant(
+    client: &Client,
+    provider: &str,
+    token_url: &str,
+    client_id: &str,
+    client_secret: &str,
+    refresh_token: &str,
+) -> Result<TokenSet, OAuthError> {
+    let params = [
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", client_id),
+        ("client_secret", client_secret),
+    ];
+
+    let response = client
+        .post(token_url)
+        .header("Accept", "application/json")
+        .form(&params)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.map_err(|e| {
+        OAuthError::RefreshFailed(format!("{}: {}", provider, redact(&e.to_string())))
+    })?;
+    if !status.is_success() || body.get("error").is_some() {
+        return Err(OAuthError::RefreshFailed(format!(
+            "{} token refresh failed: {}",
+            provider,
+            redact(&body.to_string())
+        )));
+    }
+
+    let tokens: RefreshTokenResponse = serde_json::from_value(body)
+        .map_err(|e| OAuthError::RefreshFailed(format!("{}: {}", provider, e)))?;
+    Ok(TokenSet {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        expires_in: tokens.expires_in,
+        token_type: tokens.token_type,
+        scope: tokens.scope,
+        id_token: tokens.id_token,
+    })
+}
+
 // ============================================================================
 // Google OAuth Provider
 // ============================================================================
 
 /// Google OAuth provider.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct GoogleProvider {
     pub client_id: String,
     pub client_secret: String,
     http_client: Client,
 }
 
+impl std::fmt::Debug for GoogleProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GoogleProvider")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &Redact(&self.client_secret))
+            .finish()
+    }
+}
+
 impl GoogleProvider {
+    /// Creates the provider with the [default HTTP client](default_http_client).
     pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self::with_client(client_id, client_secret, default_http_client())
+    }
+
+    /// Creates the provider with the given HTTP client.
+    pub fn with_client(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        http_client: Client,
+    ) -> Self {
         Self {
             client_id: client_id.into(),
             client_secret: client_secret.into(),
-            http_client: Client::new(),
+            http_client,
         }
     }
 
@@ -151,6 +448,22 @@ impl OAuthProvider for GoogleProvider {
         "Google"
     }
 
+    fn supports_pkce(&self) -> bool {
+        true
+    }
+
+    fn issues_id_token(&self) -> bool {
+        true
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn client_secret(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.client_secret)
+    }
+
     fn http_client(&self) -> &Client {
         &self.http_client
     }
@@ -176,6 +489,7 @@ impl OAuthProvider for GoogleProvider {
         &self,
         code: &str,
         redirect_uri: &str,
+        code_verifier: Option<&str>,
     ) -> Result<TokenSet, OAuthError> {
         let mut params = HashMap::new();
         params.insert("code", code);
@@ -183,6 +497,9 @@ impl OAuthProvider for GoogleProvider {
         params.insert("client_secret", &self.client_secret);
         params.insert("redirect_uri", redirect_uri);
         params.insert("grant_type", "authorization_code");
+        if let Some(verifier) = code_verifier {
+            params.insert("code_verifier", verifier);
+        }
 
         let response = self
             .http_client
@@ -192,7 +509,7 @@ impl OAuthProvider for GoogleProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = redact(&response.text().await.unwrap_or_default());
             return Err(OAuthError::TokenExchangeFailed(format!(
                 "Google token exchange failed: {}",
                 error_text
@@ -211,6 +528,18 @@ impl OAuthProvider for GoogleProvider {
         })
     }
 
+    async fn refresh_token(&self, refresh_token: &str) -> Result<TokenSet, OAuthError> {
+        refresh_grant(
+            &self.http_client,
+            "Google",
+            Self::TOKEN_URL,
+            &self.client_id,
+            &self.client_secret,
+            refresh_token,
+        )
+        .await
+    }
+
     async fn get_user_info(&self, access_token: &str) -> Result<OAuthUserInfo, OAuthError> {
         let response = self
             .http_client
@@ -220,7 +549,7 @@ impl OAuthProvider for GoogleProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = redact(&response.text().await.unwrap_or_default());
             return Err(OAuthError::UserInfoFailed(format!(
                 "Google user info failed: {}",
                 error_text
@@ -255,19 +584,38 @@ impl OAuthProvider for GoogleProvider {
 // ============================================================================
 
 /// GitHub OAuth provider.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct GitHubProvider {
     pub client_id: String,
     pub client_secret: String,
     http_client: Client,
 }
 
+impl std::fmt::Debug for GitHubProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitHubProvider")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &Redact(&self.client_secret))
+            .finish()
+    }
+}
+
 impl GitHubProvider {
+    /// Creates the provider with the [default HTTP client](default_http_client).
     pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self::with_client(client_id, client_secret, default_http_client())
+    }
+
+    /// Creates the provider with the given HTTP client.
+    pub fn with_client(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        http_client: Client,
+    ) -> Self {
         Self {
             client_id: client_id.into(),
             client_secret: client_secret.into(),
-            http_client: Client::new(),
+            http_client,
         }
     }
 
@@ -302,6 +650,37 @@ struct GitHubEmail {
     verified: bool,
 }
 
+/// Picks the address to use from a GitHub `/user/emails` response: the
+/// primary if it is verified, else the first verified address, else the
+/// unverified primary.
+fn select_github_email(emails: Vec<GitHubEmail>) -> Option<GitHubEmail> {
+    let rank = |e: &GitHubEmail| match (e.verified, e.primary) {
+        (true, true) => 0,
+        (true, false) => 1,
+        (false, true) => 2,
+        (false, false) => 3,
+    };
+    emails.into_iter().filter(|e| rank(e) < 3).min_by_key(rank)
+}
+
+/// Returns a GitHub user's email and whether it is verified.
+///
+/// Only `/user/emails` says whether an address is verified, so the email is
+/// picked from it with [`select_github_email`]. If the list could not be
+/// fetched, the profile's public email is used with the flag unknown.
+fn github_email(
+    profile_email: Option<String>,
+    emails: Option<Vec<GitHubEmail>>,
+) -> (Option<String>, Option<bool>) {
+    match emails {
+        Some(emails) => match select_github_email(emails) {
+            Some(e) => (Some(e.email), Some(e.verified)),
+            None => (None, None),
+        },
+        None => (profile_email, None),
+    }
+}
+
 #[async_trait]
 impl OAuthProvider for GitHubProvider {
     fn name(&self) -> &str {
@@ -312,6 +691,18 @@ impl OAuthProvider for GitHubProvider {
         "GitHub"
     }
 
+    fn supports_pkce(&self) -> bool {
+        true
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn client_secret(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.client_secret)
+    }
+
     fn http_client(&self) -> &Client {
         &self.http_client
     }
@@ -337,12 +728,16 @@ impl OAuthProvider for GitHubProvider {
         &self,
         code: &str,
         redirect_uri: &str,
+        code_verifier: Option<&str>,
     ) -> Result<TokenSet, OAuthError> {
         let mut params = HashMap::new();
         params.insert("code", code);
         params.insert("client_id", &self.client_id);
         params.insert("client_secret", &self.client_secret);
         params.insert("redirect_uri", redirect_uri);
+        if let Some(verifier) = code_verifier {
+            params.insert("code_verifier", verifier);
+        }
 
         let response = self
             .http_client
@@ -353,7 +748,7 @@ impl OAuthProvider for GitHubProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = redact(&response.text().await.unwrap_or_default());
             return Err(OAuthError::TokenExchangeFailed(format!(
                 "GitHub token exchange failed: {}",
                 error_text
@@ -372,6 +767,18 @@ impl OAuthProvider for GitHubProvider {
         })
     }
 
+    async fn refresh_token(&self, refresh_token: &str) -> Result<TokenSet, OAuthError> {
+        refresh_grant(
+            &self.http_client,
+            "GitHub",
+            Self::TOKEN_URL,
+            &self.client_id,
+            &self.client_secret,
+            refresh_token,
+        )
+        .await
+    }
+
     async fn get_user_info(&self, access_token: &str) -> Result<OAuthUserInfo, OAuthError> {
         // Get user profile
         let response = self
@@ -383,7 +790,7 @@ impl OAuthProvider for GitHubProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = redact(&response.text().await.unwrap_or_default());
             return Err(OAuthError::UserInfoFailed(format!(
                 "GitHub user info failed: {}",
                 error_text
@@ -394,30 +801,21 @@ impl OAuthProvider for GitHubProvider {
         let user_info: GitHubUserInfo = serde_json::from_value(raw.clone())
             .map_err(|e| OAuthError::UserInfoFailed(e.to_string()))?;
 
-        // Get primary email if not in profile
-        let (email, email_verified) = if user_info.email.is_some() {
-            (user_info.email.clone(), Some(true))
+        // The profile's public email carries no verified flag, so the
+        // address is always picked from the email list.
+        let emails_response = self
+            .http_client
+            .get(Self::EMAILS_URL)
+            .header("User-Agent", "better-auth")
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+        let emails = if emails_response.status().is_success() {
+            emails_response.json::<Vec<GitHubEmail>>().await.ok()
         } else {
-            // Fetch emails separately
-            let emails_response = self
-                .http_client
-                .get(Self::EMAILS_URL)
-                .header("User-Agent", "better-auth")
-                .bearer_auth(access_token)
-                .send()
-                .await?;
-
-            if emails_response.status().is_success() {
-                let emails: Vec<GitHubEmail> = emails_response.json().await.unwrap_or_default();
-                let primary_email = emails.into_iter().find(|e| e.primary);
-                match primary_email {
-                    Some(e) => (Some(e.email), Some(e.verified)),
-                    None => (None, None),
-                }
-            } else {
-                (None, None)
-            }
+            None
         };
+        let (email, email_verified) = github_email(user_info.email, emails);
 
         Ok(OAuthUserInfo {
             id: user_info.id.to_string(),
@@ -439,22 +837,53 @@ impl OAuthProvider for GitHubProvider {
 // ============================================================================
 
 /// Discord OAuth provider.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct DiscordProvider {
     pub client_id: String,
     pub client_secret: String,
+    /// Whether to drop emails Discord has not verified, so they are never
+    /// used to create or match users.
+    pub require_verified_email: bool,
     http_client: Client,
 }
 
+impl std::fmt::Debug for DiscordProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiscordProvider")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &Redact(&self.client_secret))
+            .field("require_verified_email", &self.require_verified_email)
+            .finish()
+    }
+}
+
 impl DiscordProvider {
+    /// Creates the provider with the [default HTTP client](default_http_client).
     pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self::with_client(client_id, client_secret, default_http_client())
+    }
+
+    /// Creates the provider with the given HTTP client.
+    pub fn with_client(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        http_client: Client,
+    ) -> Self {
         Self {
             client_id: client_id.into(),
             client_secret: client_secret.into(),
-            http_client: Client::new(),
+            require_verified_email: false,
+            http_client,
         }
     }
 
+    /// Sets whether to drop emails Discord has not verified (off by
+    /// default).
+    pub fn require_verified_email(mut self, require: bool) -> Self {
+        self.require_verified_email = require;
+        self
+    }
+
     const AUTH_URL: &'static str = "https://discord.com/api/oauth2/authorize";
     const TOKEN_URL: &'static str = "https://discord.com/api/oauth2/token";
     const USERINFO_URL: &'static str = "https://discord.com/api/users/@me";
@@ -477,10 +906,57 @@ struct DiscordUserInfo {
     username: String,
     global_name: Option<String>,
     avatar: Option<String>,
-    #[allow(dead_code)]
+    #[serde(default)]
     discriminator: String,
 }
 
+/// Maps a Discord `/users/@me` response.
+///
+/// Discord sends an avatar hash, which is turned into a CDN URL: `.gif`
+/// for animated (`a_`) hashes, `.png` otherwise. Users without an avatar
+/// get the default avatar Discord shows for them. With
+/// `require_verified_email`, an unverified email is left out.
+fn map_discord_user(
+    raw: serde_json::Value,
+    require_verified_email: bool,
+) -> Result<OAuthUserInfo, OAuthError> {
+    let user_info: DiscordUserInfo = serde_json::from_value(raw.clone())
+        .map_err(|e| OAuthError::UserInfoFailed(e.to_string()))?;
+
+    let picture = match &user_info.avatar {
+        Some(hash) => {
+            let extension = if hash.starts_with("a_") { "gif" } else { "png" };
+            format!(
+                "https://cdn.discordapp.com/avatars/{}/{}.{}",
+                user_info.id, hash, extension
+            )
+        }
+        None => {
+            // Users on unique usernames have discriminator "0" and a
+            // default avatar picked from their ID; legacy users by tag.
+            let index = match user_info.discriminator.parse::<u64>() {
+                Ok(discriminator) if discriminator != 0 => discriminator % 5,
+                _ => user_info.id.parse::<u64>().map_or(0, |id| (id >> 22) % 6),
+            };
+            format!("https://cdn.discordapp.com/embed/avatars/{}.png", index)
+        }
+    };
+
+    let email = user_info
+        .email
+        .filter(|_| user_info.verified == Some(true) || !require_verified_email);
+
+    Ok(OAuthUserInfo {
+        id: user_info.id,
+        email,
+        email_verified: user_info.verified,
+        // Use global_name if available, otherwise username
+        name: user_info.global_name.or(Some(user_info.username)),
+        picture: Some(picture),
+        raw,
+    })
+}
+
 #[async_trait]
 impl OAuthProvider for DiscordProvider {
     fn name(&self) -> &str {
@@ -491,6 +967,14 @@ impl OAuthProvider for DiscordProvider {
         "Discord"
     }
 
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn client_secret(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.client_secret)
+    }
+
     fn http_client(&self) -> &Client {
         &self.http_client
     }
@@ -516,6 +1000,7 @@ impl OAuthProvider for DiscordProvider {
         &self,
         code: &str,
         redirect_uri: &str,
+        code_verifier: Option<&str>,
     ) -> Result<TokenSet, OAuthError> {
         let mut params = HashMap::new();
         params.insert("code", code);
@@ -523,6 +1008,9 @@ impl OAuthProvider for DiscordProvider {
         params.insert("client_secret", &self.client_secret);
         params.insert("redirect_uri", redirect_uri);
         params.insert("grant_type", "authorization_code");
+        if let Some(verifier) = code_verifier {
+            params.insert("code_verifier", verifier);
+        }
 
         let response = self
             .http_client
@@ -532,7 +1020,7 @@ impl OAuthProvider for DiscordProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = redact(&response.text().await.unwrap_or_default());
             return Err(OAuthError::TokenExchangeFailed(format!(
                 "Discord token exchange failed: {}",
                 error_text
@@ -551,6 +1039,18 @@ impl OAuthProvider for DiscordProvider {
         })
     }
 
+    async fn refresh_token(&self, refresh_token: &str) -> Result<TokenSet, OAuthError> {
+        refresh_grant(
+            &self.http_client,
+            "Discord",
+            Self::TOKEN_URL,
+            &self.client_id,
+            &self.client_secret,
+            refresh_token,
+        )
+        .await
+    }
+
     async fn get_user_info(&self, access_token: &str) -> Result<OAuthUserInfo, OAuthError> {
         let response = self
             .http_client
@@ -560,7 +1060,7 @@ impl OAuthProvider for DiscordProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = redact(&response.text().await.unwrap_or_default());
             return Err(OAuthError::UserInfoFailed(format!(
                 "Discord user info failed: {}",
                 error_text
@@ -568,30 +1068,7 @@ impl OAuthProvider for DiscordProvider {
         }
 
         let raw: serde_json::Value = response.json().await?;
-        let user_info: DiscordUserInfo = serde_json::from_value(raw.clone())
-            .map_err(|e| OAuthError::UserInfoFailed(e.to_string()))?;
-
-        // Build avatar URL if avatar hash exists
-        let picture = user_info.avatar.map(|hash| {
-            format!(
-                "https://cdn.discordapp.com/avatars/{}/{}.png",
-                user_info.id, hash
-            )
-        });
-
-        // Use global_name if available, otherwise username
-        let name = user_info
-            .global_name
-            .or(Some(user_info.username.clone()));
-
-        Ok(OAuthUserInfo {
-            id: user_info.id,
-            email: user_info.email,
-            email_verified: user_info.verified,
-            name,
-            picture,
-            raw,
-        })
+        map_discord_user(raw, self.require_verified_email)
     }
 
     fn default_scopes(&self) -> Vec<String> {
@@ -600,75 +1077,835 @@ impl OAuthProvider for DiscordProvider {
 }
 
 // ============================================================================
-// Generic OAuth Provider
+// Microsoft OAuth Provider
 // ============================================================================
 
-/// A user info mapper function type.
-pub type UserInfoMapper = Box<dyn Fn(serde_json::Value) -> Result<OAuthUserInfo, OAuthError> + Send + Sync>;
-
-/// A generic OAuth2 provider that can be configured for any OAuth2-compliant service.
-///
-/// This allows users to add custom OAuth providers without implementing the full trait.
+/// Microsoft identity platform (Azure AD / Entra ID) provider.
 ///
-/// # Example
-///
-/// ```rust,ignore
-/// let provider = GenericOAuthProvider::builder("custom")
-///     .display_name("Custom Provider")
-///     .auth_url("https://custom.com/oauth/authorize")
-///     .token_url("https://custom.com/oauth/token")
-///     .userinfo_url("https://custom.com/api/user")
-///     .client_id("your_client_id")
-///     .client_secret("your_client_secret")
-///     .scopes(vec!["email", "profile"])
-///     .build();
-/// ```
-pub struct GenericOAuthProvider {
-    name: String,
-    display_name: String,
-    auth_url: String,
-    token_url: String,
-    userinfo_url: String,
-    client_id: String,
-    client_secret: String,
-    scopes: Vec<String>,
+/// The tenant decides who can sign in: `common` (any work, school, or
+/// personal account, the default), `organizations` (work or school only),
+/// `consumers` (personal only), or a specific tenant ID or domain.
+#[derive(Clone)]
+pub struct MicrosoftProvider {
+    pub client_id: String,
+    pub client_secret: String,
+    pub tenant: String,
     http_client: Client,
-    userinfo_mapper: Option<UserInfoMapper>,
-    /// Additional parameters to include in the auth URL.
-    auth_params: HashMap<String, String>,
-    /// Additional parameters to include in the token request.
-    token_params: HashMap<String, String>,
 }
 
-impl std::fmt::Debug for GenericOAuthProvider {
+impl std::fmt::Debug for MicrosoftProvider {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("GenericOAuthProvider")
-            .field("name", &self.name)
-            .field("display_name", &self.display_name)
-            .field("auth_url", &self.auth_url)
-            .field("token_url", &self.token_url)
-            .field("userinfo_url", &self.userinfo_url)
+        f.debug_struct("MicrosoftProvider")
             .field("client_id", &self.client_id)
-            .field("scopes", &self.scopes)
+            .field("client_secret", &Redact(&self.client_secret))
+            .field("tenant", &self.tenant)
             .finish()
     }
 }
 
-impl GenericOAuthProvider {
-    /// Creates a new builder for a generic OAuth provider.
-    pub fn builder(name: impl Into<String>) -> GenericOAuthProviderBuilder {
-        GenericOAuthProviderBuilder::new(name)
+impl MicrosoftProvider {
+    /// Creates the provider with the [default HTTP client](default_http_client).
+    pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self::with_client(client_id, client_secret, default_http_client())
+    }
+
+    /// Creates the provider with the given HTTP client.
+    pub fn with_client(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        http_client: Client,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            tenant: "common".to_string(),
+            http_client,
+        }
+    }
+
+    /// Sets the tenant: `common`, `organizations`, `consumers`, or a
+    /// tenant ID.
+    pub fn tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = tenant.into();
+        self
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!(
+            "https://login.microsoftonline.com/{}/oauth2/v2.0/{}",
+            urlencoding::encode(&self.tenant),
+            path
+        )
     }
+
+    const USERINFO_URL: &'static str = "https://graph.microsoft.com/v1.0/me";
+}
+
+#[derive(Debug, Deserialize)]
+struct MicrosoftTokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    token_type: String,
+    scope: Option<String>,
+    id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MicrosoftUserInfo {
+    id: String,
+    mail: Option<String>,
+    user_principal_name: Option<String>,
+    display_name: Option<String>,
+}
+
+/// Maps a Microsoft Graph `/me` response.
+///
+/// `mail` is unset for many accounts, so the user principal name (usually
+/// the sign-in address) is used instead. Graph does not report whether the
+/// address is verified.
+fn map_microsoft_user(raw: serde_json::Value) -> Result<OAuthUserInfo, OAuthError> {
+    let user_info: MicrosoftUserInfo = serde_json::from_value(raw.clone())
+        .map_err(|e| OAuthError::UserInfoFailed(e.to_string()))?;
+
+    Ok(OAuthUserInfo {
+        id: user_info.id,
+        email: user_info.mail.or(user_info.user_principal_name),
+        email_verified: None,
+        name: user_info.display_name,
+        picture: None,
+        raw,
+    })
 }
 
 #[async_trait]
-impl OAuthProvider for GenericOAuthProvider {
+impl OAuthProvider for MicrosoftProvider {
     fn name(&self) -> &str {
-        &self.name
+        "microsoft"
     }
 
     fn display_name(&self) -> &str {
-        &self.display_name
+        "Microsoft"
+    }
+
+    fn supports_pkce(&self) -> bool {
+        true
+    }
+
+    fn issues_id_token(&self) -> bool {
+        true
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn client_secret(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.client_secret)
+    }
+
+    fn http_client(&self) -> &Client {
+        &self.http_client
+    }
+
+    fn auth_url(&self, state: &str, scopes: &[String], redirect_uri: &str) -> String {
+        let scopes = if scopes.is_empty() {
+            self.default_scopes().join(" ")
+        } else {
+            scopes.join(" ")
+        };
+
+        format!(
+            "{}?client_id={}&redirect_uri={}&response_type=code&response_mode=query&scope={}&state={}",
+            self.endpoint("authorize"),
+            urlencoding::encode(&self.client_id),
+            urlencoding::encode(redirect_uri),
+            urlencoding::encode(&scopes),
+            urlencoding::encode(state)
+        )
+    }
+
+    async fn token_exchange(
+        &self,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: Option<&str>,
+    ) -> Result<TokenSet, OAuthError> {
+        let mut params = HashMap::new();
+        params.insert("code", code);
+        params.insert("client_id", &self.client_id);
+        params.insert("client_secret", &self.client_secret);
+        params.insert("redirect_uri", redirect_uri);
+        params.insert("grant_type", "authorization_code");
+        if let Some(verifier) = code_verifier {
+            params.insert("code_verifier", verifier);
+        }
+
+        let response = self
+            .http_client
+            .post(self.endpoint("token"))
+            .form(&params)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = redact(&response.text().await.unwrap_or_default());
+            return Err(OAuthError::TokenExchangeFailed(format!(
+                "Microsoft token exchange failed: {}",
+                error_text
+            )));
+        }
+
+        let token_response: MicrosoftTokenResponse = response.json().await?;
+
+        Ok(TokenSet {
+            access_token: token_response.access_token,
+            refresh_token: token_response.refresh_token,
+            expires_in: token_response.expires_in,
+            token_type: token_response.token_type,
+            scope: token_response.scope,
+            id_token: token_response.id_token,
+        })
+    }
+
+    async fn refresh_token(&self, refresh_token: &str) -> Result<TokenSet, OAuthError> {
+        refresh_grant(
+            &self.http_client,
+            "Microsoft",
+            &self.endpoint("token"),
+            &self.client_id,
+            &self.client_secret,
+            refresh_token,
+        )
+        .await
+    }
+
+    async fn get_user_info(&self, access_token: &str) -> Result<OAuthUserInfo, OAuthError> {
+        let response = self
+            .http_client
+            .get(Self::USERINFO_URL)
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = redact(&response.text().await.unwrap_or_default());
+            return Err(OAuthError::UserInfoFailed(format!(
+                "Microsoft user info failed: {}",
+                error_text
+            )));
+        }
+
+        map_microsoft_user(response.json().await?)
+    }
+
+    fn default_scopes(&self) -> Vec<String> {
+        vec![
+            "openid".to_string(),
+            "email".to_string(),
+            "profile".to_string(),
+            "User.Read".to_string(),
+        ]
+    }
+}
+
+// ============================================================================
+// Apple OAuth Provider
+// ============================================================================
+
+/// Sign in with Apple.
+///
+/// Apple differs from other providers in two ways:
+///
+/// - The client secret is a short-lived ES256 JWT signed with the private
+///   key from the Apple developer account. A fresh one is generated for
+///   every token exchange.
+/// - There is no userinfo endpoint. The user's ID and email come from the
+///   `id_token`, and the name is only posted to the callback (as the `user`
+///   form field) on the first authorization. The `id_token` is verified
+///   against Apple's published keys, which are fetched on first use and
+///   again when a token names a key that isn't cached.
+#[derive(Clone)]
+pub struct AppleProvider {
+    pub team_id: String,
+    pub key_id: String,
+    pub client_id: String,
+    private_key_pem: String,
+    signing_key: EncodingKey,
+    http_client: Client,
+    apple_keys: Arc<RwLock<Option<AppleKeys>>>,
+}
+
+/// Apple's `id_token` signing keys, as last fetched.
+struct AppleKeys {
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+impl std::fmt::Debug for AppleProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppleProvider")
+            .field("team_id", &self.team_id)
+            .field("key_id", &self.key_id)
+            .field("client_id", &self.client_id)
+            .field("private_key_pem", &Redact(&self.private_key_pem))
+            .finish()
+    }
+}
+
+impl AppleProvider {
+    /// Creates an Apple provider with the [default HTTP
+    /// client](default_http_client).
+    ///
+    /// `client_id` is the Services ID (or the app's bundle ID for native
+    /// apps), and `private_key_pem` is the `.p8` key downloaded from Apple.
+    pub fn new(
+        team_id: impl Into<String>,
+        key_id: impl Into<String>,
+        client_id: impl Into<String>,
+        private_key_pem: impl Into<String>,
+    ) -> Result<Self, OAuthError> {
+        Self::with_client(
+            team_id,
+            key_id,
+            client_id,
+            private_key_pem,
+            default_http_client(),
+        )
+    }
+
+    /// Creates an Apple provider with the given HTTP client. See
+    /// [`AppleProvider::new`] for the other arguments.
+    pub fn with_client(
+        team_id: impl Into<String>,
+        key_id: impl Into<String>,
+        client_id: impl Into<String>,
+        private_key_pem: impl Into<String>,
+        http_client: Client,
+    ) -> Result<Self, OAuthError> {
+        let private_key_pem = private_key_pem.into();
+        let signing_key = EncodingKey::from_ec_pem(private_key_pem.as_bytes())
+            .map_err(|e| OAuthError::InvalidConfig(format!("invalid Apple private key: {}", e)))?;
+
+        Ok(Self {
+            team_id: team_id.into(),
+            key_id: key_id.into(),
+            client_id: client_id.into(),
+            private_key_pem,
+            signing_key,
+            http_client,
+            apple_keys: Arc::new(RwLock::new(None)),
+        })
+    }
+
+    /// Verifies `id_token`s with `jwks` instead of fetching Apple's keys.
+    /// Tokens signed with other keys still cause a fetch.
+    pub fn jwks(self, jwks: JwkSet) -> Self {
+        *self.apple_keys.write().unwrap() = Some(AppleKeys {
+            jwks,
+            fetched_at: Instant::now(),
+        });
+        self
+    }
+
+    const AUTH_URL: &'static str = "https://appleid.apple.com/auth/authorize";
+    const TOKEN_URL: &'static str = "https://appleid.apple.com/auth/token";
+    const ISSUER: &'static str = "https://appleid.apple.com";
+    const JWKS_URL: &'static str = "https://appleid.apple.com/auth/keys";
+
+    /// How soon Apple's keys may be fetched again for an unknown key ID, so
+    /// forged tokens can't make every sign-in fetch them.
+    const JWKS_REFETCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+    /// Lifetime of generated client secrets. Apple allows up to six months.
+    const CLIENT_SECRET_TTL: chrono::Duration = chrono::Duration::minutes(5);
+
+    /// Generates the client secret JWT for the token endpoint.
+    pub fn client_secret_jwt(&self) -> Result<String, OAuthError> {
+        let now = chrono::Utc::now();
+        let claims = AppleClientSecretClaims {
+            iss: &self.team_id,
+            iat: now.timestamp(),
+            exp: (now + Self::CLIENT_SECRET_TTL).timestamp(),
+            aud: Self::ISSUER,
+            sub: &self.client_id,
+        };
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(self.key_id.clone());
+
+        jsonwebtoken::encode(&header, &claims, &self.signing_key)
+            .map_err(|e| OAuthError::TokenExchangeFailed(format!("Apple client secret: {}", e)))
+    }
+
+    /// Returns the key Apple signs tokens with under `kid`, fetching Apple's
+    /// keys if it isn't cached.
+    async fn apple_key(&self, kid: &str) -> Result<DecodingKey, OAuthError> {
+        let to_key = |jwk: &Jwk| {
+            DecodingKey::from_jwk(jwk)
+                .map_err(|e| OAuthError::UserInfoFailed(format!("invalid Apple key: {}", e)))
+        };
+        let can_fetch = match &*self.apple_keys.read().unwrap() {
+            Some(keys) => match keys.jwks.find(kid) {
+                Some(jwk) => return to_key(jwk),
+                None => keys.fetched_at.elapsed() >= Self::JWKS_REFETCH_INTERVAL,
+            },
+            None => true,
+        };
+        let unknown = || OAuthError::UserInfoFailed(format!("unknown Apple signing key '{}'", kid));
+        if !can_fetch {
+            return Err(unknown());
+        }
+
+        let response = self.http_client.get(Self::JWKS_URL).send().await?;
+        if !response.status().is_success() {
+            return Err(OAuthError::UserInfoFailed(format!(
+                "fetching Apple keys failed: {}",
+                response.status()
+            )));
+        }
+        let jwks: JwkSet = response.json().await?;
+        let key = jwks.find(kid).map(to_key).unwrap_or_else(|| Err(unknown()));
+        *self.apple_keys.write().unwrap() = Some(AppleKeys {
+            jwks,
+            fetched_at: Instant::now(),
+        });
+        key
+    }
+
+    /// Reads the user from an `id_token`, after checking its signature
+    /// against Apple's keys and its issuer, audience, and expiry.
+    async fn decode_id_token(&self, id_token: &str) -> Result<OAuthUserInfo, OAuthError> {
+        let invalid = |e: jsonwebtoken::errors::Error| {
+            OAuthError::UserInfoFailed(format!("invalid Apple id_token: {}", e))
+        };
+        let header = jsonwebtoken::decode_header(id_token).map_err(invalid)?;
+        let kid = header
+            .kid
+            .ok_or_else(|| OAuthError::UserInfoFailed("Apple id_token has no kid".to_string()))?;
+        let key = self.apple_key(&kid).await?;
+
+        // The key's type must match the algorithm, so a token can't pick a
+        // weaker one.
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[Self::ISSUER]);
+        validation.set_audience(&[&self.client_id]);
+
+        let data = jsonwebtoken::decode::<serde_json::Value>(id_token, &key, &validation)
+            .map_err(invalid)?;
+
+        let raw = data.claims;
+        let claims: AppleIdTokenClaims = serde_json::from_value(raw.clone())
+            .map_err(|e| OAuthError::UserInfoFailed(e.to_string()))?;
+
+        Ok(OAuthUserInfo {
+            id: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+            name: None,
+            picture: None,
+            raw,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct AppleClientSecretClaims<'a> {
+    iss: &'a str,
+    iat: i64,
+    exp: i64,
+    aud: &'a str,
+    sub: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppleTokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    token_type: String,
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppleIdTokenClaims {
+    sub: String,
+    email: Option<String>,
+    #[serde(default, deserialize_with = "bool_or_string")]
+    email_verified: Option<bool>,
+}
+
+/// The `user` field Apple posts to the callback on first authorization.
+#[derive(Debug, Deserialize)]
+struct AppleCallbackUser {
+    name: Option<AppleName>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AppleName {
+    first_name: Option<String>,
+    last_name: Option<String>,
+}
+
+/// Apple sends boolean claims as either `true` or `"true"`.
+fn bool_or_string<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrString {
+        Bool(bool),
+        String(String),
+    }
+
+    Ok(match Option::<BoolOrString>::deserialize(deserializer)? {
+        Some(BoolOrString::Bool(b)) => Some(b),
+        Some(BoolOrString::String(s)) => Some(s.eq_ignore_ascii_case("true")),
+        None => None,
+    })
+}
+
+#[async_trait]
+impl OAuthProvider for AppleProvider {
+    fn name(&self) -> &str {
+        "apple"
+    }
+
+    fn display_name(&self) -> &str {
+        "Apple"
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    /// Apple has no static client secret; this signs a fresh one. It is
+    /// empty if signing fails.
+    fn client_secret(&self) -> Cow<'_, str> {
+        Cow::Owned(self.client_secret_jwt().unwrap_or_default())
+    }
+
+    fn http_client(&self) -> &Client {
+        &self.http_client
+    }
+
+    fn auth_url(&self, state: &str, scopes: &[String], redirect_uri: &str) -> String {
+        let scopes = if scopes.is_empty() {
+            self.default_scopes().join(" ")
+        } else {
+            scopes.join(" ")
+        };
+
+        // Apple requires form_post when the name or email scope is requested.
+        format!(
+            "{}?client_id={}&redirect_uri={}&response_type=code&response_mode=form_post&scope={}&state={}",
+            Self::AUTH_URL,
+            urlencoding::encode(&self.client_id),
+            urlencoding::encode(redirect_uri),
+            urlencoding::encode(&scopes),
+            urlencoding::encode(state)
+        )
+    }
+
+    fn issues_id_token(&self) -> bool {
+        true
+    }
+
+    async fn token_exchange(
+        &self,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: Option<&str>,
+    ) -> Result<TokenSet, OAuthError> {
+        let client_secret = self.client_secret_jwt()?;
+
+        let mut params = HashMap::new();
+        params.insert("code", code);
+        params.insert("client_id", &self.client_id);
+        params.insert("client_secret", &client_secret);
+        params.insert("redirect_uri", redirect_uri);
+        params.insert("grant_type", "authorization_code");
+        if let Some(verifier) = code_verifier {
+            params.insert("code_verifier", verifier);
+        }
+
+        let response = self
+            .http_client
+            .post(Self::TOKEN_URL)
+            .form(&params)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = redact(&response.text().await.unwrap_or_default());
+            return Err(OAuthError::TokenExchangeFailed(format!(
+                "Apple token exchange failed: {}",
+                error_text
+            )));
+        }
+
+        let token_response: AppleTokenResponse = response.json().await?;
+
+        Ok(TokenSet {
+            access_token: token_response.access_token,
+            refresh_token: token_response.refresh_token,
+            expires_in: token_response.expires_in,
+            token_type: token_response.token_type,
+            scope: None,
+            id_token: Some(token_response.id_token),
+        })
+    }
+
+    /// Decodes an Apple `id_token`. Apple has no userinfo endpoint, so the
+    /// argument must be the `id_token`, not the access token.
+    async fn get_user_info(&self, id_token: &str) -> Result<OAuthUserInfo, OAuthError> {
+        self.decode_id_token(id_token).await
+    }
+
+    async fn get_user_info_from_tokens(
+        &self,
+        tokens: &TokenSet,
+        callback_user: Option<&str>,
+    ) -> Result<OAuthUserInfo, OAuthError> {
+        let id_token = tokens
+            .id_token
+            .as_deref()
+            .ok_or_else(|| OAuthError::MissingField("id_token".to_string()))?;
+        let mut info = self.decode_id_token(id_token).await?;
+
+        if let Some(name) = callback_user
+            .and_then(|user| serde_json::from_str::<AppleCallbackUser>(user).ok())
+            .and_then(|user| user.name)
+        {
+            let full_name = [name.first_name, name.last_name]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !full_name.is_empty() {
+                info.name = Some(full_name);
+            }
+        }
+
+        Ok(info)
+    }
+
+    fn default_scopes(&self) -> Vec<String> {
+        vec!["name".to_string(), "email".to_string()]
+    }
+}
+
+// ============================================================================
+// Generic OAuth Provider
+// ============================================================================
+
+/// A user info mapper function type.
+pub type UserInfoMapper = Box<dyn Fn(serde_json::Value) -> Result<OAuthUserInfo, OAuthError> + Send + Sync>;
+
+/// Where a [`GenericOAuthProvider`] finds each user field in the userinfo
+/// response.
+///
+/// Fields are JSON pointers (RFC 6901), such as `/sub` or `/data/user/id`. A
+/// field without a leading `/` names a top-level key, so `preferred_username`
+/// and `/preferred_username` are the same. Defaults to the standard OIDC
+/// claims.
+///
+/// ```rust,ignore
+/// let mapping = UserInfoFieldMapping::default()
+///     .id_field("/data/id")
+///     .name_field("preferred_username");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserInfoFieldMapping {
+    /// The user's ID. Required; numbers are converted to strings.
+    pub id_field: String,
+    /// The user's email.
+    pub email_field: String,
+    /// The user's display name.
+    pub name_field: String,
+    /// The user's picture URL.
+    pub picture_field: String,
+    /// Whether the email is verified, as a boolean or `"true"`/`"false"`.
+    pub email_verified_field: String,
+}
+
+impl Default for UserInfoFieldMapping {
+    fn default() -> Self {
+        Self {
+            id_field: "/sub".to_string(),
+            email_field: "/email".to_string(),
+            name_field: "/name".to_string(),
+            picture_field: "/picture".to_string(),
+            email_verified_field: "/email_verified".to_string(),
+        }
+    }
+}
+
+impl UserInfoFieldMapping {
+    /// Sets the path of the user's ID.
+    pub fn id_field(mut self, path: impl Into<String>) -> Self {
+        self.id_field = path.into();
+        self
+    }
+
+    /// Sets the path of the user's email.
+    pub fn email_field(mut self, path: impl Into<String>) -> Self {
+        self.email_field = path.into();
+        self
+    }
+
+    /// Sets the path of the user's display name.
+    pub fn name_field(mut self, path: impl Into<String>) -> Self {
+        self.name_field = path.into();
+        self
+    }
+
+    /// Sets the path of the user's picture URL.
+    pub fn picture_field(mut self, path: impl Into<String>) -> Self {
+        self.picture_field = path.into();
+        self
+    }
+
+    /// Sets the path of the email verified flag.
+    pub fn email_verified_field(mut self, path: impl Into<String>) -> Self {
+        self.email_verified_field = path.into();
+        self
+    }
+
+    /// Extracts user info from a raw userinfo response.
+    pub fn extract(&self, raw: serde_json::Value) -> Result<OAuthUserInfo, OAuthError> {
+        let id = match lookup(&raw, &self.id_field) {
+            Some(serde_json::Value::String(id)) if !id.is_empty() => id.clone(),
+            Some(serde_json::Value::Number(id)) => id.to_string(),
+            _ => return Err(OAuthError::MissingField(self.id_field.clone())),
+        };
+        let string = |path: &str| lookup(&raw, path).and_then(|v| v.as_str()).map(String::from);
+        let email_verified = match lookup(&raw, &self.email_verified_field) {
+            Some(serde_json::Value::Bool(verified)) => Some(*verified),
+            Some(serde_json::Value::String(verified)) => verified.parse().ok(),
+            _ => None,
+        };
+
+        Ok(OAuthUserInfo {
+            id,
+            email: string(&self.email_field),
+            email_verified,
+            name: string(&self.name_field),
+            picture: string(&self.picture_field),
+            raw,
+        })
+    }
+}
+
+/// Resolves a JSON pointer, or a top-level key if `path` has no leading `/`.
+fn lookup<'a>(raw: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    if path.starts_with('/') {
+        raw.pointer(path)
+    } else {
+        raw.get(path)
+    }
+    .filter(|v| !v.is_null())
+}
+
+/// A generic OAuth2 provider that can be configured for any OAuth2-compliant service.
+///
+/// This allows users to add custom OAuth providers without implementing the full trait.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let provider = GenericOAuthProvider::builder("custom")
+///     .display_name("Custom Provider")
+///     .auth_url("https://custom.com/oauth/authorize")
+///     .token_url("https://custom.com/oauth/token")
+///     .userinfo_url("https://custom.com/api/user")
+///     .client_id("your_client_id")
+///     .client_secret("your_client_secret")
+///     .scopes(vec!["email", "profile"])
+///     .build();
+/// ```
+pub struct GenericOAuthProvider {
+    name: String,
+    display_name: String,
+    auth_url: String,
+    token_url: String,
+    userinfo_url: String,
+    client_id: String,
+    client_secret: String,
+    scopes: Vec<String>,
+    http_client: Client,
+    userinfo_mapper: Option<UserInfoMapper>,
+    /// Where to find user fields in the userinfo response.
+    field_mapping: Option<UserInfoFieldMapping>,
+    /// Additional parameters to include in the auth URL.
+    auth_params: HashMap<String, String>,
+    /// Additional parameters to include in the token request.
+    token_params: HashMap<String, String>,
+    /// Whether the provider accepts PKCE.
+    pkce: bool,
+    /// Discovery document, if the provider was configured from an issuer.
+    discovery: Option<OidcDiscovery>,
+}
+
+impl std::fmt::Debug for GenericOAuthProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GenericOAuthProvider")
+            .field("name", &self.name)
+            .field("display_name", &self.display_name)
+            .field("auth_url", &self.auth_url)
+            .field("token_url", &self.token_url)
+            .field("userinfo_url", &self.userinfo_url)
+            .field("client_id", &self.client_id)
+            .field("scopes", &self.scopes)
+            .field("field_mapping", &self.field_mapping)
+            .field("issuer", &self.discovery.as_ref().map(|d| &d.issuer))
+            .finish()
+    }
+}
+
+impl GenericOAuthProvider {
+    /// Creates a new builder for a generic OAuth provider.
+    pub fn builder(name: impl Into<String>) -> GenericOAuthProviderBuilder {
+        GenericOAuthProviderBuilder::new(name)
+    }
+
+    /// Returns the OIDC discovery document the provider was built from.
+    pub fn discovery(&self) -> Option<&OidcDiscovery> {
+        self.discovery.as_ref()
+    }
+
+    /// Returns the issuer's JWKS URL, if known from discovery.
+    pub fn jwks_uri(&self) -> Option<&str> {
+        self.discovery.as_ref()?.jwks_uri.as_deref()
+    }
+}
+
+#[async_trait]
+impl OAuthProvider for GenericOAuthProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    fn supports_pkce(&self) -> bool {
+        self.pkce
+    }
+
+    fn issues_id_token(&self) -> bool {
+        self.scopes.iter().any(|s| s == "openid")
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn client_secret(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.client_secret)
     }
 
     fn http_client(&self) -> &Client {
@@ -703,6 +1940,7 @@ impl OAuthProvider for GenericOAuthProvider {
         &self,
         code: &str,
         redirect_uri: &str,
+        code_verifier: Option<&str>,
     ) -> Result<TokenSet, OAuthError> {
         let mut params = HashMap::new();
         params.insert("code", code.to_string());
@@ -710,6 +1948,9 @@ impl OAuthProvider for GenericOAuthProvider {
         params.insert("client_secret", self.client_secret.clone());
         params.insert("redirect_uri", redirect_uri.to_string());
         params.insert("grant_type", "authorization_code".to_string());
+        if let Some(verifier) = code_verifier {
+            params.insert("code_verifier", verifier.to_string());
+        }
 
         // Add any additional token parameters
         for (key, value) in &self.token_params {
@@ -725,7 +1966,7 @@ impl OAuthProvider for GenericOAuthProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = redact(&response.text().await.unwrap_or_default());
             return Err(OAuthError::TokenExchangeFailed(format!(
                 "{} token exchange failed: {}",
                 self.display_name, error_text
@@ -759,7 +2000,7 @@ impl OAuthProvider for GenericOAuthProvider {
             .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+            let error_text = redact(&response.text().await.unwrap_or_default());
             return Err(OAuthError::UserInfoFailed(format!(
                 "{} user info failed: {}",
                 self.display_name, error_text
@@ -768,9 +2009,11 @@ impl OAuthProvider for GenericOAuthProvider {
 
         let raw: serde_json::Value = response.json().await?;
 
-        // Use custom mapper if provided, otherwise use default mapping
+        // Use custom mapper or field mapping if provided, otherwise use default mapping
         if let Some(ref mapper) = self.userinfo_mapper {
             mapper(raw)
+        } else if let Some(ref mapping) = self.field_mapping {
+            mapping.extract(raw)
         } else {
             // Default mapping - tries common field names
             Ok(OAuthUserInfo {
@@ -813,8 +2056,12 @@ pub struct GenericOAuthProviderBuilder {
     client_secret: Option<String>,
     scopes: Vec<String>,
     userinfo_mapper: Option<UserInfoMapper>,
+    field_mapping: Option<UserInfoFieldMapping>,
     auth_params: HashMap<String, String>,
     token_params: HashMap<String, String>,
+    pkce: bool,
+    discovery: Option<OidcDiscovery>,
+    http_client: Option<Client>,
 }
 
 impl GenericOAuthProviderBuilder {
@@ -830,8 +2077,12 @@ impl GenericOAuthProviderBuilder {
             client_secret: None,
             scopes: vec!["email".to_string(), "profile".to_string()],
             userinfo_mapper: None,
+            field_mapping: None,
             auth_params: HashMap::new(),
             token_params: HashMap::new(),
+            pkce: false,
+            discovery: None,
+            http_client: None,
         }
     }
 
@@ -892,6 +2143,15 @@ impl GenericOAuthProviderBuilder {
         self
     }
 
+    /// Sets where to find user fields in the userinfo response.
+    ///
+    /// Without a mapping, common field names are tried in turn. A custom
+    /// [`userinfo_mapper`](Self::userinfo_mapper) takes precedence.
+    pub fn field_mapping(mut self, mapping: UserInfoFieldMapping) -> Self {
+        self.field_mapping = Some(mapping);
+        self
+    }
+
     /// Adds an additional parameter to the auth URL.
     pub fn auth_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.auth_params.insert(key.into(), value.into());
@@ -904,6 +2164,59 @@ impl GenericOAuthProviderBuilder {
         self
     }
 
+    /// Sets the HTTP client, for discovery and all provider requests
+    /// (default: [`default_http_client`]).
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Configures the endpoints from an OpenID Connect issuer.
+    ///
+    /// Fetches `{issuer}/.well-known/openid-configuration` once and fills
+    /// in any authorization, token, and userinfo URL not already set. PKCE
+    /// is enabled if the issuer advertises `S256`, and `openid` is added to
+    /// the scopes. The document is kept on the built
+    /// provider, so later requests never refetch it.
+    ///
+    /// ```rust,ignore
+    /// let okta = GenericOAuthProvider::builder("okta")
+    ///     .client_id("...")
+    ///     .client_secret("...")
+    ///     .discover("https://example.okta.com")
+    ///     .await?
+    ///     .build();
+    /// ```
+    pub async fn discover(mut self, issuer: &str) -> Result<Self, OAuthError> {
+        let client = self.http_client.get_or_insert_with(default_http_client);
+        let document = OidcDiscovery::fetch(client, issuer).await?;
+
+        self.auth_url
+            .get_or_insert_with(|| document.authorization_endpoint.clone());
+        self.token_url
+            .get_or_insert_with(|| document.token_endpoint.clone());
+        if self.userinfo_url.is_none() {
+            self.userinfo_url = Some(document.userinfo_endpoint.clone().ok_or_else(|| {
+                OAuthError::DiscoveryFailed(format!(
+                    "{} has no userinfo_endpoint; set userinfo_url explicitly",
+                    document.issuer
+                ))
+            })?);
+        }
+        self.pkce |= document.supports_pkce();
+        if !self.scopes.iter().any(|s| s == "openid") {
+            self.scopes.insert(0, "openid".to_string());
+        }
+        self.discovery = Some(document);
+        Ok(self)
+    }
+
+    /// Sets whether the provider accepts PKCE (off by default).
+    pub fn pkce(mut self, supported: bool) -> Self {
+        self.pkce = supported;
+        self
+    }
+
     /// Builds the GenericOAuthProvider.
     ///
     /// # Panics
@@ -920,10 +2233,13 @@ impl GenericOAuthProviderBuilder {
             client_id: self.client_id.expect("client_id is required"),
             client_secret: self.client_secret.expect("client_secret is required"),
             scopes: self.scopes,
-            http_client: Client::new(),
+            http_client: self.http_client.unwrap_or_else(default_http_client),
             userinfo_mapper: self.userinfo_mapper,
+            field_mapping: self.field_mapping,
             auth_params: self.auth_params,
             token_params: self.token_params,
+            pkce: self.pkce,
+            discovery: self.discovery,
         }
     }
 
@@ -948,10 +2264,13 @@ impl GenericOAuthProviderBuilder {
                 .client_secret
                 .ok_or_else(|| OAuthError::MissingField("client_secret".to_string()))?,
             scopes: self.scopes,
-            http_client: Client::new(),
+            http_client: self.http_client.unwrap_or_else(default_http_client),
             userinfo_mapper: self.userinfo_mapper,
+            field_mapping: self.field_mapping,
             auth_params: self.auth_params,
             token_params: self.token_params,
+            pkce: self.pkce,
+            discovery: self.discovery,
         })
     }
 }
@@ -981,6 +2300,7 @@ mod urlencoding {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_server::serve_once;
 
     #[test]
     fn test_google_auth_url() {
@@ -991,6 +2311,24 @@ mod tests {
         assert!(url.contains("state=test_state"));
     }
 
+    #[test]
+    fn test_debug_masks_secrets() {
+        let provider = GoogleProvider::new("client_id", "very_secret");
+        assert!(!format!("{:?}", provider).contains("very_secret"));
+
+        let tokens = TokenSet {
+            access_token: "ya29.access".to_string(),
+            refresh_token: Some("1//refresh".to_string()),
+            expires_in: Some(3600),
+            token_type: "Bearer".to_string(),
+            scope: None,
+            id_token: None,
+        };
+        let debug = format!("{:?}", tokens);
+        assert!(!debug.contains("ya29.access"));
+        assert!(!debug.contains("1//refresh"));
+    }
+
     #[test]
     fn test_github_auth_url() {
         let provider = GitHubProvider::new("client_id", "client_secret");
@@ -1000,6 +2338,337 @@ mod tests {
         assert!(url.contains("state=test_state"));
     }
 
+    #[test]
+    fn test_auth_url_with_pkce() {
+        let provider = GoogleProvider::new("client_id", "client_secret");
+        assert!(provider.supports_pkce());
+        let url = provider.auth_url_with_pkce(
+            "test_state",
+            &[],
+            "http://localhost/callback",
+            Some("E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"),
+        );
+        assert!(url.contains("&code_challenge=E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"));
+        assert!(url.contains("&code_challenge_method=S256"));
+
+        let url = provider.auth_url_with_pkce("test_state", &[], "http://localhost/callback", None);
+        assert!(!url.contains("code_challenge"));
+        assert!(!DiscordProvider::new("id", "secret").supports_pkce());
+    }
+
+    #[test]
+    fn test_microsoft_auth_url() {
+        let provider = MicrosoftProvider::new("client_id", "client_secret");
+        let url = provider.auth_url("test_state", &[], "http://localhost/callback");
+        assert!(url.starts_with("https://login.microsoftonline.com/common/oauth2/v2.0/authorize?"));
+        assert!(url.contains("scope=openid%20email%20profile%20User.Read"));
+
+        let tenant = "72f988bf-86f1-41af-91ab-2d7cd011db47";
+        let provider = provider.tenant(tenant);
+        let url = provider.auth_url("test_state", &[], "http://localhost/callback");
+        assert!(url.starts_with(&format!(
+            "https://login.microsoftonline.com/{}/oauth2/v2.0/authorize?",
+            tenant
+        )));
+        assert_eq!(
+            provider.endpoint("token"),
+            format!("https://login.microsoftonline.com/{}/oauth2/v2.0/token", tenant)
+        );
+    }
+
+    #[test]
+    fn test_microsoft_user_mapping() {
+        let info = map_microsoft_user(serde_json::json!({
+            "id": "87d349ed-44d7-43e1-9a83-5f2406dee5bd",
+            "displayName": "Adele Vance",
+            "mail": "AdeleV@contoso.com",
+            "userPrincipalName": "adelev@contoso.onmicrosoft.com",
+        }))
+        .unwrap();
+        assert_eq!(info.id, "87d349ed-44d7-43e1-9a83-5f2406dee5bd");
+        assert_eq!(info.email.as_deref(), Some("AdeleV@contoso.com"));
+        assert_eq!(info.name.as_deref(), Some("Adele Vance"));
+
+        // Falls back to the UPN when mail is not set.
+        let info = map_microsoft_user(serde_json::json!({
+            "id": "1",
+            "displayName": null,
+            "mail": null,
+            "userPrincipalName": "adelev@contoso.onmicrosoft.com",
+        }))
+        .unwrap();
+        assert_eq!(info.email.as_deref(), Some("adelev@contoso.onmicrosoft.com"));
+    }
+
+    fn github_emails(emails: &[(&str, bool, bool)]) -> Vec<GitHubEmail> {
+        emails
+            .iter()
+            .map(|&(email, primary, verified)| GitHubEmail {
+                email: email.to_string(),
+                primary,
+                verified,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_github_email_selection() {
+        let selected = |emails| select_github_email(github_emails(emails)).map(|e| e.email);
+
+        // A verified primary wins.
+        let emails = [
+            ("old@example.com", false, true),
+            ("jane@example.com", true, true),
+        ];
+        assert_eq!(selected(&emails).as_deref(), Some("jane@example.com"));
+
+        // An unverified primary loses to the first verified address.
+        let emails = [
+            ("new@example.com", true, false),
+            ("unverified@example.com", false, false),
+            ("jane@example.com", false, true),
+            ("work@example.com", false, true),
+        ];
+        let email = select_github_email(github_emails(&emails)).unwrap();
+        assert_eq!(email.email, "jane@example.com");
+        assert!(email.verified);
+
+        // With nothing verified, the primary is kept, flagged unverified.
+        let emails = [
+            ("other@example.com", false, false),
+            ("new@example.com", true, false),
+        ];
+        let email = select_github_email(github_emails(&emails)).unwrap();
+        assert_eq!(email.email, "new@example.com");
+        assert!(!email.verified);
+
+        assert!(selected(&[("other@example.com", false, false)]).is_none());
+        assert!(selected(&[]).is_none());
+    }
+
+    #[test]
+    fn test_github_public_email_is_not_assumed_verified() {
+        let public = Some("jane@example.com".to_string());
+
+        // The public email is used with the list's verified flag.
+        let emails = github_emails(&[("jane@example.com", true, false)]);
+        assert_eq!(
+            github_email(public.clone(), Some(emails)),
+            (Some("jane@example.com".to_string()), Some(false))
+        );
+        let emails = github_emails(&[("jane@example.com", true, true)]);
+        assert_eq!(
+            github_email(public.clone(), Some(emails)),
+            (Some("jane@example.com".to_string()), Some(true))
+        );
+
+        // Without the list, whether it is verified is unknown.
+        assert_eq!(
+            github_email(public, None),
+            (Some("jane@example.com".to_string()), None)
+        );
+    }
+
+    fn discord_user(avatar: Option<&str>, discriminator: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": "80351110224678912",
+            "username": "nelly",
+            "global_name": "Nelly",
+            "discriminator": discriminator,
+            "avatar": avatar,
+            "email": "nelly@discord.com",
+            "verified": true,
+        })
+    }
+
+    #[test]
+    fn test_discord_avatar_urls() {
+        let picture = |raw| map_discord_user(raw, false).unwrap().picture.unwrap();
+        assert_eq!(
+            picture(discord_user(Some("8342729096ea3675442027381ff50dfe"), "0")),
+            "https://cdn.discordapp.com/avatars/80351110224678912/8342729096ea3675442027381ff50dfe.png"
+        );
+        assert_eq!(
+            picture(discord_user(
+                Some("a_d5efa99b3eeaa7dd43acca82f5692432"),
+                "0"
+            )),
+            "https://cdn.discordapp.com/avatars/80351110224678912/a_d5efa99b3eeaa7dd43acca82f5692432.gif"
+        );
+
+        // Without an avatar: by ID for unique usernames, by tag for legacy ones.
+        assert_eq!(
+            picture(discord_user(None, "0")),
+            "https://cdn.discordapp.com/embed/avatars/5.png"
+        );
+        assert_eq!(
+            picture(discord_user(None, "1337")),
+            "https://cdn.discordapp.com/embed/avatars/2.png"
+        );
+    }
+
+    #[test]
+    fn test_discord_email_verification() {
+        let info = map_discord_user(discord_user(None, "0"), true).unwrap();
+        assert_eq!(info.email.as_deref(), Some("nelly@discord.com"));
+        assert_eq!(info.email_verified, Some(true));
+        assert_eq!(info.name.as_deref(), Some("Nelly"));
+
+        let mut raw = discord_user(None, "0");
+        raw["verified"] = serde_json::json!(false);
+        let info = map_discord_user(raw.clone(), false).unwrap();
+        assert_eq!(info.email.as_deref(), Some("nelly@discord.com"));
+        assert_eq!(info.email_verified, Some(false));
+
+        let info = map_discord_user(raw, true).unwrap();
+        assert!(info.email.is_none());
+        assert_eq!(info.email_verified, Some(false));
+    }
+
+    fn apple() -> AppleProvider {
+        AppleProvider::new(
+            "TEAM123456",
+            "KEY1234567",
+            "com.example.web",
+            include_str!("../testdata/apple_private.pem"),
+        )
+        .unwrap()
+        .jwks(serde_json::from_str(include_str!("../testdata/apple_jwks.json")).unwrap())
+    }
+
+    /// Signs an id_token as Apple would (with our test key standing in).
+    fn apple_id_token(claims: serde_json::Value) -> String {
+        let key = EncodingKey::from_ec_pem(include_bytes!("../testdata/apple_private.pem")).unwrap();
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some("TESTKEY".to_string());
+        jsonwebtoken::encode(&header, &claims, &key).unwrap()
+    }
+
+    #[test]
+    fn test_apple_client_secret_jwt() {
+        let provider = apple();
+        let secret = provider.client_secret_jwt().unwrap();
+
+        let header = jsonwebtoken::decode_header(&secret).unwrap();
+        assert_eq!(header.alg, Algorithm::ES256);
+        assert_eq!(header.kid.as_deref(), Some("KEY1234567"));
+
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.set_issuer(&["TEAM123456"]);
+        validation.set_audience(&["https://appleid.apple.com"]);
+        let public_key =
+            DecodingKey::from_ec_pem(include_bytes!("../testdata/apple_public.pem")).unwrap();
+        let claims = jsonwebtoken::decode::<serde_json::Value>(&secret, &public_key, &validation)
+            .unwrap()
+            .claims;
+        assert_eq!(claims["sub"], "com.example.web");
+        assert!(claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap() <= 300);
+
+        // The trait's client secret is a signed JWT too, never the key.
+        let secret = provider.client_secret();
+        assert!(!secret.contains("PRIVATE KEY"));
+        assert!(
+            jsonwebtoken::decode::<serde_json::Value>(&secret, &public_key, &validation).is_ok()
+        );
+
+        assert!(!format!("{:?}", provider).contains("PRIVATE KEY"));
+        assert!(AppleProvider::new("T", "K", "C", "not a key").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apple_user_info_from_id_token() {
+        let provider = apple();
+        let exp = chrono::Utc::now().timestamp() + 600;
+        let tokens = TokenSet {
+            access_token: "opaque".to_string(),
+            refresh_token: None,
+            expires_in: Some(3600),
+            token_type: "Bearer".to_string(),
+            scope: None,
+            id_token: Some(apple_id_token(serde_json::json!({
+                "iss": "https://appleid.apple.com",
+                "aud": "com.example.web",
+                "exp": exp,
+                "sub": "001234.abcd",
+                "email": "jane@privaterelay.appleid.com",
+                "email_verified": "true",
+            }))),
+        };
+
+        // The name only arrives in the first callback's form post.
+        let user = r#"{"name":{"firstName":"Jane","lastName":"Doe"},"email":"jane@privaterelay.appleid.com"}"#;
+        let info = provider.get_user_info_from_tokens(&tokens, Some(user)).await.unwrap();
+        assert_eq!(info.id, "001234.abcd");
+        assert_eq!(info.email.as_deref(), Some("jane@privaterelay.appleid.com"));
+        assert_eq!(info.email_verified, Some(true));
+        assert_eq!(info.name.as_deref(), Some("Jane Doe"));
+
+        let info = provider.get_user_info_from_tokens(&tokens, None).await.unwrap();
+        assert_eq!(info.name, None);
+
+        // Boolean form of email_verified.
+        let id_token = apple_id_token(serde_json::json!({
+            "iss": "https://appleid.apple.com",
+            "aud": "com.example.web",
+            "exp": exp,
+            "sub": "001234.abcd",
+            "email_verified": false,
+        }));
+        let info = provider.get_user_info(&id_token).await.unwrap();
+        assert_eq!(info.email_verified, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_apple_rejects_foreign_id_token() {
+        let id_token = apple_id_token(serde_json::json!({
+            "iss": "https://appleid.apple.com",
+            "aud": "com.other.app",
+            "exp": chrono::Utc::now().timestamp() + 600,
+            "sub": "001234.abcd",
+        }));
+        assert!(matches!(
+            apple().get_user_info(&id_token).await,
+            Err(OAuthError::UserInfoFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_apple_rejects_unverified_id_token() {
+        let claims = serde_json::json!({
+            "iss": "https://appleid.apple.com",
+            "aud": "com.example.web",
+            "exp": chrono::Utc::now().timestamp() + 600,
+            "sub": "001234.abcd",
+        });
+
+        // Forged signature.
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("TESTKEY".to_string());
+        let forged =
+            jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(b"guess")).unwrap();
+        assert!(apple().get_user_info(&forged).await.is_err());
+
+        // Another token's signature.
+        let token = apple_id_token(claims.clone());
+        let other = apple_id_token(serde_json::json!({ "sub": "someone.else" }));
+        let (body, _) = token.rsplit_once('.').unwrap();
+        let (_, signature) = other.rsplit_once('.').unwrap();
+        let spliced = format!("{}.{}", body, signature);
+        assert!(apple().get_user_info(&spliced).await.is_err());
+
+        // A key Apple never published. The keys were just loaded, so this
+        // fails without fetching them again.
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some("OTHERKEY".to_string());
+        let key =
+            EncodingKey::from_ec_pem(include_bytes!("../testdata/apple_private.pem")).unwrap();
+        let unknown = jsonwebtoken::encode(&header, &claims, &key).unwrap();
+        assert!(matches!(
+            apple().get_user_info(&unknown).await,
+            Err(OAuthError::UserInfoFailed(message)) if message.contains("OTHERKEY")
+        ));
+    }
+
     #[test]
     fn test_discord_auth_url() {
         let provider = DiscordProvider::new("client_id", "client_secret");
@@ -1008,4 +2677,201 @@ mod tests {
         assert!(url.contains("client_id=client_id"));
         assert!(url.contains("state=test_state"));
     }
+
+    #[tokio::test]
+    async fn test_refresh_grant() {
+        let (url, request) = serve_once("200 OK", |_| {
+            r#"{"access_token":"new-access","expires_in":3599,"scope":"email"}"#.to_string()
+        })
+        .await;
+
+        let tokens = refresh_grant(&Client::new(), "Google", &url, "id", "secret", "1//refresh")
+            .await
+            .unwrap();
+        assert_eq!(tokens.access_token, "new-access");
+        assert_eq!(tokens.token_type, "Bearer");
+        assert_eq!(tokens.expires_in, Some(3599));
+        assert!(tokens.refresh_token.is_none());
+
+        let request = request.await.unwrap();
+        assert!(request.starts_with("POST "));
+        assert!(request.contains("grant_type=refresh_token"));
+        assert!(request.contains("refresh_token=1%2F%2Frefresh"));
+    }
+
+    #[tokio::test]
+    async fn test_provider_uses_given_http_client() {
+        let (token_url, request) = serve_once("200 OK", |_| {
+            r#"{"access_token":"at","token_type":"Bearer"}"#.to_string()
+        })
+        .await;
+        let client = Client::builder()
+            .user_agent("acme-auth/1.0")
+            .build()
+            .unwrap();
+        let provider = GenericOAuthProvider::builder("acme")
+            .client_id("id")
+            .client_secret("secret")
+            .auth_url("https://idp.example.com/authorize")
+            .token_url(token_url)
+            .userinfo_url("https://idp.example.com/userinfo")
+            .http_client(client)
+            .build();
+
+        let tokens = provider
+            .token_exchange("code", "https://app.example.com/callback", None)
+            .await
+            .unwrap();
+        assert_eq!(tokens.access_token, "at");
+        let request = request.await.unwrap().to_lowercase();
+        assert!(request.contains("user-agent: acme-auth/1.0"), "{}", request);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_grant_errors() {
+        // GitHub reports grant errors with a 200 status.
+        let (url, _) =
+            serve_once("200 OK", |_| r#"{"error":"bad_refresh_token"}"#.to_string()).await;
+        let err = refresh_grant(&Client::new(), "GitHub", &url, "id", "secret", "stale")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OAuthError::RefreshFailed(ref m) if m.contains("bad_refresh_token")));
+
+        let (url, _) = serve_once("400 Bad Request", |_| {
+            r#"{"error":"invalid_grant"}"#.to_string()
+        })
+        .await;
+        let err = refresh_grant(&Client::new(), "Google", &url, "id", "secret", "stale")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OAuthError::RefreshFailed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_not_supported_by_default() {
+        let provider = GenericOAuthProvider::builder("custom")
+            .client_id("id")
+            .client_secret("secret")
+            .auth_url("https://example.com/authorize")
+            .token_url("https://example.com/token")
+            .userinfo_url("https://example.com/userinfo")
+            .build();
+        assert!(matches!(
+            provider.refresh_token("refresh").await,
+            Err(OAuthError::RefreshNotSupported(ref name)) if name == "custom"
+        ));
+    }
+
+    fn id_token(claims: serde_json::Value) -> String {
+        format!(
+            "{}.{}.sig",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256"}"#),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        )
+    }
+
+    #[test]
+    fn test_verify_id_token_nonce() {
+        let token = id_token(serde_json::json!({ "sub": "1", "nonce": "n-0S6_WzA2Mj" }));
+        assert!(verify_id_token_nonce(&token, "n-0S6_WzA2Mj").is_ok());
+        assert!(matches!(
+            verify_id_token_nonce(&token, "other"),
+            Err(OAuthError::InvalidState)
+        ));
+
+        let without_nonce = id_token(serde_json::json!({ "sub": "1" }));
+        assert!(verify_id_token_nonce(&without_nonce, "n-0S6_WzA2Mj").is_err());
+        assert!(verify_id_token_nonce("not-a-jwt", "n-0S6_WzA2Mj").is_err());
+    }
+
+    #[test]
+    fn test_authorization_url_includes_nonce() {
+        let provider = GoogleProvider::new("client_id", "client_secret");
+        assert!(provider.issues_id_token());
+        let state = OAuthState::new("google").with_nonce();
+        let url = provider.authorization_url(&state, &[], "http://localhost/callback");
+        assert!(url.contains(&format!("&nonce={}", state.nonce.unwrap())));
+
+        assert!(!GitHubProvider::new("id", "secret").issues_id_token());
+        assert!(!DiscordProvider::new("id", "secret").issues_id_token());
+        assert!(MicrosoftProvider::new("id", "secret").issues_id_token());
+    }
+
+    #[test]
+    fn test_field_mapping_defaults_to_oidc_claims() {
+        let info = UserInfoFieldMapping::default()
+            .extract(serde_json::json!({
+                "sub": "248289761001",
+                "id": "ignored",
+                "email": "jane@example.com",
+                "email_verified": true,
+                "name": "Jane Doe",
+                "picture": "https://example.com/jane.jpg"
+            }))
+            .unwrap();
+        assert_eq!(info.id, "248289761001");
+        assert_eq!(info.email.as_deref(), Some("jane@example.com"));
+        assert_eq!(info.email_verified, Some(true));
+        assert_eq!(info.name.as_deref(), Some("Jane Doe"));
+        assert_eq!(info.picture.as_deref(), Some("https://example.com/jane.jpg"));
+
+        assert!(matches!(
+            UserInfoFieldMapping::default().extract(serde_json::json!({ "id": "1" })),
+            Err(OAuthError::MissingField(ref field)) if field == "/sub"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_generic_provider_field_mapping() {
+        let (url, _) = serve_once("200 OK", |_| {
+            r#"{"data":{"user":{"id":42,"mail":"jane@example.com","verified":"true"}},"preferred_username":"jane"}"#
+                .to_string()
+        })
+        .await;
+        let provider = GenericOAuthProvider::builder("custom")
+            .client_id("id")
+            .client_secret("secret")
+            .auth_url("https://example.com/authorize")
+            .token_url("https://example.com/token")
+            .userinfo_url(url)
+            .field_mapping(
+                UserInfoFieldMapping::default()
+                    .id_field("/data/user/id")
+                    .email_field("/data/user/mail")
+                    .email_verified_field("/data/user/verified")
+                    .name_field("preferred_username")
+                    .picture_field("/data/user/avatar"),
+            )
+            .build();
+
+        let info = provider.get_user_info("access").await.unwrap();
+        assert_eq!(info.id, "42");
+        assert_eq!(info.email.as_deref(), Some("jane@example.com"));
+        assert_eq!(info.email_verified, Some(true));
+        assert_eq!(info.name.as_deref(), Some("jane"));
+        assert!(info.picture.is_none());
+    }
+
+    #[test]
+    fn test_callback_errors() {
+        let denied = OAuthError::from_callback("access_denied", Some("The user cancelled"));
+        assert!(matches!(denied, OAuthError::AccessDenied));
+        assert_eq!(denied.status_code(), 403);
+
+        let err = OAuthError::from_callback("invalid_scope", Some("Unknown scope: admin"));
+        assert!(matches!(
+            err,
+            OAuthError::ProviderError { ref code, ref description }
+                if code == "invalid_scope" && description.as_deref() == Some("Unknown scope: admin")
+        ));
+        assert_eq!(err.status_code(), 502);
+        assert_eq!(
+            err.to_string(),
+            "Provider error invalid_scope: Unknown scope: admin"
+        );
+        assert_eq!(
+            OAuthError::from_callback("temporarily_unavailable", None).status_code(),
+            503
+        );
+    }
 }
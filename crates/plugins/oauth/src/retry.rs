@@ -0,0 +1,121 @@
+//! Timeouts and retries for requests to providers.
+//!
+//! Every request is bounded by [`OAuthConfig::request_timeout`]. Only
+//! idempotent requests, such as fetching user info, are retried; the token
+//! exchange spends a single-use code, so it is never sent twice.
+//!
+//! [`OAuthConfig::request_timeout`]: crate::OAuthConfig::request_timeout
+
+use crate::OAuthError;
+use std::future::Future;
+use std::time::Duration;
+
+/// Upper bound of the delay before the first retry. It doubles for each
+/// retry after that.
+const BASE_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Runs `request`, failing with [`OAuthError::Timeout`] if it takes longer
+/// than `timeout`. `what` names the request in the error.
+pub(crate) async fn with_timeout<T>(
+    what: &str,
+    timeout: Duration,
+    request: impl Future<Output = Result<T, OAuthError>>,
+) -> Result<T, OAuthError> {
+    tokio::time::timeout(timeout, request)
+        .await
+        .unwrap_or_else(|_| Err(OAuthError::Timeout(what.to_string())))
+}
+
+/// Runs an idempotent request with [`with_timeout`], sending it up to
+/// `retries` more times while it fails with a
+/// [transient](OAuthError::is_transient) error.
+pub(crate) async fn with_retries<T, F, Fut>(
+    what: &str,
+    timeout: Duration,
+    retries: u32,
+    mut request: F,
+) -> Result<T, OAuthError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, OAuthError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match with_timeout(what, timeout, request()).await {
+            Err(err) if attempt < retries && err.is_transient() => {
+                tokio::time::sleep(backoff(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Returns a random delay of up to `BASE_BACKOFF * 2^attempt`, so that
+/// callers failing together do not retry together.
+fn backoff(attempt: u32) -> Duration {
+    let max = BASE_BACKOFF.as_millis() as u64 * 2u64.pow(attempt.min(6));
+    let random = uuid::Uuid::new_v4().as_u128() as u64;
+    Duration::from_millis(random % (max + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let calls = AtomicU32::new(0);
+        let result = with_retries("user info", TIMEOUT, 2, || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(OAuthError::HttpError("connection reset".to_string())),
+                1 => {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok("late")
+                }
+                _ => Ok("profile"),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "profile");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retries_are_bounded() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = with_retries("user info", TIMEOUT, 2, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await;
+        let err = result.unwrap_err();
+        assert!(matches!(&err, OAuthError::Timeout(what) if what == "user info"));
+        assert_eq!(err.status_code(), 504);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_provider_answers_are_not_retried() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = with_retries("user info", TIMEOUT, 2, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(OAuthError::UserInfoFailed("401 Unauthorized".to_string()))
+        })
+        .await;
+        assert!(matches!(result, Err(OAuthError::UserInfoFailed(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        for attempt in 0..10 {
+            let max = BASE_BACKOFF * 2u32.pow(attempt.min(6));
+            assert!(backoff(attempt) <= max);
+        }
+    }
+}
@@ -1,49 +1,23 @@
 //! OAuth route handlers.
 
-use crate::{OAuthConfig, OAuthState};
+use crate::provider::verify_id_token_nonce;
+use crate::retry::{with_retries, with_timeout};
+use crate::{
+    OAuthConfig, OAuthError, OAuthProvider, OAuthState, OAuthStateStore, OAuthUserInfo, TokenSet,
+};
 use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::events::Event;
+use better_auth_core::redirect::{is_allowed_redirect, safe_redirect, validate_redirect};
 use better_auth_core::router::{CookieOptions, Method, Request, RequestHandler, Response, Route};
-use better_auth_core::types::{Session, User};
-use serde::Serialize;
+use better_auth_core::run_in_transaction;
+use better_auth_core::session::{SESSION_COOKIE, SessionResolver, end_replaced_session};
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::{Account, AuthFactor, Session, User};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-
-// ============================================================================
-// OAuth State Store (In-Memory)
-// ============================================================================
-
-/// In-memory store for OAuth state during the authorization flow.
-/// In production, this should be backed by Redis or a database.
-#[derive(Debug, Default)]
-pub struct OAuthStateStore {
-    states: RwLock<HashMap<String, OAuthState>>,
-}
-
-impl OAuthStateStore {
-    pub fn new() -> Self {
-        Self {
-            states: RwLock::new(HashMap::new()),
-        }
-    }
-
-    /// Stores an OAuth state.
-    pub fn store(&self, state: &OAuthState) {
-        let mut states = self.states.write().unwrap();
-        states.insert(state.state.clone(), state.clone());
-    }
-
-    /// Retrieves and removes an OAuth state.
-    pub fn take(&self, state_key: &str) -> Option<OAuthState> {
-        let mut states = self.states.write().unwrap();
-        states.remove(state_key)
-    }
-
-    /// Cleans up expired states.
-    pub fn cleanup_expired(&self) {
-        let mut states = self.states.write().unwrap();
-        states.retain(|_, state| !state.is_expired());
-    }
-}
+use std::sync::Arc;
 
 // ============================================================================
 // Token Response Strategy
@@ -61,6 +35,28 @@ pub enum TokenResponseStrategy {
     Both,
 }
 
+// ============================================================================
+// Email Conflict Strategy
+// ============================================================================
+
+/// What to do when a first OAuth sign-in brings an email that already
+/// belongs to a user, such as one who signed up with a password or another
+/// provider.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmailConflictStrategy {
+    /// Link the provider account to the existing user, but only if the
+    /// provider reports the email as verified. Otherwise the sign-in is
+    /// rejected, since anyone can claim an unverified address (default).
+    #[default]
+    LinkIfVerified,
+    /// Reject the sign-in. The user can sign in the way they signed up and
+    /// link the provider from there.
+    AlwaysReject,
+    /// Create a second user with the same email. The storage adapter must
+    /// allow duplicate emails.
+    CreateSeparate,
+}
+
 // ============================================================================
 // Route Handlers
 // ============================================================================
@@ -69,7 +65,7 @@ pub enum TokenResponseStrategy {
 /// Redirects the user to the OAuth provider's authorization page.
 pub struct SignInHandler {
     pub config: Arc<OAuthConfig>,
-    pub state_store: Arc<OAuthStateStore>,
+    pub state_store: Arc<dyn OAuthStateStore>,
 }
 
 #[async_trait]
@@ -99,19 +95,30 @@ impl RequestHandler for SignInHandler {
 
         // Parse query parameters
         let redirect_url = req.query_param("redirect_url").cloned();
+        if let Some(url) = &redirect_url
+            && let Err(err) =
+                validate_redirect("redirect_url", url, &self.config.allowed_redirect_origins)
+        {
+            return Response::bad_request().json(ErrorResponse {
+                error: "invalid_redirect".to_string(),
+                message: err.to_string(),
+            });
+        }
         let scopes: Vec<String> = req
             .query_param("scopes")
             .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
             .unwrap_or_default();
 
         // Create OAuth state for CSRF protection
-        let mut oauth_state = OAuthState::new(&provider_name);
+        let mut oauth_state = new_state(&self.config, provider.as_ref(), &provider_name);
         if let Some(url) = redirect_url {
             oauth_state = oauth_state.with_redirect(url);
         }
 
         // Store the state
-        self.state_store.store(&oauth_state);
+        if let Err(err) = self.state_store.store(&oauth_state).await {
+            return state_store_error(err);
+        }
 
         // Build the callback URL
         let callback_url = format!(
@@ -120,7 +127,7 @@ impl RequestHandler for SignInHandler {
         );
 
         // Generate the authorization URL
-        let auth_url = provider.auth_url(&oauth_state.state, &scopes, &callback_url);
+        let auth_url = provider.authorization_url(&oauth_state, &scopes, &callback_url);
 
         // Redirect to the provider
         Response::new(302)
@@ -133,7 +140,7 @@ impl RequestHandler for SignInHandler {
 /// Handles the OAuth callback from the provider.
 pub struct CallbackHandler {
     pub config: Arc<OAuthConfig>,
-    pub state_store: Arc<OAuthStateStore>,
+    pub state_store: Arc<dyn OAuthStateStore>,
     pub token_strategy: TokenResponseStrategy,
 }
 
@@ -164,6 +171,41 @@ struct SessionResponse {
     expires_at: String,
 }
 
+impl CallbackHandler {
+    /// Responds to an error the provider sent to the callback, redirecting
+    /// to the configured error URL if there is one.
+    fn provider_error(&self, error: &str, description: Option<&str>) -> Response {
+        let err = OAuthError::from_callback(error, description);
+
+        if let Some(url) = &self.config.error_redirect_url {
+            let mut query = vec![("error", error)];
+            query.extend(description.map(|d| ("error_description", d)));
+            let separator = if url.contains('?') { '&' } else { '?' };
+            let location = format!(
+                "{}{}{}",
+                url,
+                separator,
+                serde_urlencoded::to_string(&query).unwrap_or_default()
+            );
+            return Response::new(302).header("Location", location);
+        }
+
+        let body = match &err {
+            OAuthError::AccessDenied => json!({
+                "error": "access_denied",
+                "message": "The user denied the authorization request",
+            }),
+            _ => json!({
+                "error": "provider_error",
+                "message": err.to_string(),
+                "code": error,
+                "description": description,
+            }),
+        };
+        Response::new(err.status_code()).json(body)
+    }
+}
+
 #[async_trait]
 impl RequestHandler for CallbackHandler {
     async fn handle(&self, req: Request) -> Response {
@@ -178,20 +220,18 @@ impl RequestHandler for CallbackHandler {
             }
         };
 
+        // Providers using response_mode=form_post send the parameters in
+        // the body instead of the query string.
+        let params = callback_params(&req);
+
         // Check for OAuth error from provider
-        if let Some(error) = req.query_param("error") {
-            let description = req
-                .query_param("error_description")
-                .cloned()
-                .unwrap_or_else(|| "Unknown error".to_string());
-            return Response::bad_request().json(ErrorResponse {
-                error: error.clone(),
-                message: description,
-            });
+        if let Some(error) = params.get("error") {
+            let description = params.get("error_description").map(String::as_str);
+            return self.provider_error(error, description);
         }
 
         // Get the authorization code
-        let code = match req.query_param("code") {
+        let code = match params.get("code") {
             Some(c) => c.clone(),
             None => {
                 return Response::bad_request().json(ErrorResponse {
@@ -202,7 +242,7 @@ impl RequestHandler for CallbackHandler {
         };
 
         // Validate state for CSRF protection
-        let state_key = match req.query_param("state") {
+        let state_key = match params.get("state") {
             Some(s) => s.clone(),
             None => {
                 return Response::bad_request().json(ErrorResponse {
@@ -212,9 +252,10 @@ impl RequestHandler for CallbackHandler {
             }
         };
 
-        let oauth_state = match self.state_store.take(&state_key) {
-            Some(s) => s,
-            None => {
+        let oauth_state = match self.state_store.take(&state_key).await {
+            Ok(Some(s)) => s,
+            Err(err) => return state_store_error(err),
+            Ok(None) => {
                 return Response::bad_request().json(ErrorResponse {
                     error: "invalid_state".to_string(),
                     message: "Invalid or expired state".to_string(),
@@ -255,51 +296,93 @@ impl RequestHandler for CallbackHandler {
             self.config.callback_base, provider_name
         );
 
-        // Exchange the code for tokens
-        let token_set = match provider.token_exchange(&code, &callback_url).await {
-            Ok(tokens) => tokens,
-            Err(e) => {
-                return Response::internal_error().json(ErrorResponse {
-                    error: "token_exchange_failed".to_string(),
-                    message: e.to_string(),
-                });
-            }
-        };
+        // Exchange the code for tokens. The code is single-use, so a failed
+        // exchange is never retried.
+        let exchange =
+            provider.token_exchange(&code, &callback_url, oauth_state.code_verifier.as_deref());
+        let token_set =
+            match with_timeout("token exchange", self.config.request_timeout, exchange).await {
+                Ok(tokens) => tokens,
+                Err(e) => return provider_request_error("token_exchange_failed", e),
+            };
+
+        // The id_token must echo the nonce sent with the authorization request
+        if let (Some(nonce), Some(id_token)) = (&oauth_state.nonce, &token_set.id_token)
+            && let Err(e) = verify_id_token_nonce(id_token, nonce)
+        {
+            return Response::bad_request().json(ErrorResponse {
+                error: "invalid_state".to_string(),
+                message: e.to_string(),
+            });
+        }
 
         // Get user info from the provider
-        let user_info = match provider.get_user_info(&token_set.access_token).await {
+        let user_info = match with_retries(
+            "user info request",
+            self.config.request_timeout,
+            self.config.user_info_retries,
+            || {
+                provider
+                    .get_user_info_from_tokens(&token_set, params.get("user").map(String::as_str))
+            },
+        )
+        .await
+        {
             Ok(info) => info,
-            Err(e) => {
-                return Response::internal_error().json(ErrorResponse {
-                    error: "user_info_failed".to_string(),
-                    message: e.to_string(),
-                });
-            }
+            Err(e) => return provider_request_error("user_info_failed", e),
         };
 
-        // At this point, we would:
-        // 1. Check if an account exists for this provider + provider_account_id
-        // 2. If yes, get the associated user and create a session
-        // 3. If no, create a new user (if auto_create_user is true) and account
-        //
-        // For now, we'll create a mock user and session since we don't have
-        // access to the storage adapter in this handler. In a real implementation,
-        // the handler would receive the storage adapter via dependency injection.
-
-        let user_id = uuid::Uuid::new_v4().to_string();
-        let user = User::new(
-            user_id.clone(),
-            user_info.email.clone().unwrap_or_default(),
-        );
+        if self.config.auto_create_user
+            && let Some(domains) = &self.config.email_domains
+        {
+            let email = user_info.email.as_deref().unwrap_or_default();
+            if let Err(err) = domains.check_signup(email).await {
+                return Response::new(err.status_code()).json(ErrorResponse {
+                    error: "email_domain_not_allowed".to_string(),
+                    message: err.to_string(),
+                });
+            }
+        }
 
-        let session = Session::new(user_id);
+        // Without storage nothing is persisted: the user and session only
+        // exist in the response.
+        let (user, session) = match &self.config.storage {
+            Some(storage) => {
+                let signed_in = match sign_in_user(
+                    storage,
+                    &self.config,
+                    &provider_name,
+                    &user_info,
+                    &token_set,
+                )
+                .await
+                {
+                    Ok(signed_in) => signed_in,
+                    Err(err @ AuthError::Conflict { .. }) => {
+                        return auth_error("email_conflict", err);
+                    }
+                    Err(err) => return auth_error("sign_in_failed", err),
+                };
+                if let Err(err) = end_replaced_session(storage.as_ref(), &req).await {
+                    return auth_error("sign_in_failed", err);
+                }
+                signed_in
+            }
+            None => {
+                let user = new_user(&user_info);
+                let user = map_profile(&self.config, &user_info, &user).unwrap_or(user);
+                let mut session = self.config.session_tokens.new_session(user.id.clone());
+                session.record_factor(AuthFactor::OAuth);
+                (user, session)
+            }
+        };
 
         // Build response based on token strategy
         let user_response = UserResponse {
             id: user.id.clone(),
             email: user.email.clone(),
-            name: user_info.name,
-            image: user_info.picture,
+            name: user.name.clone(),
+            image: user.image.clone(),
         };
 
         let session_response = SessionResponse {
@@ -310,18 +393,17 @@ impl RequestHandler for CallbackHandler {
 
         match self.token_strategy {
             TokenResponseStrategy::SessionCookie => {
-                // Set cookie and redirect
-                let redirect_url = oauth_state
-                    .redirect_url
-                    .unwrap_or_else(|| "/".to_string());
+                // Set cookie and redirect. The URL was checked at sign-in,
+                // but the state store may be shared with other services.
+                let redirect_url = safe_redirect(
+                    oauth_state.redirect_url.as_deref(),
+                    &self.config.allowed_redirect_origins,
+                    "/",
+                );
 
                 Response::new(302)
                     .header("Location", redirect_url)
-                    .cookie(
-                        "better_auth_session",
-                        &session.token,
-                        CookieOptions::secure(),
-                    )
+                    .cookie(SESSION_COOKIE, &session.token, CookieOptions::secure())
             }
             TokenResponseStrategy::JwtResponse => {
                 // Return JSON response with tokens
@@ -336,7 +418,9 @@ impl RequestHandler for CallbackHandler {
             }
             TokenResponseStrategy::Both => {
                 // Set cookie AND return JSON
-                let redirect_url = oauth_state.redirect_url.clone();
+                let redirect_url = oauth_state.redirect_url.as_deref().filter(|url| {
+                    is_allowed_redirect(url, &self.config.allowed_redirect_origins)
+                });
 
                 let mut response = Response::ok()
                     .json(CallbackSuccessResponse {
@@ -346,11 +430,7 @@ impl RequestHandler for CallbackHandler {
                         refresh_token: None,
                         expires_in: Some(604800),
                     })
-                    .cookie(
-                        "better_auth_session",
-                        &session.token,
-                        CookieOptions::secure(),
-                    );
+                    .cookie(SESSION_COOKIE, &session.token, CookieOptions::secure());
 
                 // If there's a redirect URL, include it in the response
                 if let Some(url) = redirect_url {
@@ -367,7 +447,7 @@ impl RequestHandler for CallbackHandler {
 /// Links an OAuth account to an existing authenticated user.
 pub struct LinkAccountHandler {
     pub config: Arc<OAuthConfig>,
-    pub state_store: Arc<OAuthStateStore>,
+    pub state_store: Arc<dyn OAuthStateStore>,
 }
 
 #[async_trait]
@@ -403,16 +483,18 @@ impl RequestHandler for LinkAccountHandler {
         }
 
         // Create OAuth state for the linking flow
-        let oauth_state = OAuthState::new(&provider_name);
-        self.state_store.store(&oauth_state);
+        let provider = self.config.providers.get(&provider_name).unwrap();
+        let oauth_state = new_state(&self.config, provider.as_ref(), &provider_name);
+        if let Err(err) = self.state_store.store(&oauth_state).await {
+            return state_store_error(err);
+        }
 
         // Return the authorization URL for the client to redirect to
-        let provider = self.config.providers.get(&provider_name).unwrap();
         let callback_url = format!(
             "{}/oauth/callback/{}",
             self.config.callback_base, provider_name
         );
-        let auth_url = provider.auth_url(&oauth_state.state, &[], &callback_url);
+        let auth_url = provider.authorization_url(&oauth_state, &[], &callback_url);
 
         Response::ok().json(LinkResponse {
             auth_url,
@@ -427,27 +509,42 @@ struct LinkResponse {
     state: String,
 }
 
-/// Handler for DELETE /oauth/unlink/:provider
+/// Handler for POST /oauth/unlink and DELETE /oauth/unlink/:provider
 /// Unlinks an OAuth account from the authenticated user.
+///
+/// The last remaining way to sign in is never removed: if the user has no
+/// password, passkey or other linked account, the request fails with
+/// `last_auth_method`.
 pub struct UnlinkAccountHandler {
     pub config: Arc<OAuthConfig>,
 }
 
+/// Request body for POST /oauth/unlink.
+#[derive(Debug, Deserialize)]
+struct UnlinkRequest {
+    provider: String,
+    /// Provider account ID, for users with several accounts at one provider.
+    #[serde(default)]
+    account_id: Option<String>,
+}
+
 #[async_trait]
 impl RequestHandler for UnlinkAccountHandler {
     async fn handle(&self, req: Request) -> Response {
-        // This endpoint requires authentication
-        // In a real implementation, we'd check for a valid session and get the user
-
+        let body = req.json::<UnlinkRequest>();
         let provider_name = match req.param("provider") {
             Some(name) => name.clone(),
-            None => {
-                return Response::bad_request().json(ErrorResponse {
-                    error: "missing_provider".to_string(),
-                    message: "Provider name is required".to_string(),
-                });
-            }
+            None => match &body {
+                Some(body) => body.provider.clone(),
+                None => {
+                    return Response::bad_request().json(ErrorResponse {
+                        error: "missing_provider".to_string(),
+                        message: "Provider name is required".to_string(),
+                    });
+                }
+            },
         };
+        let account_id = body.and_then(|body| body.account_id);
 
         // Check if provider exists
         if !self.config.providers.contains_key(&provider_name) {
@@ -465,11 +562,103 @@ impl RequestHandler for UnlinkAccountHandler {
             });
         }
 
-        // In a real implementation, we would:
-        // 1. Get the authenticated user
-        // 2. Find the account for this provider
-        // 3. Ensure the user has at least one other auth method
-        // 4. Delete the account
+        let Some(storage) = &self.config.storage else {
+            return Response::internal_error().json(ErrorResponse {
+                error: "storage_not_configured".to_string(),
+                message: "Unlinking accounts requires a storage adapter".to_string(),
+            });
+        };
+
+        let session = match SessionResolver::new(storage.clone())
+            .resolve_request(&req)
+            .await
+        {
+            Ok(Some(resolved)) => resolved.session,
+            Ok(None) => {
+                return Response::unauthorized().json(ErrorResponse {
+                    error: "unauthorized".to_string(),
+                    message: "A valid session is required".to_string(),
+                });
+            }
+            Err(err) => return auth_error("storage_error", err),
+        };
+
+        let accounts = match storage.get_accounts_by_user_id(&session.user_id).await {
+            Ok(accounts) => accounts,
+            Err(err) => return auth_error("storage_error", err),
+        };
+        let Some(account) = accounts.into_iter().find(|a| {
+            a.provider == provider_name
+                && account_id
+                    .as_ref()
+                    .is_none_or(|id| *id == a.provider_account_id)
+        }) else {
+            return Response::not_found().json(ErrorResponse {
+                error: "account_not_found".to_string(),
+                message: format!("No '{}' account is linked to this user", provider_name),
+            });
+        };
+
+        let user_id = session.user_id.clone();
+        let account_id = account.id.clone();
+        let passkeys = self.config.passkeys.clone();
+        let unlinked = run_in_transaction(storage, |tx| async move {
+            // Rewriting the user row locks it until commit, so concurrent
+            // unlinks for one user check and delete one at a time and the
+            // second sees what the first removed.
+            let user = tx
+                .get_user_by_id(&user_id)
+                .await?
+                .ok_or(AuthError::UserNotFound)?;
+            let user = tx.update_user(&user).await?;
+
+            // Never leave the user without a way to sign in.
+            let has_password = user.get_extension::<String>("password_hash").is_some();
+            let has_other_account = tx
+                .get_accounts_by_user_id(&user_id)
+                .await?
+                .iter()
+                .any(|a| a.id != account_id);
+            let has_passkey = match &passkeys {
+                Some(store) => !store.get_user_passkeys(&user_id).await?.is_empty(),
+                None => false,
+            };
+            if !has_password && !has_other_account && !has_passkey {
+                return Ok(None);
+            }
+            tx.delete_account(&account_id).await?;
+            Ok(Some(user))
+        })
+        .await;
+        let user = match unlinked {
+            Ok(Some(user)) => user,
+            Ok(None) => {
+                return Response::new(409).json(ErrorResponse {
+                    error: "last_auth_method".to_string(),
+                    message: "Cannot unlink the only sign-in method; set a password, add a passkey or link another account first"
+                        .to_string(),
+                });
+            }
+            Err(AuthError::UserNotFound) => {
+                return auth_error("user_not_found", AuthError::UserNotFound);
+            }
+            Err(err) => return auth_error("storage_error", err),
+        };
+
+        if let Some(bus) = &self.config.event_bus {
+            bus.emit(
+                Event::simple(
+                    "oauth.account_unlinked",
+                    json!({
+                        "user_id": user.id,
+                        "provider": account.provider,
+                        "provider_account_id": account.provider_account_id,
+                    }),
+                )
+                .with_source("oauth"),
+            )
+            .await;
+        }
 
         Response::ok().json(UnlinkResponse {
             success: true,
@@ -518,6 +707,42 @@ struct ProvidersResponse {
     providers: Vec<ProviderInfo>,
 }
 
+/// Collects callback parameters from the query string and, for form posts,
+/// the request body. Body values take precedence.
+fn callback_params(req: &Request) -> HashMap<String, String> {
+    let mut params = req.query.clone();
+    match &req.body {
+        Some(serde_json::Value::Object(body)) => {
+            for (key, value) in body {
+                if let Some(value) = value.as_str() {
+                    params.insert(key.clone(), value.to_string());
+                }
+            }
+        }
+        Some(serde_json::Value::String(form)) => {
+            if let Ok(form) = serde_urlencoded::from_str::<Vec<(String, String)>>(form) {
+                params.extend(form);
+            }
+        }
+        _ => {}
+    }
+    params
+}
+
+/// Creates the OAuth state for a new flow, with PKCE when both the config
+/// and the provider allow it, and a nonce for providers that issue an
+/// `id_token`.
+fn new_state(config: &OAuthConfig, provider: &dyn OAuthProvider, provider_name: &str) -> OAuthState {
+    let mut state = OAuthState::new(provider_name);
+    if config.pkce && provider.supports_pkce() {
+        state = state.with_pkce();
+    }
+    if provider.issues_id_token() {
+        state = state.with_nonce();
+    }
+    state
+}
+
 // ============================================================================
 // Error Response
 // ============================================================================
@@ -528,6 +753,171 @@ struct ErrorResponse {
     message: String,
 }
 
+/// Builds a user from a provider's profile.
+fn new_user(user_info: &OAuthUserInfo) -> User {
+    let mut user = User::new(
+        uuid::Uuid::new_v4().to_string(),
+        user_info.email.clone().unwrap_or_default(),
+    );
+    user.email_verified = user_info.email_verified.unwrap_or(false);
+    user.name = user_info.name.clone();
+    user.image = user_info.picture.clone();
+    user
+}
+
+/// Signs in the user linked to a provider login and starts a session.
+///
+/// The config's profile mapper is applied to the user, and with
+/// `store_raw_profile` the provider's userinfo is kept on the account.
+/// A login with no linked account whose email belongs to an existing user
+/// is handled by `config.on_email_conflict`; linking emits
+/// `oauth.account_linked`, and rejecting fails with
+/// [`AuthError::Conflict`]. Otherwise a login with no linked account
+/// creates a user when `auto_create_user` is set. The user, account, and
+/// session are written in one transaction so a failure part way through
+/// leaves no user without an account.
+pub(crate) async fn sign_in_user(
+    storage: &Arc<dyn StorageAdapter>,
+    config: &OAuthConfig,
+    provider: &str,
+    user_info: &OAuthUserInfo,
+    tokens: &TokenSet,
+) -> AuthResult<(User, Session)> {
+    if let Some(mut account) = storage.get_account(provider, &user_info.id).await? {
+        let mut user = storage
+            .get_user_by_id(&account.user_id)
+            .await?
+            .ok_or_else(|| AuthError::not_found("user", "id", &account.user_id))?;
+        if let Some(mapped) = map_profile(config, user_info, &user) {
+            user = storage.update_user(&mapped).await?;
+        }
+        let profile = raw_profile(config, user_info);
+        if profile.is_some() && account.profile != profile {
+            account.profile = profile;
+            storage.update_account(&account).await?;
+        }
+        let mut session = config.session_tokens.new_session(user.id.clone());
+        session.record_factor(AuthFactor::OAuth);
+        let session = storage.create_session(&session).await?;
+        return Ok((user, session));
+    }
+
+    let existing = match user_info.email.as_deref().filter(|email| !email.is_empty()) {
+        Some(email) => storage.get_user_by_email(email).await?,
+        None => None,
+    };
+    let link_to = match (existing, config.on_email_conflict) {
+        (None, _) | (Some(_), EmailConflictStrategy::CreateSeparate) => None,
+        (Some(user), EmailConflictStrategy::LinkIfVerified)
+            if user_info.email_verified == Some(true) =>
+        {
+            Some(user)
+        }
+        (Some(_), EmailConflictStrategy::LinkIfVerified) => {
+            return Err(AuthError::conflict(format!(
+                "An account already uses this email, and {} has not verified it",
+                provider
+            )));
+        }
+        (Some(_), EmailConflictStrategy::AlwaysReject) => {
+            return Err(AuthError::conflict(format!(
+                "An account already uses this email; sign in to it and link {} instead",
+                provider
+            )));
+        }
+    };
+    if link_to.is_none() && !config.auto_create_user {
+        return Err(AuthError::forbidden(format!(
+            "No user is linked to this {} account",
+            provider
+        )));
+    }
+
+    let linking = link_to.is_some();
+    let user = link_to.unwrap_or_else(|| new_user(user_info));
+    let mapped = map_profile(config, user_info, &user);
+    let mut account = Account::new(user.id.clone(), provider.to_string(), user_info.id.clone());
+    account.access_token = Some(tokens.access_token.clone());
+    account.refresh_token = tokens.refresh_token.clone();
+    account.expires_at = tokens
+        .expires_in
+        .map(|secs| chrono::Utc::now() + chrono::Duration::seconds(secs as i64));
+    account.profile = raw_profile(config, user_info);
+    let provider_account_id = account.provider_account_id.clone();
+
+    let tokens = config.session_tokens.clone();
+    let (user, session) = run_in_transaction(storage, |tx| async move {
+        let user = match (linking, mapped) {
+            (false, mapped) => tx.create_user(&mapped.unwrap_or(user)).await?,
+            (true, Some(mapped)) => tx.update_user(&mapped).await?,
+            (true, None) => user,
+        };
+        tx.create_account(&account).await?;
+        let mut session = tokens.new_session(user.id.clone());
+        session.record_factor(AuthFactor::OAuth);
+        let session = tx.create_session(&session).await?;
+        Ok((user, session))
+    })
+    .await?;
+
+    if linking && let Some(bus) = &config.event_bus {
+        bus.emit(
+            Event::simple(
+                "oauth.account_linked",
+                json!({
+                    "user_id": user.id,
+                    "provider": provider,
+                    "provider_account_id": provider_account_id,
+                }),
+            )
+            .with_source("oauth"),
+        )
+        .await;
+    }
+    Ok((user, session))
+}
+
+/// Runs the configured [`ProfileMapper`](crate::ProfileMapper) on `user`,
+/// returning the mapped user if it differs.
+fn map_profile(config: &OAuthConfig, user_info: &OAuthUserInfo, user: &User) -> Option<User> {
+    let mapper = config.profile_mapper.as_ref()?;
+    let mapped = mapper(user_info, user);
+    // `User` has no `PartialEq`, so compare what would be stored.
+    (serde_json::to_value(&mapped).ok() != serde_json::to_value(user).ok()).then_some(mapped)
+}
+
+/// The profile to store on the account, if the config asks for it.
+fn raw_profile(config: &OAuthConfig, user_info: &OAuthUserInfo) -> Option<serde_json::Value> {
+    (config.store_raw_profile && !user_info.raw.is_null()).then(|| user_info.raw.clone())
+}
+
+fn auth_error(error: &str, err: AuthError) -> Response {
+    Response::new(err.status_code()).json(ErrorResponse {
+        error: error.to_string(),
+        message: err.to_string(),
+    })
+}
+
+/// Responds to a failed request to the provider. Timeouts get a 504 and
+/// `provider_timeout`, so clients can tell them from rejected requests.
+fn provider_request_error(error: &str, err: OAuthError) -> Response {
+    let (status, error) = match err {
+        OAuthError::Timeout(_) => (err.status_code(), "provider_timeout"),
+        _ => (500, error),
+    };
+    Response::new(status).json(ErrorResponse {
+        error: error.to_string(),
+        message: err.to_string(),
+    })
+}
+
+fn state_store_error(err: AuthError) -> Response {
+    Response::internal_error().json(ErrorResponse {
+        error: "state_store_failed".to_string(),
+        message: err.to_string(),
+    })
+}
+
 // ============================================================================
 // Route Registration
 // ============================================================================
@@ -535,7 +925,7 @@ struct ErrorResponse {
 /// Creates all OAuth routes.
 pub fn create_routes(
     config: Arc<OAuthConfig>,
-    state_store: Arc<OAuthStateStore>,
+    state_store: Arc<dyn OAuthStateStore>,
     token_strategy: TokenResponseStrategy,
 ) -> Vec<Route> {
     vec![
@@ -556,12 +946,24 @@ pub fn create_routes(
             CallbackHandler {
                 config: config.clone(),
                 state_store: state_store.clone(),
-                token_strategy,
+                token_strategy: token_strategy.clone(),
             },
         )
         .summary("OAuth callback")
         .description("Handles the OAuth callback from the provider")
         .tag("oauth"),
+        Route::new(
+            Method::POST,
+            "/oauth/callback/:provider",
+            CallbackHandler {
+                config: config.clone(),
+                state_store: state_store.clone(),
+                token_strategy,
+            },
+        )
+        .summary("OAuth form_post callback")
+        .description("Handles OAuth callbacks posted as forms (e.g. Sign in with Apple)")
+        .tag("oauth"),
         Route::new(
             Method::POST,
             "/oauth/link/:provider",
@@ -585,6 +987,20 @@ pub fn create_routes(
         .description("Unlinks an OAuth account from the authenticated user")
         .tag("oauth")
         .requires_auth(),
+        Route::new(
+            Method::POST,
+            "/oauth/unlink",
+            UnlinkAccountHandler {
+                config: config.clone(),
+            },
+        )
+        .summary("Unlink OAuth account")
+        .description(
+            "Unlinks the authenticated user's account for the `provider` in the body, \
+             unless it is their only sign-in method",
+        )
+        .tag("oauth")
+        .requires_auth(),
         Route::new(
             Method::GET,
             "/oauth/providers",
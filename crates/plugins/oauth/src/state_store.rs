@@ -0,0 +1,86 @@
+//! Storage for OAuth state between the authorization redirect and callback.
+//!
+//! The default [`InMemoryOAuthStateStore`] only works when the callback is
+//! handled by the instance that started the flow. Horizontally scaled
+//! deployments should implement [`OAuthStateStore`] on shared storage.
+//! [`OAuthState`] is serializable, so a Redis-backed store can be as small as:
+//!
+//! ```rust,ignore
+//! #[async_trait]
+//! impl OAuthStateStore for RedisStateStore {
+//!     async fn store(&self, state: &OAuthState) -> AuthResult<()> {
+//!         let ttl = (state.expires_at - Utc::now()).num_seconds().max(1);
+//!         let json = serde_json::to_string(state)?;
+//!         self.conn().set_ex(key(&state.state), json, ttl as u64).await?;
+//!         Ok(())
+//!     }
+//!
+//!     async fn take(&self, state_key: &str) -> AuthResult<Option<OAuthState>> {
+//!         let json: Option<String> = self.conn().get_del(key(state_key)).await?;
+//!         Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
+//!     }
+//!
+//!     // Redis expires keys on its own.
+//!     async fn cleanup_expired(&self) -> AuthResult<()> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let oauth = OAuthPlugin::new(config, Arc::new(RedisStateStore::new(client)));
+//! ```
+
+use crate::OAuthState;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Storage for in-flight OAuth states.
+#[async_trait]
+pub trait OAuthStateStore: Send + Sync {
+    /// Stores an OAuth state, keyed by `state.state`.
+    async fn store(&self, state: &OAuthState) -> AuthResult<()>;
+
+    /// Retrieves and removes an OAuth state.
+    ///
+    /// Must be atomic: a state can only be taken once, even by concurrent
+    /// callbacks on different instances.
+    async fn take(&self, state_key: &str) -> AuthResult<Option<OAuthState>>;
+
+    /// Removes expired states.
+    async fn cleanup_expired(&self) -> AuthResult<()>;
+}
+
+/// In-memory OAuth state store.
+///
+/// Suitable for a single instance and for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryOAuthStateStore {
+    states: RwLock<HashMap<String, OAuthState>>,
+}
+
+impl InMemoryOAuthStateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl OAuthStateStore for InMemoryOAuthStateStore {
+    async fn store(&self, state: &OAuthState) -> AuthResult<()> {
+        let mut states = self.states.write().unwrap();
+        states.insert(state.state.clone(), state.clone());
+        Ok(())
+    }
+
+    async fn take(&self, state_key: &str) -> AuthResult<Option<OAuthState>> {
+        let mut states = self.states.write().unwrap();
+        Ok(states.remove(state_key))
+    }
+
+    async fn cleanup_expired(&self) -> AuthResult<()> {
+        let mut states = self.states.write().unwrap();
+        states.retain(|_, state| !state.is_expired());
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 //! OTP generation utilities.
 
+use better_auth_core::error::{AuthError, AuthResult};
 use rand::Rng;
 
 /// Configuration for OTP generation.
@@ -58,10 +59,31 @@ impl OtpConfig {
             otp_type: OtpType::Alphabetic,
         }
     }
+
+    /// Creates an OTP config drawing from the characters of `charset`,
+    /// e.g. to leave out characters users confuse. Repeated characters
+    /// are only counted once.
+    ///
+    /// Fails if `charset` is empty or contains non-ASCII characters.
+    pub fn with_charset(length: usize, charset: &str) -> AuthResult<Self> {
+        if charset.is_empty() || !charset.is_ascii() {
+            return Err(AuthError::config("OTP charset must be non-empty ASCII"));
+        }
+        let mut unique = String::with_capacity(charset.len());
+        for c in charset.chars() {
+            if !unique.contains(c) {
+                unique.push(c);
+            }
+        }
+        Ok(Self {
+            length,
+            otp_type: OtpType::Custom(unique),
+        })
+    }
 }
 
 /// Type of OTP to generate.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OtpType {
     /// Numeric only (0-9).
     Numeric,
@@ -71,16 +93,20 @@ pub enum OtpType {
     Alphabetic,
     /// Alphanumeric without ambiguous characters (0, O, l, 1, I).
     AlphanumericUnambiguous,
+    /// The characters of a custom ASCII string; see
+    /// [`OtpConfig::with_charset`].
+    Custom(String),
 }
 
 impl OtpType {
     /// Returns the character set for this OTP type.
-    pub fn charset(&self) -> &'static [u8] {
+    pub fn charset(&self) -> &[u8] {
         match self {
             OtpType::Numeric => b"0123456789",
             OtpType::Alphanumeric => b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz",
             OtpType::Alphabetic => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz",
             OtpType::AlphanumericUnambiguous => b"23456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghjkmnpqrstuvwxyz",
+            OtpType::Custom(charset) => charset.as_bytes(),
         }
     }
 }
@@ -108,6 +134,9 @@ impl OtpGenerator {
     }
 
     /// Generates a new OTP code.
+    ///
+    /// Characters come from the thread-local CSPRNG, and `gen_range` samples
+    /// uniformly, so no character is likelier than another.
     pub fn generate(&self) -> String {
         let mut rng = rand::thread_rng();
         let charset = self.config.otp_type.charset();
@@ -167,6 +196,26 @@ mod tests {
         assert!(otp.chars().all(|c| c.is_ascii_alphanumeric()));
     }
 
+    #[test]
+    fn test_custom_charset_otp() {
+        let generator =
+            OtpGenerator::new(OtpConfig::with_charset(12, "ACDEFHJKMNPRTUVWXY34679").unwrap());
+        for _ in 0..50 {
+            let otp = generator.generate();
+            assert_eq!(otp.len(), 12);
+            assert!(otp.chars().all(|c| "ACDEFHJKMNPRTUVWXY34679".contains(c)));
+        }
+
+        let config = OtpConfig::with_charset(4, "aab").unwrap();
+        assert_eq!(config.otp_type.charset(), b"ab");
+    }
+
+    #[test]
+    fn test_invalid_charset_rejected() {
+        assert!(OtpConfig::with_charset(6, "").is_err());
+        assert!(OtpConfig::with_charset(6, "abcé").is_err());
+    }
+
     #[test]
     fn test_secure_token() {
         let token = OtpGenerator::generate_secure_token(32);
@@ -7,19 +7,25 @@
 //! - Attempt tracking
 //! - Expiration handling
 //! - Token storage patterns
+//! - Message delivery over email or SMS
 
 mod generator;
 mod rate_limit;
+mod sender;
 mod storage;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 mod verification;
 
 pub use generator::{OtpGenerator, OtpConfig, OtpType};
 pub use rate_limit::{RateLimiter, RateLimitConfig, RateLimitResult};
+pub use sender::{LoggingSender, MessageSender};
 pub use storage::{TokenStorage, TokenStorageMode, StoredToken};
 pub use verification::{VerificationResult, VerificationError, AttemptTracker};
 
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 
 /// Represents a verification code/token with metadata.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,7 +114,11 @@ impl VerificationCode {
             return VerificationResult::TooManyAttempts;
         }
 
-        if self.code == provided_code {
+        // Constant time, so response timing reveals nothing about how much
+        // of the code matched. Only the length may leak.
+        let matches = self.code.len() == provided_code.len()
+            && bool::from(self.code.as_bytes().ct_eq(provided_code.as_bytes()));
+        if matches {
             self.mark_used();
             VerificationResult::Valid
         } else {
@@ -183,4 +193,21 @@ mod tests {
         // Even correct code should fail after max attempts
         assert_eq!(code.verify("123456"), VerificationResult::TooManyAttempts);
     }
+
+    #[test]
+    fn test_verification_code_length_mismatch() {
+        let mut code = VerificationCode::new(
+            "test@example.com",
+            "123456",
+            "sign-in",
+            Duration::minutes(5),
+            5,
+        );
+
+        assert_eq!(code.verify("12345"), VerificationResult::Invalid);
+        assert_eq!(code.verify("1234567"), VerificationResult::Invalid);
+        assert_eq!(code.verify(""), VerificationResult::Invalid);
+        assert_eq!(code.attempts, 3);
+        assert!(!code.used);
+    }
 }
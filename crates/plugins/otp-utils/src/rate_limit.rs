@@ -1,5 +1,6 @@
 //! Rate limiting utilities.
 
+use better_auth_core::error::{AuthError, AuthResult};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -13,6 +14,11 @@ pub struct RateLimitConfig {
     pub time_window: Duration,
     /// Whether rate limiting is enabled.
     pub enabled: bool,
+    /// How long a key is locked out for once it reaches the limit, if at
+    /// all. See [`with_lockout`](Self::with_lockout).
+    pub lockout: Option<Duration>,
+    /// The longest a lockout can grow to.
+    pub max_lockout: Duration,
 }
 
 impl Default for RateLimitConfig {
@@ -21,6 +27,8 @@ impl Default for RateLimitConfig {
             max_requests: 10,
             time_window: Duration::minutes(1),
             enabled: true,
+            lockout: None,
+            max_lockout: Duration::zero(),
         }
     }
 }
@@ -32,9 +40,22 @@ impl RateLimitConfig {
             max_requests,
             time_window,
             enabled: true,
+            ..Default::default()
         }
     }
 
+    /// Locks a key out for `lockout` once it reaches the limit.
+    ///
+    /// Each lockout that follows within one window of the previous one
+    /// ending doubles in length, up to `max_lockout`; a key that stays quiet
+    /// for a whole window starts over. The key's count starts again after
+    /// each lockout.
+    pub fn with_lockout(mut self, lockout: Duration, max_lockout: Duration) -> Self {
+        self.lockout = Some(lockout);
+        self.max_lockout = max_lockout;
+        self
+    }
+
     /// Disables rate limiting.
     pub fn disabled() -> Self {
         Self {
@@ -88,6 +109,19 @@ impl RateLimitResult {
     pub fn is_limited(&self) -> bool {
         matches!(self, RateLimitResult::Limited { .. })
     }
+
+    /// Converts a limited result into [`AuthError::RateLimitExceeded`].
+    pub fn into_result(self) -> AuthResult<()> {
+        match self {
+            RateLimitResult::Allowed { .. } => Ok(()),
+            RateLimitResult::Limited { retry_after_ms, .. } => {
+                // Round up so clients never retry a moment too early.
+                Err(AuthError::RateLimitExceeded {
+                    retry_after_seconds: (retry_after_ms as u64).div_ceil(1000),
+                })
+            }
+        }
+    }
 }
 
 /// Tracks rate limit state for a single key.
@@ -99,6 +133,12 @@ pub struct RateLimitState {
     pub window_start: DateTime<Utc>,
     /// Last request timestamp.
     pub last_request: DateTime<Utc>,
+    /// When the latest lockout ends, if the key was ever locked out.
+    #[serde(default)]
+    pub locked_until: Option<DateTime<Utc>>,
+    /// How many lockouts in a row the key has had.
+    #[serde(default)]
+    pub lockouts: u32,
 }
 
 impl RateLimitState {
@@ -109,6 +149,8 @@ impl RateLimitState {
             request_count: 1,
             window_start: now,
             last_request: now,
+            locked_until: None,
+            lockouts: 0,
         }
     }
 }
@@ -119,11 +161,21 @@ impl Default for RateLimitState {
     }
 }
 
+/// The fewest tracked keys at which [`RateLimiter`] sweeps expired entries.
+const MIN_SWEEP_LEN: usize = 1024;
+
 /// In-memory rate limiter.
+///
+/// Keys whose window has expired are swept whenever the number of tracked
+/// keys doubles, so memory stays proportional to the keys seen within one
+/// window. With a [lockout](RateLimitConfig::with_lockout), a key is kept
+/// until a window has passed since its lockout ended.
 #[derive(Debug)]
 pub struct RateLimiter {
     config: RateLimitConfig,
     states: HashMap<String, RateLimitState>,
+    /// Number of tracked keys at which the next sweep runs.
+    sweep_at: usize,
 }
 
 impl RateLimiter {
@@ -132,6 +184,7 @@ impl RateLimiter {
         Self {
             config,
             states: HashMap::new(),
+            sweep_at: MIN_SWEEP_LEN,
         }
     }
 
@@ -149,15 +202,24 @@ impl RateLimiter {
         if let Some(state) = self.states.get_mut(key) {
             let window_end = state.window_start + self.config.time_window;
             
-            // Check if window has expired
-            if now > window_end {
-                // Reset window
+            if let Some(until) = state.locked_until.filter(|until| *until > now) {
+                // Locked out
+                RateLimitResult::Limited {
+                    reset_at: until,
+                    retry_after_ms: (until - now).num_milliseconds().max(0),
+                }
+            } else if now > window_end {
+                // Window has expired, so reset it
                 state.request_count = 1;
                 state.window_start = now;
                 state.last_request = now;
+                let remaining = self.config.max_requests.saturating_sub(1);
+                if remaining == 0 {
+                    Self::lock_out(&self.config, state, now);
+                }
                 
                 RateLimitResult::Allowed {
-                    remaining: self.config.max_requests - 1,
+                    remaining,
                     reset_at: now + self.config.time_window,
                 }
             } else if state.request_count >= self.config.max_requests {
@@ -171,23 +233,63 @@ impl RateLimiter {
                 // Increment counter
                 state.request_count += 1;
                 state.last_request = now;
+                let remaining = self.config.max_requests - state.request_count;
+                if remaining == 0 {
+                    Self::lock_out(&self.config, state, now);
+                }
                 
                 RateLimitResult::Allowed {
-                    remaining: self.config.max_requests - state.request_count,
+                    remaining,
                     reset_at: window_end,
                 }
             }
         } else {
             // First request for this key
-            self.states.insert(key.to_string(), RateLimitState::new());
+            if self.states.len() >= self.sweep_at {
+                self.cleanup();
+                self.sweep_at = (self.states.len() * 2).max(MIN_SWEEP_LEN);
+            }
+            let mut state = RateLimitState::new();
+            let remaining = self.config.max_requests.saturating_sub(1);
+            if remaining == 0 {
+                Self::lock_out(&self.config, &mut state, now);
+            }
+            self.states.insert(key.to_string(), state);
             
             RateLimitResult::Allowed {
-                remaining: self.config.max_requests - 1,
+                remaining,
                 reset_at: now + self.config.time_window,
             }
         }
     }
 
+    /// Returns when `key`'s lockout ends, if it is locked out now.
+    ///
+    /// Unlike [`check`](Self::check), this doesn't count a request.
+    pub fn locked_until(&self, key: &str) -> Option<DateTime<Utc>> {
+        self.states
+            .get(key)
+            .and_then(|state| state.locked_until)
+            .filter(|until| *until > Utc::now())
+    }
+
+    // Internal helper to lock out a key that just reached the limit, if
+    // lockouts are configured, and start its count again.
+    fn lock_out(config: &RateLimitConfig, state: &mut RateLimitState, now: DateTime<Utc>) {
+        let Some(lockout) = config.lockout else {
+            return;
+        };
+        state.lockouts = match state.locked_until {
+            Some(previous) if previous + config.time_window > now => state.lockouts + 1,
+            _ => 1,
+        };
+        let factor = 2i32.saturating_pow(state.lockouts.saturating_sub(1).min(30));
+        let until = now + (lockout * factor).min(config.max_lockout);
+        state.locked_until = Some(until);
+        state.request_count = 0;
+        state.window_start = until;
+    }
+
     /// Resets the rate limit for a key.
     pub fn reset(&mut self, key: &str) {
         self.states.remove(key);
@@ -198,6 +300,9 @@ impl RateLimiter {
         let now = Utc::now();
         self.states.retain(|_, state| {
             state.window_start + self.config.time_window > now
+                || state
+                    .locked_until
+                    .is_some_and(|until| until + self.config.time_window > now)
         });
     }
 
@@ -238,6 +343,60 @@ mod tests {
         assert!(limiter.check("user2").is_allowed()); // Different key
     }
 
+    #[test]
+    fn test_limited_result_into_error() {
+        let mut limiter = RateLimiter::new(RateLimitConfig::new(1, Duration::minutes(15)));
+
+        assert!(limiter.check("user1").into_result().is_ok());
+        match limiter.check("user1").into_result() {
+            Err(AuthError::RateLimitExceeded { retry_after_seconds }) => {
+                assert!(retry_after_seconds > 14 * 60 && retry_after_seconds <= 15 * 60);
+            }
+            other => panic!("expected a rate limit error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_expired_keys_are_swept() {
+        let mut limiter = RateLimiter::new(RateLimitConfig::new(1, Duration::zero()));
+
+        for i in 0..10 * MIN_SWEEP_LEN {
+            assert!(limiter.check(&format!("user{}", i)).is_allowed());
+            assert!(limiter.states.len() <= MIN_SWEEP_LEN);
+        }
+    }
+
+    #[test]
+    fn test_lockouts_double_up_to_the_cap() {
+        let config = RateLimitConfig::new(2, Duration::minutes(15))
+            .with_lockout(Duration::minutes(1), Duration::minutes(3));
+        let mut limiter = RateLimiter::new(config);
+        let lockout_secs = |limiter: &mut RateLimiter| {
+            assert!(limiter.check("user1").is_allowed());
+            assert!(limiter.locked_until("user1").is_none());
+            assert!(matches!(
+                limiter.check("user1"),
+                RateLimitResult::Allowed { remaining: 0, .. }
+            ));
+            let until = limiter.locked_until("user1").unwrap();
+            assert!(limiter.check("user1").is_limited());
+            // Let the lockout run out without waiting.
+            let state = limiter.states.get_mut("user1").unwrap();
+            state.window_start = Utc::now();
+            state.locked_until = Some(Utc::now() - Duration::seconds(1));
+            (until - Utc::now()).num_seconds()
+        };
+
+        assert!((58..=60).contains(&lockout_secs(&mut limiter)));
+        assert!((118..=120).contains(&lockout_secs(&mut limiter)));
+        assert!((178..=180).contains(&lockout_secs(&mut limiter)));
+        assert!((178..=180).contains(&lockout_secs(&mut limiter)));
+
+        limiter.reset("user1");
+        assert!(limiter.locked_until("user1").is_none());
+        assert!(limiter.check("user1").is_allowed());
+    }
+
     #[test]
     fn test_rate_limiter_disabled() {
         let mut limiter = RateLimiter::new(RateLimitConfig::disabled());
@@ -0,0 +1,60 @@
+//! Delivery of OTPs and links to users.
+
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+
+/// Delivers a message to an email address or phone number.
+///
+/// Implement this for your email or SMS provider. Plugins hold one as
+/// `Arc<dyn MessageSender>` and call it whenever a code or link needs to
+/// reach a user.
+#[async_trait]
+pub trait MessageSender: Send + Sync {
+    /// Sends `body` to `to`. `subject` is set for email and `None` for SMS.
+    async fn send(&self, to: &str, subject: Option<&str>, body: &str) -> AuthResult<()>;
+}
+
+/// A sender that only logs messages at `debug` level.
+///
+/// Useful during development to see that a message would have gone out.
+/// The body is not logged, since it holds the code or link meant for the
+/// user; only the recipient, subject and body length are.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LoggingSender;
+
+impl LoggingSender {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl MessageSender for LoggingSender {
+    async fn send(&self, to: &str, subject: Option<&str>, body: &str) -> AuthResult<()> {
+        tracing::debug!(
+            to,
+            subject,
+            body_len = body.len(),
+            "message not delivered, logged with its body redacted"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_logging_sender() {
+        let sender: Arc<dyn MessageSender> = Arc::new(LoggingSender::new());
+        assert!(
+            sender
+                .send("jane@example.com", Some("Hi"), "123456")
+                .await
+                .is_ok()
+        );
+        assert!(sender.send("+15555550100", None, "123456").await.is_ok());
+    }
+}
@@ -0,0 +1,43 @@
+//! A message sender for tests.
+//!
+//! Available to other crates with the `testing` feature.
+
+use crate::MessageSender;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use std::sync::Mutex;
+
+/// A message handed to a [`RecordingSender`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentMessage {
+    pub to: String,
+    pub subject: Option<String>,
+    pub body: String,
+}
+
+/// A sender that keeps every message instead of delivering it.
+#[derive(Debug, Default)]
+pub struct RecordingSender(Mutex<Vec<SentMessage>>);
+
+impl RecordingSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the messages sent so far, oldest first.
+    pub fn sent(&self) -> Vec<SentMessage> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl MessageSender for RecordingSender {
+    async fn send(&self, to: &str, subject: Option<&str>, body: &str) -> AuthResult<()> {
+        self.0.lock().unwrap().push(SentMessage {
+            to: to.to_string(),
+            subject: subject.map(String::from),
+            body: body.to_string(),
+        });
+        Ok(())
+    }
+}
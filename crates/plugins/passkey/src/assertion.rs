@@ -0,0 +1,216 @@
+//! WebAuthn assertion verification.
+//!
+//! Only ES256 credentials are supported: [`Passkey::public_key`] must hold
+//! the base64url encoded, uncompressed SEC1 point of the P-256 key.
+
+use crate::webauthn::{
+    client_data_challenge, decode_base64url, verify_authenticator_data, verify_client_data,
+};
+use crate::{Passkey, PasskeyConfig, WebAuthnChallenge};
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::events::Event;
+use ring::signature::{ECDSA_P256_SHA256_ASN1, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+
+/// A `PublicKeyCredential` returned by `navigator.credentials.get()`,
+/// serialized with `toJSON()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertionResponse {
+    /// The credential ID (base64url).
+    pub id: String,
+    /// The authenticator's response.
+    pub response: AuthenticatorAssertion,
+}
+
+/// The `response` member of an assertion. Every field is base64url encoded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthenticatorAssertion {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    #[serde(rename = "authenticatorData")]
+    pub authenticator_data: String,
+    pub signature: String,
+    #[serde(
+        rename = "userHandle",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub user_handle: Option<String>,
+}
+
+impl AssertionResponse {
+    /// Returns the challenge the authenticator signed, for looking up the
+    /// pending [`WebAuthnChallenge`].
+    pub fn challenge(&self) -> AuthResult<String> {
+        client_data_challenge(&self.response.client_data_json)
+    }
+}
+
+/// Verifies an assertion made with `passkey` against `challenge`.
+///
+/// Returns the authenticator's new signature counter. Any mismatch, from
+/// the origin to the signature, fails with
+/// [`AuthError::InvalidCredentials`]. A valid signature whose counter did
+/// not increase fails with [`AuthError::Forbidden`] instead: the
+/// credential has probably been cloned.
+pub fn verify_assertion(
+    config: &PasskeyConfig,
+    challenge: &WebAuthnChallenge,
+    passkey: &Passkey,
+    response: &AssertionResponse,
+) -> AuthResult<i64> {
+    let client_data_hash = verify_client_data(
+        config,
+        challenge,
+        "webauthn.get",
+        &response.response.client_data_json,
+    )?;
+    let auth_data = decode_base64url(&response.response.authenticator_data)?;
+    let counter = i64::from(verify_authenticator_data(config, &auth_data)?.sign_count);
+
+    let mut signed = auth_data;
+    signed.extend_from_slice(&client_data_hash);
+    UnparsedPublicKey::new(
+        &ECDSA_P256_SHA256_ASN1,
+        decode_base64url(&passkey.public_key)?,
+    )
+    .verify(&signed, &decode_base64url(&response.response.signature)?)
+    .map_err(|_| AuthError::InvalidCredentials)?;
+
+    // Authenticators without a counter always report zero. Otherwise it
+    // must grow, or the credential may have been cloned.
+    if (counter != 0 || passkey.counter != 0) && counter <= passkey.counter {
+        return Err(AuthError::forbidden(format!(
+            "Passkey signature counter went from {} to {}",
+            passkey.counter, counter
+        )));
+    }
+    Ok(counter)
+}
+
+/// Like [`verify_assertion`], but also emits `passkey.clone_detected` on
+/// the configured event bus when the signature counter regressed.
+pub(crate) async fn verify_assertion_reporting(
+    config: &PasskeyConfig,
+    challenge: &WebAuthnChallenge,
+    passkey: &Passkey,
+    response: &AssertionResponse,
+) -> AuthResult<i64> {
+    let result = verify_assertion(config, challenge, passkey, response);
+    if let (Err(AuthError::Forbidden { .. }), Some(bus)) = (&result, &config.event_bus) {
+        bus.emit(
+            Event::simple(
+                "passkey.clone_detected",
+                serde_json::json!({
+                    "user_id": passkey.user_id,
+                    "passkey_id": passkey.id,
+                    "credential_id": passkey.credential_id,
+                    "stored_counter": passkey.counter,
+                }),
+            )
+            .with_source("passkey"),
+        )
+        .await;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestAuthenticator;
+    use base64::Engine;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+    fn config() -> PasskeyConfig {
+        PasskeyConfig::new("example.com", "Example", "https://example.com")
+    }
+
+    #[test]
+    fn test_valid_assertion() {
+        let mut authenticator = TestAuthenticator::new();
+        let passkey = authenticator.passkey("user_1", "cred_1");
+        let challenge = WebAuthnChallenge::for_authentication(60);
+        let response = authenticator.sign(
+            "cred_1",
+            &challenge.challenge,
+            "https://example.com",
+            "example.com",
+        );
+
+        assert_eq!(response.challenge().unwrap(), challenge.challenge);
+        assert_eq!(
+            verify_assertion(&config(), &challenge, &passkey, &response).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rejects_mismatches() {
+        let mut authenticator = TestAuthenticator::new();
+        let passkey = authenticator.passkey("user_1", "cred_1");
+        let challenge = WebAuthnChallenge::for_authentication(60);
+        let verify = |response: &AssertionResponse| {
+            verify_assertion(&config(), &challenge, &passkey, response)
+        };
+
+        let other_origin = authenticator.sign(
+            "cred_1",
+            &challenge.challenge,
+            "https://evil.com",
+            "example.com",
+        );
+        assert!(verify(&other_origin).is_err());
+        let other_rp = authenticator.sign(
+            "cred_1",
+            &challenge.challenge,
+            "https://example.com",
+            "evil.com",
+        );
+        assert!(verify(&other_rp).is_err());
+        let other_challenge =
+            authenticator.sign("cred_1", "other", "https://example.com", "example.com");
+        assert!(verify(&other_challenge).is_err());
+
+        let mut tampered = authenticator.sign(
+            "cred_1",
+            &challenge.challenge,
+            "https://example.com",
+            "example.com",
+        );
+        let mut auth_data = URL_SAFE_NO_PAD
+            .decode(&tampered.response.authenticator_data)
+            .unwrap();
+        auth_data[36] += 1;
+        tampered.response.authenticator_data = URL_SAFE_NO_PAD.encode(auth_data);
+        assert!(verify(&tampered).is_err());
+
+        let wrong_key = TestAuthenticator::new().passkey("user_1", "cred_1");
+        let response = authenticator.sign(
+            "cred_1",
+            &challenge.challenge,
+            "https://example.com",
+            "example.com",
+        );
+        assert!(verify_assertion(&config(), &challenge, &wrong_key, &response).is_err());
+    }
+
+    #[test]
+    fn test_rejects_counter_regression() {
+        let mut authenticator = TestAuthenticator::new();
+        let mut passkey = authenticator.passkey("user_1", "cred_1");
+        passkey.counter = 5;
+        let challenge = WebAuthnChallenge::for_authentication(60);
+        let response = authenticator.sign(
+            "cred_1",
+            &challenge.challenge,
+            "https://example.com",
+            "example.com",
+        );
+
+        assert!(matches!(
+            verify_assertion(&config(), &challenge, &passkey, &response),
+            Err(AuthError::Forbidden { .. })
+        ));
+    }
+}
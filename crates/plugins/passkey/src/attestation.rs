@@ -0,0 +1,281 @@
+//! WebAuthn registration (attestation) verification.
+//!
+//! Only ES256 credentials are accepted, matching what
+//! [`verify_assertion`](crate::verify_assertion) can check later. The
+//! attestation statement must be `none` or `packed` self attestation.
+//! Certificate-based statements are rejected: this plugin asks for
+//! `attestation: "none"`, so it has no use for them.
+
+use crate::cbor::{self, Value};
+use crate::webauthn::{
+    FLAG_ATTESTED_CREDENTIAL, FLAG_BACKED_UP, FLAG_BACKUP_ELIGIBLE, client_data_challenge,
+    decode_base64url, verify_authenticator_data, verify_client_data,
+};
+use crate::{Passkey, PasskeyConfig, WebAuthnChallenge};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use better_auth_core::error::{AuthError, AuthResult};
+use ring::signature::{ECDSA_P256_SHA256_ASN1, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+
+/// COSE algorithm identifier for ES256.
+const COSE_ALG_ES256: i128 = -7;
+
+/// A `PublicKeyCredential` returned by `navigator.credentials.create()`,
+/// serialized with `toJSON()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrationResponse {
+    /// The credential ID (base64url).
+    pub id: String,
+    /// The authenticator's response.
+    pub response: AuthenticatorAttestation,
+}
+
+/// The `response` member of a registration credential.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthenticatorAttestation {
+    /// Base64url encoded client data.
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    /// Base64url encoded CBOR attestation object.
+    #[serde(rename = "attestationObject")]
+    pub attestation_object: String,
+    /// Transports reported by the browser, e.g. `["internal", "hybrid"]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transports: Option<Vec<String>>,
+}
+
+impl RegistrationResponse {
+    /// Returns the challenge the authenticator was given, for looking up
+    /// the pending [`WebAuthnChallenge`].
+    pub fn challenge(&self) -> AuthResult<String> {
+        client_data_challenge(&self.response.client_data_json)
+    }
+}
+
+/// Verifies a registration response against a registration `challenge`
+/// and returns the new passkey, owned by the challenge's user.
+///
+/// The passkey is not stored. Any mismatch fails with
+/// [`AuthError::InvalidCredentials`].
+pub fn verify_registration(
+    config: &PasskeyConfig,
+    challenge: &WebAuthnChallenge,
+    response: &RegistrationResponse,
+) -> AuthResult<Passkey> {
+    let user_id = challenge
+        .user_id
+        .as_deref()
+        .filter(|_| challenge.challenge_type == "registration")
+        .ok_or(AuthError::InvalidCredentials)?;
+    let client_data_hash = verify_client_data(
+        config,
+        challenge,
+        "webauthn.create",
+        &response.response.client_data_json,
+    )?;
+
+    let attestation = decode_base64url(&response.response.attestation_object)?;
+    let attestation = match cbor::decode(&attestation) {
+        Some((value, [])) => value,
+        _ => return Err(AuthError::InvalidCredentials),
+    };
+    let (Some(format), Some(statement), Some(auth_data)) = (
+        attestation.get_text("fmt").and_then(Value::as_text),
+        attestation.get_text("attStmt"),
+        attestation.get_text("authData").and_then(Value::as_bytes),
+    ) else {
+        return Err(AuthError::InvalidCredentials);
+    };
+
+    let header = verify_authenticator_data(config, auth_data)?;
+    if header.flags & FLAG_ATTESTED_CREDENTIAL == 0 {
+        return Err(AuthError::InvalidCredentials);
+    }
+    let credential = parse_attested_credential(header.rest)?;
+    if decode_base64url(&response.id)? != credential.id {
+        return Err(AuthError::InvalidCredentials);
+    }
+
+    match format {
+        "none" if *statement == Value::Map(Vec::new()) => {}
+        // Self attestation: signed by the credential's own key.
+        "packed" if statement.get_text("x5c").is_none() => {
+            let (Some(COSE_ALG_ES256), Some(signature)) = (
+                statement.get_text("alg").and_then(Value::as_int),
+                statement.get_text("sig").and_then(Value::as_bytes),
+            ) else {
+                return Err(AuthError::InvalidCredentials);
+            };
+            let mut signed = auth_data.to_vec();
+            signed.extend_from_slice(&client_data_hash);
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, &credential.public_key)
+                .verify(&signed, signature)
+                .map_err(|_| AuthError::InvalidCredentials)?;
+        }
+        _ => return Err(AuthError::InvalidCredentials),
+    }
+
+    let mut passkey = Passkey::new(
+        user_id,
+        response.id.clone(),
+        URL_SAFE_NO_PAD.encode(&credential.public_key),
+    )
+    .with_backed_up(header.flags & FLAG_BACKED_UP != 0);
+    if header.flags & FLAG_BACKUP_ELIGIBLE != 0 {
+        passkey = passkey.with_device_type("multiDevice");
+    }
+    if let Some(transports) = &response.response.transports {
+        passkey = passkey.with_transports(transports.clone());
+    }
+    passkey.counter = i64::from(header.sign_count);
+    // An all-zero AAGUID means the authenticator chose not to identify itself.
+    passkey.aaguid = Some(credential.aaguid)
+        .filter(|aaguid| !aaguid.is_nil())
+        .map(|aaguid| aaguid.to_string());
+    Ok(passkey)
+}
+
+/// The attested credential data of a registration.
+struct AttestedCredential {
+    aaguid: uuid::Uuid,
+    id: Vec<u8>,
+    /// Uncompressed SEC1 point of the P-256 public key.
+    public_key: Vec<u8>,
+}
+
+/// Parses `aaguid (16) | credentialIdLength (2) | credentialId | COSE key`.
+/// Extensions may follow the key and are ignored.
+fn parse_attested_credential(data: &[u8]) -> AuthResult<AttestedCredential> {
+    if data.len() < 18 {
+        return Err(AuthError::InvalidCredentials);
+    }
+    let aaguid = uuid::Uuid::from_slice(&data[..16]).map_err(|_| AuthError::InvalidCredentials)?;
+    let id_len = usize::from(u16::from_be_bytes([data[16], data[17]]));
+    let data = &data[18..];
+    if data.len() < id_len {
+        return Err(AuthError::InvalidCredentials);
+    }
+    let (id, data) = data.split_at(id_len);
+    let (key, _) = cbor::decode(data).ok_or(AuthError::InvalidCredentials)?;
+
+    Ok(AttestedCredential {
+        aaguid,
+        id: id.to_vec(),
+        public_key: es256_public_key(&key).ok_or(AuthError::InvalidCredentials)?,
+    })
+}
+
+/// Converts a COSE EC2 P-256 key into an uncompressed SEC1 point.
+fn es256_public_key(key: &Value) -> Option<Vec<u8>> {
+    // kty 2 (EC2), alg -7 (ES256), crv 1 (P-256).
+    if key.get_int(1)?.as_int()? != 2
+        || key.get_int(3)?.as_int()? != COSE_ALG_ES256
+        || key.get_int(-1)?.as_int()? != 1
+    {
+        return None;
+    }
+    let x = key.get_int(-2)?.as_bytes().filter(|x| x.len() == 32)?;
+    let y = key.get_int(-3)?.as_bytes().filter(|y| y.len() == 32)?;
+
+    let mut point = Vec::with_capacity(65);
+    point.push(0x04);
+    point.extend_from_slice(x);
+    point.extend_from_slice(y);
+    Some(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestAuthenticator;
+
+    fn config() -> PasskeyConfig {
+        PasskeyConfig::new("example.com", "Example", "https://example.com")
+    }
+
+    #[test]
+    fn test_valid_registration() {
+        let authenticator = TestAuthenticator::new();
+        let challenge = WebAuthnChallenge::for_registration("user_1", 60);
+        let response = authenticator.register(
+            "cred_1",
+            &challenge.challenge,
+            "https://example.com",
+            "example.com",
+        );
+        assert_eq!(response.challenge().unwrap(), challenge.challenge);
+
+        let passkey = verify_registration(&config(), &challenge, &response).unwrap();
+        assert_eq!(passkey.user_id, "user_1");
+        assert_eq!(passkey.credential_id, response.id);
+        assert_eq!(
+            passkey.public_key,
+            authenticator.passkey("user_1", "cred_1").public_key
+        );
+        assert_eq!(passkey.get_transports(), vec!["internal"]);
+        assert_eq!(passkey.device_type, "multiDevice");
+        assert!(passkey.aaguid.is_none());
+    }
+
+    #[test]
+    fn test_rejects_mismatches() {
+        let authenticator = TestAuthenticator::new();
+        let challenge = WebAuthnChallenge::for_registration("user_1", 60);
+        let verify =
+            |response: &RegistrationResponse| verify_registration(&config(), &challenge, response);
+
+        let other_origin = authenticator.register(
+            "cred_1",
+            &challenge.challenge,
+            "https://evil.com",
+            "example.com",
+        );
+        assert!(verify(&other_origin).is_err());
+        let other_rp = authenticator.register(
+            "cred_1",
+            &challenge.challenge,
+            "https://example.com",
+            "evil.com",
+        );
+        assert!(verify(&other_rp).is_err());
+        let other_challenge =
+            authenticator.register("cred_1", "other", "https://example.com", "example.com");
+        assert!(verify(&other_challenge).is_err());
+
+        let mut other_id = authenticator.register(
+            "cred_1",
+            &challenge.challenge,
+            "https://example.com",
+            "example.com",
+        );
+        other_id.id = URL_SAFE_NO_PAD.encode("cred_2");
+        assert!(verify(&other_id).is_err());
+
+        // An authentication challenge cannot register a passkey.
+        let authentication = WebAuthnChallenge {
+            challenge_type: "authentication".to_string(),
+            ..challenge.clone()
+        };
+        let response = authenticator.register(
+            "cred_1",
+            &challenge.challenge,
+            "https://example.com",
+            "example.com",
+        );
+        assert!(verify_registration(&config(), &authentication, &response).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unsupported_keys() {
+        let mut key = vec![(Value::Integer(1), Value::Integer(2))];
+        key.push((Value::Integer(3), Value::Integer(-257)));
+        key.push((Value::Integer(-1), Value::Integer(1)));
+        key.push((Value::Integer(-2), Value::Bytes(vec![0; 32])));
+        key.push((Value::Integer(-3), Value::Bytes(vec![0; 32])));
+        assert!(es256_public_key(&Value::Map(key.clone())).is_none());
+
+        key[1].1 = Value::Integer(-7);
+        assert_eq!(es256_public_key(&Value::Map(key)).unwrap().len(), 65);
+    }
+}
@@ -0,0 +1,172 @@
+//! A minimal CBOR decoder for attestation objects and COSE keys.
+//!
+//! Authenticators emit CTAP2 canonical CBOR, so indefinite lengths and
+//! floats are rejected rather than supported.
+
+/// A decoded CBOR item.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Integer(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Bool(bool),
+    Null,
+}
+
+impl Value {
+    /// Looks up `key` in a map.
+    pub(crate) fn get(&self, key: &Value) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Looks up a text key in a map.
+    pub(crate) fn get_text(&self, key: &str) -> Option<&Value> {
+        self.get(&Value::Text(key.to_string()))
+    }
+
+    /// Looks up an integer key in a map.
+    pub(crate) fn get_int(&self, key: i128) -> Option<&Value> {
+        self.get(&Value::Integer(key))
+    }
+
+    pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_int(&self) -> Option<i128> {
+        match self {
+            Value::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+/// Nesting deeper than this is rejected. Attestation objects need three.
+const MAX_DEPTH: usize = 8;
+
+/// Decodes one item from the start of `input`, returning it and the bytes
+/// after it.
+pub(crate) fn decode(input: &[u8]) -> Option<(Value, &[u8])> {
+    decode_item(input, 0)
+}
+
+fn decode_item(input: &[u8], depth: usize) -> Option<(Value, &[u8])> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let (&initial, rest) = input.split_first()?;
+    let major = initial >> 5;
+    let (arg, mut rest) = read_argument(initial & 0x1f, rest)?;
+
+    let value = match major {
+        0 => Value::Integer(arg as i128),
+        1 => Value::Integer(-1 - arg as i128),
+        2 | 3 => {
+            let len = usize::try_from(arg).ok().filter(|len| *len <= rest.len())?;
+            let (bytes, tail) = rest.split_at(len);
+            rest = tail;
+            if major == 2 {
+                Value::Bytes(bytes.to_vec())
+            } else {
+                Value::Text(String::from_utf8(bytes.to_vec()).ok()?)
+            }
+        }
+        4 => {
+            // Every item takes at least one byte, which bounds the length.
+            let len = usize::try_from(arg).ok().filter(|len| *len <= rest.len())?;
+            let mut items = Vec::with_capacity(len);
+            for _ in 0..len {
+                let (item, tail) = decode_item(rest, depth + 1)?;
+                items.push(item);
+                rest = tail;
+            }
+            Value::Array(items)
+        }
+        5 => {
+            let len = usize::try_from(arg).ok().filter(|len| *len <= rest.len())?;
+            let mut entries = Vec::with_capacity(len);
+            for _ in 0..len {
+                let (key, tail) = decode_item(rest, depth + 1)?;
+                let (value, tail) = decode_item(tail, depth + 1)?;
+                entries.push((key, value));
+                rest = tail;
+            }
+            Value::Map(entries)
+        }
+        // Tags carry no meaning we need; decode the tagged item.
+        6 => return decode_item(rest, depth + 1),
+        _ => match initial & 0x1f {
+            20 => Value::Bool(false),
+            21 => Value::Bool(true),
+            22 => Value::Null,
+            _ => return None,
+        },
+    };
+    Some((value, rest))
+}
+
+/// Reads the argument encoded by the low five bits of an initial byte.
+fn read_argument(info: u8, input: &[u8]) -> Option<(u64, &[u8])> {
+    let len = match info {
+        0..=23 => return Some((u64::from(info), input)),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => return None,
+    };
+    if input.len() < len {
+        return None;
+    }
+    let (bytes, rest) = input.split_at(len);
+    let arg = bytes.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+    Some((arg, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_map() {
+        // {"fmt": "none", -1: h'0102', 3: -7} followed by a trailing byte.
+        let input = [
+            0xa3, 0x63, b'f', b'm', b't', 0x64, b'n', b'o', b'n', b'e', 0x20, 0x42, 0x01, 0x02,
+            0x03, 0x26, 0xff,
+        ];
+        let (value, rest) = decode(&input).unwrap();
+        assert_eq!(rest, [0xff]);
+        assert_eq!(value.get_text("fmt").unwrap().as_text(), Some("none"));
+        assert_eq!(value.get_int(-1).unwrap().as_bytes(), Some(&[1u8, 2][..]));
+        assert_eq!(value.get_int(3).unwrap().as_int(), Some(-7));
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        // Truncated byte string, indefinite length array, float.
+        assert!(decode(&[0x43, 0x01]).is_none());
+        assert!(decode(&[0x9f, 0x01, 0xff]).is_none());
+        assert!(decode(&[0xf9, 0x3c, 0x00]).is_none());
+        // A declared length far beyond the input.
+        assert!(decode(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_none());
+        // Nesting beyond the depth limit.
+        let mut nested = vec![0x81; 16];
+        nested.push(0x00);
+        assert!(decode(&nested).is_none());
+    }
+}
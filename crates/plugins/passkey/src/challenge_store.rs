@@ -0,0 +1,64 @@
+//! Storage for WebAuthn challenges between issuing options and verifying
+//! the authenticator's response.
+//!
+//! Challenges are keyed by their base64url value, which the authenticator
+//! echoes back in `clientDataJSON`, so verification can find the pending
+//! challenge without a cookie. Any plugin that verifies passkeys should use
+//! the store of the [`PasskeyPlugin`](crate::PasskeyPlugin) it delegates to;
+//! see [`PasskeyPlugin::verifier`](crate::PasskeyPlugin::verifier).
+
+use crate::WebAuthnChallenge;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Storage for pending WebAuthn challenges.
+#[async_trait]
+pub trait PasskeyChallengeStore: Send + Sync {
+    /// Stores a challenge, keyed by `challenge.challenge`.
+    async fn store(&self, challenge: &WebAuthnChallenge) -> AuthResult<()>;
+
+    /// Retrieves and removes a challenge.
+    ///
+    /// Must be atomic: a challenge can only be taken once, so an assertion
+    /// cannot be replayed.
+    async fn take(&self, challenge: &str) -> AuthResult<Option<WebAuthnChallenge>>;
+
+    /// Removes expired challenges.
+    async fn cleanup_expired(&self) -> AuthResult<()>;
+}
+
+/// In-memory challenge store.
+///
+/// Suitable for a single instance and for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryPasskeyChallengeStore {
+    challenges: RwLock<HashMap<String, WebAuthnChallenge>>,
+}
+
+impl InMemoryPasskeyChallengeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PasskeyChallengeStore for InMemoryPasskeyChallengeStore {
+    async fn store(&self, challenge: &WebAuthnChallenge) -> AuthResult<()> {
+        let mut challenges = self.challenges.write().unwrap();
+        challenges.insert(challenge.challenge.clone(), challenge.clone());
+        Ok(())
+    }
+
+    async fn take(&self, challenge: &str) -> AuthResult<Option<WebAuthnChallenge>> {
+        let mut challenges = self.challenges.write().unwrap();
+        Ok(challenges.remove(challenge))
+    }
+
+    async fn cleanup_expired(&self) -> AuthResult<()> {
+        let mut challenges = self.challenges.write().unwrap();
+        challenges.retain(|_, challenge| !challenge.is_expired());
+        Ok(())
+    }
+}
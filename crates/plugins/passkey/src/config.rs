@@ -1,5 +1,12 @@
 //! Configuration for the Passkey plugin.
 
+use crate::PasskeyStore;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::events::EventBus;
+use better_auth_core::session_token::SessionTokenStrategy;
+use better_auth_core::traits::StorageAdapter;
+use std::sync::Arc;
+
 /// Authenticator selection criteria.
 #[derive(Debug, Clone)]
 pub struct AuthenticatorSelection {
@@ -67,7 +74,7 @@ impl Default for AdvancedOptions {
 }
 
 /// Configuration for the Passkey plugin.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct PasskeyConfig {
     /// Relying Party ID (domain).
     pub rp_id: String,
@@ -79,6 +86,17 @@ pub struct PasskeyConfig {
     pub authenticator_selection: Option<AuthenticatorSelection>,
     /// Advanced options.
     pub advanced: AdvancedOptions,
+    /// Storage for users and sessions. Together with `passkey_storage`,
+    /// enables registration and sign-in; without both the routes return
+    /// placeholder responses.
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    /// How tokens for the sessions it signs users into are generated.
+    /// Use the app's strategy. Default: opaque tokens.
+    pub session_tokens: SessionTokenStrategy,
+    /// Storage for passkey credentials.
+    pub passkey_storage: Option<Arc<dyn PasskeyStore>>,
+    /// Event bus for security events such as `passkey.clone_detected`.
+    pub event_bus: Option<Arc<EventBus>>,
 }
 
 impl Default for PasskeyConfig {
@@ -89,6 +107,10 @@ impl Default for PasskeyConfig {
             origin: "http://localhost".to_string(),
             authenticator_selection: None,
             advanced: AdvancedOptions::default(),
+            storage: None,
+            session_tokens: SessionTokenStrategy::default(),
+            passkey_storage: None,
+            event_bus: None,
         }
     }
 }
@@ -119,4 +141,97 @@ impl PasskeyConfig {
         self.advanced.webauthn_challenge_cookie = name.into();
         self
     }
+
+    /// Sets the storage adapter used to resolve sessions and sign users in.
+    pub fn storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Sets how session tokens are generated.
+    pub fn session_tokens(mut self, strategy: SessionTokenStrategy) -> Self {
+        self.session_tokens = strategy;
+        self
+    }
+
+    /// Sets the passkey credential storage.
+    pub fn passkey_storage(mut self, storage: Arc<dyn PasskeyStore>) -> Self {
+        self.passkey_storage = Some(storage);
+        self
+    }
+
+    /// Sets the event bus used to emit security events.
+    pub fn event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// Checks the configuration, reporting every problem found.
+    ///
+    /// WebAuthn requires the origin's host to be the RP ID or a subdomain
+    /// of it, and the origin to be HTTPS unless it is `localhost`.
+    pub fn validate(&self) -> AuthResult<()> {
+        let mut issues = Vec::new();
+
+        if self.rp_id.trim().is_empty() {
+            issues.push("rp_id must not be empty".to_string());
+        }
+        if self.rp_name.trim().is_empty() {
+            issues.push("rp_name must not be empty".to_string());
+        }
+
+        match origin_host(&self.origin) {
+            Some((scheme, host)) => {
+                let rp_id = self.rp_id.to_lowercase();
+                if host != rp_id && !host.ends_with(&format!(".{}", rp_id)) {
+                    issues.push(format!(
+                        "origin '{}' does not match rp_id '{}'",
+                        self.origin, self.rp_id
+                    ));
+                }
+                if scheme != "https" && host != "localhost" {
+                    issues.push(format!("origin '{}' must use https", self.origin));
+                }
+            }
+            None => issues.push(format!("origin '{}' is not a valid URL", self.origin)),
+        }
+
+        if self.storage.is_some() != self.passkey_storage.is_some() {
+            issues.push("storage and passkey_storage must be set together".to_string());
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(AuthError::invalid_config("passkey", issues))
+        }
+    }
+}
+
+impl std::fmt::Debug for PasskeyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasskeyConfig")
+            .field("rp_id", &self.rp_id)
+            .field("rp_name", &self.rp_name)
+            .field("origin", &self.origin)
+            .field("authenticator_selection", &self.authenticator_selection)
+            .field("advanced", &self.advanced)
+            .field("storage", &self.storage.is_some())
+            .field("session_tokens", &self.session_tokens)
+            .field("passkey_storage", &self.passkey_storage.is_some())
+            .field("event_bus", &self.event_bus.is_some())
+            .finish()
+    }
+}
+
+/// Splits an origin like `https://app.example.com:8443` into its scheme and
+/// lowercase host.
+fn origin_host(origin: &str) -> Option<(String, String)> {
+    let (scheme, rest) = origin.split_once("://")?;
+    let authority = rest.split('/').next()?;
+    let host = authority.rsplit_once(':').map_or(authority, |(host, _)| host);
+    if host.is_empty() {
+        return None;
+    }
+    Some((scheme.to_lowercase(), host.to_lowercase()))
 }
@@ -1,8 +1,177 @@
 //! Request handlers for the Passkey plugin.
 
+use crate::assertion::verify_assertion_reporting;
+use crate::webauthn::{
+    AuthenticationOptions, CHALLENGE_TIMEOUT, PubKeyCredParam, RegistrationOptions,
+    RelyingParty, UserEntity,
+};
+use crate::{
+    AssertionResponse, PasskeyChallengeStore, PasskeyConfig, PasskeyStore, RegistrationResponse,
+    WebAuthnChallenge, verify_registration,
+};
 use async_trait::async_trait;
-use better_auth_core::router::{Request, RequestHandler, Response};
-use serde::{Deserialize, Serialize};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::router::{CookieOptions, Request, RequestHandler, Response};
+use better_auth_core::session::{SESSION_COOKIE, SessionResolver, end_replaced_session};
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::{AuthFactor, Session, User};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Everything the handlers need to run real WebAuthn ceremonies. Without
+/// it they return placeholder responses.
+#[derive(Clone)]
+pub(crate) struct Ceremonies {
+    pub(crate) config: PasskeyConfig,
+    pub(crate) challenges: Arc<dyn PasskeyChallengeStore>,
+    pub(crate) storage: Arc<dyn StorageAdapter>,
+    pub(crate) passkeys: Arc<dyn PasskeyStore>,
+}
+
+impl Ceremonies {
+    /// Resolves the user of the request's session.
+    async fn session_user(&self, req: &Request) -> AuthResult<User> {
+        let session = SessionResolver::new(self.storage.clone())
+            .resolve_request(req)
+            .await?
+            .ok_or(AuthError::SessionNotFound)?
+            .session;
+        self.storage
+            .get_user_by_id(&session.user_id)
+            .await?
+            .ok_or(AuthError::UserNotFound)
+    }
+
+    /// Takes the pending challenge of type `challenge_type`.
+    async fn take_challenge(&self, challenge: &str, challenge_type: &str) -> AuthResult<WebAuthnChallenge> {
+        self.challenges
+            .take(challenge)
+            .await?
+            .filter(|c| !c.is_expired() && c.challenge_type == challenge_type)
+            .ok_or(AuthError::InvalidCredentials)
+    }
+
+    async fn registration_options(&self, req: &Request) -> AuthResult<RegistrationOptions> {
+        let user = self.session_user(req).await?;
+        let challenge = WebAuthnChallenge::for_registration(&user.id, CHALLENGE_TIMEOUT);
+        self.challenges.store(&challenge).await?;
+
+        Ok(RegistrationOptions {
+            challenge: challenge.challenge,
+            rp: RelyingParty {
+                id: self.config.rp_id.clone(),
+                name: self.config.rp_name.clone(),
+            },
+            user: UserEntity {
+                id: URL_SAFE_NO_PAD.encode(&user.id),
+                name: user.email.clone(),
+                display_name: user.name.clone().unwrap_or_else(|| user.email.clone()),
+            },
+            // Only ES256 assertions can be verified.
+            pub_key_cred_params: vec![PubKeyCredParam::es256()],
+            authenticator_selection: self.config.authenticator_selection.as_ref().map(|s| {
+                crate::AuthenticatorSelection {
+                    authenticator_attachment: s.authenticator_attachment.clone(),
+                    resident_key: s.resident_key.clone(),
+                    user_verification: s.user_verification.clone(),
+                }
+            }),
+            timeout: (CHALLENGE_TIMEOUT * 1000) as u32,
+            attestation: "none".to_string(),
+        })
+    }
+
+    async fn authentication_options(&self) -> AuthResult<AuthenticationOptions> {
+        let challenge = WebAuthnChallenge::for_authentication(CHALLENGE_TIMEOUT);
+        self.challenges.store(&challenge).await?;
+
+        Ok(AuthenticationOptions {
+            challenge: challenge.challenge,
+            rp_id: self.config.rp_id.clone(),
+            allow_credentials: None,
+            user_verification: self
+                .config
+                .authenticator_selection
+                .as_ref()
+                .map_or("preferred", |s| s.user_verification.as_str())
+                .to_string(),
+            timeout: (CHALLENGE_TIMEOUT * 1000) as u32,
+        })
+    }
+
+    /// Verifies a registration for the session user and stores the passkey.
+    async fn register(&self, req: &Request, body: AddPasskeyRequest) -> AuthResult<crate::Passkey> {
+        let user = self.session_user(req).await?;
+        let response = body.response.ok_or_else(|| AuthError::MissingField {
+            field: "response".to_string(),
+        })?;
+        let challenge = self.take_challenge(&response.challenge()?, "registration").await?;
+        if challenge.user_id.as_deref() != Some(user.id.as_str()) {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let mut passkey = verify_registration(&self.config, &challenge, &response)?;
+        if let Some(name) = body.name {
+            passkey = passkey.with_name(name);
+        }
+        self.passkeys.create_passkey(&passkey).await
+    }
+
+    /// Verifies an assertion and creates a session for the passkey's owner.
+    async fn sign_in(
+        &self,
+        req: &Request,
+        body: SignInPasskeyRequest,
+    ) -> AuthResult<(User, Session)> {
+        let response = body.response.ok_or_else(|| AuthError::MissingField {
+            field: "response".to_string(),
+        })?;
+        let challenge = self.take_challenge(&response.challenge()?, "authentication").await?;
+        let mut passkey = self
+            .passkeys
+            .get_passkey_by_credential_id(&response.id)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        // Challenges issued for a specific user only sign that user in.
+        if challenge.user_id.as_ref().is_some_and(|id| *id != passkey.user_id) {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        let counter =
+            verify_assertion_reporting(&self.config, &challenge, &passkey, &response).await?;
+        passkey.increment_counter(counter);
+        self.passkeys.update_passkey(&passkey).await?;
+
+        let user = self
+            .storage
+            .get_user_by_id(&passkey.user_id)
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+        let mut session = self.config.session_tokens.new_session(user.id.clone());
+        session.record_factor(AuthFactor::Passkey);
+        let session = self.storage.create_session(&session).await?;
+        end_replaced_session(self.storage.as_ref(), req).await?;
+        Ok((user, session))
+    }
+}
+
+/// Converts an error into the plugin's error body.
+fn error_response(err: AuthError) -> Response {
+    let code = match &err {
+        AuthError::InvalidCredentials => "VERIFICATION_FAILED",
+        AuthError::MissingField { .. } => "MISSING_RESPONSE",
+        AuthError::SessionNotFound => "SESSION_NOT_FOUND",
+        AuthError::UserNotFound => "USER_NOT_FOUND",
+        AuthError::DuplicateEntry { .. } => "PASSKEY_EXISTS",
+        AuthError::Forbidden { .. } => "CLONE_DETECTED",
+        _ => err.error_code(),
+    };
+    Response::new(err.status_code()).json(serde_json::json!({
+        "error": { "code": code, "message": err.to_string() }
+    }))
+}
 
 /// Request body for adding a passkey.
 #[derive(Debug, Deserialize)]
@@ -11,22 +180,36 @@ pub struct AddPasskeyRequest {
     #[serde(rename = "authenticatorAttachment")]
     pub authenticator_attachment: Option<String>,
     /// The attestation response from the client.
-    pub response: Option<serde_json::Value>,
+    pub response: Option<RegistrationResponse>,
 }
 
 /// Handler for POST /passkey/add-passkey
-pub struct AddPasskeyHandler;
+pub struct AddPasskeyHandler {
+    pub(crate) ceremonies: Option<Ceremonies>,
+}
 
 #[async_trait]
 impl RequestHandler for AddPasskeyHandler {
     async fn handle(&self, req: Request) -> Response {
         let body: Option<AddPasskeyRequest> = req.json();
-        
-        // In a real implementation, this would:
-        // 1. Verify the attestation response
-        // 2. Extract the public key and credential ID
-        // 3. Store the passkey in the database
-        
+
+        if let Some(ceremonies) = &self.ceremonies {
+            let Some(body) = body else {
+                return Response::bad_request().json(serde_json::json!({
+                    "error": { "code": "INVALID_REQUEST", "message": "Invalid request body" }
+                }));
+            };
+            return match ceremonies.register(&req, body).await {
+                Ok(passkey) => Response::ok().json(serde_json::json!({
+                    "id": passkey.id,
+                    "name": passkey.name,
+                    "credentialId": passkey.credential_id,
+                    "createdAt": passkey.created_at
+                })),
+                Err(err) => error_response(err),
+            };
+        }
+
         Response::ok().json(serde_json::json!({
             "id": "passkey_placeholder",
             "name": body.and_then(|b| b.name).unwrap_or_else(|| "My Passkey".to_string()),
@@ -42,23 +225,42 @@ pub struct SignInPasskeyRequest {
     #[serde(rename = "autoFill")]
     pub auto_fill: Option<bool>,
     /// The assertion response from the client.
-    pub response: Option<serde_json::Value>,
+    pub response: Option<AssertionResponse>,
 }
 
 /// Handler for POST /sign-in/passkey
-pub struct SignInPasskeyHandler;
+pub struct SignInPasskeyHandler {
+    pub(crate) ceremonies: Option<Ceremonies>,
+}
 
 #[async_trait]
 impl RequestHandler for SignInPasskeyHandler {
     async fn handle(&self, req: Request) -> Response {
-        let _body: Option<SignInPasskeyRequest> = req.json();
-        
-        // In a real implementation, this would:
-        // 1. Verify the assertion response
-        // 2. Find the user by credential ID
-        // 3. Update the counter
-        // 4. Create a session
-        
+        let body: Option<SignInPasskeyRequest> = req.json();
+
+        if let Some(ceremonies) = &self.ceremonies {
+            let Some(body) = body else {
+                return Response::bad_request().json(serde_json::json!({
+                    "error": { "code": "INVALID_REQUEST", "message": "Invalid request body" }
+                }));
+            };
+            return match ceremonies.sign_in(&req, body).await {
+                Ok((user, session)) => Response::ok()
+                    .json(serde_json::json!({
+                        "user": {
+                            "id": user.id,
+                            "email": user.email
+                        },
+                        "session": {
+                            "id": session.id,
+                            "token": session.token
+                        }
+                    }))
+                    .cookie(SESSION_COOKIE, &session.token, CookieOptions::secure()),
+                Err(err) => error_response(err),
+            };
+        }
+
         Response::ok().json(serde_json::json!({
             "user": {
                 "id": "user_placeholder",
@@ -158,13 +360,20 @@ impl RequestHandler for UpdatePasskeyHandler {
 }
 
 /// Handler for POST /passkey/generate-registration-options
-pub struct GenerateRegistrationOptionsHandler;
+pub struct GenerateRegistrationOptionsHandler {
+    pub(crate) ceremonies: Option<Ceremonies>,
+}
 
 #[async_trait]
 impl RequestHandler for GenerateRegistrationOptionsHandler {
-    async fn handle(&self, _req: Request) -> Response {
-        // In a real implementation, this would generate proper WebAuthn options
-        
+    async fn handle(&self, req: Request) -> Response {
+        if let Some(ceremonies) = &self.ceremonies {
+            return match ceremonies.registration_options(&req).await {
+                Ok(options) => Response::ok().json(options),
+                Err(err) => error_response(err),
+            };
+        }
+
         Response::ok().json(serde_json::json!({
             "challenge": "random_challenge_base64",
             "rp": {
@@ -187,13 +396,20 @@ impl RequestHandler for GenerateRegistrationOptionsHandler {
 }
 
 /// Handler for POST /passkey/generate-authentication-options
-pub struct GenerateAuthenticationOptionsHandler;
+pub struct GenerateAuthenticationOptionsHandler {
+    pub(crate) ceremonies: Option<Ceremonies>,
+}
 
 #[async_trait]
 impl RequestHandler for GenerateAuthenticationOptionsHandler {
     async fn handle(&self, _req: Request) -> Response {
-        // In a real implementation, this would generate proper WebAuthn options
-        
+        if let Some(ceremonies) = &self.ceremonies {
+            return match ceremonies.authentication_options().await {
+                Ok(options) => Response::ok().json(options),
+                Err(err) => error_response(err),
+            };
+        }
+
         Response::ok().json(serde_json::json!({
             "challenge": "random_challenge_base64",
             "rpId": "localhost",
@@ -3,15 +3,33 @@
 //! This plugin provides passkey (WebAuthn) authentication support.
 //! Passkeys are a secure, passwordless authentication method using
 //! cryptographic key pairs.
+//!
+//! Registration and sign-in are verified when the config has both
+//! `storage` and `passkey_storage`: client data, RP ID hash, flags and
+//! signatures are checked against the pending [`WebAuthnChallenge`], and
+//! sign-ins whose signature counter does not grow are rejected.
 
 mod config;
 mod schema;
 mod handlers;
 mod webauthn;
+mod assertion;
+mod attestation;
+mod cbor;
+mod challenge_store;
+mod storage;
+mod verifier;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use config::PasskeyConfig;
 pub use schema::{Passkey, PasskeySchema};
-pub use webauthn::{WebAuthnChallenge, AuthenticatorSelection};
+pub use webauthn::{WebAuthnChallenge, AuthenticatorSelection, AuthenticationOptions, AllowCredential};
+pub use assertion::{AssertionResponse, AuthenticatorAssertion, verify_assertion};
+pub use attestation::{AuthenticatorAttestation, RegistrationResponse, verify_registration};
+pub use challenge_store::{InMemoryPasskeyChallengeStore, PasskeyChallengeStore};
+pub use storage::PasskeyStore;
+pub use verifier::PasskeyVerifier;
 
 use async_trait::async_trait;
 use better_auth_core::context::AuthContext;
@@ -21,22 +39,59 @@ use better_auth_core::schema::SchemaBuilder;
 use better_auth_core::traits::{AuthPlugin, SchemaProvider};
 use better_auth_core::types::User;
 use better_auth_events_sdk::{EventDefinition, EventProvider};
+use std::sync::Arc;
 
 /// The Passkey authentication plugin.
 pub struct PasskeyPlugin {
     config: PasskeyConfig,
+    challenge_store: Arc<dyn PasskeyChallengeStore>,
 }
 
 impl PasskeyPlugin {
-    /// Creates a new Passkey plugin with the given configuration.
+    /// Creates a new Passkey plugin with the given configuration and an
+    /// in-memory challenge store.
     pub fn new(config: PasskeyConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            challenge_store: Arc::new(InMemoryPasskeyChallengeStore::new()),
+        }
+    }
+
+    /// Sets the challenge store. Multi-instance deployments need one on
+    /// shared storage.
+    pub fn with_challenge_store(mut self, store: Arc<dyn PasskeyChallengeStore>) -> Self {
+        self.challenge_store = store;
+        self
     }
 
     /// Gets the plugin configuration.
     pub fn config(&self) -> &PasskeyConfig {
         &self.config
     }
+
+    /// Gets the challenge store.
+    pub fn challenge_store(&self) -> Arc<dyn PasskeyChallengeStore> {
+        self.challenge_store.clone()
+    }
+
+    /// Creates a verifier for asking a known user for one of their
+    /// passkeys, e.g. as a second factor.
+    ///
+    /// The verifier shares this plugin's configuration and challenge store,
+    /// so a challenge issued through it is pending in the same place as the
+    /// plugin's own.
+    pub fn verifier(&self, passkeys: Arc<dyn PasskeyStore>) -> PasskeyVerifier {
+        PasskeyVerifier::new(self.config.clone(), self.challenge_store.clone(), passkeys)
+    }
+
+    fn ceremonies(&self) -> Option<handlers::Ceremonies> {
+        Some(handlers::Ceremonies {
+            config: self.config.clone(),
+            challenges: self.challenge_store.clone(),
+            storage: self.config.storage.clone()?,
+            passkeys: self.config.passkey_storage.clone()?,
+        })
+    }
 }
 
 impl Default for PasskeyPlugin {
@@ -58,6 +113,11 @@ impl EventProvider for PasskeyPlugin {
                 "Emitted when authentication with passkey succeeds",
                 "passkey",
             ),
+            EventDefinition::simple(
+                "passkey.clone_detected",
+                "Emitted when a passkey's signature counter fails to increase",
+                "passkey",
+            ),
             EventDefinition::simple(
                 "passkey.deleted",
                 "Emitted when a passkey is deleted",
@@ -86,6 +146,10 @@ impl AuthPlugin for PasskeyPlugin {
         "Passkey (WebAuthn) Authentication"
     }
 
+    fn validate_config(&self) -> AuthResult<()> {
+        self.config.validate()
+    }
+
     fn define_schema(&self, builder: &mut SchemaBuilder) {
         for model in PasskeySchema::schema() {
             builder.add_model_mut(model);
@@ -93,9 +157,11 @@ impl AuthPlugin for PasskeyPlugin {
     }
 
     fn register_routes(&self, router: &mut Router) {
+        let ceremonies = self.ceremonies();
+
         // POST /passkey/add-passkey
         router.route(
-            Route::new(Method::POST, "/passkey/add-passkey", handlers::AddPasskeyHandler)
+            Route::new(Method::POST, "/passkey/add-passkey", handlers::AddPasskeyHandler { ceremonies: ceremonies.clone() })
                 .summary("Register a passkey")
                 .description("Registers a new passkey for the authenticated user.")
                 .tag("passkey")
@@ -104,7 +170,7 @@ impl AuthPlugin for PasskeyPlugin {
 
         // POST /sign-in/passkey
         router.route(
-            Route::new(Method::POST, "/sign-in/passkey", handlers::SignInPasskeyHandler)
+            Route::new(Method::POST, "/sign-in/passkey", handlers::SignInPasskeyHandler { ceremonies: ceremonies.clone() })
                 .summary("Sign in with passkey")
                 .description("Authenticates a user using their passkey.")
                 .tag("passkey"),
@@ -139,7 +205,7 @@ impl AuthPlugin for PasskeyPlugin {
 
         // POST /passkey/generate-registration-options
         router.route(
-            Route::new(Method::POST, "/passkey/generate-registration-options", handlers::GenerateRegistrationOptionsHandler)
+            Route::new(Method::POST, "/passkey/generate-registration-options", handlers::GenerateRegistrationOptionsHandler { ceremonies: ceremonies.clone() })
                 .summary("Generate registration options")
                 .description("Generates WebAuthn registration options for passkey creation.")
                 .tag("passkey")
@@ -148,7 +214,7 @@ impl AuthPlugin for PasskeyPlugin {
 
         // POST /passkey/generate-authentication-options
         router.route(
-            Route::new(Method::POST, "/passkey/generate-authentication-options", handlers::GenerateAuthenticationOptionsHandler)
+            Route::new(Method::POST, "/passkey/generate-authentication-options", handlers::GenerateAuthenticationOptionsHandler { ceremonies })
                 .summary("Generate authentication options")
                 .description("Generates WebAuthn authentication options for passkey sign-in.")
                 .tag("passkey"),
@@ -158,11 +224,27 @@ impl AuthPlugin for PasskeyPlugin {
     async fn on_after_signup(&self, _ctx: &AuthContext, _user: &User) -> AuthResult<()> {
         Ok(())
     }
+
+    async fn export_user_data(&self, _ctx: &AuthContext, user_id: &str) -> AuthResult<serde_json::Value> {
+        let Some(passkeys) = &self.config.passkey_storage else {
+            return Ok(serde_json::Value::Null);
+        };
+        let passkeys = passkeys.get_user_passkeys(user_id).await?;
+        Ok(serde_json::json!({ "passkey": passkeys }))
+    }
+
+    async fn on_before_user_delete(&self, _ctx: &AuthContext, user_id: &str) -> AuthResult<()> {
+        match &self.config.passkey_storage {
+            Some(passkeys) => passkeys.delete_user_passkeys(user_id).await,
+            None => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use better_auth_core::events::EventBus;
 
     #[test]
     fn test_plugin_creation() {
@@ -178,4 +260,154 @@ mod tests {
         assert_eq!(plugin.config().rp_id, "example.com");
         assert_eq!(plugin.config().rp_name, "Example App");
     }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(PasskeyConfig::default().validate().is_ok());
+        assert!(
+            PasskeyConfig::new("example.com", "Example", "https://app.example.com:8443")
+                .validate()
+                .is_ok()
+        );
+
+        let config = PasskeyConfig::new("example.com", "Example", "http://evil.com");
+        let Err(better_auth_core::error::AuthError::InvalidPluginConfig { issues }) =
+            config.validate()
+        else {
+            panic!("expected config issues");
+        };
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("does not match rp_id"));
+    }
+
+    /// Routes with storage, a user with a session, and that session's token.
+    async fn ceremony_setup() -> (Router, String, Arc<EventBus>) {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::traits::StorageAdapter;
+        use better_auth_core::types::Session;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let user = storage
+            .create_user(&User::new("user_1".to_string(), "jane@example.com".to_string()))
+            .await
+            .unwrap();
+        let session = storage.create_session(&Session::new(user.id.clone())).await.unwrap();
+        let bus = Arc::new(EventBus::new());
+        let plugin = PasskeyPlugin::new(
+            PasskeyConfig::new("example.com", "Example", "https://example.com")
+                .storage(storage)
+                .passkey_storage(Arc::new(testing::MemoryPasskeys::default()))
+                .event_bus(bus.clone()),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        (router, session.token, bus)
+    }
+
+    async fn call(
+        router: &Router,
+        path: &str,
+        token: Option<&str>,
+        body: serde_json::Value,
+    ) -> better_auth_core::router::Response {
+        use better_auth_core::router::Request;
+
+        let route = router.routes().find(|r| r.path == path).unwrap();
+        let mut req = Request::new(Method::POST, path);
+        if let Some(token) = token {
+            req.headers.insert("authorization".to_string(), format!("Bearer {}", token));
+        }
+        req.body = Some(body);
+        route.handler.handle(req).await
+    }
+
+    /// Signs in through fresh authentication options.
+    async fn sign_in(
+        router: &Router,
+        authenticator: &mut testing::TestAuthenticator,
+        credential_id: &str,
+    ) -> better_auth_core::router::Response {
+        let options = call(router, "/passkey/generate-authentication-options", None, serde_json::json!({})).await;
+        let challenge = options.body.unwrap()["challenge"].as_str().unwrap().to_string();
+        let assertion = authenticator.sign(credential_id, &challenge, "https://example.com", "example.com");
+        call(router, "/sign-in/passkey", None, serde_json::json!({ "response": assertion })).await
+    }
+
+    #[tokio::test]
+    async fn test_register_and_sign_in() {
+        let (router, token, _) = ceremony_setup().await;
+        let mut authenticator = testing::TestAuthenticator::new();
+
+        let options = call(&router, "/passkey/generate-registration-options", Some(&token), serde_json::json!({})).await;
+        assert_eq!(options.status, 200);
+        let options = options.body.unwrap();
+        assert_eq!(options["rp"]["id"], "example.com");
+        let registration = authenticator.register(
+            "cred_1",
+            options["challenge"].as_str().unwrap(),
+            "https://example.com",
+            "example.com",
+        );
+        let added = call(
+            &router,
+            "/passkey/add-passkey",
+            Some(&token),
+            serde_json::json!({ "name": "Laptop", "response": registration }),
+        )
+        .await;
+        assert_eq!(added.status, 200);
+        assert_eq!(added.body.unwrap()["name"], "Laptop");
+
+        let options = call(&router, "/passkey/generate-authentication-options", None, serde_json::json!({})).await;
+        let challenge = options.body.unwrap()["challenge"].as_str().unwrap().to_string();
+        let assertion = authenticator.sign(&registration.id, &challenge, "https://example.com", "example.com");
+        let signed_in = call(&router, "/sign-in/passkey", None, serde_json::json!({ "response": assertion })).await;
+        assert_eq!(signed_in.status, 200);
+        let body = signed_in.body.unwrap();
+        assert_eq!(body["user"]["id"], "user_1");
+        assert!(body["session"]["token"].is_string());
+
+        // The challenge is gone, so the assertion cannot be replayed.
+        let replayed = call(&router, "/sign-in/passkey", None, serde_json::json!({ "response": assertion })).await;
+        assert_eq!(replayed.status, 401);
+        assert_eq!(replayed.body.unwrap()["error"]["code"], "VERIFICATION_FAILED");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_counter_going_backwards() {
+        let (router, token, bus) = ceremony_setup().await;
+        let mut authenticator = testing::TestAuthenticator::new();
+
+        let options = call(&router, "/passkey/generate-registration-options", Some(&token), serde_json::json!({})).await;
+        let registration = authenticator.register(
+            "cred_1",
+            options.body.unwrap()["challenge"].as_str().unwrap(),
+            "https://example.com",
+            "example.com",
+        );
+        call(&router, "/passkey/add-passkey", Some(&token), serde_json::json!({ "response": registration })).await;
+
+        authenticator.counter = 10;
+        let response = sign_in(&router, &mut authenticator, &registration.id).await;
+        assert_eq!(response.status, 200);
+
+        // A clone still at the old counter is refused and reported.
+        authenticator.counter = 5;
+        let response = sign_in(&router, &mut authenticator, &registration.id).await;
+        assert_eq!(response.status, 403);
+        assert_eq!(response.body.unwrap()["error"]["code"], "CLONE_DETECTED");
+
+        let events = bus.events_of_type("passkey.clone_detected").await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload["credential_id"], registration.id);
+        assert_eq!(events[0].payload["stored_counter"], 11);
+    }
+
+    #[tokio::test]
+    async fn test_registration_requires_session() {
+        let (router, _, _) = ceremony_setup().await;
+        let options = call(&router, "/passkey/generate-registration-options", None, serde_json::json!({})).await;
+        assert_eq!(options.status, 404);
+        assert_eq!(options.body.unwrap()["error"]["code"], "SESSION_NOT_FOUND");
+    }
 }
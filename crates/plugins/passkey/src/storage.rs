@@ -0,0 +1,29 @@
+//! Storage for registered passkeys.
+
+use crate::Passkey;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+
+/// Storage for passkey credentials.
+///
+/// Adapters implement this trait to persist the `passkey` model.
+#[async_trait]
+pub trait PasskeyStore: Send + Sync {
+    /// Stores a newly registered passkey.
+    async fn create_passkey(&self, passkey: &Passkey) -> AuthResult<Passkey>;
+
+    /// Gets every passkey registered by a user.
+    async fn get_user_passkeys(&self, user_id: &str) -> AuthResult<Vec<Passkey>>;
+
+    /// Gets a passkey by its base64url credential ID.
+    async fn get_passkey_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> AuthResult<Option<Passkey>>;
+
+    /// Updates a passkey, e.g. its signature counter after an assertion.
+    async fn update_passkey(&self, passkey: &Passkey) -> AuthResult<Passkey>;
+
+    /// Deletes every passkey registered by a user.
+    async fn delete_user_passkeys(&self, user_id: &str) -> AuthResult<()>;
+}
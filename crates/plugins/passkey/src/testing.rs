@@ -0,0 +1,185 @@
+//! A software authenticator and an in-memory passkey store for tests.
+//!
+//! Available to other crates with the `testing` feature.
+
+use crate::webauthn::{
+    FLAG_ATTESTED_CREDENTIAL, FLAG_BACKUP_ELIGIBLE, FLAG_USER_PRESENT, FLAG_USER_VERIFIED,
+};
+use crate::{
+    AssertionResponse, AuthenticatorAssertion, AuthenticatorAttestation, Passkey, PasskeyStore,
+    RegistrationResponse,
+};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use better_auth_core::error::AuthResult;
+use ring::digest::{SHA256, digest};
+use ring::rand::SystemRandom;
+use ring::signature::{ECDSA_P256_SHA256_ASN1_SIGNING, EcdsaKeyPair, KeyPair};
+use std::sync::Mutex;
+
+/// Passkeys held in memory.
+#[derive(Default)]
+pub struct MemoryPasskeys(Mutex<Vec<Passkey>>);
+
+#[async_trait]
+impl PasskeyStore for MemoryPasskeys {
+    async fn create_passkey(&self, passkey: &Passkey) -> AuthResult<Passkey> {
+        self.0.lock().unwrap().push(passkey.clone());
+        Ok(passkey.clone())
+    }
+
+    async fn get_user_passkeys(&self, user_id: &str) -> AuthResult<Vec<Passkey>> {
+        let passkeys = self.0.lock().unwrap();
+        Ok(passkeys
+            .iter()
+            .filter(|p| p.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_passkey_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> AuthResult<Option<Passkey>> {
+        let passkeys = self.0.lock().unwrap();
+        Ok(passkeys
+            .iter()
+            .find(|p| p.credential_id == credential_id)
+            .cloned())
+    }
+
+    async fn update_passkey(&self, passkey: &Passkey) -> AuthResult<Passkey> {
+        let mut passkeys = self.0.lock().unwrap();
+        let stored = passkeys.iter_mut().find(|p| p.id == passkey.id).unwrap();
+        *stored = passkey.clone();
+        Ok(passkey.clone())
+    }
+
+    async fn delete_user_passkeys(&self, user_id: &str) -> AuthResult<()> {
+        self.0.lock().unwrap().retain(|p| p.user_id != user_id);
+        Ok(())
+    }
+}
+
+/// An authenticator holding one P-256 key in memory.
+pub struct TestAuthenticator {
+    key: EcdsaKeyPair,
+    pub counter: u32,
+}
+
+impl Default for TestAuthenticator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestAuthenticator {
+    /// Creates an authenticator with a fresh key and a zero counter.
+    pub fn new() -> Self {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+            .unwrap();
+        Self { key, counter: 0 }
+    }
+
+    /// Returns a passkey for this authenticator, owned by `user_id`.
+    pub fn passkey(&self, user_id: &str, credential_id: &str) -> Passkey {
+        Passkey::new(
+            user_id,
+            credential_id,
+            URL_SAFE_NO_PAD.encode(self.key.public_key().as_ref()),
+        )
+    }
+
+    /// Creates a credential with `none` attestation as if for `origin` and
+    /// `rp_id`. The response's ID is `credential_id`, base64url encoded.
+    pub fn register(
+        &self,
+        credential_id: &str,
+        challenge: &str,
+        origin: &str,
+        rp_id: &str,
+    ) -> RegistrationResponse {
+        let client_data = client_data("webauthn.create", challenge, origin);
+        let mut auth_data = digest(&SHA256, rp_id.as_bytes()).as_ref().to_vec();
+        auth_data.push(
+            FLAG_USER_PRESENT
+                | FLAG_USER_VERIFIED
+                | FLAG_BACKUP_ELIGIBLE
+                | FLAG_ATTESTED_CREDENTIAL,
+        );
+        auth_data.extend_from_slice(&self.counter.to_be_bytes());
+        auth_data.extend_from_slice(&[0; 16]);
+        auth_data.extend_from_slice(&(credential_id.len() as u16).to_be_bytes());
+        auth_data.extend_from_slice(credential_id.as_bytes());
+
+        // {1: 2, 3: -7, -1: 1, -2: x, -3: y}
+        let point = self.key.public_key().as_ref();
+        auth_data.extend_from_slice(&[0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20]);
+        auth_data.extend_from_slice(&point[1..33]);
+        auth_data.extend_from_slice(&[0x22, 0x58, 0x20]);
+        auth_data.extend_from_slice(&point[33..]);
+
+        // {"fmt": "none", "attStmt": {}, "authData": auth_data}
+        let mut attestation = vec![0xa3, 0x63];
+        attestation.extend_from_slice(b"fmt");
+        attestation.push(0x64);
+        attestation.extend_from_slice(b"none");
+        attestation.push(0x67);
+        attestation.extend_from_slice(b"attStmt");
+        attestation.push(0xa0);
+        attestation.push(0x68);
+        attestation.extend_from_slice(b"authData");
+        attestation.extend_from_slice(&[0x59, (auth_data.len() >> 8) as u8, auth_data.len() as u8]);
+        attestation.extend_from_slice(&auth_data);
+
+        RegistrationResponse {
+            id: URL_SAFE_NO_PAD.encode(credential_id),
+            response: AuthenticatorAttestation {
+                client_data_json: URL_SAFE_NO_PAD.encode(client_data),
+                attestation_object: URL_SAFE_NO_PAD.encode(attestation),
+                transports: Some(vec!["internal".to_string()]),
+            },
+        }
+    }
+
+    /// Signs `challenge` as if for `origin` and `rp_id`, bumping the
+    /// counter first.
+    pub fn sign(
+        &mut self,
+        credential_id: &str,
+        challenge: &str,
+        origin: &str,
+        rp_id: &str,
+    ) -> AssertionResponse {
+        self.counter += 1;
+        let client_data = client_data("webauthn.get", challenge, origin);
+        let mut auth_data = digest(&SHA256, rp_id.as_bytes()).as_ref().to_vec();
+        auth_data.push(FLAG_USER_PRESENT | FLAG_USER_VERIFIED);
+        auth_data.extend_from_slice(&self.counter.to_be_bytes());
+
+        let mut signed = auth_data.clone();
+        signed.extend_from_slice(digest(&SHA256, client_data.as_bytes()).as_ref());
+        let signature = self.key.sign(&SystemRandom::new(), &signed).unwrap();
+        AssertionResponse {
+            id: credential_id.to_string(),
+            response: AuthenticatorAssertion {
+                client_data_json: URL_SAFE_NO_PAD.encode(client_data),
+                authenticator_data: URL_SAFE_NO_PAD.encode(auth_data),
+                signature: URL_SAFE_NO_PAD.encode(signature.as_ref()),
+                user_handle: None,
+            },
+        }
+    }
+}
+
+fn client_data(ceremony: &str, challenge: &str, origin: &str) -> String {
+    serde_json::json!({
+        "type": ceremony,
+        "challenge": challenge,
+        "origin": origin,
+    })
+    .to_string()
+}
@@ -0,0 +1,169 @@
+//! Passkey verification for a known user, for use by other plugins.
+
+use crate::assertion::{AssertionResponse, verify_assertion_reporting};
+use crate::webauthn::{AllowCredential, AuthenticationOptions, CHALLENGE_TIMEOUT};
+use crate::{Passkey, PasskeyChallengeStore, PasskeyConfig, PasskeyStore, WebAuthnChallenge};
+use better_auth_core::error::{AuthError, AuthResult};
+use std::sync::Arc;
+
+/// Issues and verifies WebAuthn challenges scoped to one user's passkeys.
+///
+/// Used to ask an already identified user for a passkey, e.g. as a second
+/// factor. Obtain one from [`PasskeyPlugin::verifier`](crate::PasskeyPlugin::verifier)
+/// so that it shares the plugin's relying party settings and challenge
+/// store.
+#[derive(Clone)]
+pub struct PasskeyVerifier {
+    config: PasskeyConfig,
+    challenges: Arc<dyn PasskeyChallengeStore>,
+    passkeys: Arc<dyn PasskeyStore>,
+}
+
+impl PasskeyVerifier {
+    /// Creates a verifier from its parts.
+    pub fn new(
+        config: PasskeyConfig,
+        challenges: Arc<dyn PasskeyChallengeStore>,
+        passkeys: Arc<dyn PasskeyStore>,
+    ) -> Self {
+        Self {
+            config,
+            challenges,
+            passkeys,
+        }
+    }
+
+    /// Issues a challenge for `user_id`, allowing only their passkeys.
+    ///
+    /// Fails with [`AuthError::NotFound`] if the user has none.
+    pub async fn authentication_options(&self, user_id: &str) -> AuthResult<AuthenticationOptions> {
+        let passkeys = self.passkeys.get_user_passkeys(user_id).await?;
+        if passkeys.is_empty() {
+            return Err(AuthError::not_found("passkey", "user_id", user_id));
+        }
+
+        let mut challenge = WebAuthnChallenge::for_authentication(CHALLENGE_TIMEOUT);
+        challenge.user_id = Some(user_id.to_string());
+        self.challenges.store(&challenge).await?;
+
+        let user_verification = self
+            .config
+            .authenticator_selection
+            .as_ref()
+            .map_or("preferred", |s| s.user_verification.as_str());
+        Ok(AuthenticationOptions {
+            challenge: challenge.challenge,
+            rp_id: self.config.rp_id.clone(),
+            allow_credentials: Some(
+                passkeys
+                    .iter()
+                    .map(|passkey| AllowCredential {
+                        id: passkey.credential_id.clone(),
+                        cred_type: "public-key".to_string(),
+                        transports: Some(passkey.get_transports()).filter(|t| !t.is_empty()),
+                    })
+                    .collect(),
+            ),
+            user_verification: user_verification.to_string(),
+            timeout: (CHALLENGE_TIMEOUT * 1000) as u32,
+        })
+    }
+
+    /// Verifies an assertion for a challenge issued to `user_id` and
+    /// records the passkey's new signature counter.
+    ///
+    /// The challenge is consumed whether or not verification succeeds.
+    /// Fails with [`AuthError::InvalidCredentials`] if the challenge is
+    /// unknown, expired or issued to someone else, or the assertion does
+    /// not verify against one of the user's passkeys.
+    pub async fn verify(&self, user_id: &str, response: &AssertionResponse) -> AuthResult<Passkey> {
+        let challenge = self
+            .challenges
+            .take(&response.challenge()?)
+            .await?
+            .filter(|c| !c.is_expired() && c.challenge_type == "authentication")
+            .filter(|c| c.user_id.as_deref() == Some(user_id))
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        let mut passkey = self
+            .passkeys
+            .get_user_passkeys(user_id)
+            .await?
+            .into_iter()
+            .find(|p| p.credential_id == response.id)
+            .ok_or(AuthError::InvalidCredentials)?;
+        let counter =
+            verify_assertion_reporting(&self.config, &challenge, &passkey, response).await?;
+        passkey.increment_counter(counter);
+        self.passkeys.update_passkey(&passkey).await
+    }
+}
+
+impl std::fmt::Debug for PasskeyVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasskeyVerifier")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryPasskeyChallengeStore;
+    use crate::testing::{MemoryPasskeys, TestAuthenticator};
+
+    async fn setup() -> (PasskeyVerifier, TestAuthenticator) {
+        let authenticator = TestAuthenticator::new();
+        let passkeys = Arc::new(MemoryPasskeys::default());
+        passkeys
+            .create_passkey(&authenticator.passkey("user_1", "cred_1"))
+            .await
+            .unwrap();
+        let verifier = PasskeyVerifier::new(
+            PasskeyConfig::new("example.com", "Example", "https://example.com"),
+            Arc::new(InMemoryPasskeyChallengeStore::new()),
+            passkeys,
+        );
+        (verifier, authenticator)
+    }
+
+    #[tokio::test]
+    async fn test_challenge_is_scoped_to_user() {
+        let (verifier, mut authenticator) = setup().await;
+        let options = verifier.authentication_options("user_1").await.unwrap();
+        let allowed = options.allow_credentials.unwrap();
+        assert_eq!(allowed.len(), 1);
+        assert_eq!(allowed[0].id, "cred_1");
+        assert!(matches!(
+            verifier.authentication_options("user_2").await,
+            Err(AuthError::NotFound { .. })
+        ));
+
+        let response = authenticator.sign(
+            "cred_1",
+            &options.challenge,
+            "https://example.com",
+            "example.com",
+        );
+        assert!(verifier.verify("user_2", &response).await.is_err());
+        // The failed attempt used up the challenge.
+        assert!(verifier.verify("user_1", &response).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_updates_counter_and_consumes_challenge() {
+        let (verifier, mut authenticator) = setup().await;
+        let options = verifier.authentication_options("user_1").await.unwrap();
+        let response = authenticator.sign(
+            "cred_1",
+            &options.challenge,
+            "https://example.com",
+            "example.com",
+        );
+
+        let passkey = verifier.verify("user_1", &response).await.unwrap();
+        assert_eq!(passkey.counter, 1);
+        assert!(verifier.verify("user_1", &response).await.is_err());
+    }
+}
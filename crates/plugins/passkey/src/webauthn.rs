@@ -1,8 +1,27 @@
 //! WebAuthn utilities.
 
+use crate::PasskeyConfig;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use better_auth_core::error::{AuthError, AuthResult};
+use ring::digest::{SHA256, digest};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// User present flag in the authenticator data.
+pub(crate) const FLAG_USER_PRESENT: u8 = 0x01;
+/// User verified flag in the authenticator data.
+pub(crate) const FLAG_USER_VERIFIED: u8 = 0x04;
+/// Backup eligible flag: the credential may be synced between devices.
+pub(crate) const FLAG_BACKUP_ELIGIBLE: u8 = 0x08;
+/// Backed up flag: the credential is currently synced.
+pub(crate) const FLAG_BACKED_UP: u8 = 0x10;
+/// Attested credential data follows the fixed-size header.
+pub(crate) const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+/// How long an issued challenge stays valid, in seconds.
+pub(crate) const CHALLENGE_TIMEOUT: i64 = 5 * 60;
+
 /// WebAuthn challenge for registration or authentication.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebAuthnChallenge {
@@ -168,6 +187,94 @@ pub struct AllowCredential {
     pub transports: Option<Vec<String>>,
 }
 
+/// The fields of `clientDataJSON` that are checked.
+#[derive(Debug, Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    ceremony: String,
+    challenge: String,
+    origin: String,
+}
+
+impl ClientData {
+    fn parse(client_data_json: &[u8]) -> AuthResult<Self> {
+        serde_json::from_slice(client_data_json).map_err(|_| AuthError::InvalidCredentials)
+    }
+}
+
+/// Decodes a base64url value, with or without padding.
+pub(crate) fn decode_base64url(value: &str) -> AuthResult<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| AuthError::InvalidCredentials)
+}
+
+/// Returns the challenge in an encoded `clientDataJSON`.
+pub(crate) fn client_data_challenge(client_data_json: &str) -> AuthResult<String> {
+    Ok(ClientData::parse(&decode_base64url(client_data_json)?)?.challenge)
+}
+
+/// Checks an encoded `clientDataJSON` against the pending challenge and
+/// returns its SHA-256 hash, which the authenticator signs.
+///
+/// `ceremony` is `webauthn.create` for registration and `webauthn.get` for
+/// authentication.
+pub(crate) fn verify_client_data(
+    config: &PasskeyConfig,
+    challenge: &WebAuthnChallenge,
+    ceremony: &str,
+    client_data_json: &str,
+) -> AuthResult<Vec<u8>> {
+    let raw = decode_base64url(client_data_json)?;
+    let client_data = ClientData::parse(&raw)?;
+    if client_data.ceremony != ceremony
+        || client_data.challenge != challenge.challenge
+        || client_data.origin != config.origin
+    {
+        return Err(AuthError::InvalidCredentials);
+    }
+    Ok(digest(&SHA256, &raw).as_ref().to_vec())
+}
+
+/// The fixed-size header of authenticator data.
+pub(crate) struct AuthenticatorData<'a> {
+    pub(crate) flags: u8,
+    pub(crate) sign_count: u32,
+    /// Attested credential data and extensions, if any.
+    pub(crate) rest: &'a [u8],
+}
+
+/// Parses authenticator data, checking the RP ID hash and that the user
+/// was present, and verified if the config requires it.
+pub(crate) fn verify_authenticator_data<'a>(
+    config: &PasskeyConfig,
+    auth_data: &'a [u8],
+) -> AuthResult<AuthenticatorData<'a>> {
+    // rpIdHash (32 bytes) | flags (1 byte) | signCount (4 bytes) | ...
+    if auth_data.len() < 37 {
+        return Err(AuthError::InvalidCredentials);
+    }
+    let rp_id_hash = digest(&SHA256, config.rp_id.as_bytes());
+    if auth_data[..32] != *rp_id_hash.as_ref() {
+        return Err(AuthError::InvalidCredentials);
+    }
+
+    let flags = auth_data[32];
+    let verification_required = config
+        .authenticator_selection
+        .as_ref()
+        .is_some_and(|s| s.user_verification == "required");
+    if flags & FLAG_USER_PRESENT == 0 || (verification_required && flags & FLAG_USER_VERIFIED == 0) {
+        return Err(AuthError::InvalidCredentials);
+    }
+
+    Ok(AuthenticatorData {
+        flags,
+        sign_count: u32::from_be_bytes([auth_data[33], auth_data[34], auth_data[35], auth_data[36]]),
+        rest: &auth_data[37..],
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
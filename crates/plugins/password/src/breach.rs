@@ -0,0 +1,152 @@
+//! Breached password checks against the HaveIBeenPwned range API.
+//!
+//! The password is hashed with SHA-1 and only the first five hex characters
+//! of the hash are sent (k-anonymity). The API returns every known suffix
+//! for that prefix, and the match is done locally.
+
+use crate::PasswordPlugin;
+use better_auth_core::error::{AuthError, AuthResult};
+use sha1::{Digest, Sha1};
+
+/// Default base URL of the HaveIBeenPwned range API.
+pub const HIBP_RANGE_URL: &str = "https://api.pwnedpasswords.com/range/";
+
+impl PasswordPlugin {
+    /// Rejects passwords that appear in the HaveIBeenPwned breach corpus.
+    ///
+    /// Returns [`AuthError::WeakPassword`] for breached passwords. If the API
+    /// cannot be reached, the password is accepted unless
+    /// `breached_check_fail_closed` is set. Does nothing when
+    /// `check_breached` is off.
+    pub async fn check_breached_password(&self, password: &str) -> AuthResult<()> {
+        let config = self.config();
+        if !config.check_breached {
+            return Ok(());
+        }
+
+        match breach_count(&config.hibp_client, &config.hibp_base_url, password).await {
+            Ok(0) => Ok(()),
+            Ok(count) => Err(AuthError::WeakPassword {
+                reason: format!(
+                    "This password has appeared in {} data breaches; choose a different one",
+                    count
+                ),
+            }),
+            Err(e) if config.breached_check_fail_closed => Err(AuthError::plugin(
+                "password",
+                format!("Breached password check failed: {}", e),
+            )),
+            Err(e) => {
+                tracing::warn!(error = %e, "Breached password check failed; allowing password");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Returns how often `password` appears in the breach corpus.
+async fn breach_count(
+    client: &reqwest::Client,
+    base_url: &str,
+    password: &str,
+) -> Result<u64, reqwest::Error> {
+    let hash = hex_upper(&Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = hash.split_at(5);
+
+    let body = client
+        .get(format!("{}/{}", base_url.trim_end_matches('/'), prefix))
+        .header("Add-Padding", "true")
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    Ok(find_suffix(&body, suffix))
+}
+
+/// Finds `suffix` in a range response of `SUFFIX:COUNT` lines.
+///
+/// Padding entries have a count of 0 and never match.
+fn find_suffix(body: &str, suffix: &str) -> u64 {
+    body.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+fn hex_upper(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PasswordConfig;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // SHA-1("password") = 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+    const PASSWORD_SUFFIX: &str = "1E4C9B93F3F0682250B6CF8331B7EE68FD8";
+
+    /// Serves `body` to every request and returns the base URL.
+    async fn mock_hibp(status: u16, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/range", addr)
+    }
+
+    fn plugin(base_url: String, fail_closed: bool) -> PasswordPlugin {
+        PasswordPlugin::new(
+            PasswordConfig::new()
+                .check_breached(true)
+                .breached_check_fail_closed(fail_closed)
+                .hibp_base_url(base_url),
+        )
+    }
+
+    #[test]
+    fn test_find_suffix() {
+        let body = format!("0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n{}:3861493\r\n", PASSWORD_SUFFIX);
+        assert_eq!(find_suffix(&body, PASSWORD_SUFFIX), 3861493);
+        assert_eq!(find_suffix(&body, "00000000000000000000000000000000000"), 0);
+        assert_eq!(find_suffix("ABC:0", "ABC"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_breached_password() {
+        let body = "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:42\r\n";
+        let plugin = plugin(mock_hibp(200, body).await, false);
+
+        assert!(matches!(
+            plugin.check_breached_password("password").await,
+            Err(AuthError::WeakPassword { reason }) if reason.contains("42")
+        ));
+        assert!(plugin.check_breached_password("correct horse battery staple").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_api_fail_open_and_closed() {
+        let base = mock_hibp(503, "").await;
+
+        assert!(plugin(base.clone(), false).check_breached_password("password").await.is_ok());
+        assert!(matches!(
+            plugin(base, true).check_breached_password("password").await,
+            Err(AuthError::PluginError { .. })
+        ));
+    }
+}
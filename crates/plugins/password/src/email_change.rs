@@ -0,0 +1,429 @@
+//! Email change flow.
+//!
+//! `POST /user/change-email` re-authenticates the user, records a pending
+//! change on the user, emails a confirmation link to the new address and a
+//! notification to the old one. The user's `email` is only updated once
+//! `GET /user/change-email/verify` consumes the new-address token. Pending
+//! changes that are never confirmed simply expire.
+
+use crate::reauthenticate::require_recent_auth;
+use crate::{PasswordConfig, PasswordPlugin};
+use async_trait::async_trait;
+use better_auth_core::crypto::constant_time_eq;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::events::Event;
+use better_auth_core::redirect::{is_allowed_redirect, validate_redirect};
+use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_core::schema::{Field, FieldType};
+use better_auth_core::session::SessionResolver;
+use better_auth_core::traits::{ExtensionProvider, StorageAdapter};
+use better_auth_core::types::{Session, User};
+use better_auth_otp_utils::OtpGenerator;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Data passed to the change-email verification callback.
+///
+/// Sent to the new address; `url` confirms the change.
+#[derive(Debug, Clone)]
+pub struct ChangeEmailVerificationData {
+    /// The ID of the user changing their email.
+    pub user_id: String,
+    /// The new email address (recipient).
+    pub email: String,
+    /// The complete confirmation URL.
+    pub url: String,
+    /// The raw token (in case custom URL building is needed).
+    pub token: String,
+}
+
+/// Data passed to the change-email notification callback.
+///
+/// Sent to the current address so the owner learns about the request.
+#[derive(Debug, Clone)]
+pub struct EmailChangeNotificationData {
+    /// The ID of the user changing their email.
+    pub user_id: String,
+    /// The current email address (recipient).
+    pub email: String,
+    /// The requested new email address.
+    pub new_email: String,
+}
+
+/// A requested email change awaiting confirmation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingEmailChange {
+    /// The requested new email address.
+    pub new_email: String,
+    /// The confirmation token sent to the new address.
+    pub token: String,
+    /// When the request lapses.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PendingEmailChange {
+    /// Creates a pending change for `user_id` that expires after `expires_in` seconds.
+    ///
+    /// The token is prefixed with the user ID so the confirmation link alone
+    /// is enough to find the pending change.
+    pub fn new(user_id: &str, new_email: impl Into<String>, expires_in: u64) -> Self {
+        Self {
+            new_email: new_email.into(),
+            token: format!("{}.{}", user_id, OtpGenerator::generate_secure_token(48)),
+            expires_at: Utc::now() + Duration::seconds(expires_in as i64),
+        }
+    }
+
+    /// Checks if the request has lapsed.
+    pub fn is_expired(&self) -> bool {
+        Utc::now() > self.expires_at
+    }
+
+    /// Extracts the user ID from a confirmation token.
+    pub fn user_id_from_token(token: &str) -> Option<&str> {
+        token
+            .rsplit_once('.')
+            .map(|(user_id, _)| user_id)
+            .filter(|user_id| !user_id.is_empty())
+    }
+}
+
+/// User extension fields for the email change flow.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EmailChangeUserExt {
+    /// The pending email change, if any.
+    pub pending_email_change: Option<PendingEmailChange>,
+}
+
+impl ExtensionProvider for EmailChangeUserExt {
+    fn extends() -> &'static str {
+        "user"
+    }
+
+    fn fields() -> Vec<Field> {
+        vec![Field::optional("pending_email_change", FieldType::Json).private()]
+    }
+}
+
+/// Trait for pending email change operations on users.
+pub trait EmailChangeExt {
+    /// Gets the pending email change.
+    fn pending_email_change(&self) -> Option<PendingEmailChange>;
+
+    /// Sets the pending email change, replacing any earlier one.
+    fn set_pending_email_change(&mut self, change: PendingEmailChange);
+
+    /// Clears the pending email change.
+    fn clear_pending_email_change(&mut self);
+}
+
+impl EmailChangeExt for User {
+    fn pending_email_change(&self) -> Option<PendingEmailChange> {
+        self.get_extension("pending_email_change")
+    }
+
+    fn set_pending_email_change(&mut self, change: PendingEmailChange) {
+        self.set_extension("pending_email_change", change);
+    }
+
+    fn clear_pending_email_change(&mut self) {
+        self.remove_extension("pending_email_change");
+    }
+}
+
+/// Request body for changing email.
+#[derive(Debug, Deserialize)]
+pub struct ChangeEmailRequest {
+    /// The new email address.
+    #[serde(rename = "newEmail")]
+    pub new_email: String,
+    /// The current password. Optional if the session recently authenticated.
+    pub password: Option<String>,
+    /// URL to redirect to after confirmation.
+    #[serde(rename = "callbackURL")]
+    pub callback_url: Option<String>,
+}
+
+/// Handler for POST /user/change-email
+pub struct ChangeEmailHandler {
+    plugin: Arc<PasswordPlugin>,
+}
+
+impl ChangeEmailHandler {
+    /// Creates a new handler.
+    pub fn new(plugin: Arc<PasswordPlugin>) -> Self {
+        Self { plugin }
+    }
+
+    async fn change_email(&self, req: &Request) -> AuthResult<Response> {
+        let config = self.plugin.config();
+        let storage = storage(config)?;
+        let body: ChangeEmailRequest = req.json().ok_or_else(|| AuthError::MissingField {
+            field: "newEmail".to_string(),
+        })?;
+
+        let mut session = current_session(&storage, req).await?;
+        let mut user = storage
+            .get_user_by_id(&session.user_id)
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        require_recent_auth(&self.plugin, &storage, &user, &mut session, body.password.as_deref()).await?;
+
+        if let Some(url) = &body.callback_url {
+            validate_redirect("callbackURL", url, &config.allowed_redirect_origins)?;
+        }
+
+        let new_email = body.new_email.trim().to_lowercase();
+        if !new_email.contains('@') {
+            return Err(AuthError::InvalidEmail);
+        }
+        if new_email == user.email.to_lowercase() {
+            return Err(AuthError::InvalidField {
+                field: "newEmail".to_string(),
+                reason: "New email must differ from the current email".to_string(),
+            });
+        }
+        if storage.get_user_by_email(&new_email).await?.is_some() {
+            return Err(AuthError::duplicate("user", "email", &new_email));
+        }
+
+        let change = PendingEmailChange::new(&user.id, &new_email, config.email_change_expiry);
+        let url = build_verify_url(&change.token, body.callback_url.as_deref());
+        user.set_pending_email_change(change.clone());
+        storage.update_user(&user).await?;
+
+        if let Some(send) = &config.send_change_email_verification {
+            send(ChangeEmailVerificationData {
+                user_id: user.id.clone(),
+                email: new_email.clone(),
+                url,
+                token: change.token,
+            })
+            .await
+            .map_err(|e| AuthError::plugin("password", e))?;
+        }
+        if let Some(notify) = &config.send_email_change_notification {
+            notify(EmailChangeNotificationData {
+                user_id: user.id.clone(),
+                email: user.email.clone(),
+                new_email,
+            })
+            .await
+            .map_err(|e| AuthError::plugin("password", e))?;
+        }
+
+        Ok(Response::ok().json(json!({ "success": true })))
+    }
+}
+
+#[async_trait]
+impl RequestHandler for ChangeEmailHandler {
+    async fn handle(&self, req: Request) -> Response {
+        self.change_email(&req).await.unwrap_or_else(error_response)
+    }
+}
+
+/// Handler for GET /user/change-email/verify
+pub struct VerifyEmailChangeHandler {
+    plugin: Arc<PasswordPlugin>,
+}
+
+impl VerifyEmailChangeHandler {
+    /// Creates a new handler.
+    pub fn new(plugin: Arc<PasswordPlugin>) -> Self {
+        Self { plugin }
+    }
+
+    async fn verify(&self, req: &Request) -> AuthResult<Response> {
+        let config = self.plugin.config();
+        let storage = storage(config)?;
+        let token = req
+            .query_param("token")
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| AuthError::MissingField {
+                field: "token".to_string(),
+            })?;
+
+        let user_id = PendingEmailChange::user_id_from_token(token).ok_or(AuthError::InvalidToken)?;
+        let mut user = storage
+            .get_user_by_id(user_id)
+            .await?
+            .ok_or(AuthError::InvalidToken)?;
+
+        let change = user
+            .pending_email_change()
+            .filter(|change| constant_time_eq(&change.token, token))
+            .ok_or(AuthError::InvalidToken)?;
+
+        if change.is_expired() {
+            user.clear_pending_email_change();
+            storage.update_user(&user).await?;
+            return Err(AuthError::TokenExpired);
+        }
+
+        // The address may have been claimed while the change was pending.
+        if storage.get_user_by_email(&change.new_email).await?.is_some() {
+            user.clear_pending_email_change();
+            storage.update_user(&user).await?;
+            return Err(AuthError::duplicate("user", "email", &change.new_email));
+        }
+
+        let old_email = std::mem::replace(&mut user.email, change.new_email.clone());
+        user.email_verified = false;
+        user.clear_pending_email_change();
+        let user = storage.update_user(&user).await?;
+
+        if let Some(bus) = &config.event_bus {
+            bus.emit(
+                Event::simple(
+                    "user.email_changed",
+                    json!({
+                        "user_id": user.id,
+                        "old_email": old_email,
+                        "new_email": user.email,
+                    }),
+                )
+                .with_source("password"),
+            )
+            .await;
+        }
+
+        // The link may have been edited since it was sent, so the callback is
+        // checked again rather than trusted.
+        if let Some(url) = req.query_param("callbackURL")
+            && is_allowed_redirect(url, &config.allowed_redirect_origins)
+        {
+            return Ok(Response::new(302).header("Location", url.as_str()));
+        }
+
+        Ok(Response::ok().json(json!({
+            "success": true,
+            "user": {
+                "id": user.id,
+                "email": user.email,
+                "email_verified": user.email_verified,
+            }
+        })))
+    }
+}
+
+#[async_trait]
+impl RequestHandler for VerifyEmailChangeHandler {
+    async fn handle(&self, req: Request) -> Response {
+        self.verify(&req).await.unwrap_or_else(error_response)
+    }
+}
+
+/// Builds the confirmation URL sent to the new address.
+///
+/// The token and callback URL are percent-encoded.
+pub fn build_verify_url(token: &str, callback_url: Option<&str>) -> String {
+    let token = urlencoding::encode(token);
+    match callback_url {
+        Some(callback) => format!(
+            "/api/auth/user/change-email/verify?token={}&callbackURL={}",
+            token,
+            urlencoding::encode(callback)
+        ),
+        None => format!("/api/auth/user/change-email/verify?token={}", token),
+    }
+}
+
+pub(crate) fn storage(config: &PasswordConfig) -> AuthResult<Arc<dyn StorageAdapter>> {
+    config
+        .storage
+        .clone()
+        .ok_or_else(|| AuthError::config("Password routes require a storage adapter"))
+}
+
+/// Loads the session identified by the bearer token or session cookie.
+pub(crate) async fn current_session(storage: &Arc<dyn StorageAdapter>, req: &Request) -> AuthResult<Session> {
+    SessionResolver::new(storage.clone())
+        .resolve_request(req)
+        .await?
+        .map(|resolved| resolved.session)
+        .ok_or(AuthError::SessionNotFound)
+}
+
+pub(crate) fn error_response(err: AuthError) -> Response {
+    Response::new(err.status_code()).json(json!({ "error": err.to_string() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_carries_user_id() {
+        let change = PendingEmailChange::new("user.123", "new@example.com", 60);
+        assert_eq!(PendingEmailChange::user_id_from_token(&change.token), Some("user.123"));
+        assert_eq!(PendingEmailChange::user_id_from_token("no-separator"), None);
+        assert!(!change.is_expired());
+    }
+
+    #[test]
+    fn test_pending_change_expires() {
+        let mut change = PendingEmailChange::new("user_1", "new@example.com", 60);
+        change.expires_at = Utc::now() - Duration::seconds(1);
+        assert!(change.is_expired());
+    }
+
+    #[test]
+    fn test_pending_change_extension() {
+        let mut user = User::new("user_1".to_string(), "old@example.com".to_string());
+        assert!(user.pending_email_change().is_none());
+
+        let change = PendingEmailChange::new(&user.id, "new@example.com", 60);
+        user.set_pending_email_change(change.clone());
+        assert_eq!(user.pending_email_change(), Some(change));
+
+        user.clear_pending_email_change();
+        assert!(user.pending_email_change().is_none());
+    }
+
+    #[test]
+    fn test_verify_url_is_encoded() {
+        assert_eq!(
+            build_verify_url("user_1.a&b", Some("/done?x=1&y=2")),
+            "/api/auth/user/change-email/verify?token=user_1.a%26b&callbackURL=%2Fdone%3Fx%3D1%26y%3D2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_only_redirects_to_allowed_callbacks() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::router::Method;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let plugin = Arc::new(PasswordPlugin::new(
+            PasswordConfig::new()
+                .storage(storage.clone())
+                .allowed_redirect_origins(["https://app.example.com"]),
+        ));
+        let handler = VerifyEmailChangeHandler::new(plugin);
+
+        let callbacks = ["https://evil.com", "//evil.com", "https://app.example.com/done"];
+        for (n, callback) in callbacks.into_iter().enumerate() {
+            let mut user = User::new(format!("user_{}", n), format!("old{}@example.com", n));
+            let change = PendingEmailChange::new(&user.id, &format!("new{}@example.com", n), 60);
+            user.set_pending_email_change(change.clone());
+            storage.create_user(&user).await.unwrap();
+
+            let mut req = Request::new(Method::GET, "/user/change-email/verify");
+            req.query.insert("token".to_string(), change.token);
+            req.query
+                .insert("callbackURL".to_string(), callback.to_string());
+            let response = handler.handle(req).await;
+            if callback.starts_with("https://app.example.com") {
+                assert_eq!(response.status, 302);
+                assert_eq!(response.headers["location"], callback);
+            } else {
+                assert_eq!(response.status, 200, "{}", callback);
+                assert!(!response.headers.contains_key("location"));
+            }
+        }
+    }
+}
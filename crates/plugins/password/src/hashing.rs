@@ -0,0 +1,211 @@
+//! Password hashing algorithms.
+//!
+//! New passwords are hashed with the configured [`PasswordHashAlgorithm`].
+//! Verification detects the algorithm from the hash prefix, so hashes
+//! imported from another system keep working after the default changes.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use scrypt::Scrypt;
+use serde::{Deserialize, Serialize};
+
+/// A password hashing algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordHashAlgorithm {
+    /// Argon2id in PHC format (`$argon2id$...`).
+    #[default]
+    Argon2id,
+    /// bcrypt in modular crypt format (`$2b$...`).
+    Bcrypt,
+    /// scrypt in PHC format (`$scrypt$...`).
+    Scrypt,
+}
+
+impl PasswordHashAlgorithm {
+    /// Detects the algorithm a hash was created with from its prefix.
+    ///
+    /// Any Argon2 variant is reported as [`Argon2id`](Self::Argon2id); the
+    /// variant itself is checked by [`needs_rehash`](PasswordHashers::needs_rehash).
+    pub fn detect(hash: &str) -> Option<Self> {
+        if hash.starts_with("$argon2") {
+            Some(Self::Argon2id)
+        } else if ["$2a$", "$2b$", "$2x$", "$2y$"]
+            .iter()
+            .any(|prefix| hash.starts_with(prefix))
+        {
+            Some(Self::Bcrypt)
+        } else if hash.starts_with("$scrypt$") {
+            Some(Self::Scrypt)
+        } else {
+            None
+        }
+    }
+}
+
+/// Hashes and verifies passwords for every supported algorithm.
+#[derive(Clone)]
+pub(crate) struct PasswordHashers {
+    algorithm: PasswordHashAlgorithm,
+    argon2: Argon2<'static>,
+    bcrypt_cost: u32,
+}
+
+impl PasswordHashers {
+    pub(crate) fn new(algorithm: PasswordHashAlgorithm, argon2: Params, bcrypt_cost: u32) -> Self {
+        Self {
+            algorithm,
+            argon2: Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2),
+            bcrypt_cost,
+        }
+    }
+
+    /// Hashes with the configured algorithm and a random salt.
+    ///
+    /// Hashing only fails for invalid parameters, which the plugin checks
+    /// before it is used, or for inputs too large to hash.
+    pub(crate) fn hash(&self, password: &str) -> String {
+        match self.algorithm {
+            PasswordHashAlgorithm::Argon2id => {
+                let salt = SaltString::generate(&mut OsRng);
+                self.argon2
+                    .hash_password(password.as_bytes(), &salt)
+                    .expect("Argon2 hashing failed")
+                    .to_string()
+            }
+            PasswordHashAlgorithm::Bcrypt => {
+                bcrypt::hash(password, self.bcrypt_cost).expect("bcrypt hashing failed")
+            }
+            PasswordHashAlgorithm::Scrypt => {
+                let salt = SaltString::generate(&mut OsRng);
+                Scrypt
+                    .hash_password(password.as_bytes(), &salt)
+                    .expect("scrypt hashing failed")
+                    .to_string()
+            }
+        }
+    }
+
+    /// Verifies `password` against a hash of any supported algorithm, using
+    /// the parameters recorded in the hash. Comparison is constant-time.
+    pub(crate) fn verify(&self, password: &str, hash: &str) -> bool {
+        match PasswordHashAlgorithm::detect(hash) {
+            Some(PasswordHashAlgorithm::Argon2id) => PasswordHash::new(hash)
+                .and_then(|parsed| self.argon2.verify_password(password.as_bytes(), &parsed))
+                .is_ok(),
+            Some(PasswordHashAlgorithm::Bcrypt) => bcrypt::verify(password, hash).unwrap_or(false),
+            Some(PasswordHashAlgorithm::Scrypt) => PasswordHash::new(hash)
+                .and_then(|parsed| Scrypt.verify_password(password.as_bytes(), &parsed))
+                .is_ok(),
+            None => false,
+        }
+    }
+
+    /// Returns true if `hash` was not created with the configured algorithm
+    /// and parameters.
+    pub(crate) fn needs_rehash(&self, hash: &str) -> bool {
+        if PasswordHashAlgorithm::detect(hash) != Some(self.algorithm) {
+            return true;
+        }
+        match self.algorithm {
+            PasswordHashAlgorithm::Argon2id => {
+                let Ok(parsed) = PasswordHash::new(hash) else {
+                    return true;
+                };
+                if parsed.algorithm != Algorithm::Argon2id.ident()
+                    || parsed.version != Some(Version::V0x13.into())
+                {
+                    return true;
+                }
+                let Ok(params) = Params::try_from(&parsed) else {
+                    return true;
+                };
+                let current = self.argon2.params();
+                params.m_cost() != current.m_cost()
+                    || params.t_cost() != current.t_cost()
+                    || params.p_cost() != current.p_cost()
+            }
+            PasswordHashAlgorithm::Bcrypt => {
+                hash.get(4..6).and_then(|cost| cost.parse().ok()) != Some(self.bcrypt_cost)
+            }
+            PasswordHashAlgorithm::Scrypt => {
+                let Ok(parsed) = PasswordHash::new(hash) else {
+                    return true;
+                };
+                let Ok(params) = scrypt::Params::try_from(&parsed) else {
+                    return true;
+                };
+                let current = scrypt::Params::recommended();
+                params.log_n() != current.log_n()
+                    || params.r() != current.r()
+                    || params.p() != current.p()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "correct horse battery staple";
+
+    fn hashers(algorithm: PasswordHashAlgorithm) -> PasswordHashers {
+        PasswordHashers::new(
+            algorithm,
+            Params::new(8 * 1024, 1, 1, None).unwrap(),
+            bcrypt::DEFAULT_COST,
+        )
+    }
+
+    #[test]
+    fn test_detect() {
+        use PasswordHashAlgorithm::*;
+        assert_eq!(PasswordHashAlgorithm::detect("$argon2id$v=19$m=19456,t=2,p=1$a$b"), Some(Argon2id));
+        assert_eq!(PasswordHashAlgorithm::detect("$2b$12$abc"), Some(Bcrypt));
+        assert_eq!(PasswordHashAlgorithm::detect("$2y$10$abc"), Some(Bcrypt));
+        assert_eq!(PasswordHashAlgorithm::detect("$scrypt$ln=17,r=8,p=1$a$b"), Some(Scrypt));
+        assert_eq!(PasswordHashAlgorithm::detect("hashed:secret"), None);
+    }
+
+    #[test]
+    fn test_verifies_external_bcrypt_hash() {
+        // OpenBSD bcrypt test vector for the password "U*U".
+        let hash = "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW";
+        let hashers = hashers(PasswordHashAlgorithm::Argon2id);
+
+        assert!(hashers.verify("U*U", hash));
+        assert!(!hashers.verify("U*V", hash));
+        // Imported hashes are upgraded to the configured algorithm.
+        assert!(hashers.needs_rehash(hash));
+    }
+
+    #[test]
+    fn test_verifies_external_scrypt_hash() {
+        // Generated with Python's hashlib.scrypt (n=1024, r=8, p=1).
+        let hash = "$scrypt$ln=10,r=8,p=1$bGVnYWN5c2FsdDEyMzQ1Ng$Zdb+mCE0hl5PhDyvqHilx7Z7SIEbCknGV6J3uDjJvI8";
+        let hashers = hashers(PasswordHashAlgorithm::Argon2id);
+
+        assert!(hashers.verify(PASSWORD, hash));
+        assert!(!hashers.verify("wrong", hash));
+    }
+
+    #[test]
+    fn test_hashes_with_configured_algorithm() {
+        let bcrypt = PasswordHashers::new(
+            PasswordHashAlgorithm::Bcrypt,
+            Params::default(),
+            4,
+        );
+        let hash = bcrypt.hash(PASSWORD);
+        assert!(hash.starts_with("$2b$04$"));
+        assert!(bcrypt.verify(PASSWORD, &hash));
+        assert!(!bcrypt.needs_rehash(&hash));
+
+        // A bcrypt hash still verifies once Argon2id is the default again.
+        let argon2 = hashers(PasswordHashAlgorithm::Argon2id);
+        assert!(argon2.verify(PASSWORD, &hash));
+        assert!(argon2.needs_rehash(&hash));
+    }
+}
@@ -0,0 +1,378 @@
+//! Password history for reuse prevention.
+//!
+//! When `password_history` is set, the plugin keeps the hashes of each
+//! user's last N passwords and rejects a new password that verifies against
+//! any of them. Candidates are checked with the normal hash verification, so
+//! plaintext passwords are never stored or compared.
+//!
+//! `POST /change-password` changes the signed-in user's password. It only
+//! runs for sessions that fully authenticated within `fresh_session_age`;
+//! others must re-authenticate first.
+
+use crate::email_change::{current_session, error_response, storage};
+use crate::{PasswordExt, PasswordPlugin};
+use async_trait::async_trait;
+use better_auth_core::context::AuthContext;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_core::schema::{Field, FieldType, IndexDefinition, ModelDefinition, ReferentialAction};
+use better_auth_core::traits::SchemaProvider;
+use better_auth_core::types::User;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A previously used password hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordHistoryEntry {
+    pub id: String,
+    pub user_id: String,
+    pub password_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PasswordHistoryEntry {
+    /// Creates an entry for `user_id`.
+    pub fn new(user_id: impl Into<String>, password_hash: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.into(),
+            password_hash: password_hash.into(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+impl SchemaProvider for PasswordHistoryEntry {
+    fn schema() -> Vec<ModelDefinition> {
+        vec![ModelDefinition::new("password_history")
+            .field(Field::primary_key("id"))
+            .field(
+                Field::new("user_id", FieldType::String(36))
+                    .references("user.id")
+                    .on_delete(ReferentialAction::Cascade),
+            )
+            .field(Field::new("password_hash", FieldType::Text).private())
+            .field(Field::new("created_at", FieldType::Timestamp))
+            .index(IndexDefinition::new(
+                "idx_password_history_user",
+                vec!["user_id".to_string(), "created_at".to_string()],
+            ))]
+    }
+}
+
+/// Storage for password history.
+///
+/// Adapters implement this trait to persist the `password_history` model.
+#[async_trait]
+pub trait PasswordHistoryStore: Send + Sync {
+    /// Records a password hash.
+    async fn add_password_history(&self, entry: &PasswordHistoryEntry) -> AuthResult<()>;
+
+    /// Gets a user's most recent entries, newest first.
+    async fn get_password_history(
+        &self,
+        user_id: &str,
+        limit: usize,
+    ) -> AuthResult<Vec<PasswordHistoryEntry>>;
+
+    /// Deletes all but the `keep` most recent entries for a user.
+    async fn trim_password_history(&self, user_id: &str, keep: usize) -> AuthResult<()>;
+}
+
+impl PasswordPlugin {
+    /// Rejects `new_password` if it matches the user's current password or
+    /// one of their last `password_history` passwords.
+    ///
+    /// The current hash is read from `ctx.db`, so it is covered even when it
+    /// was set before history was enabled. Returns
+    /// [`AuthError::WeakPassword`] on reuse. Does nothing when password
+    /// history is disabled.
+    pub async fn check_password_reuse(
+        &self,
+        ctx: &AuthContext,
+        user_id: &str,
+        new_password: &str,
+    ) -> AuthResult<()> {
+        let limit = self.config().password_history;
+        if limit == 0 {
+            return Ok(());
+        }
+
+        let current = ctx
+            .db
+            .get_user_by_id(user_id)
+            .await?
+            .and_then(|user| user.password_hash());
+        let history = self
+            .history_store()?
+            .get_password_history(user_id, limit)
+            .await?;
+        if current
+            .iter()
+            .chain(history.iter().map(|entry| &entry.password_hash))
+            .any(|hash| self.verify_password(new_password, hash))
+        {
+            return Err(AuthError::WeakPassword {
+                reason: format!(
+                    "Password must not match any of your last {} passwords",
+                    limit
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Records a new password hash and drops entries beyond the configured
+    /// history length. Does nothing when password history is disabled.
+    pub async fn record_password_history(&self, user_id: &str, password_hash: &str) -> AuthResult<()> {
+        let limit = self.config().password_history;
+        if limit == 0 {
+            return Ok(());
+        }
+
+        let store = self.history_store()?;
+        store
+            .add_password_history(&PasswordHistoryEntry::new(user_id, password_hash))
+            .await?;
+        store.trim_password_history(user_id, limit).await
+    }
+
+    /// Changes a user's password.
+    ///
+    /// The new password is validated and checked against the password
+    /// history and breach corpus before it is hashed and saved; the new hash
+    /// is then added to the history.
+    ///
+    /// Recent authentication is not checked here. Users reach this through
+    /// [`ChangePasswordHandler`], which is mounted behind
+    /// [`RequireRecentAuth`](better_auth_core::session::RequireRecentAuth).
+    pub(crate) async fn change_password(
+        &self,
+        ctx: &AuthContext,
+        user_id: &str,
+        new_password: &str,
+    ) -> AuthResult<User> {
+        self.validate_password(new_password)?;
+        self.check_password_reuse(ctx, user_id, new_password).await?;
+        self.check_breached_password(new_password).await?;
+
+        let mut user = ctx
+            .db
+            .get_user_by_id(user_id)
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+        let hash = self.hash_password(new_password);
+        user.set_password_hash(hash.clone());
+        let user = ctx.db.update_user(&user).await?;
+
+        self.record_password_history(user_id, &hash).await?;
+        Ok(user)
+    }
+
+    fn history_store(&self) -> AuthResult<Arc<dyn PasswordHistoryStore>> {
+        self.config()
+            .history_storage
+            .clone()
+            .ok_or_else(|| AuthError::config("Password history requires a history store"))
+    }
+}
+
+/// Request body for changing the password.
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    /// The new password.
+    #[serde(rename = "newPassword")]
+    pub new_password: String,
+}
+
+/// Handler for POST /change-password
+///
+/// Register it behind `RequireRecentAuth`, as the plugin's routes do.
+pub struct ChangePasswordHandler {
+    plugin: Arc<PasswordPlugin>,
+}
+
+impl ChangePasswordHandler {
+    /// Creates a new handler.
+    pub fn new(plugin: Arc<PasswordPlugin>) -> Self {
+        Self { plugin }
+    }
+
+    async fn change(&self, req: &Request) -> AuthResult<Response> {
+        let storage = storage(self.plugin.config())?;
+        let body: ChangePasswordRequest = req.json().ok_or_else(|| AuthError::MissingField {
+            field: "newPassword".to_string(),
+        })?;
+
+        let session = current_session(&storage, req).await?;
+        let ctx = AuthContext::new(storage);
+        self.plugin
+            .change_password(&ctx, &session.user_id, &body.new_password)
+            .await?;
+        Ok(Response::ok().json(serde_json::json!({ "success": true })))
+    }
+}
+
+#[async_trait]
+impl RequestHandler for ChangePasswordHandler {
+    async fn handle(&self, req: Request) -> Response {
+        self.change(&req).await.unwrap_or_else(error_response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PasswordConfig;
+    use better_auth_adapter_memory::MemoryAdapter;
+    use better_auth_core::traits::StorageAdapter;
+    use better_auth_core::types::Session;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryHistory(Mutex<Vec<PasswordHistoryEntry>>);
+
+    #[async_trait]
+    impl PasswordHistoryStore for MemoryHistory {
+        async fn add_password_history(&self, entry: &PasswordHistoryEntry) -> AuthResult<()> {
+            self.0.lock().unwrap().push(entry.clone());
+            Ok(())
+        }
+
+        async fn get_password_history(
+            &self,
+            user_id: &str,
+            limit: usize,
+        ) -> AuthResult<Vec<PasswordHistoryEntry>> {
+            let entries = self.0.lock().unwrap();
+            Ok(entries
+                .iter()
+                .rev()
+                .filter(|e| e.user_id == user_id)
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+
+        async fn trim_password_history(&self, user_id: &str, keep: usize) -> AuthResult<()> {
+            let mut entries = self.0.lock().unwrap();
+            let count = entries.iter().filter(|e| e.user_id == user_id).count();
+            let mut excess = count.saturating_sub(keep);
+            entries.retain(|e| {
+                if e.user_id == user_id && excess > 0 {
+                    excess -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
+            Ok(())
+        }
+    }
+
+    async fn setup(history: usize) -> (PasswordPlugin, AuthContext, Arc<MemoryHistory>) {
+        let store = Arc::new(MemoryHistory::default());
+        let plugin = PasswordPlugin::new(
+            PasswordConfig::new()
+                .memory_cost(8 * 1024)
+                .iterations(1)
+                .password_history(history)
+                .history_storage(store.clone()),
+        );
+        let storage = Arc::new(MemoryAdapter::new());
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        storage.create_user(&user).await.unwrap();
+        (plugin, AuthContext::new(storage), store)
+    }
+
+    #[tokio::test]
+    async fn test_rejects_recent_passwords() {
+        let (plugin, ctx, store) = setup(2).await;
+
+        plugin.change_password(&ctx, "user_1", "first-password").await.unwrap();
+        plugin.change_password(&ctx, "user_1", "second-password").await.unwrap();
+        assert!(matches!(
+            plugin.change_password(&ctx, "user_1", "first-password").await,
+            Err(AuthError::WeakPassword { .. })
+        ));
+
+        // History keeps only the last two hashes, never plaintext.
+        let entries = store.0.lock().unwrap().clone();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.password_hash.starts_with("$argon2id$")));
+
+        // A third password pushes the first out of the window.
+        plugin.change_password(&ctx, "user_1", "third-password").await.unwrap();
+        plugin.change_password(&ctx, "user_1", "first-password").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_current_password_missing_from_history() {
+        let (plugin, ctx, store) = setup(2).await;
+
+        // Set at sign-up, before anything was recorded.
+        let mut user = ctx.db.get_user_by_id("user_1").await.unwrap().unwrap();
+        user.set_password_hash(plugin.hash_password("signup-password"));
+        ctx.db.update_user(&user).await.unwrap();
+        assert!(store.0.lock().unwrap().is_empty());
+
+        assert!(matches!(
+            plugin
+                .change_password(&ctx, "user_1", "signup-password")
+                .await,
+            Err(AuthError::WeakPassword { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_change_password_route_requires_recent_auth() {
+        use better_auth_core::router::{Method, Router};
+        use better_auth_core::traits::AuthPlugin;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        storage.create_user(&user).await.unwrap();
+        let fresh = storage
+            .create_session(&Session::new("user_1".to_string()))
+            .await
+            .unwrap();
+        let mut stale = Session::new("user_1".to_string());
+        stale.authenticated_at -= chrono::Duration::hours(1);
+        let stale = storage.create_session(&stale).await.unwrap();
+
+        let plugin = PasswordPlugin::new(
+            PasswordConfig::new()
+                .memory_cost(8 * 1024)
+                .iterations(1)
+                .storage(storage.clone()),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router.routes().find(|r| r.path == "/change-password").unwrap();
+        let change = |token: &str| {
+            let mut req = Request::new(Method::POST, "/change-password");
+            req.headers
+                .insert("authorization".to_string(), format!("Bearer {}", token));
+            req.body = Some(serde_json::json!({ "newPassword": "correct horse battery" }));
+            route.handler.handle(req)
+        };
+
+        assert_eq!(change(&stale.token).await.status, 403);
+        let user = storage.get_user_by_id("user_1").await.unwrap().unwrap();
+        assert!(user.password_hash().is_none());
+
+        assert_eq!(change(&fresh.token).await.status, 200);
+        let user = storage.get_user_by_id("user_1").await.unwrap().unwrap();
+        assert!(plugin.verify_password("correct horse battery", &user.password_hash().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_history_disabled_by_default() {
+        let (plugin, ctx, store) = setup(0).await;
+
+        plugin.change_password(&ctx, "user_1", "same-password").await.unwrap();
+        plugin.change_password(&ctx, "user_1", "same-password").await.unwrap();
+        assert!(store.0.lock().unwrap().is_empty());
+    }
+}
@@ -1,19 +1,73 @@
 //! # Better Auth Password Plugin
 //!
 //! This plugin provides email/password authentication for Better Auth.
-//! It handles password hashing, verification, and password reset flows.
+//! It handles password hashing, verification, and password reset flows,
+//! as well as changing a user's email address and re-authenticating before
+//! sensitive actions.
 
+mod breach;
+mod email_change;
+mod hashing;
+mod history;
+mod reauthenticate;
+mod signin_limit;
+mod strength;
+
+pub use breach::HIBP_RANGE_URL;
+pub use email_change::{
+    build_verify_url, ChangeEmailHandler, ChangeEmailRequest, ChangeEmailVerificationData,
+    EmailChangeExt, EmailChangeNotificationData, EmailChangeUserExt, PendingEmailChange,
+    VerifyEmailChangeHandler,
+};
+pub use hashing::PasswordHashAlgorithm;
+pub use history::{
+    ChangePasswordHandler, ChangePasswordRequest, PasswordHistoryEntry, PasswordHistoryStore,
+};
+pub use reauthenticate::{ReauthenticateHandler, ReauthenticateRequest};
+pub use signin_limit::SignInFailure;
+pub use strength::{analyze_strength, estimate_strength, StrengthEstimate};
+
+use argon2::Params;
+use hashing::PasswordHashers;
 use async_trait::async_trait;
 use better_auth_core::context::{AuthContext, SignInCredentials, SignUpData};
 use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::auth_events;
+use better_auth_core::events::{Event, EventBus};
+use better_auth_core::router::{Method, Route, Router};
 use better_auth_core::schema::{Field, FieldType, ModelDefinition, SchemaBuilder};
-use better_auth_core::traits::{AuthPlugin, ExtensionProvider, SchemaProvider};
-use better_auth_core::types::User;
+use better_auth_core::session::{RequireRecentAuth, SessionResolver};
+use better_auth_core::traits::{AuthPlugin, ExtensionProvider, SchemaProvider, StorageAdapter};
+use better_auth_core::types::{Session, User};
 use better_auth_events_sdk::{EventDefinition, EventProvider};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use signin_limit::SignInLimiter;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+
+/// Smallest bcrypt cost factor.
+const BCRYPT_MIN_COST: u32 = 4;
+/// Largest bcrypt cost factor.
+const BCRYPT_MAX_COST: u32 = 31;
+
+/// Type alias for the change-email verification callback.
+pub type SendChangeEmailVerificationCallback = Arc<
+    dyn Fn(ChangeEmailVerificationData) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Type alias for the email change notification callback.
+pub type SendEmailChangeNotificationCallback = Arc<
+    dyn Fn(EmailChangeNotificationData) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>>
+        + Send
+        + Sync,
+>;
 
 /// Password plugin configuration.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct PasswordConfig {
     /// Minimum password length.
     pub min_length: usize,
@@ -25,8 +79,60 @@ pub struct PasswordConfig {
     pub require_numbers: bool,
     /// Require special characters.
     pub require_special: bool,
+    /// Minimum score from [`estimate_strength`] (0–4). 0 disables the check.
+    pub min_strength_score: u8,
+    /// Reject password sign-ins until the user's email is verified.
+    pub require_email_verification: bool,
     /// Password reset token expiration (in seconds).
     pub reset_token_expiry: u64,
+    /// Pending email change expiration (in seconds).
+    pub email_change_expiry: u64,
+    /// How long after the last full authentication (in seconds) a session
+    /// may change the email without re-entering the password.
+    pub fresh_session_age: u64,
+    /// Origins, such as `https://app.example.com`, that the email change
+    /// `callbackURL` may point to. Relative paths are always allowed.
+    pub allowed_redirect_origins: Vec<String>,
+    /// Callback to send the confirmation link to the new address.
+    pub send_change_email_verification: Option<SendChangeEmailVerificationCallback>,
+    /// Callback to notify the current address of a requested change.
+    pub send_email_change_notification: Option<SendEmailChangeNotificationCallback>,
+    /// Storage used by the email change and re-authentication routes.
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    /// Event bus for `user.email_changed` and `signin.failed`.
+    pub event_bus: Option<Arc<EventBus>>,
+    /// Algorithm used to hash new passwords. Existing hashes of any
+    /// supported algorithm still verify.
+    pub hash_algorithm: PasswordHashAlgorithm,
+    /// bcrypt cost factor (4-31).
+    pub bcrypt_cost: u32,
+    /// Argon2id memory cost in KiB.
+    pub memory_cost: u32,
+    /// Argon2id number of iterations.
+    pub iterations: u32,
+    /// Argon2id degree of parallelism.
+    pub parallelism: u32,
+    /// Number of previous passwords a user may not reuse. 0 disables the check.
+    pub password_history: usize,
+    /// Storage for password history.
+    pub history_storage: Option<Arc<dyn PasswordHistoryStore>>,
+    /// Reject passwords found in the HaveIBeenPwned breach corpus.
+    pub check_breached: bool,
+    /// Reject passwords when the breach check cannot be completed.
+    pub breached_check_fail_closed: bool,
+    /// HTTP client for the breach check.
+    pub hibp_client: reqwest::Client,
+    /// Base URL of the HaveIBeenPwned range API.
+    pub hibp_base_url: String,
+    /// Failed sign-ins per email and IP before a lockout. 0 disables the limit.
+    pub max_signin_failures: u32,
+    /// Window (in seconds) in which failed sign-ins are counted.
+    pub signin_failure_window: u64,
+    /// Length of the first lockout (in seconds). Each consecutive lockout
+    /// doubles it.
+    pub signin_lockout: u64,
+    /// Longest lockout (in seconds).
+    pub signin_lockout_max: u64,
 }
 
 impl Default for PasswordConfig {
@@ -37,7 +143,31 @@ impl Default for PasswordConfig {
             require_lowercase: false,
             require_numbers: false,
             require_special: false,
+            min_strength_score: 0,
+            require_email_verification: false,
             reset_token_expiry: 3600, // 1 hour
+            email_change_expiry: 24 * 60 * 60, // 24 hours
+            fresh_session_age: 5 * 60, // 5 minutes
+            allowed_redirect_origins: Vec::new(),
+            send_change_email_verification: None,
+            send_email_change_notification: None,
+            storage: None,
+            event_bus: None,
+            hash_algorithm: PasswordHashAlgorithm::Argon2id,
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+            memory_cost: Params::DEFAULT_M_COST, // 19 MiB
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+            password_history: 0,
+            history_storage: None,
+            check_breached: false,
+            breached_check_fail_closed: false,
+            hibp_client: reqwest::Client::new(),
+            hibp_base_url: HIBP_RANGE_URL.to_string(),
+            max_signin_failures: 5,
+            signin_failure_window: 15 * 60, // 15 minutes
+            signin_lockout: 60, // 1 minute
+            signin_lockout_max: 60 * 60, // 1 hour
         }
     }
 }
@@ -78,6 +208,166 @@ impl PasswordConfig {
         self
     }
 
+    /// Sets the minimum strength score (0–4) passwords must reach.
+    pub fn min_strength_score(mut self, score: u8) -> Self {
+        self.min_strength_score = score;
+        self
+    }
+
+    /// Rejects password sign-ins with [`AuthError::EmailNotVerified`] until
+    /// the user's email is verified.
+    pub fn require_email_verification(mut self) -> Self {
+        self.require_email_verification = true;
+        self
+    }
+
+    /// Sets the pending email change expiration in seconds.
+    pub fn email_change_expiry(mut self, seconds: u64) -> Self {
+        self.email_change_expiry = seconds;
+        self
+    }
+
+    /// Sets how long after authenticating a session counts as fresh, in seconds.
+    pub fn fresh_session_age(mut self, seconds: u64) -> Self {
+        self.fresh_session_age = seconds;
+        self
+    }
+
+    /// Sets the origins that the email change `callbackURL` may point to.
+    pub fn allowed_redirect_origins<I, S>(mut self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_redirect_origins = origins.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the callback that sends the confirmation link to the new address.
+    pub fn send_change_email_verification<F, Fut>(mut self, callback: F) -> Self
+    where
+        F: Fn(ChangeEmailVerificationData) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.send_change_email_verification = Some(Arc::new(move |data| Box::pin(callback(data))));
+        self
+    }
+
+    /// Sets the callback that notifies the current address of a requested change.
+    pub fn send_email_change_notification<F, Fut>(mut self, callback: F) -> Self
+    where
+        F: Fn(EmailChangeNotificationData) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.send_email_change_notification = Some(Arc::new(move |data| Box::pin(callback(data))));
+        self
+    }
+
+    /// Sets the storage adapter used by the email change and re-authentication routes.
+    pub fn storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Sets the event bus used to emit `user.email_changed`.
+    pub fn event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// Sets the algorithm used to hash new passwords.
+    pub fn hash_algorithm(mut self, algorithm: PasswordHashAlgorithm) -> Self {
+        self.hash_algorithm = algorithm;
+        self
+    }
+
+    /// Sets the bcrypt cost factor.
+    pub fn bcrypt_cost(mut self, cost: u32) -> Self {
+        self.bcrypt_cost = cost;
+        self
+    }
+
+    /// Sets the Argon2id memory cost in KiB.
+    pub fn memory_cost(mut self, kib: u32) -> Self {
+        self.memory_cost = kib;
+        self
+    }
+
+    /// Sets the number of Argon2id iterations.
+    pub fn iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Sets the Argon2id degree of parallelism.
+    pub fn parallelism(mut self, lanes: u32) -> Self {
+        self.parallelism = lanes;
+        self
+    }
+
+    /// Prevents reuse of the last `n` passwords.
+    pub fn password_history(mut self, n: usize) -> Self {
+        self.password_history = n;
+        self
+    }
+
+    /// Sets the storage used for password history.
+    pub fn history_storage(mut self, storage: Arc<dyn PasswordHistoryStore>) -> Self {
+        self.history_storage = Some(storage);
+        self
+    }
+
+    /// Enables or disables the HaveIBeenPwned breached password check.
+    pub fn check_breached(mut self, enabled: bool) -> Self {
+        self.check_breached = enabled;
+        self
+    }
+
+    /// Sets whether passwords are rejected when the breach check fails.
+    /// By default they are accepted (fail-open).
+    pub fn breached_check_fail_closed(mut self, fail_closed: bool) -> Self {
+        self.breached_check_fail_closed = fail_closed;
+        self
+    }
+
+    /// Sets the HTTP client for the breach check, e.g. to add a timeout or proxy.
+    pub fn hibp_client(mut self, client: reqwest::Client) -> Self {
+        self.hibp_client = client;
+        self
+    }
+
+    /// Overrides the HaveIBeenPwned range API base URL.
+    pub fn hibp_base_url(mut self, url: impl Into<String>) -> Self {
+        self.hibp_base_url = url.into();
+        self
+    }
+
+    /// Sets how many failed sign-ins an email and IP may have before they
+    /// are locked out. 0 disables the limit.
+    pub fn max_signin_failures(mut self, failures: u32) -> Self {
+        self.max_signin_failures = failures;
+        self
+    }
+
+    /// Sets the window (in seconds) in which failed sign-ins are counted.
+    pub fn signin_failure_window(mut self, seconds: u64) -> Self {
+        self.signin_failure_window = seconds;
+        self
+    }
+
+    /// Sets the first lockout length and the longest lockout (in seconds).
+    pub fn signin_lockout(mut self, seconds: u64, max_seconds: u64) -> Self {
+        self.signin_lockout = seconds;
+        self.signin_lockout_max = max_seconds;
+        self
+    }
+
+    /// Returns the Argon2id parameters, or an error if they are out of range.
+    pub fn argon2_params(&self) -> Result<Params, String> {
+        Params::new(self.memory_cost, self.iterations, self.parallelism, None)
+            .map_err(|e| format!("invalid Argon2 parameters: {}", e))
+    }
+
     /// Validates a password against the configuration.
     pub fn validate(&self, password: &str) -> Result<(), String> {
         if password.len() < self.min_length {
@@ -103,10 +393,66 @@ impl PasswordConfig {
             return Err("Password must contain at least one special character".to_string());
         }
 
+        if self.min_strength_score > 0 {
+            let estimate = analyze_strength(password);
+            if estimate.score < self.min_strength_score {
+                let mut message = "Password is too easy to guess.".to_string();
+                for hint in estimate.warning.into_iter().chain([estimate.suggestion]) {
+                    if !hint.is_empty() {
+                        message.push(' ');
+                        message.push_str(hint);
+                    }
+                }
+                return Err(message);
+            }
+        }
+
         Ok(())
     }
 }
 
+impl std::fmt::Debug for PasswordConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasswordConfig")
+            .field("min_length", &self.min_length)
+            .field("require_uppercase", &self.require_uppercase)
+            .field("require_lowercase", &self.require_lowercase)
+            .field("require_numbers", &self.require_numbers)
+            .field("require_special", &self.require_special)
+            .field("min_strength_score", &self.min_strength_score)
+            .field("require_email_verification", &self.require_email_verification)
+            .field("reset_token_expiry", &self.reset_token_expiry)
+            .field("email_change_expiry", &self.email_change_expiry)
+            .field("fresh_session_age", &self.fresh_session_age)
+            .field("allowed_redirect_origins", &self.allowed_redirect_origins)
+            .field(
+                "send_change_email_verification",
+                &self.send_change_email_verification.is_some(),
+            )
+            .field(
+                "send_email_change_notification",
+                &self.send_email_change_notification.is_some(),
+            )
+            .field("storage", &self.storage.is_some())
+            .field("event_bus", &self.event_bus.is_some())
+            .field("hash_algorithm", &self.hash_algorithm)
+            .field("bcrypt_cost", &self.bcrypt_cost)
+            .field("memory_cost", &self.memory_cost)
+            .field("iterations", &self.iterations)
+            .field("parallelism", &self.parallelism)
+            .field("password_history", &self.password_history)
+            .field("history_storage", &self.history_storage.is_some())
+            .field("check_breached", &self.check_breached)
+            .field("breached_check_fail_closed", &self.breached_check_fail_closed)
+            .field("hibp_base_url", &self.hibp_base_url)
+            .field("max_signin_failures", &self.max_signin_failures)
+            .field("signin_failure_window", &self.signin_failure_window)
+            .field("signin_lockout", &self.signin_lockout)
+            .field("signin_lockout_max", &self.signin_lockout_max)
+            .finish()
+    }
+}
+
 /// User extension fields for password authentication.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PasswordUserExt {
@@ -178,14 +524,48 @@ impl PasswordExt for User {
 }
 
 /// The password authentication plugin.
+///
+/// New passwords are hashed with the configured [`PasswordHashAlgorithm`]
+/// (Argon2id by default) and stored in PHC or modular crypt format, so each
+/// hash records the algorithm and parameters it was created with.
+///
+/// Repeated failed sign-ins for the same email and IP lock that pair out
+/// with a growing delay; see [`record_signin_failure`](Self::record_signin_failure).
 pub struct PasswordPlugin {
     config: PasswordConfig,
+    hashers: PasswordHashers,
+    signin_limiter: Arc<SignInLimiter>,
+    dummy_hash: OnceLock<String>,
 }
 
 impl PasswordPlugin {
     /// Creates a new password plugin.
+    ///
+    /// Out-of-range hashing parameters fall back to the defaults and are
+    /// reported by [`validate_config`](AuthPlugin::validate_config).
     pub fn new(config: PasswordConfig) -> Self {
-        Self { config }
+        let bcrypt_cost = if (BCRYPT_MIN_COST..=BCRYPT_MAX_COST).contains(&config.bcrypt_cost) {
+            config.bcrypt_cost
+        } else {
+            bcrypt::DEFAULT_COST
+        };
+        let hashers = PasswordHashers::new(
+            config.hash_algorithm,
+            config.argon2_params().unwrap_or_default(),
+            bcrypt_cost,
+        );
+        let signin_limiter = Arc::new(SignInLimiter::new(
+            config.max_signin_failures,
+            seconds(config.signin_failure_window),
+            seconds(config.signin_lockout),
+            seconds(config.signin_lockout_max),
+        ));
+        Self {
+            config,
+            hashers,
+            signin_limiter,
+            dummy_hash: OnceLock::new(),
+        }
     }
 
     /// Gets the configuration.
@@ -193,20 +573,69 @@ impl PasswordPlugin {
         &self.config
     }
 
-    /// Hashes a password.
-    ///
-    /// Note: In production, use a proper password hashing library like argon2 or bcrypt.
-    /// This is a placeholder implementation.
+    /// Hashes a password with the configured algorithm and a random salt.
     pub fn hash_password(&self, password: &str) -> String {
-        // PLACEHOLDER: In production, use argon2 or bcrypt
-        // This is NOT secure and is only for demonstration
-        format!("hashed:{}", password)
+        self.hashers.hash(password)
     }
 
     /// Verifies a password against a hash.
+    ///
+    /// The algorithm is detected from the hash prefix and the hash's own
+    /// parameters are used, so imported or older hashes still verify. The
+    /// comparison is constant-time. Unrecognized hashes never verify.
     pub fn verify_password(&self, password: &str, hash: &str) -> bool {
-        // PLACEHOLDER: In production, use proper verification
-        hash == format!("hashed:{}", password)
+        self.hashers.verify(password, hash)
+    }
+
+    /// Verifies `password` against `hash`, or against a throwaway hash when
+    /// there is none.
+    ///
+    /// Use this when signing in, passing `None` for unknown users and users
+    /// without a password, so rejecting them takes as long as rejecting a
+    /// wrong password.
+    pub fn verify_password_or_dummy(&self, password: &str, hash: Option<&str>) -> bool {
+        match hash {
+            Some(hash) => self.verify_password(password, hash),
+            None => {
+                let dummy = self
+                    .dummy_hash
+                    .get_or_init(|| self.hash_password("not a real password"));
+                self.verify_password(password, dummy);
+                false
+            }
+        }
+    }
+
+    /// Returns true if `hash` was not created with the current algorithm
+    /// and parameters.
+    ///
+    /// Sign-in handlers upgrade such hashes with
+    /// [`upgrade_password_hash`](Self::upgrade_password_hash).
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        self.hashers.needs_rehash(hash)
+    }
+
+    /// Re-hashes and stores `user`'s password if its hash
+    /// [needs a rehash](Self::needs_rehash).
+    ///
+    /// Call this once `password` has been verified against the stored hash.
+    /// A failed write is logged and leaves the old hash in place, so the
+    /// sign-in still succeeds.
+    pub async fn upgrade_password_hash(
+        &self,
+        storage: &dyn StorageAdapter,
+        user: &User,
+        password: &str,
+    ) {
+        match user.password_hash() {
+            Some(hash) if self.needs_rehash(&hash) => {}
+            _ => return,
+        }
+        let mut user = user.clone();
+        user.set_password_hash(self.hash_password(password));
+        if let Err(err) = storage.update_user(&user).await {
+            tracing::warn!(user_id = %user.id, error = %err, "failed to upgrade password hash");
+        }
     }
 
     /// Validates a password against the configuration.
@@ -215,6 +644,69 @@ impl PasswordPlugin {
             AuthError::WeakPassword { reason }
         })
     }
+
+    /// Fails with [`AuthError::TooManyAttempts`] while `email` is locked out
+    /// from `ip`.
+    ///
+    /// The lockout error differs from [`AuthError::InvalidCredentials`] but
+    /// is returned for unknown emails too, so it doesn't reveal which
+    /// accounts exist.
+    pub fn check_signin(&self, email: &str, ip: Option<&str>) -> AuthResult<()> {
+        self.signin_limiter.check(&SignInLimiter::key(email, ip))
+    }
+
+    /// Records a failed sign-in for `email` from `ip` and emits
+    /// `signin.failed`.
+    ///
+    /// Call this whenever credentials are rejected, whether or not the email
+    /// belongs to a user. Once `max_signin_failures` is reached within
+    /// `signin_failure_window`, [`check_signin`](Self::check_signin) rejects
+    /// the pair for `signin_lockout` seconds, doubling with each consecutive
+    /// lockout up to `signin_lockout_max`.
+    pub async fn record_signin_failure(&self, email: &str, ip: Option<&str>) -> SignInFailure {
+        let failure = self.signin_limiter.record_failure(&SignInLimiter::key(email, ip));
+
+        if let Some(bus) = &self.config.event_bus {
+            bus.emit(
+                Event::simple(
+                    auth_events::SIGNIN_FAILED,
+                    json!({
+                        "email": email,
+                        "ip": ip,
+                        "failures": failure.failures,
+                        "locked_until": failure.locked_until.map(|t| t.to_rfc3339()),
+                    }),
+                )
+                .with_source("password"),
+            )
+            .await;
+        }
+
+        failure
+    }
+
+    /// Clears failed sign-ins and any lockout for `email` from `ip`.
+    pub fn record_signin_success(&self, email: &str, ip: Option<&str>) {
+        self.signin_limiter.record_success(&SignInLimiter::key(email, ip));
+    }
+
+    /// Returns a plugin with the same configuration that shares this
+    /// plugin's sign-in failure counts.
+    ///
+    /// Give it to plugins that verify passwords themselves, such as the
+    /// username plugin, so every sign-in path counts towards one lockout.
+    pub fn shared(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            hashers: self.hashers.clone(),
+            signin_limiter: self.signin_limiter.clone(),
+            dummy_hash: OnceLock::new(),
+        }
+    }
+}
+
+fn seconds(secs: u64) -> chrono::Duration {
+    chrono::Duration::seconds(secs.min(i64::MAX as u64) as i64)
 }
 
 impl Default for PasswordPlugin {
@@ -241,6 +733,16 @@ impl EventProvider for PasswordPlugin {
                 "Emitted when a password reset is completed",
                 "password",
             ),
+            EventDefinition::simple(
+                "user.email_changed",
+                "Emitted when a user confirms a change of email address",
+                "password",
+            ),
+            EventDefinition::simple(
+                auth_events::SIGNIN_FAILED,
+                "Emitted when a password sign-in is rejected",
+                "password",
+            ),
         ]
     }
 
@@ -259,11 +761,113 @@ impl AuthPlugin for PasswordPlugin {
         "Email/Password Authentication"
     }
 
+    fn validate_config(&self) -> AuthResult<()> {
+        let mut issues = Vec::new();
+        if let Err(message) = self.config.argon2_params() {
+            issues.push(message);
+        }
+        if self.config.min_strength_score > 4 {
+            issues.push("min_strength_score must be between 0 and 4".to_string());
+        }
+        if !(BCRYPT_MIN_COST..=BCRYPT_MAX_COST).contains(&self.config.bcrypt_cost) {
+            issues.push(format!(
+                "bcrypt_cost must be between {} and {}",
+                BCRYPT_MIN_COST, BCRYPT_MAX_COST
+            ));
+        }
+        if self.config.password_history > 0 && self.config.history_storage.is_none() {
+            issues.push("password_history requires a history store".to_string());
+        }
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(AuthError::invalid_config(self.id(), issues))
+        }
+    }
+
     fn define_schema(&self, builder: &mut SchemaBuilder) {
         // Add password reset token table
         for model in PasswordResetToken::schema() {
             builder.add_model_mut(model);
         }
+
+        // Add password history table
+        for model in PasswordHistoryEntry::schema() {
+            builder.add_model_mut(model);
+        }
+    }
+
+    fn register_routes(&self, router: &mut Router) {
+        let plugin = Arc::new(self.shared());
+
+        // POST /user/change-email
+        router.route(
+            Route::new(
+                Method::POST,
+                "/user/change-email",
+                ChangeEmailHandler::new(plugin.clone()),
+            )
+            .summary("Change email")
+            .description(
+                "Starts an email change. Sends a confirmation link to the new address \
+                 and a notification to the current one.",
+            )
+            .tag("user")
+            .requires_auth(),
+        );
+
+        // GET /user/change-email/verify
+        router.route(
+            Route::new(
+                Method::GET,
+                "/user/change-email/verify",
+                VerifyEmailChangeHandler::new(plugin.clone()),
+            )
+            .summary("Confirm email change")
+            .description("Consumes the confirmation token and updates the user's email.")
+            .tag("user"),
+        );
+
+        // POST /change-password
+        let change_password = ChangePasswordHandler::new(plugin.clone());
+        let change_password = match &self.config.storage {
+            Some(storage) => Route::new(
+                Method::POST,
+                "/change-password",
+                RequireRecentAuth::new(
+                    SessionResolver::new(storage.clone()),
+                    chrono::Duration::seconds(self.config.fresh_session_age as i64),
+                    change_password,
+                ),
+            ),
+            None => Route::new(Method::POST, "/change-password", change_password),
+        };
+        router.route(
+            change_password
+                .summary("Change password")
+                .description(
+                    "Sets a new password for the signed-in user. The session must have \
+                     fully authenticated recently.",
+                )
+                .tag("user")
+                .requires_auth(),
+        );
+
+        // POST /reauthenticate
+        router.route(
+            Route::new(
+                Method::POST,
+                "/reauthenticate",
+                ReauthenticateHandler::new(plugin),
+            )
+            .summary("Re-authenticate")
+            .description(
+                "Checks the current password and refreshes the session's authentication \
+                 time without rotating the session.",
+            )
+            .tag("user")
+            .requires_auth(),
+        );
     }
 
     async fn on_before_signup(
@@ -274,13 +878,21 @@ impl AuthPlugin for PasswordPlugin {
         // Validate password if provided
         if let Some(password) = &data.password {
             self.validate_password(password)?;
+            self.check_breached_password(password).await?;
+        }
+        Ok(())
+    }
+
+    async fn on_after_signup(&self, _ctx: &AuthContext, user: &User) -> AuthResult<()> {
+        if let Some(hash) = user.password_hash() {
+            self.record_password_history(&user.id, &hash).await?;
         }
         Ok(())
     }
 
     async fn on_before_signin(
         &self,
-        _ctx: &AuthContext,
+        ctx: &AuthContext,
         creds: &SignInCredentials,
     ) -> AuthResult<()> {
         // Basic validation
@@ -294,8 +906,45 @@ impl AuthPlugin for PasswordPlugin {
                 field: "password".to_string(),
             });
         }
+        let ip = ctx.request.ip.map(|ip| ip.to_string());
+        self.check_signin(&creds.email, ip.as_deref())
+    }
+
+    async fn on_after_signin(&self, ctx: &AuthContext, session: &mut Session) -> AuthResult<()> {
+        let user = match &ctx.user {
+            Some(user) => Some(user.clone()),
+            None => ctx.db.get_user_by_id(&session.user_id).await?,
+        };
+        if let Some(user) = user {
+            // Checked only once the password is known to be right, so the
+            // response doesn't reveal whether an unverified account exists.
+            if self.config.require_email_verification {
+                user.require_verified_email()?;
+            }
+            // A refused sign-in must not clear the failure count.
+            let ip = ctx.request.ip.map(|ip| ip.to_string());
+            self.record_signin_success(&user.email, ip.as_deref());
+        }
         Ok(())
     }
+
+    async fn on_signin_failure(
+        &self,
+        ctx: &AuthContext,
+        creds: &SignInCredentials,
+    ) -> AuthResult<()> {
+        let ip = ctx.request.ip.map(|ip| ip.to_string());
+        self.record_signin_failure(&creds.email, ip.as_deref()).await;
+        Ok(())
+    }
+
+    async fn on_before_user_delete(&self, _ctx: &AuthContext, user_id: &str) -> AuthResult<()> {
+        // Reset tokens cascade with the user row; old hashes do not.
+        match &self.config.history_storage {
+            Some(store) => store.trim_password_history(user_id, 0).await,
+            None => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -315,11 +964,133 @@ mod tests {
         assert!(config.validate("LongEnough1").is_ok());
     }
 
+    #[test]
+    fn test_strength_score_gate() {
+        let config = PasswordConfig::new().require_numbers().min_strength_score(3);
+
+        let err = config.validate("Password1").unwrap_err();
+        assert!(err.contains("Add another word or two"), "{}", err);
+        assert!(config.validate("correct horse battery 9").is_ok());
+        // Character-class checks still apply.
+        assert!(config.validate("correct horse battery staple").is_err());
+    }
+
     #[test]
     fn test_password_hashing() {
         let plugin = PasswordPlugin::default();
         let hash = plugin.hash_password("mypassword");
+        assert!(hash.starts_with("$argon2id$v=19$"));
         assert!(plugin.verify_password("mypassword", &hash));
         assert!(!plugin.verify_password("wrongpassword", &hash));
+        assert_ne!(hash, plugin.hash_password("mypassword"));
+    }
+
+    #[test]
+    fn test_legacy_and_malformed_hashes_do_not_verify() {
+        let plugin = PasswordPlugin::default();
+        assert!(!plugin.verify_password("mypassword", "hashed:mypassword"));
+        assert!(!plugin.verify_password("mypassword", ""));
+        assert!(plugin.needs_rehash("hashed:mypassword"));
+    }
+
+    #[test]
+    fn test_verify_password_or_dummy() {
+        let plugin = PasswordPlugin::default();
+        let hash = plugin.hash_password("mypassword");
+        assert!(plugin.verify_password_or_dummy("mypassword", Some(&hash)));
+        assert!(!plugin.verify_password_or_dummy("mypassword", None));
+        assert!(!plugin.verify_password_or_dummy("not a real password", None));
+    }
+
+    #[test]
+    fn test_needs_rehash_when_parameters_change() {
+        let old = PasswordPlugin::new(PasswordConfig::new().memory_cost(8 * 1024).iterations(1));
+        let hash = old.hash_password("mypassword");
+        assert!(!old.needs_rehash(&hash));
+
+        let new = PasswordPlugin::new(PasswordConfig::new().memory_cost(8 * 1024).iterations(2));
+        assert!(new.needs_rehash(&hash));
+        // Old hashes keep verifying until they are upgraded.
+        assert!(new.verify_password("mypassword", &hash));
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_password_hash() {
+        use better_auth_adapter_memory::MemoryAdapter;
+
+        let storage = MemoryAdapter::new();
+        let old = PasswordPlugin::new(PasswordConfig::new().memory_cost(8 * 1024).iterations(1));
+        let mut user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        user.set_password_hash(old.hash_password("mypassword"));
+        let user = storage.create_user(&user).await.unwrap();
+
+        // Current hashes are left alone.
+        old.upgrade_password_hash(&storage, &user, "mypassword").await;
+        let stored = storage.get_user_by_id("user_1").await.unwrap().unwrap();
+        assert_eq!(stored.password_hash(), user.password_hash());
+
+        let new = PasswordPlugin::new(PasswordConfig::new().memory_cost(8 * 1024).iterations(2));
+        new.upgrade_password_hash(&storage, &user, "mypassword").await;
+        let hash = storage
+            .get_user_by_id("user_1")
+            .await
+            .unwrap()
+            .unwrap()
+            .password_hash()
+            .unwrap();
+        assert!(!new.needs_rehash(&hash));
+        assert!(new.verify_password("mypassword", &hash));
+    }
+
+    #[test]
+    fn test_invalid_hash_params_rejected() {
+        let plugin = PasswordPlugin::new(PasswordConfig::new().parallelism(0).bcrypt_cost(2));
+        assert!(matches!(
+            plugin.validate_config(),
+            Err(AuthError::InvalidPluginConfig { issues }) if issues.len() == 2
+        ));
+        assert!(PasswordPlugin::default().validate_config().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unverified_email_is_checked_after_the_password() {
+        use better_auth_adapter_memory::MemoryAdapter;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        storage.create_user(&user).await.unwrap();
+        let plugin = PasswordPlugin::new(PasswordConfig::new().require_email_verification());
+        let ctx = AuthContext::new(storage);
+
+        // Before the password is checked, the account's state isn't revealed.
+        let creds = SignInCredentials::new("jane@example.com", "wrong");
+        plugin.on_before_signin(&ctx, &creds).await.unwrap();
+
+        let mut session = Session::new("user_1".to_string());
+        let err = plugin.on_after_signin(&ctx, &mut session).await.unwrap_err();
+        assert!(matches!(err, AuthError::EmailNotVerified { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_unverified_signin_keeps_failures() {
+        use better_auth_adapter_memory::MemoryAdapter;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        storage.create_user(&user).await.unwrap();
+        let config = PasswordConfig::new().require_email_verification();
+        let max_failures = config.max_signin_failures;
+        let plugin = PasswordPlugin::new(config);
+        let ctx = AuthContext::new(storage);
+
+        for _ in 1..max_failures {
+            plugin.record_signin_failure("jane@example.com", None).await;
+        }
+        let mut session = Session::new("user_1".to_string());
+        assert!(plugin.on_after_signin(&ctx, &mut session).await.is_err());
+
+        let failure = plugin.record_signin_failure("jane@example.com", None).await;
+        assert_eq!(failure.failures, max_failures);
+        assert!(plugin.check_signin("jane@example.com", None).is_err());
     }
 }
@@ -0,0 +1,90 @@
+//! Re-authentication for sensitive actions.
+//!
+//! `POST /reauthenticate` checks the current password and refreshes the
+//! session's `authenticated_at`. The session keeps its ID and token, so other
+//! tabs and clients using it are unaffected.
+
+use crate::email_change::{current_session, error_response, storage};
+use crate::{PasswordExt, PasswordPlugin};
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::{Session, User};
+use chrono::Duration;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Request body for re-authenticating.
+#[derive(Debug, Deserialize)]
+pub struct ReauthenticateRequest {
+    /// The current password.
+    pub password: String,
+}
+
+/// Handler for POST /reauthenticate
+pub struct ReauthenticateHandler {
+    plugin: Arc<PasswordPlugin>,
+}
+
+impl ReauthenticateHandler {
+    /// Creates a new handler.
+    pub fn new(plugin: Arc<PasswordPlugin>) -> Self {
+        Self { plugin }
+    }
+
+    async fn reauthenticate(&self, req: &Request) -> AuthResult<Response> {
+        let storage = storage(self.plugin.config())?;
+        let body: ReauthenticateRequest = req.json().ok_or_else(|| AuthError::MissingField {
+            field: "password".to_string(),
+        })?;
+
+        let mut session = current_session(&storage, req).await?;
+        let user = storage
+            .get_user_by_id(&session.user_id)
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+
+        require_recent_auth(&self.plugin, &storage, &user, &mut session, Some(&body.password)).await?;
+
+        Ok(Response::ok().json(json!({
+            "success": true,
+            "authenticated_at": session.authenticated_at,
+        })))
+    }
+}
+
+#[async_trait]
+impl RequestHandler for ReauthenticateHandler {
+    async fn handle(&self, req: Request) -> Response {
+        self.reauthenticate(&req).await.unwrap_or_else(error_response)
+    }
+}
+
+/// Requires the current password, or a session that fully authenticated
+/// within `fresh_session_age` when no password is given.
+///
+/// A correct password marks the session as re-authenticated and saves it.
+pub(crate) async fn require_recent_auth(
+    plugin: &PasswordPlugin,
+    storage: &Arc<dyn StorageAdapter>,
+    user: &User,
+    session: &mut Session,
+    password: Option<&str>,
+) -> AuthResult<()> {
+    match (password, user.password_hash()) {
+        (Some(password), Some(hash)) if plugin.verify_password(password, &hash) => {
+            plugin
+                .upgrade_password_hash(storage.as_ref(), user, password)
+                .await;
+            session.mark_authenticated();
+            storage.update_session(session).await?;
+            Ok(())
+        }
+        (Some(_), _) => Err(AuthError::InvalidCredentials),
+        (None, _) => {
+            session.require_recent_auth(Duration::seconds(plugin.config().fresh_session_age as i64))
+        }
+    }
+}
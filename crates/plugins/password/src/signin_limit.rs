@@ -0,0 +1,198 @@
+//! Brute-force protection for password sign-in.
+//!
+//! Failed sign-ins are counted per email and client IP with the shared
+//! [`RateLimiter`]. Once a key reaches the failure limit inside the window it
+//! is locked out, and each further lockout doubles in length up to a cap. A
+//! successful sign-in clears the key. Successful attempts are never counted.
+//!
+//! Lockouts apply to any submitted email, registered or not, and are reported
+//! as [`AuthError::TooManyAttempts`] rather than invalid credentials, so the
+//! response reveals nothing about which accounts exist.
+
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_otp_utils::{RateLimitConfig, RateLimitResult, RateLimiter};
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Mutex;
+
+/// Outcome of recording a failed sign-in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignInFailure {
+    /// Failures counted in the current window, including this one.
+    pub failures: u32,
+    /// When the key is locked out until, if this failure locked it.
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+/// Counts failed sign-ins and locks out keys that fail too often.
+#[derive(Debug)]
+pub(crate) struct SignInLimiter {
+    max_failures: u32,
+    failures: Mutex<RateLimiter>,
+}
+
+impl SignInLimiter {
+    /// Creates a limiter. A `max_failures` of 0 disables it.
+    pub(crate) fn new(
+        max_failures: u32,
+        window: Duration,
+        lockout: Duration,
+        max_lockout: Duration,
+    ) -> Self {
+        let config = if max_failures == 0 {
+            RateLimitConfig::disabled()
+        } else {
+            RateLimitConfig::new(max_failures, window).with_lockout(lockout, max_lockout)
+        };
+        Self {
+            max_failures,
+            failures: Mutex::new(RateLimiter::new(config)),
+        }
+    }
+
+    /// Returns the limiter key for an email and client IP.
+    pub(crate) fn key(email: &str, ip: Option<&str>) -> String {
+        format!(
+            "{}|{}",
+            email.trim().to_lowercase(),
+            ip.unwrap_or("unknown")
+        )
+    }
+
+    /// Fails with [`AuthError::TooManyAttempts`] while `key` is locked out.
+    pub(crate) fn check(&self, key: &str) -> AuthResult<()> {
+        match self.failures.lock().unwrap().locked_until(key) {
+            Some(until) => {
+                let remaining_ms = (until - Utc::now()).num_milliseconds().max(0) as u64;
+                Err(AuthError::TooManyAttempts {
+                    retry_after_seconds: remaining_ms.div_ceil(1000),
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Counts a failed sign-in for `key`, locking it out at the limit.
+    pub(crate) fn record_failure(&self, key: &str) -> SignInFailure {
+        if self.max_failures == 0 {
+            return SignInFailure {
+                failures: 0,
+                locked_until: None,
+            };
+        }
+
+        let mut failures = self.failures.lock().unwrap();
+        match failures.check(key) {
+            RateLimitResult::Allowed { remaining, .. } => SignInFailure {
+                failures: self.max_failures - remaining,
+                locked_until: failures.locked_until(key).filter(|_| remaining == 0),
+            },
+            // Already locked out; the lockout is not extended.
+            RateLimitResult::Limited { .. } => SignInFailure {
+                failures: 0,
+                locked_until: None,
+            },
+        }
+    }
+
+    /// Clears failures and lockouts for `key` after a successful sign-in.
+    pub(crate) fn record_success(&self, key: &str) {
+        self.failures.lock().unwrap().reset(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> SignInLimiter {
+        SignInLimiter::new(
+            3,
+            Duration::minutes(15),
+            Duration::minutes(1),
+            Duration::minutes(3),
+        )
+    }
+
+    #[test]
+    fn test_locks_out_after_max_failures() {
+        let limiter = limiter();
+        let key = SignInLimiter::key("User@Example.com", Some("1.2.3.4"));
+        assert_eq!(key, "user@example.com|1.2.3.4");
+
+        for failures in 1..=2 {
+            let failure = limiter.record_failure(&key);
+            assert_eq!(failure.failures, failures);
+            assert!(failure.locked_until.is_none());
+            assert!(limiter.check(&key).is_ok());
+        }
+
+        let failure = limiter.record_failure(&key);
+        assert_eq!(failure.failures, 3);
+        assert!(failure.locked_until.is_some());
+        match limiter.check(&key) {
+            Err(AuthError::TooManyAttempts {
+                retry_after_seconds,
+            }) => {
+                assert!(retry_after_seconds > 55 && retry_after_seconds <= 60);
+            }
+            other => panic!("expected a lockout, got {:?}", other),
+        }
+
+        // Other IPs are counted separately.
+        assert!(
+            limiter
+                .check(&SignInLimiter::key("user@example.com", None))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_failures_while_locked_out_do_not_extend_the_lockout() {
+        let limiter = limiter();
+        let until = (0..3)
+            .map(|_| limiter.record_failure("k"))
+            .last()
+            .unwrap()
+            .locked_until
+            .unwrap();
+        assert!((58..=60).contains(&(until - Utc::now()).num_seconds()));
+
+        for _ in 0..3 {
+            let failure = limiter.record_failure("k");
+            assert!(failure.locked_until.is_none());
+        }
+        match limiter.check("k") {
+            Err(AuthError::TooManyAttempts {
+                retry_after_seconds,
+            }) => assert!(retry_after_seconds <= 60),
+            other => panic!("expected a lockout, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_success_resets() {
+        let limiter = limiter();
+        for _ in 0..3 {
+            limiter.record_failure("k");
+        }
+        assert!(limiter.check("k").is_err());
+
+        limiter.record_success("k");
+        assert!(limiter.check("k").is_ok());
+        assert_eq!(limiter.record_failure("k").failures, 1);
+    }
+
+    #[test]
+    fn test_zero_max_failures_disables() {
+        let limiter = SignInLimiter::new(
+            0,
+            Duration::minutes(15),
+            Duration::minutes(1),
+            Duration::minutes(3),
+        );
+        for _ in 0..100 {
+            assert!(limiter.record_failure("k").locked_until.is_none());
+        }
+        assert!(limiter.check("k").is_ok());
+    }
+}
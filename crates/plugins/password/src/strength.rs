@@ -0,0 +1,244 @@
+//! Password strength estimation.
+//!
+//! A small, dependency-free estimator in the spirit of zxcvbn. The password
+//! is split into common-password matches, keyboard and alphabet sequences,
+//! repeated characters, and free characters. Each part contributes an
+//! estimated number of guesses, and the total is bucketed into a score from
+//! 0 (trivially guessable) to 4 (very unguessable).
+
+/// Common passwords and keyboard walks, most common first.
+///
+/// Matching is case-insensitive and undoes common leet substitutions, so
+/// `P@ssw0rd` matches `password`.
+const COMMON_PASSWORDS: &[&str] = &[
+    "password", "123456", "qwerty", "letmein", "welcome", "admin", "iloveyou",
+    "monkey", "dragon", "football", "baseball", "abc123", "sunshine", "princess",
+    "master", "shadow", "trustno1", "superman", "batman", "starwars", "login",
+    "passw", "hello", "freedom", "whatever", "qazwsx", "michael", "jennifer",
+    "jordan", "hunter", "ranger", "buster", "soccer", "hockey", "killer",
+    "charlie", "secret", "summer", "winter", "spring", "autumn", "flower",
+    "cheese", "computer", "internet", "pokemon", "pepper", "ginger", "cookie",
+    "mustang", "access", "matrix", "orange", "banana", "purple", "silver",
+    "golden", "lovely", "angel", "tigger", "chocolate", "asdf", "zxcv",
+    "qwertyuiop", "asdfgh", "zxcvbn", "1q2w3e", "changeme", "default",
+    "root", "test", "guest", "user", "pass", "love", "god", "sex", "money",
+];
+
+/// Keyboard rows used to detect walks such as `asdf` or `7890`.
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// A strength estimate with feedback for the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrengthEstimate {
+    /// Score from 0 (weakest) to 4 (strongest).
+    pub score: u8,
+    /// Why the password is weak, if it is.
+    pub warning: Option<&'static str>,
+    /// How to make the password stronger.
+    pub suggestion: &'static str,
+}
+
+/// Estimates the strength of a password on a 0–4 scale.
+///
+/// | Score | Estimated guesses |
+/// |-------|-------------------|
+/// | 0     | < 10^3            |
+/// | 1     | < 10^6            |
+/// | 2     | < 10^8            |
+/// | 3     | < 10^10           |
+/// | 4     | ≥ 10^10           |
+pub fn estimate_strength(password: &str) -> u8 {
+    analyze_strength(password).score
+}
+
+/// Estimates the strength of a password and explains the score.
+pub fn analyze_strength(password: &str) -> StrengthEstimate {
+    let chars: Vec<char> = password.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let normalized: Vec<char> = lower.iter().map(|c| unleet(*c)).collect();
+    let charset_log10 = (charset_size(&chars) as f64).log10();
+
+    let mut guesses_log10 = 0.0;
+    let mut warning = None;
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some((len, rank)) = common_match(&normalized[i..]) {
+            // Rank among common passwords, plus variations in case and leet.
+            let mut bits = ((rank + 1) as f64 * 10.0).log10();
+            if chars[i..i + len].iter().any(|c| c.is_uppercase()) {
+                bits += 0.3;
+            }
+            if lower[i..i + len] != normalized[i..i + len] {
+                bits += 0.3;
+            }
+            guesses_log10 += bits;
+            warning.get_or_insert("This is similar to a commonly used password.");
+            i += len;
+        } else if let Some(len) = sequence_len(&lower[i..]) {
+            guesses_log10 += charset_log10 + 0.5;
+            warning.get_or_insert("Sequences like abc or 6543 are easy to guess.");
+            i += len;
+        } else if let Some(len) = repeat_len(&chars[i..]) {
+            guesses_log10 += charset_log10 + (len as f64).log10();
+            warning.get_or_insert("Repeated characters like aaa are easy to guess.");
+            i += len;
+        } else {
+            guesses_log10 += charset_log10;
+            i += 1;
+        }
+    }
+
+    let score = match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    };
+
+    let suggestion = match warning {
+        _ if score >= 3 => "",
+        Some(w) if w.starts_with("This is similar") => {
+            "Add another word or two. Uncommon words are better."
+        }
+        Some(w) if w.starts_with("Sequences") => "Avoid sequences.",
+        Some(_) => "Avoid repeated words and characters.",
+        None => "Add another word or two. Uncommon words are better.",
+    };
+
+    StrengthEstimate {
+        score,
+        warning: if score >= 3 { None } else { warning },
+        suggestion,
+    }
+}
+
+/// Size of the character classes used in the password.
+fn charset_size(chars: &[char]) -> u32 {
+    let mut size = 0;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        size += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        size += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        size += 10;
+    }
+    if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') {
+        size += 33;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        size += 100;
+    }
+    size.max(10)
+}
+
+/// Undoes common leet substitutions.
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        _ => c,
+    }
+}
+
+/// Finds the longest common password at the start of `text`.
+///
+/// Returns its length and rank. Digits are compared both as themselves and
+/// as their leet letters, so `123456` still matches.
+fn common_match(text: &[char]) -> Option<(usize, usize)> {
+    COMMON_PASSWORDS
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| {
+            let word: Vec<char> = word.chars().collect();
+            word.len() >= 3
+                && text.len() >= word.len()
+                && word
+                    .iter()
+                    .zip(text)
+                    .all(|(w, t)| *w == *t || unleet(*w) == *t)
+        })
+        .map(|(rank, word)| (word.chars().count(), rank))
+        .max_by_key(|(len, _)| *len)
+}
+
+/// Length of an alphabet, digit, or keyboard-row sequence (3+ characters)
+/// at the start of `text`.
+fn sequence_len(text: &[char]) -> Option<usize> {
+    let step_len = |step: i32| {
+        1 + text
+            .windows(2)
+            .take_while(|w| w[1] as i32 - w[0] as i32 == step)
+            .count()
+    };
+    let keyboard_len = || {
+        KEYBOARD_ROWS
+            .iter()
+            .flat_map(|row| [row.to_string(), row.chars().rev().collect()])
+            .map(|row| {
+                let row: Vec<char> = row.chars().collect();
+                row.iter()
+                    .position(|c| Some(c) == text.first())
+                    .map(|start| {
+                        row[start..]
+                            .iter()
+                            .zip(text)
+                            .take_while(|(r, t)| r == t)
+                            .count()
+                    })
+                    .unwrap_or(0)
+            })
+            .max()
+            .unwrap_or(0)
+    };
+
+    let len = step_len(1).max(step_len(-1)).max(keyboard_len());
+    (len >= 3).then_some(len)
+}
+
+/// Length of a run of one repeated character (3+) at the start of `text`.
+fn repeat_len(text: &[char]) -> Option<usize> {
+    let first = text.first()?;
+    let len = text.iter().take_while(|c| *c == first).count();
+    (len >= 3).then_some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_passwords_score_low() {
+        assert_eq!(estimate_strength(""), 0);
+        assert_eq!(estimate_strength("password"), 0);
+        assert!(estimate_strength("Password1") <= 1);
+        assert!(estimate_strength("P@ssw0rd!") <= 1);
+        assert!(estimate_strength("qwerty123") <= 1);
+        assert!(estimate_strength("aaaaaaaaaa") <= 1);
+        assert!(estimate_strength("abcdefgh12345") <= 1);
+    }
+
+    #[test]
+    fn test_unpredictable_passwords_score_high() {
+        assert!(estimate_strength("LongEnough1") >= 3);
+        assert_eq!(estimate_strength("correct horse battery staple"), 4);
+        assert_eq!(estimate_strength("tK9#vQ2!mZ"), 4);
+    }
+
+    #[test]
+    fn test_feedback() {
+        let estimate = analyze_strength("Password1");
+        assert_eq!(estimate.warning, Some("This is similar to a commonly used password."));
+        assert!(estimate.suggestion.contains("Add another word or two"));
+
+        let estimate = analyze_strength("correct horse battery staple");
+        assert_eq!(estimate.warning, None);
+        assert_eq!(estimate.suggestion, "");
+    }
+}
@@ -1,5 +1,10 @@
 //! Configuration for the Phone Number plugin.
 
+use crate::{InMemoryPhoneVerificationStore, PhoneVerification, PhoneVerificationStore};
+use better_auth_core::crypto::constant_time_eq;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_otp_utils::{LoggingSender, MessageSender, OtpConfig, OtpGenerator};
+use chrono::{Duration, Utc};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -89,10 +94,17 @@ pub struct PhoneNumberConfig {
     pub expires_in: u64,
     /// Maximum verification attempts. Default: 3.
     pub allowed_attempts: u32,
-    /// Callback to send the OTP.
+    /// Callback to send the OTP. Takes precedence over `sender`.
     pub send_otp: Option<SendOtpCallback>,
-    /// Optional custom OTP verification callback.
+    /// Delivers OTPs by SMS. Default: [`LoggingSender`], which only logs
+    /// them.
+    pub sender: Arc<dyn MessageSender>,
+    /// Optional custom OTP verification callback. Replaces the check
+    /// against `verification_store`.
     pub verify_otp: Option<VerifyOtpCallback>,
+    /// Store for sent OTPs, which `/phone-number/verify` checks codes
+    /// against. Default: [`InMemoryPhoneVerificationStore`].
+    pub verification_store: Arc<dyn PhoneVerificationStore>,
     /// Optional phone number validator.
     pub phone_number_validator: Option<PhoneValidatorFn>,
     /// Configuration for sign-up on verification.
@@ -103,6 +115,11 @@ pub struct PhoneNumberConfig {
     pub send_password_reset_otp: Option<SendOtpCallback>,
     /// Callback after phone verification.
     pub callback_on_verification: Option<Arc<dyn Fn(&str, &str) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>>,
+    /// Maximum OTPs sent to one phone number within `send_window`.
+    /// Default: 3.
+    pub max_sends: u32,
+    /// Window for `max_sends`, in seconds. Default: 900 (15 minutes).
+    pub send_window: u64,
 }
 
 impl Default for PhoneNumberConfig {
@@ -112,12 +129,16 @@ impl Default for PhoneNumberConfig {
             expires_in: 300, // 5 minutes
             allowed_attempts: 3,
             send_otp: None,
+            sender: Arc::new(LoggingSender::new()),
             verify_otp: None,
+            verification_store: Arc::new(InMemoryPhoneVerificationStore::new()),
             phone_number_validator: None,
             sign_up_on_verification: None,
             require_verification: false,
             send_password_reset_otp: None,
             callback_on_verification: None,
+            max_sends: 3,
+            send_window: 15 * 60,
         }
     }
 }
@@ -156,6 +177,12 @@ impl PhoneNumberConfig {
         self
     }
 
+    /// Sets the sender that delivers OTPs by SMS.
+    pub fn sender(mut self, sender: Arc<dyn MessageSender>) -> Self {
+        self.sender = sender;
+        self
+    }
+
     /// Sets a custom OTP verification callback.
     pub fn verify_otp<F, Fut>(mut self, callback: F) -> Self
     where
@@ -166,6 +193,12 @@ impl PhoneNumberConfig {
         self
     }
 
+    /// Sets the store for sent OTPs.
+    pub fn verification_store(mut self, store: Arc<dyn PhoneVerificationStore>) -> Self {
+        self.verification_store = store;
+        self
+    }
+
     /// Sets a phone number validator.
     pub fn phone_number_validator<F>(mut self, validator: F) -> Self
     where
@@ -187,6 +220,14 @@ impl PhoneNumberConfig {
         self
     }
 
+    /// Limits how many OTPs one phone number can be sent within
+    /// `window_seconds`.
+    pub fn send_rate_limit(mut self, max_sends: u32, window_seconds: u64) -> Self {
+        self.max_sends = max_sends;
+        self.send_window = window_seconds;
+        self
+    }
+
     /// Validates a phone number.
     pub fn validate_phone(&self, phone: &str) -> bool {
         if let Some(ref validator) = self.phone_number_validator {
@@ -196,6 +237,65 @@ impl PhoneNumberConfig {
             phone.starts_with('+') && phone.len() >= 10
         }
     }
+
+    /// Generates an OTP.
+    pub(crate) fn new_otp(&self) -> String {
+        OtpGenerator::new(OtpConfig::numeric(self.otp_length)).generate()
+    }
+
+    /// Generates an OTP, stores it, and delivers it to `phone_number`.
+    pub(crate) async fn issue_otp(&self, phone_number: &str) -> AuthResult<()> {
+        let code = self.new_otp();
+        let expires_at = Utc::now() + Duration::seconds(self.expires_in as i64);
+        self.verification_store
+            .create_phone_verification(&PhoneVerification::new(phone_number, &code, expires_at))
+            .await?;
+        self.deliver(PhoneOtpData::new(phone_number, code)).await
+    }
+
+    /// Checks `code` against the one sent to `phone_number`, using it up on
+    /// success.
+    ///
+    /// Each wrong guess counts as an attempt; after `allowed_attempts` the
+    /// code is deleted and a new one must be requested.
+    pub(crate) async fn check_otp(&self, phone_number: &str, code: &str) -> AuthResult<()> {
+        if let Some(callback) = &self.verify_otp {
+            return match callback(phone_number.to_string(), code.to_string()).await {
+                true => Ok(()),
+                false => Err(AuthError::InvalidToken),
+            };
+        }
+
+        let store = self.verification_store.as_ref();
+        let Some(mut verification) = store.get_phone_verification(phone_number).await? else {
+            return Err(AuthError::InvalidToken);
+        };
+        if verification.is_expired() {
+            store.delete_phone_verification(&verification.id).await?;
+            return Err(AuthError::TokenExpired);
+        }
+        if !constant_time_eq(&verification.code, code) {
+            verification.increment_attempts();
+            if verification.attempts >= self.allowed_attempts as i32 {
+                store.delete_phone_verification(&verification.id).await?;
+            } else {
+                store.update_phone_verification(&verification).await?;
+            }
+            return Err(AuthError::InvalidToken);
+        }
+        store.delete_phone_verification(&verification.id).await
+    }
+
+    /// Delivers an OTP through the callback, or `sender` if there is none.
+    pub(crate) async fn deliver(&self, data: PhoneOtpData) -> AuthResult<()> {
+        if let Some(callback) = &self.send_otp {
+            return callback(data)
+                .await
+                .map_err(|message| AuthError::plugin("phone_number", message));
+        }
+        let body = format!("Your verification code is {}", data.code);
+        self.sender.send(&data.phone_number, None, &body).await
+    }
 }
 
 impl std::fmt::Debug for PhoneNumberConfig {
@@ -205,10 +305,14 @@ impl std::fmt::Debug for PhoneNumberConfig {
             .field("expires_in", &self.expires_in)
             .field("allowed_attempts", &self.allowed_attempts)
             .field("send_otp", &self.send_otp.is_some())
+            .field("sender", &"<sender>")
             .field("verify_otp", &self.verify_otp.is_some())
+            .field("verification_store", &"<store>")
             .field("phone_number_validator", &self.phone_number_validator.is_some())
             .field("sign_up_on_verification", &self.sign_up_on_verification)
             .field("require_verification", &self.require_verification)
+            .field("max_sends", &self.max_sends)
+            .field("send_window", &self.send_window)
             .finish()
     }
 }
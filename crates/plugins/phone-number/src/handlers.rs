@@ -1,8 +1,12 @@
 //! Request handlers for the Phone Number plugin.
 
 use async_trait::async_trait;
+use better_auth_core::error::AuthError;
 use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_otp_utils::RateLimiter;
+use crate::PhoneNumberConfig;
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
 
 /// Request body for sending OTP.
 #[derive(Debug, Deserialize)]
@@ -19,7 +23,13 @@ pub struct SendOtpResponse {
 }
 
 /// Handler for POST /phone-number/send-otp
-pub struct SendOtpHandler;
+///
+/// Sends to each phone number are rate limited, so the endpoint cannot be
+/// used to spam a victim or run up SMS costs.
+pub struct SendOtpHandler {
+    pub config: PhoneNumberConfig,
+    pub send_limiter: Arc<Mutex<RateLimiter>>,
+}
 
 #[async_trait]
 impl RequestHandler for SendOtpHandler {
@@ -45,15 +55,63 @@ impl RequestHandler for SendOtpHandler {
             }));
         }
 
-        // In a real implementation, this would:
-        // 1. Generate OTP
-        // 2. Store in database
-        // 3. Call sendOTP callback
-        
+        let limited = self
+            .send_limiter
+            .lock()
+            .unwrap()
+            .check(&body.phone_number)
+            .into_result();
+        if let Err(err) = limited {
+            return rate_limited_response(err);
+        }
+
+        if let Err(err) = self.config.issue_otp(&body.phone_number).await {
+            return send_failed_response(err);
+        }
+
         Response::ok().json(SendOtpResponse { success: true })
     }
 }
 
+/// Converts a rate limit error into a 429 response with `Retry-After`.
+fn rate_limited_response(err: AuthError) -> Response {
+    let mut response = Response::new(err.status_code()).json(serde_json::json!({
+        "error": {
+            "code": "RATE_LIMITED",
+            "message": err.to_string()
+        }
+    }));
+    if let AuthError::RateLimitExceeded { retry_after_seconds } = err {
+        response = response.header("Retry-After", retry_after_seconds.to_string());
+    }
+    response
+}
+
+/// Converts a failed OTP check into an error response.
+fn invalid_otp_response(err: AuthError) -> Response {
+    let code = match err {
+        AuthError::TokenExpired => "OTP_EXPIRED",
+        AuthError::InvalidToken => "INVALID_OTP",
+        _ => "INTERNAL_ERROR",
+    };
+    Response::new(err.status_code()).json(serde_json::json!({
+        "error": {
+            "code": code,
+            "message": err.to_string()
+        }
+    }))
+}
+
+/// Converts a failure to deliver an OTP into an error response.
+fn send_failed_response(err: AuthError) -> Response {
+    Response::new(err.status_code()).json(serde_json::json!({
+        "error": {
+            "code": "SEND_FAILED",
+            "message": err.to_string()
+        }
+    }))
+}
+
 /// Request body for verifying phone number.
 #[derive(Debug, Deserialize)]
 pub struct VerifyPhoneRequest {
@@ -67,7 +125,11 @@ pub struct VerifyPhoneRequest {
 }
 
 /// Handler for POST /phone-number/verify
-pub struct VerifyPhoneHandler;
+///
+/// Checks the code against the one sent by `/phone-number/send-otp`.
+pub struct VerifyPhoneHandler {
+    pub config: PhoneNumberConfig,
+}
 
 #[async_trait]
 impl RequestHandler for VerifyPhoneHandler {
@@ -92,11 +154,10 @@ impl RequestHandler for VerifyPhoneHandler {
             }));
         }
 
-        // In a real implementation, this would:
-        // 1. Verify OTP
-        // 2. Mark phone as verified
-        // 3. Optionally create session
-        
+        if let Err(err) = self.config.check_otp(&body.phone_number, &body.code).await {
+            return invalid_otp_response(err);
+        }
+
         Response::ok().json(serde_json::json!({
             "success": true,
             "phone_number_verified": true
@@ -6,9 +6,11 @@
 mod config;
 mod schema;
 mod handlers;
+mod store;
 
 pub use config::{PhoneNumberConfig, PhoneOtpData, SignUpOnVerificationConfig};
 pub use schema::{PhoneVerification, PhoneNumberSchema, PhoneNumberUserExt};
+pub use store::{InMemoryPhoneVerificationStore, PhoneVerificationStore};
 
 use async_trait::async_trait;
 use better_auth_core::context::AuthContext;
@@ -18,8 +20,9 @@ use better_auth_core::schema::{Field, FieldType, SchemaBuilder};
 use better_auth_core::traits::{AuthPlugin, ExtensionProvider, SchemaProvider};
 use better_auth_core::types::User;
 use better_auth_events_sdk::{EventDefinition, EventProvider};
-use better_auth_otp_utils::{OtpGenerator, OtpConfig};
+use better_auth_otp_utils::{RateLimitConfig, RateLimiter};
 use chrono::Duration;
+use std::sync::{Arc, Mutex};
 
 /// Trait for phone number operations on users.
 pub trait PhoneNumberExt {
@@ -54,12 +57,17 @@ impl PhoneNumberExt for User {
 /// The Phone Number authentication plugin.
 pub struct PhoneNumberPlugin {
     config: PhoneNumberConfig,
+    send_limiter: Arc<Mutex<RateLimiter>>,
 }
 
 impl PhoneNumberPlugin {
     /// Creates a new Phone Number plugin with the given configuration.
     pub fn new(config: PhoneNumberConfig) -> Self {
-        Self { config }
+        let send_limiter = Arc::new(Mutex::new(RateLimiter::new(RateLimitConfig::new(
+            config.max_sends,
+            Duration::seconds(config.send_window as i64),
+        ))));
+        Self { config, send_limiter }
     }
 
     /// Gets the plugin configuration.
@@ -69,8 +77,7 @@ impl PhoneNumberPlugin {
 
     /// Generates a new OTP code.
     pub fn generate_otp(&self) -> String {
-        let generator = OtpGenerator::new(OtpConfig::numeric(self.config.otp_length));
-        generator.generate()
+        self.config.new_otp()
     }
 }
 
@@ -139,7 +146,10 @@ impl AuthPlugin for PhoneNumberPlugin {
             Route::new(
                 Method::POST,
                 "/phone-number/send-otp",
-                handlers::SendOtpHandler,
+                handlers::SendOtpHandler {
+                    config: self.config.clone(),
+                    send_limiter: self.send_limiter.clone(),
+                },
             )
             .summary("Send OTP to phone")
             .description("Sends a one-time password to the specified phone number.")
@@ -151,7 +161,9 @@ impl AuthPlugin for PhoneNumberPlugin {
             Route::new(
                 Method::POST,
                 "/phone-number/verify",
-                handlers::VerifyPhoneHandler,
+                handlers::VerifyPhoneHandler {
+                    config: self.config.clone(),
+                },
             )
             .summary("Verify phone number")
             .description("Verifies a phone number using an OTP and optionally creates a session.")
@@ -232,4 +244,99 @@ mod tests {
         assert_eq!(user.phone_number(), Some("+1234567890".to_string()));
         assert!(user.phone_number_verified());
     }
+
+    async fn send(router: &Router, path: &str, body: serde_json::Value) -> better_auth_core::router::Response {
+        use better_auth_core::router::Request;
+
+        let route = router.routes().find(|r| r.path == path).unwrap();
+        let mut req = Request::new(Method::POST, path);
+        req.body = Some(body);
+        route.handler.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_send_is_rate_limited() {
+        let plugin = PhoneNumberPlugin::new(PhoneNumberConfig::new().send_rate_limit(3, 15 * 60));
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        for _ in 0..3 {
+            assert_eq!(send(&router, "/phone-number/send-otp", serde_json::json!({ "phoneNumber": "+15555550100" })).await.status, 200);
+        }
+        let response = send(&router, "/phone-number/send-otp", serde_json::json!({ "phoneNumber": "+15555550100" })).await;
+        assert_eq!(response.status, 429);
+        assert!(response.headers.contains_key("retry-after"));
+        assert_eq!(response.body.unwrap()["error"]["code"], "RATE_LIMITED");
+
+        // Other recipients are unaffected.
+        assert_eq!(send(&router, "/phone-number/send-otp", serde_json::json!({ "phoneNumber": "+15555550101" })).await.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_send_dispatches_through_sender() {
+        let sender = Arc::new(better_auth_otp_utils::testing::RecordingSender::new());
+        let plugin = PhoneNumberPlugin::new(PhoneNumberConfig::new().otp_length(8).sender(sender.clone()));
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let response = send(&router, "/phone-number/send-otp", serde_json::json!({ "phoneNumber": "+15555550100" })).await;
+        assert_eq!(response.status, 200);
+
+        let sent = sender.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "+15555550100");
+        assert!(sent[0].subject.is_none());
+        let code = sent[0].body.rsplit(' ').next().unwrap();
+        assert_eq!(code.len(), 8);
+        assert!(code.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[tokio::test]
+    async fn test_verify_checks_the_sent_code() {
+        let sender = Arc::new(better_auth_otp_utils::testing::RecordingSender::new());
+        let plugin = PhoneNumberPlugin::new(PhoneNumberConfig::new().sender(sender.clone()));
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        let verify = |code: String| {
+            serde_json::json!({ "phoneNumber": "+15555550100", "code": code })
+        };
+
+        // Nothing has been sent yet.
+        let response = send(&router, "/phone-number/verify", verify("123456".to_string())).await;
+        assert_eq!(response.body.unwrap()["error"]["code"], "INVALID_OTP");
+
+        send(&router, "/phone-number/send-otp", serde_json::json!({ "phoneNumber": "+15555550100" })).await;
+        let code = sender.sent()[0].body.rsplit(' ').next().unwrap().to_string();
+        let wrong = if code == "000000" { "111111" } else { "000000" };
+
+        let response = send(&router, "/phone-number/verify", verify(wrong.to_string())).await;
+        assert_eq!(response.body.unwrap()["error"]["code"], "INVALID_OTP");
+
+        let response = send(&router, "/phone-number/verify", verify(code.clone())).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.unwrap()["phone_number_verified"], true);
+
+        // The code is used up.
+        let response = send(&router, "/phone-number/verify", verify(code)).await;
+        assert_eq!(response.body.unwrap()["error"]["code"], "INVALID_OTP");
+    }
+
+    #[tokio::test]
+    async fn test_verify_gives_up_after_allowed_attempts() {
+        let sender = Arc::new(better_auth_otp_utils::testing::RecordingSender::new());
+        let plugin = PhoneNumberPlugin::new(PhoneNumberConfig::new().sender(sender.clone()));
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+
+        send(&router, "/phone-number/send-otp", serde_json::json!({ "phoneNumber": "+15555550100" })).await;
+        let code = sender.sent()[0].body.rsplit(' ').next().unwrap().to_string();
+        let wrong = if code == "000000" { "111111" } else { "000000" };
+
+        for _ in 0..3 {
+            send(&router, "/phone-number/verify", serde_json::json!({ "phoneNumber": "+15555550100", "code": wrong })).await;
+        }
+        let response = send(&router, "/phone-number/verify", serde_json::json!({ "phoneNumber": "+15555550100", "code": code })).await;
+        assert_eq!(response.body.unwrap()["error"]["code"], "INVALID_OTP");
+    }
 }
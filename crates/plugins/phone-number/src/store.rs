@@ -0,0 +1,89 @@
+//! Storage for sent phone verification codes.
+
+use crate::PhoneVerification;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use std::sync::RwLock;
+
+/// Storage for sent phone verification codes.
+///
+/// Adapters implement this trait to persist the `phone_verification`
+/// model.
+#[async_trait]
+pub trait PhoneVerificationStore: Send + Sync {
+    /// Stores a sent code, replacing any earlier code for the same phone
+    /// number.
+    async fn create_phone_verification(
+        &self,
+        verification: &PhoneVerification,
+    ) -> AuthResult<PhoneVerification>;
+
+    /// Gets the code sent to `phone_number`, expired or not.
+    async fn get_phone_verification(
+        &self,
+        phone_number: &str,
+    ) -> AuthResult<Option<PhoneVerification>>;
+
+    /// Updates a code, to record a failed attempt.
+    async fn update_phone_verification(
+        &self,
+        verification: &PhoneVerification,
+    ) -> AuthResult<PhoneVerification>;
+
+    /// Deletes a code by ID.
+    async fn delete_phone_verification(&self, id: &str) -> AuthResult<()>;
+}
+
+/// In-memory phone verification store.
+///
+/// Suitable for a single instance and for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryPhoneVerificationStore {
+    verifications: RwLock<Vec<PhoneVerification>>,
+}
+
+impl InMemoryPhoneVerificationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PhoneVerificationStore for InMemoryPhoneVerificationStore {
+    async fn create_phone_verification(
+        &self,
+        verification: &PhoneVerification,
+    ) -> AuthResult<PhoneVerification> {
+        let mut verifications = self.verifications.write().unwrap();
+        verifications.retain(|v| v.phone_number != verification.phone_number);
+        verifications.push(verification.clone());
+        Ok(verification.clone())
+    }
+
+    async fn get_phone_verification(
+        &self,
+        phone_number: &str,
+    ) -> AuthResult<Option<PhoneVerification>> {
+        let verifications = self.verifications.read().unwrap();
+        Ok(verifications
+            .iter()
+            .find(|v| v.phone_number == phone_number)
+            .cloned())
+    }
+
+    async fn update_phone_verification(
+        &self,
+        verification: &PhoneVerification,
+    ) -> AuthResult<PhoneVerification> {
+        let mut verifications = self.verifications.write().unwrap();
+        if let Some(existing) = verifications.iter_mut().find(|v| v.id == verification.id) {
+            *existing = verification.clone();
+        }
+        Ok(verification.clone())
+    }
+
+    async fn delete_phone_verification(&self, id: &str) -> AuthResult<()> {
+        self.verifications.write().unwrap().retain(|v| v.id != id);
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! Failed TOTP attempt tracking and lockout.
+
+use crate::config::TotpOptions;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_otp_utils::AttemptTracker;
+use chrono::{Duration, Utc};
+use std::sync::Mutex;
+
+/// Counts failed TOTP verifications per user.
+///
+/// `max_attempts` failures within the window lock the user out until
+/// `lockout_duration` has passed since the last one. While locked, every
+/// code is rejected, valid or not. A successful verification clears the
+/// count.
+#[derive(Debug)]
+pub(crate) struct TotpAttempts {
+    tracker: Mutex<AttemptTracker>,
+    window: Duration,
+    lockout: Duration,
+}
+
+impl TotpAttempts {
+    pub(crate) fn new(options: &TotpOptions) -> Self {
+        Self {
+            tracker: Mutex::new(AttemptTracker::new(options.max_attempts)),
+            window: Duration::seconds(options.attempt_window as i64),
+            lockout: Duration::seconds(options.lockout_duration as i64),
+        }
+    }
+
+    /// Fails with [`AuthError::TooManyAttempts`] if `user_id` is locked out.
+    pub(crate) fn check(&self, user_id: &str) -> AuthResult<()> {
+        let mut tracker = self.tracker.lock().unwrap();
+        let Some(record) = tracker.get(user_id) else {
+            return Ok(());
+        };
+        let now = Utc::now();
+        if record.is_exceeded() {
+            let locked_until = record.last_attempt + self.lockout;
+            if now < locked_until {
+                // Round up so clients never retry a moment too early.
+                let remaining = (locked_until - now).num_milliseconds();
+                return Err(AuthError::TooManyAttempts {
+                    retry_after_seconds: (remaining as u64).div_ceil(1000),
+                });
+            }
+            tracker.reset(user_id);
+        } else if now - record.first_attempt > self.window {
+            tracker.reset(user_id);
+        }
+        Ok(())
+    }
+
+    /// Records a failed verification for `user_id`.
+    pub(crate) fn record_failure(&self, user_id: &str) {
+        self.tracker.lock().unwrap().record_attempt(user_id);
+    }
+
+    /// Clears the failures of `user_id` after a successful verification.
+    pub(crate) fn reset(&self, user_id: &str) {
+        self.tracker.lock().unwrap().reset(user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(max_attempts: u32, attempt_window: u64, lockout_duration: u64) -> TotpOptions {
+        TotpOptions {
+            max_attempts,
+            attempt_window,
+            lockout_duration,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_locks_after_max_failures() {
+        let attempts = TotpAttempts::new(&options(3, 300, 900));
+        for _ in 0..3 {
+            attempts.check("user_1").unwrap();
+            attempts.record_failure("user_1");
+        }
+
+        let Err(AuthError::TooManyAttempts {
+            retry_after_seconds,
+        }) = attempts.check("user_1")
+        else {
+            panic!("expected lockout");
+        };
+        assert!(retry_after_seconds > 890 && retry_after_seconds <= 900);
+        assert!(attempts.check("user_2").is_ok());
+    }
+
+    #[test]
+    fn test_failures_expire_with_window_and_lockout() {
+        let attempts = TotpAttempts::new(&options(2, 0, 0));
+        attempts.record_failure("user_1");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        // The first failure fell out of the window, so counting restarts.
+        attempts.check("user_1").unwrap();
+        attempts.record_failure("user_1");
+        attempts.check("user_1").unwrap();
+
+        attempts.record_failure("user_1");
+        attempts.record_failure("user_1");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(attempts.check("user_1").is_ok());
+    }
+
+    #[test]
+    fn test_success_clears_failures() {
+        let attempts = TotpAttempts::new(&options(2, 300, 900));
+        attempts.record_failure("user_1");
+        attempts.reset("user_1");
+        attempts.record_failure("user_1");
+        assert!(attempts.check("user_1").is_ok());
+    }
+}
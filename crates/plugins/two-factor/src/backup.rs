@@ -1,6 +1,71 @@
 //! Backup code management.
+//!
+//! Codes are shown to the user once, when generated, and only salted
+//! SHA-256 hashes are stored. A database leak then reveals no usable
+//! recovery codes.
 
+use chrono::{DateTime, Utc};
 use rand::Rng;
+use ring::digest::{SHA256, digest};
+use serde::{Deserialize, Serialize};
+
+/// A stored backup code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupCode {
+    /// Salted hash of the normalized code, as `<salt>$<sha256>` in hex.
+    pub hash: String,
+    /// When the code was used. Used codes never verify again.
+    #[serde(default)]
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+impl BackupCode {
+    /// Hashes a plaintext code with a fresh random salt.
+    pub fn new(code: &str) -> Self {
+        let salt: [u8; 16] = rand::thread_rng().r#gen();
+        let salt = to_hex(&salt);
+        let hash = hash_with_salt(&salt, &normalize(code));
+        Self {
+            hash: format!("{}${}", salt, hash),
+            used_at: None,
+        }
+    }
+
+    /// Returns true if the code has been used.
+    pub fn is_used(&self) -> bool {
+        self.used_at.is_some()
+    }
+
+    /// Checks `code` against this hash in constant time. Dashes, spaces
+    /// and case are ignored.
+    pub fn matches(&self, code: &str) -> bool {
+        let Some((salt, expected)) = self.hash.split_once('$') else {
+            return false;
+        };
+        let actual = hash_with_salt(salt, &normalize(code));
+        actual.len() == expected.len()
+            && actual
+                .bytes()
+                .zip(expected.bytes())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                == 0
+    }
+}
+
+/// Uppercases a code and strips the separators added for display.
+fn normalize(code: &str) -> String {
+    code.to_uppercase().replace(['-', ' '], "")
+}
+
+fn hash_with_salt(salt: &str, code: &str) -> String {
+    let mut input = salt.as_bytes().to_vec();
+    input.extend_from_slice(code.as_bytes());
+    to_hex(digest(&SHA256, &input).as_ref())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
 
 /// Backup code manager.
 #[derive(Debug, Clone)]
@@ -34,11 +99,29 @@ impl BackupCodeManager {
             .collect()
     }
 
-    /// Verifies a backup code against a list of valid codes.
+    /// Hashes plaintext codes for storage.
+    pub fn hash_all(codes: &[String]) -> Vec<BackupCode> {
+        codes.iter().map(|code| BackupCode::new(code)).collect()
+    }
+
+    /// Verifies a backup code against stored codes, skipping used ones.
     /// Returns the index of the matched code if found.
-    pub fn verify(&self, code: &str, valid_codes: &[String]) -> Option<usize> {
-        let normalized = code.to_uppercase().replace(['-', ' '], "");
-        valid_codes.iter().position(|c| c == &normalized)
+    pub fn verify(&self, code: &str, stored: &[BackupCode]) -> Option<usize> {
+        stored
+            .iter()
+            .position(|stored| !stored.is_used() && stored.matches(code))
+    }
+
+    /// Verifies a backup code and marks it used, so it cannot be used
+    /// again. Returns false if no unused code matched.
+    pub fn consume(&self, code: &str, stored: &mut [BackupCode]) -> bool {
+        match self.verify(code, stored) {
+            Some(index) => {
+                stored[index].used_at = Some(Utc::now());
+                true
+            }
+            None => false,
+        }
     }
 
     /// Formats a backup code for display (e.g., "ABCD-EFGH-IJKL").
@@ -77,14 +160,34 @@ mod tests {
     #[test]
     fn test_backup_code_verification() {
         let manager = BackupCodeManager::default();
-        let codes = vec!["ABCDEFGHIJ".to_string(), "KLMNOPQRST".to_string()];
+        let codes = BackupCodeManager::hash_all(&["ABCDEFGHIJ".to_string(), "KLMNOPQRST".to_string()]);
         
         assert_eq!(manager.verify("ABCDEFGHIJ", &codes), Some(0));
         assert_eq!(manager.verify("abcdefghij", &codes), Some(0)); // Case insensitive
         assert_eq!(manager.verify("ABCD-EFGH-IJ", &codes), Some(0)); // With dashes
+        assert_eq!(manager.verify("KLMNOPQRST", &codes), Some(1));
         assert_eq!(manager.verify("INVALID", &codes), None);
     }
 
+    #[test]
+    fn test_codes_are_stored_salted() {
+        let codes = BackupCodeManager::hash_all(&["ABCDEFGHIJ".to_string(), "ABCDEFGHIJ".to_string()]);
+
+        assert!(!codes[0].hash.contains("ABCDEFGHIJ"));
+        // The same code hashes differently under different salts.
+        assert_ne!(codes[0].hash, codes[1].hash);
+    }
+
+    #[test]
+    fn test_consumed_code_cannot_be_reused() {
+        let manager = BackupCodeManager::default();
+        let mut codes = BackupCodeManager::hash_all(&["ABCDEFGHIJ".to_string()]);
+
+        assert!(manager.consume("ABCD-EFGH-IJ", &mut codes));
+        assert!(codes[0].is_used());
+        assert!(!manager.consume("ABCDEFGHIJ", &mut codes));
+    }
+
     #[test]
     fn test_format_for_display() {
         let formatted = BackupCodeManager::format_for_display("ABCDEFGHIJ");
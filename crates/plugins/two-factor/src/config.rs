@@ -1,5 +1,11 @@
 //! Configuration for the Two-Factor plugin.
 
+use crate::totp::TotpAlgorithm;
+use crate::store::TwoFactorStore;
+use crate::trusted::TrustedDeviceStore;
+use better_auth_core::events::EventBus;
+use better_auth_core::traits::StorageAdapter;
+use better_auth_plugin_passkey::PasskeyVerifier;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -27,6 +33,20 @@ pub struct TotpOptions {
     pub digits: u32,
     /// Time period in seconds. Default: 30.
     pub period: u32,
+    /// HMAC algorithm. Default: SHA-1.
+    pub algorithm: TotpAlgorithm,
+    /// Periods accepted either side of the current one, to allow for
+    /// clock drift. Default: 1.
+    pub window: u32,
+    /// Failed verifications allowed within `attempt_window` before the
+    /// user is locked out. Default: 5.
+    pub max_attempts: u32,
+    /// Window in seconds over which failed verifications are counted.
+    /// Default: 300.
+    pub attempt_window: u64,
+    /// How long in seconds verification stays locked after the last
+    /// failed attempt. Default: 900.
+    pub lockout_duration: u64,
 }
 
 impl Default for TotpOptions {
@@ -34,6 +54,11 @@ impl Default for TotpOptions {
         Self {
             digits: 6,
             period: 30,
+            algorithm: TotpAlgorithm::Sha1,
+            window: 1,
+            max_attempts: 5,
+            attempt_window: 5 * 60,
+            lockout_duration: 15 * 60,
         }
     }
 }
@@ -78,7 +103,9 @@ pub struct BackupCodeOptions {
     pub length: usize,
     /// Custom backup code generator.
     pub custom_generator: Option<Arc<dyn Fn() -> Vec<String> + Send + Sync>>,
-    /// How to store backup codes: "plain", "hashed", or "encrypted".
+    /// How to store backup codes. Only "hashed" is supported, and the
+    /// plugin's config check fails for anything else: codes are never
+    /// stored in plaintext.
     pub store_backup_codes: String,
 }
 
@@ -88,7 +115,7 @@ impl Default for BackupCodeOptions {
             amount: 10,
             length: 10,
             custom_generator: None,
-            store_backup_codes: "plain".to_string(),
+            store_backup_codes: "hashed".to_string(),
         }
     }
 }
@@ -119,8 +146,23 @@ pub struct TwoFactorConfig {
     pub otp_options: OtpOptions,
     /// Backup code options.
     pub backup_code_options: BackupCodeOptions,
-    /// Trusted device duration in days. Default: 30.
-    pub trusted_device_days: u32,
+    /// How long a trusted device may skip 2FA, in seconds. Default: 30 days.
+    pub trusted_device_duration: u64,
+    /// Storage for each user's secret and backup codes. Default: in memory.
+    pub two_factor_store: Option<Arc<dyn TwoFactorStore>>,
+    /// Storage for trusted devices. Default: in memory.
+    pub trusted_device_store: Option<Arc<dyn TrustedDeviceStore>>,
+    /// How long after the last full authentication (in seconds) a session
+    /// may disable 2FA. Default: 300.
+    pub fresh_session_age: u64,
+    /// Storage used to resolve sessions. When set, disabling 2FA requires a
+    /// recent authentication.
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    /// Passkey verifier. When set, along with `storage`, a passkey can be
+    /// used as the second factor.
+    pub passkey: Option<PasskeyVerifier>,
+    /// Event bus for trusted device events.
+    pub event_bus: Option<Arc<EventBus>>,
 }
 
 impl Default for TwoFactorConfig {
@@ -132,7 +174,13 @@ impl Default for TwoFactorConfig {
             totp_options: TotpOptions::default(),
             otp_options: OtpOptions::default(),
             backup_code_options: BackupCodeOptions::default(),
-            trusted_device_days: 30,
+            trusted_device_duration: 30 * 24 * 60 * 60,
+            two_factor_store: None,
+            trusted_device_store: None,
+            fresh_session_age: 5 * 60,
+            storage: None,
+            passkey: None,
+            event_bus: None,
         }
     }
 }
@@ -179,9 +227,55 @@ impl TwoFactorConfig {
         self
     }
 
-    /// Sets trusted device duration in days.
-    pub fn trusted_device_days(mut self, days: u32) -> Self {
-        self.trusted_device_days = days;
+    /// Sets how long a trusted device may skip 2FA, in seconds.
+    pub fn trusted_device_duration(mut self, seconds: u64) -> Self {
+        self.trusted_device_duration = seconds;
+        self
+    }
+
+    /// Sets how long a trusted device may skip 2FA, in days.
+    pub fn trusted_device_days(self, days: u32) -> Self {
+        self.trusted_device_duration(u64::from(days) * 24 * 60 * 60)
+    }
+
+    /// Sets the store for each user's secret and backup codes.
+    pub fn two_factor_store(mut self, store: Arc<dyn TwoFactorStore>) -> Self {
+        self.two_factor_store = Some(store);
+        self
+    }
+
+    /// Sets the trusted device store.
+    pub fn trusted_device_store(mut self, store: Arc<dyn TrustedDeviceStore>) -> Self {
+        self.trusted_device_store = Some(store);
+        self
+    }
+
+    /// Sets the event bus used to emit trusted device events.
+    pub fn event_bus(mut self, bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(bus);
+        self
+    }
+
+    /// Sets how long after authenticating a session may disable 2FA, in seconds.
+    pub fn fresh_session_age(mut self, seconds: u64) -> Self {
+        self.fresh_session_age = seconds;
+        self
+    }
+
+    /// Sets the storage adapter, enabling the recent-authentication check.
+    pub fn storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Enables passkeys as a second factor.
+    ///
+    /// Get the verifier from the passkey plugin with
+    /// [`PasskeyPlugin::verifier`](better_auth_plugin_passkey::PasskeyPlugin::verifier)
+    /// so challenges live in its challenge store. Requires
+    /// [`storage`](Self::storage).
+    pub fn passkey(mut self, verifier: PasskeyVerifier) -> Self {
+        self.passkey = Some(verifier);
         self
     }
 
@@ -203,7 +297,13 @@ impl std::fmt::Debug for TwoFactorConfig {
             .field("two_factor_table", &self.two_factor_table)
             .field("skip_verification_on_enable", &self.skip_verification_on_enable)
             .field("totp_options", &self.totp_options)
-            .field("trusted_device_days", &self.trusted_device_days)
+            .field("trusted_device_duration", &self.trusted_device_duration)
+            .field("two_factor_store", &self.two_factor_store.is_some())
+            .field("trusted_device_store", &self.trusted_device_store.is_some())
+            .field("fresh_session_age", &self.fresh_session_age)
+            .field("storage", &self.storage.is_some())
+            .field("passkey", &self.passkey.is_some())
+            .field("event_bus", &self.event_bus.is_some())
             .finish()
     }
 }
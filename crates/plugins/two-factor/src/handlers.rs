@@ -1,8 +1,20 @@
 //! Request handlers for the Two-Factor plugin.
 
+use crate::{TwoFactorData, TwoFactorUserExt};
+use crate::store::TwoFactorStore;
+use crate::attempts::TotpAttempts;
+use crate::backup::BackupCodeManager;
+use crate::totp::TotpManager;
+use crate::trusted::TrustedDevices;
 use async_trait::async_trait;
-use better_auth_core::router::{Request, RequestHandler, Response};
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::router::{CookieOptions, Request, RequestHandler, Response};
+use better_auth_core::session::{SESSION_COOKIE, SessionResolver};
+use better_auth_core::traits::StorageAdapter;
+use better_auth_core::types::{AuthFactor, Session};
+use better_auth_plugin_passkey::{AssertionResponse, PasskeyVerifier};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Request body for enabling 2FA.
 #[derive(Debug, Deserialize)]
@@ -121,7 +133,53 @@ pub struct VerifyTotpRequest {
 }
 
 /// Handler for POST /two-factor/verify-totp
-pub struct VerifyTotpHandler;
+///
+/// With storage configured, the code is checked against the session user's
+/// secret and failures count towards a lockout.
+pub struct VerifyTotpHandler {
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    pub store: Arc<dyn TwoFactorStore>,
+    pub totp: TotpManager,
+    pub attempts: Arc<TotpAttempts>,
+    pub(crate) trusted: TrustedDevices,
+}
+
+impl VerifyTotpHandler {
+    /// Verifies `code` for the user of the request's session.
+    async fn verify(&self, storage: &Arc<dyn StorageAdapter>, req: &Request, code: &str) -> AuthResult<Session> {
+        let mut session = resolve_session(storage, req).await?;
+        let user = storage
+            .get_user_by_id(&session.user_id)
+            .await?
+            .ok_or(AuthError::UserNotFound)?;
+        let data = self
+            .store
+            .get_two_factor(&user.id)
+            .await?
+            .filter(|_| user.two_factor_enabled())
+            .ok_or_else(|| AuthError::forbidden("Two-factor authentication is not enabled"))?;
+
+        // Checked before the code so a locked-out user learns nothing from it.
+        self.attempts.check(&user.id)?;
+        // Codes from the matched step or earlier are refused from now on. A
+        // concurrent request that used the step first wins, and this one
+        // fails like a replay.
+        let verified = match self
+            .totp
+            .verify_step(&data.secret, code, data.last_used_step)
+        {
+            Some(step) => self.store.advance_totp_step(&user.id, step).await?,
+            None => false,
+        };
+        if !verified {
+            self.attempts.record_failure(&user.id);
+            return Err(AuthError::InvalidCredentials);
+        }
+        self.attempts.reset(&user.id);
+        session.record_factor(AuthFactor::Totp);
+        Ok(session)
+    }
+}
 
 #[async_trait]
 impl RequestHandler for VerifyTotpHandler {
@@ -140,16 +198,202 @@ impl RequestHandler for VerifyTotpHandler {
             }));
         }
 
-        Response::ok().json(serde_json::json!({
-            "success": true,
-            "session": {
-                "id": "session_placeholder",
-                "token": "token_placeholder"
+        let Some(storage) = &self.storage else {
+            return Response::ok().json(serde_json::json!({
+                "success": true,
+                "session": {
+                    "id": "session_placeholder",
+                    "token": "token_placeholder"
+                }
+            }));
+        };
+
+        let verified = match self.verify(storage, &req, &body.code).await {
+            Ok(session) => {
+                verified_response(storage, &self.trusted, &req, session, body.trust_device).await
             }
-        }))
+            Err(err) => Err(err),
+        };
+        verified.unwrap_or_else(error_response)
+    }
+}
+
+/// Cookie holding the trusted device token.
+const TRUST_DEVICE_COOKIE: &str = "better-auth.trust_device";
+
+/// Builds the response for a verified second factor. If the client asked,
+/// the device is trusted too, and its token returned in the body and as a
+/// cookie.
+///
+/// Passing the second factor elevates the session, so its token is rotated
+/// first and the session cookie set again with the new one. A token captured
+/// before verification is useless afterwards. The rotation also saves the
+/// factor the caller recorded on `session`. Sign-ins need no rotation, as
+/// they always create a new session; see [`end_replaced_session`].
+///
+/// [`end_replaced_session`]: better_auth_core::session::end_replaced_session
+async fn verified_response(
+    storage: &Arc<dyn StorageAdapter>,
+    trusted: &TrustedDevices,
+    req: &Request,
+    mut session: Session,
+    trust_device: Option<bool>,
+) -> AuthResult<Response> {
+    SessionResolver::new(storage.clone())
+        .rotate_session_token(&mut session)
+        .await?;
+    let session_cookie = |response: Response| {
+        response.cookie(SESSION_COOKIE, &session.token, CookieOptions::secure())
+    };
+
+    let mut body = serde_json::json!({
+        "success": true,
+        "session": {
+            "id": session.id,
+            "token": session.token,
+            "assuranceLevel": session.assurance_level()
+        }
+    });
+    if trust_device != Some(true) {
+        return Ok(session_cookie(Response::ok().json(body)));
+    }
+
+    let (token, device) = trusted.trust(&session.user_id, req).await?;
+    body["trustDeviceToken"] = serde_json::json!(token);
+    let options = CookieOptions {
+        max_age: Some((device.expires_at - chrono::Utc::now()).num_seconds()),
+        ..CookieOptions::secure()
+    };
+    Ok(session_cookie(Response::ok().json(body)).cookie(TRUST_DEVICE_COOKIE, &token, options))
+}
+
+/// Resolves the session of the request.
+async fn resolve_session(storage: &Arc<dyn StorageAdapter>, req: &Request) -> AuthResult<Session> {
+    Ok(SessionResolver::new(storage.clone())
+        .resolve_request(req)
+        .await?
+        .ok_or(AuthError::SessionNotFound)?
+        .session)
+}
+
+/// Gets the two-factor data of `user_id`, who must have 2FA enabled.
+async fn enabled_two_factor(
+    storage: &Arc<dyn StorageAdapter>,
+    store: &Arc<dyn TwoFactorStore>,
+    user_id: &str,
+) -> AuthResult<TwoFactorData> {
+    let enabled = storage
+        .get_user_by_id(user_id)
+        .await?
+        .is_some_and(|user| user.two_factor_enabled());
+    let data = store.get_two_factor(user_id).await?;
+    data.filter(|_| enabled)
+        .ok_or_else(|| AuthError::forbidden("Two-factor authentication is not enabled"))
+}
+
+/// Handler for POST /two-factor/generate-passkey-options
+///
+/// Issues a WebAuthn challenge that only the session user's passkeys can
+/// answer.
+pub struct GeneratePasskeyOptionsHandler {
+    pub storage: Arc<dyn StorageAdapter>,
+    pub passkey: PasskeyVerifier,
+}
+
+#[async_trait]
+impl RequestHandler for GeneratePasskeyOptionsHandler {
+    async fn handle(&self, req: Request) -> Response {
+        let options = match resolve_session(&self.storage, &req).await {
+            Ok(session) => self.passkey.authentication_options(&session.user_id).await,
+            Err(err) => Err(err),
+        };
+        match options {
+            Ok(options) => Response::ok().json(options),
+            Err(err) => passkey_error_response(err),
+        }
     }
 }
 
+/// Request body for verifying a passkey.
+#[derive(Debug, Deserialize)]
+pub struct VerifyPasskeyRequest {
+    /// The assertion from `navigator.credentials.get()`.
+    pub response: AssertionResponse,
+    #[serde(rename = "trustDevice")]
+    pub trust_device: Option<bool>,
+}
+
+/// Handler for POST /two-factor/verify-passkey
+pub struct VerifyPasskeyHandler {
+    pub storage: Arc<dyn StorageAdapter>,
+    pub passkey: PasskeyVerifier,
+    pub(crate) trusted: TrustedDevices,
+}
+
+#[async_trait]
+impl RequestHandler for VerifyPasskeyHandler {
+    async fn handle(&self, req: Request) -> Response {
+        let body: Option<VerifyPasskeyRequest> = req.json();
+
+        let Some(body) = body else {
+            return Response::bad_request().json(serde_json::json!({
+                "error": { "code": "INVALID_REQUEST", "message": "Invalid request body" }
+            }));
+        };
+
+        let verified = match resolve_session(&self.storage, &req).await {
+            Ok(mut session) => self
+                .passkey
+                .verify(&session.user_id, &body.response)
+                .await
+                .map(|_| {
+                    session.record_factor(AuthFactor::Passkey);
+                    session
+                }),
+            Err(err) => Err(err),
+        };
+        let verified = match verified {
+            Ok(session) => {
+                let trust_device = body.trust_device;
+                verified_response(&self.storage, &self.trusted, &req, session, trust_device).await
+            }
+            Err(err) => Err(err),
+        };
+        verified.unwrap_or_else(passkey_error_response)
+    }
+}
+
+/// Like [`error_response`], with codes for passkey failures.
+fn passkey_error_response(err: AuthError) -> Response {
+    let code = match &err {
+        AuthError::InvalidCredentials => "INVALID_PASSKEY",
+        AuthError::NotFound { .. } => "NO_PASSKEYS",
+        AuthError::Forbidden { .. } => "CLONE_DETECTED",
+        _ => return error_response(err),
+    };
+    Response::new(err.status_code()).json(serde_json::json!({
+        "error": { "code": code, "message": err.to_string() }
+    }))
+}
+
+/// Converts an error into the plugin's error body.
+fn error_response(err: AuthError) -> Response {
+    let code = match &err {
+        AuthError::TooManyAttempts { .. } => "TOO_MANY_ATTEMPTS",
+        AuthError::InvalidCredentials => "INVALID_CODE",
+        AuthError::SessionNotFound => "SESSION_NOT_FOUND",
+        AuthError::Forbidden { .. } => "TWO_FACTOR_NOT_ENABLED",
+        _ => err.error_code(),
+    };
+    let mut response = Response::new(err.status_code()).json(serde_json::json!({
+        "error": { "code": code, "message": err.to_string() }
+    }));
+    if let AuthError::TooManyAttempts { retry_after_seconds } = err {
+        response = response.header("Retry-After", retry_after_seconds.to_string());
+    }
+    response
+}
+
 /// Handler for POST /two-factor/send-otp
 pub struct SendOtpHandler;
 
@@ -205,7 +449,36 @@ pub struct GenerateBackupCodesRequest {
 }
 
 /// Handler for POST /two-factor/generate-backup-codes
-pub struct GenerateBackupCodesHandler;
+///
+/// With storage configured, new codes replace the session user's old ones.
+/// Only their hashes are stored, so this response is the one time the
+/// plaintext codes are available.
+pub struct GenerateBackupCodesHandler {
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    pub store: Arc<dyn TwoFactorStore>,
+    pub backup: BackupCodeManager,
+}
+
+impl GenerateBackupCodesHandler {
+    /// Generates and stores new codes for the user of the request's
+    /// session, returning them in plaintext.
+    async fn generate(&self, storage: &Arc<dyn StorageAdapter>, req: &Request) -> AuthResult<Vec<String>> {
+        let session = resolve_session(storage, req).await?;
+        let codes = self.backup.generate();
+        loop {
+            let mut data = enabled_two_factor(storage, &self.store, &session.user_id).await?;
+            let current = std::mem::take(&mut data.backup_codes);
+            data.set_backup_codes(&codes);
+            if self
+                .store
+                .swap_backup_codes(&data.user_id, &current, &data.backup_codes)
+                .await?
+            {
+                return Ok(codes);
+            }
+        }
+    }
+}
 
 #[async_trait]
 impl RequestHandler for GenerateBackupCodesHandler {
@@ -224,13 +497,25 @@ impl RequestHandler for GenerateBackupCodesHandler {
             }));
         }
 
-        Response::ok().json(serde_json::json!({
-            "backupCodes": [
-                "ABCD-EFGH-IJ",
-                "KLMN-OPQR-ST",
-                "UVWX-YZ12-34"
-            ]
-        }))
+        let Some(storage) = &self.storage else {
+            return Response::ok().json(serde_json::json!({
+                "backupCodes": [
+                    "ABCD-EFGH-IJ",
+                    "KLMN-OPQR-ST",
+                    "UVWX-YZ12-34"
+                ]
+            }));
+        };
+
+        match self.generate(storage, &req).await {
+            Ok(codes) => Response::ok().json(serde_json::json!({
+                "backupCodes": codes
+                    .iter()
+                    .map(|code| BackupCodeManager::format_for_display(code))
+                    .collect::<Vec<_>>()
+            })),
+            Err(err) => error_response(err),
+        }
     }
 }
 
@@ -245,7 +530,41 @@ pub struct VerifyBackupCodeRequest {
 }
 
 /// Handler for POST /two-factor/verify-backup-code
-pub struct VerifyBackupCodeHandler;
+///
+/// With storage configured, the code is checked against the session user's
+/// hashed codes and marked used on success.
+pub struct VerifyBackupCodeHandler {
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    pub store: Arc<dyn TwoFactorStore>,
+    pub backup: BackupCodeManager,
+    pub(crate) trusted: TrustedDevices,
+}
+
+impl VerifyBackupCodeHandler {
+    /// Verifies and consumes `code` for the user of the request's session.
+    async fn verify(&self, storage: &Arc<dyn StorageAdapter>, req: &Request, code: &str) -> AuthResult<Session> {
+        let mut session = resolve_session(storage, req).await?;
+        // The codes are only saved if no other request changed them since
+        // they were read; otherwise the code is checked again against the
+        // new ones. Of two requests with the same code, one succeeds.
+        loop {
+            let mut data = enabled_two_factor(storage, &self.store, &session.user_id).await?;
+            let current = data.backup_codes.clone();
+            if !data.use_backup_code(&self.backup, code) {
+                return Err(AuthError::InvalidCredentials);
+            }
+            if self
+                .store
+                .swap_backup_codes(&data.user_id, &current, &data.backup_codes)
+                .await?
+            {
+                break;
+            }
+        }
+        session.record_factor(AuthFactor::BackupCode);
+        Ok(session)
+    }
+}
 
 #[async_trait]
 impl RequestHandler for VerifyBackupCodeHandler {
@@ -264,12 +583,48 @@ impl RequestHandler for VerifyBackupCodeHandler {
             }));
         }
 
-        Response::ok().json(serde_json::json!({
-            "success": true,
-            "session": {
-                "id": "session_placeholder",
-                "token": "token_placeholder"
+        let Some(storage) = &self.storage else {
+            return Response::ok().json(serde_json::json!({
+                "success": true,
+                "session": {
+                    "id": "session_placeholder",
+                    "token": "token_placeholder"
+                }
+            }));
+        };
+
+        let verified = match self.verify(storage, &req, &body.code).await {
+            Ok(session) => {
+                verified_response(storage, &self.trusted, &req, session, body.trust_device).await
             }
-        }))
+            Err(err) => Err(err),
+        };
+        verified.unwrap_or_else(error_response)
+    }
+}
+
+/// Handler for POST /two-factor/revoke-trusted-devices
+///
+/// Removes every trusted device of the session user, so each must pass 2FA
+/// again. Useful after a password change.
+pub struct RevokeTrustedDevicesHandler {
+    pub storage: Arc<dyn StorageAdapter>,
+    pub(crate) trusted: TrustedDevices,
+}
+
+#[async_trait]
+impl RequestHandler for RevokeTrustedDevicesHandler {
+    async fn handle(&self, req: Request) -> Response {
+        let revoked = match resolve_session(&self.storage, &req).await {
+            Ok(session) => self.trusted.revoke_all(&session.user_id).await,
+            Err(err) => Err(err),
+        };
+        match revoked {
+            Ok(revoked) => Response::ok().json(serde_json::json!({
+                "success": true,
+                "revoked": revoked
+            })),
+            Err(err) => error_response(err),
+        }
     }
 }
@@ -3,27 +3,50 @@
 //! This plugin adds two-factor authentication (2FA) support to Better Auth.
 //! It supports TOTP (Time-based One-Time Password), OTP via email/SMS,
 //! backup codes, and trusted devices.
+//!
+//! Passkeys registered with the passkey plugin can also serve as the second
+//! factor. Hand this plugin a verifier built by that plugin, so both share
+//! one challenge store:
+//!
+//! ```rust,ignore
+//! let passkey = PasskeyPlugin::new(passkey_config);
+//! let two_factor = TwoFactorPlugin::new(
+//!     TwoFactorConfig::new()
+//!         .storage(storage.clone())
+//!         .passkey(passkey.verifier(passkey_store)),
+//! );
+//! ```
 
 mod config;
 mod schema;
 mod handlers;
 mod totp;
 mod backup;
+mod attempts;
+mod store;
+mod trusted;
 
 pub use config::{TwoFactorConfig, TotpOptions, OtpOptions, BackupCodeOptions};
 pub use schema::{TwoFactorData, TrustedDevice, TwoFactorSchema, TwoFactorUserExt as TwoFactorUserExtSchema};
-pub use totp::{TotpManager, TotpUri};
-pub use backup::BackupCodeManager;
+pub use totp::{TotpAlgorithm, TotpManager, TotpUri};
+pub use backup::{BackupCode, BackupCodeManager};
+pub use store::{InMemoryTwoFactorStore, TwoFactorStore};
+pub use trusted::{InMemoryTrustedDeviceStore, TrustedDeviceStore};
 
 use async_trait::async_trait;
 use better_auth_core::context::AuthContext;
-use better_auth_core::error::AuthResult;
+use better_auth_core::error::{AuthError, AuthResult};
 use better_auth_core::router::{Method, Route, Router};
 use better_auth_core::schema::{Field, FieldType, SchemaBuilder};
+use better_auth_core::session::{RequireRecentAuth, SessionResolver};
 use better_auth_core::traits::{AuthPlugin, ExtensionProvider, SchemaProvider};
 use better_auth_core::types::{Session, User};
 use better_auth_events_sdk::{EventDefinition, EventProvider};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use attempts::TotpAttempts;
+use trusted::TrustedDevices;
 
 /// Trait for accessing TwoFactor fields on User.
 pub trait TwoFactorUserExt {
@@ -64,6 +87,9 @@ pub struct TwoFactorPlugin {
     config: TwoFactorConfig,
     totp_manager: TotpManager,
     backup_manager: BackupCodeManager,
+    totp_attempts: Arc<TotpAttempts>,
+    two_factor_store: Arc<dyn TwoFactorStore>,
+    trusted_devices: TrustedDevices,
 }
 
 impl TwoFactorPlugin {
@@ -73,16 +99,35 @@ impl TwoFactorPlugin {
             config.issuer.clone(),
             config.totp_options.digits,
             config.totp_options.period,
-        );
+        )
+        .with_window(config.totp_options.window)
+        .with_algorithm(config.totp_options.algorithm);
         let backup_manager = BackupCodeManager::new(
             config.backup_code_options.amount,
             config.backup_code_options.length,
         );
         
+        let totp_attempts = Arc::new(TotpAttempts::new(&config.totp_options));
+        let two_factor_store = config
+            .two_factor_store
+            .clone()
+            .unwrap_or_else(|| Arc::new(InMemoryTwoFactorStore::new()));
+        let trusted_devices = TrustedDevices {
+            store: config
+                .trusted_device_store
+                .clone()
+                .unwrap_or_else(|| Arc::new(InMemoryTrustedDeviceStore::new())),
+            duration: chrono::Duration::seconds(config.trusted_device_duration as i64),
+            event_bus: config.event_bus.clone(),
+        };
+
         Self {
             config,
             totp_manager,
             backup_manager,
+            totp_attempts,
+            two_factor_store,
+            trusted_devices,
         }
     }
 
@@ -100,6 +145,20 @@ impl TwoFactorPlugin {
     pub fn backup_manager(&self) -> &BackupCodeManager {
         &self.backup_manager
     }
+
+    /// Gets the store holding each user's secret and backup codes.
+    pub fn two_factor_store(&self) -> &Arc<dyn TwoFactorStore> {
+        &self.two_factor_store
+    }
+
+    /// Returns true if `token`, from a verification with `trustDevice`,
+    /// belongs to an unexpired trusted device of `user_id`.
+    ///
+    /// Use this to decide whether a sign-in may skip 2FA. An expired
+    /// device is untrusted, so the user is prompted again.
+    pub async fn is_device_trusted(&self, user_id: &str, token: &str) -> AuthResult<bool> {
+        self.trusted_devices.is_trusted(user_id, token).await
+    }
 }
 
 impl Default for TwoFactorPlugin {
@@ -151,6 +210,11 @@ impl EventProvider for TwoFactorPlugin {
                 "Emitted when a device is marked as trusted",
                 "two_factor",
             ),
+            EventDefinition::simple(
+                "two_factor.device_revoked",
+                "Emitted when a trusted device is revoked",
+                "two_factor",
+            ),
         ]
     }
 
@@ -169,6 +233,25 @@ impl AuthPlugin for TwoFactorPlugin {
         "Two-Factor Authentication"
     }
 
+    fn validate_config(&self) -> AuthResult<()> {
+        let mut issues = Vec::new();
+        if self.config.passkey.is_some() && self.config.storage.is_none() {
+            issues.push("passkey verification requires storage to resolve sessions".to_string());
+        }
+        let store_backup_codes = &self.config.backup_code_options.store_backup_codes;
+        if store_backup_codes != "hashed" {
+            issues.push(format!(
+                "store_backup_codes must be \"hashed\", not \"{}\"; backup codes are never stored in plaintext",
+                store_backup_codes
+            ));
+        }
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(AuthError::invalid_config("two_factor", issues))
+        }
+    }
+
     fn define_schema(&self, builder: &mut SchemaBuilder) {
         // Add user extension field
         builder.add_field_mut("user", Field::new("two_factor_enabled", FieldType::Boolean).default("false"));
@@ -190,8 +273,20 @@ impl AuthPlugin for TwoFactorPlugin {
         );
 
         // POST /two-factor/disable
+        let disable = match &self.config.storage {
+            Some(storage) => Route::new(
+                Method::POST,
+                "/two-factor/disable",
+                RequireRecentAuth::new(
+                    SessionResolver::new(storage.clone()),
+                    chrono::Duration::seconds(self.config.fresh_session_age as i64),
+                    handlers::DisableHandler,
+                ),
+            ),
+            None => Route::new(Method::POST, "/two-factor/disable", handlers::DisableHandler),
+        };
         router.route(
-            Route::new(Method::POST, "/two-factor/disable", handlers::DisableHandler)
+            disable
                 .summary("Disable 2FA")
                 .description("Disables two-factor authentication for the user.")
                 .tag("two-factor")
@@ -209,12 +304,55 @@ impl AuthPlugin for TwoFactorPlugin {
 
         // POST /two-factor/verify-totp
         router.route(
-            Route::new(Method::POST, "/two-factor/verify-totp", handlers::VerifyTotpHandler)
+            Route::new(
+                Method::POST,
+                "/two-factor/verify-totp",
+                handlers::VerifyTotpHandler {
+                    storage: self.config.storage.clone(),
+                    store: self.two_factor_store.clone(),
+                    totp: self.totp_manager.clone(),
+                    attempts: self.totp_attempts.clone(),
+                    trusted: self.trusted_devices.clone(),
+                },
+            )
                 .summary("Verify TOTP")
-                .description("Verifies a TOTP code.")
+                .description("Verifies a TOTP code. Too many failed attempts lock verification for the user.")
                 .tag("two-factor"),
         );
 
+        if let (Some(storage), Some(passkey)) = (&self.config.storage, &self.config.passkey) {
+            // POST /two-factor/generate-passkey-options
+            router.route(
+                Route::new(
+                    Method::POST,
+                    "/two-factor/generate-passkey-options",
+                    handlers::GeneratePasskeyOptionsHandler {
+                        storage: storage.clone(),
+                        passkey: passkey.clone(),
+                    },
+                )
+                    .summary("Generate passkey options")
+                    .description("Generates WebAuthn authentication options for the user's passkeys.")
+                    .tag("two-factor"),
+            );
+
+            // POST /two-factor/verify-passkey
+            router.route(
+                Route::new(
+                    Method::POST,
+                    "/two-factor/verify-passkey",
+                    handlers::VerifyPasskeyHandler {
+                        storage: storage.clone(),
+                        passkey: passkey.clone(),
+                        trusted: self.trusted_devices.clone(),
+                    },
+                )
+                    .summary("Verify passkey")
+                    .description("Verifies a passkey assertion as the second factor.")
+                    .tag("two-factor"),
+            );
+        }
+
         // POST /two-factor/send-otp
         router.route(
             Route::new(Method::POST, "/two-factor/send-otp", handlers::SendOtpHandler)
@@ -233,20 +371,55 @@ impl AuthPlugin for TwoFactorPlugin {
 
         // POST /two-factor/generate-backup-codes
         router.route(
-            Route::new(Method::POST, "/two-factor/generate-backup-codes", handlers::GenerateBackupCodesHandler)
+            Route::new(
+                Method::POST,
+                "/two-factor/generate-backup-codes",
+                handlers::GenerateBackupCodesHandler {
+                    storage: self.config.storage.clone(),
+                    store: self.two_factor_store.clone(),
+                    backup: self.backup_manager.clone(),
+                },
+            )
                 .summary("Generate backup codes")
-                .description("Generates new backup codes. Old codes are invalidated.")
+                .description("Generates new backup codes. Old codes are invalidated. The codes are only ever returned here; just their hashes are stored.")
                 .tag("two-factor")
                 .requires_auth(),
         );
 
         // POST /two-factor/verify-backup-code
         router.route(
-            Route::new(Method::POST, "/two-factor/verify-backup-code", handlers::VerifyBackupCodeHandler)
+            Route::new(
+                Method::POST,
+                "/two-factor/verify-backup-code",
+                handlers::VerifyBackupCodeHandler {
+                    storage: self.config.storage.clone(),
+                    store: self.two_factor_store.clone(),
+                    backup: self.backup_manager.clone(),
+                    trusted: self.trusted_devices.clone(),
+                },
+            )
                 .summary("Verify backup code")
-                .description("Verifies a backup code for account recovery.")
+                .description("Verifies a backup code for account recovery. Each code can be used once.")
                 .tag("two-factor"),
         );
+
+        if let Some(storage) = &self.config.storage {
+            // POST /two-factor/revoke-trusted-devices
+            router.route(
+                Route::new(
+                    Method::POST,
+                    "/two-factor/revoke-trusted-devices",
+                    handlers::RevokeTrustedDevicesHandler {
+                        storage: storage.clone(),
+                        trusted: self.trusted_devices.clone(),
+                    },
+                )
+                    .summary("Revoke trusted devices")
+                    .description("Removes all of the user's trusted devices, so each must pass 2FA again.")
+                    .tag("two-factor")
+                    .requires_auth(),
+            );
+        }
     }
 
     async fn on_after_signin(
@@ -259,6 +432,32 @@ impl AuthPlugin for TwoFactorPlugin {
         // This would be checked by the application to redirect to 2FA page
         Ok(())
     }
+
+    async fn export_user_data(&self, ctx: &AuthContext, user_id: &str) -> AuthResult<serde_json::Value> {
+        let Some(user) = ctx.db.get_user_by_id(user_id).await? else {
+            return Ok(serde_json::Value::Null);
+        };
+        // Only the status: the secret and the code hashes stay private.
+        let unused_codes = self
+            .two_factor_store
+            .get_two_factor(user_id)
+            .await?
+            .map(|data| data.get_backup_codes())
+            .unwrap_or_default()
+            .iter()
+            .filter(|code| !code.is_used())
+            .count();
+        Ok(serde_json::json!({
+            "enabled": user.two_factor_enabled(),
+            "backup_codes_remaining": unused_codes,
+        }))
+    }
+
+    async fn on_before_user_delete(&self, _ctx: &AuthContext, user_id: &str) -> AuthResult<()> {
+        self.two_factor_store.delete_two_factor(user_id).await?;
+        self.trusted_devices.revoke_all(user_id).await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -283,4 +482,272 @@ mod tests {
         user.set_two_factor_secret(Some("secret".to_string()));
         assert_eq!(user.two_factor_secret(), Some("secret".to_string()));
     }
+
+    /// Sets up a user with TOTP enabled, returning the routes, the user's
+    /// secret and session token, and the plugin.
+    async fn totp_setup(config: TwoFactorConfig) -> (Router, String, String, TwoFactorPlugin) {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::traits::StorageAdapter;
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let plugin = TwoFactorPlugin::new(config.storage(storage.clone()));
+        let secret = plugin.totp_manager().generate_secret();
+        let mut user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        user.set_two_factor_enabled(true);
+        storage.create_user(&user).await.unwrap();
+        plugin
+            .two_factor_store()
+            .create_two_factor(&TwoFactorData::new(&user.id, secret.clone(), &[]))
+            .await
+            .unwrap();
+        let session = storage.create_session(&Session::new(user.id.clone())).await.unwrap();
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        (router, secret, session.token, plugin)
+    }
+
+    async fn verify_totp(router: &Router, token: &str, code: &str) -> better_auth_core::router::Response {
+        use better_auth_core::router::Request;
+
+        let route = router
+            .routes()
+            .find(|r| r.path == "/two-factor/verify-totp")
+            .unwrap();
+        let mut req = Request::new(Method::POST, "/two-factor/verify-totp");
+        req.headers.insert("authorization".to_string(), format!("Bearer {}", token));
+        req.body = Some(serde_json::json!({ "code": code }));
+        route.handler.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_verify_totp_locks_out_after_max_attempts() {
+        let (router, secret, token, plugin) =
+            totp_setup(TwoFactorConfig::new().totp_options(TotpOptions { max_attempts: 3, ..Default::default() })).await;
+
+        let valid = plugin.totp_manager().current_code(&secret);
+        let wrong = format!("{:06}", (valid.parse::<u32>().unwrap() + 1) % 1_000_000);
+        for _ in 0..3 {
+            let response = verify_totp(&router, &token, &wrong).await;
+            assert_eq!(response.status, 401);
+            assert_eq!(response.body.unwrap()["error"]["code"], "INVALID_CODE");
+        }
+
+        // The fourth attempt is refused even though the code is right.
+        let response = verify_totp(&router, &token, &valid).await;
+        assert_eq!(response.status, 429);
+        assert!(response.headers.contains_key("retry-after"));
+        assert_eq!(response.body.unwrap()["error"]["code"], "TOO_MANY_ATTEMPTS");
+    }
+
+    #[tokio::test]
+    async fn test_verify_totp_rejects_replayed_code() {
+        let (router, secret, token, plugin) = totp_setup(TwoFactorConfig::new()).await;
+        let code = plugin.totp_manager().current_code(&secret);
+
+        let response = verify_totp(&router, &token, &code).await;
+        assert_eq!(response.status, 200);
+        let token = response.body.unwrap()["session"]["token"].as_str().unwrap().to_string();
+        let replayed = verify_totp(&router, &token, &code).await;
+        assert_eq!(replayed.status, 401);
+        assert_eq!(replayed.body.unwrap()["error"]["code"], "INVALID_CODE");
+    }
+
+    async fn post(router: &Router, path: &str, token: &str, body: serde_json::Value) -> better_auth_core::router::Response {
+        use better_auth_core::router::Request;
+
+        let route = router.routes().find(|r| r.path == path).unwrap();
+        let mut req = Request::new(Method::POST, path);
+        req.headers.insert("authorization".to_string(), format!("Bearer {}", token));
+        req.body = Some(body);
+        route.handler.handle(req).await
+    }
+
+    #[tokio::test]
+    async fn test_backup_codes_are_hashed_and_single_use() {
+        let (router, _, token, plugin) = totp_setup(TwoFactorConfig::new()).await;
+
+        let response = post(&router, "/two-factor/generate-backup-codes", &token, serde_json::json!({ "password": "pw" })).await;
+        assert_eq!(response.status, 200);
+        let codes: Vec<String> = serde_json::from_value(response.body.unwrap()["backupCodes"].clone()).unwrap();
+        assert_eq!(codes.len(), 10);
+
+        // Only hashes are stored, and only in the two-factor data.
+        let storage = plugin.config().storage.clone().unwrap();
+        let user = storage.get_user_by_id("user_1").await.unwrap().unwrap();
+        assert!(!user.extensions.contains_key("two_factor_backup_codes"));
+        let data = plugin.two_factor_store().get_two_factor("user_1").await.unwrap().unwrap();
+        assert!(codes.iter().all(|code| !data.backup_codes.contains(&code.replace('-', ""))));
+
+        let body = serde_json::json!({ "code": codes[0] });
+        let response = post(&router, "/two-factor/verify-backup-code", &token, body.clone()).await;
+        assert_eq!(response.status, 200);
+        let token = response.body.unwrap()["session"]["token"].as_str().unwrap().to_string();
+
+        let reused = post(&router, "/two-factor/verify-backup-code", &token, body).await;
+        assert_eq!(reused.status, 401);
+        assert_eq!(reused.body.unwrap()["error"]["code"], "INVALID_CODE");
+        let other = post(&router, "/two-factor/verify-backup-code", &token, serde_json::json!({ "code": codes[1] })).await;
+        assert_eq!(other.status, 200);
+
+        let ctx = AuthContext::new(storage);
+        let export = plugin.export_user_data(&ctx, "user_1").await.unwrap();
+        assert_eq!(export, serde_json::json!({ "enabled": true, "backup_codes_remaining": 8 }));
+    }
+
+    #[tokio::test]
+    async fn test_verifying_rotates_session_token() {
+        let (router, secret, token, plugin) = totp_setup(TwoFactorConfig::new()).await;
+        let storage = plugin.config().storage.clone().unwrap();
+        let before = storage.get_session_by_token(&token).await.unwrap().unwrap();
+
+        let code = plugin.totp_manager().current_code(&secret);
+        let response = verify_totp(&router, &token, &code).await;
+        assert_eq!(response.status, 200);
+        let new_token = response.body.as_ref().unwrap()["session"]["token"].as_str().unwrap();
+        assert_ne!(new_token, token);
+        let cookie = format!("better_auth_session={};", new_token);
+        assert!(response.set_cookies().any(|c| c.starts_with(&cookie)));
+
+        // Same session under the new token; the old token no longer works.
+        assert!(storage.get_session_by_token(&token).await.unwrap().is_none());
+        let after = storage.get_session_by_token(new_token).await.unwrap().unwrap();
+        assert_eq!((&after.id, &after.user_id), (&before.id, &before.user_id));
+    }
+
+    #[tokio::test]
+    async fn test_verifying_records_factor() {
+        use better_auth_core::types::{AssuranceLevel, AuthFactor};
+
+        let (router, secret, token, plugin) = totp_setup(TwoFactorConfig::new()).await;
+        let storage = plugin.config().storage.clone().unwrap();
+        let before = storage.get_session_by_token(&token).await.unwrap().unwrap();
+        assert_eq!(before.assurance_level(), AssuranceLevel::SingleFactor);
+
+        let code = plugin.totp_manager().current_code(&secret);
+        let body = verify_totp(&router, &token, &code).await.body.unwrap();
+        assert_eq!(body["session"]["assuranceLevel"], "multi_factor");
+        let token = body["session"]["token"].as_str().unwrap();
+        let after = storage.get_session_by_token(token).await.unwrap().unwrap();
+        assert_eq!(after.assurance_level(), AssuranceLevel::MultiFactor);
+        assert!(after.require_factor(AuthFactor::Totp, chrono::Duration::minutes(5)).is_ok());
+        assert!(after.require_factor(AuthFactor::Passkey, chrono::Duration::minutes(5)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trusted_device_until_revoked() {
+        use better_auth_core::events::EventBus;
+
+        let bus = Arc::new(EventBus::new());
+        let (router, secret, token, plugin) =
+            totp_setup(TwoFactorConfig::new().event_bus(bus.clone())).await;
+
+        let code = plugin.totp_manager().current_code(&secret);
+        let body = serde_json::json!({ "code": code, "trustDevice": true });
+        let response = post(&router, "/two-factor/verify-totp", &token, body).await;
+        assert_eq!(response.status, 200);
+        assert!(response.set_cookies().any(|c| c.starts_with("better-auth.trust_device=")));
+        let body = response.body.unwrap();
+        let device_token = body["trustDeviceToken"].as_str().unwrap().to_string();
+        let token = body["session"]["token"].as_str().unwrap();
+        assert!(plugin.is_device_trusted("user_1", &device_token).await.unwrap());
+        assert_eq!(bus.events_of_type("two_factor.device_trusted").await.len(), 1);
+
+        let response = post(&router, "/two-factor/revoke-trusted-devices", token, serde_json::json!({})).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body.unwrap()["revoked"], 1);
+        assert!(!plugin.is_device_trusted("user_1", &device_token).await.unwrap());
+        assert_eq!(bus.events_of_type("two_factor.device_revoked").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_trusted_device_is_untrusted() {
+        let (router, secret, token, plugin) =
+            totp_setup(TwoFactorConfig::new().trusted_device_duration(0)).await;
+
+        let code = plugin.totp_manager().current_code(&secret);
+        let body = serde_json::json!({ "code": code, "trustDevice": true });
+        let response = post(&router, "/two-factor/verify-totp", &token, body).await;
+        let device_token = response.body.unwrap()["trustDeviceToken"].as_str().unwrap().to_string();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(!plugin.is_device_trusted("user_1", &device_token).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_verify_passkey_as_second_factor() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::router::Request;
+        use better_auth_core::traits::StorageAdapter;
+        use better_auth_plugin_passkey::testing::{MemoryPasskeys, TestAuthenticator};
+        use better_auth_plugin_passkey::{PasskeyConfig, PasskeyPlugin, PasskeyStore};
+
+        let mut authenticator = TestAuthenticator::new();
+        let storage = Arc::new(MemoryAdapter::new());
+        let user = storage
+            .create_user(&User::new("user_1".to_string(), "jane@example.com".to_string()))
+            .await
+            .unwrap();
+        let session = storage.create_session(&Session::new(user.id.clone())).await.unwrap();
+        let passkeys = Arc::new(MemoryPasskeys::default());
+        passkeys.create_passkey(&authenticator.passkey("user_1", "cred_1")).await.unwrap();
+
+        let passkey_plugin = PasskeyPlugin::new(PasskeyConfig::new("example.com", "Example", "https://example.com"));
+        let plugin = TwoFactorPlugin::new(
+            TwoFactorConfig::new()
+                .storage(storage.clone())
+                .passkey(passkey_plugin.verifier(passkeys)),
+        );
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let call = |path: &'static str, token: &str, body: serde_json::Value| {
+            let route = router.routes().find(|r| r.path == path).unwrap();
+            let mut req = Request::new(Method::POST, path);
+            req.headers.insert("authorization".to_string(), format!("Bearer {}", token));
+            req.body = Some(body);
+            route.handler.handle(req)
+        };
+
+        let options = call("/two-factor/generate-passkey-options", &session.token, serde_json::json!({})).await;
+        assert_eq!(options.status, 200);
+        let options = options.body.unwrap();
+        assert_eq!(options["allowCredentials"][0]["id"], "cred_1");
+        let challenge = options["challenge"].as_str().unwrap();
+
+        let assertion = authenticator.sign("cred_1", challenge, "https://example.com", "example.com");
+        let assertion = serde_json::to_value(assertion).unwrap();
+        let response = call("/two-factor/verify-passkey", &session.token, serde_json::json!({ "response": assertion })).await;
+        assert_eq!(response.status, 200);
+        let token = response.body.unwrap()["session"]["token"].as_str().unwrap().to_string();
+
+        // The challenge was consumed, so the same assertion is refused.
+        let replayed = call("/two-factor/verify-passkey", &token, serde_json::json!({ "response": assertion })).await;
+        assert_eq!(replayed.status, 401);
+        assert_eq!(replayed.body.unwrap()["error"]["code"], "INVALID_PASSKEY");
+    }
+
+    #[test]
+    fn test_passkey_requires_storage() {
+        use better_auth_plugin_passkey::PasskeyPlugin;
+        use better_auth_plugin_passkey::testing::MemoryPasskeys;
+
+        let verifier = PasskeyPlugin::default().verifier(Arc::new(MemoryPasskeys::default()));
+        let plugin = TwoFactorPlugin::new(TwoFactorConfig::new().passkey(verifier));
+        assert!(plugin.validate_config().is_err());
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        assert!(!router.routes().any(|r| r.path == "/two-factor/verify-passkey"));
+    }
+
+    #[test]
+    fn test_backup_codes_must_be_hashed() {
+        assert!(TwoFactorPlugin::default().validate_config().is_ok());
+
+        let options = BackupCodeOptions {
+            store_backup_codes: "plain".to_string(),
+            ..Default::default()
+        };
+        let plugin = TwoFactorPlugin::new(TwoFactorConfig::new().backup_code_options(options));
+        assert!(plugin.validate_config().is_err());
+    }
 }
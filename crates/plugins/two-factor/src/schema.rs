@@ -1,6 +1,8 @@
 //! Schema definitions for the Two-Factor plugin.
 
 use better_auth_core::schema::{Field, FieldType, IndexDefinition, ModelDefinition, ReferentialAction};
+use crate::backup::{BackupCode, BackupCodeManager};
+use crate::totp::TotpManager;
 use better_auth_core::traits::{ExtensionProvider, SchemaProvider};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -33,32 +35,62 @@ pub struct TwoFactorData {
     pub user_id: String,
     /// The TOTP secret (encrypted).
     pub secret: String,
-    /// Backup codes (encrypted, JSON array).
+    /// Hashed backup codes (JSON array of [`BackupCode`]). Plaintext codes
+    /// are never stored.
     pub backup_codes: String,
+    /// The last TOTP time step accepted, so its code cannot be replayed.
+    #[serde(default)]
+    pub last_used_step: Option<u64>,
     /// When this was created.
     pub created_at: DateTime<Utc>,
 }
 
 impl TwoFactorData {
-    /// Creates new two-factor data.
-    pub fn new(user_id: impl Into<String>, secret: impl Into<String>, backup_codes: Vec<String>) -> Self {
+    /// Creates new two-factor data, hashing the plaintext `backup_codes`.
+    pub fn new(user_id: impl Into<String>, secret: impl Into<String>, backup_codes: &[String]) -> Self {
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             user_id: user_id.into(),
             secret: secret.into(),
-            backup_codes: serde_json::to_string(&backup_codes).unwrap_or_default(),
+            backup_codes: serde_json::to_string(&BackupCodeManager::hash_all(backup_codes))
+                .unwrap_or_default(),
+            last_used_step: None,
             created_at: Utc::now(),
         }
     }
 
-    /// Gets the backup codes as a vector.
-    pub fn get_backup_codes(&self) -> Vec<String> {
+    /// Verifies a TOTP code against this secret, refusing codes from time
+    /// steps that were already used. Records the step on success.
+    pub fn verify_totp(&mut self, totp: &TotpManager, code: &str) -> bool {
+        match totp.verify_step(&self.secret, code, self.last_used_step) {
+            Some(step) => {
+                self.last_used_step = Some(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Gets the stored (hashed) backup codes.
+    pub fn get_backup_codes(&self) -> Vec<BackupCode> {
         serde_json::from_str(&self.backup_codes).unwrap_or_default()
     }
 
-    /// Sets the backup codes.
-    pub fn set_backup_codes(&mut self, codes: Vec<String>) {
+    /// Replaces the backup codes, hashing the plaintext `codes`.
+    pub fn set_backup_codes(&mut self, codes: &[String]) {
+        self.backup_codes =
+            serde_json::to_string(&BackupCodeManager::hash_all(codes)).unwrap_or_default();
+    }
+
+    /// Verifies a backup code and marks it used. Returns false if no
+    /// unused code matched.
+    pub fn use_backup_code(&mut self, manager: &BackupCodeManager, code: &str) -> bool {
+        let mut codes = self.get_backup_codes();
+        if !manager.consume(code, &mut codes) {
+            return false;
+        }
         self.backup_codes = serde_json::to_string(&codes).unwrap_or_default();
+        true
     }
 }
 
@@ -122,6 +154,7 @@ impl SchemaProvider for TwoFactorSchema {
                 )
                 .field(Field::new("secret", FieldType::Text).private())
                 .field(Field::new("backup_codes", FieldType::Text).private())
+                .field(Field::optional("last_used_step", FieldType::BigInt))
                 .field(Field::new("created_at", FieldType::Timestamp))
                 .index(IndexDefinition::unique(
                     "idx_two_factor_user",
@@ -162,12 +195,34 @@ mod tests {
         let data = TwoFactorData::new(
             "user_123",
             "secret_abc",
-            vec!["code1".to_string(), "code2".to_string()],
+            &["code1".to_string(), "code2".to_string()],
         );
 
         assert_eq!(data.user_id, "user_123");
         assert_eq!(data.secret, "secret_abc");
-        assert_eq!(data.get_backup_codes(), vec!["code1", "code2"]);
+        assert_eq!(data.get_backup_codes().len(), 2);
+        assert!(!data.backup_codes.to_uppercase().contains("CODE1"));
+    }
+
+    #[test]
+    fn test_backup_code_is_consumed() {
+        let manager = BackupCodeManager::default();
+        let mut data = TwoFactorData::new("user_123", "secret_abc", &["code1".to_string()]);
+
+        assert!(data.use_backup_code(&manager, "code1"));
+        assert!(data.get_backup_codes()[0].is_used());
+        assert!(!data.use_backup_code(&manager, "code1"));
+    }
+
+    #[test]
+    fn test_totp_code_cannot_be_replayed() {
+        let totp = TotpManager::default();
+        let mut data = TwoFactorData::new("user_123", totp.generate_secret(), &[]);
+        let code = totp.current_code(&data.secret);
+
+        assert!(data.verify_totp(&totp, &code));
+        assert!(!data.verify_totp(&totp, &code));
+        assert!(totp.verify(&data.secret, &code));
     }
 
     #[test]
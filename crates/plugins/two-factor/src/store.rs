@@ -0,0 +1,161 @@
+//! Storage for each user's [`TwoFactorData`]: the TOTP secret, the last
+//! TOTP step used and the hashed backup codes.
+//!
+//! Both the step and the codes change through compare-and-set operations,
+//! so a TOTP code or backup code accepted by one request is refused to a
+//! concurrent one.
+
+use crate::TwoFactorData;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Storage for two-factor data.
+///
+/// Adapters implement this trait to persist the `two_factor` model.
+#[async_trait]
+pub trait TwoFactorStore: Send + Sync {
+    /// Stores a user's two-factor data, replacing any they had.
+    async fn create_two_factor(&self, data: &TwoFactorData) -> AuthResult<TwoFactorData>;
+
+    /// Gets a user's two-factor data.
+    async fn get_two_factor(&self, user_id: &str) -> AuthResult<Option<TwoFactorData>>;
+
+    /// Records `step` as the user's last used TOTP step, unless the step
+    /// already recorded is the same or later.
+    ///
+    /// Returns false in that case, or if the user has no data. The check
+    /// and the write are one atomic operation, so of two concurrent
+    /// verifications of the same code only one succeeds.
+    async fn advance_totp_step(&self, user_id: &str, step: u64) -> AuthResult<bool>;
+
+    /// Replaces a user's backup codes with `codes`, if they are still
+    /// `current`. Both are [`TwoFactorData::backup_codes`] values.
+    ///
+    /// Returns false if the codes changed in the meantime, or if the user
+    /// has no data. The check and the write are one atomic operation.
+    async fn swap_backup_codes(
+        &self,
+        user_id: &str,
+        current: &str,
+        codes: &str,
+    ) -> AuthResult<bool>;
+
+    /// Deletes a user's two-factor data.
+    async fn delete_two_factor(&self, user_id: &str) -> AuthResult<()>;
+}
+
+/// In-memory two-factor store.
+///
+/// Suitable for a single instance and for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryTwoFactorStore {
+    /// Data keyed by user ID.
+    data: RwLock<HashMap<String, TwoFactorData>>,
+}
+
+impl InMemoryTwoFactorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TwoFactorStore for InMemoryTwoFactorStore {
+    async fn create_two_factor(&self, data: &TwoFactorData) -> AuthResult<TwoFactorData> {
+        self.data
+            .write()
+            .unwrap()
+            .insert(data.user_id.clone(), data.clone());
+        Ok(data.clone())
+    }
+
+    async fn get_two_factor(&self, user_id: &str) -> AuthResult<Option<TwoFactorData>> {
+        Ok(self.data.read().unwrap().get(user_id).cloned())
+    }
+
+    async fn advance_totp_step(&self, user_id: &str, step: u64) -> AuthResult<bool> {
+        let mut data = self.data.write().unwrap();
+        match data
+            .get_mut(user_id)
+            .filter(|d| d.last_used_step.is_none_or(|last| last < step))
+        {
+            Some(d) => {
+                d.last_used_step = Some(step);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn swap_backup_codes(
+        &self,
+        user_id: &str,
+        current: &str,
+        codes: &str,
+    ) -> AuthResult<bool> {
+        let mut data = self.data.write().unwrap();
+        match data.get_mut(user_id).filter(|d| d.backup_codes == current) {
+            Some(d) => {
+                d.backup_codes = codes.to_string();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete_two_factor(&self, user_id: &str) -> AuthResult<()> {
+        self.data.write().unwrap().remove(user_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BackupCodeManager;
+
+    #[tokio::test]
+    async fn test_totp_step_only_advances() {
+        let store = InMemoryTwoFactorStore::new();
+        assert!(!store.advance_totp_step("user_1", 5).await.unwrap());
+
+        store
+            .create_two_factor(&TwoFactorData::new("user_1", "secret", &[]))
+            .await
+            .unwrap();
+        assert!(store.advance_totp_step("user_1", 5).await.unwrap());
+        assert!(!store.advance_totp_step("user_1", 5).await.unwrap());
+        assert!(!store.advance_totp_step("user_1", 4).await.unwrap());
+        assert!(store.advance_totp_step("user_1", 6).await.unwrap());
+
+        let data = store.get_two_factor("user_1").await.unwrap().unwrap();
+        assert_eq!(data.last_used_step, Some(6));
+    }
+
+    #[tokio::test]
+    async fn test_backup_codes_swap_only_from_current() {
+        let store = InMemoryTwoFactorStore::new();
+        let data = TwoFactorData::new("user_1", "secret", &["ABCDEFGHIJ".to_string()]);
+        store.create_two_factor(&data).await.unwrap();
+
+        let mut first = data.clone();
+        assert!(first.use_backup_code(&BackupCodeManager::default(), "ABCDEFGHIJ"));
+        assert!(
+            store
+                .swap_backup_codes("user_1", &data.backup_codes, &first.backup_codes)
+                .await
+                .unwrap()
+        );
+        // A second request that read the codes before the first swap loses.
+        assert!(
+            !store
+                .swap_backup_codes("user_1", &data.backup_codes, &first.backup_codes)
+                .await
+                .unwrap()
+        );
+        let stored = store.get_two_factor("user_1").await.unwrap().unwrap();
+        assert!(stored.get_backup_codes()[0].is_used());
+    }
+}
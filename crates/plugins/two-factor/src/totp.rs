@@ -1,6 +1,41 @@
 //! TOTP (Time-based One-Time Password) utilities.
 
 use rand::Rng;
+use totp_rs::{Algorithm, TOTP};
+
+/// HMAC algorithm used to derive TOTP codes.
+///
+/// Most authenticator apps only support SHA-1 and may silently fall back to
+/// it, so only change this for apps or tokens known to honor the URI's
+/// `algorithm` parameter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TotpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl TotpAlgorithm {
+    /// Returns the name used in `otpauth://` URIs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA1",
+            Self::Sha256 => "SHA256",
+            Self::Sha512 => "SHA512",
+        }
+    }
+}
+
+impl From<TotpAlgorithm> for Algorithm {
+    fn from(algorithm: TotpAlgorithm) -> Self {
+        match algorithm {
+            TotpAlgorithm::Sha1 => Algorithm::SHA1,
+            TotpAlgorithm::Sha256 => Algorithm::SHA256,
+            TotpAlgorithm::Sha512 => Algorithm::SHA512,
+        }
+    }
+}
 
 /// TOTP URI for QR code generation.
 #[derive(Debug, Clone)]
@@ -20,6 +55,10 @@ pub struct TotpManager {
     digits: u32,
     /// Time period in seconds.
     period: u32,
+    /// Time steps accepted either side of the current one.
+    window: u32,
+    /// HMAC algorithm.
+    algorithm: TotpAlgorithm,
 }
 
 impl TotpManager {
@@ -29,9 +68,24 @@ impl TotpManager {
             issuer: issuer.into(),
             digits,
             period,
+            window: 1,
+            algorithm: TotpAlgorithm::Sha1,
         }
     }
 
+    /// Sets how many time steps either side of the current one are
+    /// accepted, to allow for clock drift. Default: 1.
+    pub fn with_window(mut self, window: u32) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets the HMAC algorithm. Default: SHA-1.
+    pub fn with_algorithm(mut self, algorithm: TotpAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
     /// Generates a new secret.
     pub fn generate_secret(&self) -> String {
         use rand::RngCore;
@@ -44,11 +98,12 @@ impl TotpManager {
     /// Generates a TOTP URI for the given account and secret.
     pub fn generate_uri(&self, account: &str, secret: &str) -> TotpUri {
         let uri = format!(
-            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm={}&digits={}&period={}",
             urlencoding_encode(&self.issuer),
             urlencoding_encode(account),
             secret,
             urlencoding_encode(&self.issuer),
+            self.algorithm.as_str(),
             self.digits,
             self.period
         );
@@ -61,16 +116,22 @@ impl TotpManager {
 
     /// Verifies a TOTP code.
     /// 
-    /// This accepts codes from one period before and one after the current time
-    /// to account for clock drift.
+    /// This accepts codes from `window` periods before and after the current
+    /// time to account for clock drift. It does not prevent replays; use
+    /// [`verify_step`](Self::verify_step) for that.
     pub fn verify(&self, secret: &str, code: &str) -> bool {
-        // In a real implementation, this would use the totp-rs crate
-        // For now, we'll do a simple placeholder verification
-        
+        self.verify_step(secret, code, None).is_some()
+    }
+
+    /// Verifies a TOTP code that must come from a time step after
+    /// `last_used_step`, returning the step it matched.
+    ///
+    /// Store the returned step and pass it back on the next verification:
+    /// a code is then accepted at most once, and no code from an earlier
+    /// step in the drift window is accepted after it.
+    pub fn verify_step(&self, secret: &str, code: &str, last_used_step: Option<u64>) -> Option<u64> {
         // Decode the secret
-        let Some(secret_bytes) = base32::decode(base32::Alphabet::Rfc4648 { padding: false }, secret) else {
-            return false;
-        };
+        let secret_bytes = base32::decode(base32::Alphabet::Rfc4648 { padding: false }, secret)?;
 
         // Get current time
         let now = std::time::SystemTime::now()
@@ -79,32 +140,29 @@ impl TotpManager {
             .as_secs();
 
         // Check current period and adjacent periods
-        for offset in [-1i64, 0, 1] {
-            let counter = ((now as i64 / self.period as i64) + offset) as u64;
-            let expected = self.generate_code_for_counter(&secret_bytes, counter);
-            if expected == code {
-                return true;
-            }
-        }
+        let current = now / self.period as u64;
+        let window = self.window as u64;
+        (current.saturating_sub(window)..=current + window)
+            .filter(|step| last_used_step.is_none_or(|last| *step > last))
+            .find(|step| self.generate_code_for_counter(&secret_bytes, *step) == code)
+    }
 
-        false
+    /// Generates the code for the current period.
+    #[cfg(test)]
+    pub(crate) fn current_code(&self, secret: &str) -> String {
+        let secret_bytes = base32::decode(base32::Alphabet::Rfc4648 { padding: false }, secret).unwrap();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.generate_code_for_counter(&secret_bytes, now / self.period as u64)
     }
 
     /// Generates a TOTP code for a specific counter value.
     fn generate_code_for_counter(&self, secret: &[u8], counter: u64) -> String {
-        // HMAC-SHA1 based TOTP generation
-        // This is a simplified implementation - in production use totp-rs
-        
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        let mut hasher = DefaultHasher::new();
-        secret.hash(&mut hasher);
-        counter.hash(&mut hasher);
-        let hash = hasher.finish();
-        
-        let code = (hash % 10u64.pow(self.digits)) as u32;
-        format!("{:0>width$}", code, width = self.digits as usize)
+        // With a one second step, the "time" passed to totp-rs is the counter.
+        TOTP::new_unchecked(self.algorithm.into(), self.digits as usize, 0, 1, secret.to_vec())
+            .generate(counter)
     }
 }
 
@@ -150,5 +208,34 @@ mod tests {
         assert!(uri.uri.contains("MyApp"));
         assert!(uri.uri.contains("user%40example.com"));
         assert!(uri.uri.contains("secret=JBSWY3DPEHPK3PXP"));
+        assert!(uri.uri.contains("algorithm=SHA1"));
+
+        let uri = manager
+            .with_algorithm(TotpAlgorithm::Sha512)
+            .generate_uri("user@example.com", "JBSWY3DPEHPK3PXP");
+        assert!(uri.uri.contains("algorithm=SHA512"));
+    }
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        // RFC 6238 appendix B, T = 59s (counter 1), 8 digits. Each algorithm
+        // uses a seed of its own output length.
+        let cases = [
+            (TotpAlgorithm::Sha1, &b"12345678901234567890"[..], "94287082"),
+            (TotpAlgorithm::Sha256, &b"12345678901234567890123456789012"[..], "46119246"),
+            (
+                TotpAlgorithm::Sha512,
+                &b"1234567890123456789012345678901234567890123456789012345678901234"[..],
+                "90693936",
+            ),
+        ];
+        for (algorithm, seed, expected) in cases {
+            let manager = TotpManager::new("Test", 8, 30).with_algorithm(algorithm);
+            assert_eq!(manager.generate_code_for_counter(seed, 59 / 30), expected);
+        }
+
+        let manager = TotpManager::new("Test", 8, 30).with_algorithm(TotpAlgorithm::Sha256);
+        let code = manager.generate_code_for_counter(b"12345678901234567890123456789012", 1111111109 / 30);
+        assert_eq!(code, "68084774");
     }
 }
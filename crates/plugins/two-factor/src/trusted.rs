@@ -0,0 +1,200 @@
+//! Trusted devices, which may skip the second factor until they expire.
+//!
+//! Trusting a device hands the client a random token. Only its SHA-256
+//! hash is stored, as [`TrustedDevice::device_hash`].
+
+use crate::TrustedDevice;
+use async_trait::async_trait;
+use better_auth_core::error::AuthResult;
+use better_auth_core::events::{Event, EventBus};
+use better_auth_core::router::Request;
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use ring::digest::{SHA256, digest};
+use serde_json::json;
+use std::sync::{Arc, RwLock};
+
+/// Storage for trusted devices.
+///
+/// Adapters implement this trait to persist the `trusted_device` model.
+#[async_trait]
+pub trait TrustedDeviceStore: Send + Sync {
+    /// Stores a newly trusted device.
+    async fn create_trusted_device(&self, device: &TrustedDevice) -> AuthResult<TrustedDevice>;
+
+    /// Gets a user's trusted device by its hash, expired or not.
+    async fn get_trusted_device(
+        &self,
+        user_id: &str,
+        device_hash: &str,
+    ) -> AuthResult<Option<TrustedDevice>>;
+
+    /// Deletes every trusted device of a user, returning them.
+    async fn delete_user_trusted_devices(&self, user_id: &str) -> AuthResult<Vec<TrustedDevice>>;
+}
+
+/// In-memory trusted device store.
+///
+/// Suitable for a single instance and for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryTrustedDeviceStore {
+    devices: RwLock<Vec<TrustedDevice>>,
+}
+
+impl InMemoryTrustedDeviceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TrustedDeviceStore for InMemoryTrustedDeviceStore {
+    async fn create_trusted_device(&self, device: &TrustedDevice) -> AuthResult<TrustedDevice> {
+        self.devices.write().unwrap().push(device.clone());
+        Ok(device.clone())
+    }
+
+    async fn get_trusted_device(
+        &self,
+        user_id: &str,
+        device_hash: &str,
+    ) -> AuthResult<Option<TrustedDevice>> {
+        let devices = self.devices.read().unwrap();
+        Ok(devices
+            .iter()
+            .find(|d| d.user_id == user_id && d.device_hash == device_hash)
+            .cloned())
+    }
+
+    async fn delete_user_trusted_devices(&self, user_id: &str) -> AuthResult<Vec<TrustedDevice>> {
+        let mut devices = self.devices.write().unwrap();
+        let (removed, kept) = devices.drain(..).partition(|d| d.user_id == user_id);
+        *devices = kept;
+        Ok(removed)
+    }
+}
+
+/// Creates, checks and revokes trusted devices.
+#[derive(Clone)]
+pub(crate) struct TrustedDevices {
+    pub(crate) store: Arc<dyn TrustedDeviceStore>,
+    pub(crate) duration: Duration,
+    pub(crate) event_bus: Option<Arc<EventBus>>,
+}
+
+impl TrustedDevices {
+    /// Trusts the device making `req` for `user_id`, returning the token
+    /// the client presents later and the stored device.
+    pub(crate) async fn trust(
+        &self,
+        user_id: &str,
+        req: &Request,
+    ) -> AuthResult<(String, TrustedDevice)> {
+        let mut token = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut token);
+        let token: String = token.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let mut device =
+            TrustedDevice::new(user_id, hash_token(&token), Utc::now() + self.duration);
+        device.user_agent = req.header("user-agent").cloned();
+        device.ip_address = req
+            .header("x-forwarded-for")
+            .and_then(|ips| ips.split(',').next())
+            .map(|ip| ip.trim().to_string());
+        let device = self.store.create_trusted_device(&device).await?;
+
+        self.emit(
+            "two_factor.device_trusted",
+            json!({
+                "user_id": device.user_id,
+                "device_id": device.id,
+                "expires_at": device.expires_at,
+            }),
+        )
+        .await;
+        Ok((token, device))
+    }
+
+    /// Returns true if `token` belongs to an unexpired trusted device of
+    /// `user_id`.
+    pub(crate) async fn is_trusted(&self, user_id: &str, token: &str) -> AuthResult<bool> {
+        Ok(self
+            .store
+            .get_trusted_device(user_id, &hash_token(token))
+            .await?
+            .is_some_and(|device| !device.is_expired()))
+    }
+
+    /// Revokes every trusted device of `user_id`, returning how many there
+    /// were.
+    pub(crate) async fn revoke_all(&self, user_id: &str) -> AuthResult<usize> {
+        let devices = self.store.delete_user_trusted_devices(user_id).await?;
+        for device in &devices {
+            self.emit(
+                "two_factor.device_revoked",
+                json!({ "user_id": device.user_id, "device_id": device.id }),
+            )
+            .await;
+        }
+        Ok(devices.len())
+    }
+
+    async fn emit(&self, event_type: &str, payload: serde_json::Value) {
+        if let Some(bus) = &self.event_bus {
+            bus.emit(Event::simple(event_type, payload).with_source("two_factor"))
+                .await;
+        }
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    digest(&SHA256, token.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use better_auth_core::router::Method;
+
+    fn devices(duration: Duration) -> TrustedDevices {
+        TrustedDevices {
+            store: Arc::new(InMemoryTrustedDeviceStore::new()),
+            duration,
+            event_bus: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trusted_until_expiry() {
+        let req = Request::new(Method::POST, "/two-factor/verify-totp");
+
+        let trusted = devices(Duration::days(30));
+        let (token, device) = trusted.trust("user_1", &req).await.unwrap();
+        assert_ne!(device.device_hash, token);
+        assert!(trusted.is_trusted("user_1", &token).await.unwrap());
+        assert!(!trusted.is_trusted("user_2", &token).await.unwrap());
+        assert!(!trusted.is_trusted("user_1", "other").await.unwrap());
+
+        let expired = devices(Duration::seconds(-1));
+        let (token, _) = expired.trust("user_1", &req).await.unwrap();
+        assert!(!expired.is_trusted("user_1", &token).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_all() {
+        let req = Request::new(Method::POST, "/two-factor/verify-totp");
+        let trusted = devices(Duration::days(30));
+        let (first, _) = trusted.trust("user_1", &req).await.unwrap();
+        let (second, _) = trusted.trust("user_1", &req).await.unwrap();
+        let (other, _) = trusted.trust("user_2", &req).await.unwrap();
+
+        assert_eq!(trusted.revoke_all("user_1").await.unwrap(), 2);
+        assert!(!trusted.is_trusted("user_1", &first).await.unwrap());
+        assert!(!trusted.is_trusted("user_1", &second).await.unwrap());
+        assert!(trusted.is_trusted("user_2", &other).await.unwrap());
+    }
+}
@@ -0,0 +1,207 @@
+//! Configuration for the Username plugin.
+
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::session_token::SessionTokenStrategy;
+use better_auth_core::traits::StorageAdapter;
+use better_auth_plugin_password::PasswordConfig;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Usernames rejected by default.
+const DEFAULT_RESERVED: &[&str] = &[
+    "admin",
+    "administrator",
+    "api",
+    "root",
+    "support",
+    "system",
+];
+
+/// Type alias for a custom username validator.
+pub type UsernameValidatorFn = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Configuration for the Username plugin.
+#[derive(Clone)]
+pub struct UsernameConfig {
+    /// Minimum username length. Default: 3.
+    pub min_length: usize,
+    /// Maximum username length. Default: 30.
+    pub max_length: usize,
+    /// Characters allowed in addition to ASCII letters and digits. Default: `_.`.
+    pub allowed_chars: String,
+    /// Usernames that can't be claimed (compared case-insensitively).
+    pub reserved: HashSet<String>,
+    /// Custom validator run after the built-in rules.
+    pub validator: Option<UsernameValidatorFn>,
+    /// Password settings used when signing in by username.
+    pub password: PasswordConfig,
+    /// Storage used by the sign-in route.
+    pub storage: Option<Arc<dyn StorageAdapter>>,
+    /// How tokens for the sessions it signs users into are generated.
+    /// Use the app's strategy. Default: opaque tokens.
+    pub session_tokens: SessionTokenStrategy,
+}
+
+impl Default for UsernameConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 3,
+            max_length: 30,
+            allowed_chars: "_.".to_string(),
+            reserved: DEFAULT_RESERVED.iter().map(|s| s.to_string()).collect(),
+            validator: None,
+            password: PasswordConfig::default(),
+            storage: None,
+            session_tokens: SessionTokenStrategy::default(),
+        }
+    }
+}
+
+impl UsernameConfig {
+    /// Creates a new config with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum username length.
+    pub fn min_length(mut self, len: usize) -> Self {
+        self.min_length = len;
+        self
+    }
+
+    /// Sets the maximum username length.
+    pub fn max_length(mut self, len: usize) -> Self {
+        self.max_length = len;
+        self
+    }
+
+    /// Sets the characters allowed besides ASCII letters and digits.
+    pub fn allowed_chars(mut self, chars: impl Into<String>) -> Self {
+        self.allowed_chars = chars.into();
+        self
+    }
+
+    /// Adds a reserved username.
+    pub fn reserve(mut self, username: impl Into<String>) -> Self {
+        self.reserved.insert(username.into().to_lowercase());
+        self
+    }
+
+    /// Replaces the reserved username list.
+    pub fn reserved<I, S>(mut self, usernames: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.reserved = usernames
+            .into_iter()
+            .map(|u| u.into().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Sets a custom validator, run after the built-in rules.
+    pub fn validate_with<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Sets the password settings used when signing in by username.
+    pub fn password(mut self, config: PasswordConfig) -> Self {
+        self.password = config;
+        self
+    }
+
+    /// Sets the storage adapter used by the sign-in route.
+    pub fn storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Sets how session tokens are generated.
+    pub fn session_tokens(mut self, strategy: SessionTokenStrategy) -> Self {
+        self.session_tokens = strategy;
+        self
+    }
+
+    /// Validates a username and returns its normalized (trimmed, lowercased) form.
+    pub fn validate(&self, username: &str) -> AuthResult<String> {
+        let normalized = username.trim().to_lowercase();
+        let invalid = |reason: String| AuthError::InvalidField {
+            field: "username".to_string(),
+            reason,
+        };
+
+        let len = normalized.chars().count();
+        if len < self.min_length || len > self.max_length {
+            return Err(invalid(format!(
+                "Username must be between {} and {} characters",
+                self.min_length, self.max_length
+            )));
+        }
+
+        if let Some(c) = normalized
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && !self.allowed_chars.contains(*c))
+        {
+            return Err(invalid(format!("Username may not contain '{}'", c)));
+        }
+
+        if self.reserved.contains(&normalized) {
+            return Err(invalid("Username is reserved".to_string()));
+        }
+
+        if let Some(validator) = &self.validator
+            && !validator(&normalized)
+        {
+            return Err(invalid("Username is not allowed".to_string()));
+        }
+
+        Ok(normalized)
+    }
+}
+
+impl std::fmt::Debug for UsernameConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UsernameConfig")
+            .field("min_length", &self.min_length)
+            .field("max_length", &self.max_length)
+            .field("allowed_chars", &self.allowed_chars)
+            .field("reserved", &self.reserved)
+            .field("validator", &self.validator.is_some())
+            .field("password", &self.password)
+            .field("storage", &self.storage.is_some())
+            .field("session_tokens", &self.session_tokens)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_normalizes() {
+        let config = UsernameConfig::default();
+        assert_eq!(config.validate("  Jane_Doe ").unwrap(), "jane_doe");
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        let config = UsernameConfig::default()
+            .reserve("Staff")
+            .validate_with(|u| !u.starts_with('.'));
+
+        assert!(config.validate("ab").is_err());
+        assert!(config.validate(&"a".repeat(31)).is_err());
+        assert!(config.validate("jane doe").is_err());
+        assert!(config.validate("jane-doe").is_err());
+        assert!(config.validate("Admin").is_err());
+        assert!(config.validate("staff").is_err());
+        assert!(config.validate(".jane").is_err());
+        assert!(config.validate("jane.doe").is_ok());
+    }
+}
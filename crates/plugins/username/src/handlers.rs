@@ -0,0 +1,116 @@
+//! Request handlers for the Username plugin.
+
+use crate::UsernamePlugin;
+use async_trait::async_trait;
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::router::{CookieOptions, Request, RequestHandler, Response};
+use better_auth_core::session::{SESSION_COOKIE, end_replaced_session};
+use better_auth_core::types::AuthFactor;
+use better_auth_plugin_password::PasswordExt;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Request body for signing in by username or email.
+#[derive(Debug, Deserialize)]
+pub struct SignInUsernameRequest {
+    /// A username or email address.
+    #[serde(alias = "username", alias = "email")]
+    pub identifier: String,
+    /// The user's password.
+    pub password: String,
+}
+
+/// Handler for POST /sign-in/username
+pub struct SignInUsernameHandler {
+    plugin: Arc<UsernamePlugin>,
+}
+
+impl SignInUsernameHandler {
+    /// Creates a new handler.
+    pub fn new(plugin: Arc<UsernamePlugin>) -> Self {
+        Self { plugin }
+    }
+
+    async fn sign_in(&self, req: &Request) -> AuthResult<Response> {
+        let storage = self
+            .plugin
+            .config()
+            .storage
+            .clone()
+            .ok_or_else(|| AuthError::config("Username sign-in requires a storage adapter"))?;
+        let body: SignInUsernameRequest = req.json().ok_or_else(|| AuthError::MissingField {
+            field: "identifier".to_string(),
+        })?;
+
+        let password = self.plugin.password();
+        let ip = req.ip.as_deref();
+
+        // Failures are counted by email, as for email sign-in, so a user is
+        // locked out however they identify themselves. Unknown identifiers
+        // are counted as given.
+        let user = UsernamePlugin::resolve_user(storage.as_ref(), &body.identifier).await?;
+        let key = user
+            .as_ref()
+            .map_or_else(|| body.identifier.clone(), |user| user.email.clone());
+        password.check_signin(&key, ip)?;
+
+        // Unknown users and wrong passwords are indistinguishable to the
+        // caller, in response and timing, and both count towards the lockout.
+        let hash = user.as_ref().and_then(|user| user.password_hash());
+        let verified = password.verify_password_or_dummy(&body.password, hash.as_deref());
+        let Some(user) = user.filter(|_| verified) else {
+            password.record_signin_failure(&key, ip).await;
+            return Err(AuthError::InvalidCredentials);
+        };
+        password.record_signin_success(&user.email, ip);
+        password
+            .upgrade_password_hash(storage.as_ref(), &user, &body.password)
+            .await;
+        // Checked only after the password, so the response doesn't reveal
+        // whether an unverified account exists.
+        if password.config().require_email_verification {
+            user.require_verified_email()?;
+        }
+
+        let mut session = self
+            .plugin
+            .config()
+            .session_tokens
+            .new_session(user.id.clone());
+        session.record_factor(AuthFactor::Password);
+        let session = storage.create_session(&session).await?;
+        end_replaced_session(storage.as_ref(), req).await?;
+
+        Ok(Response::ok()
+            .json(json!({
+                "user": {
+                    "id": user.id,
+                    "email": user.email,
+                    "name": user.name,
+                    "image": user.image,
+                },
+                "session": {
+                    "id": session.id,
+                    "token": session.token,
+                    "expires_at": session.expires_at.to_rfc3339(),
+                }
+            }))
+            .cookie(SESSION_COOKIE, &session.token, CookieOptions::secure()))
+    }
+}
+
+#[async_trait]
+impl RequestHandler for SignInUsernameHandler {
+    async fn handle(&self, req: Request) -> Response {
+        self.sign_in(&req).await.unwrap_or_else(|err| {
+            let mut error = json!({ "code": err.error_code(), "message": err.to_string() });
+            // Lets the client offer to resend the verification email.
+            if let AuthError::EmailNotVerified { user_id, email } = &err {
+                error["userId"] = json!(user_id);
+                error["email"] = json!(email);
+            }
+            Response::new(err.status_code()).json(json!({ "error": error }))
+        })
+    }
+}
@@ -0,0 +1,393 @@
+//! # Better Auth Username Plugin
+//!
+//! This plugin adds an optional, unique username to users. Users can sign up
+//! with a username and sign in with either their username or their email,
+//! alongside the existing email-centric flows.
+
+mod config;
+mod handlers;
+mod schema;
+
+pub use config::{UsernameConfig, UsernameValidatorFn};
+pub use handlers::{SignInUsernameHandler, SignInUsernameRequest};
+pub use schema::UsernameUserExt;
+
+use schema::MAX_USERNAME_COLUMN;
+
+use async_trait::async_trait;
+use better_auth_core::context::{AuthContext, SignUpData};
+use better_auth_core::error::{AuthError, AuthResult};
+use better_auth_core::router::{Method, Route, Router};
+use better_auth_core::schema::SchemaBuilder;
+use better_auth_core::traits::{AuthPlugin, ExtensionProvider, StorageAdapter};
+use better_auth_core::types::User;
+use better_auth_events_sdk::{EventDefinition, EventProvider};
+use better_auth_plugin_password::PasswordPlugin;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Trait for username operations on users.
+pub trait UsernameExt {
+    /// Gets the user's username.
+    fn username(&self) -> Option<String>;
+    /// Sets the user's username.
+    ///
+    /// The value is stored as given; validate it with
+    /// [`UsernameConfig::validate`] first.
+    fn set_username(&mut self, username: impl Into<String>);
+}
+
+impl UsernameExt for User {
+    fn username(&self) -> Option<String> {
+        self.get_extension("username")
+    }
+
+    fn set_username(&mut self, username: impl Into<String>) {
+        self.set_extension("username", username.into());
+    }
+}
+
+/// The Username authentication plugin.
+pub struct UsernamePlugin {
+    config: UsernameConfig,
+    password: PasswordPlugin,
+}
+
+impl UsernamePlugin {
+    /// Creates a new Username plugin with the given configuration.
+    pub fn new(config: UsernameConfig) -> Self {
+        let password = PasswordPlugin::new(config.password.clone());
+        Self { config, password }
+    }
+
+    /// Verifies passwords with `password` instead of a plugin of its own.
+    ///
+    /// Pass the registered [`PasswordPlugin`] so username sign-ins share its
+    /// settings and sign-in failure counts; `config.password` is then unused.
+    pub fn with_password(mut self, password: &PasswordPlugin) -> Self {
+        self.password = password.shared();
+        self
+    }
+
+    /// Gets the plugin configuration.
+    pub fn config(&self) -> &UsernameConfig {
+        &self.config
+    }
+
+    /// Gets the password plugin used to verify credentials.
+    pub fn password(&self) -> &PasswordPlugin {
+        &self.password
+    }
+
+    /// Finds a user by username.
+    pub async fn get_user_by_username(
+        storage: &dyn StorageAdapter,
+        username: &str,
+    ) -> AuthResult<Option<User>> {
+        let normalized = username.trim().to_lowercase();
+        storage
+            .get_user_by_extension("username", &Value::String(normalized))
+            .await
+    }
+
+    /// Resolves a sign-in identifier to a user, trying email then username.
+    pub async fn resolve_user(
+        storage: &dyn StorageAdapter,
+        identifier: &str,
+    ) -> AuthResult<Option<User>> {
+        if let Some(user) = storage.get_user_by_email(identifier.trim()).await? {
+            return Ok(Some(user));
+        }
+        Self::get_user_by_username(storage, identifier).await
+    }
+
+    /// Validates a username and checks that no other user has claimed it.
+    ///
+    /// Returns the normalized username.
+    pub async fn check_available(
+        &self,
+        storage: &dyn StorageAdapter,
+        username: &str,
+    ) -> AuthResult<String> {
+        let normalized = self.config.validate(username)?;
+        if Self::get_user_by_username(storage, &normalized).await?.is_some() {
+            return Err(AuthError::duplicate("user", "username", &normalized));
+        }
+        Ok(normalized)
+    }
+}
+
+impl Default for UsernamePlugin {
+    fn default() -> Self {
+        Self::new(UsernameConfig::default())
+    }
+}
+
+impl EventProvider for UsernamePlugin {
+    fn provided_events() -> Vec<EventDefinition> {
+        vec![EventDefinition::simple(
+            "username.sign_in",
+            "Emitted when a user signs in with their username",
+            "username",
+        )]
+    }
+
+    fn event_source() -> &'static str {
+        "username"
+    }
+}
+
+#[async_trait]
+impl AuthPlugin for UsernamePlugin {
+    fn id(&self) -> &'static str {
+        "username"
+    }
+
+    fn name(&self) -> &'static str {
+        "Username Authentication"
+    }
+
+    fn validate_config(&self) -> AuthResult<()> {
+        let mut issues = Vec::new();
+        if self.config.min_length == 0 {
+            issues.push("min_length must be at least 1".to_string());
+        }
+        if self.config.min_length > self.config.max_length {
+            issues.push("min_length must not exceed max_length".to_string());
+        }
+        if self.config.max_length > MAX_USERNAME_COLUMN {
+            issues.push(format!(
+                "max_length must not exceed {} (the username column size)",
+                MAX_USERNAME_COLUMN
+            ));
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(AuthError::invalid_config("username", issues))
+        }
+    }
+
+    fn define_schema(&self, builder: &mut SchemaBuilder) {
+        for field in UsernameUserExt::fields() {
+            builder.add_field_mut("user", field);
+        }
+        builder.add_index_mut("user", UsernameUserExt::index());
+    }
+
+    fn register_routes(&self, router: &mut Router) {
+        let plugin = Arc::new(UsernamePlugin {
+            config: self.config.clone(),
+            password: self.password.shared(),
+        });
+
+        // POST /sign-in/username
+        router.route(
+            Route::new(
+                Method::POST,
+                "/sign-in/username",
+                SignInUsernameHandler::new(plugin),
+            )
+            .summary("Sign in with username")
+            .description("Signs in a user with their username or email and password.")
+            .tag("username"),
+        );
+    }
+
+    async fn on_before_signup(&self, ctx: &AuthContext, data: &mut SignUpData) -> AuthResult<()> {
+        let Some(username) = data.extra.get("username") else {
+            return Ok(());
+        };
+        let username = username.as_str().ok_or_else(|| AuthError::InvalidField {
+            field: "username".to_string(),
+            reason: "Username must be a string".to_string(),
+        })?;
+
+        let normalized = self.check_available(ctx.db.as_ref(), username).await?;
+        data.extra.insert("username".to_string(), Value::String(normalized));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use better_auth_core::types::{AuthFactor, Session};
+
+    #[test]
+    fn test_plugin_creation() {
+        let plugin = UsernamePlugin::default();
+        assert_eq!(plugin.id(), "username");
+        assert_eq!(plugin.config().max_length, 30);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        assert!(UsernamePlugin::default().validate_config().is_ok());
+
+        let plugin = UsernamePlugin::new(UsernameConfig::default().min_length(10).max_length(100));
+        let Err(AuthError::InvalidPluginConfig { issues }) = plugin.validate_config() else {
+            panic!("expected config issues");
+        };
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("column size"));
+    }
+
+    #[test]
+    fn test_user_extension() {
+        let mut user = User::new("test_id".to_string(), "test@example.com".to_string());
+        assert!(user.username().is_none());
+
+        user.set_username("jane_doe");
+        assert_eq!(user.username(), Some("jane_doe".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_requires_verified_email() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::router::{Method, Request};
+        use better_auth_plugin_password::{PasswordConfig, PasswordExt};
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let config = UsernameConfig::new()
+            .password(PasswordConfig::new().require_email_verification())
+            .storage(storage.clone());
+        let plugin = UsernamePlugin::new(config);
+        let mut user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        user.set_password_hash(plugin.password().hash_password("correct horse"));
+        storage.create_user(&user).await.unwrap();
+        let old = storage
+            .create_session(&Session::new("user_1".to_string()))
+            .await
+            .unwrap();
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router.routes().find(|r| r.path == "/sign-in/username").unwrap();
+        let sign_in = |password: &str| {
+            let mut req = Request::new(Method::POST, "/sign-in/username");
+            req.body = Some(serde_json::json!({ "email": "jane@example.com", "password": password }));
+            let cookie = format!("better_auth_session={}", old.token);
+            req.headers.insert("cookie".to_string(), cookie);
+            route.handler.handle(req)
+        };
+
+        // A wrong password doesn't reveal that the email is unverified.
+        let response = sign_in("wrong").await;
+        assert_eq!(response.status, 401);
+        assert_eq!(
+            response.body.unwrap()["error"]["code"],
+            "INVALID_CREDENTIALS"
+        );
+
+        let response = sign_in("correct horse").await;
+        assert_eq!(response.status, 403);
+        let error = &response.body.unwrap()["error"];
+        assert_eq!(error["code"], "EMAIL_NOT_VERIFIED");
+        assert_eq!(error["userId"], "user_1");
+        assert_eq!(error["email"], "jane@example.com");
+
+        storage.mark_email_verified("jane@example.com").await.unwrap();
+        let response = sign_in("correct horse").await;
+        assert_eq!(response.status, 200);
+        let token = response.body.unwrap()["session"]["token"].clone();
+        let session = storage
+            .get_session_by_token(token.as_str().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(session.factor_verified_at(AuthFactor::Password).is_some());
+        // The session the request came with is replaced, not kept alongside.
+        assert!(storage.get_session_by_id(&old.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_locks_out_after_failures() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::router::{Method, Request};
+        use better_auth_plugin_password::{PasswordConfig, PasswordExt};
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let config = UsernameConfig::new()
+            .password(PasswordConfig::new().max_signin_failures(2))
+            .storage(storage.clone());
+        let plugin = UsernamePlugin::new(config);
+        let mut user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        user.set_password_hash(plugin.password().hash_password("correct horse"));
+        storage.create_user(&user).await.unwrap();
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router.routes().find(|r| r.path == "/sign-in/username").unwrap();
+        let sign_in = |email: &str, password: &str| {
+            let mut req = Request::new(Method::POST, "/sign-in/username");
+            req.ip = Some("203.0.113.7".to_string());
+            req.body = Some(serde_json::json!({ "email": email, "password": password }));
+            route.handler.handle(req)
+        };
+
+        // A success in between resets the count.
+        assert_eq!(sign_in("jane@example.com", "wrong").await.status, 401);
+        assert_eq!(sign_in("jane@example.com", "correct horse").await.status, 200);
+        assert_eq!(sign_in("jane@example.com", "wrong").await.status, 401);
+        assert_eq!(sign_in("jane@example.com", "wrong").await.status, 401);
+        // Locked out even with the right password, with a distinct status.
+        assert_eq!(sign_in("jane@example.com", "correct horse").await.status, 429);
+
+        // Unknown emails lock out the same way.
+        assert_eq!(sign_in("nobody@example.com", "wrong").await.status, 401);
+        assert_eq!(sign_in("nobody@example.com", "wrong").await.status, 401);
+        assert_eq!(sign_in("nobody@example.com", "wrong").await.status, 429);
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_failures_are_shared_with_the_password_plugin() {
+        use better_auth_adapter_memory::MemoryAdapter;
+        use better_auth_core::context::{AuthContext, SignInCredentials};
+        use better_auth_core::router::{Method, Request};
+        use better_auth_plugin_password::{PasswordConfig, PasswordExt};
+
+        let storage = Arc::new(MemoryAdapter::new());
+        let password = PasswordPlugin::new(PasswordConfig::new().max_signin_failures(3));
+        let plugin = UsernamePlugin::new(UsernameConfig::new().storage(storage.clone()))
+            .with_password(&password);
+        let mut user = User::new("user_1".to_string(), "jane@example.com".to_string());
+        user.set_username("jane");
+        user.set_password_hash(password.hash_password("correct horse"));
+        storage.create_user(&user).await.unwrap();
+
+        let mut router = Router::new("/api/auth");
+        plugin.register_routes(&mut router);
+        let route = router.routes().find(|r| r.path == "/sign-in/username").unwrap();
+        let sign_in = |identifier: &str, password: &str| {
+            let mut req = Request::new(Method::POST, "/sign-in/username");
+            req.ip = Some("203.0.113.7".to_string());
+            req.body = Some(serde_json::json!({ "identifier": identifier, "password": password }));
+            route.handler.handle(req)
+        };
+
+        // Failures by username, by email, and through the email sign-in hook
+        // all count towards the same lockout.
+        assert_eq!(sign_in("jane", "wrong").await.status, 401);
+        assert_eq!(sign_in("jane@example.com", "wrong").await.status, 401);
+        let mut ctx = AuthContext::new(storage.clone());
+        ctx.request.ip = Some("203.0.113.7".parse().unwrap());
+        let creds = SignInCredentials::new("Jane@example.com", "wrong");
+        password.on_signin_failure(&ctx, &creds).await.unwrap();
+        assert_eq!(sign_in("jane", "correct horse").await.status, 429);
+        assert!(password.on_before_signin(&ctx, &creds).await.is_err());
+    }
+
+    #[test]
+    fn test_schema_adds_unique_index() {
+        let plugin = UsernamePlugin::default();
+        let mut builder = SchemaBuilder::with_core();
+        plugin.define_schema(&mut builder);
+
+        let schema = builder.build();
+        let user = schema.models.iter().find(|m| m.name == "user").unwrap();
+        assert!(user.fields.iter().any(|f| f.name == "username" && f.unique));
+        assert!(user.indexes.iter().any(|i| i.name == "idx_user_username"));
+    }
+}
@@ -0,0 +1,45 @@
+//! Schema definitions for the Username plugin.
+
+use better_auth_core::schema::{Field, FieldType, IndexDefinition};
+use better_auth_core::traits::ExtensionProvider;
+use serde::{Deserialize, Serialize};
+
+/// Size of the `username` column.
+pub(crate) const MAX_USERNAME_COLUMN: usize = 64;
+
+/// User extension fields for username authentication.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UsernameUserExt {
+    /// The user's normalized username.
+    pub username: Option<String>,
+}
+
+impl ExtensionProvider for UsernameUserExt {
+    fn extends() -> &'static str {
+        "user"
+    }
+
+    fn fields() -> Vec<Field> {
+        vec![Field::optional("username", FieldType::String(MAX_USERNAME_COLUMN as u32)).unique()]
+    }
+}
+
+impl UsernameUserExt {
+    /// Returns the unique index on `user.username`.
+    pub fn index() -> IndexDefinition {
+        IndexDefinition::unique("idx_user_username", vec!["username".to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_extension_fields() {
+        let fields = UsernameUserExt::fields();
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].name, "username");
+        assert!(fields[0].unique);
+    }
+}
@@ -1,11 +1,12 @@
 //! Webhook endpoint configuration builder.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
-use better_auth_webhooks::{EventFilter, WebhookEndpoint, WebhookMetadata};
+use better_auth_webhooks::{EventFilter, WebhookEndpoint, WebhookMetadata, WebhookTransformer};
 
 /// Configuration for a webhook endpoint.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct WebhookEndpointConfig {
     url: String,
     secret: Option<String>,
@@ -14,6 +15,21 @@ pub struct WebhookEndpointConfig {
     headers: HashMap<String, String>,
     timeout_ms: u64,
     enabled: bool,
+    transformer: Option<Arc<dyn WebhookTransformer>>,
+}
+
+impl std::fmt::Debug for WebhookEndpointConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookEndpointConfig")
+            .field("url", &self.url)
+            .field("events", &self.events)
+            .field("description", &self.description)
+            .field("headers", &self.headers)
+            .field("timeout_ms", &self.timeout_ms)
+            .field("enabled", &self.enabled)
+            .field("transformer", &self.transformer.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl WebhookEndpointConfig {
@@ -27,6 +43,7 @@ impl WebhookEndpointConfig {
             headers: HashMap::new(),
             timeout_ms: 30000,
             enabled: true,
+            transformer: None,
         }
     }
 
@@ -86,6 +103,12 @@ impl WebhookEndpointConfig {
         self
     }
 
+    /// Sets the transformer that shapes payloads for this endpoint.
+    pub fn transformer(mut self, transformer: impl WebhookTransformer + 'static) -> Self {
+        self.transformer = Some(Arc::new(transformer));
+        self
+    }
+
     /// Converts to a WebhookEndpoint.
     pub fn into_endpoint(self) -> WebhookEndpoint {
         let secret = self.secret.unwrap_or_else(|| {
@@ -109,6 +132,10 @@ impl WebhookEndpointConfig {
             endpoint = endpoint.disabled();
         }
 
+        if let Some(transformer) = self.transformer {
+            endpoint = endpoint.transformer(transformer);
+        }
+
         endpoint
     }
 }
@@ -24,7 +24,7 @@ impl WebhookClient {
 
     /// Verifies and parses a webhook.
     pub fn verify(&self, signature: &str, payload: &[u8]) -> WebhookResult<WebhookPayload> {
-        self.receiver.verify(signature, payload)
+        self.receiver.verify_and_parse(signature, payload)
     }
 
     /// Verifies a webhook signature only.
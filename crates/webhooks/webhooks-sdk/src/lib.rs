@@ -28,7 +28,7 @@ mod traits;
 mod builder;
 mod client;
 
-pub use traits::{WebhookProvider, WebhookTransformer, PluginWebhookRegistrar};
+pub use traits::{WebhookProvider, PluginWebhookRegistrar};
 pub use builder::{WebhookEndpointConfig, WebhookConfigBuilder};
 pub use client::{WebhookClient, WebhookClientBuilder};
 
@@ -40,4 +40,5 @@ pub use better_auth_webhooks::{
     WebhookError, WebhookResult,
     RetryStrategy, ExponentialBackoff, LinearBackoff, FixedDelay,
     WebhookSystem, WebhookConfig,
+    WebhookTransformer, DefaultTransformer,
 };
@@ -2,9 +2,7 @@
 
 use std::sync::Arc;
 
-use better_auth_events::Event;
 use better_auth_webhooks::{WebhookEndpoint, WebhookSystem};
-use serde_json::Value;
 
 use crate::builder::WebhookEndpointConfig;
 
@@ -19,38 +17,6 @@ pub trait WebhookProvider {
     fn webhook_source() -> &'static str;
 }
 
-/// Trait for transforming event payloads before webhook delivery.
-///
-/// Implement this trait to customize how events are serialized for webhooks.
-pub trait WebhookTransformer {
-    /// Transforms an event payload for webhook delivery.
-    ///
-    /// Return `None` to use the default payload format.
-    /// Return `Some(value)` to use a custom payload.
-    fn transform_payload(&self, event: &Event) -> Option<Value> {
-        None
-    }
-
-    /// Filters whether an event should be sent to webhooks.
-    ///
-    /// Return `true` to allow the event, `false` to skip it.
-    fn should_send(&self, event: &Event) -> bool {
-        true
-    }
-
-    /// Adds custom headers to webhook requests.
-    fn custom_headers(&self, event: &Event) -> std::collections::HashMap<String, String> {
-        std::collections::HashMap::new()
-    }
-}
-
-/// Default transformer that passes events through unchanged.
-pub struct DefaultTransformer;
-
-impl WebhookTransformer for DefaultTransformer {
-    // Uses all default implementations
-}
-
 /// Helper for registering plugin webhooks.
 pub struct PluginWebhookRegistrar<Q, R>
 where
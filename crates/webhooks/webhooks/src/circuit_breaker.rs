@@ -6,6 +6,7 @@
 //! - Half-Open: Testing recovery, limited requests allowed
 
 use crate::{WebhookError, WebhookResult};
+use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -21,7 +22,7 @@ pub struct CircuitBreaker {
 }
 
 /// Configuration for circuit breaker behavior
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CircuitBreakerConfig {
     /// Number of consecutive failures before opening circuit
     pub failure_threshold: u32,
@@ -93,6 +94,20 @@ impl CircuitBreaker {
         F: FnOnce() -> Fut,
         Fut: Future<Output = WebhookResult<T>>,
     {
+        self.acquire().await?;
+
+        // Execute the call
+        let result = f().await;
+
+        self.release(result.is_ok()).await;
+        result
+    }
+
+    /// Check whether a call may proceed and reserve a slot for it
+    ///
+    /// Every successful acquire must be paired with a [`release`](Self::release)
+    /// once the outcome is known.
+    pub(crate) async fn acquire(&self) -> WebhookResult<()> {
         // Check current state and decide if call is allowed
         {
             let state = self.state.read().await;
@@ -119,23 +134,19 @@ impl CircuitBreaker {
 
         // Increment active calls if in half-open
         self.increment_active_calls().await;
+        Ok(())
+    }
 
-        // Execute the call
-        let result = f().await;
-
+    /// Release a slot taken by [`acquire`](Self::acquire) and record the outcome
+    pub(crate) async fn release(&self, success: bool) {
         // Decrement active calls if in half-open
         self.decrement_active_calls().await;
 
         // Update state based on result
-        match result {
-            Ok(value) => {
-                self.on_success().await;
-                Ok(value)
-            }
-            Err(e) => {
-                self.on_failure().await;
-                Err(e)
-            }
+        if success {
+            self.on_success().await;
+        } else {
+            self.on_failure().await;
         }
     }
 
@@ -259,6 +270,23 @@ impl CircuitBreaker {
         self.state.read().await.clone()
     }
 
+    /// Time left before an open circuit moves to half-open
+    ///
+    /// Returns `None` unless the circuit is open.
+    pub async fn retry_after(&self) -> Option<Duration> {
+        match *self.state.read().await {
+            CircuitState::Open { opened_at } => {
+                Some(self.config.timeout.saturating_sub(opened_at.elapsed()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
     /// Check if circuit is open
     pub async fn is_open(&self) -> bool {
         matches!(*self.state.read().await, CircuitState::Open { .. })
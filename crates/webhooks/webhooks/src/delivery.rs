@@ -3,9 +3,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
 use better_auth_events::Event;
 
+use crate::circuit_breaker::{CircuitBreaker, CircuitState};
 use crate::endpoint::WebhookEndpoint;
 use crate::error::{WebhookError, WebhookResult};
 use crate::queue::{QueueError, WebhookQueue};
@@ -19,6 +23,9 @@ pub struct WebhookJob {
     pub id: String,
     /// Endpoint ID.
     pub endpoint_id: String,
+    /// Type of the event being delivered.
+    #[serde(default)]
+    pub event_type: String,
     /// Target URL.
     pub url: String,
     /// Payload to send.
@@ -58,18 +65,32 @@ pub enum WebhookJobStatus {
 
 impl WebhookJob {
     /// Creates a new webhook job from an endpoint and event.
+    ///
+    /// The endpoint's transformer, if any, supplies the payload and extra
+    /// headers.
     pub fn new(endpoint: &WebhookEndpoint, event: &Event) -> Self {
-        let payload = serde_json::json!({
-            "id": uuid::Uuid::new_v4().to_string(),
-            "type": event.simple_type_string(),
-            "data": event.payload,
-            "timestamp": event.timestamp.to_rfc3339(),
-            "correlation_id": event.correlation_id,
-        });
+        let transformer = endpoint.transformer.as_deref();
+        let payload = transformer
+            .and_then(|t| t.transform_payload(event))
+            .unwrap_or_else(|| {
+                serde_json::json!({
+                    "id": uuid::Uuid::new_v4().to_string(),
+                    "type": event.simple_type_string(),
+                    "data": event.payload,
+                    "timestamp": event.timestamp.to_rfc3339(),
+                    "correlation_id": event.correlation_id,
+                })
+            });
+
+        let mut headers = endpoint.metadata.headers.clone();
+        if let Some(transformer) = transformer {
+            headers.extend(transformer.custom_headers(event));
+        }
 
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             endpoint_id: endpoint.id.clone(),
+            event_type: event.simple_type_string(),
             url: endpoint.url.clone(),
             payload,
             secret: endpoint.secret.clone(),
@@ -79,7 +100,7 @@ impl WebhookJob {
             created_at: Utc::now(),
             last_error: None,
             status: WebhookJobStatus::Pending,
-            headers: endpoint.metadata.headers.clone(),
+            headers,
             timeout_ms: endpoint.metadata.timeout_ms,
         }
     }
@@ -126,6 +147,18 @@ impl WebhookJob {
         signer.sign_header(timestamp, payload_bytes.as_bytes())
     }
 
+    /// Gets the type of the event being delivered.
+    pub fn event_type(&self) -> String {
+        // Jobs queued before `event_type` was recorded only have it in the
+        // default payload.
+        if self.event_type.is_empty() {
+            let event_type = self.payload["type"].as_str();
+            event_type.unwrap_or("unknown").to_string()
+        } else {
+            self.event_type.clone()
+        }
+    }
+
     /// Checks if the job can be retried.
     pub fn can_retry(&self) -> bool {
         self.attempts < self.max_attempts && self.status != WebhookJobStatus::Completed
@@ -162,7 +195,7 @@ impl WebhookDelivery {
             id: uuid::Uuid::new_v4().to_string(),
             job_id: job.id.clone(),
             endpoint_id: job.endpoint_id.clone(),
-            event_type: job.payload["type"].as_str().unwrap_or("unknown").to_string(),
+            event_type: job.event_type(),
             status_code: Some(status_code),
             response_body,
             error: None,
@@ -177,7 +210,7 @@ impl WebhookDelivery {
             id: uuid::Uuid::new_v4().to_string(),
             job_id: job.id.clone(),
             endpoint_id: job.endpoint_id.clone(),
-            event_type: job.payload["type"].as_str().unwrap_or("unknown").to_string(),
+            event_type: job.event_type(),
             status_code: None,
             response_body: None,
             error: Some(error.into()),
@@ -188,9 +221,14 @@ impl WebhookDelivery {
 }
 
 /// Webhook delivery engine.
+///
+/// Each endpoint gets its own [`CircuitBreaker`]. While an endpoint's circuit
+/// is open its jobs are deferred until the cooldown ends instead of being
+/// attempted, so a failing endpoint doesn't hold up the rest of the queue.
 pub struct DeliveryEngine<Q: WebhookQueue, R: RetryStrategy> {
     queue: Q,
     retry_strategy: R,
+    breakers: RwLock<HashMap<String, Arc<CircuitBreaker>>>,
     #[cfg(feature = "http-client")]
     client: reqwest::Client,
 }
@@ -201,6 +239,7 @@ impl<Q: WebhookQueue, R: RetryStrategy> DeliveryEngine<Q, R> {
         Self {
             queue,
             retry_strategy,
+            breakers: RwLock::new(HashMap::new()),
             #[cfg(feature = "http-client")]
             client: reqwest::Client::new(),
         }
@@ -211,16 +250,117 @@ impl<Q: WebhookQueue, R: RetryStrategy> DeliveryEngine<Q, R> {
         self.queue.enqueue(job).await
     }
 
+    /// Builds and enqueues a job delivering an event to an endpoint.
+    ///
+    /// The endpoint's transformer, if any, decides whether the event is sent
+    /// and shapes the body, which is what gets signed on delivery. Returns
+    /// `false` if the transformer skipped the event.
+    pub async fn enqueue_event(
+        &self,
+        endpoint: &WebhookEndpoint,
+        event: &Event,
+        max_attempts: u32,
+    ) -> Result<bool, QueueError> {
+        if let Some(transformer) = &endpoint.transformer
+            && !transformer.should_send(event)
+        {
+            return Ok(false);
+        }
+
+        self.configure_circuit_breaker(endpoint).await;
+        let job = WebhookJob::new(endpoint, event).with_max_attempts(max_attempts);
+        self.queue.enqueue(job).await?;
+        Ok(true)
+    }
+
+    /// Applies an endpoint's circuit breaker settings.
+    ///
+    /// The breaker keeps its state unless the settings changed. Endpoints
+    /// that were never configured use the default settings.
+    pub async fn configure_circuit_breaker(&self, endpoint: &WebhookEndpoint) {
+        let mut breakers = self.breakers.write().await;
+        let current = breakers.get(&endpoint.id);
+        if current.is_none_or(|b| *b.config() != endpoint.circuit_breaker) {
+            let breaker = CircuitBreaker::with_config(endpoint.circuit_breaker.clone());
+            breakers.insert(endpoint.id.clone(), Arc::new(breaker));
+        }
+    }
+
+    /// Gets the circuit state of an endpoint, if it has a breaker yet.
+    pub async fn circuit_state(&self, endpoint_id: &str) -> Option<CircuitState> {
+        let breaker = self.breakers.read().await.get(endpoint_id).cloned()?;
+        Some(breaker.state().await)
+    }
+
+    /// Gets the circuit state of every endpoint with a breaker.
+    pub async fn circuit_states(&self) -> HashMap<String, CircuitState> {
+        let breakers: Vec<_> = {
+            let breakers = self.breakers.read().await;
+            breakers
+                .iter()
+                .map(|(id, b)| (id.clone(), b.clone()))
+                .collect()
+        };
+
+        let mut states = HashMap::with_capacity(breakers.len());
+        for (id, breaker) in breakers {
+            states.insert(id, breaker.state().await);
+        }
+        states
+    }
+
+    /// Closes an endpoint's circuit so its jobs are attempted again.
+    pub async fn reset_circuit(&self, endpoint_id: &str) {
+        let breaker = self.breakers.read().await.get(endpoint_id).cloned();
+        if let Some(breaker) = breaker {
+            breaker.reset().await;
+        }
+    }
+
+    /// Gets or creates the breaker for an endpoint.
+    async fn breaker(&self, endpoint_id: &str) -> Arc<CircuitBreaker> {
+        if let Some(breaker) = self.breakers.read().await.get(endpoint_id) {
+            return breaker.clone();
+        }
+
+        let mut breakers = self.breakers.write().await;
+        breakers.entry(endpoint_id.to_string()).or_default().clone()
+    }
+
     /// Processes the next job in the queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WebhookError::CircuitOpen` if the job's endpoint is tripped.
+    /// The job is put back without using up an attempt and becomes ready
+    /// again once the breaker's cooldown has passed.
     #[cfg(feature = "http-client")]
     pub async fn process_next(&self) -> WebhookResult<Option<WebhookDelivery>> {
-        let job = match self.queue.dequeue().await {
+        let mut job = match self.queue.dequeue().await {
             Ok(Some(job)) => job,
             Ok(None) => return Ok(None),
             Err(e) => return Err(WebhookError::QueueError(e.to_string())),
         };
 
+        let breaker = self.breaker(&job.endpoint_id).await;
+        if let Err(e) = breaker.acquire().await {
+            // Half-open with every trial slot taken has no cooldown to wait
+            // out, so check back shortly.
+            let wait = breaker
+                .retry_after()
+                .await
+                .unwrap_or(std::time::Duration::from_secs(1));
+            job.next_attempt = Utc::now() + chrono::Duration::from_std(wait).unwrap_or_default();
+            self.queue
+                .schedule_retry(job)
+                .await
+                .map_err(|e| WebhookError::QueueError(e.to_string()))?;
+            return Err(e);
+        }
+
         let delivery = self.deliver(&job).await;
+        let delivered = matches!(&delivery, Ok(d) if d.error.is_none());
+        breaker.release(delivered).await;
 
         match &delivery {
             Ok(d) if d.error.is_none() => {
@@ -3,9 +3,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::transform::WebhookTransformer;
 
 /// Webhook endpoint configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct WebhookEndpoint {
     /// Unique identifier.
     pub id: String,
@@ -19,6 +23,26 @@ pub struct WebhookEndpoint {
     pub enabled: bool,
     /// Endpoint metadata.
     pub metadata: WebhookMetadata,
+    /// Circuit breaker settings used when delivering to this endpoint.
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Transformer that shapes the payload sent to this endpoint.
+    #[serde(skip)]
+    pub transformer: Option<Arc<dyn WebhookTransformer>>,
+}
+
+impl std::fmt::Debug for WebhookEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookEndpoint")
+            .field("id", &self.id)
+            .field("url", &self.url)
+            .field("events", &self.events)
+            .field("enabled", &self.enabled)
+            .field("metadata", &self.metadata)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("transformer", &self.transformer.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl WebhookEndpoint {
@@ -31,6 +55,8 @@ impl WebhookEndpoint {
             events: EventFilter::All,
             enabled: true,
             metadata: WebhookMetadata::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            transformer: None,
         }
     }
 
@@ -78,6 +104,18 @@ impl WebhookEndpoint {
         self
     }
 
+    /// Sets the circuit breaker settings for deliveries to this endpoint.
+    pub fn circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = config;
+        self
+    }
+
+    /// Sets the transformer that shapes payloads for this endpoint.
+    pub fn transformer(mut self, transformer: Arc<dyn WebhookTransformer>) -> Self {
+        self.transformer = Some(transformer);
+        self
+    }
+
     /// Disables the endpoint.
     pub fn disabled(mut self) -> Self {
         self.enabled = false;
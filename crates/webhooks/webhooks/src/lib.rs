@@ -32,6 +32,7 @@ mod receiver;
 mod storage;
 mod error;
 mod system;
+mod transform;
 pub mod circuit_breaker;
 pub mod rate_limiter;
 
@@ -44,5 +45,6 @@ pub use receiver::{WebhookReceiver, WebhookPayload};
 pub use storage::WebhookStorage;
 pub use error::{WebhookError, WebhookResult};
 pub use system::{WebhookSystem, WebhookConfig};
+pub use transform::{WebhookTransformer, DefaultTransformer};
 pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
 pub use rate_limiter::{WebhookRateLimiter, EndpointRateLimit, RateLimitPermit, RateLimitInfo};
@@ -8,15 +8,29 @@
 
 use crate::{WebhookError, WebhookResult};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// The fewest buckets at which idle default buckets are evicted
+const MIN_SWEEP_LEN: usize = 1024;
+
+/// Source of the current time, so tests can move it forward
+type Clock = Arc<dyn Fn() -> Instant + Send + Sync>;
+
 /// Rate limiter for webhook endpoints
+///
+/// Buckets created with the default limit are evicted once they have
+/// refilled and have no requests in flight, since a fresh bucket behaves
+/// the same. The sweep runs whenever the number of buckets doubles. Limits
+/// set with [`set_limit`](Self::set_limit) are kept.
 pub struct WebhookRateLimiter {
     limiters: Arc<RwLock<HashMap<String, Arc<TokenBucket>>>>,
     default_limit: EndpointRateLimit,
+    /// Number of buckets at which the next sweep runs
+    sweep_at: AtomicUsize,
+    clock: Clock,
 }
 
 /// Rate limit configuration for an endpoint
@@ -61,6 +75,9 @@ pub struct TokenBucket {
     
     /// Max concurrent requests
     max_concurrent: u32,
+
+    /// Whether the limit was set with `set_limit`
+    custom: bool,
 }
 
 /// Permission to make a rate-limited request
@@ -80,9 +97,18 @@ impl WebhookRateLimiter {
         Self {
             limiters: Arc::new(RwLock::new(HashMap::new())),
             default_limit,
+            sweep_at: AtomicUsize::new(MIN_SWEEP_LEN),
+            clock: Arc::new(Instant::now),
         }
     }
 
+    /// Use `clock` instead of the system clock
+    #[cfg(test)]
+    fn with_clock(mut self, clock: impl Fn() -> Instant + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
     /// Acquire a permit for an endpoint
     ///
     /// Returns error if rate limit is exceeded
@@ -90,7 +116,7 @@ impl WebhookRateLimiter {
         let bucket = self.get_or_create_bucket(endpoint_id).await;
         
         // Refill tokens based on elapsed time
-        bucket.refill().await;
+        bucket.refill((self.clock)()).await;
         
         // Check concurrent requests
         let current_concurrent = bucket.concurrent.load(Ordering::Acquire);
@@ -136,9 +162,10 @@ impl WebhookRateLimiter {
             tokens: AtomicU32::new(limit.burst),
             capacity: limit.burst,
             refill_rate: limit.requests_per_second,
-            last_refill: Arc::new(RwLock::new(Instant::now())),
+            last_refill: Arc::new(RwLock::new((self.clock)())),
             concurrent: AtomicU32::new(0),
             max_concurrent: limit.concurrent_requests,
+            custom: true,
         });
         
         let mut limiters = self.limiters.write().await;
@@ -181,12 +208,19 @@ impl WebhookRateLimiter {
             tokens: AtomicU32::new(self.default_limit.burst),
             capacity: self.default_limit.burst,
             refill_rate: self.default_limit.requests_per_second,
-            last_refill: Arc::new(RwLock::new(Instant::now())),
+            last_refill: Arc::new(RwLock::new((self.clock)())),
             concurrent: AtomicU32::new(0),
             max_concurrent: self.default_limit.concurrent_requests,
+            custom: false,
         });
         
         let mut limiters = self.limiters.write().await;
+        if limiters.len() >= self.sweep_at.load(Ordering::Acquire) {
+            let now = (self.clock)();
+            limiters.retain(|_, bucket| bucket.custom || !bucket.is_idle(now));
+            self.sweep_at
+                .store((limiters.len() * 2).max(MIN_SWEEP_LEN), Ordering::Release);
+        }
         limiters.insert(endpoint_id.to_string(), bucket.clone());
         bucket
     }
@@ -199,11 +233,23 @@ impl Default for WebhookRateLimiter {
 }
 
 impl TokenBucket {
-    /// Refill tokens based on elapsed time
-    async fn refill(&self) {
+    /// Whether the bucket has refilled by `now` and has no requests in flight
+    fn is_idle(&self, now: Instant) -> bool {
+        if self.concurrent.load(Ordering::Acquire) > 0 {
+            return false;
+        }
+        let Ok(last_refill) = self.last_refill.try_read() else {
+            return false;
+        };
+        let elapsed = now.saturating_duration_since(*last_refill);
+        let refilled = elapsed.as_secs() * u64::from(self.refill_rate);
+        u64::from(self.tokens.load(Ordering::Acquire)) + refilled >= u64::from(self.capacity)
+    }
+
+    /// Refill tokens based on the time elapsed until `now`
+    async fn refill(&self, now: Instant) {
         let mut last_refill = self.last_refill.write().await;
-        let now = Instant::now();
-        let elapsed = now.duration_since(*last_refill);
+        let elapsed = now.saturating_duration_since(*last_refill);
         
         if elapsed >= Duration::from_secs(1) {
             let seconds_elapsed = elapsed.as_secs() as u32;
@@ -303,6 +349,29 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_idle_buckets_are_evicted() {
+        let now = Arc::new(std::sync::Mutex::new(Instant::now()));
+        let clock = now.clone();
+        let limiter = WebhookRateLimiter::new().with_clock(move || *clock.lock().unwrap());
+        limiter
+            .set_limit("custom", EndpointRateLimit::default())
+            .await;
+        let _busy = limiter.acquire("busy").await.unwrap();
+
+        for i in 2..MIN_SWEEP_LEN {
+            drop(limiter.acquire(&format!("endpoint-{}", i)).await.unwrap());
+        }
+
+        // Once the buckets have refilled, the next new endpoint sweeps them.
+        *now.lock().unwrap() += Duration::from_secs(1);
+        drop(limiter.acquire("endpoint-new").await.unwrap());
+        let limiters = limiter.limiters.read().await;
+        assert_eq!(limiters.len(), 3);
+        assert!(limiters.contains_key("custom"));
+        assert!(limiters.contains_key("busy"));
+    }
+
     #[tokio::test]
     async fn test_get_limit_info() {
         let limiter = WebhookRateLimiter::new();
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::error::{WebhookError, WebhookResult};
-use crate::signature::WebhookSigner;
+use crate::signature::{SignatureError, WebhookSigner};
 
 /// Parsed webhook payload.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,7 +45,11 @@ impl WebhookReceiver {
     }
 
     /// Verifies a webhook signature and parses the payload.
-    pub fn verify(&self, signature: &str, payload: &[u8]) -> WebhookResult<WebhookPayload> {
+    pub fn verify_and_parse(
+        &self,
+        signature: &str,
+        payload: &[u8],
+    ) -> WebhookResult<WebhookPayload> {
         // Verify signature
         self.signer
             .verify_header(signature, payload, self.tolerance_secs)
@@ -77,6 +81,30 @@ impl WebhookReceiver {
         serde_json::from_slice(payload).map_err(|e| WebhookError::InvalidPayload(e.to_string()))
     }
 
+    /// Verifies a webhook signature against the given endpoint secret.
+    ///
+    /// Use this when one receiver serves several endpoints, each with its
+    /// own secret. The header may be `t={timestamp},v1={signature}`, whose
+    /// timestamp must be within the receiver's tolerance (see
+    /// [`with_tolerance`](Self::with_tolerance)), or a bare hex HMAC of the
+    /// body, which has no timestamp and so no replay protection. See
+    /// [`WebhookSigner::verify_any_header`] for details.
+    pub fn verify(
+        &self,
+        payload: &[u8],
+        signature_header: &str,
+        secret: &str,
+    ) -> WebhookResult<()> {
+        WebhookSigner::with_version(secret, self.signer.version())
+            .verify_any_header(signature_header, payload, self.tolerance_secs)
+            .map_err(|e| match e {
+                SignatureError::InvalidFormat | SignatureError::Invalid => {
+                    WebhookError::InvalidSignature
+                }
+                SignatureError::Expired => WebhookError::ExpiredSignature,
+            })
+    }
+
     /// Verifies only the signature without parsing.
     pub fn verify_signature(&self, signature: &str, payload: &[u8]) -> WebhookResult<()> {
         self.signer
@@ -135,4 +163,51 @@ mod tests {
         assert_eq!(payload.event_type, "user.created");
         assert_eq!(payload.correlation_id, Some("trace-789".to_string()));
     }
+
+    #[test]
+    fn test_verify_with_endpoint_secret() {
+        let receiver = WebhookReceiver::new("receiver-secret").with_tolerance(60);
+        let signer = WebhookSigner::new("endpoint-secret");
+        let body = br#"{"type":"user.created"}"#;
+        let now = chrono::Utc::now().timestamp();
+        let verify = |body: &[u8], header: &str| receiver.verify(body, header, "endpoint-secret");
+
+        let header = signer.sign_header(now, body);
+        assert!(verify(body, &header).is_ok());
+        assert!(verify(body, &signer.sign_payload(body)).is_ok());
+        assert!(verify(body, &signer.sign_payload(body).to_uppercase()).is_ok());
+
+        // A rotated-out signature alongside the current one still passes.
+        let old = "0".repeat(64);
+        let rotated = format!("t={},v1={},v1={}", now, old, signer.sign(now, body));
+        assert!(verify(body, &rotated).is_ok());
+
+        let tampered = br#"{"type":"user.deleted"}"#;
+        assert!(matches!(
+            verify(tampered, &header),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify(tampered, &signer.sign_payload(body)),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            receiver.verify(body, &header, "other-secret"),
+            Err(WebhookError::InvalidSignature)
+        ));
+
+        let stale = signer.sign_header(now - 120, body);
+        assert!(matches!(
+            verify(body, &stale),
+            Err(WebhookError::ExpiredSignature)
+        ));
+        assert!(matches!(
+            verify(body, "not a signature"),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify(body, &format!("t={}", now)),
+            Err(WebhookError::InvalidSignature)
+        ));
+    }
 }
@@ -12,6 +12,15 @@ pub enum SignatureVersion {
     V1,
 }
 
+impl SignatureVersion {
+    /// Returns the key this version uses in signature headers.
+    pub fn header_key(&self) -> &'static str {
+        match self {
+            SignatureVersion::V1 => "v1",
+        }
+    }
+}
+
 impl Default for SignatureVersion {
     fn default() -> Self {
         SignatureVersion::V1
@@ -41,6 +50,11 @@ impl WebhookSigner {
         }
     }
 
+    /// Gets the signature version.
+    pub fn version(&self) -> SignatureVersion {
+        self.version
+    }
+
     /// Generates a signature for the given payload and timestamp.
     pub fn sign(&self, timestamp: i64, payload: &[u8]) -> String {
         match self.version {
@@ -75,8 +89,7 @@ impl WebhookSigner {
             .ok_or(SignatureError::InvalidFormat)?;
 
         // Check timestamp tolerance
-        let now = chrono::Utc::now().timestamp();
-        if (now - timestamp).abs() > tolerance_secs {
+        if !within_tolerance(timestamp, tolerance_secs) {
             return Err(SignatureError::Expired);
         }
 
@@ -89,6 +102,80 @@ impl WebhookSigner {
         Ok(())
     }
 
+    /// Generates a signature over the payload alone, for senders that put a
+    /// bare hex digest in the header.
+    pub fn sign_payload(&self, payload: &[u8]) -> String {
+        match self.version {
+            SignatureVersion::V1 => {
+                let mut mac = HmacSha256::new_from_slice(self.secret.as_bytes())
+                    .expect("HMAC can take key of any size");
+                mac.update(payload);
+                hex::encode(mac.finalize().into_bytes())
+            }
+        }
+    }
+
+    /// Verifies a signature header in either supported format.
+    ///
+    /// - `t={timestamp},v1={signature}`, as written by [`sign_header`]. The
+    ///   timestamp must be within `tolerance_secs` of now. Several `v1`
+    ///   entries may be given, for example while a secret is rotated, and
+    ///   any one of them matching is enough.
+    /// - A bare hex signature of the payload, as from [`sign_payload`]. It
+    ///   carries no timestamp, so replays can't be detected.
+    ///
+    /// [`sign_header`]: Self::sign_header
+    /// [`sign_payload`]: Self::sign_payload
+    pub fn verify_any_header(
+        &self,
+        header: &str,
+        payload: &[u8],
+        tolerance_secs: i64,
+    ) -> Result<(), SignatureError> {
+        let header = header.trim();
+        if !header.is_empty() && header.bytes().all(|b| b.is_ascii_hexdigit()) {
+            let expected = self.sign_payload(payload);
+            return if constant_time_compare(&expected, &header.to_ascii_lowercase()) {
+                Ok(())
+            } else {
+                Err(SignatureError::Invalid)
+            };
+        }
+
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in header.split(',') {
+            let (key, value) = part
+                .trim()
+                .split_once('=')
+                .ok_or(SignatureError::InvalidFormat)?;
+            if key == "t" {
+                timestamp = value.parse::<i64>().ok();
+            } else if key == self.version.header_key() {
+                signatures.push(value);
+            }
+        }
+        let timestamp = timestamp.ok_or(SignatureError::InvalidFormat)?;
+        if signatures.is_empty() {
+            return Err(SignatureError::InvalidFormat);
+        }
+
+        if !within_tolerance(timestamp, tolerance_secs) {
+            return Err(SignatureError::Expired);
+        }
+
+        // Check every signature, so timing doesn't reveal which one matched.
+        let expected = self.sign(timestamp, payload);
+        let matched = signatures.iter().fold(false, |matched, s| {
+            constant_time_compare(&expected, s) | matched
+        });
+        if matched {
+            Ok(())
+        } else {
+            Err(SignatureError::Invalid)
+        }
+    }
+
     fn sign_v1(&self, timestamp: i64, payload: &[u8]) -> String {
         let mut mac =
             HmacSha256::new_from_slice(self.secret.as_bytes()).expect("HMAC can take key of any size");
@@ -140,6 +227,18 @@ fn parse_signature_header(header: &str) -> Result<std::collections::HashMap<&str
     Ok(parts)
 }
 
+/// Returns true if `timestamp` is within `tolerance_secs` of now.
+///
+/// `timestamp` comes from the request, so extreme values are rejected
+/// rather than allowed to overflow.
+fn within_tolerance(timestamp: i64, tolerance_secs: i64) -> bool {
+    let now = chrono::Utc::now().timestamp();
+    match now.checked_sub(timestamp).map(i64::unsigned_abs) {
+        Some(age) => age <= u64::try_from(tolerance_secs).unwrap_or(0),
+        None => false,
+    }
+}
+
 /// Constant-time string comparison to prevent timing attacks.
 fn constant_time_compare(a: &str, b: &str) -> bool {
     if a.len() != b.len() {
@@ -204,4 +303,22 @@ mod tests {
         let result = signer.verify_header(&header, payload, 300); // 5 minute tolerance
         assert_eq!(result, Err(SignatureError::Expired));
     }
+
+    #[test]
+    fn test_extreme_timestamps_are_expired() {
+        let signer = WebhookSigner::new("test-secret");
+        let payload = b"test payload";
+
+        for timestamp in [i64::MIN, i64::MAX] {
+            let header = signer.sign_header(timestamp, payload);
+            assert_eq!(
+                signer.verify_header(&header, payload, 300),
+                Err(SignatureError::Expired)
+            );
+            assert_eq!(
+                signer.verify_any_header(&header, payload, 300),
+                Err(SignatureError::Expired)
+            );
+        }
+    }
 }
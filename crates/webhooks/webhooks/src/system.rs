@@ -1,11 +1,13 @@
 //! Webhook system - main entry point.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use better_auth_events::{Event, EventBus, EventError, EventHandler};
 
-use crate::delivery::{DeliveryEngine, WebhookJob};
+use crate::circuit_breaker::CircuitState;
+use crate::delivery::DeliveryEngine;
 use crate::endpoint::WebhookEndpoint;
 use crate::error::WebhookResult;
 use crate::queue::{InMemoryQueue, WebhookQueue};
@@ -107,6 +109,7 @@ impl<Q: WebhookQueue + 'static, R: RetryStrategy + 'static> WebhookSystem<Q, R>
 
     /// Registers a webhook endpoint.
     pub async fn register_endpoint(&self, endpoint: WebhookEndpoint) {
+        self.engine.configure_circuit_breaker(&endpoint).await;
         let mut endpoints = self.endpoints.write().await;
         endpoints.push(endpoint);
     }
@@ -130,6 +133,14 @@ impl<Q: WebhookQueue + 'static, R: RetryStrategy + 'static> WebhookSystem<Q, R>
         endpoints.iter().find(|e| e.id == id).cloned()
     }
 
+    /// Gets the circuit state of each registered endpoint.
+    ///
+    /// Endpoints in `CircuitState::Open` are tripped and their jobs are
+    /// being deferred.
+    pub async fn circuit_states(&self) -> HashMap<String, CircuitState> {
+        self.engine.circuit_states().await
+    }
+
     /// Queues webhooks for an event.
     pub async fn queue_event(&self, event: &Event) -> WebhookResult<usize> {
         let endpoints = self.endpoints.read().await;
@@ -138,12 +149,14 @@ impl<Q: WebhookQueue + 'static, R: RetryStrategy + 'static> WebhookSystem<Q, R>
 
         for endpoint in endpoints.iter() {
             if endpoint.should_receive(&event_type) {
-                let job = WebhookJob::new(endpoint, event)
-                    .with_max_attempts(self.config.max_retries);
-                self.engine.enqueue(job).await.map_err(|e| {
-                    crate::error::WebhookError::QueueError(e.to_string())
-                })?;
-                queued += 1;
+                let sent = self
+                    .engine
+                    .enqueue_event(endpoint, event, self.config.max_retries)
+                    .await
+                    .map_err(|e| crate::error::WebhookError::QueueError(e.to_string()))?;
+                if sent {
+                    queued += 1;
+                }
             }
         }
 
@@ -250,4 +263,151 @@ mod tests {
         let queued = system.queue_event(&event).await.unwrap();
         assert_eq!(queued, 0);
     }
+
+    struct PublicPayload;
+
+    impl crate::WebhookTransformer for PublicPayload {
+        fn transform_payload(&self, event: &Event) -> Option<serde_json::Value> {
+            let mut data = event.payload.clone();
+            data.as_object_mut()?.remove("internal_id");
+            Some(serde_json::json!({ "event": event.simple_type_string(), "user": data }))
+        }
+
+        fn should_send(&self, event: &Event) -> bool {
+            event.payload.get("test_user").is_none()
+        }
+    }
+
+    #[cfg(feature = "http-client")]
+    #[tokio::test]
+    async fn test_transformer_shapes_delivered_payload() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).into_owned();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .filter_map(|line| line.split_once(':'))
+                        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                        .and_then(|(_, value)| value.trim().parse().ok())
+                        .unwrap_or(0);
+                    if body.len() >= length || n == 0 {
+                        let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n";
+                        socket.write_all(response.as_bytes()).await.unwrap();
+                        return (head.to_string(), body.to_string());
+                    }
+                }
+            }
+        });
+
+        let system = WebhookSystem::new();
+        let endpoint = WebhookEndpoint::new(url, "secret").transformer(Arc::new(PublicPayload));
+        system.register_endpoint(endpoint).await;
+
+        let skipped = Event::simple("user.created", serde_json::json!({ "test_user": true }));
+        assert_eq!(system.queue_event(&skipped).await.unwrap(), 0);
+
+        let payload = serde_json::json!({ "user_id": "123", "internal_id": "db-42" });
+        let event = Event::simple("user.created", payload);
+        assert_eq!(system.queue_event(&event).await.unwrap(), 1);
+
+        let delivery = system.engine().process_next().await.unwrap().unwrap();
+        assert!(delivery.error.is_none());
+        assert_eq!(delivery.event_type, "user.created");
+
+        let (head, body) = server.await.unwrap();
+        let delivered: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            delivered,
+            serde_json::json!({ "event": "user.created", "user": { "user_id": "123" } })
+        );
+
+        // The signature covers the transformed body.
+        let signature = head
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.eq_ignore_ascii_case("x-webhook-signature"))
+            .map(|(_, value)| value.trim())
+            .unwrap();
+        let signer = crate::WebhookSigner::new("secret");
+        let verified = signer.verify_header(signature, body.as_bytes(), 300);
+        assert!(verified.is_ok());
+    }
+
+    #[cfg(feature = "http-client")]
+    #[tokio::test]
+    async fn test_open_circuit_defers_jobs() {
+        use crate::{CircuitBreakerConfig, FixedDelay, WebhookError};
+        use std::time::Duration;
+
+        // Nothing listens on this port once the listener is dropped.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+        drop(listener);
+
+        let retry = FixedDelay::new(Duration::from_secs(3600)).max_attempts(5);
+        let system = WebhookSystem::with_queue_and_retry(
+            WebhookConfig::default(),
+            InMemoryQueue::new(),
+            retry,
+        );
+        let endpoint = WebhookEndpoint::new(url, "secret").circuit_breaker(CircuitBreakerConfig {
+            failure_threshold: 2,
+            timeout: Duration::from_millis(100),
+            ..Default::default()
+        });
+        let endpoint_id = endpoint.id.clone();
+        system.register_endpoint(endpoint).await;
+        assert!(matches!(
+            system.circuit_states().await[&endpoint_id],
+            CircuitState::Closed { failure_count: 0 }
+        ));
+
+        for i in 0..3 {
+            let event = Event::simple("user.created", serde_json::json!({ "n": i }));
+            system.queue_event(&event).await.unwrap();
+        }
+
+        let engine = system.engine();
+        for _ in 0..2 {
+            let delivery = engine.process_next().await.unwrap().unwrap();
+            assert!(delivery.error.is_some());
+        }
+        assert!(matches!(
+            system.circuit_states().await[&endpoint_id],
+            CircuitState::Open { .. }
+        ));
+
+        // The third job is parked until the cooldown ends, without using an attempt.
+        let result = engine.process_next().await;
+        assert!(matches!(result, Err(WebhookError::CircuitOpen)));
+        assert!(engine.process_next().await.unwrap().is_none());
+        let pending = engine.queue().pending_jobs().await.unwrap();
+        let deferred = pending.iter().find(|j| j.attempts == 0).unwrap();
+        assert!(deferred.next_attempt > chrono::Utc::now());
+
+        // After the cooldown the job is tried again as a half-open probe.
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let delivery = engine.process_next().await.unwrap().unwrap();
+        assert_eq!(delivery.job_id, deferred.id);
+        assert!(matches!(
+            engine.circuit_state(&endpoint_id).await,
+            Some(CircuitState::Open { .. })
+        ));
+
+        engine.reset_circuit(&endpoint_id).await;
+        assert!(!matches!(
+            engine.circuit_state(&endpoint_id).await,
+            Some(CircuitState::Open { .. })
+        ));
+    }
 }
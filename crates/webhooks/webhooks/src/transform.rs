@@ -0,0 +1,40 @@
+//! Per-endpoint transformation of events into webhook payloads.
+
+use std::collections::HashMap;
+
+use better_auth_events::Event;
+use serde_json::Value;
+
+/// Trait for transforming event payloads before webhook delivery.
+///
+/// Attach one to a [`WebhookEndpoint`](crate::WebhookEndpoint) to customize
+/// how events are serialized for it, for example to strip internal fields or
+/// rename them. The transformed body is what gets signed and sent.
+pub trait WebhookTransformer: Send + Sync {
+    /// Transforms an event payload for webhook delivery.
+    ///
+    /// Return `None` to use the default payload format.
+    /// Return `Some(value)` to use a custom payload.
+    fn transform_payload(&self, _event: &Event) -> Option<Value> {
+        None
+    }
+
+    /// Filters whether an event should be sent to webhooks.
+    ///
+    /// Return `true` to allow the event, `false` to skip it.
+    fn should_send(&self, _event: &Event) -> bool {
+        true
+    }
+
+    /// Adds custom headers to webhook requests.
+    fn custom_headers(&self, _event: &Event) -> HashMap<String, String> {
+        HashMap::new()
+    }
+}
+
+/// Default transformer that passes events through unchanged.
+pub struct DefaultTransformer;
+
+impl WebhookTransformer for DefaultTransformer {
+    // Uses all default implementations
+}